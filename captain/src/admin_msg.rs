@@ -1,9 +1,41 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc, Duration};
+use clap::Subcommand;
 use colored::*;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use crate::captain::config::ConfigManager;
+#[derive(Subcommand, Debug)]
+pub enum MsgAction {
+    List { #[arg(long)] all: bool },
+    Ack { id: Option<i32>, #[arg(long)] all: bool },
+    Check,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warn,
+    Critical,
+}
+impl Severity {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "info" => Some(Severity::Info),
+            "warn" | "warning" => Some(Severity::Warn),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+    fn label(&self) -> ColoredString {
+        match self {
+            Severity::Info => "info".cyan(),
+            Severity::Warn => "warn".yellow(),
+            Severity::Critical => "critical".red().bold(),
+        }
+    }
+}
 #[derive(Debug, Serialize, Deserialize)]
 struct AdminMessageResponse {
     success: bool,
@@ -11,7 +43,7 @@ struct AdminMessageResponse {
     rate_limited: Option<bool>,
     message: Option<AdminMessage>,
 }
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct AdminMessage {
     id: i32,
     title: String,
@@ -19,20 +51,45 @@ struct AdminMessage {
     #[serde(rename = "type")]
     message_type: String,
     priority: String,
+    #[serde(default)]
+    severity: Option<String>,
+}
+impl AdminMessage {
+    fn severity(&self) -> Severity {
+        if let Some(severity) = self.severity.as_deref().and_then(Severity::parse) {
+            return severity;
+        }
+        match (self.priority.as_str(), self.message_type.as_str()) {
+            ("critical", _) => Severity::Critical,
+            ("urgent", _) | (_, "error") | (_, "warning") => Severity::Warn,
+            _ => Severity::Info,
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredMessage {
+    message: AdminMessage,
+    severity: Severity,
+    received_at: DateTime<Utc>,
+    acknowledged_at: Option<DateTime<Utc>>,
 }
 #[derive(Debug, Serialize, Deserialize)]
 struct MessageCheckRecord {
     last_checks: Vec<DateTime<Utc>>,
     seen_message_ids: Vec<i32>,
+    #[serde(default)]
+    messages: Vec<StoredMessage>,
 }
 impl Default for MessageCheckRecord {
     fn default() -> Self {
         Self {
             last_checks: Vec::new(),
             seen_message_ids: Vec::new(),
+            messages: Vec::new(),
         }
     }
 }
+const MAX_STORED_MESSAGES: usize = 100;
 fn get_message_record_path() -> PathBuf {
     dirs::home_dir()
         .unwrap_or_else(|| PathBuf::from("."))
@@ -40,29 +97,17 @@ fn get_message_record_path() -> PathBuf {
         .join("admin_msg_record.json")
 }
 fn load_check_record() -> Result<MessageCheckRecord> {
-    let path = get_message_record_path();
-    if !path.exists() {
-        return Ok(MessageCheckRecord::default());
-    }
-    let content = fs::read_to_string(&path)?;
-    let record: MessageCheckRecord = serde_json::from_str(&content)?;
-    Ok(record)
+    Ok(crate::storage::read_json_file(&get_message_record_path())?.unwrap_or_default())
 }
 fn save_check_record(record: &MessageCheckRecord) -> Result<()> {
-    let path = get_message_record_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    let json = serde_json::to_string_pretty(record)?;
-    fs::write(&path, json)?;
-    Ok(())
+    crate::storage::write_json_atomic(&get_message_record_path(), record)
 }
-fn should_check_messages() -> Result<bool> {
+fn should_check_messages(once_per_day: bool) -> Result<bool> {
     let mut record = load_check_record()?;
     let now = Utc::now();
     let one_day_ago = now - Duration::hours(24);
     record.last_checks.retain(|check| *check > one_day_ago);
-    if record.last_checks.len() >= 3 {
+    if record.last_checks.len() >= 3 || (once_per_day && !record.last_checks.is_empty()) {
         return Ok(false);
     }
     if let Some(last_check) = record.last_checks.last() {
@@ -79,17 +124,64 @@ fn record_message_check() -> Result<()> {
     save_check_record(&record)?;
     Ok(())
 }
-fn has_seen_message(message_id: i32) -> Result<bool> {
-    let record = load_check_record()?;
-    Ok(record.seen_message_ids.contains(&message_id))
+impl MessageCheckRecord {
+    /// Keeps a fetched message unless it is already stored; one seen before (and since
+    /// pruned) comes back acknowledged
+    fn store(&mut self, message: AdminMessage) {
+        if self.messages.iter().any(|m| m.message.id == message.id) {
+            return;
+        }
+        let acknowledged_at = self.seen_message_ids.contains(&message.id).then(Utc::now);
+        self.messages
+            .push(StoredMessage {
+                severity: message.severity(),
+                message,
+                received_at: Utc::now(),
+                acknowledged_at,
+            });
+        let excess = self.messages.len().saturating_sub(MAX_STORED_MESSAGES);
+        self.messages.drain(..excess);
+    }
+    fn pending(&self, min_severity: Severity) -> Vec<StoredMessage> {
+        self.messages
+            .iter()
+            .filter(|m| m.acknowledged_at.is_none() && m.severity >= min_severity)
+            .cloned()
+            .collect()
+    }
+    /// Whether this is the first time the message was seen
+    fn mark_seen(&mut self, message_id: i32, acknowledge: bool) -> bool {
+        let first_seen = !self.seen_message_ids.contains(&message_id);
+        if first_seen {
+            self.seen_message_ids.push(message_id);
+        }
+        if acknowledge {
+            for stored in self.messages.iter_mut().filter(|m| m.message.id == message_id) {
+                stored.acknowledged_at.get_or_insert_with(Utc::now);
+            }
+        }
+        first_seen
+    }
 }
-fn mark_message_seen(message_id: i32) -> Result<()> {
+fn store_message(message: AdminMessage) -> Result<()> {
     let mut record = load_check_record()?;
-    if !record.seen_message_ids.contains(&message_id) {
-        record.seen_message_ids.push(message_id);
-        save_check_record(&record)?;
-    }
-    Ok(())
+    record.store(message);
+    save_check_record(&record)
+}
+fn pending_messages(min_severity: Severity) -> Result<Vec<StoredMessage>> {
+    Ok(load_check_record()?.pending(min_severity))
+}
+fn mark_message_seen(message_id: i32, acknowledge: bool) -> Result<bool> {
+    let mut record = load_check_record()?;
+    let first_seen = record.mark_seen(message_id, acknowledge);
+    save_check_record(&record)?;
+    Ok(first_seen)
+}
+fn min_severity(config: &ConfigManager) -> Severity {
+    config
+        .get("messages.min_severity")
+        .and_then(|value| Severity::parse(&value))
+        .unwrap_or(Severity::Info)
 }
 fn get_user_id() -> Result<String> {
     let user_id_path = dirs::home_dir()
@@ -104,47 +196,72 @@ fn get_user_id() -> Result<String> {
         Ok(format!("CM-USER-{}", Utc::now().timestamp()))
     }
 }
+/// `messages.enabled`, and never while `privacy.telemetry` is off
+pub fn messages_enabled(config: &ConfigManager) -> bool {
+    config.get_bool("messages.enabled", true) && crate::privacy::telemetry_in(config)
+}
 pub async fn check_and_display_message() -> Result<()> {
-    if !should_check_messages()? {
+    let config = ConfigManager::new()?;
+    if !messages_enabled(&config)
+        || !should_check_messages(config.get_bool("messages.once_per_day", false))?
+    {
         return Ok(());
     }
     record_message_check()?;
     let user_id = get_user_id()?;
     let version = env!("CARGO_PKG_VERSION");
-    let client = reqwest::Client::new();
-    let response = client
-        .get("https://cargo.do/api/admin/msg")
-        .query(&[("user_id", user_id.as_str()), ("version", version)])
-        .timeout(std::time::Duration::from_secs(5))
-        .send()
-        .await;
+    let endpoint = format!("{}/admin/msg", crate::user::api_base_url());
+    // Offline, only messages fetched earlier are shown
+    let client = crate::net::async_client(std::time::Duration::from_secs(5)).ok();
+    let response = match &client {
+        Some(client) => Some(
+            client
+                .get(&endpoint)
+                .query(&[("user_id", user_id.as_str()), ("version", version)])
+                .send()
+                .await,
+        ),
+        None => None,
+    };
     match response {
-        Ok(resp) if resp.status().is_success() => {
+        Some(Ok(resp)) if resp.status().is_success() => {
             if let Ok(data) = resp.json::<AdminMessageResponse>().await {
                 if data.success && data.has_message {
                     if let Some(message) = data.message {
-                        if !has_seen_message(message.id)? {
-                            display_admin_message(&message);
-                            mark_message_seen(message.id)?;
-                            let _ = client
-                                .post("https://cargo.do/api/admin/msg")
-                                .form(
-                                    &[
-                                        ("user_id", user_id.as_str()),
-                                        ("message_id", &message.id.to_string()),
-                                        ("mark_seen", "true"),
-                                    ],
-                                )
-                                .timeout(std::time::Duration::from_secs(2))
-                                .send()
-                                .await;
-                        }
+                        store_message(message)?;
                     }
                 }
             }
         }
         _ => {}
     }
+    for stored in pending_messages(min_severity(&config))? {
+        display_admin_message(&stored.message);
+        let auto_ack = stored.severity == Severity::Info;
+        if !auto_ack {
+            println!(
+                "  {} {}\n", "Acknowledge with:".dimmed(), format!("cm msg ack {}", stored
+                .message.id).cyan()
+            );
+        }
+        if !mark_message_seen(stored.message.id, auto_ack)? {
+            continue;
+        }
+        if let Some(client) = &client {
+            let _ = client
+                .post(&endpoint)
+                .form(
+                    &[
+                        ("user_id", user_id.as_str()),
+                        ("message_id", &stored.message.id.to_string()),
+                        ("mark_seen", "true"),
+                    ],
+                )
+                .timeout(std::time::Duration::from_secs(2))
+                .send()
+                .await;
+        }
+    }
     Ok(())
 }
 fn display_admin_message(message: &AdminMessage) {
@@ -242,4 +359,93 @@ pub async fn force_check_message() -> Result<()> {
     record.last_checks.clear();
     save_check_record(&record)?;
     check_and_display_message().await
-}
\ No newline at end of file
+}
+pub async fn handle_msg_action(action: MsgAction) -> Result<()> {
+    match action {
+        MsgAction::List { all } => {
+            let record = load_check_record()?;
+            let messages: Vec<&StoredMessage> = record
+                .messages
+                .iter()
+                .rev()
+                .filter(|m| all || m.acknowledged_at.is_none())
+                .collect();
+            if messages.is_empty() {
+                println!(
+                    "📭 No {}messages", if all { "" } else { "unacknowledged " }
+                );
+                return Ok(());
+            }
+            println!("📬 {}", "Admin messages".bold().blue());
+            for stored in messages {
+                let status = match stored.acknowledged_at {
+                    Some(at) => format!("acknowledged {}", at.format("%Y-%m-%d")).dimmed(),
+                    None => "unacknowledged".yellow(),
+                };
+                println!(
+                    "  #{} [{}] {} - {} ({})", stored.message.id, stored.severity.label(),
+                    stored.message.title.bold(), stored.received_at.format("%Y-%m-%d %H:%M"),
+                    status
+                );
+            }
+        }
+        MsgAction::Ack { id, all } => {
+            let mut record = load_check_record()?;
+            let mut acknowledged = 0;
+            for stored in record
+                .messages
+                .iter_mut()
+                .filter(|m| m.acknowledged_at.is_none())
+                .filter(|m| all || Some(m.message.id) == id)
+            {
+                stored.acknowledged_at = Some(Utc::now());
+                acknowledged += 1;
+            }
+            if acknowledged == 0 && !all {
+                let id = id.context("Specify a message id or --all")?;
+                anyhow::bail!("No unacknowledged message with id {}", id);
+            }
+            save_check_record(&record)?;
+            println!("✅ Acknowledged {} message(s)", acknowledged);
+        }
+        MsgAction::Check => force_check_message().await?,
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn message(id: i32, severity: &str) -> AdminMessage {
+        AdminMessage {
+            id,
+            title: format!("Message {}", id),
+            content: "Hello".to_string(),
+            message_type: "info".to_string(),
+            priority: "normal".to_string(),
+            severity: Some(severity.to_string()),
+        }
+    }
+    #[test]
+    fn test_seen_messages_stay_hidden_across_reloads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("admin_msg_record.json");
+        let mut record = MessageCheckRecord::default();
+        record.store(message(1, "info"));
+        record.store(message(2, "warn"));
+        record.store(message(1, "info"));
+        assert_eq!(record.messages.len(), 2);
+        assert_eq!(record.pending(Severity::Warn).len(), 1);
+        assert!(record.mark_seen(1, true));
+        assert!(record.mark_seen(2, false));
+        assert!(!record.mark_seen(2, false));
+        crate::storage::write_json_atomic(&path, &record).unwrap();
+        let mut record: MessageCheckRecord = crate::storage::read_json_file(&path).unwrap().unwrap();
+        let pending: Vec<i32> = record.pending(Severity::Info).iter().map(|m| m.message.id).collect();
+        assert_eq!(pending, vec![2]);
+        record.messages.clear();
+        record.store(message(1, "info"));
+        record.store(message(3, "info"));
+        let pending: Vec<i32> = record.pending(Severity::Info).iter().map(|m| m.message.id).collect();
+        assert_eq!(pending, vec![3]);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::workspace::Member;
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use colored::*;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+/// Root files whose changes invalidate every crate in the workspace
+const WORKSPACE_FILES: [&str; 6] =
+    ["Cargo.toml", "Cargo.lock", "rust-toolchain", "rust-toolchain.toml", ".cargo/config", ".cargo/config.toml"];
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AffectedCommand {
+    Build,
+    Check,
+    Test,
+    Clippy,
+}
+impl AffectedCommand {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AffectedCommand::Build => "build",
+            AffectedCommand::Check => "check",
+            AffectedCommand::Test => "test",
+            AffectedCommand::Clippy => "clippy",
+        }
+    }
+}
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Impact {
+    /// Crates containing changed files, with how many files changed
+    pub changed: BTreeMap<String, usize>,
+    /// Crates pulled in through a dependency, mapped to the dependency that brought them in
+    pub dependents: BTreeMap<String, String>,
+    pub whole_workspace: bool,
+}
+impl Impact {
+    pub fn crates(&self) -> BTreeSet<&str> {
+        self.changed.keys().chain(self.dependents.keys()).map(String::as_str).collect()
+    }
+}
+/// The member owning `file`: the one with the longest directory prefix
+fn owner<'a>(members: &'a [Member], file: &Path) -> Option<&'a Member> {
+    members
+        .iter()
+        .filter(|member| file.starts_with(&member.dir))
+        .max_by_key(|member| member.dir.components().count())
+}
+pub fn analyze(members: &[Member], changed_files: &[PathBuf]) -> Impact {
+    let mut impact = Impact::default();
+    for file in changed_files {
+        if WORKSPACE_FILES.iter().any(|name| file == Path::new(name)) {
+            impact.whole_workspace = true;
+        }
+        if let Some(member) = owner(members, file) {
+            *impact.changed.entry(member.name.clone()).or_default() += 1;
+        }
+    }
+    if impact.whole_workspace {
+        for member in members {
+            impact.changed.entry(member.name.clone()).or_default();
+        }
+        return impact;
+    }
+    let mut queue: VecDeque<String> = impact.changed.keys().cloned().collect();
+    while let Some(name) = queue.pop_front() {
+        for member in members.iter().filter(|m| m.deps.iter().any(|(dep, _)| *dep == name)) {
+            if impact.changed.contains_key(&member.name) || impact.dependents.contains_key(&member.name) {
+                continue;
+            }
+            impact.dependents.insert(member.name.clone(), name.clone());
+            queue.push_back(member.name.clone());
+        }
+    }
+    impact
+}
+fn git_lines(root: &Path, args: &[&str]) -> Result<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).lines().filter(|l| !l.is_empty()).map(PathBuf::from).collect())
+}
+/// Files changed since `rev` (committed, staged, unstaged and untracked), relative to `root`
+fn changed_files(root: &Path, rev: &str) -> Result<Vec<PathBuf>> {
+    let mut files = git_lines(root, &["diff", "--name-only", "--relative", rev])?;
+    files.extend(git_lines(root, &["ls-files", "--others", "--exclude-standard"])?);
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+pub fn handle_affected(command: Option<AffectedCommand>, since: Option<String>, args: Vec<String>) -> Result<()> {
+    let (root, members) = crate::workspace::load_members()?;
+    let since = since.unwrap_or_else(crate::lockdiff::default_base);
+    let files = changed_files(&root, &since)?;
+    let impact = analyze(&members, &files);
+    println!(
+        "{}",
+        format!("🎯 Affected crates since {} ({} changed files)", since, files.len()).bold().blue()
+    );
+    if impact.whole_workspace {
+        println!("  {} Workspace-level files changed - every crate is affected", "⚠️".yellow());
+    }
+    for (name, count) in &impact.changed {
+        println!("  {} {} {}", "●".red(), name.cyan(), format!("({} files changed)", count).dimmed());
+    }
+    for (name, via) in &impact.dependents {
+        println!("  {} {} {}", "○".yellow(), name.cyan(), format!("(depends on {})", via).dimmed());
+    }
+    let crates = impact.crates();
+    if crates.is_empty() {
+        println!("  ✨ No workspace crates affected");
+        return Ok(());
+    }
+    let Some(command) = command else {
+        println!();
+        println!("Run {} or {} to act on just these crates", "cm affected test".yellow(), "cm affected build".yellow());
+        return Ok(());
+    };
+    let mut cargo_args: Vec<String> = vec![command.as_str().to_string()];
+    for name in crates {
+        cargo_args.push("-p".to_string());
+        cargo_args.push(name.to_string());
+    }
+    cargo_args.extend(args);
+    println!();
+    println!("🚀 cargo {}", cargo_args.join(" ").cyan());
+    let cargo_args: Vec<&str> = cargo_args.iter().map(String::as_str).collect();
+    if command == AffectedCommand::Test {
+        crate::display::run_cargo_passthrough(&cargo_args);
+    }
+    if !crate::display::run_cargo_with_display(&cargo_args) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn member(name: &str, dir: &str, deps: &[&str]) -> Member {
+        Member {
+            name: name.to_string(),
+            version: "0.1.0".to_string(),
+            dir: PathBuf::from(dir),
+            deps: deps.iter().map(|d| (d.to_string(), "normal")).collect(),
+        }
+    }
+    #[test]
+    fn maps_files_to_crates_and_follows_reverse_dependencies() {
+        let members = vec![
+            member("app", "", &["api"]),
+            member("api", "crates/api", &["core"]),
+            member("core", "crates/core", &[]),
+            member("tools", "crates/tools", &[]),
+        ];
+        let impact = analyze(&members, &[PathBuf::from("crates/core/src/lib.rs"), PathBuf::from("crates/core/README.md")]);
+        assert_eq!(impact.changed.get("core"), Some(&2));
+        assert_eq!(impact.dependents.get("api").map(String::as_str), Some("core"));
+        assert_eq!(impact.dependents.get("app").map(String::as_str), Some("api"));
+        assert!(!impact.crates().contains("tools"));
+        let impact = analyze(&members, &[PathBuf::from("src/main.rs")]);
+        assert_eq!(impact.crates(), BTreeSet::from(["app"]));
+        let impact = analyze(&members, &[PathBuf::from("Cargo.lock")]);
+        assert!(impact.whole_workspace);
+        assert_eq!(impact.crates().len(), 4);
+    }
+}
@@ -38,12 +38,8 @@ fn get_affiliate_dir() -> PathBuf {
 }
 pub fn save_affiliate_info(info: &AffiliateInfo) -> Result<()> {
     let dir = get_affiliate_dir();
-    fs::create_dir_all(&dir).context("Failed to create affiliate directory")?;
-    let info_path = dir.join("info.json");
-    let json = serde_json::to_string_pretty(info)?;
-    fs::write(&info_path, json).context("Failed to save affiliate info")?;
-    let code_path = dir.join("code");
-    fs::write(&code_path, &info.code).context("Failed to save affiliate code")?;
+    crate::storage::write_json_atomic(&dir.join("info.json"), info).context("Failed to save affiliate info")?;
+    crate::storage::write_atomic(&dir.join("code"), &info.code).context("Failed to save affiliate code")?;
     Ok(())
 }
 pub fn load_affiliate_info() -> Result<Option<AffiliateInfo>> {
@@ -81,7 +77,7 @@ pub async fn register_affiliate(email: Option<String>) -> Result<AffiliateRespon
         { "email" : email, "user_id" : user_id, "name" : "Cargo Mate User",
         "affiliate_code" : code }
     );
-    let client = reqwest::Client::new();
+    let client = crate::net::async_client(std::time::Duration::from_secs(15))?;
     let response = client
         .post("https://cargo.do/api/create-affiliate")
         .json(&payload)
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use colored::*;
+#[cfg(not(target_arch = "wasm32"))]
 use notify::{Event, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
@@ -8,9 +9,14 @@ use std::collections::HashMap;
 use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
 use std::process::Command;
+#[cfg(not(target_arch = "wasm32"))]
 use std::sync::mpsc::channel;
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
 use crate::captain::license;
+use crate::storage::{self, Storage};
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Anchor {
     pub name: String,
@@ -21,6 +27,9 @@ pub struct Anchor {
     pub files_snapshot: HashMap<String, FileSnapshot>,
     pub environment: HashMap<String, String>,
     pub metadata: AnchorMetadata,
+    /// Metadata and file backups are sealed with AES-256-GCM; loading is transparent
+    #[serde(default)]
+    pub encrypted: bool,
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FileSnapshot {
@@ -37,22 +46,38 @@ pub struct AnchorMetadata {
     pub total_loc: usize,
 }
 pub struct AnchorManager {
-    anchors_dir: PathBuf,
-    snapshots_dir: PathBuf,
+    storage: Arc<dyn Storage>,
+    encrypt: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    crypto: crate::anchor_crypto::AnchorCrypto,
 }
 impl AnchorManager {
     pub fn new() -> Result<Self> {
-        let shipwreck = dirs::home_dir()
-            .context("Could not find home directory")?
-            .join(".shipwreck");
-        let anchors_dir = shipwreck.join("anchors");
-        let snapshots_dir = shipwreck.join("snapshots");
-        fs::create_dir_all(&anchors_dir)?;
-        fs::create_dir_all(&snapshots_dir)?;
-        Ok(Self { anchors_dir, snapshots_dir })
-    }
-    pub fn save(&self, name: &str, description: &str) -> Result<()> {
-        println!("⚓ Dropping anchor: {}", name.cyan().bold());
+        let manager = Self::with_storage(storage::default_storage()?);
+        #[cfg(not(target_arch = "wasm32"))]
+        let manager = manager.with_encryption(
+            crate::captain::config::ConfigManager::new()
+                .is_ok_and(|config| config.get_bool("anchor.encrypt", false)),
+        );
+        Ok(manager)
+    }
+    pub fn with_storage(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            encrypt: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            crypto: crate::anchor_crypto::AnchorCrypto::new(),
+        }
+    }
+    /// Whether newly saved anchors are encrypted; existing anchors keep their own setting
+    pub fn with_encryption(mut self, encrypt: bool) -> Self {
+        self.encrypt = encrypt;
+        self
+    }
+    pub fn save(&self, name: &str, description: &str) -> Result<Anchor> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let git_commit = git_line(&["rev-parse", "HEAD"], &[]).ok();
+        #[cfg(target_arch = "wasm32")]
         let git_commit = None;
         let cargo_lock_hash = self.hash_cargo_lock()?;
         let files_snapshot = self.create_files_snapshot()?;
@@ -67,24 +92,27 @@ impl AnchorManager {
             files_snapshot: files_snapshot.clone(),
             environment,
             metadata,
+            encrypted: self.encrypt,
         };
-        self.save_anchor(&anchor)?;
+        self.store(&anchor)?;
         self.save_file_backups(&anchor)?;
-        println!("✅ Anchor '{}' saved successfully!", name.green());
-        println!("   📁 {} files backed up", files_snapshot.len());
-        Ok(())
+        Ok(anchor)
     }
-    pub fn restore(&self, name: &str) -> Result<()> {
-        println!("⚓ Restoring anchor: {}", name.cyan().bold());
+    pub fn restore(&self, name: &str) -> Result<RestoreReport> {
         let anchor = self.load_anchor(name)?;
-        self.restore_cargo_lock(&anchor)?;
-        let restored_count = self.restore_files(&anchor)?;
-        println!("✅ Anchor '{}' restored successfully!", name.green());
-        println!("   📁 {} files restored", restored_count);
-        println!("   🕐 From: {}", anchor.timestamp.format("%Y-%m-%d %H:%M:%S"));
-        Ok(())
+        let cargo_lock_restored = self.restore_cargo_lock(&anchor)?;
+        let files_restored = self.restore_files(&anchor)?;
+        Ok(RestoreReport {
+            name: anchor.name,
+            timestamp: anchor.timestamp,
+            files_restored,
+            cargo_lock_restored,
+        })
     }
-    pub fn update_file(&self, anchor_name: &str, file_path: &Path) -> Result<()> {
+    pub fn get(&self, name: &str) -> Result<Anchor> {
+        self.load_anchor(name)
+    }
+    pub fn update_file(&self, anchor_name: &str, file_path: &Path) -> Result<bool> {
         let mut anchor = self.load_anchor(anchor_name)?;
         if let Some(file_key) = anchor
             .files_snapshot
@@ -94,17 +122,20 @@ impl AnchorManager {
             let file_snapshot = self.create_file_snapshot(file_path)?;
             anchor.files_snapshot.insert(file_key.clone(), file_snapshot);
             anchor.timestamp = Utc::now();
-            self.save_anchor(&anchor)?;
-            println!("🔄 Updated {} in anchor '{}'", file_path.display(), anchor_name);
+            self.store(&anchor)?;
+            return Ok(true);
         }
-        Ok(())
+        Ok(false)
     }
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn start_auto_update(&self, anchor_name: &str) -> Result<()> {
         self.start_auto_update_with_options(anchor_name, false)
     }
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn start_auto_update_background(&self, anchor_name: &str) -> Result<()> {
         self.start_auto_update_with_options(anchor_name, true)
     }
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn start_auto_update_with_options(
         &self,
         anchor_name: &str,
@@ -124,7 +155,7 @@ impl AnchorManager {
                 if let Err(e) = manager
                     .run_auto_update_loop(&anchor_clone, &anchor_name_clone)
                 {
-                    eprintln!("❌ Auto-update error for {}: {}", anchor_name_clone, e);
+                    log::error!("Auto-update error for {}: {}", anchor_name_clone, e);
                 }
             });
             println!("✅ {}", "Auto-update STARTED successfully!".green().bold());
@@ -168,7 +199,7 @@ impl AnchorManager {
                                 if anchor.files_snapshot.contains_key(&path_str.to_string())
                                 {
                                     if let Err(e) = self.update_file(anchor_name, &path) {
-                                        eprintln!("❌ Failed to update {}: {}", path.display(), e);
+                                        log::error!("Failed to update {}: {}", path.display(), e);
                                     } else {
                                         println!(
                                             "🔄 Updated {} in anchor '{}'", path.display(),
@@ -179,18 +210,19 @@ impl AnchorManager {
                             }
                         }
                         Err(e) => {
-                            eprintln!("❌ File watcher error: {}", e);
+                            log::error!("File watcher error: {}", e);
                         }
                     }
                 }
                 Err(e) => {
-                    eprintln!("❌ Channel receive error: {}", e);
+                    log::error!("Channel receive error: {}", e);
                     break;
                 }
             }
         }
         Ok(())
     }
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn run_auto_update_loop(
         &self,
         anchor: &Anchor,
@@ -219,7 +251,7 @@ impl AnchorManager {
                                 if anchor.files_snapshot.contains_key(&path_str.to_string())
                                 {
                                     if let Err(e) = self.update_file(anchor_name, &path) {
-                                        eprintln!("❌ Failed to update {}: {}", path.display(), e);
+                                        log::error!("Failed to update {}: {}", path.display(), e);
                                     } else {
                                         println!(
                                             "🔄 [{}] Updated {} in anchor '{}'", chrono::Utc::now()
@@ -230,18 +262,19 @@ impl AnchorManager {
                             }
                         }
                         Err(e) => {
-                            eprintln!("❌ File watcher error: {}", e);
+                            log::error!("File watcher error: {}", e);
                         }
                     }
                 }
                 Err(e) => {
-                    eprintln!("❌ Channel receive error: {}", e);
+                    log::error!("Channel receive error: {}", e);
                     break;
                 }
             }
         }
         Ok(())
     }
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn stop_auto_update(&self, anchor_name: &str) -> Result<()> {
         println!("🛑 Stopping auto-update for anchor: {}", anchor_name.cyan().bold());
         println!(
@@ -252,108 +285,107 @@ impl AnchorManager {
     }
     pub fn list(&self) -> Result<Vec<AnchorSummary>> {
         let mut anchors = Vec::new();
-        for entry in fs::read_dir(&self.anchors_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension() == Some(std::ffi::OsStr::new("json")) {
-                let content = fs::read_to_string(&path)?;
-                let anchor: Anchor = serde_json::from_str(&content)?;
+        for file in self.storage.list("anchors")? {
+            if let Some(name) = file.strip_suffix(".json") {
+                let anchor = match self.load_anchor(name) {
+                    Ok(anchor) => anchor,
+                    Err(e) => {
+                        eprintln!("🔒 Skipping anchor '{}': {:#}", name, e);
+                        continue;
+                    }
+                };
                 anchors
                     .push(AnchorSummary {
                         name: anchor.name,
                         timestamp: anchor.timestamp,
                         description: anchor.description,
                         files_count: anchor.files_snapshot.len(),
+                        project: anchor.metadata.project_name,
                     });
             }
         }
         anchors.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
         Ok(anchors)
     }
-    pub fn show(&self, name: &str) -> Result<()> {
-        let anchor = self.load_anchor(name)?;
-        println!("{}", format!("=== Anchor: {} ===", anchor.name) .blue().bold());
-        println!("📅 Created: {}", anchor.timestamp.format("%Y-%m-%d %H:%M:%S"));
-        println!("📝 Description: {}", anchor.description);
-        if let Some(ref commit) = anchor.git_commit {
-            println!("🔗 Git commit: {}", commit.dimmed());
-        }
-        println!("\n📊 Metadata:");
-        println!("   Project: {}", anchor.metadata.project_name);
-        println!("   Rust version: {}", anchor.metadata.rust_version);
-        println!("   Dependencies: {}", anchor.metadata.dependencies_count);
-        println!("   Lines of code: {}", anchor.metadata.total_loc);
-        println!("\n📁 Files snapshot ({} files):", anchor.files_snapshot.len());
-        let mut files: Vec<_> = anchor.files_snapshot.values().collect();
-        files.sort_by(|a, b| a.path.cmp(&b.path));
-        for (i, file) in files.iter().enumerate().take(10) {
-            println!("   {} {}", if i < 9 { " " } else { "" }, file.path.display());
-        }
-        if anchor.files_snapshot.len() > 10 {
-            println!("   ... and {} more files", anchor.files_snapshot.len() - 10);
-        }
-        Ok(())
-    }
-    pub fn diff(&self, name: &str) -> Result<()> {
+    pub fn diff(&self, name: &str) -> Result<AnchorDiff> {
         let anchor = self.load_anchor(name)?;
         let current_snapshot = self.create_files_snapshot()?;
-        println!("{}", format!("=== Diff from anchor '{}' ===", name) .blue().bold());
-        let mut added = Vec::new();
-        let mut modified = Vec::new();
-        let mut deleted = Vec::new();
+        let mut diff = AnchorDiff {
+            name: anchor.name.clone(),
+            ..AnchorDiff::default()
+        };
         for (path, current_file) in &current_snapshot {
             match anchor.files_snapshot.get(path) {
                 Some(anchor_file) => {
                     if anchor_file.hash != current_file.hash {
-                        modified.push(path.clone());
+                        diff.modified.push(path.clone());
                     }
                 }
-                None => added.push(path.clone()),
+                None => diff.added.push(path.clone()),
             }
         }
         for path in anchor.files_snapshot.keys() {
             if !current_snapshot.contains_key(path) {
-                deleted.push(path.clone());
+                diff.deleted.push(path.clone());
             }
         }
-        if !added.is_empty() {
-            println!("\n✨ Added files:");
-            for path in &added {
-                println!("   + {}", path.green());
-            }
-        }
-        if !modified.is_empty() {
-            println!("\n📝 Modified files:");
-            for path in &modified {
-                println!("   ~ {}", path.yellow());
-            }
+        diff.added.sort();
+        diff.modified.sort();
+        diff.deleted.sort();
+        Ok(diff)
+    }
+    pub fn store(&self, anchor: &Anchor) -> Result<()> {
+        let data = self.seal(anchor, serde_json::to_vec_pretty(anchor)?)?;
+        self.storage.write(&format!("anchors/{}.json", anchor.name), &data)
+    }
+    /// Deletes an anchor together with its file snapshots
+    pub fn remove(&self, name: &str) -> Result<()> {
+        self.storage.remove(&format!("anchors/{}.json", name))?;
+        self.storage.remove(&format!("snapshots/{}", name))
+    }
+    fn load_anchor(&self, name: &str) -> Result<Anchor> {
+        let key = format!("anchors/{}.json", name);
+        let data = self
+            .storage
+            .read(&key)?
+            .ok_or_else(|| anyhow::anyhow!("Anchor '{}' not found", name))?;
+        serde_json::from_slice(&self.open(data)?).with_context(|| format!("Invalid JSON in {}", key))
+    }
+    fn read_backup(&self, anchor: &Anchor, path: &Path) -> Result<Option<Vec<u8>>> {
+        match self.storage.read(&Self::backup_key(anchor, path))? {
+            Some(data) => Ok(Some(self.open(data)?)),
+            None => Ok(None),
         }
-        if !deleted.is_empty() {
-            println!("\n🗑️  Deleted files:");
-            for path in &deleted {
-                println!("   - {}", path.red());
-            }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn seal(&self, anchor: &Anchor, data: Vec<u8>) -> Result<Vec<u8>> {
+        if anchor.encrypted {
+            return self.crypto.seal(&data);
         }
-        if added.is_empty() && modified.is_empty() && deleted.is_empty() {
-            println!("✅ No changes since anchor '{}'", name);
+        Ok(data)
+    }
+    #[cfg(target_arch = "wasm32")]
+    fn seal(&self, anchor: &Anchor, data: Vec<u8>) -> Result<Vec<u8>> {
+        if anchor.encrypted {
+            anyhow::bail!("Anchor encryption is not available in this build");
         }
-        Ok(())
+        Ok(data)
     }
-    fn save_anchor(&self, anchor: &Anchor) -> Result<()> {
-        let anchor_file = self.anchors_dir.join(format!("{}.json", anchor.name));
-        let json = serde_json::to_string_pretty(anchor)?;
-        fs::write(&anchor_file, json)?;
-        Ok(())
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open(&self, data: Vec<u8>) -> Result<Vec<u8>> {
+        self.crypto.open(&data)
     }
-    fn load_anchor(&self, name: &str) -> Result<Anchor> {
-        let anchor_file = self.anchors_dir.join(format!("{}.json", name));
-        if !anchor_file.exists() {
-            return Err(anyhow::anyhow!("Anchor '{}' not found", name));
-        }
-        let content = fs::read_to_string(&anchor_file)?;
-        let anchor: Anchor = serde_json::from_str(&content)?;
-        Ok(anchor)
+    #[cfg(target_arch = "wasm32")]
+    fn open(&self, data: Vec<u8>) -> Result<Vec<u8>> {
+        Ok(data)
     }
+    fn backup_key(anchor: &Anchor, path: &Path) -> String {
+        format!(
+            "snapshots/{}/{}", anchor.name, path.strip_prefix("./").unwrap_or(path)
+            .to_string_lossy()
+        )
+    }
+    #[cfg(not(target_arch = "wasm32"))]
     fn checkout_git_commit(&self, commit: &str) -> Result<()> {
         let output = Command::new("git").args(&["checkout", commit]).output()?;
         if !output.status.success() {
@@ -435,41 +467,30 @@ impl AnchorManager {
         Ok(format!("{:x}", hasher.finalize()))
     }
     fn save_file_backups(&self, anchor: &Anchor) -> Result<()> {
-        let backup_dir = self.snapshots_dir.join(&anchor.name);
-        fs::create_dir_all(&backup_dir)?;
         for (_, file) in &anchor.files_snapshot {
             if file.path.exists() {
-                let backup_path = backup_dir
-                    .join(file.path.strip_prefix("./").unwrap_or(&file.path));
-                if let Some(parent) = backup_path.parent() {
-                    fs::create_dir_all(parent)?;
-                }
-                fs::copy(&file.path, &backup_path)?;
+                let data = self.seal(anchor, fs::read(&file.path)?)?;
+                self.storage.write(&Self::backup_key(anchor, &file.path), &data)?;
             }
         }
         Ok(())
     }
-    fn restore_cargo_lock(&self, anchor: &Anchor) -> Result<()> {
+    fn restore_cargo_lock(&self, anchor: &Anchor) -> Result<bool> {
         let current_hash = self.hash_cargo_lock()?;
         if current_hash != anchor.cargo_lock_hash
             && anchor.cargo_lock_hash != "no-cargo-lock"
         {
-            let backup_dir = self.snapshots_dir.join(&anchor.name);
-            let backup_cargo_lock = backup_dir.join("Cargo.lock");
-            if backup_cargo_lock.exists() {
-                fs::copy(&backup_cargo_lock, "Cargo.lock")?;
-                println!("   📦 Cargo.lock restored");
+            if let Some(content) = self.read_backup(anchor, Path::new("Cargo.lock"))? {
+                fs::write("Cargo.lock", content)?;
+                return Ok(true);
             }
         }
-        Ok(())
+        Ok(false)
     }
     fn restore_files(&self, anchor: &Anchor) -> Result<usize> {
-        let backup_dir = self.snapshots_dir.join(&anchor.name);
         let mut restored_count = 0;
         for (_, file) in &anchor.files_snapshot {
-            let backup_path = backup_dir
-                .join(file.path.strip_prefix("./").unwrap_or(&file.path));
-            if backup_path.exists() {
+            if let Some(content) = self.read_backup(anchor, &file.path)? {
                 let current_hash = if file.path.exists() {
                     self.hash_file(&file.path).unwrap_or_default()
                 } else {
@@ -479,7 +500,7 @@ impl AnchorManager {
                     if let Some(parent) = file.path.parent() {
                         fs::create_dir_all(parent)?;
                     }
-                    fs::copy(&backup_path, &file.path)?;
+                    fs::write(&file.path, content)?;
                     restored_count += 1;
                 }
             }
@@ -504,11 +525,14 @@ impl AnchorManager {
             .and_then(|n| n.as_str())
             .unwrap_or("unknown")
             .to_string();
+        #[cfg(not(target_arch = "wasm32"))]
         let rust_version = Command::new("rustc")
             .arg("--version")
             .output()
             .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
             .unwrap_or_else(|_| "unknown".to_string());
+        #[cfg(target_arch = "wasm32")]
+        let rust_version = "unknown".to_string();
         let dependencies_count = manifest
             .get("dependencies")
             .and_then(|d| d.as_table())
@@ -535,12 +559,309 @@ impl AnchorManager {
         Ok(total)
     }
 }
-#[derive(Debug)]
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Serialize)]
+pub struct GitExport {
+    pub anchor: String,
+    pub commit: String,
+    /// The branch name, or `stash@{0}`
+    pub reference: String,
+    pub files: usize,
+    pub skipped: Vec<String>,
+}
+#[cfg(not(target_arch = "wasm32"))]
+impl GitExport {
+    pub fn display(&self) {
+        println!("✅ Anchor '{}' is now {}", self.anchor.green(), self.reference.cyan().bold());
+        println!("   🔗 Commit {} with {} file(s)", &self.commit[..12.min(self.commit.len())], self.files);
+        if !self.skipped.is_empty() {
+            println!("   ⏭️  Left out: {} (pass --include-env to export .env files)", self.skipped.join(", ").dimmed());
+        }
+    }
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn run_git(args: &[&str], envs: &[(&str, &str)], input: Option<&[u8]>) -> Result<Vec<u8>> {
+    use std::io::Write;
+    use std::process::Stdio;
+    let mut child = Command::new("git")
+        .args(args)
+        .envs(envs.iter().copied())
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run git")?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input)?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(output.stdout)
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn git_line(args: &[&str], envs: &[(&str, &str)]) -> Result<String> {
+    Ok(String::from_utf8_lossy(&run_git(args, envs, None)?).trim().to_string())
+}
+/// The files `save` snapshots, so a git export and import cover the same set
+#[cfg(not(target_arch = "wasm32"))]
+fn is_snapshot_path(path: &str) -> bool {
+    path.ends_with(".rs") || path.ends_with(".toml") || path.ends_with(".lock") || is_env_path(path)
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn is_env_path(path: &str) -> bool {
+    path.rsplit('/').next().is_some_and(|file| file.starts_with(".env"))
+}
+#[cfg(not(target_arch = "wasm32"))]
+impl AnchorManager {
+    /// Commits the anchor's files on top of the commit it was taken at (or HEAD) through a
+    /// temporary index, so the working tree and the real index are never touched
+    pub fn to_git(
+        &self,
+        name: &str,
+        branch: Option<&str>,
+        stash: bool,
+        force: bool,
+        include_env: bool,
+    ) -> Result<GitExport> {
+        let anchor = self.load_anchor(name)?;
+        let prefix = git_line(&["rev-parse", "--show-prefix"], &[]).context("Not inside a git repository")?;
+        let base = anchor
+            .git_commit
+            .clone()
+            .filter(|commit| git_line(&["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", commit)], &[]).is_ok())
+            .map_or_else(|| git_line(&["rev-parse", "HEAD"], &[]), Ok)
+            .context("The repository has no commits to put the anchor on")?;
+        let index = std::env::temp_dir().join(format!("cm-anchor-{}-{}.index", name, std::process::id()));
+        let index_path = index.to_string_lossy().to_string();
+        let env = [("GIT_INDEX_FILE", index_path.as_str())];
+        let mut files: Vec<&FileSnapshot> = anchor.files_snapshot.values().collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut skipped = Vec::new();
+        let mut written = 0;
+        let tree = (|| -> Result<String> {
+            git_line(&["read-tree", &base], &env)?;
+            for file in files {
+                let path = file.path.strip_prefix("./").unwrap_or(&file.path).to_string_lossy().replace('\\', "/");
+                if is_env_path(&path) && !include_env {
+                    skipped.push(path);
+                    continue;
+                }
+                let Some(content) = self.read_backup(&anchor, &file.path)? else {
+                    skipped.push(path);
+                    continue;
+                };
+                let blob = String::from_utf8_lossy(&run_git(&["hash-object", "-w", "--stdin"], &[], Some(&content))?)
+                    .trim()
+                    .to_string();
+                let cacheinfo = format!("100644,{},{}{}", blob, prefix, path);
+                git_line(&["update-index", "--add", "--cacheinfo", &cacheinfo], &env)?;
+                written += 1;
+            }
+            git_line(&["write-tree"], &env)
+        })();
+        let _ = fs::remove_file(&index);
+        let tree = tree?;
+        let date = anchor.timestamp.to_rfc3339();
+        let dated = [("GIT_AUTHOR_DATE", date.as_str())];
+        let message = format!("anchor: {}\n\n{}", anchor.name, anchor.description);
+        let reference = if stash {
+            // `git stash apply` expects the working-tree commit to have HEAD and an index commit as parents
+            let base_tree = git_line(&["rev-parse", &format!("{}^{{tree}}", base)], &[])?;
+            let index_commit = git_line(
+                &["commit-tree", &base_tree, "-p", &base, "-m", &format!("index on anchor {}", anchor.name)],
+                &dated,
+            )?;
+            let commit = git_line(&["commit-tree", &tree, "-p", &base, "-p", &index_commit, "-m", &message], &dated)?;
+            git_line(&["stash", "store", "-m", &format!("anchor: {}", anchor.name), &commit], &[])?;
+            ("stash@{0}".to_string(), commit)
+        } else {
+            let branch = branch.map(str::to_string).unwrap_or_else(|| format!("anchor/{}", anchor.name));
+            let commit = git_line(&["commit-tree", &tree, "-p", &base, "-m", &message], &dated)?;
+            let mut args = vec!["branch"];
+            if force {
+                args.push("--force");
+            }
+            args.extend([branch.as_str(), commit.as_str()]);
+            git_line(&args, &[])?;
+            (branch, commit)
+        };
+        Ok(GitExport {
+            anchor: anchor.name,
+            reference: reference.0,
+            commit: reference.1,
+            files: written,
+            skipped,
+        })
+    }
+    /// Saves the snapshot-able files of a git ref, relative to the current directory, as an anchor
+    pub fn save_from_git(&self, reference: &str, name: Option<&str>, description: Option<&str>, force: bool) -> Result<Anchor> {
+        let commit = git_line(&["rev-parse", "--verify", &format!("{}^{{commit}}", reference)], &[])
+            .with_context(|| format!("'{}' is not a git commit, branch or stash", reference))?;
+        let name = name.map(str::to_string).unwrap_or_else(|| reference.replace(['/', '@', '{', '}', ':', '~', '^'], "-"));
+        if !force && self.load_anchor(&name).is_ok() {
+            anyhow::bail!("Anchor '{}' already exists (use --force to replace it)", name);
+        }
+        let committed: DateTime<Utc> = git_line(&["show", "-s", "--format=%cI", &commit], &[])?
+            .parse()
+            .unwrap_or_else(|_| Utc::now());
+        let listing = git_line(&["ls-tree", "-r", "--name-only", &commit], &[])?;
+        let mut contents = Vec::new();
+        for path in listing.lines().filter(|path| is_snapshot_path(path)) {
+            let content = run_git(&["cat-file", "blob", &format!("{}:./{}", commit, path)], &[], None)?;
+            contents.push((PathBuf::from(path), content));
+        }
+        let digest = |data: &[u8]| format!("{:x}", Sha256::digest(data));
+        let text = |wanted: &str| {
+            contents
+                .iter()
+                .find(|(path, _)| path == Path::new(wanted))
+                .map(|(_, content)| String::from_utf8_lossy(content).to_string())
+        };
+        let manifest: Option<toml::Value> = text("Cargo.toml").and_then(|content| toml::from_str(&content).ok());
+        let metadata = AnchorMetadata {
+            project_name: manifest
+                .as_ref()
+                .and_then(|m| m.get("package")?.get("name")?.as_str().map(str::to_string))
+                .unwrap_or_else(|| "unknown".to_string()),
+            rust_version: "unknown (imported from git)".to_string(),
+            dependencies_count: manifest
+                .as_ref()
+                .and_then(|m| m.get("dependencies")?.as_table().map(|t| t.len()))
+                .unwrap_or(0),
+            total_loc: contents
+                .iter()
+                .filter(|(path, _)| path.starts_with("src") && path.extension().is_some_and(|e| e == "rs"))
+                .map(|(_, content)| String::from_utf8_lossy(content).lines().count())
+                .sum(),
+        };
+        let anchor = Anchor {
+            name: name.clone(),
+            timestamp: Utc::now(),
+            description: description
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("Imported from git {} ({})", reference, &commit[..12.min(commit.len())])),
+            git_commit: Some(commit.clone()),
+            cargo_lock_hash: text("Cargo.lock").map_or_else(|| "no-cargo-lock".to_string(), |lock| digest(lock.as_bytes())),
+            files_snapshot: contents
+                .iter()
+                .map(|(path, content)| {
+                    let snapshot = FileSnapshot {
+                        path: path.clone(),
+                        hash: digest(content),
+                        size: content.len() as u64,
+                        modified: committed,
+                    };
+                    (path.to_string_lossy().to_string(), snapshot)
+                })
+                .collect(),
+            environment: HashMap::new(),
+            metadata,
+            encrypted: self.encrypt,
+        };
+        self.store(&anchor)?;
+        for (path, content) in contents {
+            let data = self.seal(&anchor, content)?;
+            self.storage.write(&Self::backup_key(&anchor, &path), &data)?;
+        }
+        Ok(anchor)
+    }
+}
+impl Anchor {
+    pub fn display(&self) {
+        println!("{}", format!("=== Anchor: {} ===", self.name) .blue().bold());
+        println!("📅 Created: {}", self.timestamp.format("%Y-%m-%d %H:%M:%S"));
+        println!("📝 Description: {}", self.description);
+        if self.encrypted {
+            println!("🔒 Encrypted at rest");
+        }
+        if let Some(ref commit) = self.git_commit {
+            println!("🔗 Git commit: {}", commit.dimmed());
+        }
+        println!("\n📊 Metadata:");
+        println!("   Project: {}", self.metadata.project_name);
+        println!("   Rust version: {}", self.metadata.rust_version);
+        println!("   Dependencies: {}", self.metadata.dependencies_count);
+        println!("   Lines of code: {}", self.metadata.total_loc);
+        println!("\n📁 Files snapshot ({} files):", self.files_snapshot.len());
+        let mut files: Vec<_> = self.files_snapshot.values().collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        for (i, file) in files.iter().enumerate().take(10) {
+            println!("   {} {}", if i < 9 { " " } else { "" }, file.path.display());
+        }
+        if self.files_snapshot.len() > 10 {
+            println!("   ... and {} more files", self.files_snapshot.len() - 10);
+        }
+    }
+    pub fn display_saved(&self) {
+        println!("✅ Anchor '{}' saved successfully!", self.name.green());
+        println!("   📁 {} files backed up", self.files_snapshot.len());
+        if self.encrypted {
+            println!("   🔒 Encrypted at rest");
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub name: String,
+    pub timestamp: DateTime<Utc>,
+    pub files_restored: usize,
+    pub cargo_lock_restored: bool,
+}
+impl RestoreReport {
+    pub fn display(&self) {
+        if self.cargo_lock_restored {
+            println!("   📦 Cargo.lock restored");
+        }
+        println!("✅ Anchor '{}' restored successfully!", self.name.green());
+        println!("   📁 {} files restored", self.files_restored);
+        println!("   🕐 From: {}", self.timestamp.format("%Y-%m-%d %H:%M:%S"));
+    }
+}
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AnchorDiff {
+    pub name: String,
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub deleted: Vec<String>,
+}
+impl AnchorDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+    pub fn display(&self) {
+        println!("{}", format!("=== Diff from anchor '{}' ===", self.name) .blue().bold());
+        if !self.added.is_empty() {
+            println!("\n✨ Added files:");
+            for path in &self.added {
+                println!("   + {}", path.green());
+            }
+        }
+        if !self.modified.is_empty() {
+            println!("\n📝 Modified files:");
+            for path in &self.modified {
+                println!("   ~ {}", path.yellow());
+            }
+        }
+        if !self.deleted.is_empty() {
+            println!("\n🗑️  Deleted files:");
+            for path in &self.deleted {
+                println!("   - {}", path.red());
+            }
+        }
+        if self.is_empty() {
+            println!("✅ No changes since anchor '{}'", self.name);
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize)]
 pub struct AnchorSummary {
     pub name: String,
     pub timestamp: DateTime<Utc>,
     pub description: String,
     pub files_count: usize,
+    pub project: String,
 }
 impl AnchorSummary {
     pub fn display(&self) {
@@ -551,6 +872,7 @@ impl AnchorSummary {
         println!("   {}", self.description.dimmed());
     }
 }
+#[cfg(not(target_arch = "wasm32"))]
 pub fn check_license_ahoy(command: &str) -> Result<bool> {
     println!(
         "🏴‍☠️ Ahoy there, matey! Let me check yer license for '{}'", command
@@ -576,4 +898,64 @@ pub fn check_license_ahoy(command: &str) -> Result<bool> {
             Ok(false)
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    const REPO_ENV: &str = "CM_ANCHOR_GIT_TEST";
+    fn git(args: &[&str]) {
+        git_line(args, &[]).unwrap();
+    }
+    fn hashes(anchor: &Anchor) -> HashMap<String, String> {
+        anchor
+            .files_snapshot
+            .values()
+            .filter(|file| is_snapshot_path(&file.path.to_string_lossy()))
+            .map(|file| (file.path.to_string_lossy().to_string(), file.hash.clone()))
+            .collect()
+    }
+    #[test]
+    fn test_git_round_trip_through_branch_and_stash() {
+        // git runs in the current directory, so the body runs in a child test process
+        // whose working directory is a scratch repository
+        if std::env::var_os(REPO_ENV).is_none() {
+            let repo = tempfile::tempdir().unwrap();
+            let status = Command::new(std::env::current_exe().unwrap())
+                .args(["anchor::tests::test_git_round_trip_through_branch_and_stash", "--exact", "--nocapture"])
+                .current_dir(repo.path())
+                .env(REPO_ENV, "1")
+                .status()
+                .unwrap();
+            assert!(status.success());
+            return;
+        }
+        git(&["init", "--quiet"]);
+        git(&["config", "user.name", "cm"]);
+        git(&["config", "user.email", "cm@example.com"]);
+        fs::create_dir_all("src").unwrap();
+        fs::write("Cargo.toml", "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n").unwrap();
+        fs::write("Cargo.lock", "version = 3\n").unwrap();
+        fs::write("src/main.rs", "fn main() {}\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "init"]);
+        fs::write("src/main.rs", "fn main() { demo::run() }\n").unwrap();
+        fs::write("src/lib.rs", "pub fn run() {}\n").unwrap();
+        let manager = AnchorManager::with_storage(Arc::new(MemoryStorage::new()));
+        let saved = manager.save("wip", "work in progress").unwrap();
+        let expected = hashes(&saved);
+        assert_eq!(expected.len(), 4);
+        let branch = manager.to_git("wip", Some("anchor/wip"), false, false, false).unwrap();
+        assert_eq!((branch.reference.as_str(), branch.files), ("anchor/wip", 4));
+        assert_eq!(hashes(&manager.save_from_git("anchor/wip", Some("from-branch"), None, false).unwrap()), expected);
+        let stash = manager.to_git("wip", None, true, false, false).unwrap();
+        assert_eq!(stash.reference, "stash@{0}");
+        assert_eq!(hashes(&manager.save_from_git("stash@{0}", Some("from-stash"), None, false).unwrap()), expected);
+        git(&["checkout", "--quiet", "--", "."]);
+        fs::remove_file("src/lib.rs").unwrap();
+        git(&["stash", "apply", "--quiet"]);
+        for (path, hash) in &expected {
+            assert_eq!(&manager.hash_file(Path::new(path)).unwrap(), hash, "{}", path);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Mutex;
+/// Every encrypted blob starts with this, so plaintext anchors saved before encryption
+/// existed keep loading unchanged
+const MAGIC: &[u8] = b"CMANCHOR1";
+const KEYRING_SOURCE: u8 = 0;
+const PASSPHRASE_SOURCE: u8 = 1;
+pub const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const PBKDF2_ROUNDS: u32 = 600_000;
+const KEYRING_SERVICE: &str = "cargo-mate";
+const KEYRING_USER: &str = "anchor-key";
+/// Takes precedence over the keyring; the only option on machines without one
+pub const PASSPHRASE_ENV: &str = "CM_ANCHOR_PASSPHRASE";
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+/// Seals anchor metadata and file backups with AES-256-GCM.
+///
+/// Layout: `MAGIC | source | [salt] | nonce | ciphertext`. Passphrase keys go through
+/// PBKDF2, which is deliberately slow, so derived keys are cached per salt and one salt
+/// is shared by everything written through the same instance.
+pub struct AnchorCrypto {
+    salt: [u8; SALT_LEN],
+    passphrase: Mutex<Option<String>>,
+    keys: Mutex<HashMap<Vec<u8>, [u8; 32]>>,
+}
+impl Default for AnchorCrypto {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl AnchorCrypto {
+    pub fn new() -> Self {
+        Self {
+            salt: rand::random(),
+            passphrase: Mutex::new(None),
+            keys: Mutex::new(HashMap::new()),
+        }
+    }
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut header = MAGIC.to_vec();
+        if std::env::var(PASSPHRASE_ENV).is_ok_and(|p| !p.is_empty()) {
+            header.push(PASSPHRASE_SOURCE);
+            header.extend(self.salt);
+        } else {
+            header.push(KEYRING_SOURCE);
+        }
+        let key = self.key(&header, true)?;
+        let nonce = rand::random::<[u8; NONCE_LEN]>();
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt anchor data"))?;
+        header.extend(nonce);
+        header.extend(ciphertext);
+        Ok(header)
+    }
+    /// Plaintext passes through untouched
+    pub fn open(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if !is_encrypted(data) {
+            return Ok(data.to_vec());
+        }
+        let header_len = match data.get(MAGIC.len()) {
+            Some(&KEYRING_SOURCE) => MAGIC.len() + 1,
+            Some(&PASSPHRASE_SOURCE) => MAGIC.len() + 1 + SALT_LEN,
+            _ => bail!("Unsupported anchor encryption format"),
+        };
+        if data.len() <= header_len + NONCE_LEN {
+            bail!("Encrypted anchor data is truncated");
+        }
+        let (header, rest) = data.split_at(header_len);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let key = self.key(header, false)?;
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow::anyhow!("Cannot decrypt anchor - wrong passphrase or key"))
+    }
+    fn key(&self, header: &[u8], create: bool) -> Result<[u8; 32]> {
+        let mut keys = self.keys.lock().unwrap();
+        if let Some(key) = keys.get(header) {
+            return Ok(*key);
+        }
+        let key = if header[MAGIC.len()] == KEYRING_SOURCE {
+            keyring_key(create)?
+        } else {
+            derive_key(&self.passphrase()?, &header[MAGIC.len() + 1..])
+        };
+        keys.insert(header.to_vec(), key);
+        Ok(key)
+    }
+    fn passphrase(&self) -> Result<String> {
+        let mut cached = self.passphrase.lock().unwrap();
+        if let Some(passphrase) = cached.as_ref() {
+            return Ok(passphrase.clone());
+        }
+        let passphrase = match std::env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) if !passphrase.is_empty() => passphrase,
+            _ if atty::is(atty::Stream::Stdin) => rpassword::prompt_password("🔑 Anchor passphrase: ")?,
+            _ => bail!("Anchor is encrypted with a passphrase - set {}", PASSPHRASE_ENV),
+        };
+        *cached = Some(passphrase.clone());
+        Ok(passphrase)
+    }
+}
+/// PBKDF2-HMAC-SHA256 over `salt`; also keys passphrase-sealed secrets
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+    key
+}
+fn keyring_key(create: bool) -> Result<[u8; 32]> {
+    let hint = || format!("OS keyring unavailable - set {} to use a passphrase instead", PASSPHRASE_ENV);
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).with_context(hint)?;
+    match entry.get_password() {
+        Ok(encoded) => {
+            let key = general_purpose::STANDARD.decode(encoded.trim()).context("Corrupt anchor key in the OS keyring")?;
+            key.try_into().map_err(|_| anyhow::anyhow!("Anchor key in the OS keyring must hold 32 bytes"))
+        }
+        Err(keyring::Error::NoEntry) if create => {
+            let key = rand::random::<[u8; 32]>();
+            entry.set_password(&general_purpose::STANDARD.encode(key)).with_context(hint)?;
+            Ok(key)
+        }
+        Err(keyring::Error::NoEntry) => bail!("No anchor key in the OS keyring - was this anchor saved by another user?"),
+        Err(e) => Err(e).with_context(hint),
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_passphrase_round_trip() {
+        std::env::set_var(PASSPHRASE_ENV, "correct horse");
+        let crypto = AnchorCrypto::new();
+        let sealed = crypto.seal(b"fn main() {}").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert_eq!(crypto.open(&sealed).unwrap(), b"fn main() {}");
+        assert_eq!(crypto.open(b"{}").unwrap(), b"{}");
+        let other = AnchorCrypto::new();
+        *other.passphrase.lock().unwrap() = Some("battery staple".to_string());
+        assert!(other.open(&sealed).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use crate::parser::CompilerArtifact;
+use crate::display::format_size;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+pub const CATALOG_FILE: &str = "artifacts/catalog.json";
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactRecord {
+    pub name: String,
+    pub kind: String,
+    pub package: String,
+    pub path: String,
+    pub size: u64,
+    pub profile: String,
+    pub test: bool,
+    pub executable: bool,
+    pub timestamp: DateTime<Utc>,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ArtifactSort {
+    Name,
+    Kind,
+    Size,
+    Profile,
+    Time,
+}
+/// The profile directory is the one holding `deps/`, `build/` or `examples/`, or the
+/// file's own directory for top-level outputs like `target/release/app`
+pub fn profile_of(path: &Path) -> String {
+    for dir in path.ancestors().skip(1) {
+        let name = dir.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if matches!(name, "deps" | "build" | "examples") {
+            if let Some(profile) = dir.parent().and_then(|p| p.file_name()) {
+                return profile.to_string_lossy().to_string();
+            }
+        }
+    }
+    path.parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+fn package_name(package_id: &str) -> String {
+    if let Some((name, _)) = package_id.split_once(' ') {
+        return name.to_string();
+    }
+    let spec = package_id.rsplit('/').next().unwrap_or(package_id);
+    match spec.split_once('#') {
+        Some((name, version)) if version.contains('@') => {
+            version.split('@').next().unwrap_or(name).to_string()
+        }
+        Some((name, _)) => name.to_string(),
+        None => spec.to_string(),
+    }
+}
+pub fn records(artifacts: &[CompilerArtifact], now: DateTime<Utc>) -> Vec<ArtifactRecord> {
+    artifacts
+        .iter()
+        .flat_map(|artifact| {
+            artifact
+                .filenames
+                .iter()
+                .map(move |file| {
+                    let path = Path::new(file);
+                    ArtifactRecord {
+                        name: artifact.target.name.clone(),
+                        kind: artifact.target.kind.join(","),
+                        package: package_name(&artifact.package_id),
+                        path: file.clone(),
+                        size: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+                        profile: profile_of(path),
+                        test: artifact.profile.test,
+                        executable: artifact.executable.as_deref() == Some(file.as_str()),
+                        timestamp: now,
+                    }
+                })
+        })
+        .collect()
+}
+/// Newer records replace older ones for the same path; files removed by `cargo clean`
+/// drop out of the catalog
+pub fn merge(
+    catalog: Vec<ArtifactRecord>,
+    fresh: Vec<ArtifactRecord>,
+    exists: impl Fn(&str) -> bool,
+) -> Vec<ArtifactRecord> {
+    let mut merged: Vec<ArtifactRecord> = catalog
+        .into_iter()
+        .filter(|old| !fresh.iter().any(|new| new.path == old.path) && exists(&old.path))
+        .collect();
+    merged.extend(fresh);
+    merged
+}
+fn load_file(path: &Path) -> Vec<ArtifactRecord> {
+    crate::storage::read_json_file(path).ok().flatten().unwrap_or_default()
+}
+pub fn record_build(artifacts: &[CompilerArtifact]) -> Result<()> {
+    let path = crate::project::data_file(CATALOG_FILE)?;
+    let fresh = records(artifacts, Utc::now());
+    crate::storage::update_json(&path, |catalog: &mut Vec<ArtifactRecord>| {
+        *catalog = merge(std::mem::take(catalog), fresh, |file| Path::new(file).exists());
+    })
+}
+pub fn load_catalog() -> Result<Vec<ArtifactRecord>> {
+    Ok(
+        crate::project::read_files(CATALOG_FILE)?
+            .iter()
+            .flat_map(|(_, path)| load_file(path))
+            .collect(),
+    )
+}
+pub fn sort(records: &mut [ArtifactRecord], by: ArtifactSort) {
+    match by {
+        ArtifactSort::Name => records.sort_by(|a, b| a.name.cmp(&b.name).then(a.path.cmp(&b.path))),
+        ArtifactSort::Kind => records.sort_by(|a, b| a.kind.cmp(&b.kind).then(a.name.cmp(&b.name))),
+        ArtifactSort::Size => records.sort_by_key(|r| Reverse(r.size)),
+        ArtifactSort::Profile => {
+            records.sort_by(|a, b| a.profile.cmp(&b.profile).then(a.name.cmp(&b.name)))
+        }
+        ArtifactSort::Time => records.sort_by_key(|r| Reverse(r.timestamp)),
+    }
+}
+pub fn show_catalog(by: ArtifactSort, kind: Option<&str>, all: bool) -> Result<()> {
+    let mut records: Vec<ArtifactRecord> = load_catalog()?
+        .into_iter()
+        .filter(|r| kind.is_none_or(|k| r.kind.split(',').any(|rk| rk == k)))
+        .filter(|r| all || r.kind.split(',').all(|k| k != "custom-build") && !r.path.ends_with(".rmeta"))
+        .collect();
+    if records.is_empty() {
+        println!("📁 No artifacts found");
+        return Ok(());
+    }
+    sort(&mut records, by);
+    println!("📦 Generated Artifacts:");
+    println!("{}", "═".repeat(90).blue());
+    let name_width = records.iter().map(|r| r.name.len()).max().unwrap_or(4).max(4);
+    println!(
+        "{}", format!(
+            "{:<name_width$}  {:<10} {:<10} {:>9}  {:<16}  {}", "NAME", "KIND", "PROFILE",
+            "SIZE", "BUILT", "PATH", name_width = name_width
+        ).bold()
+    );
+    for record in &records {
+        let name = format!("{:<name_width$}", record.name, name_width = name_width);
+        let name = if record.executable { name.green().bold() } else { name.normal() };
+        println!(
+            "{}  {:<10} {:<10} {:>9}  {:<16}  {}", name, record.kind, record.profile,
+            format_size(record.size), record.timestamp.format("%Y-%m-%d %H:%M"), record.path
+            .dimmed()
+        );
+    }
+    let total: u64 = records.iter().map(|r| r.size).sum();
+    println!();
+    println!("{} artifacts, {} total", records.len(), format_size(total));
+    Ok(())
+}
+/// Prefer executables, then the newest build, when several artifacts share a name
+pub fn find<'a>(records: &'a [ArtifactRecord], name: &str) -> Option<&'a ArtifactRecord> {
+    records
+        .iter()
+        .filter(|r| r.name == name || Path::new(&r.path).file_name().is_some_and(|f| f == name))
+        .max_by_key(|r| (r.executable, r.timestamp))
+}
+pub fn reveal(name: &str) -> Result<()> {
+    let records = load_catalog()?;
+    let Some(record) = find(&records, name) else {
+        bail!("No artifact named '{}' in the catalog - run a build through cargo-mate first", name);
+    };
+    let path = PathBuf::from(&record.path);
+    if !path.exists() {
+        bail!("{} no longer exists (run the build again)", path.display());
+    }
+    println!("🚀 Revealing {}", path.display().to_string().cyan());
+    let spawned = if cfg!(target_os = "macos") {
+        Command::new("open").arg("-R").arg(&path).spawn()
+    } else if cfg!(target_os = "windows") {
+        Command::new("explorer").arg(format!("/select,{}", path.display())).spawn()
+    } else {
+        Command::new("xdg-open").arg(path.parent().unwrap_or(&path)).spawn()
+    };
+    spawned.map(|_| ()).map_err(|e| anyhow::anyhow!("Could not open the file manager: {}", e))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn record(name: &str, path: &str, size: u64) -> ArtifactRecord {
+        ArtifactRecord {
+            name: name.to_string(),
+            kind: "bin".to_string(),
+            package: name.to_string(),
+            path: path.to_string(),
+            size,
+            profile: profile_of(Path::new(path)),
+            test: false,
+            executable: true,
+            timestamp: Utc::now(),
+        }
+    }
+    #[test]
+    fn catalog_infers_profiles_merges_and_sorts() {
+        assert_eq!(profile_of(Path::new("/p/target/release/app")), "release");
+        assert_eq!(profile_of(Path::new("/p/target/debug/deps/libserde-1a2b.rlib")), "debug");
+        assert_eq!(
+            profile_of(Path::new("/p/target/x86_64-unknown-linux-gnu/release-lto/build/app-9f/build-script-build")),
+            "release-lto"
+        );
+        assert_eq!(package_name("path+file:///work/app#0.1.0"), "app");
+        assert_eq!(package_name("registry+https://github.com/rust-lang/crates.io-index#serde@1.0.210"), "serde");
+        assert_eq!(package_name("serde 1.0.210 (registry+https://github.com/rust-lang/crates.io-index)"), "serde");
+        let old = vec![record("app", "/p/target/debug/app", 10), record("gone", "/p/target/debug/gone", 5)];
+        let fresh = vec![record("app", "/p/target/debug/app", 20), record("app", "/p/target/release/app", 8)];
+        let mut merged = merge(old, fresh, |path| !path.ends_with("gone"));
+        assert_eq!(merged.len(), 2);
+        sort(&mut merged, ArtifactSort::Size);
+        assert_eq!(merged[0].size, 20);
+        assert_eq!(find(&merged, "app").unwrap().profile, "release");
+    }
+}
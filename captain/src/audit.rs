@@ -0,0 +1,250 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::Subcommand;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+const AUDIT_FILE: &str = "audit.jsonl";
+/// `prev` of the first entry; every later entry chains to its predecessor's hash
+const GENESIS: &str = "genesis";
+#[derive(Subcommand, Debug)]
+pub enum AuditAction {
+    /// Show recorded destructive operations, newest last
+    Show {
+        /// Only entries whose action starts with this (e.g. "scat", "anchor")
+        #[arg(long)]
+        action: Option<String>,
+        /// Only entries newer than this: "7d", "12h" or a date like 2026-01-31
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries that touched a path containing this
+        #[arg(long)]
+        path: Option<String>,
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check that no entry was edited or removed
+    Verify,
+}
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Change {
+    pub path: String,
+    /// `sha256:<hex>` for files, `dir:<bytes>` for directories, `missing` when absent
+    pub before: String,
+    pub after: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub action: String,
+    pub details: String,
+    pub user: Option<String>,
+    pub cwd: String,
+    pub project: Option<String>,
+    pub changes: Vec<Change>,
+    pub prev: String,
+    #[serde(default)]
+    pub hash: String,
+}
+impl AuditEntry {
+    fn digest(&self) -> String {
+        let mut unsigned = self.clone();
+        unsigned.hash = String::new();
+        let body = serde_json::to_string(&unsigned).unwrap_or_default();
+        format!("{:x}", Sha256::digest(body.as_bytes()))
+    }
+}
+/// Fingerprint of a file or directory as stored in `Change::before`/`after`
+pub fn fingerprint(path: &Path) -> String {
+    if path.is_dir() {
+        let bytes: u64 = WalkDir::new(path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.metadata().ok())
+            .filter(|m| m.is_file())
+            .map(|m| m.len())
+            .sum();
+        format!("dir:{}", bytes)
+    } else {
+        match fs::read(path) {
+            Ok(content) => format!("sha256:{:x}", Sha256::digest(&content)),
+            Err(_) => "missing".to_string(),
+        }
+    }
+}
+/// Every `.rs` file below `root` (or `root` itself when it is a file)
+pub fn rust_sources(root: &Path) -> Vec<PathBuf> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|x| x == "rs"))
+        .map(|e| e.into_path())
+        .collect()
+}
+fn audit_path() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join("audit").join(AUDIT_FILE))
+}
+pub fn read_entries(path: &Path) -> Result<Vec<AuditEntry>> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Ok(Vec::new());
+    };
+    content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            serde_json::from_str(line).with_context(|| format!("{}: line {} is not an audit entry", path.display(), i + 1))
+        })
+        .collect()
+}
+pub fn append(path: &Path, action: &str, details: &str, changes: Vec<Change>) -> Result<AuditEntry> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let last = read_entries(path)?.pop();
+    let mut entry = AuditEntry {
+        seq: last.as_ref().map_or(1, |e| e.seq + 1),
+        timestamp: Utc::now(),
+        action: action.to_string(),
+        details: details.to_string(),
+        user: std::env::var("USER").ok(),
+        cwd: std::env::current_dir().map(|d| d.display().to_string()).unwrap_or_default(),
+        project: crate::project::current_project().map(|p| p.name),
+        changes,
+        prev: last.map_or_else(|| GENESIS.to_string(), |e| e.hash),
+        hash: String::new(),
+    };
+    entry.hash = entry.digest();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+    Ok(entry)
+}
+/// Record a destructive operation; failures warn instead of failing the operation itself
+pub fn log(action: &str, details: &str, changes: Vec<Change>) {
+    if let Err(e) = audit_path().and_then(|path| append(&path, action, details, changes)) {
+        log::warn!("Could not write audit log: {}", e);
+    }
+}
+/// Index of the first entry whose hash or chain link does not match
+pub fn verify(entries: &[AuditEntry]) -> Option<usize> {
+    let mut prev = GENESIS.to_string();
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.prev != prev || entry.hash != entry.digest() {
+            return Some(i);
+        }
+        prev = entry.hash.clone();
+    }
+    None
+}
+fn parse_since(value: &str) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    let unit = value.chars().last().unwrap_or(' ');
+    if let Ok(n) = value[..value.len() - unit.len_utf8().min(value.len())].parse::<i64>() {
+        let span = match unit {
+            'd' => Some(Duration::days(n)),
+            'h' => Some(Duration::hours(n)),
+            'w' => Some(Duration::weeks(n)),
+            _ => None,
+        };
+        if let Some(span) = span {
+            return Ok(Utc::now() - span);
+        }
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("Invalid --since '{}' (use 7d, 12h, 2w or YYYY-MM-DD)", value))?;
+    Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+}
+pub fn filter<'a>(
+    entries: &'a [AuditEntry],
+    action: Option<&str>,
+    since: Option<DateTime<Utc>>,
+    path: Option<&str>,
+) -> Vec<&'a AuditEntry> {
+    entries
+        .iter()
+        .filter(|e| action.is_none_or(|a| e.action.starts_with(a)))
+        .filter(|e| since.is_none_or(|s| e.timestamp >= s))
+        .filter(|e| path.is_none_or(|p| e.changes.iter().any(|c| c.path.contains(p))))
+        .collect()
+}
+fn short(print: &str) -> String {
+    match print.strip_prefix("sha256:") {
+        Some(hex) => hex.chars().take(12).collect(),
+        None => print.to_string(),
+    }
+}
+fn display(entry: &AuditEntry) {
+    println!(
+        "#{} {} {} {}",
+        entry.seq,
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string().dimmed(),
+        entry.action.cyan().bold(),
+        entry.details
+    );
+    let who = entry.user.as_deref().unwrap_or("unknown");
+    println!("   👤 {} in {}", who, entry.project.as_deref().unwrap_or(&entry.cwd));
+    for change in &entry.changes {
+        println!("   {} {} → {}", change.path, short(&change.before).dimmed(), short(&change.after));
+    }
+}
+pub fn handle_audit(action: AuditAction) -> Result<()> {
+    let path = audit_path()?;
+    let entries = read_entries(&path)?;
+    match action {
+        AuditAction::Show { action, since, path: touched, limit, json } => {
+            let since = since.as_deref().map(parse_since).transpose()?;
+            let matched = filter(&entries, action.as_deref(), since, touched.as_deref());
+            let shown = &matched[matched.len().saturating_sub(limit)..];
+            if json {
+                println!("{}", serde_json::to_string_pretty(shown)?);
+            } else if shown.is_empty() {
+                println!("📜 No matching audit entries");
+            } else {
+                println!("📜 {} ({} of {} entries)", "Audit log".bold().blue(), shown.len(), matched.len());
+                for entry in shown {
+                    display(entry);
+                }
+            }
+        }
+        AuditAction::Verify => match verify(&entries) {
+            None => println!("✅ Audit log intact ({} entries)", entries.len()),
+            Some(i) => anyhow::bail!("Audit log was modified at entry #{} ({})", entries[i].seq, path.display()),
+        },
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_entries_chain_and_detect_tampering() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let file = root.join("lib.rs");
+        fs::write(&file, "fn a() {}").unwrap();
+        let before = fingerprint(&file);
+        fs::write(&file, "fn b() {}").unwrap();
+        assert_eq!(rust_sources(&root), vec![file.clone()]);
+        let changes = vec![Change { path: file.display().to_string(), before, after: fingerprint(&file) }];
+        assert!(changes[0].before.starts_with("sha256:") && changes[0].before != changes[0].after);
+        let log = root.join("audit").join(AUDIT_FILE);
+        append(&log, "scat code", "obfuscated", changes).unwrap();
+        append(&log, "scrub", "cleaned", vec![]).unwrap();
+        let entries = read_entries(&log).unwrap();
+        assert_eq!(entries.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(verify(&entries), None);
+        assert_eq!(filter(&entries, Some("scat"), None, None).len(), 1);
+        assert_eq!(filter(&entries, None, None, Some("lib.rs")).len(), 1);
+        assert_eq!(filter(&entries, None, Some(parse_since("1d").unwrap()), None).len(), 2);
+        let tampered = fs::read_to_string(&log).unwrap().replace("obfuscated", "nothing");
+        fs::write(&log, tampered).unwrap();
+        assert_eq!(verify(&read_entries(&log).unwrap()), Some(0));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::captain::config::ConfigManager;
+use crate::display::format_size;
+use anyhow::{bail, Context, Result};
+use colored::*;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+/// Only the parts of a profile directory that are portable between machines;
+/// `incremental/` is large and tied to the local checkout
+const PROFILE_DIRS: [&str; 3] = ["deps", "build", ".fingerprint"];
+const TOKEN_ENV: &str = "CM_CACHE_TOKEN";
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backend {
+    File(PathBuf),
+    S3(String),
+    Gcs(String),
+    Http(String),
+}
+impl Backend {
+    pub fn parse(url: &str) -> Result<Self> {
+        let url = url.trim().trim_end_matches('/');
+        if let Some(path) = url.strip_prefix("file://") {
+            Ok(Backend::File(PathBuf::from(path)))
+        } else if url.starts_with("s3://") {
+            Ok(Backend::S3(url.to_string()))
+        } else if url.starts_with("gs://") {
+            Ok(Backend::Gcs(url.to_string()))
+        } else if url.starts_with("http://") || url.starts_with("https://") {
+            Ok(Backend::Http(url.to_string()))
+        } else {
+            bail!("Unsupported cache backend '{}' - use s3://, gs://, http(s):// or file://", url)
+        }
+    }
+    pub fn location(&self, object: &str) -> String {
+        match self {
+            Backend::File(dir) => dir.join(object).display().to_string(),
+            Backend::S3(base) | Backend::Gcs(base) | Backend::Http(base) => format!("{}/{}", base, object),
+        }
+    }
+    fn cli_copy(&self, from: &str, to: &str) -> Result<bool> {
+        let (program, args): (&str, &[&str]) = match self {
+            Backend::S3(_) => ("aws", &["s3", "cp", "--only-show-errors"]),
+            Backend::Gcs(_) => ("gcloud", &["storage", "cp"]),
+            _ => bail!("{} is not an object store", self.location("")),
+        };
+        let status = Command::new(program)
+            .args(args)
+            .args([from, to])
+            .stdout(Stdio::null())
+            .status()
+            .with_context(|| format!("Failed to run the {} CLI - is it installed?", program))?;
+        Ok(status.success())
+    }
+    fn upload(&self, file: &Path, object: &str) -> Result<()> {
+        let location = self.location(object);
+        match self {
+            Backend::File(dir) => {
+                fs::create_dir_all(dir)?;
+                fs::copy(file, &location)?;
+            }
+            Backend::S3(_) | Backend::Gcs(_) => {
+                if !self.cli_copy(&file.display().to_string(), &location)? {
+                    bail!("Upload to {} failed", location);
+                }
+            }
+            Backend::Http(_) => {
+                let response = crate::net::send(authorize(client()?.put(&location)).body(fs::File::open(file)?))?;
+                if !response.status().is_success() {
+                    bail!("Upload to {} failed: HTTP {}", location, response.status());
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Returns false when the backend has no object under that name
+    fn download(&self, object: &str, file: &Path) -> Result<bool> {
+        let location = self.location(object);
+        match self {
+            Backend::File(_) => {
+                if !Path::new(&location).is_file() {
+                    return Ok(false);
+                }
+                fs::copy(&location, file)?;
+                Ok(true)
+            }
+            Backend::S3(_) | Backend::Gcs(_) => self.cli_copy(&location, &file.display().to_string()),
+            Backend::Http(_) => {
+                let mut response = crate::net::send(authorize(client()?.get(&location)))?;
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Ok(false);
+                }
+                if !response.status().is_success() {
+                    bail!("Download from {} failed: HTTP {}", location, response.status());
+                }
+                response.copy_to(&mut fs::File::create(file)?)?;
+                Ok(true)
+            }
+        }
+    }
+}
+fn client() -> Result<reqwest::blocking::Client> {
+    crate::net::client(Duration::from_secs(600))
+}
+fn authorize(request: reqwest::blocking::RequestBuilder) -> reqwest::blocking::RequestBuilder {
+    let token = std::env::var(TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.is_empty())
+        .or_else(|| crate::secrets::lookup(crate::secrets::CACHE_TOKEN));
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+/// Artifacts are only reusable with the same compiler and the same resolved dependencies
+pub fn cache_key(scope: &str, rustc_version: &str, lockfile: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(rustc_version.trim().as_bytes());
+    hasher.update([0]);
+    hasher.update(lockfile.as_bytes());
+    let hash = format!("{:x}", hasher.finalize());
+    format!("{}-{}", scope, &hash[..16])
+}
+fn rustc_version() -> Result<String> {
+    let output = Command::new("rustc").arg("-vV").output().context("Failed to run rustc")?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+struct Workspace {
+    name: String,
+    lockfile: String,
+    target_dir: PathBuf,
+}
+fn workspace() -> Result<Workspace> {
+    let manifest = crate::project::find_manifest(&std::env::current_dir()?)
+        .context("Not inside a cargo project")?;
+    let root = manifest.parent().map(PathBuf::from).unwrap_or_default();
+    let lockfile = fs::read_to_string(root.join("Cargo.lock"))
+        .context("No Cargo.lock - run 'cargo generate-lockfile' so the cache key is stable")?;
+    let target_dir = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| root.join("target"));
+    let name = root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    Ok(Workspace { name, lockfile, target_dir })
+}
+fn sccache_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("SCCACHE_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    Ok(dirs::cache_dir().context("Could not find the cache directory")?.join("sccache"))
+}
+/// Archive `entries` (paths relative to `root`) into a gzipped tarball
+pub fn pack(root: &Path, entries: &[PathBuf], archive: &Path) -> Result<usize> {
+    let encoder = GzEncoder::new(fs::File::create(archive)?, Compression::fast());
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+    let mut packed = 0;
+    for entry in entries {
+        let path = root.join(entry);
+        if path.is_dir() {
+            builder.append_dir_all(entry, &path)?;
+            packed += 1;
+        }
+    }
+    builder.into_inner()?.finish()?;
+    Ok(packed)
+}
+pub fn unpack(archive: &Path, root: &Path) -> Result<()> {
+    fs::create_dir_all(root)?;
+    let mut archive = tar::Archive::new(GzDecoder::new(fs::File::open(archive)?));
+    archive.set_preserve_mtime(true);
+    archive.unpack(root)?;
+    Ok(())
+}
+fn resolve_backend(backend: Option<String>) -> Result<Backend> {
+    let url = backend
+        .or_else(|| ConfigManager::new().ok()?.get("cache.backend"))
+        .context("No cache backend - set cache.backend in .cg (s3://, gs://, https:// or file://) or pass --backend")?;
+    Backend::parse(&url)
+}
+/// The object name, the directory the archive is relative to, and what goes in it
+fn plan(profile: &str, sccache: bool, key: Option<String>) -> Result<(String, PathBuf, Vec<PathBuf>)> {
+    let ws = workspace()?;
+    let rustc = rustc_version()?;
+    if sccache {
+        let key = key.unwrap_or_else(|| cache_key("sccache", &rustc, &ws.lockfile));
+        return Ok((format!("{}.tar.gz", key), sccache_dir()?, vec![PathBuf::from(".")]));
+    }
+    let profile = if profile == "dev" { "debug" } else { profile };
+    let key = key.unwrap_or_else(|| cache_key(&format!("{}-{}", ws.name, profile), &rustc, &ws.lockfile));
+    let entries = PROFILE_DIRS.iter().map(|dir| Path::new(profile).join(dir)).collect();
+    Ok((format!("{}.tar.gz", key), ws.target_dir, entries))
+}
+pub fn handle_push(profile: &str, sccache: bool, key: Option<String>, backend: Option<String>) -> Result<()> {
+    let backend = resolve_backend(backend)?;
+    let (object, root, entries) = plan(profile, sccache, key)?;
+    if !entries.iter().any(|entry| root.join(entry).is_dir()) {
+        bail!("Nothing to push in {} - build first", root.display());
+    }
+    let started = Instant::now();
+    let archive = std::env::temp_dir().join(&object);
+    println!("📦 Packing {}...", root.display().to_string().cyan());
+    pack(&root, &entries, &archive)?;
+    let size = fs::metadata(&archive)?.len();
+    println!("⬆️  Uploading {} to {}", format_size(size), backend.location(&object).cyan());
+    let result = backend.upload(&archive, &object);
+    let _ = fs::remove_file(&archive);
+    result?;
+    println!("✅ Cache pushed in {:.1}s", started.elapsed().as_secs_f64());
+    Ok(())
+}
+pub fn handle_pull(profile: &str, sccache: bool, key: Option<String>, backend: Option<String>) -> Result<()> {
+    let backend = resolve_backend(backend)?;
+    let (object, root, _) = plan(profile, sccache, key)?;
+    let started = Instant::now();
+    let archive = std::env::temp_dir().join(&object);
+    println!("⬇️  Fetching {}", backend.location(&object).cyan());
+    if !backend.download(&object, &archive)? {
+        println!("{} No cache for this toolchain and Cargo.lock yet - run 'cm cache push' after a build", "🌱".yellow());
+        return Ok(());
+    }
+    let size = fs::metadata(&archive)?.len();
+    println!("📦 Unpacking {} into {}", format_size(size), root.display().to_string().cyan());
+    let result = unpack(&archive, &root);
+    let _ = fs::remove_file(&archive);
+    result?;
+    println!("✅ Cache warmed in {:.1}s", started.elapsed().as_secs_f64());
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn keys_backends_and_archive_round_trip() {
+        let key = cache_key("app-debug", "rustc 1.80.0\nhost: x86_64", "lock v1");
+        assert!(key.starts_with("app-debug-") && key.len() == "app-debug-".len() + 16);
+        assert_eq!(key, cache_key("app-debug", "rustc 1.80.0\nhost: x86_64\n", "lock v1"));
+        assert_ne!(key, cache_key("app-debug", "rustc 1.81.0\nhost: x86_64", "lock v1"));
+        assert_ne!(key, cache_key("app-debug", "rustc 1.80.0\nhost: x86_64", "lock v2"));
+        assert_eq!(Backend::parse("s3://bucket/ci/").unwrap().location("k.tar.gz"), "s3://bucket/ci/k.tar.gz");
+        assert!(matches!(Backend::parse("gs://b").unwrap(), Backend::Gcs(_)));
+        assert!(Backend::parse("ftp://nope").is_err());
+        let temp = tempfile::tempdir().unwrap();
+        let target = temp.path().join("target");
+        fs::create_dir_all(target.join("debug/deps")).unwrap();
+        fs::create_dir_all(target.join("debug/incremental")).unwrap();
+        fs::write(target.join("debug/deps/libfoo.rlib"), b"rlib").unwrap();
+        fs::write(target.join("debug/incremental/state"), b"big").unwrap();
+        let backend = Backend::parse(&format!("file://{}", temp.path().join("store").display())).unwrap();
+        let entries: Vec<PathBuf> = PROFILE_DIRS.iter().map(|d| Path::new("debug").join(d)).collect();
+        let archive = temp.path().join("k.tar.gz");
+        assert_eq!(pack(&target, &entries, &archive).unwrap(), 1);
+        backend.upload(&archive, "k.tar.gz").unwrap();
+        let fetched = temp.path().join("fetched.tar.gz");
+        assert!(!backend.download("missing.tar.gz", &fetched).unwrap());
+        assert!(backend.download("k.tar.gz", &fetched).unwrap());
+        let checkout = temp.path().join("checkout");
+        unpack(&fetched, &checkout).unwrap();
+        assert_eq!(fs::read(checkout.join("debug/deps/libfoo.rlib")).unwrap(), b"rlib");
+        assert!(!checkout.join("debug/incremental").exists());
+    }
+}
@@ -132,7 +132,9 @@ pub fn handle_captain_command(command: CaptainCommand) -> Result<()> {
         let captain_path = crate::captain::captain_status::get_captain_path();
         if let Some(captain_binary) = captain_path {
             let args = command_to_args(&command);
-            match std::process::Command::new(&captain_binary).args(&args).status() {
+            let mut captain = std::process::Command::new(&captain_binary);
+            crate::net::NetworkSettings::load().apply_env(&mut captain);
+            match captain.args(&args).status() {
                 Ok(status) if status.success() => {
                     return Ok(());
                 }
@@ -189,6 +191,12 @@ fn command_to_args(command: &CaptainCommand) -> Vec<String> {
                         args.push("--local".to_string());
                     }
                 }
+                crate::captain::config::ConfigAction::Unset { key, local } => {
+                    args.extend(vec!["unset".to_string(), key.clone()]);
+                    if *local {
+                        args.push("--local".to_string());
+                    }
+                }
                 crate::captain::config::ConfigAction::List => {
                     args.push("list".to_string());
                 }
@@ -200,9 +208,8 @@ fn command_to_args(command: &CaptainCommand) -> Vec<String> {
                     command,
                     local,
                 } => {
-                    args.extend(
-                        vec!["shortcut".to_string(), name.clone(), command.clone()],
-                    );
+                    args.extend(vec!["shortcut".to_string(), name.clone()]);
+                    args.extend(command.clone());
                     if *local {
                         args.push("--local".to_string());
                     }
@@ -219,6 +226,34 @@ fn command_to_args(command: &CaptainCommand) -> Vec<String> {
                         args.push("--local".to_string());
                     }
                 }
+                crate::captain::config::ConfigAction::Hooks { action } => {
+                    args.push("hooks".to_string());
+                    match action {
+                        crate::captain::config::HooksAction::List => {
+                            args.push("list".to_string())
+                        }
+                    }
+                }
+                crate::captain::config::ConfigAction::Sync {
+                    from,
+                    git_ref,
+                    dry_run,
+                    yes,
+                } => {
+                    args.push("sync".to_string());
+                    if let Some(from) = from {
+                        args.extend(vec!["--from".to_string(), from.clone()]);
+                    }
+                    if let Some(git_ref) = git_ref {
+                        args.extend(vec!["--ref".to_string(), git_ref.clone()]);
+                    }
+                    if *dry_run {
+                        args.push("--dry-run".to_string());
+                    }
+                    if *yes {
+                        args.push("--yes".to_string());
+                    }
+                }
             }
             args
         }
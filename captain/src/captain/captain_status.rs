@@ -28,24 +28,11 @@ fn load_captain_status() -> Option<CaptainStatus> {
         Ok(path) => path,
         Err(_) => return None,
     };
-    if !status_file.exists() {
-        return None;
-    }
-    match fs::read_to_string(&status_file) {
-        Ok(content) => {
-            match serde_json::from_str::<CaptainStatus>(&content) {
-                Ok(status) => Some(status),
-                Err(_) => None,
-            }
-        }
-        Err(_) => None,
-    }
+    crate::storage::read_json_file(&status_file).ok().flatten()
 }
 fn save_captain_status(status: &CaptainStatus) -> Result<()> {
     let status_file = get_status_file_path()?;
-    let content = serde_json::to_string_pretty(status)?;
-    fs::write(&status_file, content)?;
-    Ok(())
+    crate::storage::write_json_atomic(&status_file, status)
 }
 fn verify_captain_binary(path: &str) -> Result<(), String> {
     match fs::metadata(path) {
@@ -1,67 +1,406 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Subcommand;
+use colored::*;
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
 #[derive(Subcommand, Debug)]
 pub enum ConfigAction {
     Set { key: String, value: String, #[arg(long)] local: bool },
     Get { key: String },
+    Unset { key: String, #[arg(long)] local: bool },
     List,
     Init,
-    Shortcut { name: String, command: String, #[arg(long)] local: bool },
+    Shortcut { name: String, command: Option<String>, #[arg(long)] local: bool },
     Hook { hook_type: String, command: String, #[arg(long)] local: bool },
+    Hooks { #[command(subcommand)] action: HooksAction },
+    /// Pull the team's .cg and shared journeys; this project's .cg still wins
+    Sync {
+        #[arg(long)]
+        from: Option<String>,
+        #[arg(long = "ref")]
+        git_ref: Option<String>,
+        #[arg(long)]
+        dry_run: bool,
+        /// Accept hook changes without asking
+        #[arg(long)]
+        yes: bool,
+    },
+}
+#[derive(Subcommand, Debug)]
+pub enum HooksAction {
+    List,
+}
+const ENV_PREFIX: &str = "CARGO_MATE_";
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    Global,
+    Team,
+    Local,
+    Env,
+}
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Global => write!(f, "global"),
+            ConfigSource::Team => write!(f, "team"),
+            ConfigSource::Local => write!(f, "local"),
+            ConfigSource::Env => write!(f, "env"),
+        }
+    }
+}
+#[derive(Debug, Clone)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: String,
+    pub source: ConfigSource,
+}
+pub struct ConfigManager {
+    global_path: PathBuf,
+    local_path: PathBuf,
+    global: Table,
+    /// Synced by `cm config sync`; sits between the global config and `.cg`
+    team: Table,
+    local: Table,
 }
-pub struct ConfigManager;
 impl ConfigManager {
     pub fn new() -> Result<Self> {
-        Ok(ConfigManager)
+        let global_path = dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".shipwreck")
+            .join("config.toml");
+        let local_path = PathBuf::from(".cg");
+        let team = crate::project::current_project()
+            .map(|bucket| Self::read_table(&crate::team_sync::team_dir(&bucket.dir).join(crate::team_sync::TEAM_CONFIG)))
+            .unwrap_or_default();
+        Ok(ConfigManager {
+            global: Self::read_table(&global_path),
+            team,
+            local: Self::read_table(&local_path),
+            global_path,
+            local_path,
+        })
     }
-    pub fn load(&self) -> Result<HashMap<String, String>> {
-        Ok(HashMap::new())
+    #[cfg(test)]
+    pub(crate) fn from_layers(global: &str, team: &str, local: &str) -> Self {
+        ConfigManager {
+            global_path: PathBuf::new(),
+            local_path: PathBuf::new(),
+            global: global.parse().unwrap(),
+            team: team.parse().unwrap(),
+            local: local.parse().unwrap(),
+        }
     }
-    pub fn save(&self, _config: HashMap<String, String>) -> Result<()> {
+    fn read_table(path: &PathBuf) -> Table {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Table::new();
+        };
+        content
+            .parse::<Table>()
+            .unwrap_or_else(|e| {
+                eprintln!("⚠️  Ignoring invalid config {}: {}", path.display(), e);
+                Table::new()
+            })
+    }
+    pub(crate) fn flatten(prefix: &str, table: &Table, out: &mut HashMap<String, String>) {
+        for (key, value) in table {
+            let full_key = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            match value {
+                Value::Table(inner) => Self::flatten(&full_key, inner, out),
+                Value::String(s) => {
+                    out.insert(full_key, s.clone());
+                }
+                other => {
+                    out.insert(full_key, other.to_string());
+                }
+            }
+        }
+    }
+    fn parse_value(value: &str) -> Value {
+        if let Ok(b) = value.parse::<bool>() {
+            Value::Boolean(b)
+        } else if let Ok(i) = value.parse::<i64>() {
+            Value::Integer(i)
+        } else if let Ok(f) = value.parse::<f64>() {
+            Value::Float(f)
+        } else {
+            Value::String(value.to_string())
+        }
+    }
+    fn insert_dotted(table: &mut Table, key: &str, value: Value) -> Result<()> {
+        let mut parts: Vec<&str> = key.split('.').collect();
+        let last = parts.pop().filter(|k| !k.is_empty()).context("Empty config key")?;
+        let mut current = table;
+        for part in parts {
+            current = current
+                .entry(part.to_string())
+                .or_insert_with(|| Value::Table(Table::new()))
+                .as_table_mut()
+                .with_context(|| format!("Config key '{}' is not a section", part))?;
+        }
+        current.insert(last.to_string(), value);
         Ok(())
     }
+    fn write_table(path: &Path, table: &Table) -> Result<()> {
+        crate::storage::write_atomic(path, toml::to_string_pretty(table)?)
+    }
+    pub fn load(&self) -> Result<HashMap<String, String>> {
+        let mut config = HashMap::new();
+        Self::flatten("", &self.global, &mut config);
+        Self::flatten("", &self.team, &mut config);
+        Self::flatten("", &self.local, &mut config);
+        Ok(config)
+    }
+    pub fn save(&self, config: HashMap<String, String>) -> Result<()> {
+        let mut table = Table::new();
+        for (key, value) in config {
+            Self::insert_dotted(&mut table, &key, Self::parse_value(&value))?;
+        }
+        Self::write_table(&self.global_path, &table)
+    }
+    fn env_overrides() -> HashMap<String, String> {
+        std::env::vars()
+            .filter_map(|(name, value)| {
+                let key = name.strip_prefix(ENV_PREFIX)?;
+                Some((key.to_lowercase().replace("__", "."), value))
+            })
+            .collect()
+    }
     pub fn merge_with_env(&self) -> Result<HashMap<String, String>> {
-        Ok(HashMap::new())
+        let mut config = self.load()?;
+        config.extend(Self::env_overrides());
+        Ok(config)
     }
-    pub fn get(&self, _key: &str) -> Option<String> {
-        None
+    pub fn entries(&self) -> Vec<ConfigEntry> {
+        let mut merged: HashMap<String, (String, ConfigSource)> = HashMap::new();
+        let mut global = HashMap::new();
+        Self::flatten("", &self.global, &mut global);
+        let mut team = HashMap::new();
+        Self::flatten("", &self.team, &mut team);
+        let mut local = HashMap::new();
+        Self::flatten("", &self.local, &mut local);
+        for (layer, source) in [
+            (global, ConfigSource::Global),
+            (team, ConfigSource::Team),
+            (local, ConfigSource::Local),
+            (Self::env_overrides(), ConfigSource::Env),
+        ] {
+            for (key, value) in layer {
+                merged.insert(key, (value, source));
+            }
+        }
+        let mut entries: Vec<ConfigEntry> = merged
+            .into_iter()
+            .map(|(key, (value, source))| ConfigEntry { key, value, source })
+            .collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        entries
+    }
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.merge_with_env().ok()?.remove(key)
+    }
+    /// `get` with the environment lookup injected, e.g. `network.timeout` checks `CARGO_MATE_NETWORK__TIMEOUT`
+    pub fn get_with(&self, key: &str, env: impl Fn(&str) -> Option<String>) -> Option<String> {
+        env(&format!("{}{}", ENV_PREFIX, key.to_uppercase().replace('.', "__")))
+            .or_else(|| self.load().ok()?.remove(key))
+    }
+    pub fn get_bool(&self, key: &str, default: bool) -> bool {
+        self.get(key)
+            .and_then(|value| match value.to_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => Some(true),
+                "false" | "0" | "no" | "off" => Some(false),
+                _ => None,
+            })
+            .unwrap_or(default)
+    }
+    pub fn get_list(&self, key: &str) -> Vec<String> {
+        self.get(key).map(|value| Self::parse_list(&value)).unwrap_or_default()
+    }
+    /// A TOML array (`["a", "b"]`) or a comma-separated string
+    pub fn parse_list(value: &str) -> Vec<String> {
+        if value.trim_start().starts_with('[') {
+            if let Ok(table) = format!("list = {}", value).parse::<Table>() {
+                if let Some(items) = table.get("list").and_then(|v| v.as_array()) {
+                    return items
+                        .iter()
+                        .filter_map(|item| item.as_str().map(String::from))
+                        .collect();
+                }
+            }
+        }
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect()
     }
     pub fn init_local(&self) -> Result<()> {
-        Ok(())
+        if self.local_path.exists() {
+            return Ok(());
+        }
+        let name = std::env::current_dir()?
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "my-project".to_string());
+        let template = format!(
+            "[project]\nname = \"{}\"\nauto_checklist = true\n\n[version]\nauto_increment = true\nincrement_policy = \"patch\"\n\n[publish]\nallow_dirty = false\nrequire_tests = true\nrequire_version_bump = true\nrequire_changelog = true\nconfirm = true\n\n[shortcuts]\n",
+            name
+        );
+        crate::storage::write_atomic(&self.local_path, template)
     }
-    pub fn set(&mut self, _key: &str, _value: &str, _local: bool) -> Result<()> {
-        Ok(())
+    pub fn set(&mut self, key: &str, value: &str, local: bool) -> Result<()> {
+        let (table, path) = if local {
+            (&mut self.local, &self.local_path)
+        } else {
+            (&mut self.global, &self.global_path)
+        };
+        Self::insert_dotted(table, key, Self::parse_value(value))?;
+        Self::write_table(path, table)
+    }
+    /// Like `set`, but never coerces the value to a bool or number
+    pub fn set_string(&mut self, key: &str, value: &str, local: bool) -> Result<()> {
+        let (table, path) = if local {
+            (&mut self.local, &self.local_path)
+        } else {
+            (&mut self.global, &self.global_path)
+        };
+        Self::insert_dotted(table, key, Value::String(value.to_string()))?;
+        Self::write_table(path, table)
+    }
+    /// Returns false when the key was not set in that layer
+    pub fn unset(&mut self, key: &str, local: bool) -> Result<bool> {
+        let (table, path) = if local {
+            (&mut self.local, &self.local_path)
+        } else {
+            (&mut self.global, &self.global_path)
+        };
+        let mut parts: Vec<&str> = key.split('.').collect();
+        let last = parts.pop().context("Empty config key")?;
+        let mut current = &mut *table;
+        for part in parts {
+            match current.get_mut(part).and_then(Value::as_table_mut) {
+                Some(inner) => current = inner,
+                None => return Ok(false),
+            }
+        }
+        if current.remove(last).is_none() {
+            return Ok(false);
+        }
+        Self::write_table(path, table)?;
+        Ok(true)
     }
     pub fn show(&self) -> Result<()> {
+        let entries = self.entries();
+        if entries.is_empty() {
+            println!("No configuration set. Use 'cm config set <key> <value>'");
+            return Ok(());
+        }
+        for entry in entries {
+            println!(
+                "{} = {} {}", entry.key.cyan(), entry.value, format!("({})", entry.source)
+                .dimmed()
+            );
+        }
         Ok(())
     }
     pub fn add_shortcut(
         &mut self,
-        _name: &str,
-        _command: &str,
-        _local: bool,
+        name: &str,
+        command: &str,
+        local: bool,
     ) -> Result<()> {
-        Ok(())
+        self.set(&format!("shortcuts.{}", name), command, local)
     }
     pub fn add_hook(
         &mut self,
-        _hook_type: &str,
-        _command: &str,
-        _local: bool,
+        hook_type: &str,
+        command: &str,
+        local: bool,
     ) -> Result<()> {
-        Ok(())
+        self.set(&format!("hooks.{}", hook_type), command, local)
     }
 }
 pub fn load_captain_config() -> Result<HashMap<String, String>> {
-    Ok(HashMap::new())
+    ConfigManager::new()?.merge_with_env()
+}
+pub fn save_captain_config(config: HashMap<String, String>) -> Result<()> {
+    ConfigManager::new()?.save(config)
 }
-pub fn save_captain_config(_config: HashMap<String, String>) -> Result<()> {
-    Ok(())
+fn report_set(key: &str, value: &str, local: bool) {
+    println!("✅ Set {} = {} ({})", key.cyan(), value, if local { "local" } else { "global" });
+}
+pub fn handle_config_action(action: ConfigAction) -> Result<()> {
+    let mut config = ConfigManager::new()?;
+    match action {
+        ConfigAction::Set { key, value, local } => {
+            config.set(&key, &value, local)?;
+            report_set(&key, &value, local);
+            Ok(())
+        }
+        ConfigAction::Get { key } => {
+            match config.get(&key) {
+                Some(value) => println!("{}", value),
+                None => println!("Config key '{}' not found", key),
+            }
+            Ok(())
+        }
+        ConfigAction::Unset { key, local } => {
+            let layer = if local { "local" } else { "global" };
+            if config.unset(&key, local)? {
+                println!("🗑️  Unset {} ({})", key.cyan(), layer);
+            } else {
+                println!("Config key '{}' is not set in the {} config", key, layer);
+            }
+            Ok(())
+        }
+        ConfigAction::List => config.show(),
+        ConfigAction::Init => config.init_local(),
+        ConfigAction::Shortcut { name, command: None, .. } if name == "list" => {
+            crate::shortcuts::list(&config);
+            Ok(())
+        }
+        ConfigAction::Shortcut { name, command: None, .. } => crate::shortcuts::show(&config, &name),
+        ConfigAction::Shortcut { name, command: Some(command), local } => {
+            config.add_shortcut(&name, &command, local)?;
+            report_set(&format!("shortcuts.{}", name), &command, local);
+            Ok(())
+        }
+        ConfigAction::Hook { hook_type, command, local } => {
+            let hook: crate::hooks::HookType = hook_type.parse()?;
+            config.add_hook(hook.name(), &command, local)?;
+            report_set(&hook.config_key(), &command, local);
+            Ok(())
+        }
+        ConfigAction::Hooks { action: HooksAction::List } => {
+            crate::hooks::HookSet::from_config(&config).list();
+            Ok(())
+        }
+        ConfigAction::Sync { from, git_ref, dry_run, yes } => {
+            crate::team_sync::handle_sync(from, git_ref, dry_run, yes)
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_dotted_keys_round_trip() {
+        let mut table = Table::new();
+        ConfigManager::insert_dotted(&mut table, "publish.allow_dirty", ConfigManager::parse_value("false"))
+            .unwrap();
+        ConfigManager::insert_dotted(&mut table, "shortcuts.fast", ConfigManager::parse_value("build --release"))
+            .unwrap();
+        assert!(ConfigManager::insert_dotted(&mut table, "publish.allow_dirty.x", Value::Integer(1)).is_err());
+        let mut flat = HashMap::new();
+        ConfigManager::flatten("", &table, &mut flat);
+        assert_eq!(flat["publish.allow_dirty"], "false");
+        assert_eq!(flat["shortcuts.fast"], "build --release");
+        assert!(toml::to_string_pretty(&table).unwrap().contains("[publish]"));
+    }
 }
-pub fn handle_config_action(_action: ConfigAction) -> Result<()> {
-    eprintln!("⚙️ Advanced configuration management requires the captain binary.");
-    eprintln!("💡 Download captain with: cm install");
-    eprintln!("   Captain provides configuration persistence and advanced settings.");
-    Ok(())
-}
\ No newline at end of file
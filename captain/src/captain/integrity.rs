@@ -0,0 +1,306 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use blake2::Blake2b512;
+use colored::*;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+pub const MANIFEST_NAME: &str = "captain-manifest.json";
+const EMBEDDED_PUBLIC_KEY: Option<&str> = option_env!("CM_CAPTAIN_PUBKEY");
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptainManifest {
+    pub version: String,
+    pub artifacts: BTreeMap<String, ArtifactDigest>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactDigest {
+    pub archive_sha256: String,
+    pub binary_sha256: String,
+}
+impl CaptainManifest {
+    pub fn artifact(&self, platform: &str) -> Result<&ArtifactDigest> {
+        self.artifacts
+            .get(platform)
+            .with_context(|| {
+                format!("Manifest for captain {} has no entry for {}", self.version, platform)
+            })
+    }
+}
+#[derive(Debug, Clone)]
+pub struct VerifiedManifest {
+    pub manifest: CaptainManifest,
+    pub raw: String,
+    pub signature: String,
+    pub trusted_comment: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallRecord {
+    pub path: String,
+    pub platform: String,
+    pub version: String,
+    pub binary_sha256: String,
+    pub manifest: String,
+    pub signature: String,
+    pub verified_at: String,
+}
+pub struct MinisignPublicKey {
+    key_id: [u8; 8],
+    key: VerifyingKey,
+}
+impl MinisignPublicKey {
+    pub fn parse(text: &str) -> Result<Self> {
+        let line = text
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with("untrusted comment:"))
+            .context("Empty minisign public key")?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(line)
+            .context("Minisign public key is not valid base64")?;
+        if bytes.len() != 42 || &bytes[..2] != b"Ed" {
+            anyhow::bail!("Unsupported minisign public key format");
+        }
+        let mut key_id = [0u8; 8];
+        key_id.copy_from_slice(&bytes[2..10]);
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&bytes[10..]);
+        Ok(Self {
+            key_id,
+            key: VerifyingKey::from_bytes(&key)
+                .context("Minisign public key is not a valid Ed25519 key")?,
+        })
+    }
+    pub fn verify(&self, data: &[u8], signature: &str) -> Result<String> {
+        let lines: Vec<&str> = signature
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        if lines.len() < 4 {
+            anyhow::bail!("Truncated minisign signature");
+        }
+        let engine = base64::engine::general_purpose::STANDARD;
+        let sig = engine.decode(lines[1]).context("Signature is not valid base64")?;
+        if sig.len() != 74 {
+            anyhow::bail!("Unsupported minisign signature format");
+        }
+        if sig[2..10] != self.key_id {
+            anyhow::bail!("Signature was made with a different key");
+        }
+        let signature = Signature::from_slice(&sig[10..])?;
+        let result = match &sig[..2] {
+            b"Ed" => self.key.verify(data, &signature),
+            b"ED" => self.key.verify(&Blake2b512::digest(data), &signature),
+            _ => anyhow::bail!("Unsupported minisign signature algorithm"),
+        };
+        result.map_err(|_| anyhow::anyhow!("Signature verification failed"))?;
+        let trusted_comment = lines[2]
+            .strip_prefix("trusted comment: ")
+            .context("Missing minisign trusted comment")?;
+        let global = Signature::from_slice(
+            &engine.decode(lines[3]).context("Global signature is not valid base64")?,
+        )?;
+        let mut signed = sig[10..].to_vec();
+        signed.extend_from_slice(trusted_comment.as_bytes());
+        self.key
+            .verify(&signed, &global)
+            .map_err(|_| anyhow::anyhow!("Trusted comment signature verification failed"))?;
+        Ok(trusted_comment.to_string())
+    }
+}
+/// The key embedded at build time. Only debug and test builds also accept `CM_CAPTAIN_PUBKEY`
+/// at runtime; in a release build whoever controls the environment could swap in their own key
+pub fn public_key() -> Result<MinisignPublicKey> {
+    let runtime = if cfg!(any(debug_assertions, test)) { std::env::var("CM_CAPTAIN_PUBKEY").ok() } else { None };
+    let text = runtime
+        .or_else(|| EMBEDDED_PUBLIC_KEY.map(str::to_string))
+        .context("No captain signing key configured (build with CM_CAPTAIN_PUBKEY set)")?;
+    MinisignPublicKey::parse(&text)
+}
+/// Reads one file out of a `.tar.gz` held in memory, so what was hashed is what gets installed
+pub fn file_from_archive(archive: &[u8], path: &Path) -> Result<Vec<u8>> {
+    let mut entries = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    for entry in entries.entries()? {
+        let mut entry = entry?;
+        if entry.path()? == path {
+            let mut data = Vec::new();
+            std::io::Read::read_to_end(&mut entry, &mut data)?;
+            return Ok(data);
+        }
+    }
+    anyhow::bail!("{} not found in archive", path.display())
+}
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+pub fn check_sha256(data: &[u8], expected: &str, what: &str) -> Result<()> {
+    let actual = sha256_hex(data);
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        anyhow::bail!(
+            "Checksum mismatch for {}: expected {}, got {}", what, expected, actual
+        );
+    }
+    Ok(())
+}
+pub fn verify_manifest(
+    key: &MinisignPublicKey,
+    raw: String,
+    signature: String,
+) -> Result<VerifiedManifest> {
+    let trusted_comment = key.verify(raw.as_bytes(), &signature)?;
+    let manifest: CaptainManifest = serde_json::from_str(&raw)
+        .context("Captain manifest is not valid JSON")?;
+    Ok(VerifiedManifest {
+        manifest,
+        raw,
+        signature,
+        trusted_comment,
+    })
+}
+pub async fn fetch_verified_manifest(
+    client: &reqwest::Client,
+    base_url: &str,
+) -> Result<VerifiedManifest> {
+    fetch_signed_manifest(client, base_url, MANIFEST_NAME).await
+}
+/// Downloads `<base_url>/<name>` and its `.minisig`, checked against the release key
+pub async fn fetch_signed_manifest(
+    client: &reqwest::Client,
+    base_url: &str,
+    manifest_name: &str,
+) -> Result<VerifiedManifest> {
+    let key = public_key()?;
+    let mut texts = Vec::new();
+    for name in [manifest_name.to_string(), format!("{}.minisig", manifest_name)] {
+        let url = format!("{}/{}", base_url, name);
+        let response = client.get(&url).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to download {}: {}", url, response.status());
+        }
+        texts.push(response.text().await?);
+    }
+    let signature = texts.pop().unwrap_or_default();
+    let raw = texts.pop().unwrap_or_default();
+    verify_manifest(&key, raw, signature)
+}
+fn record_path() -> Result<PathBuf> {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    let shipwreck_dir = PathBuf::from(&home).join(".shipwreck");
+    fs::create_dir_all(&shipwreck_dir)?;
+    Ok(shipwreck_dir.join("captain_integrity.json"))
+}
+pub fn save_install_record(
+    path: &Path,
+    platform: &str,
+    verified: &VerifiedManifest,
+) -> Result<InstallRecord> {
+    let record = InstallRecord {
+        path: path.to_string_lossy().to_string(),
+        platform: platform.to_string(),
+        version: verified.manifest.version.clone(),
+        binary_sha256: verified.manifest.artifact(platform)?.binary_sha256.clone(),
+        manifest: verified.raw.clone(),
+        signature: verified.signature.clone(),
+        verified_at: chrono::Utc::now().to_rfc3339(),
+    };
+    crate::storage::write_json_atomic(&record_path()?, &record)?;
+    Ok(record)
+}
+pub fn load_install_record() -> Result<Option<InstallRecord>> {
+    let path = record_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&fs::read_to_string(&path)?)?))
+}
+pub fn verify_installed() -> Result<InstallRecord> {
+    let mut record = load_install_record()?
+        .context("No captain install record found; reinstall captain to create one")?;
+    let verified = verify_manifest(
+        &public_key()?,
+        record.manifest.clone(),
+        record.signature.clone(),
+    )?;
+    let expected = &verified.manifest.artifact(&record.platform)?.binary_sha256;
+    if *expected != record.binary_sha256 {
+        anyhow::bail!("Install record does not match the signed manifest");
+    }
+    let data = fs::read(&record.path)
+        .with_context(|| format!("Cannot read captain binary at {}", record.path))?;
+    check_sha256(&data, expected, &record.path)?;
+    record.verified_at = chrono::Utc::now().to_rfc3339();
+    crate::storage::write_json_atomic(&record_path()?, &record)?;
+    Ok(record)
+}
+pub fn clear_install_record() -> Result<()> {
+    let path = record_path()?;
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+pub fn verify_command() -> Result<()> {
+    match verify_installed() {
+        Ok(record) => {
+            super::captain_status::mark_captain_installed(&record.path)?;
+            println!("✅ Captain {} verified", record.version.green().bold());
+            println!("   Path: {}", record.path);
+            println!("   SHA-256: {}", record.binary_sha256.dimmed());
+            Ok(())
+        }
+        Err(e) => {
+            if let Some(path) = super::captain_status::get_captain_path() {
+                super::captain_status::mark_captain_corrupted(&path, &e.to_string())?;
+            }
+            Err(e.context("Captain integrity check failed"))
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    fn sign(key: &SigningKey, key_id: [u8; 8], data: &[u8], comment: &str) -> String {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let sig = key.sign(&Blake2b512::digest(data)).to_bytes();
+        let mut blob = b"ED".to_vec();
+        blob.extend_from_slice(&key_id);
+        blob.extend_from_slice(&sig);
+        let mut global = sig.to_vec();
+        global.extend_from_slice(comment.as_bytes());
+        format!(
+            "untrusted comment: test\n{}\ntrusted comment: {}\n{}\n", engine.encode(blob),
+            comment, engine.encode(key.sign(&global).to_bytes())
+        )
+    }
+    #[test]
+    fn test_minisign_manifest_verification() {
+        let signing = SigningKey::from_bytes(&[7u8; 32]);
+        let key_id = [1, 2, 3, 4, 5, 6, 7, 8];
+        let mut public = b"Ed".to_vec();
+        public.extend_from_slice(&key_id);
+        public.extend_from_slice(signing.verifying_key().as_bytes());
+        let key = MinisignPublicKey::parse(
+                &base64::engine::general_purpose::STANDARD.encode(public),
+            )
+            .unwrap();
+        let raw = format!(
+            r#"{{"version":"1.2.0","artifacts":{{"x86_64-unknown-linux-gnu":{{"archive_sha256":"{}","binary_sha256":"{}"}}}}}}"#,
+            sha256_hex(b"archive"), sha256_hex(b"binary")
+        );
+        let signature = sign(&signing, key_id, raw.as_bytes(), "captain 1.2.0");
+        let verified = verify_manifest(&key, raw.clone(), signature.clone()).unwrap();
+        assert_eq!(verified.trusted_comment, "captain 1.2.0");
+        let digest = verified.manifest.artifact("x86_64-unknown-linux-gnu").unwrap();
+        assert!(check_sha256(b"binary", &digest.binary_sha256, "captain").is_ok());
+        assert!(check_sha256(b"tampered", &digest.binary_sha256, "captain").is_err());
+        assert!(verified.manifest.artifact("aarch64-apple-darwin").is_err());
+        let tampered = raw.replace("1.2.0", "6.6.6");
+        assert!(verify_manifest(&key, tampered, signature.clone()).is_err());
+        let forged_comment = signature.replace("captain 1.2.0", "captain 9.9.9");
+        assert!(verify_manifest(&key, raw, forged_comment).is_err());
+    }
+}
@@ -18,7 +18,10 @@ impl LicenseManager {
         eprintln!("🔐 LicenseManager needed to be a captain");
         Ok(LicenseManager)
     }
-    pub fn enforce_license(&self, _command: &str) -> Result<()> {
+    pub fn enforce_license(&self, command: &str) -> Result<()> {
+        if super::license_cache::enforce(command)? {
+            return Ok(());
+        }
         eprintln!("🔐 License enforcement requires captain to be sober.");
         Ok(())
     }
@@ -0,0 +1,228 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+const GRANT_FILE: &str = "license-grant.json";
+const USAGE_FILE: &str = "license-usage.json";
+const MAX_GRANT_AGE_DAYS: i64 = 7;
+const SYNC_BATCH: u32 = 25;
+const SYNC_INTERVAL_MINUTES: i64 = 60;
+const RETRY_MINUTES: i64 = 5;
+const REFRESH_BEFORE_HOURS: i64 = 24;
+static GRANT: OnceLock<Option<Grant>> = OnceLock::new();
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Grant {
+    pub license_key_sha256: String,
+    pub tier: String,
+    pub daily_limit: Option<u32>,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SignedGrant {
+    grant: String,
+    signature: String,
+}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Usage {
+    pub day: Option<NaiveDate>,
+    pub today: u32,
+    pub pending: BTreeMap<String, u32>,
+    pub last_sync: Option<DateTime<Utc>>,
+    pub last_attempt: Option<DateTime<Utc>>,
+}
+#[derive(Serialize)]
+struct SyncRequest<'a> {
+    license_key: &'a str,
+    usage: &'a BTreeMap<String, u32>,
+}
+impl Grant {
+    pub fn fresh_until(&self) -> DateTime<Utc> {
+        self.expires_at.min(self.issued_at + Duration::days(MAX_GRANT_AGE_DAYS))
+    }
+    pub fn is_valid_for(&self, license_key: &str, now: DateTime<Utc>) -> bool {
+        self.license_key_sha256 == super::integrity::sha256_hex(license_key.as_bytes())
+            && self.issued_at <= now + Duration::minutes(5) && now < self.fresh_until()
+    }
+}
+impl Usage {
+    /// The daily count stays local; the per-command counters are telemetry and are dropped when it's off
+    pub fn record(&mut self, command: &str, now: DateTime<Utc>, telemetry: bool) {
+        let today = now.date_naive();
+        if self.day != Some(today) {
+            self.day = Some(today);
+            self.today = 0;
+        }
+        self.today += 1;
+        if telemetry {
+            *self.pending.entry(command.to_string()).or_insert(0) += 1;
+        } else {
+            self.pending.clear();
+        }
+    }
+    pub fn used_today(&self, now: DateTime<Utc>) -> u32 {
+        if self.day == Some(now.date_naive()) { self.today } else { 0 }
+    }
+    pub fn pending_total(&self) -> u32 {
+        self.pending.values().sum()
+    }
+    pub fn needs_sync(&self, grant: &Grant, now: DateTime<Utc>) -> bool {
+        let recently_tried = self
+            .last_attempt
+            .is_some_and(|t| now - t < Duration::minutes(RETRY_MINUTES));
+        if recently_tried {
+            return false;
+        }
+        let stale = self
+            .last_sync
+            .is_none_or(|t| now - t >= Duration::minutes(SYNC_INTERVAL_MINUTES));
+        self.pending_total() >= SYNC_BATCH || (stale && self.pending_total() > 0)
+            || grant.fresh_until() - now < Duration::hours(REFRESH_BEFORE_HOURS)
+    }
+    pub fn acknowledge(&mut self, sent: &BTreeMap<String, u32>) {
+        for (command, count) in sent {
+            if let Some(pending) = self.pending.get_mut(command) {
+                *pending = pending.saturating_sub(*count);
+            }
+        }
+        self.pending.retain(|_, count| *count > 0);
+    }
+}
+fn shipwreck_dir() -> Result<PathBuf> {
+    Ok(dirs::home_dir().context("Could not find home directory")?.join(".shipwreck"))
+}
+fn license_key() -> Option<String> {
+    std::env::var("CARGO_MATE_LICENSE")
+        .ok()
+        .or_else(crate::user::license_key)
+        .filter(|key| !key.trim().is_empty())
+}
+fn verify(signed: &SignedGrant) -> Result<Grant> {
+    super::integrity::public_key()?
+        .verify(signed.grant.as_bytes(), &signed.signature)
+        .context("License grant signature is invalid")?;
+    Ok(serde_json::from_str(&signed.grant)?)
+}
+fn load_grant() -> Option<Grant> {
+    let content = fs::read_to_string(shipwreck_dir().ok()?.join(GRANT_FILE)).ok()?;
+    let grant = verify(&serde_json::from_str(&content).ok()?).ok()?;
+    grant.is_valid_for(&license_key()?, Utc::now()).then_some(grant)
+}
+pub fn cached_grant() -> Option<&'static Grant> {
+    GRANT.get_or_init(load_grant).as_ref()
+}
+fn load_usage() -> Usage {
+    shipwreck_dir()
+        .ok()
+        .and_then(|dir| crate::storage::read_json_file(&dir.join(USAGE_FILE)).ok().flatten())
+        .unwrap_or_default()
+}
+fn save_usage(usage: &Usage) -> Result<()> {
+    crate::storage::write_json_atomic(&shipwreck_dir()?.join(USAGE_FILE), usage)
+}
+pub fn enforce(command: &str) -> Result<bool> {
+    let Some(grant) = cached_grant() else {
+        return Ok(false);
+    };
+    let now = Utc::now();
+    let path = shipwreck_dir()?.join(USAGE_FILE);
+    let lock = crate::storage::lock(&path)?;
+    let mut usage = load_usage();
+    if let Some(limit) = grant.daily_limit {
+        if usage.used_today(now) >= limit {
+            bail!(
+                "Daily limit of {} commands reached on the {} tier - upgrade at https://cargo.do/pro",
+                limit, grant.tier
+            );
+        }
+    }
+    usage.record(command, now, crate::privacy::telemetry_enabled());
+    let sync_due = usage.needs_sync(grant, now) && !crate::net::is_offline();
+    if sync_due {
+        usage.last_attempt = Some(now);
+    }
+    save_usage(&usage)?;
+    drop(lock);
+    if sync_due {
+        std::thread::spawn(|| {
+            let _ = sync();
+        });
+    }
+    Ok(true)
+}
+pub fn sync() -> Result<Grant> {
+    let key = license_key().context("No license key registered - run `cm register <license-key>`")?;
+    let path = shipwreck_dir()?.join(USAGE_FILE);
+    let sent = crate::storage::update_json(&path, |usage: &mut Usage| {
+        usage.last_attempt = Some(Utc::now());
+        usage.pending.clone()
+    })?;
+    let request = crate::net::client(std::time::Duration::from_secs(10))?
+        .post(format!("{}/license/sync", crate::user::api_base_url()))
+        .json(&SyncRequest { license_key: &key, usage: &sent });
+    let response = crate::net::send(request).context("Could not reach the license server")?;
+    if !response.status().is_success() {
+        bail!("License server returned {}", response.status());
+    }
+    let signed: SignedGrant = response.json().context("Unexpected license server response")?;
+    let grant = verify(&signed)?;
+    if !grant.is_valid_for(&key, Utc::now()) {
+        bail!("License server issued a grant for a different or expired license");
+    }
+    crate::storage::write_json_atomic(&shipwreck_dir()?.join(GRANT_FILE), &signed)?;
+    crate::storage::update_json(&path, |usage: &mut Usage| {
+        usage.acknowledge(&sent);
+        usage.last_sync = Some(Utc::now());
+    })?;
+    Ok(grant)
+}
+pub fn handle_sync() -> Result<()> {
+    println!("🔄 Syncing license grant and usage...");
+    let pending = load_usage().pending_total();
+    let grant = sync()?;
+    println!("✅ License grant refreshed: {} tier", grant.tier.green());
+    println!("   Valid until: {}", grant.fresh_until().format("%Y-%m-%d %H:%M UTC").to_string().cyan());
+    match grant.daily_limit {
+        Some(limit) => println!("   Daily limit: {} commands", limit),
+        None => println!("   Usage: {}", "UNLIMITED".green()),
+    }
+    println!("   Reported {} batched command(s)", pending);
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn grant_ttl_and_usage_batching() {
+        let now = Utc::now();
+        let grant = Grant {
+            license_key_sha256: super::super::integrity::sha256_hex(b"CM-KEY"),
+            tier: "FREE".to_string(),
+            daily_limit: Some(10),
+            issued_at: now - Duration::days(1),
+            expires_at: now + Duration::days(30),
+        };
+        assert!(grant.is_valid_for("CM-KEY", now));
+        assert!(!grant.is_valid_for("CM-OTHER", now));
+        assert!(!grant.is_valid_for("CM-KEY", now + Duration::days(7)));
+        let mut usage = Usage::default();
+        usage.record("build", now - Duration::days(1), true);
+        usage.record("build", now, true);
+        usage.record("test", now, true);
+        assert_eq!(usage.used_today(now), 2);
+        assert_eq!(usage.pending_total(), 3);
+        usage.last_sync = Some(now);
+        assert!(!usage.needs_sync(&grant, now));
+        assert!(usage.needs_sync(&grant, now + Duration::hours(2)));
+        let sent = usage.pending.clone();
+        usage.record("build", now, true);
+        usage.acknowledge(&sent);
+        assert_eq!(usage.pending, BTreeMap::from([("build".to_string(), 1)]));
+        usage.last_attempt = Some(now);
+        assert!(!usage.needs_sync(&grant, now + Duration::minutes(1)));
+    }
+}
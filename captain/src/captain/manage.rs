@@ -0,0 +1,321 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use super::captain_status;
+use super::integrity::{self, InstallRecord, VerifiedManifest};
+const DOWNLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+#[derive(Subcommand, Debug)]
+pub enum CaptainAction {
+    Status,
+    Update,
+    Pin { version: Option<String>, #[arg(long)] clear: bool },
+    Uninstall,
+    Verify,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptainSettings {
+    #[serde(default)]
+    pub pinned_version: Option<String>,
+    #[serde(default = "default_auto_install")]
+    pub auto_install: bool,
+}
+fn default_auto_install() -> bool {
+    true
+}
+impl Default for CaptainSettings {
+    fn default() -> Self {
+        Self {
+            pinned_version: None,
+            auto_install: true,
+        }
+    }
+}
+fn shipwreck_dir() -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME not set")?;
+    Ok(PathBuf::from(&home).join(".shipwreck"))
+}
+fn settings_path() -> Result<PathBuf> {
+    Ok(shipwreck_dir()?.join("captain_settings.json"))
+}
+pub fn load_settings() -> CaptainSettings {
+    settings_path()
+        .ok()
+        .and_then(|path| crate::storage::read_json_file(&path).ok().flatten())
+        .unwrap_or_default()
+}
+pub fn save_settings(settings: &CaptainSettings) -> Result<()> {
+    crate::storage::write_json_atomic(&settings_path()?, settings)
+}
+pub fn auto_install_disabled_reason() -> Option<&'static str> {
+    if std::env::var("CM_NO_AUTO_INSTALL").is_ok() {
+        Some("CM_NO_AUTO_INSTALL")
+    } else if crate::net::is_offline() {
+        Some("offline mode (CM_OFFLINE or 'offline' in .cg)")
+    } else if !load_settings().auto_install {
+        Some("'cm captain uninstall' (run 'cm captain update' to reinstall)")
+    } else {
+        None
+    }
+}
+pub fn detect_platform() -> Result<String> {
+    let os = std::env::consts::OS;
+    let arch = std::env::consts::ARCH;
+    let platform = match (os, arch) {
+        ("linux", "x86_64") => {
+            if std::path::Path::new("/etc/alpine-release").exists() {
+                "x86_64-unknown-linux-musl"
+            } else {
+                "x86_64-unknown-linux-gnu"
+            }
+        }
+        ("linux", "aarch64") => "aarch64-unknown-linux-gnu",
+        ("macos", "x86_64") => "x86_64-apple-darwin",
+        ("macos", "aarch64") => "aarch64-apple-darwin",
+        ("windows", "x86_64") => "x86_64-pc-windows-gnu",
+        _ => return Err(anyhow::anyhow!("Unsupported platform: {}-{}", os, arch)),
+    };
+    Ok(platform.to_string())
+}
+pub fn release_url(settings: &CaptainSettings) -> String {
+    let base_url = std::env::var("CAPTAIN_DOWNLOAD_URL")
+        .unwrap_or_else(|_| "https://get.cargo.do/captain".to_string());
+    match &settings.pinned_version {
+        Some(version) => format!("{}/v{}", base_url, version.trim_start_matches('v')),
+        None => base_url,
+    }
+}
+fn check_pin(settings: &CaptainSettings, verified: &VerifiedManifest) -> Result<()> {
+    if let Some(pinned) = &settings.pinned_version {
+        if verified.manifest.version.trim_start_matches('v') != pinned.trim_start_matches('v')
+        {
+            anyhow::bail!(
+                "Captain is pinned to {} but the manifest is for {}", pinned,
+                verified.manifest.version
+            );
+        }
+    }
+    Ok(())
+}
+/// Whether `verified` should replace the installed captain. An older signed manifest is refused
+/// so a replayed one can't downgrade it; a pinned version is installed either way.
+fn check_upgrade(settings: &CaptainSettings, installed: &str, verified: &VerifiedManifest) -> Result<bool> {
+    let parse = |version: &str| {
+        semver::Version::parse(version.trim_start_matches('v'))
+            .with_context(|| format!("Invalid captain version '{}'", version))
+    };
+    let (installed, offered) = (parse(installed)?, parse(&verified.manifest.version)?);
+    if offered == installed {
+        return Ok(false);
+    }
+    if offered < installed && settings.pinned_version.is_none() {
+        anyhow::bail!(
+            "Refusing to downgrade captain {} to {}; run 'cm captain pin {}' to install it anyway",
+            installed, offered, offered
+        );
+    }
+    Ok(true)
+}
+async fn fetch_manifest(
+    client: &reqwest::Client,
+    settings: &CaptainSettings,
+) -> Result<VerifiedManifest> {
+    let verified = integrity::fetch_verified_manifest(client, &release_url(settings))
+        .await?;
+    check_pin(settings, &verified)?;
+    Ok(verified)
+}
+pub async fn install_captain() -> Result<InstallRecord> {
+    let settings = load_settings();
+    let client = crate::net::async_client(DOWNLOAD_TIMEOUT)?;
+    eprintln!("🔏 Verifying signed manifest...");
+    let verified = fetch_manifest(&client, &settings).await?;
+    eprintln!("   {}", verified.trusted_comment.dimmed());
+    install_from_manifest(&client, &settings, &verified).await
+}
+async fn install_from_manifest(
+    client: &reqwest::Client,
+    settings: &CaptainSettings,
+    verified: &VerifiedManifest,
+) -> Result<InstallRecord> {
+    let platform = detect_platform()?;
+    eprintln!("📦 Detected platform: {}", platform);
+    let shipwreck_bin = shipwreck_dir()?.join("bin");
+    fs::create_dir_all(&shipwreck_bin)?;
+    let digest = verified.manifest.artifact(&platform)?.clone();
+    let archive_name = format!("captain-{}.tar.gz", platform);
+    let download_url = format!("{}/{}", release_url(settings), archive_name);
+    eprintln!("📥 Downloading from: {}", download_url);
+    let response = client.get(&download_url).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Failed to download captain: {}", response.status()));
+    }
+    let bytes = response.bytes().await?;
+    integrity::check_sha256(&bytes, &digest.archive_sha256, &archive_name)?;
+    eprintln!("📂 Extracting captain...");
+    // Extracted and installed from the verified bytes; nothing is re-read from a shared temp dir
+    let captain_enc = integrity::file_from_archive(&bytes, &Path::new(&format!("captain-{}", platform)).join("captain.enc"))?;
+    integrity::check_sha256(&captain_enc, &digest.binary_sha256, "captain.enc")?;
+    let captain_dest = shipwreck_bin.join("captain");
+    crate::storage::write_atomic(&captain_dest, &captain_enc)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&captain_dest, fs::Permissions::from_mode(0o755))?;
+    }
+    eprintln!("✅ Captain installed successfully to: {}", captain_dest.display());
+    let record = integrity::save_install_record(&captain_dest, &platform, verified)?;
+    let captain_path = captain_dest
+        .to_str()
+        .with_context(|| format!("Captain install path {} is not valid UTF-8", captain_dest.display()))?;
+    captain_status::mark_captain_installed(captain_path)?;
+    if !std::env::var("PATH").unwrap_or_default().contains(".shipwreck/bin") {
+        println!();
+        println!("⚠️  Add ~/.shipwreck/bin to your PATH:");
+        println!("   export PATH=\"$HOME/.shipwreck/bin:$PATH\"");
+    }
+    Ok(record)
+}
+fn show_status() -> Result<()> {
+    let settings = load_settings();
+    println!("🧭 {}", "Captain Status".bold());
+    println!("{}", captain_status::get_captain_status_info());
+    match integrity::load_install_record()? {
+        Some(record) => {
+            println!("Installed Version: {}", record.version.cyan());
+            println!("Platform: {}", record.platform);
+            println!("Last Integrity Check: {}", record.verified_at);
+        }
+        None => println!("Installed Version: {}", "unknown (no install record)".dimmed()),
+    }
+    println!(
+        "Pinned Version: {}", settings.pinned_version.as_deref().unwrap_or("none")
+    );
+    println!(
+        "Auto-install: {}", if settings.auto_install { "enabled".green() } else {
+        "disabled".yellow() }
+    );
+    Ok(())
+}
+async fn update() -> Result<()> {
+    let mut settings = load_settings();
+    let client = crate::net::async_client(DOWNLOAD_TIMEOUT)?;
+    let verified = fetch_manifest(&client, &settings).await?;
+    let installed = integrity::load_install_record()?;
+    let binary_present = installed
+        .as_ref()
+        .is_some_and(|record| PathBuf::from(&record.path).exists());
+    if let Some(record) = installed.filter(|_| binary_present) {
+        if !check_upgrade(&settings, &record.version, &verified)? {
+            println!(
+                "✅ Captain {} is already up to date", record.version.green().bold()
+            );
+            return Ok(());
+        }
+        println!(
+            "⬆️  Updating captain {} → {}", record.version.yellow(), verified.manifest
+            .version.green()
+        );
+    } else {
+        println!("📦 Installing captain {}", verified.manifest.version.green());
+    }
+    install_from_manifest(&client, &settings, &verified).await?;
+    if !settings.auto_install {
+        settings.auto_install = true;
+        save_settings(&settings)?;
+    }
+    Ok(())
+}
+fn pin(version: Option<String>, clear: bool) -> Result<()> {
+    let mut settings = load_settings();
+    if clear {
+        settings.pinned_version = None;
+        save_settings(&settings)?;
+        println!("📌 Captain pin cleared - 'cm captain update' will follow the latest release");
+        return Ok(());
+    }
+    match version {
+        Some(version) => {
+            let version = version.trim_start_matches('v').to_string();
+            semver::Version::parse(&version)
+                .with_context(|| format!("Invalid captain version '{}'", version))?;
+            println!("📌 Captain pinned to {}", version.cyan().bold());
+            settings.pinned_version = Some(version);
+            save_settings(&settings)?;
+            println!("   Run 'cm captain update' to install the pinned release");
+        }
+        None => {
+            match settings.pinned_version {
+                Some(version) => println!("📌 Captain pinned to {}", version.cyan().bold()),
+                None => println!("📌 Captain is not pinned"),
+            }
+        }
+    }
+    Ok(())
+}
+fn uninstall() -> Result<()> {
+    let mut paths = vec![shipwreck_dir()?.join("bin").join("captain")];
+    if let Some(record) = integrity::load_install_record()? {
+        paths.push(PathBuf::from(record.path));
+    }
+    let mut removed = 0;
+    for path in paths {
+        if path.is_file() {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            println!("🗑️  Removed {}", path.display());
+            removed += 1;
+        }
+    }
+    integrity::clear_install_record()?;
+    captain_status::clear_captain_status()?;
+    let mut settings = load_settings();
+    settings.auto_install = false;
+    save_settings(&settings)?;
+    if removed == 0 {
+        println!("⚠️  No captain binary managed by cargo-mate was found");
+    }
+    println!("✅ Captain uninstalled - auto-install is disabled until 'cm captain update'");
+    Ok(())
+}
+pub async fn handle_captain_command(action: CaptainAction) -> Result<()> {
+    match action {
+        CaptainAction::Status => show_status()?,
+        CaptainAction::Update => update().await?,
+        CaptainAction::Pin { version, clear } => pin(version, clear)?,
+        CaptainAction::Uninstall => uninstall()?,
+        CaptainAction::Verify => integrity::verify_command()?,
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use integrity::CaptainManifest;
+    #[test]
+    fn test_pin_rejects_other_versions() {
+        let settings: CaptainSettings = serde_json::from_str(r#"{"pinned_version":"1.4.0"}"#)
+            .unwrap();
+        assert!(settings.auto_install);
+        let verified = |version: &str| VerifiedManifest {
+            manifest: CaptainManifest {
+                version: version.to_string(),
+                artifacts: Default::default(),
+            },
+            raw: String::new(),
+            signature: String::new(),
+            trusted_comment: String::new(),
+        };
+        assert!(check_pin(&settings, &verified("v1.4.0")).is_ok());
+        assert!(check_pin(&settings, &verified("1.5.0")).is_err());
+        assert!(check_pin(&CaptainSettings::default(), &verified("1.5.0")).is_ok());
+        let latest = CaptainSettings::default();
+        assert!(!check_upgrade(&latest, "1.4.0", &verified("v1.4.0")).unwrap());
+        assert!(check_upgrade(&latest, "1.4.0", &verified("1.10.0")).unwrap());
+        assert!(check_upgrade(&latest, "1.10.0", &verified("1.4.0")).is_err());
+        assert!(check_upgrade(&latest, "1.4.0", &verified("latest")).is_err());
+        assert!(check_upgrade(&settings, "1.10.0", &verified("1.4.0")).unwrap());
+    }
+}
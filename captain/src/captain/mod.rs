@@ -5,8 +5,11 @@ pub mod config;
 pub mod create_secure_binary;
 pub mod create_self_protected_binary;
 pub mod encrypt_binaries;
+pub mod integrity;
+pub mod license_cache;
 pub mod license_guard;
 pub mod license;
+pub mod manage;
 pub mod protection;
 pub mod shell_integration;
 pub mod version_commands;
@@ -9,29 +9,23 @@ fn get_cache_file_path() -> PathBuf {
     if let Ok(home) = env::var("HOME") {
         let dir = PathBuf::from(&home).join(".shipwreck").join(".parlor");
         match std::fs::create_dir_all(&dir) {
-            Ok(_) => eprintln!("✅ Directory created successfully: {}", dir.display()),
-            Err(e) => {
-                eprintln!(
-                    "❌ Failed to create directory: {} - Error: {}", dir.display(), e
-                )
-            }
+            Ok(_) => log::debug!("Key cache directory ready: {}", dir.display()),
+            Err(e) => log::warn!("Failed to create directory: {} - Error: {}", dir.display(), e),
         }
         dir.join(KEY_CACHE_FILE)
     } else {
         let dir = PathBuf::from("/tmp");
         match std::fs::create_dir_all(&dir) {
-            Ok(_) => eprintln!("✅ Fallback directory ready: {}", dir.display()),
-            Err(e) => {
-                eprintln!(
-                    "❌ Failed to create fallback directory: {} - Error: {}", dir
-                    .display(), e
-                )
-            }
+            Ok(_) => log::debug!("Fallback directory ready: {}", dir.display()),
+            Err(e) => log::warn!("Failed to create fallback directory: {} - Error: {}", dir.display(), e),
         }
         dir.join(KEY_CACHE_FILE)
     }
 }
 fn fetch_key_from_server() -> Option<String> {
+    if crate::net::is_offline() {
+        return None;
+    }
     if let Ok(output) = std::process::Command::new("curl")
         .args(&["-s", "--max-time", "5", "https://mate.cargo.do/overboard/key"])
         .output()
@@ -73,7 +67,7 @@ fn save_key_to_cache(key: &str) {
         .unwrap_or_default()
         .as_secs();
     let content = format!("{}\n{}", timestamp, key);
-    let _ = fs::write(&cache_file, content);
+    let _ = crate::storage::write_atomic(&cache_file, content);
 }
 pub fn get_protection_key() -> String {
     if let Ok(key) = env::var("CURRENT_KEY") {
@@ -11,6 +11,11 @@ pub enum WtfAction {
     History { #[arg(default_value = "10")] limit: usize },
     Checklist { #[arg(default_value = "10")] limit: usize },
     Interactive,
+    /// Recorded conversations, optionally only those about an error (stored line or fingerprint)
+    Sessions { #[arg(long)] error: Option<String> },
+    /// Continue a session with its earlier questions and answers as context
+    Resume { id: String, #[arg(trailing_var_arg = true)] question: Vec<String> },
+    Export { id: String, #[arg(short, long)] output: Option<std::path::PathBuf> },
 }
 #[derive(Debug, Clone, clap::Subcommand)]
 pub enum OllamaCommand {
@@ -1,4 +1,4 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use chrono::{DateTime, Utc, TimeDelta};
 use colored::*;
 use serde::{Deserialize, Serialize};
@@ -6,8 +6,11 @@ use std::collections::{HashMap, VecDeque, HashSet};
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
 use crate::captain::license;
+use crate::storage::{self, Storage};
 use crate::parser::ParsedError;
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CargoMessage {
@@ -105,13 +108,9 @@ impl Default for BuildImpact {
 }
 impl PatternCache {
     pub fn new() -> Result<Self> {
-        let cache_file = dirs::home_dir()
-            .unwrap()
-            .join(".shipwreck")
-            .join("pattern_cache.json");
-        if cache_file.exists() {
-            let content = fs::read_to_string(&cache_file)?;
-            Ok(serde_json::from_str(&content).unwrap_or_default())
+        let storage = storage::default_storage()?;
+        if let Some(cache) = storage::read_json(storage.as_ref(), "pattern_cache.json")? {
+            Ok(cache)
         } else {
             Ok(Self {
                 recent_sessions: VecDeque::with_capacity(50),
@@ -304,14 +303,7 @@ impl PatternCache {
             .map(|(file, error_count)| ErrorHotspot { file, error_count })
     }
     fn save(&self) -> Result<()> {
-        let cache_file = dirs::home_dir()
-            .unwrap()
-            .join(".shipwreck")
-            .join("pattern_cache.json");
-        fs::create_dir_all(cache_file.parent().unwrap())?;
-        let json = serde_json::to_string_pretty(self)?;
-        fs::write(cache_file, json)?;
-        Ok(())
+        storage::write_json(&*storage::default_storage()?, "pattern_cache.json", self)
     }
 }
 impl Default for PatternCache {
@@ -388,6 +380,25 @@ pub struct LogEntry {
     pub severity: Option<String>,
     pub suppressed: Option<bool>,
 }
+impl LogEntry {
+    pub fn display(&self) {
+        match (&self.command, &self.build_result) {
+            (Some(command), Some(result)) => {
+                let status_icon = if result.success { "✅" } else { "❌" };
+                println!(
+                    "{} Command logged: {} ({}s)", status_icon, command.cyan(), result
+                    .duration_seconds
+                );
+            }
+            _ => {
+                println!("📝 {}", format!("Logged: {}", self.message) .green());
+                if !self.tags.is_empty() {
+                    println!("   🏷️  Tags: {}", self.tags.join(", ").dimmed());
+                }
+            }
+        }
+    }
+}
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BuildResult {
     pub success: bool,
@@ -395,31 +406,25 @@ pub struct BuildResult {
     pub warning_count: usize,
     pub duration_seconds: f64,
 }
+const LOG_KEY: &str = "captain.log";
 pub struct CaptainLog {
     entries: Vec<LogEntry>,
     current_session: Vec<LogEntry>,
-    log_file: PathBuf,
+    storage: Arc<dyn Storage>,
 }
 impl CaptainLog {
     pub fn new() -> Result<Self> {
-        let shipwreck_dir = dirs::home_dir()
-            .context("Could not find home directory")?
-            .join(".shipwreck");
-        fs::create_dir_all(&shipwreck_dir)?;
-        let log_file = shipwreck_dir.join("captain.log");
-        let entries = if log_file.exists() {
-            let content = fs::read_to_string(&log_file)?;
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            Vec::new()
-        };
+        Self::with_storage(storage::default_storage()?)
+    }
+    pub fn with_storage(storage: Arc<dyn Storage>) -> Result<Self> {
+        let entries = storage::read_json(storage.as_ref(), LOG_KEY)?.unwrap_or_default();
         Ok(Self {
             entries,
             current_session: Vec::new(),
-            log_file,
+            storage,
         })
     }
-    pub fn log(&mut self, message: &str, tags: Vec<String>) -> Result<()> {
+    pub fn log(&mut self, message: &str, tags: Vec<String>) -> Result<LogEntry> {
         let entry = LogEntry {
             timestamp: Utc::now(),
             message: message.to_string(),
@@ -443,19 +448,19 @@ impl CaptainLog {
         self.entries.push(entry.clone());
         self.current_session.push(entry.clone());
         self.save()?;
-        println!("📝 {}", format!("Logged: {}", message) .green());
-        if !entry.tags.is_empty() {
-            println!("   🏷️  Tags: {}", entry.tags.join(", ").dimmed());
-        }
-        Ok(())
+        Ok(entry)
     }
-    pub fn log_command(&mut self, command: &str, result: BuildResult) -> Result<()> {
+    pub fn log_command(
+        &mut self,
+        command: &str,
+        result: BuildResult,
+    ) -> Result<LogEntry> {
         let entry = LogEntry {
             timestamp: Utc::now(),
             message: format!("Executed: {}", command),
             tags: vec!["command".to_string()],
             command: Some(command.to_string()),
-            build_result: Some(result.clone()),
+            build_result: Some(result),
             context: self.capture_context(),
             error_code: None,
             error_type: None,
@@ -471,14 +476,9 @@ impl CaptainLog {
             suppressed: None,
         };
         self.entries.push(entry.clone());
-        self.current_session.push(entry);
+        self.current_session.push(entry.clone());
         self.save()?;
-        let status_icon = if result.success { "✅" } else { "❌" };
-        println!(
-            "{} Command logged: {} ({}s)", status_icon, command.cyan(), result
-            .duration_seconds
-        );
-        Ok(())
+        Ok(entry)
     }
     pub fn search(&self, query: &str) -> Vec<&LogEntry> {
         self.entries
@@ -506,16 +506,18 @@ impl CaptainLog {
         };
         self.entries[start..].iter().collect()
     }
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
     pub fn get_session_logs(&self) -> &[LogEntry] {
         &self.current_session
     }
-    pub fn show_timeline(&self, days: i64) -> Result<()> {
+    pub fn timeline(&self, days: i64) -> Vec<&LogEntry> {
         let cutoff = Utc::now() - chrono::Duration::days(days);
-        let filtered: Vec<&LogEntry> = self
-            .entries
-            .iter()
-            .filter(|entry| entry.timestamp > cutoff)
-            .collect();
+        self.entries.iter().filter(|entry| entry.timestamp > cutoff).collect()
+    }
+    pub fn show_timeline(&self, days: i64) -> Result<()> {
+        let filtered = self.timeline(days);
         if filtered.is_empty() {
             println!("No log entries in the last {} days", days);
             return Ok(());
@@ -656,7 +658,6 @@ impl CaptainLog {
                 fs::write(path, content)?;
             }
         }
-        println!("✅ Log exported to {}", path.display());
         Ok(())
     }
     pub fn analyze(&self) -> LogAnalysis {
@@ -712,14 +713,14 @@ impl CaptainLog {
         if let Ok(dir) = std::env::current_dir() {
             context.insert("working_dir".to_string(), dir.to_string_lossy().to_string());
         }
+        #[cfg(not(target_arch = "wasm32"))]
         if let Ok(branch) = get_git_branch() {
             context.insert("git_branch".to_string(), branch);
         }
         context
     }
     fn save(&self) -> Result<()> {
-        let json = serde_json::to_string_pretty(&self.entries)?;
-        fs::write(&self.log_file, json)?;
+        storage::write_json(&*self.storage, LOG_KEY, &self.entries)?;
         Ok(())
     }
 }
@@ -758,6 +759,7 @@ pub enum ExportFormat {
     Markdown,
     Html,
 }
+#[cfg(not(target_arch = "wasm32"))]
 fn get_git_branch() -> Result<String> {
     use std::process::Command;
     let output = Command::new("git").args(&["branch", "--show-current"]).output();
@@ -932,6 +934,7 @@ impl PatternDetector {
         clusters
     }
 }
+#[cfg(not(target_arch = "wasm32"))]
 pub fn check_quartermaster_status(command: &str) -> Result<bool> {
     println!(
         "📋 Quartermaster verifying status for command '{}' - all provisions checked!",
@@ -1012,6 +1015,7 @@ fn format_duration(duration: Duration) -> String {
         format!("{}h {}m", seconds / 3600, (seconds % 3600) / 60)
     }
 }
+#[cfg(not(target_arch = "wasm32"))]
 pub fn detect_changed_files() -> Vec<String> {
     use std::process::Command;
     let output = Command::new("git").args(&["diff", "--name-only", "HEAD"]).output();
@@ -1025,6 +1029,11 @@ pub fn detect_changed_files() -> Vec<String> {
         _ => vec![],
     }
 }
+#[cfg(not(target_arch = "wasm32"))]
 pub fn generate_session_id() -> String {
     format!("session_{}", rand::random::< u64 > ())
+}
+#[cfg(target_arch = "wasm32")]
+pub fn generate_session_id() -> String {
+    format!("session_{}", Utc::now().timestamp_micros())
 }
\ No newline at end of file
@@ -1,11 +1,61 @@
+use crate::fingerprint::{self, Occurrence};
 use crate::parser::{ParsedError, ParsedWarning};
 use chrono::Utc;
 use colored::*;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub id: usize,
+    pub text: String,
+    pub done: bool,
+    pub created_at: String,
+}
+impl ChecklistItem {
+    pub fn new(id: usize, text: &str) -> Self {
+        Self {
+            id,
+            text: text.to_string(),
+            done: false,
+            created_at: Utc::now().to_rfc3339(),
+        }
+    }
+}
+pub fn items_file() -> Result<PathBuf> {
+    crate::project::data_file("checklists/items.json")
+}
+pub fn load_items(path: &Path) -> Result<Vec<ChecklistItem>> {
+    Ok(crate::storage::read_json_file(path)?.unwrap_or_default())
+}
+/// Load, change and save under the checklist's lock, for writers that may run alongside a build
+pub fn update_items<R>(path: &Path, update: impl FnOnce(&mut Vec<ChecklistItem>) -> R) -> Result<R> {
+    crate::storage::update_json(path, update)
+}
+/// Appends `text` under the next free id and returns it
+pub fn add_item(items: &mut Vec<ChecklistItem>, text: &str) -> usize {
+    let id = items.iter().map(|i| i.id).max().unwrap_or(0) + 1;
+    items.push(ChecklistItem::new(id, text));
+    id
+}
+/// Marks `ids` done and returns how many of them were still open
+pub fn mark_done(items: &mut [ChecklistItem], ids: &[usize]) -> usize {
+    let mut marked = 0;
+    for item in items.iter_mut().filter(|item| ids.contains(&item.id) && !item.done) {
+        item.done = true;
+        marked += 1;
+    }
+    marked
+}
 pub fn generate_checklist(errors: &[ParsedError], warnings: &[ParsedWarning]) {
     let checklist_file = get_checklist_file();
+    let history = crate::history::load_visible_history();
+    let errors = with_occurrences(errors, &fingerprint::error_occurrences(&history));
+    let warnings = with_occurrences(
+        warnings,
+        &fingerprint::warning_occurrences(&history),
+    );
     let mut content = String::new();
     content
         .push_str(
@@ -18,12 +68,12 @@ pub fn generate_checklist(errors: &[ParsedError], warnings: &[ParsedWarning]) {
         .push_str(&format!("Generated: {}\n\n", Utc::now().format("%Y-%m-%d %H:%M:%S")));
     if !errors.is_empty() {
         content.push_str("ERRORS (must fix):\n");
-        for error in errors {
+        for (error, seen) in &errors {
             content
                 .push_str(
                     &format!(
-                        "[ ] Fix {} in {}:{} - {}\n", error.code, error.file, error.line,
-                        error.message
+                        "[ ] Fix {} in {}:{} - {}{}\n", error.code, error.file, error.line,
+                        error.message, seen
                     ),
                 );
         }
@@ -31,32 +81,34 @@ pub fn generate_checklist(errors: &[ParsedError], warnings: &[ParsedWarning]) {
     }
     if !warnings.is_empty() {
         content.push_str("WARNINGS (consider fixing):\n");
-        for warning in warnings {
+        for (warning, seen) in &warnings {
             content
                 .push_str(
                     &format!(
-                        "[ ] {} in {}:{} - {}\n", warning.code, warning.file, warning
-                        .line, warning.message
+                        "[ ] {} in {}:{} - {}{}\n", warning.code, warning.file, warning
+                        .line, warning.message, seen
                     ),
                 );
         }
     }
-    let mut file = fs::File::create(&checklist_file).unwrap();
-    file.write_all(content.as_bytes()).unwrap();
+    crate::storage::write_atomic(&checklist_file, &content).unwrap();
     let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
     let archive_file = get_checklist_dir().join(format!("checklist_{}.txt", timestamp));
     fs::copy(&checklist_file, &archive_file).unwrap();
 }
 pub fn show_checklist() {
-    let checklist_file = get_checklist_file();
-    if !checklist_file.exists() {
-        println!("No checklist found. Run a cargo command first!");
-        return;
-    }
-    let content = fs::read_to_string(&checklist_file).unwrap();
+    let content = match crate::project::read_text("checklists/latest.txt") {
+        Ok(Some(content)) => content,
+        _ => {
+            println!("No checklist found. Run a cargo command first!");
+            return;
+        }
+    };
     for line in content.lines() {
         if line.starts_with("===") {
             println!("{}", line.blue().bold());
+        } else if line.starts_with("──") {
+            println!("{}", line.cyan().bold());
         } else if line.starts_with("ERRORS") {
             println!("{}", line.red().bold());
         } else if line.starts_with("WARNINGS") {
@@ -73,11 +125,76 @@ pub fn show_checklist() {
     }
     println!("\n💡 Tip: Copy this checklist to your editor to track progress!");
 }
+fn with_occurrences<'a, T: std::fmt::Display>(
+    diagnostics: &'a [T],
+    occurrences: &[Occurrence],
+) -> Vec<(&'a T, String)> {
+    let mut seen = Vec::new();
+    let mut lines = Vec::new();
+    for diagnostic in diagnostics {
+        let line = diagnostic.to_string();
+        let fingerprint = fingerprint::of_stored(&line);
+        if seen.contains(&fingerprint) {
+            continue;
+        }
+        let note = match fingerprint::lookup(occurrences, &line) {
+            Some(occurrence) if occurrence.builds > 1 => {
+                format!(" ({})", occurrence.summary())
+            }
+            _ => String::new(),
+        };
+        seen.push(fingerprint);
+        lines.push((diagnostic, note));
+    }
+    lines
+}
 fn get_checklist_file() -> PathBuf {
-    let shipwreck = dirs::home_dir().unwrap().join(".shipwreck");
-    shipwreck.join("checklists").join("latest.txt")
+    get_checklist_dir().join("latest.txt")
 }
 fn get_checklist_dir() -> PathBuf {
-    let shipwreck = dirs::home_dir().unwrap().join(".shipwreck");
-    shipwreck.join("checklists")
-}
\ No newline at end of file
+    let dir = crate::project::data_dir().unwrap().join("checklists");
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_concurrent_writers_keep_every_item() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("items.json");
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for n in 0..10 {
+                        update_items(&path, |items| {
+                            add_item(items, &format!("task {}-{}", writer, n))
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        let mut ids: Vec<usize> = load_items(&path)
+            .unwrap()
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, (1..=80).collect::<Vec<_>>());
+        assert_eq!(
+            update_items(&path, |items| mark_done(items, &[3, 5, 99])).unwrap(),
+            2
+        );
+        assert_eq!(
+            update_items(&path, |items| mark_done(items, &[3])).unwrap(),
+            0
+        );
+        update_items(&path, |items| items.retain(|item| !item.done)).unwrap();
+        assert_eq!(load_items(&path).unwrap().len(), 78);
+    }
+}
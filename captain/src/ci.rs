@@ -0,0 +1,123 @@
+use anyhow::Result;
+use regex::Regex;
+use std::borrow::Cow;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+static CI_MODE: AtomicBool = AtomicBool::new(false);
+/// Set for the child of a CI run, whose output the parent already filters
+const FILTERED_ENV: &str = "CM_CI_FILTERED";
+fn env_flag(name: &str) -> bool {
+    match std::env::var(name) {
+        Ok(value) => {
+            let value = value.trim();
+            !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("false")
+        }
+        Err(_) => false,
+    }
+}
+pub fn enable() {
+    CI_MODE.store(true, Ordering::Relaxed);
+    std::env::set_var("CM_CI", "1");
+    colored::control::set_override(false);
+}
+/// In CI mode the command runs as a child whose output passes through [`strip_ansi`] and
+/// [`plain`] here, so nothing printed anywhere in cm reaches the log with color or emoji
+pub fn init(flag: bool) -> bool {
+    if flag || env_flag("CM_CI") {
+        enable();
+        if std::env::var_os(FILTERED_ENV).is_none() {
+            match run_filtered() {
+                Ok(code) => std::process::exit(code),
+                // Unfiltered output beats no output
+                Err(e) => eprintln!("Could not filter CI output: {}", e),
+            }
+        }
+    }
+    is_ci()
+}
+fn run_filtered() -> std::io::Result<i32> {
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(std::env::args_os().skip(1))
+        .env(FILTERED_ENV, "1")
+        .env("NO_COLOR", "1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let errors = std::thread::spawn(move || forward(stderr, std::io::stderr()));
+    let forwarded = forward(stdout, std::io::stdout());
+    let _ = errors.join();
+    let status = child.wait()?;
+    forwarded?;
+    Ok(status.code().unwrap_or(1))
+}
+fn forward(from: impl Read, mut to: impl Write) -> std::io::Result<()> {
+    let mut reader = BufReader::new(from);
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        to.write_all(plain(&strip_ansi(&String::from_utf8_lossy(&line))).as_bytes())?;
+        to.flush()?;
+        line.clear();
+    }
+    Ok(())
+}
+/// Removes color and cursor escape sequences, including ones from tools cm runs
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    static ANSI: OnceLock<Regex> = OnceLock::new();
+    ANSI.get_or_init(|| {
+            Regex::new(r"\x1b(\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(\x07|\x1b\\)|[@-Z\\-_])").unwrap()
+        })
+        .replace_all(text, "")
+}
+pub fn is_ci() -> bool {
+    CI_MODE.load(Ordering::Relaxed) || env_flag("CM_CI")
+}
+fn is_pictograph(c: char) -> bool {
+    matches!(
+        c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2300..=0x23FF | 0x2B00..=0x2BFF
+        | 0xFE00..=0xFE0F | 0x200D | 0x20E3
+    )
+}
+pub fn plain(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let indent = line.len() - line.trim_start().len();
+        let stripped: String = line.chars().filter(|c| !is_pictograph(*c)).collect();
+        if stripped.len() == line.len() {
+            out.push_str(line);
+        } else {
+            out.push_str(&line[..indent]);
+            out.push_str(stripped.trim_start());
+        }
+    }
+    out
+}
+pub fn decorate(text: &str) -> Cow<'_, str> {
+    if is_ci() { Cow::Owned(plain(text)) } else { Cow::Borrowed(text) }
+}
+pub fn confirm(prompt: &str) -> Result<bool> {
+    if is_ci() {
+        println!("{} (y/N): n [CI mode]", plain(prompt));
+        return Ok(false);
+    }
+    print!("{} (y/N): ", prompt);
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_lowercase().starts_with('y'))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_plain_strips_emoji() {
+        assert_eq!(plain("✅ Build Successful!"), "Build Successful!");
+        assert_eq!(plain("⏱️  Build time: 1.0s"), "Build time: 1.0s");
+        assert_eq!(plain("  🔴 2 errors\n  plain → text"), "  2 errors\n  plain → text");
+        assert_eq!(plain("═══"), "═══");
+        assert_eq!(strip_ansi("\x1b[1;32mok\x1b[0m \x1b]8;;https://x\x07link\x1b]8;;\x07"), "ok link");
+    }
+}
@@ -0,0 +1,212 @@
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use colored::*;
+use reqwest::blocking::RequestBuilder;
+use reqwest::Method;
+use serde::Deserialize;
+use std::path::Path;
+const API: &str = "https://crates.io/api/v1/crates";
+/// Same variable cargo itself reads, for CI that already sets it
+const TOKEN_ENV: &str = "CARGO_REGISTRY_TOKEN";
+const CRATES_IO_SOURCE: &str = "registry+https://github.com/rust-lang/crates.io-index";
+#[derive(Subcommand, Debug)]
+pub enum CratesAction {
+    /// List owners, or invite/remove them with --add/--remove (`github:org:team` for teams)
+    Owners {
+        #[arg(long = "crate")]
+        crate_name: Option<String>,
+        #[arg(long)]
+        add: Vec<String>,
+        #[arg(long)]
+        remove: Vec<String>,
+    },
+    Yank {
+        version: String,
+        #[arg(long = "crate")]
+        crate_name: Option<String>,
+        /// Un-yank the version instead
+        #[arg(long)]
+        undo: bool,
+        /// Yank even when workspace crates still depend on the version
+        #[arg(long)]
+        force: bool,
+    },
+}
+#[derive(Debug, Deserialize)]
+struct Owner {
+    login: String,
+    name: Option<String>,
+    kind: Option<String>,
+}
+#[derive(Debug, Deserialize)]
+struct OwnersResponse {
+    users: Vec<Owner>,
+}
+fn token() -> Result<String> {
+    std::env::var(TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.is_empty())
+        .or_else(|| crate::secrets::lookup(crate::secrets::CRATES_TOKEN))
+        .with_context(|| {
+            format!(
+                "No crates.io token - run 'cm secret set {}' or set {}",
+                crate::secrets::CRATES_TOKEN, TOKEN_ENV
+            )
+        })
+}
+fn request(method: Method, url: &str) -> Result<RequestBuilder> {
+    Ok(crate::outdated::client()?.request(method, url))
+}
+/// crates.io answers errors as `{"errors": [{"detail": ...}]}`
+fn send(request: RequestBuilder) -> Result<serde_json::Value> {
+    let response = crate::net::send(request)?;
+    let status = response.status();
+    let body: serde_json::Value = response.json().unwrap_or_default();
+    if !status.is_success() {
+        let detail = body["errors"][0]["detail"].as_str().unwrap_or("no details").to_string();
+        bail!("crates.io returned HTTP {}: {}", status, detail);
+    }
+    Ok(body)
+}
+fn crate_name(explicit: Option<String>) -> Result<String> {
+    explicit
+        .or_else(|| crate::publish::package_identity().map(|(name, _)| name))
+        .context("No crate given and ./Cargo.toml has no package name - pass --crate")
+}
+fn show_owners(name: &str) -> Result<()> {
+    let body = send(request(Method::GET, &format!("{}/{}/owners", API, name))?)?;
+    let owners: OwnersResponse = serde_json::from_value(body)?;
+    println!("👑 Owners of {}:", name.cyan());
+    for owner in owners.users {
+        let kind = owner.kind.as_deref().unwrap_or("user");
+        println!(
+            "  {} {}{}",
+            owner.login.bold(),
+            owner.name.map(|n| format!("({}) ", n)).unwrap_or_default(),
+            format!("[{}]", kind).dimmed()
+        );
+    }
+    Ok(())
+}
+fn change_owners(name: &str, logins: &[String], method: Method) -> Result<()> {
+    let body = send(
+        request(method, &format!("{}/{}/owners", API, name))?
+            .header("Authorization", token()?)
+            .json(&serde_json::json!({ "users": logins })),
+    )?;
+    if let Some(message) = body["msg"].as_str() {
+        println!("✅ {}", message);
+    }
+    Ok(())
+}
+/// Workspace crates whose requirement on `name` accepts `version`, and whether
+/// Cargo.lock currently resolves exactly that release from crates.io. Reads the manifests
+/// and the lockfile only, so it works offline
+fn workspace_dependents(
+    manifest: Option<&Path>,
+    name: &str,
+    version: &semver::Version,
+) -> Result<(Vec<String>, bool)> {
+    let mut command = cargo_metadata::MetadataCommand::new();
+    command.no_deps();
+    if let Some(manifest) = manifest {
+        command.manifest_path(manifest);
+    }
+    let metadata = command.exec().context("Failed to read cargo metadata")?;
+    let dependents = metadata
+        .workspace_packages()
+        .iter()
+        .filter(|package| {
+            package.dependencies.iter().any(|dep| dep.name == name && dep.req.matches(version))
+        })
+        .map(|package| package.name.clone())
+        .collect();
+    let lock = std::fs::read_to_string(metadata.workspace_root.join("Cargo.lock")).unwrap_or_default();
+    let locked = crate::lockdiff::parse_lock(&lock).unwrap_or_default().iter().any(|package| {
+        package.name == name
+            && package.version == version.to_string()
+            && package.source.as_deref() == Some(CRATES_IO_SOURCE)
+    });
+    Ok((dependents, locked))
+}
+fn yank(name: &str, version: &str, undo: bool, force: bool) -> Result<()> {
+    let parsed = semver::Version::parse(version)
+        .with_context(|| format!("'{}' is not a valid version", version))?;
+    if !undo {
+        let (dependents, locked) = workspace_dependents(None, name, &parsed).unwrap_or_default();
+        if !dependents.is_empty() || locked {
+            println!("⚠️  {} {} is still in use in this workspace:", name, version);
+            for dependent in &dependents {
+                println!("   • {} depends on a requirement matching {}", dependent.cyan(), version);
+            }
+            if locked {
+                println!("   • Cargo.lock resolves {} {} from crates.io", name, version);
+            }
+            if !force && !crate::ci::confirm(&format!("Yank {} {} anyway?", name, version))? {
+                println!("Yank cancelled.");
+                return Ok(());
+            }
+        }
+    }
+    let (method, action) = if undo { (Method::PUT, "unyank") } else { (Method::DELETE, "yank") };
+    send(
+        request(method, &format!("{}/{}/{}/{}", API, name, version, action))?
+            .header("Authorization", token()?),
+    )?;
+    let reason = if undo { "un-yanked on crates.io" } else { "yanked from crates.io" };
+    println!("✅ {} {} {}", name.cyan(), version, reason);
+    let recorded = crate::version::VersionManager::new(None).and_then(|manager| manager.record(version, reason));
+    if let Err(e) = recorded {
+        log::warn!("Could not record the yank in version history: {}", e);
+    }
+    Ok(())
+}
+pub fn handle_crates_command(action: CratesAction) -> Result<()> {
+    match action {
+        CratesAction::Owners { crate_name: name, add, remove } => {
+            let name = crate_name(name)?;
+            if !add.is_empty() {
+                change_owners(&name, &add, Method::PUT)?;
+            }
+            if !remove.is_empty() {
+                change_owners(&name, &remove, Method::DELETE)?;
+            }
+            show_owners(&name)?;
+        }
+        CratesAction::Yank { version, crate_name: name, undo, force } => {
+            yank(&crate_name(name)?, &version, undo, force)?;
+        }
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_yank_guard_finds_workspace_requirements_and_the_lockfile() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\"app\", \"pinned\"]\n").unwrap();
+        for (member, req) in [("app", "1.0"), ("pinned", "=1.0.100")] {
+            std::fs::create_dir_all(root.join(member).join("src")).unwrap();
+            std::fs::write(root.join(member).join("src").join("lib.rs"), "").unwrap();
+            std::fs::write(
+                root.join(member).join("Cargo.toml"),
+                format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = \"{}\"\n", member, req),
+            )
+            .unwrap();
+        }
+        std::fs::write(
+            root.join("Cargo.lock"),
+            format!("version = 3\n\n[[package]]\nname = \"serde\"\nversion = \"1.0.210\"\nsource = \"{}\"\n", CRATES_IO_SOURCE),
+        )
+        .unwrap();
+        let manifest = root.join("Cargo.toml");
+        let guard = |version: &str| {
+            workspace_dependents(Some(&manifest), "serde", &semver::Version::parse(version).unwrap()).unwrap()
+        };
+        assert_eq!(guard("1.0.210"), (vec!["app".to_string()], true));
+        assert_eq!(guard("1.0.100"), (vec!["app".to_string(), "pinned".to_string()], false));
+        assert_eq!(guard("2.0.0"), (Vec::new(), false));
+    }
+}
@@ -0,0 +1,408 @@
+use crate::fingerprint;
+use crate::history::{self, HistoryEntry};
+use crate::lockdiff::{self, LockDiff, LockPackage};
+use crate::tide::{BuildMetrics, TideCharts};
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::process::Command;
+const LOCKS_DIR: &str = "builds/locks";
+/// How far apart a tide record and its history entry may be written
+const HISTORY_MATCH_SECONDS: i64 = 30;
+const TRACKED_ENV: &[&str] = &[
+    "RUSTFLAGS",
+    "RUSTDOCFLAGS",
+    "RUSTC",
+    "RUSTC_WRAPPER",
+    "RUSTUP_TOOLCHAIN",
+    "CC",
+    "CXX",
+    "CFLAGS",
+    "CXXFLAGS",
+    "LDFLAGS",
+    "PKG_CONFIG_PATH",
+];
+/// What a build ran with, beyond what tide already times; stored alongside each tide record
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuildEnvironment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rustc: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Hash of the Cargo.lock snapshot kept in the project's `builds/locks`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lockfile: Option<String>,
+}
+fn is_tracked(name: &str) -> bool {
+    TRACKED_ENV.contains(&name)
+        || (name.starts_with("CARGO_") && !name.starts_with("CARGO_TERM_") && !name.starts_with("CARGO_MATE_"))
+}
+fn is_secret(name: &str) -> bool {
+    ["TOKEN", "SECRET", "PASSWORD", "KEY"].iter().any(|word| name.contains(word))
+}
+/// Secrets are kept only as a hash so a change still shows up
+pub fn tracked_env(vars: impl IntoIterator<Item = (String, String)>) -> BTreeMap<String, String> {
+    vars.into_iter()
+        .filter(|(name, _)| is_tracked(name))
+        .map(|(name, value)| {
+            let value = if is_secret(&name) {
+                format!("sha256:{}", &format!("{:x}", Sha256::digest(value.as_bytes()))[..12])
+            } else {
+                value
+            };
+            (name, value)
+        })
+        .collect()
+}
+fn rustc_version() -> Option<String> {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+fn snapshot_lockfile() -> Option<String> {
+    let bucket = crate::project::current_project()?;
+    let content = fs::read_to_string(bucket.manifest.with_file_name("Cargo.lock")).ok()?;
+    let hash = format!("{:x}", Sha256::digest(content.as_bytes()))[..16].to_string();
+    let path = crate::project::data_file(&format!("{}/{}.lock", LOCKS_DIR, hash)).ok()?;
+    if !path.exists() {
+        fs::write(&path, content).ok()?;
+    }
+    Some(hash)
+}
+impl BuildEnvironment {
+    pub fn capture() -> Self {
+        Self {
+            project: crate::project::current_project().map(|bucket| bucket.key),
+            rustc: rustc_version(),
+            env: tracked_env(std::env::vars()),
+            lockfile: snapshot_lockfile(),
+        }
+    }
+}
+fn load_lock(hash: &str) -> Option<Vec<LockPackage>> {
+    let path = crate::project::data_dir().ok()?.join(LOCKS_DIR).join(format!("{}.lock", hash));
+    lockdiff::parse_lock(&fs::read_to_string(path).ok()?).ok()
+}
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+fn change<T: PartialEq + Clone>(from: &T, to: &T) -> Option<Change<T>> {
+    (from != to).then(|| Change { from: from.clone(), to: to.clone() })
+}
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnvChange {
+    pub name: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildDiff {
+    pub from: BuildMetrics,
+    pub to: BuildMetrics,
+    pub duration_delta_seconds: f64,
+    pub rustc: Option<Change<Option<String>>>,
+    pub profile: Option<Change<String>>,
+    pub features_added: Vec<String>,
+    pub features_removed: Vec<String>,
+    pub env: Vec<EnvChange>,
+    /// None when either build predates lockfile snapshots
+    pub dependencies: Option<LockDiff>,
+    pub new_diagnostics: Vec<String>,
+    pub gone_diagnostics: Vec<String>,
+}
+impl BuildDiff {
+    fn dependency_changes(&self) -> usize {
+        self.dependencies
+            .as_ref()
+            .map_or(0, |deps| deps.changed.len() + deps.added.len() + deps.removed.len())
+    }
+}
+fn diagnostics_diff(from: &[String], to: &[String]) -> (Vec<String>, Vec<String>) {
+    let ids = |lines: &[String]| -> BTreeSet<String> { lines.iter().map(|l| fingerprint::of_stored(l)).collect() };
+    let (from_ids, to_ids) = (ids(from), ids(to));
+    let mut seen = BTreeSet::new();
+    let new = to
+        .iter()
+        .filter(|l| {
+            let id = fingerprint::of_stored(l);
+            !from_ids.contains(&id) && seen.insert(id)
+        })
+        .cloned()
+        .collect();
+    let gone = from
+        .iter()
+        .filter(|l| {
+            let id = fingerprint::of_stored(l);
+            !to_ids.contains(&id) && seen.insert(id)
+        })
+        .cloned()
+        .collect();
+    (new, gone)
+}
+pub fn compare(
+    from: &BuildMetrics,
+    to: &BuildMetrics,
+    locks: Option<(Vec<LockPackage>, Vec<LockPackage>)>,
+    diagnostics: (&[String], &[String]),
+) -> BuildDiff {
+    let (from_env, to_env) = (&from.environment.env, &to.environment.env);
+    let names: BTreeSet<&String> = from_env.keys().chain(to_env.keys()).collect();
+    let env = names
+        .into_iter()
+        .filter(|name| from_env.get(*name) != to_env.get(*name))
+        .map(|name| EnvChange {
+            name: name.clone(),
+            from: from_env.get(name).cloned(),
+            to: to_env.get(name).cloned(),
+        })
+        .collect();
+    let (new_diagnostics, gone_diagnostics) = diagnostics_diff(diagnostics.0, diagnostics.1);
+    BuildDiff {
+        duration_delta_seconds: to.duration_seconds - from.duration_seconds,
+        rustc: change(&from.environment.rustc, &to.environment.rustc),
+        profile: change(&from.profile, &to.profile),
+        features_added: to.features.iter().filter(|f| !from.features.contains(f)).cloned().collect(),
+        features_removed: from.features.iter().filter(|f| !to.features.contains(f)).cloned().collect(),
+        env,
+        dependencies: locks.map(|(old, new)| lockdiff::diff("earlier build", &old, &new)),
+        new_diagnostics,
+        gone_diagnostics,
+        from: from.clone(),
+        to: to.clone(),
+    }
+}
+/// Builds of the current project, newest first
+fn project_builds() -> Result<Vec<BuildMetrics>> {
+    let tide = TideCharts::new()?;
+    let project = crate::project::current_project().map(|bucket| bucket.key);
+    let builds: Vec<BuildMetrics> = tide
+        .builds()
+        .iter()
+        .rev()
+        .filter(|build| project.is_none() || build.environment.project == project)
+        .cloned()
+        .collect();
+    if builds.is_empty() {
+        bail!("No recorded builds for this project yet - builds run through cm are recorded");
+    }
+    Ok(builds)
+}
+fn history_for(history: &[HistoryEntry], build: &BuildMetrics) -> Vec<String> {
+    let command = build.command.strip_prefix("cargo ").unwrap_or(&build.command);
+    history
+        .iter()
+        .filter(|entry| entry.command == command)
+        .map(|entry| (entry, (entry.timestamp - build.timestamp).num_seconds().abs()))
+        .filter(|(_, distance)| *distance <= HISTORY_MATCH_SECONDS)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(entry, _)| entry.errors.iter().chain(&entry.warnings).cloned().collect())
+        .unwrap_or_default()
+}
+fn pick(builds: &[BuildMetrics], number: usize) -> Result<&BuildMetrics> {
+    builds
+        .get(number.checked_sub(1).context("Build numbers start at 1 (the latest build)")?)
+        .with_context(|| format!("Only {} build(s) recorded - see 'cm diff-build --list'", builds.len()))
+}
+fn list(builds: &[BuildMetrics]) {
+    println!("{}", "🏗️  Recorded builds (newest first)".bold().blue());
+    for (i, build) in builds.iter().take(20).enumerate() {
+        let status = if build.success { "✓".green() } else { "✗".red() };
+        println!(
+            "  {:>3}  {} {}  {:>7.1}s  {}  {}",
+            i + 1,
+            status,
+            build.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+            build.duration_seconds,
+            build.command.cyan(),
+            format!("{}E {}W", build.error_count, build.warning_count).dimmed()
+        );
+    }
+}
+fn describe(label: &str, build: &BuildMetrics) -> String {
+    format!(
+        "{} {} {} ({}, {:.1}s)",
+        label,
+        if build.success { "✓".green() } else { "✗".red() },
+        build.command.cyan(),
+        build.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+        build.duration_seconds
+    )
+}
+fn print_diff(diff: &BuildDiff) {
+    println!("{}", "🔬 Build comparison".bold().blue());
+    println!("  {}", describe("from", &diff.from));
+    println!("  {}", describe("to  ", &diff.to));
+    let percent = if diff.from.duration_seconds > 0.0 {
+        format!(" ({:+.0}%)", diff.duration_delta_seconds * 100.0 / diff.from.duration_seconds)
+    } else {
+        String::new()
+    };
+    let delta = format!("{:+.1}s{}", diff.duration_delta_seconds, percent);
+    println!(
+        "\n⏱️  Duration {:.1}s → {:.1}s {}",
+        diff.from.duration_seconds,
+        diff.to.duration_seconds,
+        if diff.duration_delta_seconds > 0.0 { delta.red() } else { delta.green() }
+    );
+    if let Some(rustc) = &diff.rustc {
+        let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "unknown".to_string());
+        println!("\n🦀 Toolchain {} → {}", show(&rustc.from), show(&rustc.to).bold());
+    }
+    if let Some(profile) = &diff.profile {
+        println!("\n🎛️  Profile {} → {}", profile.from, profile.to.bold());
+    }
+    if !diff.features_added.is_empty() || !diff.features_removed.is_empty() {
+        println!("\n🧩 Features");
+        for feature in &diff.features_added {
+            println!("  {} {}", "+".green(), feature);
+        }
+        for feature in &diff.features_removed {
+            println!("  {} {}", "-".red(), feature);
+        }
+    }
+    if !diff.env.is_empty() {
+        println!("\n🌱 Environment");
+        for change in &diff.env {
+            match (&change.from, &change.to) {
+                (None, Some(to)) => println!("  {} {}={}", "+".green(), change.name.cyan(), to),
+                (Some(from), None) => println!("  {} {}={}", "-".red(), change.name.cyan(), from),
+                (from, to) => println!(
+                    "  {} {} {} → {}",
+                    "~".yellow(),
+                    change.name.cyan(),
+                    from.as_deref().unwrap_or(""),
+                    to.as_deref().unwrap_or("")
+                ),
+            }
+        }
+    }
+    match &diff.dependencies {
+        Some(deps) if diff.dependency_changes() > 0 => {
+            println!("\n📦 Dependencies");
+            for change in &deps.changed {
+                let arrow = if change.downgrade { "↓".red() } else { "↑".normal() };
+                println!("  {} {} {} → {}", arrow, change.name.cyan(), change.from, change.to.bold());
+            }
+            for added in &deps.added {
+                println!("  {} {} {}", "+".green(), added.name.cyan(), added.version);
+            }
+            for removed in &deps.removed {
+                println!("  {} {}", "-".red(), removed);
+            }
+        }
+        Some(_) => {}
+        None => println!("\n📦 Dependencies: {}", "no Cargo.lock snapshot for one of the builds".dimmed()),
+    }
+    if !diff.new_diagnostics.is_empty() || !diff.gone_diagnostics.is_empty() {
+        println!("\n🩺 Diagnostics");
+        for line in &diff.new_diagnostics {
+            println!("  {} {}", "new ".red().bold(), line);
+        }
+        for line in &diff.gone_diagnostics {
+            println!("  {} {}", "gone".green(), line.dimmed());
+        }
+    }
+    let unchanged = diff.rustc.is_none()
+        && diff.profile.is_none()
+        && diff.features_added.is_empty()
+        && diff.features_removed.is_empty()
+        && diff.env.is_empty()
+        && diff.dependency_changes() == 0;
+    if unchanged {
+        println!("\n💡 Same toolchain, features, environment and dependencies - the difference is in your own code");
+    }
+}
+pub fn handle_diff_build(from: Option<usize>, to: Option<usize>, last_good: bool, list_only: bool, json: bool) -> Result<()> {
+    let builds = project_builds()?;
+    if list_only {
+        list(&builds);
+        return Ok(());
+    }
+    let to_number = to.unwrap_or(1);
+    let to_build = pick(&builds, to_number)?;
+    let from_build = match (from, last_good) {
+        (Some(number), _) => pick(&builds, number)?,
+        (None, true) => builds
+            .iter()
+            .skip(to_number)
+            .find(|build| build.success)
+            .context("No successful build before it to compare with")?,
+        (None, false) => pick(&builds, to_number + 1)?,
+    };
+    let locks = match (&from_build.environment.lockfile, &to_build.environment.lockfile) {
+        (Some(old), Some(new)) => load_lock(old).zip(load_lock(new)),
+        _ => None,
+    };
+    let history = history::load_visible_history();
+    let diff = compare(
+        from_build,
+        to_build,
+        locks,
+        (&history_for(&history, from_build), &history_for(&history, to_build)),
+    );
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print_diff(&diff);
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    fn build(seconds: f64, rustc: &str, env: &[(&str, &str)], features: &[&str]) -> BuildMetrics {
+        BuildMetrics {
+            timestamp: Utc::now(),
+            command: "cargo build".to_string(),
+            duration_seconds: seconds,
+            success: true,
+            error_count: 0,
+            warning_count: 0,
+            incremental: false,
+            profile: "debug".to_string(),
+            features: features.iter().map(|f| f.to_string()).collect(),
+            dependencies_compiled: 0,
+            crate_units_compiled: 0,
+            memory_peak_mb: None,
+            cpu_usage_percent: None,
+            environment: BuildEnvironment {
+                rustc: Some(rustc.to_string()),
+                env: env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+                ..Default::default()
+            },
+        }
+    }
+    #[test]
+    fn test_compare_builds() {
+        let env = tracked_env(
+            [("RUSTFLAGS", "-Ctarget-cpu=native"), ("CARGO_REGISTRY_TOKEN", "hunter2"), ("HOME", "/root"), ("CARGO_TERM_COLOR", "always")]
+                .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        assert_eq!(env.len(), 2);
+        assert!(env["CARGO_REGISTRY_TOKEN"].starts_with("sha256:"));
+        let from = build(10.0, "rustc 1.80.0", &[("RUSTFLAGS", "")], &["serde"]);
+        let to = build(15.0, "rustc 1.81.0", &[("RUSTFLAGS", "-Ctarget-cpu=native"), ("CC", "clang")], &["tracing"]);
+        let old = vec!["[E0308] src/lib.rs:10 - mismatched types".to_string()];
+        let new = vec![
+            "[E0308] src/lib.rs:12 - mismatched types".to_string(),
+            "[unused_variables] src/main.rs:3 - unused variable: `x`".to_string(),
+        ];
+        let diff = compare(&from, &to, None, (&old, &new));
+        assert_eq!(diff.duration_delta_seconds, 5.0);
+        assert_eq!(diff.rustc.as_ref().unwrap().to.as_deref(), Some("rustc 1.81.0"));
+        assert!(diff.profile.is_none());
+        assert_eq!((diff.features_added.clone(), diff.features_removed.clone()), (vec!["tracing".to_string()], vec!["serde".to_string()]));
+        assert_eq!(diff.env.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["CC", "RUSTFLAGS"]);
+        assert_eq!(diff.new_diagnostics, vec![new[1].clone()]);
+        assert!(diff.gone_diagnostics.is_empty());
+        assert!(diff.dependencies.is_none());
+    }
+}
@@ -1,13 +1,15 @@
 use crate::checklist;
+use crate::ci;
+use crate::hooks::{self, Diagnostics, HookContext, HookSet};
 use crate::history;
 use crate::parser::{self, MessageData, ParsedError, ParsedWarning};
+use crate::smart_parser;
 use crate::tide::{TideCharts, BuildMetrics};
 use crate::captain::license;
 use colored::*;
 use anyhow::{Result, Context};
-use indicatif::{ProgressBar, ProgressStyle, MultiProgress};
-use std::fs;
-use std::io::{BufRead, BufReader, Write};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::{BufRead, BufReader};
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -236,7 +238,8 @@ fn process_and_display_errors(errors: &[ParsedError]) {
     let groups = deduplicator.process_errors(errors);
     if !groups.is_empty() {
         println!(
-            "\n{}", format!("🔴 {} Unique Error Patterns:", groups.len()) .red().bold()
+            "\n{}", ci::decorate(&format!("🔴 {} Unique Error Patterns:", groups.len()))
+            .red().bold()
         );
         for (i, group) in groups.iter().take(5).enumerate() {
             println!(
@@ -252,9 +255,29 @@ fn process_and_display_errors(errors: &[ParsedError]) {
         }
     }
 }
+/// Byte counts for humans: `512B`, `1.5KB`, `12.3MB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{}B", bytes) } else { format!("{:.1}{}", size, UNITS[unit]) }
+}
 pub fn run_cargo_passthrough(args: &[&str]) {
     let cargo_path = std::env::var("CARGO_BIN_PATH")
         .unwrap_or_else(|_| "/root/.cargo/bin/cargo".to_string());
+    if crate::test_results::is_test_command(args) {
+        std::process::exit(crate::test_results::run_cargo_test(&cargo_path, args));
+    }
+    let hooks = HookSet::load();
+    if !hooks.is_empty() || crate::notify::NotifySettings::load().is_active() {
+        std::process::exit(hooks::run_cargo_with_hooks(&cargo_path, args, &hooks));
+    }
+    // stderr stays a terminal here so cargo keeps its progress bar; diagnostics aren't counted
+    let start = Instant::now();
     let status = Command::new(&cargo_path)
         .args(args)
         .status()
@@ -262,7 +285,9 @@ pub fn run_cargo_passthrough(args: &[&str]) {
             eprintln!("Failed to start cargo: {}", e);
             std::process::exit(1);
         });
-    std::process::exit(status.code().unwrap_or(1));
+    let code = status.code().unwrap_or(1);
+    crate::status::command_finished(args, code, start.elapsed(), 0, 0);
+    std::process::exit(code);
 }
 const NAUTICAL_MESSAGES: &[&str] = &[
     "[ANCHOR] Dropping anchor and securing position...",
@@ -369,11 +394,18 @@ const BUILD_STAGES: &[&str] = &[
     "🏆 Polishing the final executable to a mirror shine...",
     "🚀 Loading binary into launch tube - ready for deployment...",
 ];
-pub fn run_cargo_with_display(args: &[&str]) {
+/// Returns whether cargo exited successfully
+pub fn run_cargo_with_display(args: &[&str]) -> bool {
     let start_time = Instant::now();
     let mut error_deduplicator = ErrorDeduplicator::new();
     let error_prioritizer = ErrorPrioritizer::new();
     let mut build_coach = BuildCoach::new();
+    let hooks = HookSet::load();
+    let mut hook_context = HookContext::new(args);
+    if let Err(e) = hooks.run_pre_build(&hook_context) {
+        eprintln!("❌ {}", e);
+        return false;
+    }
     let mut child = Command::new("cargo")
         .args(args)
         .arg("--message-format=json")
@@ -392,10 +424,14 @@ pub fn run_cargo_with_display(args: &[&str]) {
     let mut warnings = Vec::new();
     let mut artifacts = Vec::new();
     let mut build_scripts = Vec::new();
+    let mut fix_suggestions = Vec::new();
     let error_count = Arc::new(AtomicUsize::new(0));
     let warning_count = Arc::new(AtomicUsize::new(0));
     let artifact_count = Arc::new(AtomicUsize::new(0));
     let multi_progress = MultiProgress::new();
+    if ci::is_ci() {
+        multi_progress.set_draw_target(ProgressDrawTarget::hidden());
+    }
     let main_pb = create_main_progress_bar();
     let main_pb = multi_progress.add(main_pb);
     let status_pb = create_status_bar();
@@ -428,6 +464,8 @@ pub fn run_cargo_with_display(args: &[&str]) {
             if let Some(msg) = parser::parse_cargo_message(&line) {
                 match msg.data {
                     MessageData::CompilerMessage(cm) => {
+                        fix_suggestions
+                            .extend(smart_parser::extract_suggestions(&cm.message));
                         match cm.message.level.as_str() {
                             "error" => {
                                 let parsed_error = parser::format_error(&cm.message);
@@ -544,10 +582,32 @@ pub fn run_cargo_with_display(args: &[&str]) {
         has_recurring_errors,
     };
     if let Some(tip) = build_coach.check_and_show_tip(&build_context) {
-        println!("\n{}", tip.cyan());
+        if !ci::is_ci() {
+            println!("\n{}", tip.cyan());
+        }
     }
     save_results(&errors, &warnings, &artifacts, &build_scripts, args);
+    let _ = smart_parser::save_fix_set(&fix_suggestions);
     record_build_metrics(args, elapsed, errors.len(), warnings.len(), status.success());
+    hook_context.exit_code = status.code();
+    hook_context.diagnostics = Diagnostics {
+        errors: errors.len(),
+        warnings: warnings.len(),
+        messages: errors
+            .iter()
+            .map(|e| format!("error: {}", e.message))
+            .chain(warnings.iter().map(|w| format!("warning: {}", w.message)))
+            .take(10)
+            .collect(),
+    };
+    hooks.run_post_build(&hook_context);
+    crate::status::command_finished(
+        args,
+        status.code().unwrap_or(1),
+        elapsed,
+        errors.len(),
+        warnings.len(),
+    );
     display_summary(
         &errors,
         &warnings,
@@ -562,9 +622,22 @@ pub fn run_cargo_with_display(args: &[&str]) {
     }
     if !errors.is_empty() || !warnings.is_empty() {
         checklist::generate_checklist(&errors, &warnings);
-        println!("\n📋 Run {} to see your checklist", "cm checklist".yellow());
+        println!(
+            "\n{}", ci::decorate(&format!("📋 Run {} to see your checklist",
+            "cm checklist".yellow()))
+        );
+    }
+    if ci::is_ci() {
+        return status.success();
     }
     display_view_options(&errors, &warnings, &artifacts, &build_scripts);
+    if !fix_suggestions.is_empty() {
+        println!(
+            "  {} - {} auto-fixes available", "cm view fixes".cyan(), fix_suggestions
+            .len()
+        );
+    }
+    status.success()
 }
 fn create_main_progress_bar() -> ProgressBar {
     let pb = ProgressBar::new_spinner();
@@ -606,36 +679,30 @@ fn save_results(
     build_scripts: &[parser::BuildScriptExecuted],
     args: &[&str],
 ) {
-    let shipwreck = dirs::home_dir().unwrap().join(".shipwreck");
-    fs::create_dir_all(&shipwreck).unwrap();
-    let error_file = shipwreck.join("errors").join("latest.txt");
-    fs::create_dir_all(error_file.parent().unwrap()).unwrap();
-    let mut f = fs::File::create(&error_file).unwrap();
-    for error in errors {
-        writeln!(f, "{}", error).unwrap();
-    }
-    let warning_file = shipwreck.join("warnings").join("latest.txt");
-    fs::create_dir_all(warning_file.parent().unwrap()).unwrap();
-    let mut f = fs::File::create(&warning_file).unwrap();
-    for warning in warnings {
-        writeln!(f, "{}", warning).unwrap();
-    }
-    let artifact_file = shipwreck.join("artifacts").join("latest.txt");
-    fs::create_dir_all(artifact_file.parent().unwrap()).unwrap();
-    let mut f = fs::File::create(&artifact_file).unwrap();
-    for artifact in artifacts {
-        writeln!(f, "📦 {} -> {}", artifact.target.name, artifact.filenames.join(", "))
-            .unwrap();
-    }
-    let script_file = shipwreck.join("scripts").join("latest.txt");
-    fs::create_dir_all(script_file.parent().unwrap()).unwrap();
-    let mut f = fs::File::create(&script_file).unwrap();
-    for script in build_scripts {
-        writeln!(
-            f, "🔨 {} -> libs: {}, paths: {}, cfgs: {}", script.package_id, script
-            .linked_libs.len(), script.linked_paths.len(), script.cfgs.len()
-        )
-            .unwrap();
+    let shipwreck = crate::project::data_dir().unwrap();
+    let lines = |items: Vec<String>| items.iter().map(|item| format!("{}\n", item)).collect::<String>();
+    let scripts = build_scripts
+        .iter()
+        .map(|script| {
+            format!(
+                "🔨 {} -> libs: {}, paths: {}, cfgs: {}", script.package_id, script
+                .linked_libs.len(), script.linked_paths.len(), script.cfgs.len()
+            )
+        })
+        .collect();
+    {
+        // A parallel build finishing at the same time must not leave one build's errors
+        // next to another's warnings
+        let _lock = crate::storage::lock(&shipwreck.join("latest")).unwrap();
+        let write = |dir: &str, content: String| {
+            crate::storage::write_atomic(&shipwreck.join(dir).join("latest.txt"), content).unwrap()
+        };
+        write("errors", lines(errors.iter().map(|e| e.to_string()).collect()));
+        write("warnings", lines(warnings.iter().map(|w| w.to_string()).collect()));
+        write("scripts", lines(scripts));
+    }
+    if let Err(e) = crate::artifacts::record_build(artifacts) {
+        log::warn!("Failed to update artifact catalog: {}", e);
     }
     history::save_to_history(args.join(" "), errors.to_vec(), warnings.to_vec());
 }
@@ -649,15 +716,19 @@ fn display_summary(
 ) {
     println!("\n{}", "═".repeat(60).blue());
     if success && errors.is_empty() {
-        println!("{}", "✅ Build Successful!".green().bold());
+        println!("{}", ci::decorate("✅ Build Successful!").green().bold());
     } else {
-        println!("{}", "❌ Build Failed!".red().bold());
+        println!("{}", ci::decorate("❌ Build Failed!").red().bold());
     }
-    println!("⏱️  Build time: {:.1}s", elapsed.as_secs_f32());
-    println!("📁 Files generated: {}", artifacts.len());
-    println!("🔨 Build scripts: {}", build_scripts.len());
+    println!(
+        "{}", ci::decorate(&format!("⏱️  Build time: {:.1}s", elapsed.as_secs_f32()))
+    );
+    println!("{}", ci::decorate(&format!("📁 Files generated: {}", artifacts.len())));
+    println!("{}", ci::decorate(&format!("🔨 Build scripts: {}", build_scripts.len())));
     if !errors.is_empty() {
-        println!("\n{}", format!("🔴 {} Error(s):", errors.len()) .red().bold());
+        println!(
+            "\n{}", ci::decorate(&format!("🔴 {} Error(s):", errors.len())).red().bold()
+        );
         for (i, error) in errors.iter().take(3).enumerate() {
             println!("  {}. {}", i + 1, error);
         }
@@ -667,7 +738,8 @@ fn display_summary(
     }
     if !warnings.is_empty() {
         println!(
-            "\n{}", format!("⚠️  {} Warning(s):", warnings.len()) .yellow().bold()
+            "\n{}", ci::decorate(&format!("⚠️  {} Warning(s):", warnings.len())).yellow()
+            .bold()
         );
         for (i, warning) in warnings.iter().take(3).enumerate() {
             println!("  {}. {}", i + 1, warning);
@@ -723,9 +795,10 @@ fn record_build_metrics(
             crate_units_compiled,
             memory_peak_mb: None,
             cpu_usage_percent: None,
+            environment: crate::diff_build::BuildEnvironment::capture(),
         };
         if let Err(e) = tide.record_build(metrics) {
-            eprintln!("⚠️  Failed to record build metrics: {}", e);
+            log::warn!("Failed to record build metrics: {}", e);
         }
     }
 }
@@ -0,0 +1,312 @@
+use crate::captain::config::ConfigManager;
+use crate::history;
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+#[derive(Subcommand, Debug)]
+pub enum DocsAction {
+    Build {
+        /// Include private items
+        #[arg(long)]
+        private: bool,
+        #[arg(long)]
+        open: bool,
+    },
+    /// Share of public items with doc comments, per module
+    Coverage {
+        /// Fail below this percentage (default: the docs.coverage_threshold config key)
+        #[arg(long)]
+        threshold: Option<f64>,
+    },
+    /// Broken intra-doc links
+    Deadlinks,
+}
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModuleCoverage {
+    pub documented: usize,
+    pub total: usize,
+    pub undocumented: Vec<String>,
+}
+impl ModuleCoverage {
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.documented as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+pub type CoverageReport = BTreeMap<String, ModuleCoverage>;
+fn has_docs(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("doc"))
+}
+fn is_test_only(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("cfg")
+            && attr.parse_args::<syn::Ident>().is_ok_and(|ident| ident == "test")
+    })
+}
+fn is_pub(vis: &syn::Visibility) -> bool {
+    matches!(vis, syn::Visibility::Public(_))
+}
+fn tally(report: &mut CoverageReport, module: &str, name: String, documented: bool) {
+    let entry = report.entry(module.to_string()).or_default();
+    entry.total += 1;
+    if documented {
+        entry.documented += 1;
+    } else {
+        entry.undocumented.push(name);
+    }
+}
+/// Counts `pub` items the way rustdoc's coverage does, minus struct fields and variants:
+/// functions, types, traits and their methods, constants, statics and public modules.
+/// Private modules are skipped since their items are not part of the public API.
+/// `dir` is where `mod child;` declarations of this module are looked up.
+pub fn collect_items(items: &[syn::Item], module: &str, dir: Option<&Path>, report: &mut CoverageReport) {
+    report.entry(module.to_string()).or_default();
+    for item in items {
+        let (vis, attrs, name) = match item {
+            syn::Item::Fn(i) => (&i.vis, &i.attrs, i.sig.ident.to_string()),
+            syn::Item::Struct(i) => (&i.vis, &i.attrs, i.ident.to_string()),
+            syn::Item::Enum(i) => (&i.vis, &i.attrs, i.ident.to_string()),
+            syn::Item::Union(i) => (&i.vis, &i.attrs, i.ident.to_string()),
+            syn::Item::Type(i) => (&i.vis, &i.attrs, i.ident.to_string()),
+            syn::Item::Const(i) => (&i.vis, &i.attrs, i.ident.to_string()),
+            syn::Item::Static(i) => (&i.vis, &i.attrs, i.ident.to_string()),
+            syn::Item::Trait(i) => {
+                if is_pub(&i.vis) {
+                    for trait_item in &i.items {
+                        if let syn::TraitItem::Fn(f) = trait_item {
+                            let name = format!("{}::{}", i.ident, f.sig.ident);
+                            tally(report, module, name, has_docs(&f.attrs));
+                        }
+                    }
+                }
+                (&i.vis, &i.attrs, i.ident.to_string())
+            }
+            syn::Item::Impl(i) if i.trait_.is_none() && !is_test_only(&i.attrs) => {
+                let self_ty = match &*i.self_ty {
+                    syn::Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+                    _ => None,
+                }
+                .unwrap_or_else(|| "impl".to_string());
+                for impl_item in &i.items {
+                    if let syn::ImplItem::Fn(f) = impl_item {
+                        if is_pub(&f.vis) {
+                            let name = format!("{}::{}", self_ty, f.sig.ident);
+                            tally(report, module, name, has_docs(&f.attrs));
+                        }
+                    }
+                }
+                continue;
+            }
+            syn::Item::Mod(i) if is_pub(&i.vis) && !is_test_only(&i.attrs) => {
+                let child = if module.is_empty() { i.ident.to_string() } else { format!("{}::{}", module, i.ident) };
+                let child_dir = dir.map(|d| d.join(i.ident.to_string()));
+                match &i.content {
+                    Some((_, items)) => {
+                        tally(report, module, i.ident.to_string(), has_docs(&i.attrs));
+                        collect_items(items, &child, child_dir.as_deref(), report);
+                    }
+                    None => {
+                        let file = dir.and_then(|d| {
+                            [d.join(format!("{}.rs", i.ident)), d.join(i.ident.to_string()).join("mod.rs")]
+                                .into_iter()
+                                .find(|p| p.exists())
+                        });
+                        let parsed = file.as_ref().and_then(|f| syn::parse_file(&fs::read_to_string(f).ok()?).ok());
+                        let documented = has_docs(&i.attrs) || parsed.as_ref().is_some_and(|p| has_docs(&p.attrs));
+                        tally(report, module, i.ident.to_string(), documented);
+                        if let Some(parsed) = parsed {
+                            collect_items(&parsed.items, &child, child_dir.as_deref(), report);
+                        }
+                    }
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        if is_pub(vis) && !is_test_only(attrs) {
+            tally(report, module, name, has_docs(attrs));
+        }
+    }
+}
+fn lib_root() -> Result<PathBuf> {
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .no_deps()
+        .exec()
+        .context("Failed to read cargo metadata - run this inside a cargo project")?;
+    let package = metadata.root_package().context("No package in the current directory")?;
+    package
+        .targets
+        .iter()
+        .find(|t| t.kind.iter().any(|k| k == "lib" || k == "proc-macro"))
+        .map(|t| t.src_path.clone().into_std_path_buf())
+        .context("Docs coverage needs a library target")
+}
+pub fn coverage() -> Result<CoverageReport> {
+    let root = lib_root()?;
+    let file = syn::parse_file(&fs::read_to_string(&root)?)
+        .with_context(|| format!("Failed to parse {}", root.display()))?;
+    let mut report = CoverageReport::new();
+    collect_items(&file.items, "crate", root.parent(), &mut report);
+    report.retain(|_, module| module.total > 0);
+    Ok(report)
+}
+fn total(report: &CoverageReport) -> ModuleCoverage {
+    report.values().fold(ModuleCoverage::default(), |mut sum, module| {
+        sum.documented += module.documented;
+        sum.total += module.total;
+        sum
+    })
+}
+fn handle_coverage(threshold: Option<f64>) -> Result<()> {
+    let threshold = threshold.or_else(|| {
+        ConfigManager::new().ok()?.get("docs.coverage_threshold")?.trim().parse().ok()
+    });
+    let report = coverage()?;
+    println!("📚 {}", "Documentation coverage".bold().blue());
+    for (module, stats) in &report {
+        let percent = format!("{:5.1}%", stats.percent());
+        let percent = match threshold {
+            Some(t) if stats.percent() < t => percent.red(),
+            _ => percent.green(),
+        };
+        println!("  {} {:>4}/{:<4} {}", percent, stats.documented, stats.total, module.cyan());
+    }
+    let overall = total(&report);
+    println!("\n  {} {:.1}% ({} of {} public items)", "Total".bold(), overall.percent(), overall.documented, overall.total);
+    let undocumented: Vec<String> = report
+        .iter()
+        .flat_map(|(module, stats)| stats.undocumented.iter().map(move |item| format!("{}::{}", module, item)))
+        .collect();
+    if !undocumented.is_empty() {
+        println!("\n  {}", "Undocumented:".dimmed());
+        for item in undocumented.iter().take(15) {
+            println!("    {}", item);
+        }
+        if undocumented.len() > 15 {
+            println!("    ... and {} more", undocumented.len() - 15);
+        }
+    }
+    let failed = threshold.filter(|t| overall.percent() < *t);
+    let errors = failed
+        .map(|t| vec![format!("docs coverage {:.1}% is below the {:.1}% threshold", overall.percent(), t)])
+        .unwrap_or_default();
+    history::record("cm docs coverage".to_string(), errors, undocumented);
+    if let Some(t) = failed {
+        println!("\n❌ Coverage {:.1}% is below the {:.1}% threshold", overall.percent(), t);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+/// `file:line: message` for every intra-doc link lint in `cargo doc --message-format=json` output
+pub fn parse_link_warnings(json_lines: &str) -> Vec<String> {
+    json_lines
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|msg| msg["reason"] == "compiler-message")
+        .map(|msg| msg["message"].clone())
+        .filter(|message| message["code"]["code"].as_str().is_some_and(|code| code.contains("intra_doc_links")))
+        .map(|message| {
+            let span = message["spans"].as_array().and_then(|spans| spans.first()).cloned().unwrap_or_default();
+            format!(
+                "{}:{}: {}",
+                span["file_name"].as_str().unwrap_or("?"),
+                span["line_start"].as_u64().unwrap_or(0),
+                message["message"].as_str().unwrap_or("broken link")
+            )
+        })
+        .collect()
+}
+fn handle_deadlinks() -> Result<()> {
+    println!("🔗 Checking intra-doc links...");
+    let mut child = Command::new("cargo")
+        .args(["doc", "--no-deps", "--document-private-items", "--message-format=json"])
+        .env("RUSTDOCFLAGS", "-W rustdoc::broken_intra_doc_links -W rustdoc::private_intra_doc_links")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+    let mut output = String::new();
+    for line in BufReader::new(child.stdout.take().unwrap()).lines() {
+        output.push_str(&line?);
+        output.push('\n');
+    }
+    let status = child.wait()?;
+    let mut broken = parse_link_warnings(&output);
+    broken.dedup();
+    history::record("cm docs deadlinks".to_string(), broken.clone(), Vec::new());
+    if !status.success() {
+        anyhow::bail!("cargo doc failed");
+    }
+    if broken.is_empty() {
+        println!("✅ No broken intra-doc links");
+        return Ok(());
+    }
+    println!("❌ {} broken intra-doc link(s):", broken.len());
+    for link in &broken {
+        println!("  {}", link);
+    }
+    std::process::exit(1);
+}
+pub fn handle_docs_command(action: DocsAction) -> Result<()> {
+    match action {
+        DocsAction::Build { private, open } => {
+            let mut args = vec!["doc", "--no-deps"];
+            if private {
+                args.push("--document-private-items");
+            }
+            if open {
+                args.push("--open");
+            }
+            if !crate::display::run_cargo_with_display(&args) {
+                std::process::exit(1);
+            }
+        }
+        DocsAction::Coverage { threshold } => handle_coverage(threshold)?,
+        DocsAction::Deadlinks => handle_deadlinks()?,
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_coverage_counts_public_items() {
+        let file: syn::File = syn::parse_str(
+            r#"
+            /// Documented
+            pub fn documented() {}
+            pub fn undocumented() {}
+            fn private() {}
+            pub struct Thing;
+            impl Thing {
+                /// New
+                pub fn new() -> Self { Thing }
+                pub fn run(&self) {}
+            }
+            /// Inline module
+            pub mod inner {
+                /// Yes
+                pub const X: u8 = 1;
+            }
+            #[cfg(test)]
+            pub mod tests { pub fn helper() {} }
+            "#,
+        )
+        .unwrap();
+        let mut report = CoverageReport::new();
+        collect_items(&file.items, "crate", None, &mut report);
+        let root = &report["crate"];
+        assert_eq!((root.documented, root.total), (3, 6));
+        assert_eq!(root.undocumented, vec!["undocumented", "Thing", "Thing::run"]);
+        assert_eq!(report["crate::inner"].percent(), 100.0);
+        let json = r#"{"reason":"compiler-message","message":{"message":"unresolved link to `Foo`","code":{"code":"rustdoc::broken_intra_doc_links"},"spans":[{"file_name":"src/lib.rs","line_start":3}]}}"#;
+        assert_eq!(parse_link_warnings(json), vec!["src/lib.rs:3: unresolved link to `Foo`"]);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::net::{self, NetworkSettings};
+use anyhow::Result;
+use clap::Subcommand;
+use colored::*;
+use std::time::{Duration, Instant};
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+#[derive(Subcommand, Debug)]
+pub enum DoctorAction {
+    /// Check that cargo-mate's endpoints are reachable through the configured proxy and CA bundle
+    Network,
+}
+/// Every host cargo-mate talks to, by what it is used for
+fn endpoints() -> Vec<(&'static str, String)> {
+    let settings = crate::captain::manage::load_settings();
+    vec![
+        ("cargo-mate API", crate::user::api_base_url()),
+        ("Captain downloads", format!("{}/", crate::captain::manage::release_url(&settings))),
+        ("Marketplace", "https://cargo.do/marketplace".to_string()),
+        ("crates.io index", "https://index.crates.io/config.json".to_string()),
+    ]
+}
+/// The whole source chain, since reqwest's own message rarely names the TLS or proxy problem
+fn describe(error: &reqwest::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    message
+}
+fn hint(error: &reqwest::Error, message: &str) -> &'static str {
+    let lower = message.to_lowercase();
+    if lower.contains("certificate") || lower.contains("unknownissuer") {
+        "the proxy's certificate isn't trusted - point network.ca_bundle at your corporate CA bundle"
+    } else if error.is_timeout() {
+        "timed out - raise network.timeout or check the proxy"
+    } else if error.is_connect() {
+        "could not connect - check network.proxy (or HTTPS_PROXY) and NO_PROXY"
+    } else {
+        "request failed"
+    }
+}
+fn show_settings(settings: &NetworkSettings) {
+    let proxy = match &settings.proxy {
+        Some(proxy) => format!("{} (network.proxy)", net::redact(proxy)),
+        None => ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().map(|value| format!("{} ({})", net::redact(&value), name)))
+            .unwrap_or_else(|| "none".to_string()),
+    };
+    println!("  {:<10} {}", "Offline".cyan(), if net::is_offline() { "yes".yellow() } else { "no".normal() });
+    println!("  {:<10} {}", "Proxy".cyan(), proxy);
+    match (&settings.ca_bundle, settings.certificates()) {
+        (None, _) => println!("  {:<10} system roots", "CA bundle".cyan()),
+        (Some(path), Ok(certificates)) => {
+            println!("  {:<10} {} ({} certificate(s))", "CA bundle".cyan(), path.display(), certificates.len())
+        }
+        (Some(_), Err(e)) => println!("  {:<10} {}", "CA bundle".cyan(), e.to_string().red()),
+    }
+    match settings.timeout {
+        Some(timeout) => println!("  {:<10} {}s", "Timeout".cyan(), timeout.as_secs()),
+        None => println!("  {:<10} per-feature defaults", "Timeout".cyan()),
+    }
+}
+/// False when any endpoint is unreachable
+pub fn check_network() -> Result<bool> {
+    let settings = NetworkSettings::load();
+    println!("{}", "🩺 Network".bold().blue());
+    show_settings(&settings);
+    if net::is_offline() {
+        println!("\n⏸️  Offline mode is on - no endpoints probed");
+        return Ok(true);
+    }
+    let client = match net::client(PROBE_TIMEOUT) {
+        Ok(client) => client,
+        Err(e) => {
+            println!("\n❌ {}", format!("Could not build an HTTP client: {:#}", e).red());
+            return Ok(false);
+        }
+    };
+    println!();
+    let mut healthy = true;
+    for (purpose, url) in endpoints() {
+        let started = Instant::now();
+        match client.get(&url).send() {
+            // Any HTTP answer means the proxy and TLS path work
+            Ok(response) => println!(
+                "  {} {:<18} {} {}",
+                "✓".green(),
+                purpose,
+                format!("HTTP {}", response.status().as_u16()).dimmed(),
+                format!("{}ms", started.elapsed().as_millis()).dimmed()
+            ),
+            Err(error) => {
+                healthy = false;
+                let message = describe(&error);
+                println!("  {} {:<18} {}", "✗".red(), purpose, url.dimmed());
+                println!("      {}", hint(&error, &message).yellow());
+                println!("      {}", message.dimmed());
+            }
+        }
+    }
+    println!();
+    if healthy {
+        println!("✅ All endpoints reachable");
+    } else {
+        println!("❌ Some endpoints are unreachable - see the hints above");
+    }
+    Ok(healthy)
+}
+pub fn handle_doctor_command(action: DoctorAction) -> Result<()> {
+    match action {
+        DoctorAction::Network => {
+            if !check_network()? {
+                std::process::exit(1);
+            }
+        }
+    }
+    Ok(())
+}
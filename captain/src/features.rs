@@ -0,0 +1,410 @@
+use crate::anchor::AnchorManager;
+use crate::outdated::{manifest_dependencies, ManifestDependency};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use colored::*;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use toml_edit::DocumentMut;
+/// The feature request a manifest makes of one dependency
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureSet {
+    pub default_features: bool,
+    pub features: Vec<String>,
+}
+impl std::fmt::Display for FeatureSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+        if self.default_features {
+            parts.push("default".to_string());
+        }
+        parts.extend(self.features.iter().cloned());
+        if parts.is_empty() {
+            write!(f, "(none)")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct Proposal {
+    pub manifest: PathBuf,
+    pub dependency: ManifestDependency,
+    pub current: FeatureSet,
+    pub minimal: FeatureSet,
+    /// Packages that drop out of the resolved graph with the minimal set
+    pub removed_crates: Vec<String>,
+    /// From the last `cargo build --timings` report, when there is one
+    pub estimated_seconds: Option<f64>,
+}
+fn entry<'a>(doc: &'a DocumentMut, dependency: &ManifestDependency) -> Option<&'a toml_edit::Item> {
+    let mut item = doc.as_item();
+    for key in &dependency.table {
+        item = item.get(key)?;
+    }
+    item.get(&dependency.key)
+}
+pub fn current_features(doc: &DocumentMut, dependency: &ManifestDependency) -> Option<FeatureSet> {
+    let entry = entry(doc, dependency)?;
+    if entry.as_str().is_some() {
+        return Some(FeatureSet { default_features: true, features: Vec::new() });
+    }
+    let spec = entry.as_table_like()?;
+    let default_features = ["default-features", "default_features"]
+        .iter()
+        .find_map(|key| spec.get(key).and_then(|v| v.as_bool()))
+        .unwrap_or(true);
+    let features = spec
+        .get("features")
+        .and_then(|f| f.as_array())
+        .map(|f| f.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    Some(FeatureSet { default_features, features })
+}
+/// Rewrites the dependency's `default-features` and `features`, turning a bare version string
+/// into an inline table when needed
+pub fn set_features(doc: &mut DocumentMut, dependency: &ManifestDependency, set: &FeatureSet) -> bool {
+    let mut item = doc.as_item_mut();
+    for key in &dependency.table {
+        match item.get_mut(key) {
+            Some(next) => item = next,
+            None => return false,
+        }
+    }
+    let Some(entry) = item.get_mut(&dependency.key) else {
+        return false;
+    };
+    if let Some(value) = entry.as_value().filter(|v| v.is_str()) {
+        let mut table = toml_edit::InlineTable::new();
+        table.insert("version", value.as_str().unwrap_or_default().into());
+        let mut table = toml_edit::Value::InlineTable(table);
+        *table.decor_mut() = value.decor().clone();
+        *entry = toml_edit::Item::Value(table);
+    }
+    let Some(spec) = entry.as_table_like_mut() else {
+        return false;
+    };
+    spec.remove("default_features");
+    if set.default_features {
+        spec.remove("default-features");
+    } else {
+        spec.insert("default-features", toml_edit::value(false));
+    }
+    if set.features.is_empty() {
+        spec.remove("features");
+    } else {
+        let features: toml_edit::Array = set.features.iter().map(String::as_str).collect();
+        spec.insert("features", toml_edit::value(features));
+    }
+    if let Some(table) = entry.as_inline_table_mut() {
+        table.fmt();
+    }
+    true
+}
+/// Greedy search for the smallest feature set that still passes: first without default
+/// features (spelling out the crate's defaults if the bare set fails), then without each
+/// remaining feature in turn
+pub fn minimize(
+    current: &FeatureSet,
+    default_members: &[String],
+    mut check: impl FnMut(&FeatureSet) -> Result<bool>,
+) -> Result<FeatureSet> {
+    let mut failed: Vec<FeatureSet> = Vec::new();
+    let mut passes = |set: &FeatureSet| -> Result<bool> {
+        if failed.contains(set) {
+            return Ok(false);
+        }
+        let passed = check(set)?;
+        if !passed {
+            failed.push(set.clone());
+        }
+        Ok(passed)
+    };
+    let mut best = current.clone();
+    if best.default_features {
+        let without = FeatureSet { default_features: false, features: best.features.clone() };
+        if passes(&without)? {
+            best = without;
+        } else {
+            let mut expanded = without;
+            for member in default_members.iter().filter(|m| !m.contains(':') && !m.contains('/')) {
+                if !expanded.features.contains(member) {
+                    expanded.features.push(member.clone());
+                }
+            }
+            if expanded.features.len() > best.features.len() && passes(&expanded)? {
+                best = expanded;
+            }
+        }
+    }
+    let mut index = 0;
+    while index < best.features.len() {
+        let mut trial = best.clone();
+        trial.features.remove(index);
+        if passes(&trial)? {
+            best = trial;
+        } else {
+            index += 1;
+        }
+    }
+    Ok(best)
+}
+/// Edits a manifest in place for trial checks and puts it and Cargo.lock back when dropped.
+/// A `.cm-features` backup next to the manifest survives a crash and is restored on the next run
+struct Trial {
+    manifest: PathBuf,
+    original: String,
+    lock: PathBuf,
+    original_lock: Option<String>,
+    target_dir: PathBuf,
+    root_manifest: PathBuf,
+}
+fn backup_path(manifest: &Path) -> PathBuf {
+    manifest.with_extension("toml.cm-features")
+}
+impl Trial {
+    fn new(manifest: &Path, root: &Path, target_dir: &Path) -> Result<Self> {
+        let backup = backup_path(manifest);
+        if backup.exists() {
+            fs::copy(&backup, manifest)?;
+            eprintln!("♻️  Restored {} from an interrupted run", manifest.display());
+        }
+        let original = fs::read_to_string(manifest)?;
+        fs::write(&backup, &original)?;
+        let lock = root.join("Cargo.lock");
+        Ok(Self {
+            manifest: manifest.to_path_buf(),
+            original,
+            original_lock: fs::read_to_string(&lock).ok(),
+            lock,
+            target_dir: target_dir.to_path_buf(),
+            root_manifest: root.join("Cargo.toml"),
+        })
+    }
+    fn check(&self, doc: &DocumentMut) -> Result<bool> {
+        fs::write(&self.manifest, doc.to_string())?;
+        let mut command = Command::new("cargo");
+        command
+            .args(["check", "--workspace", "--all-targets", "--all-features", "--quiet", "--manifest-path"])
+            .arg(&self.root_manifest)
+            .env("CARGO_TARGET_DIR", &self.target_dir)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        crate::net::NetworkSettings::load().apply_env(&mut command);
+        Ok(command.status().context("Failed to run cargo check")?.success())
+    }
+    /// "name version" of every package cargo resolves with `doc` in place
+    fn resolved(&self, doc: &DocumentMut) -> Result<BTreeSet<String>> {
+        fs::write(&self.manifest, doc.to_string())?;
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .manifest_path(&self.root_manifest)
+            .features(cargo_metadata::CargoOpt::AllFeatures)
+            .exec()?;
+        let names: HashMap<_, _> = metadata.packages.iter().map(|p| (&p.id, format!("{} {}", p.name, p.version))).collect();
+        Ok(metadata
+            .resolve
+            .map(|resolve| resolve.nodes.iter().filter_map(|node| names.get(&node.id).cloned()).collect())
+            .unwrap_or_default())
+    }
+}
+impl Drop for Trial {
+    fn drop(&mut self) {
+        let _ = fs::write(&self.manifest, &self.original);
+        if let Some(lock) = &self.original_lock {
+            let _ = fs::write(&self.lock, lock);
+        }
+        let _ = fs::remove_file(backup_path(&self.manifest));
+    }
+}
+/// Seconds per crate from the newest timing report
+fn crate_timings() -> HashMap<String, f64> {
+    crate::tide_report::read_timing_runs(&crate::tide_report::timings_dir())
+        .pop()
+        .map(|run| run.crates.into_iter().collect())
+        .unwrap_or_default()
+}
+pub fn find_proposals(only: &[String]) -> Result<Vec<Proposal>> {
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .exec()
+        .context("Failed to read cargo metadata - run this inside a cargo project")?;
+    let root = PathBuf::from(metadata.workspace_root.as_std_path());
+    let target_dir = PathBuf::from(metadata.target_directory.as_std_path()).join("features");
+    let mut manifests = vec![root.join("Cargo.toml")];
+    for package in metadata.workspace_packages() {
+        let manifest = PathBuf::from(package.manifest_path.as_std_path());
+        if !manifests.contains(&manifest) {
+            manifests.push(manifest);
+        }
+    }
+    let defaults: HashMap<&str, Vec<String>> = metadata
+        .packages
+        .iter()
+        .map(|p| (p.name.as_str(), p.features.get("default").cloned().unwrap_or_default()))
+        .collect();
+    let timings = crate_timings();
+    let mut proposals = Vec::new();
+    let mut baseline_checked = false;
+    for manifest in manifests {
+        let Ok(content) = fs::read_to_string(&manifest) else {
+            continue;
+        };
+        let mut doc: DocumentMut = content.parse().with_context(|| format!("Failed to parse {}", manifest.display()))?;
+        let dependencies: Vec<ManifestDependency> = manifest_dependencies(&doc)
+            .into_iter()
+            .filter(|d| only.is_empty() || only.iter().any(|name| name == &d.key || name == &d.name))
+            .collect();
+        if dependencies.is_empty() {
+            continue;
+        }
+        let trial = Trial::new(&manifest, &root, &target_dir)?;
+        if !baseline_checked {
+            eprintln!("🔍 Checking the unmodified workspace (cache in {})...", target_dir.display());
+            if !trial.check(&doc)? {
+                bail!("cargo check fails before any change; fix the build first");
+            }
+            baseline_checked = true;
+        }
+        for dependency in dependencies {
+            let Some(current) = current_features(&doc, &dependency) else {
+                continue;
+            };
+            if !current.default_features && current.features.is_empty() {
+                continue;
+            }
+            eprintln!("✂️  {} ({})", dependency.key.cyan(), current);
+            let members = defaults.get(dependency.name.as_str()).cloned().unwrap_or_default();
+            let minimal = minimize(&current, &members, |set| {
+                let mut candidate = doc.clone();
+                set_features(&mut candidate, &dependency, set);
+                let passed = trial.check(&candidate)?;
+                eprintln!("   {} {}", if passed { "✓".green() } else { "✗".red() }, set);
+                Ok(passed)
+            })?;
+            if minimal == current {
+                continue;
+            }
+            let before = trial.resolved(&doc)?;
+            set_features(&mut doc, &dependency, &minimal);
+            let removed_crates: Vec<String> = before.difference(&trial.resolved(&doc)?).cloned().collect();
+            let estimated_seconds = (!timings.is_empty()).then(|| {
+                removed_crates
+                    .iter()
+                    .filter_map(|c| c.split_whitespace().next().and_then(|name| timings.get(name)))
+                    .sum()
+            });
+            proposals.push(Proposal {
+                manifest: manifest.clone(),
+                dependency,
+                current,
+                minimal,
+                removed_crates,
+                estimated_seconds,
+            });
+        }
+    }
+    Ok(proposals)
+}
+fn print_proposals(proposals: &[Proposal]) {
+    if proposals.is_empty() {
+        println!("✅ Every dependency already asks only for the features it needs");
+        return;
+    }
+    println!("\n📦 {} ({})", "Feature pruning".bold().blue(), proposals.len());
+    for proposal in proposals {
+        println!(
+            "  {}  {} → {}  {}",
+            proposal.dependency.key.cyan(),
+            proposal.current,
+            proposal.minimal.to_string().green(),
+            format!("{} [{}]", proposal.manifest.display(), proposal.dependency.table.join(".")).dimmed()
+        );
+        if !proposal.removed_crates.is_empty() {
+            let savings = proposal.estimated_seconds.map(|s| format!(", ~{:.1}s of compile time", s)).unwrap_or_default();
+            println!("     drops {} crate(s){}: {}", proposal.removed_crates.len(), savings, proposal.removed_crates.join(", "));
+        }
+    }
+}
+pub fn apply_proposals(proposals: &[Proposal]) -> Result<usize> {
+    let anchor_name = format!("before-features-{}", Utc::now().format("%Y%m%d-%H%M%S"));
+    AnchorManager::new()?.save(&anchor_name, "Snapshot before applying cm features")?;
+    println!("⚓ Saved anchor {} (restore with 'cm anchor restore {}')", anchor_name.cyan(), anchor_name);
+    let mut by_manifest: HashMap<&Path, Vec<&Proposal>> = HashMap::new();
+    for proposal in proposals {
+        by_manifest.entry(proposal.manifest.as_path()).or_default().push(proposal);
+    }
+    let mut applied = 0;
+    for (manifest, proposals) in by_manifest {
+        let mut doc: DocumentMut = fs::read_to_string(manifest)?.parse()?;
+        for proposal in proposals {
+            if set_features(&mut doc, &proposal.dependency, &proposal.minimal) {
+                applied += 1;
+            }
+        }
+        fs::write(manifest, doc.to_string())?;
+    }
+    Ok(applied)
+}
+pub fn handle_features(crates: &[String], apply: bool, json: bool) -> Result<()> {
+    let proposals = find_proposals(crates)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&proposals)?);
+    } else {
+        print_proposals(&proposals);
+    }
+    if proposals.is_empty() {
+        return Ok(());
+    }
+    if !apply {
+        if !json {
+            println!("\n💡 Write these edits with 'cm features --apply'");
+        }
+        return Ok(());
+    }
+    let applied = apply_proposals(&proposals)?;
+    println!("✅ Pruned features of {} dependenc{}", applied, if applied == 1 { "y" } else { "ies" });
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn dependency(key: &str) -> ManifestDependency {
+        ManifestDependency {
+            name: key.to_string(),
+            key: key.to_string(),
+            table: vec!["dependencies".to_string()],
+            requirement: "1".to_string(),
+        }
+    }
+    #[test]
+    fn test_read_and_write_features() {
+        let mut doc: DocumentMut = "[dependencies]\nlog = \"0.4\" # logging\ntokio = { version = \"1\", features = [\"full\"] }\n"
+            .parse()
+            .unwrap();
+        let log = dependency("log");
+        assert_eq!(current_features(&doc, &log), Some(FeatureSet { default_features: true, features: vec![] }));
+        let minimal = FeatureSet { default_features: false, features: vec!["std".into()] };
+        assert!(set_features(&mut doc, &log, &minimal));
+        assert_eq!(current_features(&doc, &log), Some(minimal));
+        let tokio = dependency("tokio");
+        assert_eq!(current_features(&doc, &tokio).unwrap().features, vec!["full"]);
+        assert!(set_features(&mut doc, &tokio, &FeatureSet { default_features: true, features: vec![] }));
+        assert!(doc.to_string().contains("tokio = { version = \"1\" }"));
+        assert!(doc.to_string().contains("default-features = false, features = [\"std\"] } # logging"));
+    }
+    #[test]
+    fn test_minimize_expands_defaults() {
+        let current = FeatureSet { default_features: true, features: vec!["macros".into()] };
+        let defaults = vec!["rt".to_string(), "io".to_string(), "dep:mio".to_string()];
+        let mut checks = 0;
+        let minimal = minimize(&current, &defaults, |set| {
+            checks += 1;
+            Ok(set.default_features || set.features.contains(&"rt".to_string()))
+        })
+        .unwrap();
+        assert_eq!(minimal, FeatureSet { default_features: false, features: vec!["rt".into()] });
+        assert_eq!(checks, 5);
+    }
+}
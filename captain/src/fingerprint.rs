@@ -0,0 +1,135 @@
+use crate::history::HistoryEntry;
+use crate::parser::ParsedError;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+#[derive(Debug, Clone)]
+pub struct Occurrence {
+    pub fingerprint: String,
+    pub sample: String,
+    pub builds: usize,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+impl Occurrence {
+    pub fn summary(&self) -> String {
+        format!(
+            "seen in {} build(s) since {}", self.builds, self.first_seen
+            .format("%Y-%m-%d %H:%M")
+        )
+    }
+}
+pub fn normalize_message(message: &str) -> String {
+    let mut normalized = String::with_capacity(message.len());
+    let mut in_digits = false;
+    for c in message.trim().chars() {
+        if c.is_ascii_digit() {
+            if !in_digits {
+                normalized.push('#');
+            }
+            in_digits = true;
+            continue;
+        }
+        in_digits = false;
+        if c.is_whitespace() {
+            if !normalized.ends_with(' ') {
+                normalized.push(' ');
+            }
+        } else {
+            normalized.push(c);
+        }
+    }
+    normalized
+}
+pub fn fingerprint(code: &str, message: &str, file: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(code.as_bytes());
+    hasher.update([0]);
+    hasher.update(normalize_message(message).as_bytes());
+    hasher.update([0]);
+    hasher.update(file.replace('\\', "/").as_bytes());
+    hasher.finalize().iter().take(8).map(|b| format!("{:02x}", b)).collect()
+}
+pub fn of_stored(line: &str) -> String {
+    match ParsedError::parse_stored(line) {
+        Some(parsed) => fingerprint(&parsed.code, &parsed.message, &parsed.file),
+        None => fingerprint("", line, ""),
+    }
+}
+pub fn aggregate<'a>(
+    builds: impl IntoIterator<Item = (DateTime<Utc>, &'a [String])>,
+) -> Vec<Occurrence> {
+    let mut occurrences: Vec<Occurrence> = Vec::new();
+    for (timestamp, lines) in builds {
+        let mut seen_in_build: Vec<String> = Vec::new();
+        for line in lines {
+            let fingerprint = of_stored(line);
+            if seen_in_build.contains(&fingerprint) {
+                continue;
+            }
+            match occurrences.iter_mut().find(|o| o.fingerprint == fingerprint) {
+                Some(occurrence) => {
+                    occurrence.builds += 1;
+                    occurrence.first_seen = occurrence.first_seen.min(timestamp);
+                    if timestamp >= occurrence.last_seen {
+                        occurrence.last_seen = timestamp;
+                        occurrence.sample = line.clone();
+                    }
+                }
+                None => {
+                    occurrences
+                        .push(Occurrence {
+                            fingerprint: fingerprint.clone(),
+                            sample: line.clone(),
+                            builds: 1,
+                            first_seen: timestamp,
+                            last_seen: timestamp,
+                        })
+                }
+            }
+            seen_in_build.push(fingerprint);
+        }
+    }
+    occurrences.sort_by_key(|o| Reverse(o.last_seen));
+    occurrences
+}
+pub fn error_occurrences(history: &[HistoryEntry]) -> Vec<Occurrence> {
+    aggregate(history.iter().map(|entry| (entry.timestamp, entry.errors.as_slice())))
+}
+pub fn warning_occurrences(history: &[HistoryEntry]) -> Vec<Occurrence> {
+    aggregate(history.iter().map(|entry| (entry.timestamp, entry.warnings.as_slice())))
+}
+pub fn lookup<'a>(occurrences: &'a [Occurrence], line: &str) -> Option<&'a Occurrence> {
+    let fingerprint = of_stored(line);
+    occurrences.iter().find(|o| o.fingerprint == fingerprint)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn same_diagnostic_across_builds_collapses() {
+        let earlier = Utc::now() - chrono::Duration::hours(2);
+        let now = Utc::now();
+        let first = vec![
+            "[E0308] src/a.rs:10 - expected `u32`, found `i64` in 2 places".to_string(),
+            "[E0308] src/a.rs:12 - expected `u32`, found `i64` in 2 places".to_string(),
+        ];
+        let second = vec![
+            "[E0308] src/a.rs:14 - expected `u32`,  found `i64` in 3 places".to_string(),
+            "[E0425] src/b.rs:1 - cannot find value `x`".to_string(),
+        ];
+        let occurrences = aggregate(
+            vec![(earlier, first.as_slice()), (now, second.as_slice())],
+        );
+        assert_eq!(occurrences.len(), 2);
+        let mismatch = lookup(&occurrences, &first[0]).unwrap();
+        assert_eq!(mismatch.builds, 2);
+        assert_eq!(mismatch.first_seen, earlier);
+        assert_eq!(mismatch.last_seen, now);
+        assert!(mismatch.sample.contains("src/a.rs:14"));
+        assert_ne!(
+            fingerprint("E0308", "mismatched types", "src/a.rs"),
+            fingerprint("E0308", "mismatched types", "src/b.rs")
+        );
+    }
+}
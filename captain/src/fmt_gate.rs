@@ -0,0 +1,230 @@
+use crate::captain::config::ConfigManager;
+use crate::checklist;
+use crate::mutiny::MutinyMode;
+use anyhow::Result;
+use colored::*;
+use std::path::Path;
+use std::process::Command;
+const ITEM_PREFIX: &str = "[fmt-gate]";
+#[derive(Debug, Clone)]
+pub struct GatePolicy {
+    pub enabled: bool,
+    pub commands: Vec<String>,
+    pub fmt: bool,
+    pub clippy: bool,
+    pub deny: Vec<String>,
+    pub warn: Vec<String>,
+    pub allow: Vec<String>,
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub check: &'static str,
+    pub detail: String,
+}
+fn list(config: Option<&ConfigManager>, key: &str, default: &str) -> Vec<String> {
+    config
+        .and_then(|c| c.get(key))
+        .unwrap_or_else(|| default.to_string())
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+impl GatePolicy {
+    pub fn from_config(config: Option<&ConfigManager>) -> Self {
+        let flag = |key: &str, default: bool| {
+            config.map(|c| c.get_bool(key, default)).unwrap_or(default)
+        };
+        GatePolicy {
+            enabled: flag("gate.enabled", false),
+            commands: list(config, "gate.commands", "publish, release"),
+            fmt: flag("gate.fmt", true),
+            clippy: flag("gate.clippy", true),
+            deny: list(config, "gate.deny", "warnings"),
+            warn: list(config, "gate.warn", ""),
+            allow: list(config, "gate.allow", ""),
+        }
+    }
+    pub fn guards(&self, args: &[&str]) -> bool {
+        args.iter()
+            .find(|a| **a != "cargo" && !a.starts_with('+'))
+            .is_some_and(|sub| self.commands.iter().any(|c| c == sub))
+    }
+    pub fn clippy_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "clippy".to_string(), "--all-targets".to_string(), "--message-format=short"
+            .to_string(), "--".to_string(),
+        ];
+        for (flag, lints) in [("-D", &self.deny), ("-W", &self.warn), ("-A", &self.allow)] {
+            for lint in lints {
+                args.push(flag.to_string());
+                args.push(lint.clone());
+            }
+        }
+        args
+    }
+}
+pub fn parse_fmt_check(output: &str) -> Vec<Violation> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Diff in "))
+        .map(|rest| Violation {
+            check: "fmt",
+            detail: rest.trim_end_matches(':').to_string(),
+        })
+        .collect()
+}
+pub fn parse_clippy_short(output: &str) -> Vec<Violation> {
+    let mut violations: Vec<Violation> = Vec::new();
+    for line in output.lines() {
+        let Some((location, message)) = line.split_once(": error: ") else {
+            continue;
+        };
+        if location.contains(' ') || !location.contains(':') {
+            continue;
+        }
+        let violation = Violation {
+            check: "clippy",
+            detail: format!("{} - {}", location, message.trim()),
+        };
+        if !violations.contains(&violation) {
+            violations.push(violation);
+        }
+    }
+    violations
+}
+fn run_fmt() -> Result<Vec<Violation>> {
+    let output = Command::new("cargo").args(["fmt", "--all", "--", "--check"]).output()?;
+    if output.status.success() {
+        return Ok(Vec::new());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let violations = parse_fmt_check(&stdout);
+    if violations.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr.lines().next().unwrap_or("cargo fmt --check failed").to_string();
+        return Ok(vec![Violation { check: "fmt", detail }]);
+    }
+    Ok(violations)
+}
+fn run_clippy(policy: &GatePolicy) -> Result<Vec<Violation>> {
+    let output = Command::new("cargo").args(policy.clippy_args()).output()?;
+    if output.status.success() {
+        return Ok(Vec::new());
+    }
+    let violations = parse_clippy_short(&String::from_utf8_lossy(&output.stderr));
+    if violations.is_empty() {
+        let detail = "cargo clippy failed".to_string();
+        return Ok(vec![Violation { check: "clippy", detail }]);
+    }
+    Ok(violations)
+}
+pub fn run_checks(policy: &GatePolicy) -> Result<Vec<Violation>> {
+    let mut violations = Vec::new();
+    if policy.fmt {
+        println!("🧹 {}", "cargo fmt --check".cyan());
+        violations.extend(run_fmt()?);
+    }
+    if policy.clippy {
+        println!("📎 {}", format!("cargo {}", policy.clippy_args().join(" ")).cyan());
+        violations.extend(run_clippy(policy)?);
+    }
+    Ok(violations)
+}
+pub fn record_violations(path: &Path, violations: &[Violation]) -> Result<usize> {
+    checklist::update_items(path, |items| {
+        let mut added = 0;
+        for violation in violations {
+            let text = format!("{} {}: {}", ITEM_PREFIX, violation.check, violation.detail);
+            if items.iter().any(|item| !item.done && item.text == text) {
+                continue;
+            }
+            checklist::add_item(items, &text);
+            added += 1;
+        }
+        added
+    })
+}
+fn report(violations: &[Violation]) -> Result<()> {
+    for violation in violations.iter().take(20) {
+        println!("  ❌ {} - {}", violation.check.yellow(), violation.detail);
+    }
+    if violations.len() > 20 {
+        println!("  … and {} more", violations.len() - 20);
+    }
+    let added = record_violations(&checklist::items_file()?, violations)?;
+    if added > 0 {
+        println!("📋 Added {} item(s) to the checklist ('cm checklist show')", added);
+    }
+    Ok(())
+}
+pub fn enforce(args: &[&str]) -> Result<bool> {
+    let config = ConfigManager::new().ok();
+    let policy = GatePolicy::from_config(config.as_ref());
+    if !policy.enabled || !policy.guards(args) {
+        return Ok(true);
+    }
+    println!("🚧 {}", "Format and lint gate".bold().blue());
+    let violations = run_checks(&policy)?;
+    if violations.is_empty() {
+        println!("✅ Gate passed");
+        return Ok(true);
+    }
+    report(&violations)?;
+    if let Some(reason) = MutinyMode::new()?.gate_bypass() {
+        println!(
+            "🏴‍☠️ {} - {}", "Gate bypassed by mutiny".red().bold(), reason.yellow()
+        );
+        return Ok(true);
+    }
+    println!(
+        "\n🛑 {} - {} violation(s)", "Command blocked".red().bold(), violations.len()
+    );
+    println!(
+        "💡 Fix them, or bypass the gate for an hour with 'cm mutiny activate \"<reason>\" --gate'"
+    );
+    Ok(false)
+}
+pub fn handle_fmt_gate() -> Result<()> {
+    let config = ConfigManager::new().ok();
+    let policy = GatePolicy::from_config(config.as_ref());
+    if !policy.enabled {
+        println!(
+            "💡 The gate is off for wrapped commands; enable it with 'cm config set gate.enabled true --local'"
+        );
+    }
+    let violations = run_checks(&policy)?;
+    if violations.is_empty() {
+        println!("✅ Gate passed");
+        return Ok(());
+    }
+    report(&violations)?;
+    std::process::exit(1);
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn guards_configured_commands_and_parses_output() {
+        let policy = GatePolicy::from_config(None);
+        assert!(! policy.enabled);
+        assert!(policy.guards(& ["cargo", "publish", "--dry-run"]));
+        assert!(policy.guards(& ["cargo", "+nightly", "release"]));
+        assert!(! policy.guards(& ["cargo", "build"]));
+        assert_eq!(
+            policy.clippy_args()[4..], ["-D".to_string(), "warnings".to_string()]
+        );
+        let fmt = parse_fmt_check("Diff in /p/src/lib.rs at line 3:\n-a\n+b\n");
+        assert_eq!(fmt[0].detail, "/p/src/lib.rs at line 3");
+        let clippy = parse_clippy_short(
+            "src/a.rs:1:5: error: unused variable: `x`\nerror: could not compile `p`\n",
+        );
+        assert_eq!(clippy.len(), 1);
+        assert_eq!(clippy[0].detail, "src/a.rs:1:5 - unused variable: `x`");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("items.json");
+        assert_eq!(record_violations(& path, & clippy).unwrap(), 1);
+        assert_eq!(record_violations(& path, & clippy).unwrap(), 0);
+    }
+}
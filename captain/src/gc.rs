@@ -0,0 +1,301 @@
+use crate::anchor::AnchorManager;
+use crate::captain::config::ConfigManager;
+use crate::display::format_size;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+const PROJECTS_DIR: &str = "projects";
+/// JSON arrays of entries with a `timestamp`, pruned to `gc.history_days`
+const HISTORY_FILES: &[&str] = &["history/history.json", "idea_history/history.json"];
+const WTF_SESSIONS: &str = "wtf_history/sessions";
+/// Marks the last automatic size check, which runs at most daily
+const AUTO_STAMP: &str = "gc_last_check";
+/// Temp files younger than this may still belong to a write in progress
+const TEMP_FILE_GRACE_SECS: u64 = 3600;
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    pub history_days: i64,
+    pub wtf_days: i64,
+    pub anchors_per_project: usize,
+    pub auto: bool,
+    pub auto_threshold_mb: u64,
+}
+impl RetentionPolicy {
+    pub fn from_config(config: Option<&ConfigManager>) -> Self {
+        let number = |key: &str, default: i64| {
+            config.and_then(|c| c.get(key)).and_then(|v| v.trim().parse::<i64>().ok()).unwrap_or(default).max(0)
+        };
+        RetentionPolicy {
+            history_days: number("gc.history_days", 90),
+            wtf_days: number("gc.wtf_days", 90),
+            anchors_per_project: number("gc.anchors_per_project", 20) as usize,
+            auto: config.map(|c| c.get_bool("gc.auto", true)).unwrap_or(true),
+            auto_threshold_mb: number("gc.auto_threshold_mb", 512) as u64,
+        }
+    }
+}
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcReport {
+    pub history_entries: usize,
+    pub wtf_sessions: usize,
+    pub anchors: Vec<String>,
+    pub temp_files: usize,
+    pub freed_bytes: u64,
+}
+impl GcReport {
+    fn is_empty(&self) -> bool {
+        self.history_entries == 0 && self.wtf_sessions == 0 && self.anchors.is_empty() && self.temp_files == 0
+    }
+}
+fn size_of(path: &Path) -> u64 {
+    WalkDir::new(path).into_iter().flatten().filter_map(|e| e.metadata().ok()).filter(|m| m.is_file()).map(|m| m.len()).sum()
+}
+/// ~/.shipwreck and every per-project bucket inside it
+fn data_roots(root: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![root.to_path_buf()];
+    if let Ok(entries) = fs::read_dir(root.join(PROJECTS_DIR)) {
+        roots.extend(entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()));
+    }
+    roots
+}
+/// Bytes per top-level category, with project buckets folded into the same categories
+pub fn usage(root: &Path) -> BTreeMap<String, u64> {
+    let mut categories = BTreeMap::new();
+    for data_root in data_roots(root) {
+        for entry in fs::read_dir(&data_root).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if data_root == root && name == PROJECTS_DIR {
+                continue;
+            }
+            *categories.entry(name).or_insert(0) += size_of(&entry.path());
+        }
+    }
+    categories
+}
+/// Drops entries older than `cutoff` from a JSON array; entries without a readable timestamp stay
+fn prune_entries(entries: Vec<Value>, cutoff: DateTime<Utc>) -> (Vec<Value>, usize) {
+    let before = entries.len();
+    let kept: Vec<Value> = entries
+        .into_iter()
+        .filter(|entry| {
+            entry["timestamp"]
+                .as_str()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .is_none_or(|t| t.with_timezone(&Utc) >= cutoff)
+        })
+        .collect();
+    let removed = before - kept.len();
+    (kept, removed)
+}
+fn prune_history(path: &Path, cutoff: DateTime<Utc>, dry_run: bool, report: &mut GcReport) -> Result<()> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Ok(());
+    };
+    let Ok(Value::Array(entries)) = serde_json::from_str(&text) else {
+        return Ok(());
+    };
+    let (kept, removed) = prune_entries(entries, cutoff);
+    if removed == 0 {
+        return Ok(());
+    }
+    report.history_entries += removed;
+    let pruned = serde_json::to_string_pretty(&kept)?;
+    report.freed_bytes += (text.len() as u64).saturating_sub(pruned.len() as u64);
+    if !dry_run {
+        crate::storage::write_atomic(path, pruned)?;
+    }
+    Ok(())
+}
+fn prune_sessions(dir: &Path, cutoff: DateTime<Utc>, dry_run: bool, report: &mut GcReport) -> Result<()> {
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let modified: Option<DateTime<Utc>> = meta.modified().ok().map(Into::into);
+        if meta.is_file() && modified.is_some_and(|m| m < cutoff) {
+            report.wtf_sessions += 1;
+            report.freed_bytes += meta.len();
+            if !dry_run {
+                fs::remove_file(entry.path())?;
+            }
+        }
+    }
+    Ok(())
+}
+/// Temp files left by atomic writes that were killed before their rename
+fn prune_temp_files(root: &Path, dry_run: bool, report: &mut GcReport) -> Result<()> {
+    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy();
+        if !entry.file_type().is_file() || !name.starts_with('.') || !name.ends_with(crate::storage::TEMP_SUFFIX) {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let stale = meta
+            .modified()
+            .is_ok_and(|m| m.elapsed().is_ok_and(|age| age.as_secs() >= TEMP_FILE_GRACE_SECS));
+        if stale {
+            report.temp_files += 1;
+            report.freed_bytes += meta.len();
+            if !dry_run {
+                fs::remove_file(entry.path())?;
+            }
+        }
+    }
+    Ok(())
+}
+/// Anchors beyond the newest `keep` of each project
+fn prune_anchors(root: &Path, keep: usize, dry_run: bool, report: &mut GcReport) -> Result<()> {
+    let manager = AnchorManager::new()?;
+    let mut by_project: BTreeMap<String, Vec<_>> = BTreeMap::new();
+    for anchor in manager.list()? {
+        by_project.entry(anchor.project.clone()).or_default().push(anchor);
+    }
+    for anchors in by_project.values() {
+        // list() is newest first
+        for anchor in anchors.iter().skip(keep) {
+            report.freed_bytes += size_of(&root.join("anchors").join(format!("{}.json", anchor.name)))
+                + size_of(&root.join("snapshots").join(&anchor.name));
+            report.anchors.push(anchor.name.clone());
+            if !dry_run {
+                manager.remove(&anchor.name)?;
+            }
+        }
+    }
+    Ok(())
+}
+pub fn collect(root: &Path, policy: &RetentionPolicy, dry_run: bool) -> Result<GcReport> {
+    let mut report = GcReport::default();
+    let now = Utc::now();
+    for data_root in data_roots(root) {
+        for file in HISTORY_FILES {
+            prune_history(&data_root.join(file), now - Duration::days(policy.history_days), dry_run, &mut report)?;
+        }
+        prune_sessions(&data_root.join(WTF_SESSIONS), now - Duration::days(policy.wtf_days), dry_run, &mut report)?;
+    }
+    prune_anchors(root, policy.anchors_per_project, dry_run, &mut report)?;
+    prune_temp_files(root, dry_run, &mut report)?;
+    Ok(report)
+}
+fn print_report(report: &GcReport, dry_run: bool) {
+    if report.is_empty() {
+        println!("✅ Nothing is past its retention period");
+        return;
+    }
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    if report.history_entries > 0 {
+        println!("  🗑️  {} {} history entr{}", verb, report.history_entries, if report.history_entries == 1 { "y" } else { "ies" });
+    }
+    if report.wtf_sessions > 0 {
+        println!("  🗑️  {} {} wtf session(s)", verb, report.wtf_sessions);
+    }
+    if !report.anchors.is_empty() {
+        println!("  🗑️  {} {} anchor(s): {}", verb, report.anchors.len(), report.anchors.join(", "));
+    }
+    if report.temp_files > 0 {
+        println!("  🗑️  {} {} leftover temp file(s)", verb, report.temp_files);
+    }
+    println!("  💾 {} {}", if dry_run { "Would free" } else { "Freed" }, format_size(report.freed_bytes).green());
+}
+pub fn handle_gc(dry_run: bool, report_only: bool) -> Result<()> {
+    let root = crate::project::shipwreck_dir()?;
+    let policy = RetentionPolicy::from_config(ConfigManager::new().ok().as_ref());
+    println!("🧹 {}", "~/.shipwreck disk usage".bold().blue());
+    let usage = usage(&root);
+    let mut sorted: Vec<_> = usage.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1));
+    for (category, bytes) in &sorted {
+        println!("  {:>10}  {}", format_size(**bytes), category);
+    }
+    println!("  {:>10}  {}", format_size(usage.values().sum()).bold(), "total".bold());
+    if report_only {
+        return Ok(());
+    }
+    println!(
+        "\n📜 Retention: history {} day(s), wtf sessions {} day(s), {} anchor(s) per project",
+        policy.history_days, policy.wtf_days, policy.anchors_per_project
+    );
+    print_report(&collect(&root, &policy, dry_run)?, dry_run);
+    Ok(())
+}
+/// After a build: at most once a day, compact when ~/.shipwreck is over `gc.auto_threshold_mb`
+pub fn auto_compact() {
+    let Ok(root) = crate::project::shipwreck_dir() else {
+        return;
+    };
+    let policy = RetentionPolicy::from_config(ConfigManager::new().ok().as_ref());
+    let stamp = root.join(AUTO_STAMP);
+    let checked_recently = fs::metadata(&stamp)
+        .and_then(|m| m.modified())
+        .is_ok_and(|m| m.elapsed().is_ok_and(|age| age < std::time::Duration::from_secs(24 * 3600)));
+    if !policy.auto || checked_recently {
+        return;
+    }
+    let _ = fs::write(&stamp, Utc::now().to_rfc3339());
+    let total: u64 = usage(&root).values().sum();
+    if total < policy.auto_threshold_mb * 1024 * 1024 {
+        return;
+    }
+    match collect(&root, &policy, false) {
+        Ok(report) if !report.is_empty() => println!(
+            "🧹 ~/.shipwreck is {} - compacted {} ('cm gc' for details)",
+            format_size(total),
+            format_size(report.freed_bytes)
+        ),
+        Ok(_) => {}
+        Err(e) => log::warn!("Automatic ~/.shipwreck compaction failed: {}", e),
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    #[test]
+    fn test_retention_prunes_old_entries_and_sessions() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let bucket = root.join(PROJECTS_DIR).join("demo-0123456789ab");
+        fs::create_dir_all(bucket.join("history")).unwrap();
+        fs::create_dir_all(bucket.join(WTF_SESSIONS)).unwrap();
+        let old = (Utc::now() - Duration::days(200)).to_rfc3339();
+        let recent = Utc::now().to_rfc3339();
+        let history = json!([
+            {"timestamp": old, "command": "cargo build"},
+            {"timestamp": recent, "command": "cargo test"},
+            {"command": "no timestamp"}
+        ]);
+        fs::write(bucket.join(HISTORY_FILES[0]), history.to_string()).unwrap();
+        fs::write(bucket.join(WTF_SESSIONS).join("20200101-000000.json"), "{}").unwrap();
+        fs::write(root.join("license-grant.json"), "{}").unwrap();
+        let usage = usage(&root);
+        assert!(usage["history"] > 0 && usage.contains_key("wtf_history") && usage.contains_key("license-grant.json"));
+        assert!(!usage.contains_key(PROJECTS_DIR));
+        let mut report = GcReport::default();
+        let cutoff = Utc::now() - Duration::days(90);
+        prune_history(&bucket.join(HISTORY_FILES[0]), cutoff, true, &mut report).unwrap();
+        assert_eq!(report.history_entries, 1);
+        assert_eq!(fs::read_to_string(bucket.join(HISTORY_FILES[0])).unwrap(), history.to_string());
+        prune_history(&bucket.join(HISTORY_FILES[0]), cutoff, false, &mut report).unwrap();
+        let kept: Vec<Value> = serde_json::from_str(&fs::read_to_string(bucket.join(HISTORY_FILES[0])).unwrap()).unwrap();
+        assert_eq!(kept.len(), 2);
+        prune_sessions(&bucket.join(WTF_SESSIONS), Utc::now() + Duration::days(1), false, &mut report).unwrap();
+        assert_eq!(report.wtf_sessions, 1);
+        assert!(fs::read_dir(bucket.join(WTF_SESSIONS)).unwrap().next().is_none());
+        let leftover = bucket.join("history").join(".history.json.1-0.tmp");
+        fs::write(&leftover, "[").unwrap();
+        let hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(TEMP_FILE_GRACE_SECS + 60);
+        fs::File::options().write(true).open(&leftover).unwrap().set_modified(hour_ago).unwrap();
+        let fresh = bucket.join(".in-progress.json.2-0.tmp");
+        fs::write(&fresh, "{").unwrap();
+        prune_temp_files(&root, false, &mut report).unwrap();
+        assert_eq!(report.temp_files, 1);
+        assert!(!leftover.exists() && fresh.exists());
+    }
+}
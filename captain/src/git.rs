@@ -0,0 +1,6 @@
+use std::process::Command;
+/// stdout of `git <args>`, or `None` when git is missing or the command fails
+pub fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
@@ -0,0 +1,336 @@
+use crate::captain::config::ConfigManager;
+use crate::mutiny::MutinyMode;
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+const MANAGED_MARKER: &str = "# cargo-mate managed hook";
+const USER_SUFFIX: &str = "user";
+#[derive(Subcommand, Debug)]
+pub enum GithooksAction {
+    Install { #[arg(long)] force: bool },
+    Uninstall,
+    Status,
+    Run { hook: String },
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitHook {
+    PreCommit,
+    PrePush,
+}
+impl GitHook {
+    pub const ALL: [GitHook; 2] = [GitHook::PreCommit, GitHook::PrePush];
+    pub fn name(&self) -> &'static str {
+        match self {
+            GitHook::PreCommit => "pre-commit",
+            GitHook::PrePush => "pre-push",
+        }
+    }
+    pub fn config_key(&self) -> String {
+        format!("githooks.{}", self.name().replace('-', "_"))
+    }
+    fn default_steps(&self) -> &'static str {
+        match self {
+            GitHook::PreCommit => "fmt, clippy, secret_scanner",
+            GitHook::PrePush => "test, coverage_guard",
+        }
+    }
+    pub fn steps(&self, config: Option<&ConfigManager>) -> Result<Vec<Step>> {
+        let spec = config
+            .and_then(|c| c.get(&self.config_key()))
+            .unwrap_or_else(|| self.default_steps().to_string());
+        spec.split(',').map(str::trim).filter(|s| !s.is_empty()).map(Step::parse).collect()
+    }
+    fn reads_stdin(&self) -> bool {
+        matches!(self, GitHook::PrePush)
+    }
+}
+impl std::str::FromStr for GitHook {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        GitHook::ALL
+            .into_iter()
+            .find(|hook| hook.name() == s.replace('_', "-"))
+            .with_context(|| format!("Unsupported git hook '{}' (expected pre-commit or pre-push)", s))
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    Cargo { name: String, args: Vec<String> },
+    Journey(String),
+    Tool { name: String, args: Vec<String> },
+}
+impl Step {
+    pub fn parse(spec: &str) -> Result<Self> {
+        if let Some(journey) = spec.strip_prefix("journey:") {
+            return Ok(Step::Journey(journey.trim().to_string()));
+        }
+        let mut words = shell_words::split(spec)
+            .with_context(|| format!("Invalid githooks step '{}'", spec))?;
+        let name = words.remove(0);
+        let cargo_args: Option<&[&str]> = match name.as_str() {
+            "fmt" => Some(&["fmt", "--all", "--", "--check"]),
+            "clippy" => Some(&["clippy", "--all-targets", "--", "-D", "warnings"]),
+            "test" => Some(&["test"]),
+            _ => None,
+        };
+        if let Some(default_args) = cargo_args {
+            let args = if words.is_empty() {
+                default_args.iter().map(|a| a.to_string()).collect()
+            } else {
+                std::iter::once(name.clone()).chain(words).collect()
+            };
+            return Ok(Step::Cargo { name, args });
+        }
+        if !crate::tools::get_registry().has_tool(&name) {
+            anyhow::bail!(
+                "Unknown githooks step '{}' (use fmt, clippy, test, journey:<name> or a tool from 'cm tool list')",
+                name
+            );
+        }
+        if words.is_empty() && name == "secret_scanner" {
+            words.push("--ci-mode".to_string());
+        }
+        Ok(Step::Tool { name, args: words })
+    }
+    pub fn name(&self) -> &str {
+        match self {
+            Step::Cargo { name, .. } | Step::Tool { name, .. } => name,
+            Step::Journey(_) => "journey",
+        }
+    }
+    fn command(&self) -> Result<Command> {
+        let mut command = match self {
+            Step::Cargo { args, .. } => {
+                let mut command = Command::new("cargo");
+                command.args(args);
+                command
+            }
+            Step::Journey(name) => {
+                let mut command = Command::new(std::env::current_exe()?);
+                command.args(["journey", "play", name, "--ci"]);
+                command
+            }
+            Step::Tool { name, args } => {
+                let mut command = Command::new(std::env::current_exe()?);
+                command.args(["tool", "run", name]).args(args);
+                command
+            }
+        };
+        command.env("CM_GITHOOK", "1");
+        Ok(command)
+    }
+    fn describe(&self) -> String {
+        match self {
+            Step::Cargo { args, .. } => format!("cargo {}", args.join(" ")),
+            Step::Journey(name) => format!("journey {}", name),
+            Step::Tool { name, args } => format!("tool {} {}", name, args.join(" ")).trim_end().to_string(),
+        }
+    }
+}
+pub fn hooks_dir() -> Result<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!("Not inside a git repository");
+    }
+    Ok(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+fn is_managed(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| content.contains(MANAGED_MARKER))
+}
+fn user_hook_path(path: &Path) -> PathBuf {
+    path.with_extension(USER_SUFFIX)
+}
+pub fn hook_script(hook: GitHook, cm_path: &str) -> String {
+    let name = hook.name();
+    let feed = if hook.reads_stdin() { "printf '%s\\n' \"$input\" | " } else { "" };
+    let mut lines = vec![
+        "#!/bin/sh".to_string(),
+        format!("{} - regenerate with `cm githooks install`", MANAGED_MARKER),
+        "[ -n \"$CM_SKIP_GITHOOKS\" ] && exit 0".to_string(),
+    ];
+    if hook.reads_stdin() {
+        lines.push("input=$(cat)".to_string());
+    }
+    lines.extend([
+        format!("user_hook=\"$(dirname \"$0\")/{}.{}\"", name, USER_SUFFIX),
+        "if [ -x \"$user_hook\" ]; then".to_string(),
+        format!("    {}\"$user_hook\" \"$@\" || exit $?", feed),
+        "fi".to_string(),
+        format!("cm={}", shell_words::quote(cm_path)),
+        "command -v \"$cm\" >/dev/null 2>&1 || cm=cm".to_string(),
+        format!("{}\"$cm\" githooks run {} --ci", feed, name),
+    ]);
+    lines.join("\n") + "\n"
+}
+fn set_executable(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(path)?.permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(path, perms)?;
+    }
+    Ok(())
+}
+pub fn install_into(dir: &Path, cm_path: &str, force: bool) -> Result<Vec<String>> {
+    fs::create_dir_all(dir)?;
+    let mut report = Vec::new();
+    for hook in GitHook::ALL {
+        let path = dir.join(hook.name());
+        let user_hook = user_hook_path(&path);
+        if path.exists() && !is_managed(&path) {
+            if user_hook.exists() && !force {
+                anyhow::bail!(
+                    "{} and {} both exist; move one aside or rerun with --force",
+                    path.display(), user_hook.display()
+                );
+            }
+            fs::rename(&path, &user_hook)?;
+            report.push(format!("{} chained (kept as {})", hook.name(), user_hook.display()));
+        } else {
+            report.push(format!("{} installed", hook.name()));
+        }
+        fs::write(&path, hook_script(hook, cm_path))?;
+        set_executable(&path)?;
+    }
+    Ok(report)
+}
+pub fn uninstall_from(dir: &Path) -> Result<Vec<String>> {
+    let mut report = Vec::new();
+    for hook in GitHook::ALL {
+        let path = dir.join(hook.name());
+        if !is_managed(&path) {
+            continue;
+        }
+        fs::remove_file(&path)?;
+        let user_hook = user_hook_path(&path);
+        if user_hook.exists() {
+            fs::rename(&user_hook, &path)?;
+            report.push(format!("{} restored to your original hook", hook.name()));
+        } else {
+            report.push(format!("{} removed", hook.name()));
+        }
+    }
+    Ok(report)
+}
+fn skip_reason(mutiny: Option<&MutinyMode>, checks: &[&str]) -> Option<String> {
+    let mutiny = mutiny?;
+    checks
+        .iter()
+        .find(|check| mutiny.skips_check(check))
+        .map(|check| format!("'{}' is skipped by mutiny mode", check))
+}
+pub fn run_hook(hook: GitHook) -> Result<()> {
+    let mutiny = MutinyMode::new().ok();
+    if let Some(reason) = skip_reason(mutiny.as_ref(), &["githooks", hook.name()]) {
+        println!("⏭️  Skipping {} hook: {}", hook.name(), reason);
+        return Ok(());
+    }
+    let config = ConfigManager::new().ok();
+    let steps = hook.steps(config.as_ref())?;
+    println!("{}", crate::ci::decorate(&format!("🪝 cargo-mate {} hook", hook.name())).bold());
+    for step in &steps {
+        if let Some(reason) = skip_reason(mutiny.as_ref(), &[step.name()]) {
+            println!("  ⏭️  {} ({})", step.describe(), reason);
+            continue;
+        }
+        println!("  ▶ {}", step.describe().cyan());
+        let status = step
+            .command()?
+            .status()
+            .with_context(|| format!("Failed to run {}", step.describe()))?;
+        if !status.success() {
+            anyhow::bail!(
+                "{} hook failed at '{}' (bypass once with CM_SKIP_GITHOOKS=1 or --no-verify)",
+                hook.name(), step.describe()
+            );
+        }
+    }
+    println!("{}", crate::ci::decorate(&format!("✅ {} checks passed", hook.name())).green());
+    Ok(())
+}
+fn show_status(dir: &Path) -> Result<()> {
+    let config = ConfigManager::new().ok();
+    println!("🪝 Git hooks in {}", dir.display());
+    for hook in GitHook::ALL {
+        let path = dir.join(hook.name());
+        let state = if is_managed(&path) {
+            "managed".green()
+        } else if path.exists() {
+            "user hook (not managed)".yellow()
+        } else {
+            "not installed".dimmed()
+        };
+        let chained = if user_hook_path(&path).exists() { " + chained user hook" } else { "" };
+        println!("  {:<11} {}{}", hook.name().cyan(), state, chained);
+        match hook.steps(config.as_ref()) {
+            Ok(steps) => {
+                let steps: Vec<String> = steps.iter().map(Step::describe).collect();
+                println!("              {}", steps.join(", ").dimmed());
+            }
+            Err(e) => println!("              {}", e.to_string().red()),
+        }
+    }
+    Ok(())
+}
+pub fn handle_githooks_command(action: GithooksAction) -> Result<()> {
+    match action {
+        GithooksAction::Install { force } => {
+            let dir = hooks_dir()?;
+            let cm_path = std::env::current_exe()?.to_string_lossy().to_string();
+            for line in install_into(&dir, &cm_path, force)? {
+                println!("✅ {}", line);
+            }
+            println!("💡 Configure steps with 'cm config set githooks.pre_commit \"fmt, clippy\"'");
+        }
+        GithooksAction::Uninstall => {
+            let report = uninstall_from(&hooks_dir()?)?;
+            if report.is_empty() {
+                println!("⚠️  No cargo-mate managed hooks found");
+            }
+            for line in report {
+                println!("🗑️  {}", line);
+            }
+        }
+        GithooksAction::Status => show_status(&hooks_dir()?)?,
+        GithooksAction::Run { hook } => run_hook(hook.parse()?)?,
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_install_chains_and_restores_user_hooks() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::write(dir.join("pre-commit"), "#!/bin/sh\necho mine\n").unwrap();
+        install_into(dir, "/usr/local/bin/cm", false).unwrap();
+        assert!(is_managed(&dir.join("pre-commit")));
+        assert!(is_managed(&dir.join("pre-push")));
+        assert_eq!(
+            fs::read_to_string(dir.join("pre-commit.user")).unwrap(), "#!/bin/sh\necho mine\n"
+        );
+        let script = fs::read_to_string(dir.join("pre-push")).unwrap();
+        assert!(script.contains("input=$(cat)"));
+        assert!(script.contains("githooks run pre-push"));
+        install_into(dir, "/usr/local/bin/cm", false).unwrap();
+        assert!(dir.join("pre-commit.user").exists());
+        uninstall_from(dir).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("pre-commit")).unwrap(), "#!/bin/sh\necho mine\n");
+        assert!(!dir.join("pre-push").exists());
+        assert_eq!(
+            Step::parse("clippy").unwrap(), Step::Cargo { name : "clippy".into(), args :
+            vec!["clippy".into(), "--all-targets".into(), "--".into(), "-D".into(),
+            "warnings".into()] }
+        );
+        assert_eq!(Step::parse("journey:release").unwrap(), Step::Journey("release".into()));
+        assert!(Step::parse("no_such_tool").is_err());
+    }
+}
@@ -2,10 +2,9 @@ use crate::parser::{ParsedError, ParsedWarning};
 use chrono::{DateTime, Utc};
 use colored::*;
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::io::{BufReader, BufWriter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use anyhow::Result;
+const HISTORY_FILE: &str = "history/history.json";
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HistoryEntry {
     pub timestamp: DateTime<Utc>,
@@ -20,6 +19,14 @@ pub fn save_to_history(
     errors: Vec<ParsedError>,
     warnings: Vec<ParsedWarning>,
 ) {
+    record(
+        command,
+        errors.iter().map(|e| e.to_string()).collect(),
+        warnings.iter().map(|w| w.to_string()).collect(),
+    );
+}
+/// For cargo-mate's own checks (docs coverage, dead links) whose findings aren't compiler messages
+pub fn record(command: String, errors: Vec<String>, warnings: Vec<String>) {
     let history_file = match get_history_file() {
         Ok(file) => file,
         Err(_) => return,
@@ -29,25 +36,18 @@ pub fn save_to_history(
         command,
         error_count: errors.len(),
         warning_count: warnings.len(),
-        errors: errors.iter().map(|e| e.to_string()).collect(),
-        warnings: warnings.iter().map(|w| w.to_string()).collect(),
+        errors,
+        warnings,
     };
-    let mut history = load_history();
-    history.push(entry);
-    if history.len() > 1000 {
-        let skip_count = history.len() - 1000;
-        history = history.into_iter().skip(skip_count).collect();
-    }
-    if let Some(parent) = history_file.parent() {
-        let _ = fs::create_dir_all(parent);
-    }
-    if let Ok(file) = fs::File::create(&history_file) {
-        let writer = BufWriter::new(file);
-        let _ = serde_json::to_writer_pretty(writer, &history);
-    }
+    let _ = crate::storage::update_json(&history_file, |history: &mut Vec<HistoryEntry>| {
+        history.push(entry);
+        if history.len() > 1000 {
+            history.drain(..history.len() - 1000);
+        }
+    });
 }
 pub fn show_history(args: &[String]) {
-    let history = load_history();
+    let history = load_visible_history();
     if history.is_empty() {
         println!("No history found.");
         return;
@@ -70,42 +70,18 @@ fn parse_history_args(args: &[String]) -> (String, usize) {
 }
 fn show_errors(history: &[HistoryEntry], limit: usize) {
     println!("{}", "=== Error History ===".red().bold());
-    let mut all_errors = Vec::new();
-    for entry in history.iter().rev() {
-        for error in &entry.errors {
-            all_errors.push((entry.timestamp, error.clone()));
-            if all_errors.len() >= limit {
-                break;
-            }
-        }
-        if all_errors.len() >= limit {
-            break;
-        }
-    }
-    for (timestamp, error) in all_errors {
-        println!(
-            "{} - {}", timestamp.format("%Y-%m-%d %H:%M:%S").to_string().dimmed(), error
-        );
-    }
+    show_occurrences(&crate::fingerprint::error_occurrences(history), limit);
 }
 fn show_warnings(history: &[HistoryEntry], limit: usize) {
     println!("{}", "=== Warning History ===".yellow().bold());
-    let mut all_warnings = Vec::new();
-    for entry in history.iter().rev() {
-        for warning in &entry.warnings {
-            all_warnings.push((entry.timestamp, warning.clone()));
-            if all_warnings.len() >= limit {
-                break;
-            }
-        }
-        if all_warnings.len() >= limit {
-            break;
-        }
-    }
-    for (timestamp, warning) in all_warnings {
+    show_occurrences(&crate::fingerprint::warning_occurrences(history), limit);
+}
+fn show_occurrences(occurrences: &[crate::fingerprint::Occurrence], limit: usize) {
+    for occurrence in occurrences.iter().take(limit) {
         println!(
-            "{} - {}", timestamp.format("%Y-%m-%d %H:%M:%S").to_string().dimmed(),
-            warning
+            "{} - {} {}", occurrence.last_seen.format("%Y-%m-%d %H:%M:%S").to_string()
+            .dimmed(), occurrence.sample, format!("(×{}, first {})", occurrence.builds,
+            occurrence.first_seen.format("%Y-%m-%d %H:%M")).dimmed()
         );
     }
 }
@@ -132,25 +108,30 @@ fn show_summary(history: &[HistoryEntry], limit: usize) {
         );
     }
 }
+pub fn last_run_of(subcommand: &str) -> Option<HistoryEntry> {
+    load_history()
+        .into_iter()
+        .rev()
+        .find(|entry| entry.command.split_whitespace().take(2).any(|w| w == subcommand))
+}
 fn load_history() -> Vec<HistoryEntry> {
-    let history_file = match get_history_file() {
-        Ok(file) => file,
-        Err(_) => return Vec::new(),
-    };
-    if !history_file.exists() {
-        return Vec::new();
-    }
-    match fs::File::open(&history_file) {
-        Ok(file) => {
-            let reader = BufReader::new(file);
-            serde_json::from_reader(reader).unwrap_or_else(|_| Vec::new())
-        }
+    match get_history_file() {
+        Ok(file) => load_history_file(&file),
         Err(_) => Vec::new(),
     }
 }
+pub fn load_visible_history() -> Vec<HistoryEntry> {
+    let files = crate::project::read_files(HISTORY_FILE).unwrap_or_default();
+    let mut history: Vec<HistoryEntry> = files
+        .iter()
+        .flat_map(|(_, file)| load_history_file(file))
+        .collect();
+    history.sort_by_key(|entry| entry.timestamp);
+    history
+}
+fn load_history_file(history_file: &Path) -> Vec<HistoryEntry> {
+    crate::storage::read_json_file(history_file).ok().flatten().unwrap_or_default()
+}
 fn get_history_file() -> Result<PathBuf> {
-    let home = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not get home directory"))?;
-    let shipwreck = home.join(".shipwreck");
-    Ok(shipwreck.join("history").join("history.json"))
+    Ok(crate::project::data_dir()?.join(HISTORY_FILE))
 }
\ No newline at end of file
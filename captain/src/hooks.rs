@@ -0,0 +1,303 @@
+use crate::captain::config::{ConfigManager, ConfigSource};
+use anyhow::{Context, Result};
+use crate::scripting::Scripts;
+use colored::*;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+const MAX_DIAGNOSTICS: usize = 10;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookType {
+    PreBuild,
+    PostBuild,
+    OnError,
+    OnWarning,
+}
+impl HookType {
+    pub const ALL: [HookType; 4] = [
+        HookType::PreBuild,
+        HookType::PostBuild,
+        HookType::OnError,
+        HookType::OnWarning,
+    ];
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookType::PreBuild => "pre_build",
+            HookType::PostBuild => "post_build",
+            HookType::OnError => "on_error",
+            HookType::OnWarning => "on_warning",
+        }
+    }
+    pub fn config_key(&self) -> String {
+        format!("hooks.{}", self.name())
+    }
+}
+impl std::str::FromStr for HookType {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        HookType::ALL
+            .into_iter()
+            .find(|hook| hook.name() == s.replace('-', "_"))
+            .with_context(|| {
+                let names: Vec<&str> = HookType::ALL.iter().map(|h| h.name()).collect();
+                format!("Unknown hook '{}' (expected one of: {})", s, names.join(", "))
+            })
+    }
+}
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    pub errors: usize,
+    pub warnings: usize,
+    pub messages: Vec<String>,
+}
+impl Diagnostics {
+    pub fn observe(&mut self, line: &str) {
+        let line = strip_ansi(line);
+        let line = line.trim_end();
+        let is_error = line.starts_with("error:") || line.starts_with("error[");
+        let is_warning = line.starts_with("warning:");
+        if line.starts_with("error: could not compile")
+            || line.starts_with("error: aborting due to")
+            || (is_warning && line.contains(" generated ") && line.contains(" warning"))
+        {
+            return;
+        }
+        if is_error {
+            self.errors += 1;
+        } else if is_warning {
+            self.warnings += 1;
+        } else {
+            return;
+        }
+        if self.messages.len() < MAX_DIAGNOSTICS {
+            self.messages.push(line.to_string());
+        }
+    }
+    pub fn summary(&self) -> String {
+        self.messages.join("\n")
+    }
+}
+pub(crate) fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+#[derive(Debug, Clone)]
+pub struct HookContext {
+    pub cargo_args: Vec<String>,
+    pub exit_code: Option<i32>,
+    pub diagnostics: Diagnostics,
+}
+impl HookContext {
+    pub fn new(cargo_args: &[&str]) -> Self {
+        let cargo_args: Vec<String> = cargo_args
+            .iter()
+            .skip_while(|arg| **arg == "cargo")
+            .map(|arg| arg.to_string())
+            .collect();
+        Self {
+            cargo_args,
+            exit_code: None,
+            diagnostics: Diagnostics::default(),
+        }
+    }
+    fn env(&self, hook: HookType) -> Vec<(&'static str, String)> {
+        let exit_code = self.exit_code.map(|code| code.to_string()).unwrap_or_default();
+        vec![
+            ("CM_HOOK", hook.name().to_string()),
+            ("CM_CARGO_COMMAND", format!("cargo {}", self.cargo_args.join(" "))),
+            ("CM_CARGO_SUBCOMMAND", self.cargo_args.first().cloned().unwrap_or_default()),
+            ("CM_EXIT_CODE", exit_code),
+            ("CM_ERROR_COUNT", self.diagnostics.errors.to_string()),
+            ("CM_WARNING_COUNT", self.diagnostics.warnings.to_string()),
+            ("CM_DIAGNOSTICS", self.diagnostics.summary()),
+        ]
+    }
+}
+#[derive(Debug, Clone, Default)]
+pub struct HookSet {
+    hooks: Vec<(HookType, String, ConfigSource)>,
+    scripts: Scripts,
+}
+impl HookSet {
+    pub fn load() -> Self {
+        match ConfigManager::new() {
+            Ok(config) => Self::from_config(&config),
+            Err(_) => Self::default(),
+        }
+    }
+    pub fn from_config(config: &ConfigManager) -> Self {
+        let entries = config.entries();
+        let hooks = HookType::ALL
+            .into_iter()
+            .filter_map(|hook| {
+                let key = hook.config_key();
+                entries
+                    .iter()
+                    .find(|entry| entry.key == key && !entry.value.trim().is_empty())
+                    .map(|entry| (hook, entry.value.clone(), entry.source))
+            })
+            .collect();
+        Self { hooks, scripts: Scripts::load(config) }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty() && self.scripts.is_empty()
+    }
+    pub fn command(&self, hook: HookType) -> Option<&str> {
+        self.hooks.iter().find(|(h, _, _)| *h == hook).map(|(_, command, _)| command.as_str())
+    }
+    pub fn run(&self, hook: HookType, context: &HookContext) -> Result<i32> {
+        let Some(command) = self.command(hook) else {
+            return Ok(0);
+        };
+        eprintln!(
+            "{}", crate::ci::decorate(&format!("🪝 Running {} hook: {}", hook.name(),
+            command.dimmed()))
+        );
+        let mut shell = if cfg!(windows) {
+            let mut cmd = Command::new("cmd");
+            cmd.arg("/C");
+            cmd
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c");
+            cmd
+        };
+        let status = shell
+            .arg(command)
+            .envs(context.env(hook))
+            .status()
+            .with_context(|| format!("Failed to run {} hook", hook.name()))?;
+        let code = status.code().unwrap_or(1);
+        if code != 0 {
+            eprintln!(
+                "{}", crate::ci::decorate(&format!("⚠️  {} hook exited with status {}",
+                hook.name(), code))
+            );
+        }
+        Ok(code)
+    }
+    pub fn run_pre_build(&self, context: &HookContext) -> Result<()> {
+        if let Some(reason) = self.scripts.started(context) {
+            anyhow::bail!("vetoed by script: {}, cargo was not run", reason);
+        }
+        let code = self.run(HookType::PreBuild, context)?;
+        if code != 0 {
+            anyhow::bail!("pre_build hook failed with status {}, cargo was not run", code);
+        }
+        Ok(())
+    }
+    pub fn run_post_build(&self, context: &HookContext) {
+        let failed = context.exit_code.is_some_and(|code| code != 0);
+        let mut hooks = vec![HookType::PostBuild];
+        if failed || context.diagnostics.errors > 0 {
+            hooks.push(HookType::OnError);
+        }
+        if context.diagnostics.warnings > 0 {
+            hooks.push(HookType::OnWarning);
+        }
+        for hook in hooks {
+            if let Err(e) = self.run(hook, context) {
+                eprintln!("⚠️  {}", e);
+            }
+        }
+        self.scripts.finished(context);
+    }
+    pub fn list(&self) {
+        println!("{}", crate::ci::decorate("🪝 Configured hooks:"));
+        for hook in HookType::ALL {
+            match self.hooks.iter().find(|(h, _, _)| *h == hook) {
+                Some((_, command, source)) => {
+                    println!(
+                        "  {:<11} {} {}", hook.name().cyan(), command, format!("({})",
+                        source).dimmed()
+                    );
+                }
+                None => println!("  {:<11} {}", hook.name().cyan(), "-".dimmed()),
+            }
+        }
+        println!();
+        println!(
+            "Hooks receive CM_CARGO_COMMAND, CM_EXIT_CODE, CM_ERROR_COUNT, CM_WARNING_COUNT and CM_DIAGNOSTICS"
+        );
+        if !self.scripts.is_empty() {
+            println!();
+            println!("{}", crate::ci::decorate("📜 Scripts:"));
+            for path in self.scripts.paths() {
+                println!("  {}", path.display());
+            }
+        }
+    }
+}
+pub fn run_cargo_with_hooks(cargo_path: &str, args: &[&str], hooks: &HookSet) -> i32 {
+    let mut context = HookContext::new(args);
+    if let Err(e) = hooks.run_pre_build(&context) {
+        eprintln!("❌ {}", e);
+        return 1;
+    }
+    let start = std::time::Instant::now();
+    let mut command = Command::new(cargo_path);
+    command.args(args).stderr(Stdio::piped());
+    if std::env::var_os("CARGO_TERM_COLOR").is_none() && !crate::ci::is_ci()
+        && atty::is(atty::Stream::Stderr)
+    {
+        command.env("CARGO_TERM_COLOR", "always");
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Failed to start cargo: {}", e);
+            return 1;
+        }
+    };
+    if let Some(stderr) = child.stderr.take() {
+        let mut out = std::io::stderr();
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            let _ = writeln!(out, "{}", line);
+            context.diagnostics.observe(&line);
+        }
+    }
+    let code = child.wait().ok().and_then(|status| status.code()).unwrap_or(1);
+    context.exit_code = Some(code);
+    hooks.run_post_build(&context);
+    crate::status::command_finished(
+        args,
+        code,
+        start.elapsed(),
+        context.diagnostics.errors,
+        context.diagnostics.warnings,
+    );
+    code
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_diagnostics_counting() {
+        let mut diagnostics = Diagnostics::default();
+        for line in [
+            "\u{1b}[1m\u{1b}[31merror[E0308]\u{1b}[0m: mismatched types",
+            "warning: unused variable: `x`",
+            "   --> src/main.rs:2:9",
+            "warning: `demo` (bin \"demo\") generated 1 warning",
+            "error: could not compile `demo` (bin \"demo\") due to 1 previous error; 1 warning emitted",
+        ] {
+            diagnostics.observe(line);
+        }
+        assert_eq!(diagnostics.errors, 1);
+        assert_eq!(diagnostics.warnings, 1);
+        assert_eq!(diagnostics.summary(), "error[E0308]: mismatched types\nwarning: unused variable: `x`");
+        assert_eq!("on-error".parse::<HookType>().unwrap(), HookType::OnError);
+        assert!("pre_test".parse::<HookType>().is_err());
+    }
+}
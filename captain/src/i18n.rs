@@ -0,0 +1,122 @@
+use crate::captain::config::ConfigManager;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+/// Overrides `ui.language` and the system locale, e.g. `CM_LANG=es cm --help`
+pub const LANG_ENV: &str = "CM_LANG";
+/// Locales shipped in `locales/`; the first is the fallback for missing keys
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.toml")),
+    ("es", include_str!("../locales/es.toml")),
+];
+struct Catalog {
+    messages: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+fn parse(source: &str) -> HashMap<String, String> {
+    let table: toml::Table = source.parse().unwrap_or_default();
+    let mut messages = HashMap::new();
+    ConfigManager::flatten("", &table, &mut messages);
+    messages
+}
+fn source(locale: &str) -> &'static str {
+    CATALOGS.iter().find(|(name, _)| *name == locale).map(|(_, source)| *source).unwrap_or(CATALOGS[0].1)
+}
+/// The shipped locale for a value like `es`, `es-MX` or `es_ES.UTF-8`
+fn supported(value: &str) -> Option<&'static str> {
+    let language = value.split(['.', '@']).next()?.split(['_', '-']).next()?.trim().to_lowercase();
+    CATALOGS.iter().map(|(name, _)| *name).find(|name| *name == language)
+}
+/// `CM_LANG`, then `ui.language`, then the first of `LC_ALL`, `LC_MESSAGES` and `LANG`
+/// that is set; anything not shipped is English
+pub fn detect(configured: Option<&str>, env: impl Fn(&str) -> Option<String>) -> &'static str {
+    let system = || {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env(name))
+            .find(|value| !value.is_empty())
+            .and_then(|value| supported(&value))
+    };
+    env(LANG_ENV)
+        .and_then(|value| supported(&value))
+        .or_else(|| configured.and_then(supported))
+        .or_else(system)
+        .unwrap_or(CATALOGS[0].0)
+}
+fn catalog() -> &'static Catalog {
+    CATALOG.get_or_init(|| {
+        let configured = ConfigManager::new().ok().and_then(|config| config.get("ui.language"));
+        let locale = detect(configured.as_deref(), |name| std::env::var(name).ok());
+        Catalog { messages: parse(source(locale)), fallback: parse(CATALOGS[0].1) }
+    })
+}
+/// The message for `key` in the user's language, else in English, else the key itself
+pub fn t(key: &str) -> String {
+    let catalog = catalog();
+    catalog
+        .messages
+        .get(key)
+        .or_else(|| catalog.fallback.get(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+/// [`t`] with its `{name}` placeholders filled in
+pub fn t_with(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    fill(t(key), args)
+}
+fn fill(mut message: String, args: &[(&str, &dyn Display)]) -> String {
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    message
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    fn placeholders(message: &str) -> BTreeSet<String> {
+        regex::Regex::new(r"\{([a-z_]+)\}")
+            .unwrap()
+            .captures_iter(message)
+            .map(|c| c[1].to_string())
+            .collect()
+    }
+    #[test]
+    fn test_catalogs_cover_every_key_with_matching_placeholders() {
+        let english = parse(CATALOGS[0].1);
+        let mut used: Vec<String> = crate::HELP_COMMANDS.iter().map(|(_, key)| format!("help.commands.{}", key)).collect();
+        used.extend(crate::HELP_EXAMPLES.iter().map(|(_, key)| format!("help.examples.{}", key)));
+        let call = regex::Regex::new(r#"\bt(?:_with)?\(\s*"([a-z_]+\.[a-z_.]+)""#).unwrap();
+        for entry in walkdir::WalkDir::new(concat!(env!("CARGO_MANIFEST_DIR"), "/src")) {
+            let entry = entry.unwrap();
+            if entry.path().extension().is_some_and(|e| e == "rs") {
+                let code = std::fs::read_to_string(entry.path()).unwrap();
+                used.extend(call.captures_iter(&code).map(|c| c[1].to_string()));
+            }
+        }
+        assert!(used.len() > 60);
+        for key in &used {
+            assert!(english.contains_key(key), "{} is missing from en.toml", key);
+        }
+        for (locale, source) in &CATALOGS[1..] {
+            let messages = parse(source);
+            assert!(!messages.is_empty(), "{}.toml does not parse", locale);
+            for (key, message) in &messages {
+                let original = english.get(key).unwrap_or_else(|| panic!("{}.toml has unknown key {}", locale, key));
+                assert_eq!(placeholders(message), placeholders(original), "{} in {}.toml", key, locale);
+            }
+        }
+        let filled = fill(english["exec.use_directly"].clone(), &[("command", &"tide")]);
+        assert_eq!(filled, "💡 Use 'cm tide' directly for now");
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        assert_eq!(detect(None, env(&[("LANG", "es_ES.UTF-8")])), "es");
+        assert_eq!(detect(None, env(&[("LC_ALL", "C"), ("LANG", "es_ES.UTF-8")])), "en");
+        assert_eq!(detect(Some("es"), env(&[("LANG", "de_DE.UTF-8")])), "es");
+        assert_eq!(detect(Some("es"), env(&[(LANG_ENV, "en")])), "en");
+        assert_eq!(detect(Some("klingon"), env(&[("LC_MESSAGES", "es-MX")])), "es");
+        assert_eq!(detect(None, env(&[])), "en");
+    }
+}
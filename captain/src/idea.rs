@@ -0,0 +1,359 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+#[derive(Subcommand, Debug)]
+pub enum IdeaAction {
+    List { #[arg(long, default_value = "20")] limit: usize },
+    Status { id: String },
+    Withdraw { id: String },
+    Sync,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdeaHistoryEntry {
+    pub id: String,
+    pub idea: String,
+    pub timestamp: String,
+    #[serde(default)]
+    pub remote_id: Option<String>,
+    #[serde(default = "default_status")]
+    pub status: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedIdea {
+    pub id: String,
+    pub idea: String,
+    pub timestamp: String,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+fn default_status() -> String {
+    "submitted".to_string()
+}
+fn shipwreck_dir() -> Result<PathBuf> {
+    Ok(dirs::home_dir().context("Could not find home directory")?.join(".shipwreck"))
+}
+fn queue_dir(root: &Path) -> PathBuf {
+    root.join("idea_queue")
+}
+fn history_file(root: &Path) -> PathBuf {
+    root.join("idea_history").join("history.json")
+}
+fn user_id() -> Result<String> {
+    crate::captain::license::LicenseManager::new()?.get_or_create_user_id()
+}
+fn client() -> Result<reqwest::blocking::Client> {
+    crate::net::client(Duration::from_secs(10))
+}
+pub fn load_history() -> Result<Vec<IdeaHistoryEntry>> {
+    load_history_in(&shipwreck_dir()?)
+}
+fn load_history_in(root: &Path) -> Result<Vec<IdeaHistoryEntry>> {
+    Ok(crate::storage::read_json_file(&history_file(root))?.unwrap_or_default())
+}
+fn update_history(root: &Path, id: &str, update: impl FnOnce(&mut IdeaHistoryEntry)) -> Result<()> {
+    crate::storage::update_json(&history_file(root), |history: &mut Vec<IdeaHistoryEntry>| {
+        if let Some(entry) = history.iter_mut().find(|e| e.id == id) {
+            update(entry);
+        }
+    })
+}
+pub fn load_queue() -> Result<Vec<QueuedIdea>> {
+    load_queue_in(&shipwreck_dir()?)
+}
+/// Oldest first, the order they are sent in
+fn load_queue_in(root: &Path) -> Result<Vec<QueuedIdea>> {
+    let dir = queue_dir(root);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut queue: Vec<QueuedIdea> = fs::read_dir(&dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| crate::storage::read_json_file(&e.path()).ok().flatten())
+        .collect();
+    queue.sort_by(|a, b| (&a.timestamp, &a.id).cmp(&(&b.timestamp, &b.id)));
+    Ok(queue)
+}
+fn write_queued(root: &Path, queued: &QueuedIdea) -> Result<()> {
+    crate::storage::write_json_atomic(&queue_dir(root).join(format!("{}.json", queued.id)), queued)
+}
+fn remove_queued(root: &Path, id: &str) -> Result<bool> {
+    let path = queue_dir(root).join(format!("{}.json", id));
+    if path.exists() {
+        fs::remove_file(path)?;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+fn enqueue(root: &Path, idea: &str) -> Result<QueuedIdea> {
+    let now = chrono::Utc::now();
+    // Ideas sent within the same millisecond still get their own file
+    let taken = load_history_in(root)?;
+    let id = (now.timestamp_millis()..)
+        .map(|millis| format!("idea_{}", millis))
+        .find(|id| !taken.iter().any(|e| &e.id == id) && !queue_dir(root).join(format!("{}.json", id)).exists())
+        .expect("an unused id");
+    let queued = QueuedIdea {
+        id,
+        idea: idea.to_string(),
+        timestamp: now.to_rfc3339(),
+        attempts: 0,
+        last_error: None,
+    };
+    write_queued(root, &queued)?;
+    crate::storage::update_json(&history_file(root), |history: &mut Vec<IdeaHistoryEntry>| {
+        history
+            .insert(
+                0,
+                IdeaHistoryEntry {
+                    id: queued.id.clone(),
+                    idea: queued.idea.clone(),
+                    timestamp: queued.timestamp.clone(),
+                    remote_id: None,
+                    status: "queued".to_string(),
+                },
+            );
+        history.truncate(50);
+    })?;
+    Ok(queued)
+}
+fn post_idea(queued: &QueuedIdea, user_id: &str) -> std::result::Result<Option<String>, String> {
+    let endpoint = format!(
+        "{}/idea/{}", crate::user::api_base_url(), urlencoding::encode(& queued.idea)
+    );
+    let idea_data = serde_json::json!(
+        { "user_id" : user_id, "idea" : queued.idea, "timestamp" : queued.timestamp,
+        "client_id" : queued.id, "source" : "cargo-mate-cli" }
+    );
+    let request = client().map_err(|e| e.to_string())?.post(&endpoint).json(&idea_data);
+    let response = crate::net::send(request).map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("API responded with {}", response.status()));
+    }
+    Ok(
+        response
+            .json::<serde_json::Value>()
+            .ok()
+            .and_then(|json| {
+                json.get("id")
+                    .or_else(|| json.get("idea_id"))
+                    .map(|id| id.as_str().map(str::to_string).unwrap_or_else(|| id.to_string()))
+            }),
+    )
+}
+/// Drops a delivered idea from the queue and records it as submitted
+fn mark_sent(root: &Path, id: &str, remote_id: Option<String>) -> Result<()> {
+    remove_queued(root, id)?;
+    update_history(root, id, |entry| {
+        entry.status = "submitted".to_string();
+        entry.remote_id = remote_id;
+    })
+}
+pub fn sync_queue(verbose: bool) -> Result<(usize, usize)> {
+    let root = shipwreck_dir()?;
+    let queue = load_queue_in(&root)?;
+    if queue.is_empty() {
+        return Ok((0, 0));
+    }
+    let user_id = user_id()?;
+    let mut sent = 0;
+    for mut queued in queue.iter().cloned() {
+        match post_idea(&queued, &user_id) {
+            Ok(remote_id) => {
+                mark_sent(&root, &queued.id, remote_id.clone())?;
+                sent += 1;
+                if verbose {
+                    println!(
+                        "✅ Sent {} {}", queued.id.dimmed(), remote_id.map(| id |
+                        format!("(server id {})", id)).unwrap_or_default()
+                    );
+                }
+            }
+            Err(error) => {
+                queued.attempts += 1;
+                queued.last_error = Some(error.clone());
+                write_queued(&root, &queued)?;
+                if verbose {
+                    println!("⚠️  Could not reach the idea API: {}", error.yellow());
+                }
+                break;
+            }
+        }
+    }
+    Ok((sent, queue.len() - sent))
+}
+pub fn handle_idea(idea: &str) -> Result<()> {
+    println!("💡 Submitting idea: {}", idea.yellow());
+    let queued = enqueue(&shipwreck_dir()?, idea)?;
+    let (sent, pending) = sync_queue(false)?;
+    if load_queue()?.iter().any(|q| q.id == queued.id) {
+        if !crate::net::is_offline() {
+            crate::captain::wtf::display_api_failure_art();
+        }
+        println!("📥 Idea saved to the offline queue as {}", queued.id.cyan());
+        println!(
+            "🔁 {} idea(s) pending; they will be sent on the next successful connection",
+            pending
+        );
+        println!("💡 Retry now with: cm idea sync");
+    } else {
+        println!("✅ Idea submitted successfully! ({})", queued.id.cyan());
+        if sent > 1 {
+            println!("📤 Also delivered {} previously queued idea(s)", sent - 1);
+        }
+    }
+    Ok(())
+}
+fn find_entry(id: &str) -> Result<Option<IdeaHistoryEntry>> {
+    Ok(
+        load_history()?
+            .into_iter()
+            .find(|e| e.id == id || e.remote_id.as_deref() == Some(id)),
+    )
+}
+fn handle_list(limit: usize) -> Result<()> {
+    let endpoint = format!(
+        "{}/ideas/{}", crate::user::api_base_url(), urlencoding::encode(& user_id() ?)
+    );
+    let remote = client()
+        .and_then(|client| crate::net::send(client.get(&endpoint)))
+        .ok()
+        .filter(|r| r.status().is_success())
+        .and_then(|r| r.json::<serde_json::Value>().ok());
+    let history = load_history()?;
+    println!("💡 {}", "Your ideas".bold().blue());
+    if let Some(ideas) = remote.as_ref().and_then(|r| r.get("ideas").or(Some(r))).and_then(|r| r.as_array()) {
+        for idea in ideas.iter().take(limit) {
+            let field = |key: &str| idea.get(key).and_then(|v| v.as_str()).unwrap_or("");
+            println!(
+                "  • {} {} {}", field("id").cyan(), field("idea"), format!("[{}]",
+                field("status")).dimmed()
+            );
+        }
+        for entry in history.iter().filter(|e| e.status == "queued") {
+            println!(
+                "  • {} {} {}", entry.id.cyan(), entry.idea, "[queued]".yellow()
+            );
+        }
+    } else {
+        println!("{}", "  (offline - showing local history)".dimmed());
+        for entry in history.iter().take(limit) {
+            let status = match entry.status.as_str() {
+                "queued" => entry.status.yellow(),
+                "withdrawn" => entry.status.dimmed(),
+                _ => entry.status.green(),
+            };
+            println!(
+                "  • {} {} [{}]", entry.remote_id.as_deref().unwrap_or(& entry.id).cyan(),
+                entry.idea, status
+            );
+        }
+    }
+    Ok(())
+}
+fn handle_status(id: &str) -> Result<()> {
+    let entry = find_entry(id)?;
+    if let Some(entry) = entry.as_ref().filter(|e| e.status != "submitted") {
+        println!("💡 {} - {}", entry.id.cyan(), entry.idea);
+        println!("   Status: {}", entry.status.yellow());
+        if let Some(queued) = load_queue()?.into_iter().find(|q| q.id == entry.id) {
+            println!(
+                "   {} send attempt(s){}", queued.attempts, queued.last_error.map(| e |
+                format!(", last error: {}", e)).unwrap_or_default()
+            );
+        }
+        return Ok(());
+    }
+    let remote_id = entry.as_ref().and_then(|e| e.remote_id.clone()).unwrap_or_else(|| id.to_string());
+    let endpoint = format!(
+        "{}/ideas/{}/{}", crate::user::api_base_url(), urlencoding::encode(& user_id() ?),
+        urlencoding::encode(& remote_id)
+    );
+    let response = crate::net::send(client()?.get(&endpoint)).context("Could not reach the idea API")?;
+    if !response.status().is_success() {
+        anyhow::bail!("Idea {} not found ({})", id, response.status());
+    }
+    let json: serde_json::Value = response.json().unwrap_or_default();
+    let field = |key: &str| json.get(key).and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
+    println!("💡 {} - {}", remote_id.cyan(), field("idea"));
+    println!("   Status: {}", field("status").green());
+    if let Some(response) = json.get("response").and_then(|v| v.as_str()) {
+        println!("   Response: {}", response);
+    }
+    Ok(())
+}
+fn handle_withdraw(id: &str) -> Result<()> {
+    let entry = find_entry(id)?;
+    let root = shipwreck_dir()?;
+    if let Some(entry) = entry.as_ref().filter(|e| e.status == "queued") {
+        remove_queued(&root, &entry.id)?;
+        update_history(&root, &entry.id, |e| e.status = "withdrawn".to_string())?;
+        println!("🗑️  Withdrew queued idea {} before it was sent", entry.id.cyan());
+        return Ok(());
+    }
+    let remote_id = entry.as_ref().and_then(|e| e.remote_id.clone()).unwrap_or_else(|| id.to_string());
+    let endpoint = format!(
+        "{}/ideas/{}/{}", crate::user::api_base_url(), urlencoding::encode(& user_id() ?),
+        urlencoding::encode(& remote_id)
+    );
+    let response = crate::net::send(client()?.delete(&endpoint)).context("Could not reach the idea API")?;
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to withdraw idea {} ({})", id, response.status());
+    }
+    if let Some(entry) = entry {
+        update_history(&root, &entry.id, |e| e.status = "withdrawn".to_string())?;
+    }
+    println!("🗑️  Idea {} withdrawn", remote_id.cyan());
+    Ok(())
+}
+pub fn handle_idea_action(action: IdeaAction) -> Result<()> {
+    if !matches!(action, IdeaAction::Sync) {
+        sync_queue(false)?;
+    }
+    match action {
+        IdeaAction::List { limit } => handle_list(limit),
+        IdeaAction::Status { id } => handle_status(&id),
+        IdeaAction::Withdraw { id } => handle_withdraw(&id),
+        IdeaAction::Sync => {
+            let (sent, pending) = sync_queue(true)?;
+            println!("📤 {} sent, {} still queued", sent, pending);
+            Ok(())
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_queue_persists_in_order_until_sent_or_withdrawn() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let ideas: Vec<QueuedIdea> = ["first", "second", "third"].iter().map(|idea| enqueue(root, idea).unwrap()).collect();
+        let ids: Vec<&str> = ideas.iter().map(|q| q.id.as_str()).collect();
+        assert_eq!(ids.iter().collect::<std::collections::BTreeSet<_>>().len(), 3);
+        let queue = load_queue_in(root).unwrap();
+        assert_eq!(queue.iter().map(|q| q.idea.as_str()).collect::<Vec<_>>(), ["first", "second", "third"]);
+        let history = load_history_in(root).unwrap();
+        assert_eq!(history.iter().map(|e| e.idea.as_str()).collect::<Vec<_>>(), ["third", "second", "first"]);
+        assert!(history.iter().all(|e| e.status == "queued"));
+        mark_sent(root, ids[0], Some("remote-1".to_string())).unwrap();
+        assert!(remove_queued(root, ids[1]).unwrap());
+        update_history(root, ids[1], |e| e.status = "withdrawn".to_string()).unwrap();
+        assert!(!remove_queued(root, ids[1]).unwrap());
+        let queue = load_queue_in(root).unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].id, ids[2]);
+        let history = load_history_in(root).unwrap();
+        let status = |id: &str| history.iter().find(|e| e.id == id).map(|e| (e.status.clone(), e.remote_id.clone())).unwrap();
+        assert_eq!(status(ids[0]), ("submitted".to_string(), Some("remote-1".to_string())));
+        assert_eq!(status(ids[1]), ("withdrawn".to_string(), None));
+        assert_eq!(status(ids[2]), ("queued".to_string(), None));
+    }
+}
@@ -1,21 +1,30 @@
 use anyhow::{Context, Result};
+#[cfg(not(target_arch = "wasm32"))]
 use atty;
 use chrono::{DateTime, Utc};
 use colored::*;
+#[cfg(not(target_arch = "wasm32"))]
 use handlebars::Handlebars;
+#[cfg(not(target_arch = "wasm32"))]
 use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
 use shell_words;
 use std::collections::HashMap;
 use std::fs;
 use std::io::{BufReader, Write};
 use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
 use std::process::{Command, Stdio};
+#[cfg(not(target_arch = "wasm32"))]
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
 use std::thread;
 use std::time::{Duration, Instant};
+#[cfg(not(target_arch = "wasm32"))]
 use crate::captain::license;
+use crate::storage::{self, Storage};
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Journey {
     pub name: String,
@@ -34,9 +43,34 @@ pub struct Journey {
     pub downloads: u32,
     #[serde(default)]
     pub rating: f32,
+    /// Upper bound on concurrently running steps of a parallel group; defaults to the CPU count
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel: Option<usize>,
+}
+/// `prompt` and `confirm` steps stop for a human instead of running a command
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StepKind {
+    #[default]
+    Command,
+    Prompt,
+    Confirm,
+}
+impl StepKind {
+    pub fn is_command(&self) -> bool {
+        *self == StepKind::Command
+    }
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct JourneyCommand {
+    #[serde(rename = "type", skip_serializing_if = "StepKind::is_command")]
+    pub kind: StepKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<String>,
+    /// Consecutive steps sharing a group name run concurrently and fail as one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel: Option<String>,
     pub command: String,
     pub args: Vec<String>,
     pub working_dir: PathBuf,
@@ -46,6 +80,41 @@ pub struct JourneyCommand {
     pub pause_before: bool,
     pub pause_after: bool,
     pub description: Option<String>,
+    /// Variable a `prompt` step stores its answer in, for `{{name}}` in later steps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable: Option<String>,
+    /// Answer used by a `prompt` step when nobody can be asked (`--yes`, CI)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+impl Default for JourneyCommand {
+    fn default() -> Self {
+        Self {
+            kind: StepKind::Command,
+            include: None,
+            parallel: None,
+            command: String::new(),
+            args: Vec::new(),
+            working_dir: PathBuf::from("."),
+            expected_exit_code: 0,
+            timeout_seconds: 300,
+            capture_output: true,
+            pause_before: false,
+            pause_after: false,
+            description: None,
+            variable: None,
+            default: None,
+        }
+    }
+}
+impl JourneyCommand {
+    pub fn include(journey: &str) -> Self {
+        Self {
+            include: Some(journey.to_string()),
+            description: Some(format!("Include journey '{}'", journey)),
+            ..Self::default()
+        }
+    }
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Checkpoint {
@@ -60,12 +129,14 @@ pub enum CheckpointValidation {
     CommandSucceeds(String),
     Custom(String),
 }
+#[cfg(not(target_arch = "wasm32"))]
 pub struct JourneyRecorder {
     recording: Arc<Mutex<Vec<JourneyCommand>>>,
     is_recording: Arc<AtomicBool>,
     start_time: Instant,
     variables: Arc<Mutex<HashMap<String, String>>>,
 }
+#[cfg(not(target_arch = "wasm32"))]
 impl JourneyRecorder {
     pub fn new() -> Self {
         Self {
@@ -131,6 +202,9 @@ impl JourneyRecorder {
                             .unwrap_or_else(|_| vec![trimmed.clone()]);
                         if !parts.is_empty() {
                             let cmd = JourneyCommand {
+                                kind: StepKind::Command,
+                                include: None,
+                                parallel: None,
                                 command: parts[0].clone(),
                                 args: parts[1..].to_vec(),
                                 working_dir: std::env::current_dir()
@@ -141,6 +215,8 @@ impl JourneyRecorder {
                                 pause_before: false,
                                 pause_after: false,
                                 description: None,
+                                variable: None,
+                                default: None,
                             };
                             let mut rec = recording.lock().unwrap();
                             rec.push(cmd);
@@ -178,6 +254,9 @@ impl JourneyRecorder {
             return None;
         }
         Some(JourneyCommand {
+            kind: StepKind::Command,
+            include: None,
+            parallel: None,
             command: parts[0].clone(),
             args: parts[1..].to_vec(),
             working_dir: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
@@ -187,6 +266,8 @@ impl JourneyRecorder {
             pause_before: false,
             pause_after: false,
             description: None,
+            variable: None,
+            default: None,
         })
     }
     pub fn stop_recording(&self, name: &str, description: &str) -> Result<Journey> {
@@ -206,10 +287,9 @@ impl JourneyRecorder {
             tags: Vec::new(),
             downloads: 0,
             rating: 0.0,
+            max_parallel: None,
         };
         self.save_journey(&journey)?;
-        println!("✅ Journey '{}' recorded successfully!", name.green().bold());
-        println!("📁 Saved to ~/.shipwreck/journeys/{}.json", name);
         Ok(journey)
     }
     fn optimize_commands(&self, commands: Vec<JourneyCommand>) -> Vec<JourneyCommand> {
@@ -275,29 +355,105 @@ impl JourneyRecorder {
         env
     }
     fn save_journey(&self, journey: &Journey) -> Result<()> {
-        let journey_dir = dirs::home_dir()
-            .context("Could not find home directory")?
-            .join(".shipwreck")
-            .join("journeys");
-        fs::create_dir_all(&journey_dir)?;
-        let journey_file = journey_dir.join(format!("{}.json", journey.name));
-        let json = serde_json::to_string_pretty(journey)?;
-        fs::write(&journey_file, json)?;
-        let template_dir = journey_dir.join("templates");
-        fs::create_dir_all(&template_dir)?;
-        if journey.success_rate > 95.0 {
-            let template_file = template_dir.join(format!("{}.json", journey.name));
-            fs::copy(&journey_file, template_file)?;
+        save_journey(journey)
+    }
+}
+#[derive(Debug, Clone)]
+pub enum JourneyEvent {
+    Started { name: String, description: String },
+    Checkpoint(String),
+    Command { command: String, args: Vec<String>, description: Option<String> },
+    DryRun,
+    Skipped(String),
+    ChangedDirectory(String),
+    MissingDirectory(String),
+    CheckpointPassed(String),
+    GroupStarted { name: String, steps: usize, max_parallel: usize },
+    Output { label: String, line: String },
+    GroupCompleted(String),
+    Prompt { message: String, default: Option<String> },
+    Confirm(String),
+    AutoConfirmed(String),
+    Pause,
+    Completed,
+}
+impl JourneyEvent {
+    pub fn display(&self) {
+        match self {
+            JourneyEvent::Started { name, description } => {
+                println!("🚢 Playing journey: {}", name.cyan().bold());
+                println!("📝 {}", description);
+                println!();
+            }
+            JourneyEvent::Checkpoint(name) => {
+                println!("🏁 Checkpoint: {}", name.yellow())
+            }
+            JourneyEvent::Command { command, args, description } => {
+                if let Some(desc) = description {
+                    println!("📌 {}", desc.dimmed());
+                }
+                println!("$ {} {}", command.green(), args.join(" ").green());
+            }
+            JourneyEvent::DryRun => println!("  [DRY RUN - command not executed]"),
+            JourneyEvent::Skipped(reason) => println!("⚠️  {}", reason),
+            JourneyEvent::ChangedDirectory(path) => {
+                println!("📁 Changing working directory to: {}", path.cyan())
+            }
+            JourneyEvent::MissingDirectory(path) => {
+                println!("❌ Directory does not exist: {}", path)
+            }
+            JourneyEvent::CheckpointPassed(name) => {
+                println!("✅ Checkpoint passed: {}", name.green())
+            }
+            JourneyEvent::GroupStarted { name, steps, max_parallel } => {
+                println!(
+                    "🔀 Parallel group {}: {} steps, up to {} at a time", name.cyan().bold(),
+                    steps, max_parallel
+                )
+            }
+            JourneyEvent::Output { label, line } => {
+                println!("{} {}", format!("[{}]", label).cyan(), line)
+            }
+            JourneyEvent::GroupCompleted(name) => {
+                println!("✅ Parallel group finished: {}", name.green())
+            }
+            JourneyEvent::Prompt { message, default } => {
+                match default {
+                    Some(default) => print!("📝 {} [{}]: ", message.cyan(), default.dimmed()),
+                    None => print!("📝 {}: ", message.cyan()),
+                }
+                let _ = std::io::stdout().flush();
+            }
+            JourneyEvent::Confirm(message) => {
+                print!("❓ {} [y/N]: ", message.yellow().bold());
+                let _ = std::io::stdout().flush();
+            }
+            JourneyEvent::AutoConfirmed(message) => {
+                println!("✅ {} {}", message, "(confirmed by --yes)".dimmed())
+            }
+            JourneyEvent::Pause => println!("⏸️  Press Enter to continue..."),
+            JourneyEvent::Completed => println!("✅ Journey completed successfully!"),
         }
-        Ok(())
     }
 }
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PlaybackReport {
+    pub journey: String,
+    pub commands_run: usize,
+    pub commands_skipped: usize,
+    pub checkpoints_passed: Vec<String>,
+    pub dry_run: bool,
+}
+#[cfg(not(target_arch = "wasm32"))]
 pub struct JourneyPlayer {
     handlebars: Handlebars<'static>,
     variables: HashMap<String, String>,
     dry_run: bool,
     interactive: bool,
+    assume_yes: bool,
+    sandbox: Option<crate::journey_sandbox::Sandbox>,
 }
+#[cfg(not(target_arch = "wasm32"))]
 impl JourneyPlayer {
     pub fn new(dry_run: bool, interactive: bool) -> Self {
         Self {
@@ -305,53 +461,124 @@ impl JourneyPlayer {
             variables: HashMap::new(),
             dry_run,
             interactive,
+            assume_yes: false,
+            sandbox: None,
         }
     }
-    pub fn load_journey(&self, name: &str) -> Result<Journey> {
-        let journey_file = dirs::home_dir()
-            .context("Could not find home directory")?
-            .join(".shipwreck")
-            .join("journeys")
-            .join(format!("{}.json", name));
-        if !journey_file.exists() {
-            return Err(anyhow::anyhow!("Journey '{}' not found", name));
+    /// Plays every step in the sandbox instead of the working tree
+    pub fn with_sandbox(mut self, sandbox: crate::journey_sandbox::Sandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+    pub fn sandbox(&self) -> Option<&crate::journey_sandbox::Sandbox> {
+        self.sandbox.as_ref()
+    }
+    fn path(&self, path: &Path) -> PathBuf {
+        match &self.sandbox {
+            Some(sandbox) => sandbox.map(path),
+            None => path.to_path_buf(),
         }
-        let content = fs::read_to_string(&journey_file)?;
-        let journey: Journey = serde_json::from_str(&content)?;
-        Ok(journey)
     }
-    pub fn play(&mut self, journey: &Journey) -> Result<()> {
-        println!("🚢 Playing journey: {}", journey.name.cyan().bold());
-        println!("📝 {}", journey.description);
-        println!();
+    fn process(&self, command: &str, args: &[String], working_dir: &Path) -> Result<Command> {
+        match &self.sandbox {
+            Some(sandbox) => sandbox
+                .container_process(command, args, working_dir, needs_shell(command, args))
+                .unwrap_or_else(|| Ok(build_process(command, args, &sandbox.map(working_dir)))),
+            None => Ok(build_process(command, args, working_dir)),
+        }
+    }
+    fn shell(&self, script: &str) -> Result<Command> {
+        if let Some(process) = self
+            .sandbox
+            .as_ref()
+            .and_then(|sandbox| sandbox.container_process(script, &[], Path::new("."), true))
+        {
+            return process;
+        }
+        let mut process = Command::new("sh");
+        process.arg("-c").arg(script).current_dir(self.path(Path::new(".")));
+        Ok(process)
+    }
+    /// Answers `confirm` steps with yes and `prompt` steps with their defaults, for CI
+    pub fn assume_yes(mut self, assume_yes: bool) -> Self {
+        self.assume_yes = assume_yes;
+        self
+    }
+    pub fn load_journey(&self, name: &str) -> Result<Journey> {
+        load_journey(name)
+    }
+    pub fn play(&mut self, journey: &Journey) -> Result<PlaybackReport> {
+        self.run(journey, |event| event.display())
+    }
+    pub fn run(
+        &mut self,
+        journey: &Journey,
+        mut on_event: impl FnMut(&JourneyEvent),
+    ) -> Result<PlaybackReport> {
+        on_event(
+            &JourneyEvent::Started {
+                name: journey.name.clone(),
+                description: journey.description.clone(),
+            },
+        );
+        let resolved = resolve_includes(journey)?;
+        let journey = &resolved;
+        let mut report = PlaybackReport {
+            journey: journey.name.clone(),
+            dry_run: self.dry_run,
+            ..PlaybackReport::default()
+        };
         self.collect_variables(&journey.variables)?;
-        for (i, cmd) in journey.commands.iter().enumerate() {
-            if let Some(checkpoint) = journey
+        let mut i = 0;
+        while i < journey.commands.len() {
+            let end = group_end(&journey.commands, i);
+            for checkpoint in journey
                 .checkpoints
                 .iter()
-                .find(|c| c.command_index == i)
+                .filter(|c| (i..end).contains(&c.command_index))
             {
-                println!("🏁 Checkpoint: {}", checkpoint.name.yellow());
-            }
-            if cmd.pause_before && self.interactive {
-                println!("⏸️  Press Enter to continue...");
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
+                on_event(&JourneyEvent::Checkpoint(checkpoint.name.clone()));
             }
-            self.execute_command(cmd)?;
-            if cmd.pause_after && self.interactive {
-                println!("⏸️  Press Enter to continue...");
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
+            let cmd = &journey.commands[i];
+            if end - i > 1 || cmd.parallel.is_some() {
+                let ran = self
+                    .execute_group(
+                        &journey.commands[i..end],
+                        journey.max_parallel,
+                        &mut on_event,
+                    )?;
+                report.commands_run += ran;
+                report.commands_skipped += (end - i) - ran;
+            } else if !cmd.kind.is_command() {
+                self.execute_gate(cmd, &mut on_event)?;
+            } else {
+                if cmd.pause_before && self.interactive {
+                    on_event(&JourneyEvent::Pause);
+                    let mut input = String::new();
+                    std::io::stdin().read_line(&mut input)?;
+                }
+                if self.execute_command(cmd, &mut on_event)? {
+                    report.commands_run += 1;
+                } else {
+                    report.commands_skipped += 1;
+                }
+                if cmd.pause_after && self.interactive {
+                    on_event(&JourneyEvent::Pause);
+                    let mut input = String::new();
+                    std::io::stdin().read_line(&mut input)?;
+                }
             }
             for checkpoint in &journey.checkpoints {
-                if checkpoint.command_index == i {
+                if (i..end).contains(&checkpoint.command_index) {
                     self.validate_checkpoint(checkpoint)?;
+                    on_event(&JourneyEvent::CheckpointPassed(checkpoint.name.clone()));
+                    report.checkpoints_passed.push(checkpoint.name.clone());
                 }
             }
+            i = end;
         }
-        println!("✅ Journey completed successfully!");
-        Ok(())
+        on_event(&JourneyEvent::Completed);
+        Ok(report)
     }
     fn collect_variables(&mut self, defaults: &HashMap<String, String>) -> Result<()> {
         for (key, default_value) in defaults {
@@ -374,15 +601,84 @@ impl JourneyPlayer {
         }
         Ok(())
     }
-    fn execute_command(&self, cmd: &JourneyCommand) -> Result<()> {
+    fn execute_gate(
+        &mut self,
+        cmd: &JourneyCommand,
+        on_event: &mut impl FnMut(&JourneyEvent),
+    ) -> Result<()> {
+        let message = cmd.description.as_deref().map(|d| self.substitute_variables(d)).transpose()?;
+        let can_ask = self.interactive && !self.assume_yes && atty::is(atty::Stream::Stdin);
+        match cmd.kind {
+            StepKind::Prompt => {
+                let variable = cmd
+                    .variable
+                    .clone()
+                    .context("A prompt step needs a \"variable\" to store the answer in")?;
+                let default = cmd.default.as_deref().map(|d| self.substitute_variables(d)).transpose()?;
+                let value = if can_ask {
+                    on_event(
+                        &JourneyEvent::Prompt {
+                            message: message.unwrap_or_else(|| format!("Value for {}", variable)),
+                            default: default.clone(),
+                        },
+                    );
+                    let mut input = String::new();
+                    std::io::stdin().read_line(&mut input)?;
+                    match (input.trim(), default) {
+                        ("", Some(default)) => default,
+                        ("", None) => anyhow::bail!("A value for '{}' is required", variable),
+                        (answer, _) => answer.to_string(),
+                    }
+                } else {
+                    default
+                        .with_context(|| {
+                            format!(
+                                "Prompt '{}' has no default - run interactively or add one",
+                                variable
+                            )
+                        })?
+                };
+                self.variables.insert(variable, value);
+            }
+            StepKind::Confirm => {
+                let message = message.unwrap_or_else(|| "Continue?".to_string());
+                if self.assume_yes {
+                    on_event(&JourneyEvent::AutoConfirmed(message));
+                    return Ok(());
+                }
+                if self.dry_run && !can_ask {
+                    on_event(&JourneyEvent::Skipped(format!("Would ask: {}", message)));
+                    return Ok(());
+                }
+                if !can_ask {
+                    anyhow::bail!("'{}' needs confirmation - rerun with --yes to approve it", message);
+                }
+                on_event(&JourneyEvent::Confirm(message.clone()));
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input)?;
+                if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+                    anyhow::bail!("Journey stopped: '{}' was not confirmed", message);
+                }
+            }
+            StepKind::Command => {}
+        }
+        Ok(())
+    }
+    fn resolve_command(
+        &self,
+        cmd: &JourneyCommand,
+        on_event: &mut impl FnMut(&JourneyEvent),
+    ) -> Result<Option<(String, Vec<String>)>> {
         let command = self.substitute_variables(&cmd.command)?;
         if command.is_empty()
             || command
                 .chars()
                 .any(|c| c.is_control() && c != '\n' && c != '\r' && c != '\t')
         {
-            println!("⚠️  Skipping invalid command: '{}'", command);
-            return Ok(());
+            on_event(
+                &JourneyEvent::Skipped(format!("Skipping invalid command: '{}'", command)),
+            );
+            return Ok(None);
         }
         let args: Result<Vec<String>> = cmd
             .args
@@ -396,18 +692,35 @@ impl JourneyPlayer {
             .map(|arg| self.substitute_variables(arg))
             .collect();
         let args = args?;
-        if let Some(ref desc) = cmd.description {
-            println!("📌 {}", desc.dimmed());
-        }
-        println!("$ {} {}", command.green(), args.join(" ").green());
+        on_event(
+            &JourneyEvent::Command {
+                command: command.clone(),
+                args: args.clone(),
+                description: cmd.description.clone(),
+            },
+        );
+        Ok(Some((command, args)))
+    }
+    fn execute_command(
+        &self,
+        cmd: &JourneyCommand,
+        on_event: &mut impl FnMut(&JourneyEvent),
+    ) -> Result<bool> {
+        let Some((command, args)) = self.resolve_command(cmd, on_event)? else {
+            return Ok(false);
+        };
         if self.dry_run {
-            println!("  [DRY RUN - command not executed]");
-            return Ok(());
+            on_event(&JourneyEvent::DryRun);
+            return Ok(false);
         }
         if command == "cd" {
             if args.is_empty() {
-                println!("⚠️  Skipping cd command with no arguments");
-                return Ok(());
+                on_event(
+                    &JourneyEvent::Skipped(
+                        "Skipping cd command with no arguments".to_string(),
+                    ),
+                );
+                return Ok(false);
             }
             let target_dir = &args[0];
             let expanded_path = if target_dir.contains('~') {
@@ -420,48 +733,21 @@ impl JourneyPlayer {
             } else {
                 target_dir.clone()
             };
-            if std::path::Path::new(&expanded_path).exists() {
-                println!("📁 Changing working directory to: {}", expanded_path.cyan());
-                return Ok(());
+            if self.path(Path::new(&expanded_path)).exists() {
+                on_event(&JourneyEvent::ChangedDirectory(expanded_path));
+                return Ok(true);
             } else {
-                println!("❌ Directory does not exist: {}", expanded_path);
-                return Ok(());
+                on_event(&JourneyEvent::MissingDirectory(expanded_path));
+                return Ok(false);
             }
         }
-        let needs_shell = command.contains('~')
-            || args.iter().any(|arg| arg.contains('~')) || command.contains('$')
-            || args.iter().any(|arg| arg.contains('$'));
-        let status = if needs_shell {
-            let full_command = if args.is_empty() {
-                command.clone()
-            } else {
-                format!("{} {}", command, args.join(" "))
-            };
-            let mut process = Command::new("sh")
-                .arg("-c")
-                .arg(&full_command)
-                .current_dir(&cmd.working_dir)
-                .stdout(
-                    if cmd.capture_output { Stdio::piped() } else { Stdio::inherit() },
-                )
-                .stderr(
-                    if cmd.capture_output { Stdio::piped() } else { Stdio::inherit() },
-                )
-                .spawn()?;
-            process.wait()?
-        } else {
-            let mut process = Command::new(&command)
-                .args(&args)
-                .current_dir(&cmd.working_dir)
-                .stdout(
-                    if cmd.capture_output { Stdio::piped() } else { Stdio::inherit() },
-                )
-                .stderr(
-                    if cmd.capture_output { Stdio::piped() } else { Stdio::inherit() },
-                )
-                .spawn()?;
-            process.wait()?
-        };
+        let output = if cmd.capture_output { Stdio::piped } else { Stdio::inherit };
+        let status = self
+            .process(&command, &args, &cmd.working_dir)?
+            .stdout(output())
+            .stderr(output())
+            .spawn()?
+            .wait()?;
         if !status.success() && cmd.expected_exit_code == 0 {
             return Err(
                 anyhow::anyhow!(
@@ -469,7 +755,91 @@ impl JourneyPlayer {
                 ),
             );
         }
-        Ok(())
+        Ok(true)
+    }
+    /// Run a parallel group, streaming prefixed output; any failing step fails the group.
+    fn execute_group(
+        &self,
+        commands: &[JourneyCommand],
+        max_parallel: Option<usize>,
+        on_event: &mut impl FnMut(&JourneyEvent),
+    ) -> Result<usize> {
+        let name = commands[0].parallel.clone().unwrap_or_else(|| "parallel".to_string());
+        let max_parallel = max_parallel.unwrap_or_else(num_cpus::get).clamp(1, commands.len());
+        on_event(
+            &JourneyEvent::GroupStarted {
+                name: name.clone(),
+                steps: commands.len(),
+                max_parallel,
+            },
+        );
+        let mut steps = Vec::new();
+        for cmd in commands {
+            if !cmd.kind.is_command() {
+                return Err(
+                    anyhow::anyhow!(
+                        "Prompt and confirm steps cannot run inside parallel group '{}'", name
+                    ),
+                );
+            }
+            let Some((command, args)) = self.resolve_command(cmd, on_event)? else {
+                continue;
+            };
+            if command == "cd" {
+                return Err(
+                    anyhow::anyhow!("'cd' cannot run inside parallel group '{}'", name),
+                );
+            }
+            let process = self.process(&command, &args, &cmd.working_dir)?;
+            steps.push((step_label(cmd, &command, &args), command, process, cmd));
+        }
+        if self.dry_run {
+            on_event(&JourneyEvent::DryRun);
+            return Ok(0);
+        }
+        let ran = steps.len();
+        let queue = Mutex::new(steps.into_iter());
+        let aborted = AtomicBool::new(false);
+        let failures = Mutex::new(Vec::new());
+        let (tx, rx) = std::sync::mpsc::channel::<(String, String)>();
+        thread::scope(|scope| {
+            for _ in 0..max_parallel {
+                let tx = tx.clone();
+                let (queue, aborted, failures) = (&queue, &aborted, &failures);
+                scope
+                    .spawn(move || {
+                        loop {
+                            let next = queue.lock().unwrap().next();
+                            let Some((label, command, process, cmd)) = next else { break };
+                            if aborted.load(Ordering::Relaxed) {
+                                failures
+                                    .lock()
+                                    .unwrap()
+                                    .push(format!("{} (not started)", label));
+                                continue;
+                            }
+                            if let Err(e) = run_prefixed(&label, &command, process, cmd, &tx) {
+                                aborted.store(true, Ordering::Relaxed);
+                                failures.lock().unwrap().push(format!("{}: {}", label, e));
+                            }
+                        }
+                    });
+            }
+            drop(tx);
+            for (label, line) in rx {
+                on_event(&JourneyEvent::Output { label, line });
+            }
+        });
+        let failures = failures.into_inner().unwrap();
+        if !failures.is_empty() {
+            return Err(
+                anyhow::anyhow!(
+                    "Parallel group '{}' failed:\n  {}", name, failures.join("\n  ")
+                ),
+            );
+        }
+        on_event(&JourneyEvent::GroupCompleted(name));
+        Ok(ran)
     }
     fn substitute_variables(&self, template: &str) -> Result<String> {
         self.handlebars
@@ -479,7 +849,7 @@ impl JourneyPlayer {
     fn validate_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()> {
         match &checkpoint.validation {
             CheckpointValidation::FileExists(path) => {
-                if !path.exists() {
+                if !self.path(path).exists() {
                     return Err(
                         anyhow::anyhow!(
                             "Checkpoint failed: file {} does not exist", path.display()
@@ -488,7 +858,7 @@ impl JourneyPlayer {
                 }
             }
             CheckpointValidation::FileContains(path, content) => {
-                let file_content = fs::read_to_string(path)?;
+                let file_content = fs::read_to_string(self.path(path))?;
                 if !file_content.contains(content) {
                     return Err(
                         anyhow::anyhow!(
@@ -499,7 +869,7 @@ impl JourneyPlayer {
                 }
             }
             CheckpointValidation::CommandSucceeds(cmd) => {
-                let status = Command::new("sh").arg("-c").arg(cmd).status()?;
+                let status = self.shell(cmd)?.status()?;
                 if !status.success() {
                     return Err(
                         anyhow::anyhow!("Checkpoint failed: command '{}' failed", cmd),
@@ -507,7 +877,7 @@ impl JourneyPlayer {
                 }
             }
             CheckpointValidation::Custom(script) => {
-                let status = Command::new("sh").arg("-c").arg(script).status()?;
+                let status = self.shell(script)?.status()?;
                 if !status.success() {
                     return Err(
                         anyhow::anyhow!("Checkpoint failed: custom validation failed"),
@@ -515,56 +885,223 @@ impl JourneyPlayer {
                 }
             }
         }
-        println!("✅ Checkpoint passed: {}", checkpoint.name.green());
         Ok(())
     }
 }
-pub fn list_journeys() -> Result<Vec<String>> {
-    let journey_dir = dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".shipwreck")
-        .join("journeys");
-    if !journey_dir.exists() {
-        return Ok(Vec::new());
+#[cfg(not(target_arch = "wasm32"))]
+/// End (exclusive) of the step run starting at `start`: a whole parallel group, or one step.
+fn group_end(commands: &[JourneyCommand], start: usize) -> usize {
+    match &commands[start].parallel {
+        Some(group) => {
+            commands[start..]
+                .iter()
+                .position(|c| c.parallel.as_ref() != Some(group))
+                .map_or(commands.len(), |n| start + n)
+        }
+        None => start + 1,
+    }
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn step_label(cmd: &JourneyCommand, command: &str, args: &[String]) -> String {
+    if let Some(desc) = &cmd.description {
+        return desc.clone();
+    }
+    match args.first() {
+        Some(sub) if command == "cargo" => sub.clone(),
+        _ => command.to_string(),
     }
-    let mut journeys = Vec::new();
-    for entry in fs::read_dir(&journey_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.extension() == Some(std::ffi::OsStr::new("json")) {
-            if let Some(stem) = path.file_stem() {
-                journeys.push(stem.to_string_lossy().to_string());
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn needs_shell(command: &str, args: &[String]) -> bool {
+    command.contains('~') || args.iter().any(|arg| arg.contains('~')) || command.contains('$')
+        || args.iter().any(|arg| arg.contains('$'))
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn build_process(command: &str, args: &[String], working_dir: &Path) -> Command {
+    let mut process = if needs_shell(command, args) {
+        let full_command = if args.is_empty() {
+            command.to_string()
+        } else {
+            format!("{} {}", command, args.join(" "))
+        };
+        let mut process = Command::new("sh");
+        process.arg("-c").arg(full_command);
+        process
+    } else {
+        let mut process = Command::new(command);
+        process.args(args);
+        process
+    };
+    process.current_dir(working_dir);
+    process
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn run_prefixed(
+    label: &str,
+    command: &str,
+    mut process: Command,
+    cmd: &JourneyCommand,
+    tx: &std::sync::mpsc::Sender<(String, String)>,
+) -> Result<()> {
+    use std::io::BufRead;
+    let mut child = process
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start '{}'", command))?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    thread::scope(|scope| {
+        if let Some(stderr) = stderr {
+            let tx = tx.clone();
+            scope
+                .spawn(move || {
+                    for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                        let _ = tx.send((label.to_string(), line));
+                    }
+                });
+        }
+        if let Some(stdout) = stdout {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let _ = tx.send((label.to_string(), line));
             }
         }
+    });
+    let status = child.wait()?;
+    if !status.success() && cmd.expected_exit_code == 0 {
+        return Err(anyhow::anyhow!("exit code {}", status.code().unwrap_or(- 1)));
     }
-    Ok(journeys)
+    Ok(())
 }
-pub fn export_journey(name: &str, output: &Path) -> Result<()> {
-    let journey_file = dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".shipwreck")
-        .join("journeys")
-        .join(format!("{}.json", name));
-    if !journey_file.exists() {
-        return Err(anyhow::anyhow!("Journey '{}' not found", name));
+pub fn save_journey_to(storage: &dyn Storage, journey: &Journey) -> Result<()> {
+    let key = format!("journeys/{}.json", journey.name);
+    storage::write_json(storage, &key, journey)?;
+    if journey.success_rate > 95.0 {
+        storage::write_json(storage, &format!("journeys/templates/{}.json", journey.name), journey)?;
     }
-    fs::copy(&journey_file, output)?;
-    println!("✅ Journey exported to {}", output.display());
     Ok(())
 }
-pub fn import_journey(path: &Path) -> Result<()> {
-    let content = fs::read_to_string(path)?;
-    let journey: Journey = serde_json::from_str(&content)?;
-    let journey_dir = dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".shipwreck")
-        .join("journeys");
-    fs::create_dir_all(&journey_dir)?;
-    let journey_file = journey_dir.join(format!("{}.json", journey.name));
-    fs::write(&journey_file, content)?;
-    println!("✅ Journey '{}' imported successfully!", journey.name.green());
+pub fn load_journey_from(storage: &dyn Storage, name: &str) -> Result<Journey> {
+    storage::read_json(storage, &format!("journeys/{}.json", name))?
+        .ok_or_else(|| anyhow::anyhow!("Journey '{}' not found", name))
+}
+pub fn list_journeys_in(storage: &dyn Storage) -> Result<Vec<String>> {
+    Ok(
+        storage
+            .list("journeys")?
+            .into_iter()
+            .filter_map(|file| file.strip_suffix(".json").map(str::to_string))
+            .collect(),
+    )
+}
+pub fn resolve_includes_from(storage: &dyn Storage, journey: &Journey) -> Result<Journey> {
+    let mut resolved = journey.clone();
+    resolved.commands.clear();
+    resolved.checkpoints.clear();
+    let mut stack = vec![journey.name.clone()];
+    expand_into(storage, journey, &mut stack, &mut resolved)?;
+    Ok(resolved)
+}
+fn expand_into(
+    storage: &dyn Storage,
+    journey: &Journey,
+    stack: &mut Vec<String>,
+    resolved: &mut Journey,
+) -> Result<()> {
+    let mut index_map = Vec::with_capacity(journey.commands.len());
+    for cmd in &journey.commands {
+        match &cmd.include {
+            Some(name) => {
+                if stack.contains(name) {
+                    return Err(
+                        anyhow::anyhow!(
+                            "Journey include cycle detected: {} -> {}", stack.join(" -> "),
+                            name
+                        ),
+                    );
+                }
+                let included = load_journey_from(storage, name)
+                    .with_context(|| {
+                        format!("Journey '{}' includes '{}'", journey.name, name)
+                    })?;
+                for (key, value) in &included.variables {
+                    resolved.variables.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+                for (key, value) in &included.environment {
+                    resolved
+                        .environment
+                        .entry(key.clone())
+                        .or_insert_with(|| value.clone());
+                }
+                stack.push(name.clone());
+                expand_into(storage, &included, stack, resolved)?;
+                stack.pop();
+            }
+            None => resolved.commands.push(cmd.clone()),
+        }
+        index_map.push(resolved.commands.len().saturating_sub(1));
+    }
+    for checkpoint in &journey.checkpoints {
+        if let Some(&index) = index_map.get(checkpoint.command_index) {
+            let mut checkpoint = checkpoint.clone();
+            checkpoint.command_index = index;
+            resolved.checkpoints.push(checkpoint);
+        }
+    }
+    Ok(())
+}
+pub fn compose_journeys_in(
+    storage: &dyn Storage,
+    name: &str,
+    parts: &[String],
+) -> Result<Journey> {
+    if parts.iter().any(|part| part == name) {
+        return Err(anyhow::anyhow!("Journey '{}' cannot be composed from itself", name));
+    }
+    let template = Journey {
+        name: name.to_string(),
+        description: format!("Composed from {}", parts.join(", ")),
+        created: Utc::now(),
+        commands: parts.iter().map(|part| JourneyCommand::include(part)).collect(),
+        variables: HashMap::new(),
+        checkpoints: Vec::new(),
+        environment: HashMap::new(),
+        success_rate: 100.0,
+        author: std::env::var("USER").ok(),
+        tags: Vec::new(),
+        downloads: 0,
+        rating: 0.0,
+        max_parallel: None,
+    };
+    let journey = resolve_includes_from(storage, &template)?;
+    save_journey_to(storage, &journey)?;
+    Ok(journey)
+}
+pub fn resolve_includes(journey: &Journey) -> Result<Journey> {
+    resolve_includes_from(&*storage::default_storage()?, journey)
+}
+pub fn compose_journeys(name: &str, parts: &[String]) -> Result<Journey> {
+    compose_journeys_in(&*storage::default_storage()?, name, parts)
+}
+pub fn save_journey(journey: &Journey) -> Result<()> {
+    save_journey_to(&*storage::default_storage()?, journey)
+}
+pub fn load_journey(name: &str) -> Result<Journey> {
+    load_journey_from(&*storage::default_storage()?, name)
+}
+pub fn list_journeys() -> Result<Vec<String>> {
+    list_journeys_in(&*storage::default_storage()?)
+}
+pub fn export_journey(name: &str, output: &Path) -> Result<()> {
+    let journey = load_journey(name)?;
+    fs::write(output, serde_json::to_string_pretty(&journey)?)?;
     Ok(())
 }
+pub fn import_journey(path: &Path) -> Result<Journey> {
+    let journey: Journey = serde_json::from_str(&fs::read_to_string(path)?)?;
+    save_journey_to(&*storage::default_storage()?, &journey)?;
+    Ok(journey)
+}
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MarketplaceJourney {
     pub gist_id: String,
@@ -577,8 +1114,32 @@ pub struct MarketplaceJourney {
     pub created: DateTime<Utc>,
     pub updated: DateTime<Utc>,
 }
+#[cfg(not(target_arch = "wasm32"))]
 pub struct JourneyMarketplace;
+#[cfg(not(target_arch = "wasm32"))]
+const MARKETPLACE_API: &str = "https://cargo.do/api/marketplace";
+/// Search results are reused for this long, and offline for as long as they exist
+#[cfg(not(target_arch = "wasm32"))]
+const SEARCH_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(600);
+#[cfg(not(target_arch = "wasm32"))]
 impl JourneyMarketplace {
+    /// Calls the marketplace API through the shared client, POSTing `body` when given and
+    /// authenticating with the `marketplace.token` secret when one is stored
+    fn api(endpoint: &str, body: Option<&serde_json::Value>) -> Result<String> {
+        Self::api_at(MARKETPLACE_API, endpoint, body)
+    }
+    fn api_at(base: &str, endpoint: &str, body: Option<&serde_json::Value>) -> Result<String> {
+        let client = crate::net::client(std::time::Duration::from_secs(30))?;
+        let url = format!("{}/{}", base, endpoint);
+        let mut request = match body {
+            Some(body) => client.post(&url).json(body),
+            None => client.get(&url),
+        };
+        if let Some(token) = crate::secrets::lookup(crate::secrets::MARKETPLACE_TOKEN) {
+            request = request.bearer_auth(token);
+        }
+        Ok(crate::net::send(request)?.text()?)
+    }
     pub fn publish(name: &str, tags: Vec<String>) -> Result<String> {
         if std::env::var("CARGO_MATE_MARKETPLACE").is_ok() {
             let email = std::env::var("CARGO_MATE_EMAIL")
@@ -590,6 +1151,7 @@ impl JourneyMarketplace {
         Self::publish_to_gist(name, tags)
     }
     pub fn publish_to_gist(name: &str, tags: Vec<String>) -> Result<String> {
+        crate::net::ensure_online("Publishing a journey")?;
         let journey_file = dirs::home_dir()
             .context("Could not find home directory")?
             .join(".shipwreck")
@@ -648,6 +1210,7 @@ impl JourneyMarketplace {
         Ok(gist_id.to_string())
     }
     pub fn download(gist_id: &str) -> Result<()> {
+        crate::net::ensure_online("Downloading a journey")?;
         println!("📥 Downloading journey from gist {}...", gist_id.cyan());
         let output = Command::new("gh")
             .args(&["api", &format!("gists/{}", gist_id)])
@@ -674,7 +1237,7 @@ impl JourneyMarketplace {
                     .join("journeys");
                 fs::create_dir_all(&journey_dir)?;
                 let journey_file = journey_dir.join(format!("{}.json", journey.name));
-                fs::write(&journey_file, content)?;
+                crate::storage::write_atomic(&journey_file, content)?;
                 println!(
                     "✅ Journey '{}' downloaded successfully!", journey.name.green()
                 );
@@ -691,6 +1254,7 @@ impl JourneyMarketplace {
         Err(anyhow::anyhow!("No valid journey file found in gist"))
     }
     pub fn search(query: &str) -> Result<Vec<MarketplaceJourney>> {
+        crate::net::ensure_online("Searching journeys")?;
         println!("🔍 Searching for journeys matching '{}'...", query.cyan());
         let output = Command::new("gh")
             .args(&["api", "search/gists"])
@@ -769,11 +1333,8 @@ impl JourneyMarketplace {
             .join(".shipwreck")
             .join("journeys")
             .join(".published.json");
-        if !published_file.exists() {
-            return Ok(Vec::new());
-        }
-        let content = fs::read_to_string(&published_file)?;
-        let published: HashMap<String, String> = serde_json::from_str(&content)?;
+        let published: HashMap<String, String> =
+            crate::storage::read_json_file(&published_file)?.unwrap_or_default();
         let mut journeys = Vec::new();
         for (name, gist_id) in published {
             journeys.push(format!("{} ({})", name, gist_id));
@@ -786,16 +1347,10 @@ impl JourneyMarketplace {
             .join(".shipwreck")
             .join("journeys")
             .join(".published.json");
-        let mut published: HashMap<String, String> = if published_file.exists() {
-            let content = fs::read_to_string(&published_file)?;
-            serde_json::from_str(&content)?
-        } else {
-            HashMap::new()
-        };
+        let mut published: HashMap<String, String> =
+            crate::storage::read_json_file(&published_file)?.unwrap_or_default();
         published.insert(name.to_string(), gist_id.to_string());
-        let json = serde_json::to_string_pretty(&published)?;
-        fs::write(&published_file, json)?;
-        Ok(())
+        crate::storage::write_json_atomic(&published_file, &published)
     }
     pub fn publish_to_marketplace(
         name: &str,
@@ -834,27 +1389,9 @@ impl JourneyMarketplace {
         let temp_file = std::env::temp_dir()
             .join(format!("cargo-mate-marketplace-{}.json", name));
         fs::write(&temp_file, journey_data.to_string())?;
-        let output = Command::new("curl")
-            .args(
-                &[
-                    "-X",
-                    "POST",
-                    "-H",
-                    "Content-Type: application/json",
-                    "-d",
-                    &journey_data.to_string(),
-                    "https://cargo.do/api/marketplace/publish-journey",
-                ],
-            )
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()?;
+        let response = Self::api("publish-journey", Some(&journey_data));
         let _ = fs::remove_file(&temp_file);
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow::anyhow!("Marketplace API error: {}", error));
-        }
-        let response = String::from_utf8_lossy(&output.stdout);
+        let response = response.map_err(|e| anyhow::anyhow!("Marketplace API error: {}", e))?;
         if response.trim().is_empty() {
             return Err(anyhow::anyhow!("Marketplace API returned empty response"));
         }
@@ -889,26 +1426,8 @@ impl JourneyMarketplace {
             { "journey_id" : journey_id.parse::< u64 > ().unwrap_or(0), "email" : email
             .unwrap_or("") }
         );
-        let output = Command::new("curl")
-            .args(
-                &[
-                    "-X",
-                    "POST",
-                    "-H",
-                    "Content-Type: application/json",
-                    "-d",
-                    &download_data.to_string(),
-                    "https://cargo.do/api/marketplace/download-journey",
-                ],
-            )
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()?;
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow::anyhow!("Marketplace download error: {}", error));
-        }
-        let response = String::from_utf8_lossy(&output.stdout);
+        let response = Self::api("download-journey", Some(&download_data))
+            .map_err(|e| anyhow::anyhow!("Marketplace download error: {}", e))?;
         if response.trim().is_empty() {
             return Err(anyhow::anyhow!("Marketplace download returned empty response"));
         }
@@ -927,7 +1446,7 @@ impl JourneyMarketplace {
                             .join("journeys");
                         fs::create_dir_all(&journey_dir)?;
                         let journey_file = journey_dir.join(filename);
-                        fs::write(&journey_file, content)?;
+                        crate::storage::write_atomic(&journey_file, content)?;
                         println!("✅ Journey downloaded successfully!");
                         println!(
                             "📁 Saved to: {}", journey_file.display().to_string()
@@ -967,24 +1486,13 @@ impl JourneyMarketplace {
         } else {
             format!("q={}&limit={}", urlencoding::encode(query), limit)
         };
-        let output = Command::new("curl")
-            .args(
-                &[
-                    "-X",
-                    "GET",
-                    &format!(
-                        "https://cargo.do/api/marketplace/search-journeys?{}", params
-                    ),
-                ],
+        let endpoint = format!("search-journeys?{}", params);
+        let response = crate::net::cached(
+                &format!("{}/{}", MARKETPLACE_API, endpoint),
+                SEARCH_CACHE_TTL,
+                || Self::api(&endpoint, None),
             )
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()?;
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow::anyhow!("Marketplace search error: {}", error));
-        }
-        let response = String::from_utf8_lossy(&output.stdout);
+            .map_err(|e| anyhow::anyhow!("Marketplace search error: {}", e))?;
         if response.trim().is_empty() {
             return Err(anyhow::anyhow!("Marketplace search returned empty response"));
         }
@@ -1065,6 +1573,7 @@ impl JourneyMarketplace {
         }
     }
 }
+#[cfg(not(target_arch = "wasm32"))]
 pub fn check_buoy_clearance(command: &str) -> Result<bool> {
     println!(
         "🛟 Buoy check! Verifying command '{}' through the navigation channel", command
@@ -1097,4 +1606,171 @@ pub fn check_buoy_clearance(command: &str) -> Result<bool> {
             Ok(false)
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    fn journey(name: &str, commands: Vec<JourneyCommand>) -> Journey {
+        Journey {
+            name: name.to_string(),
+            description: String::new(),
+            created: Utc::now(),
+            commands,
+            variables: HashMap::new(),
+            checkpoints: Vec::new(),
+            environment: HashMap::new(),
+            success_rate: 0.0,
+            author: None,
+            tags: Vec::new(),
+            downloads: 0,
+            rating: 0.0,
+            max_parallel: None,
+        }
+    }
+    fn command(command: &str) -> JourneyCommand {
+        JourneyCommand {
+            command: command.to_string(),
+            ..JourneyCommand::default()
+        }
+    }
+    #[test]
+    fn test_includes_expand_and_remap_checkpoints() {
+        let storage = MemoryStorage::new();
+        save_journey_to(&storage, &journey("setup-db", vec![command("createdb"), command("migrate")]))
+            .unwrap();
+        let mut release = journey(
+            "release",
+            vec![JourneyCommand::include("setup-db"), command("cargo")],
+        );
+        release
+            .checkpoints
+            .push(Checkpoint {
+                name: "built".to_string(),
+                command_index: 1,
+                validation: CheckpointValidation::Custom(String::new()),
+            });
+        let resolved = resolve_includes_from(&storage, &release).unwrap();
+        let commands: Vec<&str> = resolved.commands.iter().map(|c| c.command.as_str()).collect();
+        assert_eq!(commands, vec!["createdb", "migrate", "cargo"]);
+        assert_eq!(resolved.checkpoints[0].command_index, 2);
+        let parsed: JourneyCommand = serde_json::from_str(r#"{"include": "setup-db"}"#).unwrap();
+        assert_eq!(parsed.include.as_deref(), Some("setup-db"));
+    }
+    #[test]
+    fn test_include_cycle_and_compose() {
+        let storage = MemoryStorage::new();
+        save_journey_to(&storage, &journey("a", vec![JourneyCommand::include("b")])).unwrap();
+        save_journey_to(&storage, &journey("b", vec![JourneyCommand::include("a")])).unwrap();
+        let err = resolve_includes_from(&storage, &load_journey_from(&storage, "a").unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("a -> b -> a"));
+        save_journey_to(&storage, &journey("c", vec![command("cargo")])).unwrap();
+        let composed = compose_journeys_in(&storage, "flow", &["c".to_string(), "c".to_string()])
+            .unwrap();
+        assert_eq!(composed.commands.len(), 2);
+        assert!(load_journey_from(&storage, "flow").is_ok());
+        assert!(compose_journeys_in(&storage, "c", &["c".to_string()]).is_err());
+    }
+    #[test]
+    fn test_parallel_groups_span_consecutive_steps() {
+        let parsed: JourneyCommand = serde_json::from_str(
+                r#"{"command": "cargo", "args": ["fmt"], "parallel": "checks"}"#,
+            )
+            .unwrap();
+        let mut clippy = command("cargo");
+        clippy.parallel = Some("checks".to_string());
+        let commands = vec![command("cargo"), parsed, clippy, command("cargo")];
+        assert_eq!(group_end(&commands, 0), 1);
+        assert_eq!(group_end(&commands, 1), 3);
+        assert_eq!(group_end(&commands, 3), 4);
+        assert_eq!(step_label(&commands[1], "cargo", &commands[1].args), "fmt");
+    }
+    #[test]
+    fn test_prompt_and_confirm_steps_with_yes() {
+        let prompt: JourneyCommand = serde_json::from_str(
+                r#"{"type": "prompt", "variable": "version", "default": "1.2.0"}"#,
+            )
+            .unwrap();
+        assert_eq!(prompt.kind, StepKind::Prompt);
+        let confirm = JourneyCommand {
+            kind: StepKind::Confirm,
+            description: Some("Publish {{version}}?".to_string()),
+            ..JourneyCommand::default()
+        };
+        let mut publish = command("cargo");
+        publish.args = vec!["publish".to_string(), "--tag".to_string(), "v{{version}}".to_string()];
+        let release = journey("release", vec![prompt, confirm, publish]);
+        let mut events = Vec::new();
+        JourneyPlayer::new(true, false)
+            .assume_yes(true)
+            .run(&release, |event| events.push(format!("{:?}", event)))
+            .unwrap();
+        assert!(events.iter().any(|e| e.contains("AutoConfirmed(\"Publish 1.2.0?\")")));
+        assert!(events.iter().any(|e| e.contains("\"v1.2.0\"")));
+        let gated = journey("gated", vec![JourneyCommand { kind: StepKind::Confirm, ..JourneyCommand::default() }]);
+        assert!(JourneyPlayer::new(false, false).run(&gated, |_| {}).is_err());
+    }
+    /// Answers one request per body and hands back each request as received
+    #[cfg(not(target_arch = "wasm32"))]
+    fn serve(bodies: Vec<&'static str>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}/api/marketplace", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            bodies
+                .into_iter()
+                .map(|body| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    loop {
+                        let n = stream.read(&mut buf).unwrap();
+                        request.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&request).to_string();
+                        if let Some(end) = text.find("\r\n\r\n") {
+                            let length = text
+                                .lines()
+                                .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                                .and_then(|v| v.parse::<usize>().ok())
+                                .unwrap_or(0);
+                            if n == 0 || request.len() >= end + 4 + length {
+                                break;
+                            }
+                        }
+                    }
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    stream.write_all(response.as_bytes()).unwrap();
+                    String::from_utf8_lossy(&request).to_string()
+                })
+                .collect()
+        });
+        (base, server)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_marketplace_publish_and_search_from_the_runtime() {
+        let (base, server) = serve(vec![
+            r#"{"success": true, "journey_id": 7}"#,
+            r#"{"success": true, "journeys": []}"#,
+        ]);
+        let payload = serde_json::json!({ "journey_data": { "title": "release" } });
+        // The same dispatch main uses for `cm journey`
+        let (published, found) = tokio::task::block_in_place(|| {
+            (
+                JourneyMarketplace::api_at(&base, "publish-journey", Some(&payload)).unwrap(),
+                JourneyMarketplace::api_at(&base, "search-journeys?q=release&limit=5", None).unwrap(),
+            )
+        });
+        assert!(published.contains("\"journey_id\": 7"));
+        assert!(found.contains("\"journeys\""));
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /api/marketplace/publish-journey "));
+        assert!(requests[0].contains(r#"{"journey_data":{"title":"release"}}"#));
+        assert!(requests[1].starts_with("GET /api/marketplace/search-journeys?q=release&limit=5 "));
+    }
+}
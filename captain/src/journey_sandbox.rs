@@ -0,0 +1,238 @@
+use anyhow::{Context, Result};
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+/// Left out of the copy and the final diff; cargo rebuilds into the sandbox's own target/
+const SKIPPED_DIRS: &[&str] = &["target", ".git"];
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SandboxKind {
+    /// A container when `journey.sandbox_image` is set and docker or podman is installed, else a copy
+    Auto,
+    Copy,
+    Container,
+}
+#[derive(Debug, Clone)]
+struct Container {
+    runtime: PathBuf,
+    image: String,
+}
+/// A throwaway copy of the project that a journey plays in
+#[derive(Debug)]
+pub struct Sandbox {
+    pub root: PathBuf,
+    pub source: PathBuf,
+    /// Where playback started, relative to `source`; relative working dirs resolve from here
+    start: PathBuf,
+    container: Option<Container>,
+    keep: bool,
+}
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SandboxDiff {
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub deleted: Vec<String>,
+}
+fn container_runtime() -> Option<PathBuf> {
+    which::which("docker").or_else(|_| which::which("podman")).ok()
+}
+fn sandbox_image() -> Option<String> {
+    crate::captain::config::ConfigManager::new()
+        .ok()?
+        .get("journey.sandbox_image")
+        .map(|image| image.trim().to_string())
+        .filter(|image| !image.is_empty())
+}
+fn is_skipped(relative: &Path) -> bool {
+    relative
+        .components()
+        .next()
+        .is_some_and(|first| SKIPPED_DIRS.iter().any(|dir| first.as_os_str() == *dir))
+}
+/// Relative paths of every file under `root`, minus target/ and .git/
+fn files(root: &Path) -> BTreeSet<PathBuf> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| entry.path().strip_prefix(root).map_or(true, |rel| !is_skipped(rel)))
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .collect()
+}
+pub(crate) fn copy_tree(source: &Path, target: &Path) -> Result<usize> {
+    let mut copied = 0;
+    for relative in files(source) {
+        let destination = target.join(&relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(source.join(&relative), &destination)
+            .with_context(|| format!("Failed to copy {} into the sandbox", relative.display()))?;
+        copied += 1;
+    }
+    Ok(copied)
+}
+impl Sandbox {
+    pub fn create(journey: &str, kind: SandboxKind, keep: bool) -> Result<Self> {
+        let cwd = std::env::current_dir()?;
+        let source = crate::project::current_project()
+            .and_then(|bucket| bucket.manifest.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| cwd.clone());
+        let container = match kind {
+            SandboxKind::Copy => None,
+            SandboxKind::Auto => sandbox_image().zip(container_runtime()),
+            SandboxKind::Container => {
+                let image = sandbox_image()
+                    .context("Set journey.sandbox_image to the image journeys should run in")?;
+                let runtime = container_runtime().context("Neither docker nor podman is installed")?;
+                Some((image, runtime))
+            }
+        }
+        .map(|(image, runtime)| Container { runtime, image });
+        let safe_name: String = journey
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        let root = std::env::temp_dir().join(format!("cm-sandbox-{}-{}", safe_name, std::process::id()));
+        if root.exists() {
+            fs::remove_dir_all(&root)?;
+        }
+        fs::create_dir_all(&root)?;
+        let copied = copy_tree(&source, &root)?;
+        println!(
+            "🧪 Sandbox: {} file(s) from {} copied to {}{}",
+            copied,
+            source.display(),
+            root.display(),
+            container
+                .as_ref()
+                .map(|c| format!(" (running in {})", c.image))
+                .unwrap_or_default()
+        );
+        Ok(Self {
+            start: cwd.strip_prefix(&source).map(Path::to_path_buf).unwrap_or_default(),
+            root,
+            source,
+            container,
+            keep,
+        })
+    }
+    /// Where a journey path points inside the sandbox; paths into the real project are redirected
+    pub fn map(&self, path: &Path) -> PathBuf {
+        if path.is_absolute() {
+            match path.strip_prefix(&self.source) {
+                Ok(relative) => self.root.join(relative),
+                Err(_) => path.to_path_buf(),
+            }
+        } else {
+            self.root.join(&self.start).join(path)
+        }
+    }
+    /// `command` run from `working_dir` inside the container; None for a plain copy. `raw`
+    /// passes the command line to the shell unquoted, for `~` and `$` expansion
+    pub fn container_process(&self, command: &str, args: &[String], working_dir: &Path, raw: bool) -> Option<Result<Command>> {
+        let container = self.container.as_ref()?;
+        let working_dir = self.map(working_dir);
+        let Ok(relative) = working_dir.strip_prefix(&self.root) else {
+            return Some(Err(anyhow::anyhow!("{} is outside the sandbox", working_dir.display())));
+        };
+        let script = if raw {
+            std::iter::once(command.to_string()).chain(args.iter().cloned()).collect::<Vec<_>>().join(" ")
+        } else {
+            shell_words::join(std::iter::once(command).chain(args.iter().map(String::as_str)))
+        };
+        let mut process = Command::new(&container.runtime);
+        process
+            .args(["run", "--rm", "-v"])
+            .arg(format!("{}:/sandbox", self.root.display()))
+            .arg("-w")
+            .arg(Path::new("/sandbox").join(relative).to_string_lossy().replace('\\', "/"))
+            .args([container.image.as_str(), "sh", "-c", &script]);
+        Some(Ok(process))
+    }
+    pub fn diff(&self) -> SandboxDiff {
+        let (real, sandboxed) = (files(&self.source), files(&self.root));
+        let show = |path: &PathBuf| path.to_string_lossy().replace('\\', "/");
+        SandboxDiff {
+            added: sandboxed.difference(&real).map(show).collect(),
+            deleted: real.difference(&sandboxed).map(show).collect(),
+            modified: real
+                .intersection(&sandboxed)
+                .filter(|path| fs::read(self.source.join(path)).ok() != fs::read(self.root.join(path)).ok())
+                .map(show)
+                .collect(),
+        }
+    }
+}
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        if self.keep {
+            println!("📂 Sandbox kept at {}", self.root.display());
+        } else {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+}
+impl SandboxDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+    pub fn display(&self) {
+        println!("\n{}", "🧪 Sandbox vs. working tree".bold().blue());
+        if self.is_empty() {
+            println!("✅ The journey left no changes (target/ and .git/ are not compared)");
+            return;
+        }
+        for path in &self.added {
+            println!("   + {}", path.green());
+        }
+        for path in &self.modified {
+            println!("   ~ {}", path.yellow());
+        }
+        for path in &self.deleted {
+            println!("   - {}", path.red());
+        }
+        println!("Your working tree was not touched; rerun with --keep-sandbox to inspect the result");
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_copy_map_and_diff() {
+        let base = std::env::temp_dir().join(format!("cm-sandbox-test-{}", std::process::id()));
+        let (source, root) = (base.join("project"), base.join("sandbox"));
+        for dir in ["src", "target/debug", ".git"] {
+            fs::create_dir_all(source.join(dir)).unwrap();
+        }
+        fs::write(source.join("Cargo.toml"), "[package]").unwrap();
+        fs::write(source.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(source.join("target/debug/app"), "binary").unwrap();
+        fs::write(source.join(".git/HEAD"), "ref").unwrap();
+        fs::create_dir_all(&root).unwrap();
+        assert_eq!(copy_tree(&source, &root).unwrap(), 2);
+        let sandbox = Sandbox {
+            root: root.clone(),
+            source: source.clone(),
+            start: PathBuf::from("src"),
+            container: None,
+            keep: false,
+        };
+        assert_eq!(sandbox.map(Path::new(".")), root.join("src").join("."));
+        assert_eq!(sandbox.map(&source.join("Cargo.toml")), root.join("Cargo.toml"));
+        assert_eq!(sandbox.map(Path::new("/etc/hosts")), PathBuf::from("/etc/hosts"));
+        fs::write(root.join("src/main.rs"), "fn main() { migrate(); }").unwrap();
+        fs::write(root.join("schema.sql"), "create table t();").unwrap();
+        fs::remove_file(root.join("Cargo.toml")).unwrap();
+        let diff = sandbox.diff();
+        assert_eq!(diff.added, vec!["schema.sql"]);
+        assert_eq!(diff.modified, vec!["src/main.rs"]);
+        assert_eq!(diff.deleted, vec!["Cargo.toml"]);
+        drop(sandbox);
+        assert!(!root.exists());
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -1,27 +1,159 @@
 pub mod anchor;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod anchor_crypto;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod artifacts;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod audit;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod admin_msg;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod affected;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod affiliate;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cache;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod captain;
 pub mod captain_log;
+pub mod ci;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod checklist;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod diff_build;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod display;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod doctor;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod features;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod fingerprint;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod fmt_gate;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod gc;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod git;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod githooks;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod history;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod hooks;
 pub mod journey;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod journey_sandbox;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lockdiff;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod matrix;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod map_render;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod mutiny;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod net;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod notify;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod optimize;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod outdated;
 pub mod parser;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod plugins;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod privacy;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod profiler;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod profile_bundle;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod project;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod project_env;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod recall;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod report;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod runs;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod scaffold;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod scripting;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod scrub;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod scrub_schedule;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod secrets;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod self_update;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod shortcuts;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod serve;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod smart_clean;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod smart_parser;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod stats;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod status;
+pub mod storage;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod team_sync;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod test_results;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod tide;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tide_graph;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tide_report;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod tools;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod undo;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod treasure_map;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod typosquat;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ui;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod user;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod version;
-pub use crate::captain::config::ConfigManager;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod workspace;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod wtf_sessions;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod wtf_usage;
+pub use crate::anchor::{Anchor, AnchorDiff, AnchorManager, AnchorSummary, RestoreReport};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::captain::config::{ConfigEntry, ConfigManager, ConfigSource};
+pub use crate::captain_log::{BuildResult, CaptainLog, ExportFormat, LogAnalysis, LogEntry};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::journey::JourneyPlayer;
+pub use crate::journey::{Journey, JourneyEvent, PlaybackReport};
+pub use crate::storage::{FsStorage, Storage};
+#[cfg(target_arch = "wasm32")]
+pub use crate::storage::MemoryStorage;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::tide::{BuildMetrics, DailySummary, DependencyScan, TideCharts};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::version::{VersionChange, VersionInfo, VersionManager};
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use crate::storage::indexed_db::IndexedDbStorage;
 #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
 pub fn init_wasm() {
     console_log::init_with_level(log::Level::Info).ok();
 }
 pub fn get_version() -> &'static str {
     env!("CARGO_PKG_VERSION")
-}
\ No newline at end of file
+}
@@ -0,0 +1,314 @@
+use crate::outdated::{classify, UpdateKind};
+use anyhow::{bail, Context, Result};
+use colored::*;
+use semver::Version;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LockPackage {
+    pub name: String,
+    pub version: String,
+    pub source: Option<String>,
+    pub dependencies: Vec<String>,
+}
+impl LockPackage {
+    fn id(&self) -> String {
+        format!("{} {}", self.name, self.version)
+    }
+    fn depends_on(&self, name: &str, version: &str) -> bool {
+        self.dependencies
+            .iter()
+            .any(|dep| {
+                let mut parts = dep.split_whitespace();
+                parts.next() == Some(name)
+                    && parts.next().is_none_or(|v| v == version)
+            })
+    }
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionChange {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+    pub kind: Option<UpdateKind>,
+    pub downgrade: bool,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct AddedCrate {
+    pub name: String,
+    pub version: String,
+    pub pulled_in_by: Vec<String>,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct LockDiff {
+    pub base: String,
+    pub changed: Vec<VersionChange>,
+    pub added: Vec<AddedCrate>,
+    pub removed: Vec<String>,
+}
+pub fn parse_lock(text: &str) -> Result<Vec<LockPackage>> {
+    let lock: toml::Value = text.parse().context("Failed to parse Cargo.lock")?;
+    let packages = lock.get("package").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+    Ok(
+        packages
+            .iter()
+            .filter_map(|package| {
+                let field = |key: &str| package.get(key).and_then(|v| v.as_str()).map(String::from);
+                Some(LockPackage {
+                    name: field("name")?,
+                    version: field("version")?,
+                    source: field("source"),
+                    dependencies: package
+                        .get("dependencies")
+                        .and_then(|d| d.as_array())
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|d| d.as_str().map(String::from))
+                        .collect(),
+                })
+            })
+            .collect(),
+    )
+}
+fn versions_by_name(packages: &[LockPackage]) -> BTreeMap<&str, Vec<&str>> {
+    let mut versions: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for package in packages {
+        versions.entry(&package.name).or_default().push(&package.version);
+    }
+    versions
+}
+pub fn introduction_path(packages: &[LockPackage], target: &LockPackage) -> Vec<String> {
+    let index: HashMap<String, &LockPackage> = packages.iter().map(|p| (p.id(), p)).collect();
+    let mut parent: HashMap<String, String> = HashMap::new();
+    let mut seen: HashSet<String> = HashSet::from([target.id()]);
+    let mut queue = VecDeque::from([target.id()]);
+    while let Some(id) = queue.pop_front() {
+        let current = index[&id];
+        if current.source.is_none() && id != target.id() {
+            let mut path = vec![id.clone()];
+            let mut cursor = id;
+            while let Some(next) = parent.get(&cursor) {
+                path.push(next.clone());
+                cursor = next.clone();
+            }
+            path.pop();
+            path.reverse();
+            return path;
+        }
+        for dependent in packages.iter().filter(|p| p.depends_on(&current.name, &current.version)) {
+            if seen.insert(dependent.id()) {
+                parent.insert(dependent.id(), id.clone());
+                queue.push_back(dependent.id());
+            }
+        }
+    }
+    let mut direct: Vec<String> = packages
+        .iter()
+        .filter(|p| p.depends_on(&target.name, &target.version))
+        .map(LockPackage::id)
+        .collect();
+    direct.truncate(1);
+    direct
+}
+pub fn diff(base: &str, old: &[LockPackage], new: &[LockPackage]) -> LockDiff {
+    let old_versions = versions_by_name(old);
+    let new_versions = versions_by_name(new);
+    let mut changed = Vec::new();
+    let mut added_ids: Vec<(String, String)> = Vec::new();
+    let mut removed = Vec::new();
+    for (name, new_list) in &new_versions {
+        let old_list = old_versions.get(name).cloned().unwrap_or_default();
+        let gained: Vec<&str> = new_list.iter().filter(|v| !old_list.contains(v)).copied().collect();
+        let lost: Vec<&str> = old_list.iter().filter(|v| !new_list.contains(v)).copied().collect();
+        if gained.len() == 1 && lost.len() == 1 {
+            let (from, to) = (lost[0], gained[0]);
+            let parsed = (Version::parse(from).ok(), Version::parse(to).ok());
+            let (kind, downgrade) = match parsed {
+                (Some(from), Some(to)) => (classify(&from, &to), to < from),
+                _ => (None, false),
+            };
+            changed
+                .push(VersionChange {
+                    name: name.to_string(),
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    kind,
+                    downgrade,
+                });
+            continue;
+        }
+        added_ids.extend(gained.iter().map(|v| (name.to_string(), v.to_string())));
+        removed.extend(lost.iter().map(|v| format!("{} {}", name, v)));
+    }
+    for (name, old_list) in &old_versions {
+        if !new_versions.contains_key(name) {
+            removed.extend(old_list.iter().map(|v| format!("{} {}", name, v)));
+        }
+    }
+    let added = added_ids
+        .into_iter()
+        .filter_map(|(name, version)| {
+            let package = new.iter().find(|p| p.name == name && p.version == version)?;
+            Some(AddedCrate {
+                pulled_in_by: introduction_path(new, package),
+                name,
+                version,
+            })
+        })
+        .collect();
+    LockDiff {
+        base: base.to_string(),
+        changed,
+        added,
+        removed,
+    }
+}
+fn git(args: &[&str]) -> Option<String> {
+    crate::git::git(args).map(|out| out.trim().to_string())
+}
+pub fn default_base() -> String {
+    let upstream = git(&["rev-parse", "--abbrev-ref", "origin/HEAD"])
+        .into_iter()
+        .chain(["origin/main", "origin/master", "main", "master"].map(String::from))
+        .find(|branch| git(&["rev-parse", "--verify", "--quiet", branch]).is_some());
+    let head = git(&["rev-parse", "HEAD"]);
+    if let Some(upstream) = upstream {
+        if let Some(base) = git(&["merge-base", "HEAD", &upstream]) {
+            if Some(&base) != head.as_ref() {
+                return base;
+            }
+        }
+    }
+    "HEAD~1".to_string()
+}
+pub fn lockfile_path() -> Result<PathBuf> {
+    let manifest = crate::project::find_manifest(&std::env::current_dir()?)
+        .context("No Cargo.toml found - run this inside a cargo project")?;
+    Ok(manifest.with_file_name("Cargo.lock"))
+}
+pub fn lock_at_revision(rev: &str, lockfile: &Path) -> Result<String> {
+    let toplevel = git(&["rev-parse", "--show-toplevel"]).context("Not inside a git repository")?;
+    let toplevel = Path::new(&toplevel).canonicalize()?;
+    let lockfile = lockfile.canonicalize()?;
+    let relative = lockfile
+        .strip_prefix(&toplevel)
+        .context("Cargo.lock is outside the git repository")?;
+    let spec = format!("{}:{}", rev, relative.to_string_lossy().replace('\\', "/"));
+    match git(&["show", &spec]) {
+        Some(text) => Ok(text),
+        None => bail!("Cargo.lock does not exist at {}", rev),
+    }
+}
+fn print_diff(diff: &LockDiff) {
+    println!("🔒 {} against {}", "Cargo.lock changes".bold().blue(), diff.base.cyan());
+    if diff.changed.is_empty() && diff.added.is_empty() && diff.removed.is_empty() {
+        println!("✅ No dependency changes");
+        return;
+    }
+    if !diff.changed.is_empty() {
+        println!("\n🔄 Updated ({})", diff.changed.len());
+        for change in &diff.changed {
+            let kind = match (change.downgrade, change.kind) {
+                (true, _) => "downgrade".red(),
+                (false, Some(UpdateKind::Major)) => "major".red(),
+                (false, Some(UpdateKind::Minor)) => "minor".yellow(),
+                (false, Some(UpdateKind::Patch)) => "patch".green(),
+                (false, None) => "changed".normal(),
+            };
+            println!("  {} {} → {}  {}", change.name.cyan(), change.from, change.to.bold(), kind);
+        }
+    }
+    if !diff.added.is_empty() {
+        println!("\n➕ Added ({})", diff.added.len());
+        let added: HashSet<String> = diff
+            .added
+            .iter()
+            .map(|a| format!("{} {}", a.name, a.version))
+            .collect();
+        for crate_added in &diff.added {
+            println!("  {} {}", crate_added.name.green().bold(), crate_added.version);
+            for (depth, dependent) in crate_added.pulled_in_by.iter().enumerate() {
+                let marker = if added.contains(dependent) { " (new)".green().to_string() } else { String::new() };
+                println!(
+                    "  {}└── pulled in by {}{}", "    ".repeat(depth), dependent, marker
+                );
+            }
+        }
+    }
+    if !diff.removed.is_empty() {
+        println!("\n➖ Removed ({})", diff.removed.len());
+        for removed in &diff.removed {
+            println!("  {}", removed.red());
+        }
+    }
+}
+pub fn handle_lockdiff(rev: Option<String>, json: bool) -> Result<()> {
+    let lockfile = lockfile_path()?;
+    let current = fs::read_to_string(&lockfile)
+        .with_context(|| format!("Failed to read {}", lockfile.display()))?;
+    let base = rev.unwrap_or_else(default_base);
+    let old = parse_lock(&lock_at_revision(&base, &lockfile)?)?;
+    let new = parse_lock(&current)?;
+    let diff = diff(&base, &old, &new);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print_diff(&diff);
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const OLD: &str = r#"
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["serde", "log"]
+
+[[package]]
+name = "serde"
+version = "1.0.190"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "log"
+version = "0.4.20"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+    const NEW: &str = r#"
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["serde", "tracing"]
+
+[[package]]
+name = "serde"
+version = "1.0.210"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "tracing"
+version = "0.1.40"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["pin-project-lite"]
+
+[[package]]
+name = "pin-project-lite"
+version = "0.2.13"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+    #[test]
+    fn explains_updates_additions_and_removals() {
+        let diff = diff("HEAD~1", &parse_lock(OLD).unwrap(), &parse_lock(NEW).unwrap());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].kind, Some(UpdateKind::Patch));
+        assert_eq!(diff.removed, vec!["log 0.4.20".to_string()]);
+        let pin = diff.added.iter().find(|a| a.name == "pin-project-lite").unwrap();
+        assert_eq!(pin.pulled_in_by, vec!["tracing 0.1.40", "app 0.1.0"]);
+        let tracing = diff.added.iter().find(|a| a.name == "tracing").unwrap();
+        assert_eq!(tracing.pulled_in_by, vec!["app 0.1.0"]);
+    }
+}
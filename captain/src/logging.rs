@@ -0,0 +1,291 @@
+use crate::captain::config::ConfigManager;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use clap::Subcommand;
+use colored::*;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+/// Overrides `log.level` for stderr, e.g. `CM_LOG=debug cm build`
+pub const LOG_ENV: &str = "CM_LOG";
+const LOG_FILE: &str = "cm.log";
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+/// Rotated files are kept as `cm.log.1` (newest) to `cm.log.5`
+const KEEP_ROTATED: usize = 5;
+#[derive(Subcommand, Debug)]
+pub enum LogsAction {
+    /// Print the last lines of the log and follow new ones
+    Tail {
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: usize,
+        /// Only this level and above (error, warn, info, debug, trace)
+        #[arg(long)]
+        level: Option<String>,
+        /// Print and exit instead of following
+        #[arg(long)]
+        no_follow: bool,
+        /// Raw JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print where the logs are written
+    Path,
+}
+/// One line of `~/.shipwreck/logs/cm.log`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogLine {
+    pub ts: DateTime<Utc>,
+    pub level: String,
+    pub target: String,
+    pub pid: u32,
+    pub msg: String,
+}
+impl LogLine {
+    fn level(&self) -> Level {
+        self.level.parse().unwrap_or(Level::Info)
+    }
+    fn display(&self) -> String {
+        let level = match self.level() {
+            Level::Error => "ERROR".red().bold(),
+            Level::Warn => "WARN ".yellow().bold(),
+            Level::Info => "INFO ".green(),
+            Level::Debug => "DEBUG".blue(),
+            Level::Trace => "TRACE".dimmed(),
+        };
+        format!(
+            "{} {} {} {}",
+            self.ts.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string().dimmed(),
+            level,
+            format!("[{}]", self.pid).dimmed(),
+            self.msg
+        )
+    }
+}
+/// Human output on stderr and JSON lines in the log file, each with its own level
+struct Logger {
+    stderr: LevelFilter,
+    file_level: LevelFilter,
+    file: Mutex<Option<File>>,
+}
+impl Logger {
+    /// Dependencies (reqwest, hyper, notify) only get through with warnings and errors
+    fn ours(metadata: &Metadata) -> bool {
+        let target = metadata.target();
+        target == "cm" || target.starts_with("cm::") || target.starts_with("cargo_mate")
+    }
+}
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.stderr.max(self.file_level) && (Self::ours(metadata) || metadata.level() <= Level::Warn)
+    }
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if record.level() <= self.stderr {
+            eprintln!("{}", human(record.level(), &record.args().to_string()));
+        }
+        if record.level() <= self.file_level {
+            let line = LogLine {
+                ts: Utc::now(),
+                level: record.level().to_string(),
+                target: record.target().to_string(),
+                pid: std::process::id(),
+                msg: record.args().to_string(),
+            };
+            if let (Ok(mut file), Ok(mut json)) = (self.file.lock(), serde_json::to_string(&line)) {
+                if let Some(file) = file.as_mut() {
+                    // One write per line, so lines from parallel cm processes never interleave
+                    json.push('\n');
+                    let _ = file.write_all(json.as_bytes());
+                }
+            }
+        }
+    }
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+/// The same markers the rest of cm prints, so moving a message to `log` doesn't change it
+fn human(level: Level, msg: &str) -> String {
+    match level {
+        Level::Error => format!("❌ {}", msg),
+        Level::Warn => format!("⚠️  {}", msg),
+        Level::Info => msg.to_string(),
+        Level::Debug | Level::Trace => format!("{} {}", format!("[{}]", level.as_str().to_lowercase()).dimmed(), msg),
+    }
+}
+pub fn parse_level(value: &str) -> Option<LevelFilter> {
+    value.trim().parse().ok()
+}
+pub fn logs_dir() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join("logs"))
+}
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    path.with_file_name(format!("{}.{}", LOG_FILE, n))
+}
+/// Moves `cm.log` to `cm.log.1` (and older ones up, dropping the last) once it passes `max_bytes`
+fn rotate(path: &Path, max_bytes: u64, keep: usize) -> Result<()> {
+    let size = |path: &Path| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if size(path) <= max_bytes {
+        return Ok(());
+    }
+    let _lock = crate::storage::lock(path)?;
+    // Another cm may have rotated while this one waited
+    if size(path) <= max_bytes {
+        return Ok(());
+    }
+    let _ = fs::remove_file(rotated(path, keep));
+    for n in (1..keep).rev() {
+        let _ = fs::rename(rotated(path, n), rotated(path, n + 1));
+    }
+    fs::rename(path, rotated(path, 1))?;
+    Ok(())
+}
+fn open_log() -> Result<File> {
+    let dir = logs_dir()?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(LOG_FILE);
+    rotate(&path, MAX_LOG_BYTES, KEEP_ROTATED)?;
+    OpenOptions::new().create(true).append(true).open(&path).with_context(|| format!("Failed to open {}", path.display()))
+}
+/// Installs the logger: stderr at `CM_LOG` or `log.level` (default info), the file at
+/// `log.file_level` (default debug, `off` disables it)
+pub fn init() {
+    let config = ConfigManager::new().ok();
+    let configured = |key: &str| config.as_ref().and_then(|c| c.get(key)).and_then(|v| parse_level(&v));
+    let stderr = std::env::var(LOG_ENV)
+        .ok()
+        .and_then(|v| parse_level(&v))
+        .or_else(|| configured("log.level"))
+        .unwrap_or(LevelFilter::Info);
+    let file_level = configured("log.file_level").unwrap_or(LevelFilter::Debug);
+    let file = if file_level == LevelFilter::Off { None } else { open_log().ok() };
+    let file_level = if file.is_some() { file_level } else { LevelFilter::Off };
+    let logger = Logger { stderr, file_level, file: Mutex::new(file) };
+    if log::set_logger(Box::leak(Box::new(logger))).is_ok() {
+        log::set_max_level(stderr.max(file_level));
+    }
+}
+/// The last `count` lines at `min` or above; lines that don't parse are skipped
+fn last_lines(path: &Path, count: usize, min: LevelFilter) -> Result<Vec<LogLine>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut lines: Vec<LogLine> = BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str::<LogLine>(&line).ok())
+        .filter(|line| line.level() <= min)
+        .collect();
+    lines.drain(..lines.len().saturating_sub(count));
+    Ok(lines)
+}
+fn print_line(line: &LogLine, json: bool) {
+    if json {
+        println!("{}", serde_json::to_string(line).unwrap_or_default());
+    } else {
+        println!("{}", line.display());
+    }
+}
+fn tail(count: usize, min: LevelFilter, follow: bool, json: bool) -> Result<()> {
+    let path = logs_dir()?.join(LOG_FILE);
+    for line in last_lines(&path, count, min)? {
+        print_line(&line, json);
+    }
+    if !follow {
+        return Ok(());
+    }
+    eprintln!("{}", format!("📜 Following {} (Ctrl+C to stop)", path.display()).dimmed());
+    let mut position = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    let mut pending = String::new();
+    loop {
+        std::thread::sleep(Duration::from_millis(500));
+        let Ok(mut file) = File::open(&path) else {
+            continue;
+        };
+        let len = file.metadata()?.len();
+        if len < position {
+            // Rotated: the new file starts from scratch
+            position = 0;
+            pending.clear();
+        }
+        if len == position {
+            continue;
+        }
+        file.seek(SeekFrom::Start(position))?;
+        let mut reader = BufReader::new(file);
+        let mut chunk = String::new();
+        while reader.read_line(&mut chunk)? > 0 {}
+        position += chunk.len() as u64;
+        pending.push_str(&chunk);
+        while let Some(end) = pending.find('\n') {
+            let raw: String = pending.drain(..=end).collect();
+            if let Ok(line) = serde_json::from_str::<LogLine>(raw.trim()) {
+                if line.level() <= min {
+                    print_line(&line, json);
+                }
+            }
+        }
+    }
+}
+/// `cm debug logs`
+pub fn handle_logs(action: LogsAction) -> Result<()> {
+    match action {
+        LogsAction::Tail { lines, level, no_follow, json } => {
+            let min = match level {
+                Some(level) => parse_level(&level).with_context(|| format!("Unknown log level '{}'", level))?,
+                None => LevelFilter::Trace,
+            };
+            tail(lines, min, !no_follow, json)
+        }
+        LogsAction::Path => {
+            println!("{}", logs_dir()?.join(LOG_FILE).display());
+            Ok(())
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_rotation_keeps_a_bounded_set_of_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOG_FILE);
+        let line = LogLine {
+            ts: Utc::now(),
+            level: "WARN".to_string(),
+            target: "cm::storage".to_string(),
+            pid: 7,
+            msg: "history.json was unreadable".to_string(),
+        };
+        for generation in 0..5 {
+            let mut content = String::new();
+            for level in ["ERROR", "WARN", "INFO", "DEBUG"] {
+                let entry = LogLine { level: level.to_string(), msg: format!("{} {}", line.msg, generation), ..line.clone() };
+                content.push_str(&format!("{}\n", serde_json::to_string(&entry).unwrap()));
+            }
+            fs::write(&path, content).unwrap();
+            rotate(&path, 10, 3).unwrap();
+        }
+        assert!(!path.exists());
+        assert!(rotated(&path, 1).exists() && rotated(&path, 3).exists());
+        assert!(!rotated(&path, 4).exists());
+        let newest = last_lines(&rotated(&path, 1), 10, LevelFilter::Warn).unwrap();
+        assert_eq!(newest.len(), 2);
+        assert_eq!(newest[1], LogLine { msg: format!("{} 4", line.msg), ..line.clone() });
+        assert_eq!(last_lines(&rotated(&path, 3), 1, LevelFilter::Trace).unwrap()[0].msg, format!("{} 2", line.msg));
+        assert_eq!(parse_level("Debug"), Some(LevelFilter::Debug));
+        assert_eq!(parse_level("loud"), None);
+    }
+}
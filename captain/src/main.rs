@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 use chrono;
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use colored::*;
 use serde::{Deserialize, Serialize};
 use serde_json;
@@ -10,26 +10,87 @@ use std::path::{Path, PathBuf};
 use reqwest;
 use crate::captain::config::ConfigAction;
 mod anchor;
+mod anchor_crypto;
+mod artifacts;
+mod audit;
 mod admin_msg;
 mod affiliate;
 mod captain;
 mod captain_log;
+mod ci;
 mod checklist;
 mod display;
+mod fingerprint;
+mod fmt_gate;
+mod git;
+mod githooks;
 mod history;
+mod hooks;
+mod i18n;
+mod idea;
+mod logging;
 mod journey;
+mod journey_sandbox;
+mod features;
+mod lockdiff;
+mod diff_build;
+mod matrix;
+mod map_render;
 mod mutiny;
+mod outdated;
 mod parser;
+mod project;
+mod publish;
+mod recall;
+mod report;
+mod runs;
+mod scaffold;
+mod smart_clean;
+mod cache;
+mod crates;
+mod docs;
+mod workspace;
+mod wtf_sessions;
+mod wtf_usage;
+mod net;
+mod doctor;
+mod gc;
+mod privacy;
+mod plugins;
+mod scripting;
+mod affected;
+mod profiler;
+mod profile_bundle;
+mod project_env;
+mod secrets;
+mod self_update;
 mod smart_parser;
+mod stats;
 mod strip;
 mod scat;
+mod scat_bundle;
+mod startup;
+mod release_harden;
+mod team_sync;
 mod tide;
+mod tide_graph;
+mod tide_report;
+mod typosquat;
 mod treasure_map;
+mod ui;
 mod version;
+mod notify;
 mod optimize;
 mod scrub;
+mod scrub_schedule;
+mod shortcuts;
+mod serve;
+mod status;
+mod storage;
+mod test_results;
 mod user;
 mod tools;
+mod undo;
 use crate::version::VersionManager;
 #[derive(Parser, Debug)]
 #[command(name = "cm")]
@@ -39,6 +100,10 @@ use crate::version::VersionManager;
 )]
 #[command(version, author)]
 struct Args {
+    #[arg(long, global = true)]
+    ci: bool,
+    #[arg(long, global = true)]
+    global: bool,
     #[command(subcommand)]
     command: Option<Commands>,
     #[arg(trailing_var_arg = true)]
@@ -65,6 +130,149 @@ enum Commands {
         #[arg(default_value = "50")]
         limit: usize,
     },
+    Ui,
+    Githooks { #[command(subcommand)] action: crate::githooks::GithooksAction },
+    FmtGate,
+    PublishCheck {
+        /// Package without compiling the packaged sources
+        #[arg(long)]
+        no_verify: bool,
+    },
+    Crates { #[command(subcommand)] action: crate::crates::CratesAction },
+    Docs { #[command(subcommand)] action: crate::docs::DocsAction },
+    Matrix(crate::matrix::MatrixArgs),
+    /// List `cm-<name>` plugins found on PATH
+    Plugins,
+    /// What cargo-mate sends where
+    Privacy { #[command(subcommand)] action: crate::privacy::PrivacyAction },
+    /// Diagnose the environment cargo-mate runs in
+    Doctor { #[command(subcommand)] action: crate::doctor::DoctorAction },
+    /// Shareable bug report with the latest build's diagnostics and environment
+    Report {
+        /// Defaults to a timestamped file under the project's shipwreck data
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[arg(long, value_enum)]
+        format: Option<crate::report::ReportFormat>,
+        /// Also upload it as a secret GitHub gist (needs the gh CLI)
+        #[arg(long)]
+        gist: bool,
+    },
+    Outdated {
+        #[arg(long, num_args = 1..)]
+        apply: Vec<String>,
+        #[arg(long, value_enum)]
+        upgrade: Option<crate::outdated::UpdateKind>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Find the smallest feature set each dependency needs
+    Features {
+        crates: Vec<String>,
+        #[arg(long)]
+        apply: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    Lockdiff {
+        rev: Option<String>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Explain what changed between two recorded builds (1 = latest)
+    DiffBuild {
+        from: Option<usize>,
+        to: Option<usize>,
+        /// Compare against the most recent successful build
+        #[arg(long)]
+        last_good: bool,
+        #[arg(long)]
+        list: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    Tests { #[command(subcommand)] action: TestsAction },
+    Cache { #[command(subcommand)] action: CacheAction },
+    Clean { #[command(subcommand)] action: CleanAction },
+    Gc {
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long)]
+        report: bool,
+    },
+    Audit { #[command(subcommand)] action: crate::audit::AuditAction },
+    Undo {
+        #[command(subcommand)]
+        action: Option<crate::undo::UndoAction>,
+        /// Revert even if the files were edited again afterwards
+        #[arg(long)]
+        force: bool,
+    },
+    Ws { #[command(subcommand)] action: Option<WsAction> },
+    Notify { #[command(subcommand)] action: crate::notify::NotifyAction },
+    Env { #[command(subcommand)] action: crate::project_env::EnvAction },
+    Secret { #[command(subcommand)] action: crate::secrets::SecretAction },
+    Status {
+        #[arg(long)]
+        porcelain: bool,
+        #[arg(long)]
+        here: bool,
+    },
+    Affected {
+        #[arg(value_enum)]
+        command: Option<affected::AffectedCommand>,
+        #[arg(long)]
+        since: Option<String>,
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    New {
+        template: Option<String>,
+        name: Option<String>,
+        #[arg(long = "var", value_name = "KEY=VALUE")]
+        vars: Vec<String>,
+        #[arg(long)]
+        list: bool,
+    },
+    Profile {
+        #[command(subcommand)]
+        action: Option<crate::profile_bundle::ProfileAction>,
+        bin: Option<String>,
+        #[arg(long)]
+        bench: Option<String>,
+        #[arg(long)]
+        instruments: bool,
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    Run {
+        bin: Option<String>,
+        #[arg(long)]
+        release: bool,
+        #[arg(long, conflicts_with_all = ["bin", "list"])]
+        replay: Option<String>,
+        #[arg(long)]
+        list: bool,
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    Again,
+    Recall {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        query: Vec<String>,
+    },
+    Serve {
+        #[arg(long)]
+        lsp: bool,
+        #[arg(long)]
+        port: Option<u16>,
+    },
+    Stats {
+        #[arg(long, default_value = "30")]
+        days: i64,
+        #[arg(long, default_value = "tui")]
+        output: String,
+    },
     Scrub { #[command(subcommand)] action: ScrubAction },
     Install,
     Activate,
@@ -78,22 +286,53 @@ enum Commands {
         status: bool,
         #[arg(long)]
         remaining: bool,
+        #[arg(long)]
+        sync: bool,
+    },
+    Msg { #[command(subcommand)] action: crate::admin_msg::MsgAction },
+    Idea {
+        #[command(subcommand)]
+        action: Option<crate::idea::IdeaAction>,
+        idea: Option<String>,
     },
-    Idea { idea: String },
     Wtf { #[command(subcommand)] action: crate::captain::wtf::WtfAction },
-    User,
-    Debug,
+    User { #[command(subcommand)] action: Option<crate::user::UserAction> },
+    Captain { #[command(subcommand)] action: crate::captain::manage::CaptainAction },
+    #[command(name = "self")]
+    SelfCmd { #[command(subcommand)] action: crate::self_update::SelfAction },
+    Debug { #[command(subcommand)] action: Option<DebugAction> },
     Strip(crate::strip::StripArgs),
     Scat(crate::scat::ScatArgs),
+    /// Strip, obfuscate and build a copy of the project for release
+    ReleaseHarden(crate::release_harden::ReleaseHardenArgs),
     Tool { #[command(subcommand)] action: ToolAction },
 }
 #[derive(Subcommand, Debug)]
+enum DebugAction {
+    /// Structured logs under ~/.shipwreck/logs
+    Logs { #[command(subcommand)] action: crate::logging::LogsAction },
+}
+#[derive(Subcommand, Debug)]
 enum JourneyAction {
     Record { name: String },
-    Play { name: String, #[arg(long)] dry_run: bool },
+    Play {
+        name: String,
+        #[arg(long)]
+        dry_run: bool,
+        /// Approve confirm steps and take prompt defaults without asking
+        #[arg(long, short = 'y')]
+        yes: bool,
+        /// Play in a temporary copy of the project (or a container) and diff it against the working tree
+        #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "auto")]
+        sandbox: Option<crate::journey_sandbox::SandboxKind>,
+        /// Keep the sandbox directory for inspection
+        #[arg(long, requires = "sandbox")]
+        keep_sandbox: bool,
+    },
     List,
     Export { name: String, output: PathBuf },
     Import { path: PathBuf },
+    Compose { name: String, #[arg(required = true, num_args = 2..)] journeys: Vec<String> },
     Publish { name: String, #[arg(long)] tags: Vec<String> },
     Download { gist_id: String },
     Search { query: String },
@@ -101,13 +340,44 @@ enum JourneyAction {
 }
 #[derive(Subcommand, Debug)]
 enum AnchorAction {
-    Save { name: String, #[arg(long)] message: Option<String> },
+    Save {
+        name: String,
+        #[arg(long)]
+        message: Option<String>,
+        /// Encrypt metadata and file backups (default: the anchor.encrypt config key)
+        #[arg(long)]
+        encrypt: bool,
+    },
     Restore { name: String },
     List,
     Show { name: String },
     Diff { name: String },
     Auto { name: String, #[arg(long)] foreground: bool },
     Stop { name: String },
+    /// Commit an anchor as a git branch (default anchor/<name>) or stash entry
+    ToGit {
+        name: String,
+        #[arg(long, conflicts_with = "stash")]
+        branch: Option<String>,
+        #[arg(long)]
+        stash: bool,
+        /// Replace an existing branch
+        #[arg(long)]
+        force: bool,
+        /// Also commit .env files, which are left out by default
+        #[arg(long)]
+        include_env: bool,
+    },
+    /// Save a git branch, commit or stash as an anchor
+    FromGit {
+        reference: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        message: Option<String>,
+        #[arg(long)]
+        force: bool,
+    },
 }
 #[derive(Subcommand, Debug)]
 enum LogAction {
@@ -122,23 +392,65 @@ enum LogAction {
 enum TideAction {
     Show,
     Analyze,
-    Export { path: PathBuf },
+    Export {
+        path: PathBuf,
+        #[arg(long, default_value = "csv", value_parser = ["csv", "html"])]
+        format: String,
+    },
+    Graph {
+        #[arg(long)]
+        html: Option<PathBuf>,
+        #[arg(long)]
+        build: bool,
+        #[arg(long)]
+        json: bool,
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
 }
 #[derive(Subcommand, Debug)]
 enum MapAction {
     Show,
     Analyze,
-    Export { path: PathBuf },
+    Export {
+        path: PathBuf,
+        #[arg(long, value_enum)]
+        format: Option<crate::map_render::MapFormat>,
+    },
     Path { from: String, to: String },
+    /// License of every crate and the strictest one inherited through its subtree
+    Licenses {
+        #[arg(value_name = "CRATE")]
+        krate: Option<String>,
+        /// Fail when this license is linked into a workspace binary (repeatable; adds to map.deny_licenses)
+        #[arg(long)]
+        deny: Vec<String>,
+    },
 }
 #[derive(Subcommand, Debug)]
 enum MutinyAction {
-    Activate { reason: String },
+    Activate {
+        reason: String,
+        #[arg(long)]
+        gate: bool,
+    },
     Deactivate,
-    AllowWarnings,
-    SkipTests,
-    Force,
-    Yolo,
+    AllowWarnings {
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    SkipTests {
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    Force {
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    Yolo {
+        #[arg(long)]
+        reason: Option<String>,
+    },
     Status,
 }
 #[derive(Subcommand, Debug)]
@@ -183,6 +495,15 @@ fn handle_tool_command(action: ToolAction) -> Result<()> {
         ToolAction::Run { name, args } => {
             tools::run_tool(&name, &args)?;
         }
+        ToolAction::RunAll { set, jobs } => {
+            tools::run_set(&set, jobs)?;
+        }
+        ToolAction::Pipeline { name: Some(name), report, fail_fast } => {
+            tools::pipeline::run_pipeline(&name, report, fail_fast)?;
+        }
+        ToolAction::Pipeline { name: None, .. } => {
+            tools::pipeline::list_pipelines()?;
+        }
         ToolAction::Execute(args) => {
             if args.is_empty() {
                 tools::list_tools();
@@ -198,88 +519,40 @@ fn handle_tool_command(action: ToolAction) -> Result<()> {
 #[tokio::main]
 async fn main() -> Result<()> {
     if let Err(e) = run().await {
-        eprintln!("❌ Error: {}", e);
-        crate::captain::wtf::display_api_failure_art();
+        log::debug!("Exiting after error: {:#}", e);
+        eprintln!("{}", crate::ci::decorate(&i18n::t_with("errors.error", &[("error", &e)])));
+        if !crate::ci::is_ci() {
+            crate::captain::wtf::display_api_failure_art();
+        }
         std::process::exit(1);
     }
     Ok(())
 }
 async fn run() -> Result<()> {
-    ensure_initialized();
-    let protection_key = crate::captain::protection::get_protection_key();
-    if crate::captain::protection::is_captain_drunk() {
-        eprintln!(
-            "CAPTAIN_DRUNK: Using embedded fallback protection key ({}...)", &
-            protection_key[..8]
-        );
-    } else if crate::captain::protection::is_captain_sober() {
-        eprintln!(
-            "CAPTAIN_SOBER: Using remote protection key ({}...)", & protection_key[..8]
-        );
-    } else if crate::captain::protection::is_captain_cached() {
-        eprintln!(
-            "CAPTAIN_CACHE: Using cached protection key ({}...)", & protection_key[..8]
-        );
+    let ci_mode = crate::ci::init(std::env::args().any(|arg| arg == "--ci"));
+    if std::env::args().nth(1).as_deref() == Some("status") {
+        // Prompt segments run this on every render: skip captain, license and message checks
+        if let Some(Commands::Status { porcelain, here }) = Args::parse().command {
+            return status::handle_status(porcelain, here);
+        }
     }
-    let captain_available = crate::captain::captain_status::is_captain_available();
-    if !captain_available {
-        if let Some(captain_path) = crate::captain::captain_status::find_captain_binary() {
-            eprintln!("⚠️  Captain binary found at: {}", captain_path);
-            eprintln!(
-                "   But verification failed - may need PROTECT_KEY environment variable"
-            );
-            eprintln!(
-                "   Current PROTECT_KEY: {}", std::env::var("PROTECT_KEY")
-                .unwrap_or_else(| _ | "NOT SET".to_string())
-            );
-            eprintln!();
-            eprintln!("💡 Try setting PROTECT_KEY or check if the key has rotated");
-            eprintln!("   Download from: https://get.cargo.do/captain/");
-            eprintln!();
-        } else {
-            eprintln!("⚠️  Captain binary not found");
-        }
-        if std::env::var("CM_NO_AUTO_INSTALL").is_ok() {
-            eprintln!("   Auto-install disabled by CM_NO_AUTO_INSTALL");
-            eprintln!("   Some advanced features will be unavailable.");
-            eprintln!();
-            eprintln!("💡 For full functionality, install Captain manually:");
-            eprintln!("   Download from: https://get.cargo.do/captain/");
-            eprintln!();
-            std::env::set_var("CARGO_MATE_LIMITED_MODE", "1");
-            initialize_fallback_mode()?;
-        } else {
-            match auto_install_captain().await {
-                Ok(_) => {
-                    eprintln!("✅ Captain installed successfully!");
-                    std::env::set_var("CARGO_MATE_FULL_MODE", "1");
-                    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-                    let current_path = std::env::var("PATH").unwrap_or_default();
-                    let new_path = format!("{}/.shipwreck/bin:{}", home, current_path);
-                    std::env::set_var("PATH", new_path);
-                }
-                Err(e) => {
-                    eprintln!("⚠️  Failed to auto-install captain: {}", e);
-                    eprintln!("   Running in limited mode");
-                    eprintln!();
-                    eprintln!("💡 You can manually install Captain:");
-                    eprintln!("   Download from: https://get.cargo.do/mate");
-                    eprintln!();
-                    std::env::set_var("CARGO_MATE_LIMITED_MODE", "1");
-                    initialize_fallback_mode()?;
-                }
+    logging::init();
+    log::debug!("cm {}", std::env::args().skip(1).collect::<Vec<_>>().join(" "));
+    if let Some(name) = std::env::args().nth(1).filter(|arg| !arg.starts_with('-')) {
+        // Built-ins always win, so a plugin can't shadow a cm command
+        if name != "help" && Args::command().find_subcommand(&name).is_none() {
+            if let Some(plugin) = plugins::find(&name) {
+                let args: Vec<String> = std::env::args().skip(2).collect();
+                std::process::exit(plugins::run(&name, &plugin, &args)?);
             }
         }
-    } else {
-        eprintln!("✅ Captain binary detected - full functionality enabled");
-        std::env::set_var("CARGO_MATE_FULL_MODE", "1");
     }
     let raw_args: Vec<String> = std::env::args().collect();
     if raw_args.len() >= 3 && raw_args[1] == "wtf" {
         let first_arg = &raw_args[2];
         let is_not_subcommand = !matches!(
-            first_arg.as_str(), "list" | "show" | "history" | "checklist" | "help" |
-            "--help" | "-h" | "--version"
+            first_arg.as_str(), "list" | "show" | "history" | "checklist" | "sessions" |
+            "resume" | "export" | "help" | "--help" | "-h" | "--version"
         );
         if is_not_subcommand {
             let is_direct_question = if raw_args.len() == 3 {
@@ -294,6 +567,8 @@ async fn run() -> Result<()> {
                 } else {
                     raw_args[2..].join(" ")
                 };
+                ensure_initialized();
+                startup::ensure_captain(ci_mode, false).await?;
                 println!("💭 Detected direct question: {}", question.cyan());
                 if let Err(e) = crate::captain::wtf::handle_wtf(&question, false) {
                     eprintln!("❌ Error: {}", e);
@@ -304,69 +579,30 @@ async fn run() -> Result<()> {
         }
     }
     let args = Args::parse();
-    if !matches!(args.command, Some(Commands::Register { .. }) | None) {
-        let should_check = match &args.command {
-            Some(cmd) => !matches!(cmd, Commands::Activate | Commands::Install),
-            None => true,
-        };
-        if should_check {
-            std::thread::spawn(|| {
-                let runtime = tokio::runtime::Runtime::new().unwrap();
-                runtime
-                    .block_on(async {
-                        let _ = admin_msg::check_and_display_message().await;
-                    });
-            });
-        }
+    crate::project::set_global_view(args.global);
+    ensure_initialized();
+    let command = args.command.as_ref();
+    if needs_captain(command) {
+        let managing_captain = matches!(command, Some(Commands::Captain { .. }));
+        startup::ensure_captain(ci_mode, managing_captain).await?;
     }
-    if let Some(ref command) = args.command {
-        match command {
-            Commands::Register { .. } => {}
-            _ => {
-                let license_manager = crate::captain::license::LicenseManager::new()?;
-                match command {
-                    Commands::Init => license_manager.enforce_license("init")?,
-                    Commands::Journey { .. } => {
-                        license_manager.enforce_license("journey")?
-                    }
-                    Commands::Anchor { .. } => license_manager.enforce_license("anchor")?,
-                    Commands::Log { .. } => license_manager.enforce_license("log")?,
-                    Commands::Tide { .. } => license_manager.enforce_license("tide")?,
-                    Commands::Map { .. } => license_manager.enforce_license("map")?,
-                    Commands::Mutiny { .. } => license_manager.enforce_license("mutiny")?,
-                    Commands::Config { .. } => license_manager.enforce_license("config")?,
-                    Commands::Version { .. } => {
-                        license_manager.enforce_license("version")?
-                    }
-                    Commands::View { .. } => license_manager.enforce_license("view")?,
-                    Commands::Optimize { .. } => {
-                        license_manager.enforce_license("optimize")?
-                    }
-                    Commands::Test => license_manager.enforce_license("test")?,
-                    Commands::Checklist { .. } => {
-                        license_manager.enforce_license("checklist")?
-                    }
-                    Commands::History { .. } => {
-                        license_manager.enforce_license("history")?
-                    }
-                    Commands::Scrub { .. } => license_manager.enforce_license("scrub")?,
-                    Commands::Install => license_manager.enforce_license("install")?,
-                    Commands::Activate => license_manager.enforce_license("activate")?,
-                    Commands::Idea { .. } => license_manager.enforce_license("idea")?,
-                    Commands::Wtf { .. } => license_manager.enforce_license("wtf")?,
-                    Commands::User => license_manager.enforce_license("user")?,
-                    Commands::Debug => license_manager.enforce_license("debug")?,
-                    Commands::Strip(_) => license_manager.enforce_license("strip")?,
-                    Commands::Scat(_) => license_manager.enforce_license("scat")?,
-                    Commands::Exec { .. } => {}
-                    Commands::Tool { .. } => license_manager.enforce_license("tool")?,
-                    Commands::Register { .. } => unreachable!(),
-                };
-            }
-        }
+    let shows_messages = command.is_some_and(|cmd| {
+        !matches!(
+            cmd, Commands::Register { .. } | Commands::Activate | Commands::Install | Commands::Msg { .. } |
+            Commands::Serve { .. }
+        )
+    });
+    if shows_messages && !ci_mode {
+        startup::check_messages();
+    }
+    if let Some(feature) = command.and_then(license_feature) {
+        crate::captain::license::LicenseManager::new()?.enforce_license(feature)?;
     }
     let mut config = crate::captain::config::ConfigManager::new()?;
-    config.merge_with_env();
+    config.merge_with_env()?;
+    if !matches!(args.command, Some(Commands::Env { .. })) {
+        project_env::apply(&config);
+    }
     match args.command {
         None => {
             if Path::new("Cargo.toml").exists() {
@@ -384,13 +620,13 @@ async fn run() -> Result<()> {
             }
         }
         Some(Commands::Init) => init_cargo_mate()?,
-        Some(Commands::Journey { action }) => handle_journey(action)?,
+        Some(Commands::Journey { action }) => tokio::task::block_in_place(|| handle_journey(action))?,
         Some(Commands::Anchor { action }) => handle_anchor(action)?,
         Some(Commands::Log { action }) => handle_log(action)?,
         Some(Commands::Tide { action }) => handle_tide(action)?,
         Some(Commands::Map { action }) => handle_map(action)?,
-        Some(Commands::Mutiny { action }) => handle_mutiny(action)?,
-        Some(Commands::Config { action }) => handle_config(action)?,
+        Some(Commands::Mutiny { action }) => tokio::task::block_in_place(|| handle_mutiny(action))?,
+        Some(Commands::Config { action }) => tokio::task::block_in_place(|| handle_config(action))?,
         Some(Commands::Version { action }) => handle_version(action)?,
         Some(Commands::View { action }) => handle_view(action)?,
         Some(Commands::Optimize { action }) => handle_optimize(action)?,
@@ -400,6 +636,102 @@ async fn run() -> Result<()> {
             history::show_history(&[kind, limit.to_string()]);
             return Ok(());
         }
+        Some(Commands::Ui) => ui::run()?,
+        Some(Commands::Githooks { action }) => {
+            githooks::handle_githooks_command(action)?
+        }
+        Some(Commands::FmtGate) => fmt_gate::handle_fmt_gate()?,
+        Some(Commands::PublishCheck { no_verify }) => {
+            tokio::task::block_in_place(|| publish::handle_publish_check(no_verify))?
+        }
+        Some(Commands::Crates { action }) => {
+            tokio::task::block_in_place(|| crates::handle_crates_command(action))?
+        }
+        Some(Commands::Docs { action }) => docs::handle_docs_command(action)?,
+        Some(Commands::Matrix(args)) => matrix::handle_matrix(args)?,
+        Some(Commands::Plugins) => plugins::handle_plugins()?,
+        Some(Commands::Privacy { action }) => privacy::handle_privacy_command(action)?,
+        Some(Commands::Doctor { action }) => {
+            tokio::task::block_in_place(|| doctor::handle_doctor_command(action))?
+        }
+        Some(Commands::Report { output, format, gist }) => report::handle_report(output, format, gist)?,
+        Some(Commands::Outdated { apply, upgrade, json }) => {
+            tokio::task::block_in_place(|| {
+                outdated::handle_outdated(&apply, upgrade, json)
+            })?
+        }
+        Some(Commands::Features { crates, apply, json }) => features::handle_features(&crates, apply, json)?,
+        Some(Commands::Lockdiff { rev, json }) => lockdiff::handle_lockdiff(rev, json)?,
+        Some(Commands::DiffBuild { from, to, last_good, list, json }) => {
+            diff_build::handle_diff_build(from, to, last_good, list, json)?
+        }
+        Some(Commands::Profile { action: Some(action), .. }) => {
+            profile_bundle::handle_profile_command(action)?
+        }
+        Some(Commands::Profile { action: None, bin, bench, instruments, args }) => {
+            profiler::handle_profile(bin, bench, instruments, args)?
+        }
+        Some(Commands::New { template: Some(template), name: Some(name), vars, list: false }) => {
+            let journey = scaffold::create(&template, &name, &vars)?;
+            init_cargo_mate()?;
+            scaffold::finish(&name, &template, &journey)?;
+        }
+        Some(Commands::New { .. }) => scaffold::list_templates()?,
+        Some(Commands::Ws { action: None }) => workspace::show_overview()?,
+        Some(Commands::Ws { action: Some(WsAction::Graph { format, output }) }) => {
+            workspace::show_graph(format, output)?
+        }
+        Some(Commands::Notify { action }) => notify::handle_notify_command(action)?,
+        Some(Commands::Status { porcelain, here }) => status::handle_status(porcelain, here)?,
+        Some(Commands::Env { action }) => project_env::handle_env_command(action)?,
+        Some(Commands::Secret { action }) => secrets::handle_secret_command(action)?,
+        Some(Commands::Affected { command, since, args }) => {
+            affected::handle_affected(command, since, args)?
+        }
+        Some(Commands::Clean { action: CleanAction::Smart { dry_run, keep_days } }) => {
+            smart_clean::handle_smart_clean(dry_run, keep_days)?
+        }
+        Some(Commands::Gc { dry_run, report }) => gc::handle_gc(dry_run, report)?,
+        Some(Commands::Audit { action }) => audit::handle_audit(action)?,
+        Some(Commands::Undo { action, force }) => undo::handle_undo(action, force)?,
+        Some(Commands::Cache { action }) => {
+            tokio::task::block_in_place(|| match action {
+                CacheAction::Push { profile, sccache, key, backend } => {
+                    cache::handle_push(&profile, sccache, key, backend)
+                }
+                CacheAction::Pull { profile, sccache, key, backend } => {
+                    cache::handle_pull(&profile, sccache, key, backend)
+                }
+            })?
+        }
+        Some(Commands::Tests { action }) => {
+            match action {
+                TestsAction::Flaky { runs } => test_results::show_flaky(runs)?,
+                TestsAction::Slowest { limit } => test_results::show_slowest(limit)?,
+            }
+        }
+        Some(Commands::Run { bin, release, replay, list, args }) => {
+            if list {
+                runs::list_runs()?
+            } else if let Some(id) = replay {
+                runs::handle_replay(&id)?
+            } else {
+                runs::handle_run(bin, release, args)?
+            }
+        }
+        Some(Commands::Again) => {
+            let args = recall::last_command()?;
+            let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+            run_cargo_with_wrapper(&args);
+        }
+        Some(Commands::Recall { query }) => {
+            if let Some(args) = recall::recall(&query.join(" "))? {
+                let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+                run_cargo_with_wrapper(&args);
+            }
+        }
+        Some(Commands::Serve { lsp, port }) => serve::handle_serve(lsp, port)?,
+        Some(Commands::Stats { days, output }) => stats::handle_stats(days, &output)?,
         Some(Commands::Scrub { action }) => handle_scrub(action)?,
         Some(Commands::Install) => {
             crate::captain::shell_integration::ShellIntegration::install()?;
@@ -409,11 +741,32 @@ async fn run() -> Result<()> {
             return Ok(());
         }
         Some(Commands::Activate) => handle_activate()?,
-        Some(Commands::Register { license_key, status, remaining }) => {
+        Some(Commands::Register { sync: true, .. }) => {
+            tokio::task::block_in_place(crate::captain::license_cache::handle_sync)?
+        }
+        Some(Commands::Register { license_key, status, remaining, .. }) => {
             handle_register(license_key, status, remaining)?
         }
-        Some(Commands::Idea { idea }) => handle_idea(&idea)?,
+        Some(Commands::Msg { action }) => admin_msg::handle_msg_action(action).await?,
+        Some(Commands::Idea { action, idea }) => {
+            tokio::task::block_in_place(|| match (action, idea) {
+                (Some(action), _) => crate::idea::handle_idea_action(action),
+                (None, Some(idea)) => crate::idea::handle_idea(&idea),
+                (None, None) => {
+                    crate::idea::handle_idea_action(crate::idea::IdeaAction::List {
+                        limit: 20,
+                    })
+                }
+            })?
+        }
         Some(Commands::Wtf { action }) => {
+            if wtf_usage::is_ai_action(&action) {
+                crate::net::ensure_online("CargoMate AI")?;
+                wtf_usage::check_budget()?;
+            }
+            if wtf_sessions::handle_local(&action)? {
+                return Ok(());
+            }
             if crate::captain::captain_status::is_captain_available() {
                 let captain_path = crate::captain::captain_status::get_captain_path();
                 if let Some(captain_binary) = captain_path {
@@ -430,10 +783,18 @@ async fn run() -> Result<()> {
                     } else {
                         vec![]
                     };
-                    match std::process::Command::new(&captain_binary)
+                    if wtf_sessions::forward_recorded(&captain_binary, &action, &wtf_args)? {
+                        return Ok(());
+                    }
+                    let usage_report = wtf_usage::report_path();
+                    let mut command = std::process::Command::new(&captain_binary);
+                    crate::net::NetworkSettings::load().apply_env(&mut command);
+                    let status = command
                         .args(&wtf_args)
-                        .status()
-                    {
+                        .env(wtf_usage::USAGE_FILE_ENV, &usage_report)
+                        .status();
+                    wtf_usage::record_from(&usage_report, None, None);
+                    match status {
                         Ok(status) if status.success() => {
                             return Ok(());
                         }
@@ -457,17 +818,33 @@ async fn run() -> Result<()> {
             crate::captain::wtf::handle_wtf_action(action)?;
             return Ok(());
         }
-        Some(Commands::User) => handle_user()?,
+        Some(Commands::User { action }) => handle_user(action)?,
+        Some(Commands::Captain { action }) => {
+            crate::captain::manage::handle_captain_command(action).await?;
+            return Ok(());
+        }
+        Some(Commands::SelfCmd { action }) => {
+            crate::self_update::handle_self_command(action).await?;
+            return Ok(());
+        }
         Some(Commands::Config { action }) => {
             handle_config(action)?;
             return Ok(());
         }
-        Some(Commands::Debug) => {
+        Some(Commands::Debug { action: Some(DebugAction::Logs { action }) }) => {
+            crate::logging::handle_logs(action)?;
+            return Ok(());
+        }
+        Some(Commands::Debug { action: None }) => {
             let license_manager = crate::captain::license::LicenseManager::new()?;
             license_manager.debug_command_counters()?;
             println!();
+            println!("👤 Active profile: {}", crate::user::active_profile_summary());
+            println!();
             println!("🧭 Captain Status:");
             println!("{}", crate ::captain::captain_status::get_captain_status_info());
+            println!();
+            println!("📜 Logs: {} (follow with 'cm debug logs tail')", crate::logging::logs_dir()?.display());
             return Ok(());
         }
         Some(Commands::Strip(args)) => {
@@ -478,8 +855,12 @@ async fn run() -> Result<()> {
             crate::scat::handle_scat_command(args)?;
             return Ok(());
         }
+        Some(Commands::ReleaseHarden(args)) => {
+            crate::release_harden::handle_release_harden(args)?;
+            return Ok(());
+        }
         Some(Commands::Tool { action }) => {
-            handle_tool_command(action)?;
+            tokio::task::block_in_place(|| handle_tool_command(action))?;
             return Ok(());
         }
         Some(Commands::Exec { cargo_args }) => {
@@ -487,27 +868,38 @@ async fn run() -> Result<()> {
             if !args.is_empty() && is_cm_command(args[0]) {
                 handle_cm_command(&args)?;
             } else {
-                if let Err(e) = version::pre_operation_hook(None) {
-                    eprintln!("⚠️  Version auto-increment failed: {}", e);
+                let cargo_args = shortcuts::expand_from_config(&cargo_args)?;
+                let args: Vec<&str> = cargo_args.iter().map(|s| s.as_str()).collect();
+                if !fmt_gate::enforce(&args)? {
+                    std::process::exit(1);
+                }
+                if !tokio::task::block_in_place(|| typosquat::enforce(&args))? {
+                    std::process::exit(1);
+                }
+                match version::pre_operation_hook(None) {
+                    Ok(Some(version)) => println!("🔢 Version bumped to {}", version.cyan()),
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Version auto-increment failed: {}", e),
                 }
                 let modified_args = if args.len() >= 2 && args[0] == "cargo"
                     && args[1] == "publish"
                 {
-                    let mut new_args = args.to_vec();
-                    let has_allow_dirty = new_args
-                        .iter()
-                        .any(|&arg| arg == "--allow-dirty");
-                    if !has_allow_dirty {
-                        new_args.insert(2, "--allow-dirty");
+                    match publish::prepare_publish(&args)? {
+                        Some(publish_args) => publish_args,
+                        None => std::process::exit(1),
                     }
-                    new_args
                 } else {
-                    args.to_vec()
+                    args.iter().map(|s| s.to_string()).collect()
                 };
+                let modified_args: Vec<&str> = modified_args
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect();
                 display::run_cargo_passthrough(&modified_args);
                 if let Err(e) = version::post_operation_hook(None, true) {
-                    eprintln!("⚠️  Version post-operation hook failed: {}", e);
+                    log::warn!("Version post-operation hook failed: {}", e);
                 }
+                gc::auto_compact();
             }
             return Ok(());
         }
@@ -517,9 +909,9 @@ async fn run() -> Result<()> {
 fn is_cm_command(cmd: &str) -> bool {
     matches!(
         cmd, "anchor" | "journey" | "log" | "tide" | "map" | "mutiny" | "config" |
-        "version" | "view" | "optimize" | "test" | "history" | "init" | "install" |
-        "activate" | "register" | "idea" | "wtf" | "checklist" | "add" | "done" | "clear"
-        | "show" | "list" | "user" | "debug" | "help" | "--help" | "-h" | "tool" |
+        "version" | "view" | "optimize" | "history" | "init" | "install" |
+        "activate" | "register" | "idea" | "wtf" | "checklist" | "done" | "clear"
+        | "show" | "list" | "user" | "captain" | "debug" | "help" | "--help" | "-h" | "tool" |
         "tools" | "strip" | "scat"
     )
 }
@@ -566,9 +958,15 @@ fn handle_cm_command(args: &[&str]) -> Result<()> {
                         None
                     };
                     let manager = anchor::AnchorManager::new()?;
+                    let manager = if remaining_args.contains(&"--encrypt") {
+                        manager.with_encryption(true)
+                    } else {
+                        manager
+                    };
                     let description = message
                         .unwrap_or_else(|| format!("Auto-saved via cargo anchor save"));
-                    manager.save(&name, &description)?;
+                    println!("⚓ Dropping anchor: {}", name.cyan().bold());
+                    manager.save(&name, &description)?.display_saved();
                 }
                 "restore" => {
                     if remaining_args.len() < 2 {
@@ -578,7 +976,8 @@ fn handle_cm_command(args: &[&str]) -> Result<()> {
                         std::process::exit(1);
                     }
                     let manager = anchor::AnchorManager::new()?;
-                    manager.restore(remaining_args[1])?;
+                    println!("⚓ Restoring anchor: {}", remaining_args[1].cyan().bold());
+                    restore_anchor_audited(&manager, remaining_args[1])?;
                 }
                 "list" => {
                     let manager = anchor::AnchorManager::new()?;
@@ -605,7 +1004,7 @@ fn handle_cm_command(args: &[&str]) -> Result<()> {
                         std::process::exit(1);
                     }
                     let manager = anchor::AnchorManager::new()?;
-                    manager.show(remaining_args[1])?;
+                    manager.get(remaining_args[1])?.display();
                 }
                 "diff" => {
                     if remaining_args.len() < 2 {
@@ -615,7 +1014,7 @@ fn handle_cm_command(args: &[&str]) -> Result<()> {
                         std::process::exit(1);
                     }
                     let manager = anchor::AnchorManager::new()?;
-                    manager.diff(remaining_args[1])?;
+                    manager.diff(remaining_args[1])?.display();
                 }
                 "auto" => {
                     if remaining_args.len() < 2 {
@@ -653,34 +1052,22 @@ fn handle_cm_command(args: &[&str]) -> Result<()> {
             }
         }
         "journey" => {
-            eprintln!("🚧 Journey commands not yet implemented for exec routing");
-            eprintln!("💡 Use 'cm journey' directly for now");
-            std::process::exit(1);
+            not_routed("Journey", "journey");
         }
         "log" => {
-            eprintln!("🚧 Log commands not yet implemented for exec routing");
-            eprintln!("💡 Use 'cm log' directly for now");
-            std::process::exit(1);
+            not_routed("Log", "log");
         }
         "tide" => {
-            eprintln!("🚧 Tide commands not yet implemented for exec routing");
-            eprintln!("💡 Use 'cm tide' directly for now");
-            std::process::exit(1);
+            not_routed("Tide", "tide");
         }
         "map" => {
-            eprintln!("🚧 Map commands not yet implemented for exec routing");
-            eprintln!("💡 Use 'cm map' directly for now");
-            std::process::exit(1);
+            not_routed("Map", "map");
         }
         "mutiny" => {
-            eprintln!("🚧 Mutiny commands not yet implemented for exec routing");
-            eprintln!("💡 Use 'cm mutiny' directly for now");
-            std::process::exit(1);
+            not_routed("Mutiny", "mutiny");
         }
         "config" => {
-            eprintln!("🚧 Config commands not yet implemented for exec routing");
-            eprintln!("💡 Use 'cm config' directly for now");
-            std::process::exit(1);
+            not_routed("Config", "config");
         }
         "version" => {
             let mut version_manager = VersionManager::new(None)?;
@@ -736,9 +1123,10 @@ fn handle_cm_command(args: &[&str]) -> Result<()> {
                         std::process::exit(1);
                     }
                     version_manager.set_version(remaining_args[1])?;
+                    println!("✅ Version set to: {}", remaining_args[1].cyan());
                 }
                 Some("update-cargo") => {
-                    version_manager.update_cargo_toml()?;
+                    report_cargo_update(&version_manager)?;
                 }
                 Some("config") => {
                     match remaining_args.get(1).map(|s| s.as_ref()) {
@@ -795,14 +1183,10 @@ fn handle_cm_command(args: &[&str]) -> Result<()> {
             }
         }
         "view" => {
-            eprintln!("🚧 View commands not yet implemented for exec routing");
-            eprintln!("💡 Use 'cm view' directly for now");
-            std::process::exit(1);
+            not_routed("View", "view");
         }
         "optimize" => {
-            eprintln!("🚧 Optimize commands not yet implemented for exec routing");
-            eprintln!("💡 Use 'cm optimize' directly for now");
-            std::process::exit(1);
+            not_routed("Optimize", "optimize");
         }
         "checklist" => {
             checklist::show_checklist();
@@ -850,6 +1234,32 @@ fn handle_cm_command(args: &[&str]) -> Result<()> {
             println!("    optimize   - Build performance optimization");
             println!("    checklist  - Show error/warning checklist");
             println!("    history    - Show command history");
+            println!("    outdated   - Check crates.io for newer dependency versions");
+            println!("    features   - Find the smallest feature set each dependency needs");
+            println!("    lockdiff   - Explain Cargo.lock changes against a git revision");
+            println!("    diff-build - Explain what changed between two recorded builds");
+            println!("    run        - Build and run a binary, recording its environment");
+            println!("    tests      - Flaky and slowest tests from recorded cargo test runs");
+            println!("    profile    - Profile a binary or bench and render a flamegraph");
+            println!("    cache      - Push/pull target directory caches to S3, GCS or HTTP");
+            println!("    clean      - Smart clean: drop only stale artifacts from target/");
+            println!("    gc         - Show ~/.shipwreck disk usage and apply retention policies");
+            println!("    audit      - Show the log of file-rewriting operations");
+            println!("    undo       - Revert the most recent file-rewriting operation");
+            println!("    new        - Scaffold a project from a built-in or user template");
+            println!("    self       - Update cargo-mate itself (stable or beta) and roll back");
+            println!("    ws         - Workspace members, internal deps, build status and sizes");
+            println!("    affected   - Build or test only the crates touched by recent changes");
+            println!("    notify     - Desktop/webhook notifications when long builds finish");
+            println!("    status     - Last build status for shell prompts (--porcelain)");
+            println!("    env        - Per-project environment injected into cargo commands");
+            println!("    again      - Re-run the last cargo command");
+            println!("    recall     - Fuzzy-find a cargo command in history and run it");
+            println!("    stats      - Build health and productivity dashboard");
+            println!("    ui         - Interactive terminal UI");
+            println!("    serve      - Serve stored diagnostics to editors (--lsp or --port)");
+            println!("    githooks   - Install managed pre-commit/pre-push hooks");
+            println!("    fmt-gate   - Run the fmt/clippy gate that guards publish and release");
             println!(
                 "    wtf        - CargoMate AI (Pro only) - Ask questions about your code"
             );
@@ -862,18 +1272,102 @@ fn handle_cm_command(args: &[&str]) -> Result<()> {
             println!();
         }
         _ => {
-            eprintln!("⚠️  Unknown command: {}", cmd);
+            eprintln!("{}", i18n::t_with("errors.unknown_command", &[("command", &cmd)]));
             std::process::exit(1);
         }
     }
     Ok(())
 }
+/// The license feature a command counts against; `None` runs unmetered
+fn license_feature(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::Init => Some("init"),
+        Commands::Journey { .. } => Some("journey"),
+        Commands::Anchor { .. } => Some("anchor"),
+        Commands::Log { .. } => Some("log"),
+        Commands::Tide { .. } => Some("tide"),
+        Commands::Map { .. } => Some("map"),
+        Commands::Mutiny { .. } => Some("mutiny"),
+        Commands::Config { .. } => Some("config"),
+        Commands::Version { .. } => Some("version"),
+        Commands::View { .. } => Some("view"),
+        Commands::Optimize { .. } => Some("optimize"),
+        Commands::Test => Some("test"),
+        Commands::Checklist { .. } => Some("checklist"),
+        Commands::History { .. } => Some("history"),
+        Commands::Ui => Some("ui"),
+        Commands::Serve { .. } => None,
+        Commands::Githooks { .. } => Some("githooks"),
+        Commands::FmtGate => Some("fmt-gate"),
+        Commands::PublishCheck { .. } => Some("publish-check"),
+        Commands::Crates { .. } => Some("crates"),
+        Commands::Docs { .. } => Some("docs"),
+        Commands::Matrix(_) => Some("matrix"),
+        Commands::Report { .. } => Some("report"),
+        Commands::Again | Commands::Recall { .. } => None,
+        // Must keep working when the license server is what's unreachable
+        Commands::Doctor { .. } => None,
+        Commands::Privacy { .. } => None,
+        Commands::Plugins => None,
+        Commands::Outdated { .. } => Some("outdated"),
+        Commands::Features { .. } => Some("features"),
+        Commands::Lockdiff { .. } => Some("lockdiff"),
+        Commands::DiffBuild { .. } => Some("diff-build"),
+        Commands::Run { .. } => Some("run"),
+        Commands::Tests { .. } => Some("tests"),
+        Commands::Cache { .. } => Some("cache"),
+        Commands::Clean { .. } => Some("clean"),
+        Commands::Gc { .. } => None,
+        Commands::Audit { .. } => None,
+        Commands::Undo { .. } => None,
+        Commands::New { .. } => Some("new"),
+        Commands::Ws { .. } => Some("ws"),
+        Commands::Notify { .. } => Some("notify"),
+        Commands::Status { .. } => None,
+        Commands::Env { .. } => Some("env"),
+        Commands::Secret { .. } => Some("secret"),
+        Commands::Affected { .. } => Some("affected"),
+        Commands::Profile { action: Some(_), .. } => None,
+        Commands::Profile { .. } => Some("profile"),
+        Commands::Stats { .. } => Some("stats"),
+        Commands::Scrub { .. } => Some("scrub"),
+        Commands::Install => Some("install"),
+        Commands::Activate => Some("activate"),
+        Commands::Msg { .. } => None,
+        Commands::Captain { .. } => None,
+        Commands::SelfCmd { .. } => None,
+        Commands::Idea { .. } => Some("idea"),
+        Commands::Wtf { .. } => Some("wtf"),
+        Commands::User { .. } => Some("user"),
+        Commands::Debug { action: None } => Some("debug"),
+        Commands::Debug { .. } => None,
+        Commands::Strip(_) => Some("strip"),
+        Commands::Scat(_) => Some("scat"),
+        Commands::ReleaseHarden(_) => Some("release-harden"),
+        Commands::Exec { .. } => None,
+        Commands::Tool { .. } => Some("tool"),
+        Commands::Register { .. } => None,
+    }
+}
+/// Commands that forward to, install or report on the captain binary; the rest never
+/// resolve the protection key or look for captain
+fn needs_captain(command: Option<&Commands>) -> bool {
+    matches!(
+        command,
+        Some(
+            Commands::Wtf { .. } | Commands::Captain { .. } | Commands::Debug { action: None } | Commands::Init |
+            Commands::Install | Commands::Activate | Commands::User { .. }
+        )
+    )
+}
 fn ensure_initialized() {
     let shipwreck = dirs::home_dir()
         .expect("Could not find home directory")
         .join(".shipwreck");
     if !shipwreck.exists() {
-        println!("⚓ First run! Setting up Cargo Mate...");
+        if !crate::ci::is_ci() {
+            println!("{}", i18n::t("setup.first_run"));
+        }
         std::fs::create_dir_all(&shipwreck.join("errors"))
             .expect("Failed to create errors directory");
         std::fs::create_dir_all(&shipwreck.join("warnings"))
@@ -886,9 +1380,12 @@ fn ensure_initialized() {
             .expect("Failed to create WTF history directory");
         std::fs::create_dir_all(&shipwreck.join("idea_history"))
             .expect("Failed to create idea history directory");
+        if crate::ci::is_ci() {
+            return;
+        }
         if let Err(e) = auto_install_shell_integration() {
-            eprintln!("⚠️  Auto-setup failed: {}", e);
-            println!("💡 Run 'cm install' manually if needed");
+            eprintln!("{}", i18n::t_with("setup.auto_setup_failed", &[("error", &e)]));
+            println!("{}", i18n::t("setup.install_manually"));
         }
     }
 }
@@ -911,7 +1408,7 @@ advanced_features = false
 reason = "captain binary not found"
 timestamp = "2024-01-01"
 "#;
-        let _ = fs::write(&config_file, basic_config);
+        let _ = storage::write_atomic(&config_file, basic_config);
     }
     let history_dir = shipwreck_dir.join("history");
     let _ = fs::create_dir_all(&history_dir);
@@ -919,84 +1416,9 @@ timestamp = "2024-01-01"
     let _ = fs::create_dir_all(&anchors_dir);
     let journeys_dir = shipwreck_dir.join("journeys");
     let _ = fs::create_dir_all(&journeys_dir);
-    eprintln!("📂 Fallback mode initialized with basic directories");
-    eprintln!("✅ Basic cargo commands will work");
-    eprintln!("⚠️  Advanced features require captain binary");
-    Ok(())
-}
-fn detect_platform() -> Result<String> {
-    let os = std::env::consts::OS;
-    let arch = std::env::consts::ARCH;
-    let platform = match (os, arch) {
-        ("linux", "x86_64") => {
-            if std::path::Path::new("/etc/alpine-release").exists() {
-                "x86_64-unknown-linux-musl"
-            } else {
-                "x86_64-unknown-linux-gnu"
-            }
-        }
-        ("linux", "aarch64") => "aarch64-unknown-linux-gnu",
-        ("macos", "x86_64") => "x86_64-apple-darwin",
-        ("macos", "aarch64") => "aarch64-apple-darwin",
-        ("windows", "x86_64") => "x86_64-pc-windows-gnu",
-        _ => return Err(anyhow::anyhow!("Unsupported platform: {}-{}", os, arch)),
-    };
-    Ok(platform.to_string())
-}
-async fn auto_install_captain() -> Result<()> {
-    eprintln!("🔍 Captain binary not found. Installing automatically...");
-    let platform = detect_platform()?;
-    eprintln!("📦 Detected platform: {}", platform);
-    let home = std::env::var("HOME").context("HOME not set")?;
-    let shipwreck_bin = PathBuf::from(&home).join(".shipwreck").join("bin");
-    fs::create_dir_all(&shipwreck_bin)?;
-    let base_url = std::env::var("CAPTAIN_DOWNLOAD_URL")
-        .unwrap_or_else(|_| "https://get.cargo.do/captain".to_string());
-    let archive_name = format!("captain-{}.tar.gz", platform);
-    let download_url = format!("{}/{}", base_url, archive_name);
-    eprintln!("📥 Downloading from: {}", download_url);
-    let client = reqwest::Client::new();
-    let response = client.get(&download_url).send().await?;
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Failed to download captain: {}", response.status()));
-    }
-    let bytes = response.bytes().await?;
-    let temp_dir = std::env::temp_dir();
-    let archive_path = temp_dir.join(&archive_name);
-    fs::write(&archive_path, bytes)?;
-    eprintln!("📂 Extracting captain...");
-    let output = std::process::Command::new("tar")
-        .args(&["-xzf", archive_path.to_str().unwrap()])
-        .current_dir(&temp_dir)
-        .output()?;
-    if !output.status.success() {
-        return Err(anyhow::anyhow!("Failed to extract archive"));
-    }
-    let extracted_dir = temp_dir.join(format!("captain-{}", platform));
-    let captain_enc = extracted_dir.join("captain.enc");
-    if !captain_enc.exists() {
-        return Err(anyhow::anyhow!("captain.enc not found in archive"));
-    }
-    let captain_dest = shipwreck_bin.join("captain");
-    fs::copy(&captain_enc, &captain_dest)?;
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&captain_dest)?.permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(&captain_dest, perms)?;
-    }
-    fs::remove_file(&archive_path).ok();
-    fs::remove_dir_all(&extracted_dir).ok();
-    eprintln!("✅ Captain installed successfully to: {}", captain_dest.display());
-    crate::captain::captain_status::mark_captain_installed(
-        captain_dest.to_str().unwrap(),
-    )?;
-    if !std::env::var("PATH").unwrap_or_default().contains(".shipwreck/bin") {
-        println!();
-        println!("⚠️  Add ~/.shipwreck/bin to your PATH:");
-        println!("   export PATH=\"$HOME/.shipwreck/bin:$PATH\"");
-    }
+    log::debug!("Fallback mode initialized with basic directories");
+    log::info!("✅ Basic cargo commands will work");
+    log::warn!("Advanced features require captain binary");
     Ok(())
 }
 fn auto_install_shell_integration() -> Result<()> {
@@ -1121,12 +1543,21 @@ fn handle_journey(action: JourneyAction) -> Result<()> {
             while recorder.is_recording() {
                 std::thread::sleep(std::time::Duration::from_millis(100));
             }
-            recorder.stop_recording(&name, "User recorded journey")?;
+            let journey = recorder.stop_recording(&name, "User recorded journey")?;
+            println!("✅ Journey '{}' recorded successfully!", journey.name.green().bold());
+            println!("📁 Saved to ~/.shipwreck/journeys/{}.json", journey.name);
         }
-        JourneyAction::Play { name, dry_run } => {
-            let mut player = journey::JourneyPlayer::new(dry_run, true);
+        JourneyAction::Play { name, dry_run, yes, sandbox, keep_sandbox } => {
+            let mut player = journey::JourneyPlayer::new(dry_run, !crate::ci::is_ci()).assume_yes(yes);
             let journey = player.load_journey(&name)?;
-            player.play(&journey)?;
+            if let Some(kind) = sandbox {
+                player = player.with_sandbox(journey_sandbox::Sandbox::create(&name, kind, keep_sandbox)?);
+            }
+            let result = player.play(&journey);
+            if let Some(sandbox) = player.sandbox() {
+                sandbox.diff().display();
+            }
+            result?;
         }
         JourneyAction::List => {
             let journeys = journey::list_journeys()?;
@@ -1141,9 +1572,18 @@ fn handle_journey(action: JourneyAction) -> Result<()> {
         }
         JourneyAction::Export { name, output } => {
             journey::export_journey(&name, &output)?;
+            println!("✅ Journey exported to {}", output.display());
         }
         JourneyAction::Import { path } => {
-            journey::import_journey(&path)?;
+            let journey = journey::import_journey(&path)?;
+            println!("✅ Journey '{}' imported successfully!", journey.name.green());
+        }
+        JourneyAction::Compose { name, journeys } => {
+            let journey = journey::compose_journeys(&name, &journeys)?;
+            println!(
+                "✅ Journey '{}' composed from {} ({} commands)", journey.name.green().bold(),
+                journeys.join(" + ").cyan(), journey.commands.len()
+            );
         }
         JourneyAction::Publish { name, tags } => {
             journey::JourneyMarketplace::publish(&name, tags)?;
@@ -1168,15 +1608,26 @@ fn handle_journey(action: JourneyAction) -> Result<()> {
     }
     Ok(())
 }
+fn restore_anchor_audited(manager: &anchor::AnchorManager, name: &str) -> Result<()> {
+    let paths = manager.get(name)?.files_snapshot.into_values().map(|f| f.path);
+    let undo = undo::Recorder::capture("anchor restore", name, paths.chain([PathBuf::from("Cargo.lock")]))?;
+    let report = manager.restore(name)?;
+    undo.commit();
+    report.display();
+    Ok(())
+}
 fn handle_anchor(action: AnchorAction) -> Result<()> {
     let manager = anchor::AnchorManager::new()?;
     match action {
-        AnchorAction::Save { name, message } => {
+        AnchorAction::Save { name, message, encrypt } => {
             let msg = message.unwrap_or_else(|| "Manual anchor point".to_string());
-            manager.save(&name, &msg)?;
+            let manager = if encrypt { manager.with_encryption(true) } else { manager };
+            println!("⚓ Dropping anchor: {}", name.cyan().bold());
+            manager.save(&name, &msg)?.display_saved();
         }
         AnchorAction::Restore { name } => {
-            manager.restore(&name)?;
+            println!("⚓ Restoring anchor: {}", name.cyan().bold());
+            restore_anchor_audited(&manager, &name)?;
         }
         AnchorAction::List => {
             let anchors = manager.list()?;
@@ -1190,10 +1641,10 @@ fn handle_anchor(action: AnchorAction) -> Result<()> {
             }
         }
         AnchorAction::Show { name } => {
-            manager.show(&name)?;
+            manager.get(&name)?.display();
         }
         AnchorAction::Diff { name } => {
-            manager.diff(&name)?;
+            manager.diff(&name)?.display();
         }
         AnchorAction::Auto { name, foreground } => {
             if foreground {
@@ -1205,6 +1656,12 @@ fn handle_anchor(action: AnchorAction) -> Result<()> {
         AnchorAction::Stop { name } => {
             manager.stop_auto_update(&name)?;
         }
+        AnchorAction::ToGit { name, branch, stash, force, include_env } => {
+            manager.to_git(&name, branch.as_deref(), stash, force, include_env)?.display();
+        }
+        AnchorAction::FromGit { reference, name, message, force } => {
+            manager.save_from_git(&reference, name.as_deref(), message.as_deref(), force)?.display_saved();
+        }
     }
     Ok(())
 }
@@ -1212,7 +1669,7 @@ fn handle_log(action: LogAction) -> Result<()> {
     let mut log = captain_log::CaptainLog::new()?;
     match action {
         LogAction::Add { message, tags } => {
-            log.log(&message, tags)?;
+            log.log(&message, tags)?.display();
         }
         LogAction::Search { query } => {
             let results = log.search(&query);
@@ -1238,6 +1695,7 @@ fn handle_log(action: LogAction) -> Result<()> {
                 _ => captain_log::ExportFormat::Markdown,
             };
             log.export(&path, fmt)?;
+            println!("✅ Log exported to {}", path.display());
         }
         LogAction::Analyze => {
             let analysis = log.analyze();
@@ -1263,10 +1721,26 @@ fn handle_tide(action: TideAction) -> Result<()> {
             charts.show_interactive()?;
         }
         TideAction::Analyze => {
-            charts.analyze_dependencies()?;
+            println!("🔍 Analyzing dependency compile times...");
+            let scan = charts.analyze_dependencies()?;
+            if scan.timings_collected {
+                println!(
+                    "✅ Timing data collected. Check target/cargo-timings/ for detailed report."
+                );
+            }
+            println!("📦 {} dependencies recorded", scan.dependencies);
         }
-        TideAction::Export { path } => {
-            charts.export_csv(&path)?;
+        TideAction::Export { path, format } => {
+            if format == "html" {
+                charts.export_html(&path)?;
+                println!("✅ Tide report written to {}", path.display());
+            } else {
+                charts.export_csv(&path)?;
+                println!("✅ Build metrics exported to {}", path.display());
+            }
+        }
+        TideAction::Graph { html, build, json, args } => {
+            tide_graph::handle_graph(html, build, json, &args)?;
         }
     }
     Ok(())
@@ -1281,8 +1755,10 @@ fn handle_map(action: MapAction) -> Result<()> {
             let analysis = map.analyze();
             analysis.display();
         }
-        MapAction::Export { path } => {
-            map.export_dot(&path)?;
+        MapAction::Export { path, format } => {
+            let format = format
+                .unwrap_or_else(|| crate::map_render::MapFormat::from_path(&path));
+            map.export(&path, format)?;
         }
         MapAction::Path { from, to } => {
             if let Some(path) = map.find_path(&from, &to) {
@@ -1294,61 +1770,85 @@ fn handle_map(action: MapAction) -> Result<()> {
                 println!("No path found between {} and {}", from, to);
             }
         }
+        MapAction::Licenses { krate, mut deny } => {
+            if let Ok(config) = crate::captain::config::ConfigManager::new() {
+                deny.extend(config.get_list("map.deny_licenses"));
+            }
+            if deny.is_empty() || krate.is_some() {
+                map.show_licenses(krate.as_deref())?;
+            }
+            if !deny.is_empty() {
+                let violations = map.denied_licenses(&deny);
+                if violations.is_empty() {
+                    println!("✅ No denied license ({}) is linked into a binary target", deny.join(", "));
+                } else {
+                    println!("❌ Denied licenses reachable from binary targets:");
+                    for violation in &violations {
+                        println!(
+                            "  {} {} ({}) via {}",
+                            violation.binary.cyan(),
+                            violation.krate.red().bold(),
+                            violation.license,
+                            violation.path.join(" → ")
+                        );
+                    }
+                    std::process::exit(1);
+                }
+            }
+        }
     }
     Ok(())
 }
 fn handle_mutiny(action: MutinyAction) -> Result<()> {
     let mut mutiny = mutiny::MutinyMode::new()?;
+    let policy = mutiny::MutinyPolicy::load();
     match action {
-        MutinyAction::Activate { reason } => {
-            mutiny.activate(&reason)?;
+        MutinyAction::Activate { reason, gate } => {
+            policy.enforce(if gate { "gate" } else { "activate" }, Some(&reason))?;
+            if gate {
+                mutiny.activate_gate_bypass(&reason)?;
+            } else {
+                mutiny.activate(&reason)?;
+            }
         }
         MutinyAction::Deactivate => {
             mutiny.deactivate()?;
         }
-        MutinyAction::AllowWarnings => {
+        MutinyAction::AllowWarnings { reason } => {
+            policy.enforce("allow-warnings", reason.as_deref())?;
             mutiny.allow_warnings()?;
         }
-        MutinyAction::SkipTests => {
+        MutinyAction::SkipTests { reason } => {
+            policy.enforce("skip-tests", reason.as_deref())?;
             mutiny.skip_tests()?;
         }
-        MutinyAction::Force => {
+        MutinyAction::Force { reason } => {
+            policy.enforce("force", reason.as_deref())?;
             mutiny.force_build()?;
         }
-        MutinyAction::Yolo => {
+        MutinyAction::Yolo { reason } => {
+            policy.enforce("yolo", reason.as_deref())?;
             mutiny.yolo_mode()?;
         }
         MutinyAction::Status => {
             mutiny.status();
+            policy.show();
         }
     }
     Ok(())
 }
 fn handle_config(action: ConfigAction) -> Result<()> {
-    let mut config = crate::captain::config::ConfigManager::new()?;
-    match action {
-        ConfigAction::Set { key, value, local } => {
-            config.set(&key, &value, local)?;
-        }
-        ConfigAction::Get { key } => {
-            if let Some(value) = config.get(&key) {
-                println!("{}", value);
-            } else {
-                println!("Config key '{}' not found", key);
-            }
-        }
-        ConfigAction::List => {
-            config.show();
-        }
-        ConfigAction::Init => {
-            config.init_local()?;
-        }
-        ConfigAction::Shortcut { name, command, local } => {
-            config.add_shortcut(&name, &command, local)?;
-        }
-        ConfigAction::Hook { hook_type, command, local } => {
-            config.add_hook(&hook_type, &command, local)?;
-        }
+    crate::captain::config::handle_config_action(action)
+}
+fn report_cargo_update(version_manager: &VersionManager) -> Result<()> {
+    if version_manager.update_cargo_toml()? {
+        println!(
+            "✅ Cargo.toml updated to {}", version_manager.current_version().cyan()
+        );
+    } else {
+        println!(
+            "✅ Cargo.toml already at {}", version_manager.current_version().cyan()
+        );
     }
     Ok(())
 }
@@ -1356,7 +1856,8 @@ fn handle_version(action: VersionAction) -> Result<()> {
     let mut version_manager = version::VersionManager::new(None)?;
     match action {
         VersionAction::Init { version } => {
-            version_manager.init(version)?;
+            let version = version_manager.init(version)?;
+            println!("✅ Version tracking initialized at {}", version.cyan());
         }
         VersionAction::Info => {
             version_manager.show_info();
@@ -1389,13 +1890,14 @@ fn handle_version(action: VersionAction) -> Result<()> {
         }
         VersionAction::Set { version } => {
             version_manager.set_version(&version)?;
+            println!("✅ Version set to: {}", version.cyan());
         }
         VersionAction::History => {
             let version_manager = VersionManager::new(None)?;
             version_manager.show_history()?;
         }
         VersionAction::UpdateCargo => {
-            version_manager.update_cargo_toml()?;
+            report_cargo_update(&version_manager)?;
         }
         VersionAction::Config { action } => {
             match action {
@@ -1429,13 +1931,34 @@ fn handle_version(action: VersionAction) -> Result<()> {
 #[derive(Subcommand, Debug)]
 enum ViewAction {
     Errors,
-    Artifacts,
+    Artifacts {
+        #[arg(long, value_enum, default_value = "name")]
+        sort: artifacts::ArtifactSort,
+        #[arg(long)]
+        kind: Option<String>,
+        #[arg(long)]
+        all: bool,
+        #[arg(long, value_name = "NAME")]
+        open: Option<String>,
+    },
     Scripts,
+    Profiles {
+        #[arg(long, value_name = "ID")]
+        open: Option<String>,
+    },
     History,
     Checklist,
     All,
     Latest,
     Open,
+    Fixes {
+        #[arg(long)]
+        apply: bool,
+        #[arg(long)]
+        maybe_incorrect: bool,
+        #[arg(long)]
+        file: Option<String>,
+    },
 }
 #[derive(Subcommand, Debug)]
 enum ChecklistAction {
@@ -1467,6 +1990,58 @@ enum OptimizeAction {
     Restore,
 }
 #[derive(Subcommand, Debug)]
+enum WsAction {
+    Graph {
+        #[arg(long, value_enum)]
+        format: Option<crate::map_render::MapFormat>,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+#[derive(Subcommand, Debug)]
+enum CleanAction {
+    Smart {
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long, default_value = "7")]
+        keep_days: u64,
+    },
+}
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    Push {
+        #[arg(long, default_value = "debug")]
+        profile: String,
+        #[arg(long)]
+        sccache: bool,
+        #[arg(long)]
+        key: Option<String>,
+        #[arg(long)]
+        backend: Option<String>,
+    },
+    Pull {
+        #[arg(long, default_value = "debug")]
+        profile: String,
+        #[arg(long)]
+        sccache: bool,
+        #[arg(long)]
+        key: Option<String>,
+        #[arg(long)]
+        backend: Option<String>,
+    },
+}
+#[derive(Subcommand, Debug)]
+enum TestsAction {
+    Flaky {
+        #[arg(long, default_value = "20")]
+        runs: usize,
+    },
+    Slowest {
+        #[arg(short = 'n', long, default_value = "10")]
+        limit: usize,
+    },
+}
+#[derive(Subcommand, Debug)]
 enum ScrubAction {
     Run {
         #[arg(long)]
@@ -1481,6 +2056,24 @@ enum ScrubAction {
         min_depth: usize,
         #[arg(long, default_value = "10")]
         max_depth: usize,
+        #[arg(short, long)]
+        interactive: bool,
+        #[arg(short, long)]
+        quiet: bool,
+        #[arg(long, value_name = "GB")]
+        if_free_below: Option<u64>,
+    },
+    Schedule {
+        #[arg(long, conflicts_with = "daily")]
+        weekly: bool,
+        #[arg(long)]
+        daily: bool,
+        #[arg(long, value_name = "GB")]
+        below_gb: Option<u64>,
+        #[arg(short, long, default_value = "~")]
+        start: String,
+        #[arg(long, conflicts_with_all = ["weekly", "daily", "below_gb"])]
+        remove: bool,
     },
     Help,
 }
@@ -1489,42 +2082,42 @@ enum ToolAction {
     List,
     Help { name: String },
     Run { name: String, #[arg(trailing_var_arg = true)] args: Vec<String> },
+    RunAll {
+        #[arg(long)]
+        set: String,
+        #[arg(short, long)]
+        jobs: Option<usize>,
+    },
+    Pipeline {
+        name: Option<String>,
+        #[arg(long)]
+        report: Option<std::path::PathBuf>,
+        #[arg(long)]
+        fail_fast: bool,
+    },
     #[command(external_subcommand)]
     Execute(Vec<String>),
 }
 fn handle_view(action: ViewAction) -> Result<()> {
-    let shipwreck = dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".shipwreck");
     match action {
         ViewAction::Errors => {
-            let error_file = shipwreck.join("errors").join("latest.txt");
-            if error_file.exists() {
+            if let Some(content) = project::read_text("errors/latest.txt")? {
                 println!("🔴 Latest Errors:");
                 println!("{}", "═".repeat(50).red());
-                let content = fs::read_to_string(error_file)?;
                 println!("{}", content);
             } else {
                 println!("✅ No errors found");
             }
         }
-        ViewAction::Artifacts => {
-            let artifact_file = shipwreck.join("artifacts").join("latest.txt");
-            if artifact_file.exists() {
-                println!("📦 Generated Artifacts:");
-                println!("{}", "═".repeat(50).blue());
-                let content = fs::read_to_string(artifact_file)?;
-                println!("{}", content);
-            } else {
-                println!("📁 No artifacts found");
-            }
+        ViewAction::Artifacts { open: Some(name), .. } => artifacts::reveal(&name)?,
+        ViewAction::Artifacts { sort, kind, all, open: None } => {
+            artifacts::show_catalog(sort, kind.as_deref(), all)?;
         }
+        ViewAction::Profiles { open } => profiler::show_profiles(open.as_deref())?,
         ViewAction::Scripts => {
-            let script_file = shipwreck.join("scripts").join("latest.txt");
-            if script_file.exists() {
+            if let Some(content) = project::read_text("scripts/latest.txt")? {
                 println!("🔨 Build Scripts:");
                 println!("{}", "═".repeat(50).yellow());
-                let content = fs::read_to_string(script_file)?;
                 println!("{}", content);
             }
         }
@@ -1537,49 +2130,34 @@ fn handle_view(action: ViewAction) -> Result<()> {
         ViewAction::All => {
             println!("🔍 Complete Build Results:");
             println!("{}", "═".repeat(60).cyan());
-            let error_file = shipwreck.join("errors").join("latest.txt");
-            if error_file.exists() {
+            if let Some(content) = project::read_text("errors/latest.txt")? {
                 println!("🔴 Errors:");
-                let content = fs::read_to_string(error_file)?;
                 println!("{}", content);
                 println!();
             }
-            let warning_file = shipwreck.join("warnings").join("latest.txt");
-            if warning_file.exists() {
+            if let Some(content) = project::read_text("warnings/latest.txt")? {
                 println!("⚠️  Warnings:");
-                let content = fs::read_to_string(warning_file)?;
-                println!("{}", content);
-                println!();
-            }
-            let artifact_file = shipwreck.join("artifacts").join("latest.txt");
-            if artifact_file.exists() {
-                println!("📦 Artifacts:");
-                let content = fs::read_to_string(artifact_file)?;
                 println!("{}", content);
                 println!();
             }
-            let script_file = shipwreck.join("scripts").join("latest.txt");
-            if script_file.exists() {
+            artifacts::show_catalog(artifacts::ArtifactSort::Name, None, false)?;
+            println!();
+            if let Some(content) = project::read_text("scripts/latest.txt")? {
                 println!("🔨 Build Scripts:");
-                let content = fs::read_to_string(script_file)?;
                 println!("{}", content);
             }
         }
         ViewAction::Latest => {
             println!("🔍 Latest Build Issues:");
             println!("{}", "═".repeat(50).cyan());
-            let error_file = shipwreck.join("errors").join("latest.txt");
-            if error_file.exists() {
-                let content = fs::read_to_string(error_file)?;
+            if let Some(content) = project::read_text("errors/latest.txt")? {
                 if !content.trim().is_empty() {
                     println!("🔴 Errors:");
                     println!("{}", content);
                     println!();
                 }
             }
-            let warning_file = shipwreck.join("warnings").join("latest.txt");
-            if warning_file.exists() {
-                let content = fs::read_to_string(warning_file)?;
+            if let Some(content) = project::read_text("warnings/latest.txt")? {
                 if !content.trim().is_empty() {
                     println!("⚠️  Warnings:");
                     println!("{}", content);
@@ -1607,6 +2185,71 @@ fn handle_view(action: ViewAction) -> Result<()> {
                 println!("❌ Target directory not found");
             }
         }
+        ViewAction::Fixes { apply, maybe_incorrect, file } => {
+            let Some(fix_set) = smart_parser::load_fix_set()? else {
+                println!("❌ No captured diagnostics. Run a build through cargo-mate first.");
+                return Ok(());
+            };
+            if apply {
+                let report = smart_parser::apply_fix_set(
+                    &fix_set,
+                    maybe_incorrect,
+                    file.as_deref(),
+                )?;
+                println!(
+                    "🔧 Applied {} fixes in {} files", report.applied.len().to_string()
+                    .green(), report.files_changed
+                );
+                for (fix, reason) in &report.skipped {
+                    println!(
+                        "  ⏭️  {}:{} {} ({})", fix.file, fix.line, fix.help, reason
+                        .dimmed()
+                    );
+                }
+                if !report.applied.is_empty() {
+                    println!("💡 Rebuild to verify and refresh remaining fixes");
+                }
+                return Ok(());
+            }
+            let grouped = smart_parser::group_by_file(&fix_set.suggestions);
+            if grouped.is_empty() {
+                println!("✅ No auto-fixes available from the last build");
+                return Ok(());
+            }
+            println!(
+                "🔧 Auto-fixes from the build at {}:", fix_set.captured_at
+                .format("%Y-%m-%d %H:%M")
+            );
+            println!("{}", "═".repeat(50).cyan());
+            for (path, fixes) in grouped {
+                if file.as_deref().is_some_and(|only| !path.ends_with(only)) {
+                    continue;
+                }
+                println!("\n📄 {}", path.bold());
+                for fix in fixes {
+                    let applicability = if fix.is_machine_applicable() {
+                        "auto".green()
+                    } else {
+                        fix.applicability.yellow()
+                    };
+                    println!(
+                        "  {}:{} [{}] {}", fix.line, fix.code.dimmed(), applicability, fix
+                        .message
+                    );
+                    for edit in &fix.edits {
+                        println!(
+                            "      {} {}", format!("{}:{}", edit.line, edit.column).dimmed(),
+                            if edit.replacement.is_empty() { "(remove)".red() } else { edit
+                            .replacement.lines().next().unwrap_or("").green() }
+                        );
+                    }
+                }
+            }
+            println!(
+                "\n💡 Apply machine-applicable fixes with: {}", "cm view fixes --apply"
+                .cyan()
+            );
+        }
     }
     Ok(())
 }
@@ -1715,6 +2358,7 @@ fn handle_register(
         match license_manager.register_license(&key) {
             Ok(_) => {
                 println!("✅ License registered successfully!");
+                crate::user::record_license_key(&key)?;
                 println!("🔄 Testing license validity...");
                 match license_manager.check_license_status() {
                     Ok(validation) => {
@@ -1753,6 +2397,7 @@ fn handle_register(
         println!("  cm register <license-key>    Register your Pro license");
         println!("  cm register --status         Check detailed license status");
         println!("  cm register --remaining      Show remaining commands count");
+        println!("  cm register --sync           Refresh the cached license grant now");
         println!();
         println!("EXAMPLES:");
         println!("  cm register CM-ABC12-DEF34-GHI56");
@@ -1772,129 +2417,30 @@ fn is_build_process() -> bool {
         || std::env::var("CARGO_MANIFEST_DIR").is_ok()
         || std::env::var("CARGO_PKG_NAME").is_ok()
 }
-fn handle_idea(idea: &str) -> Result<()> {
-    println!("💡 Submitting idea: {}", idea.yellow());
-    println!("🔗 Testing API communication...");
-    if let Err(e) = save_idea_history(idea) {
-        eprintln!("⚠️  Failed to save idea to history: {}", e);
-    }
-    let license_manager = crate::captain::license::LicenseManager::new()?;
-    let api_base_url = env::var("CARGO_MATE_API")
-        .unwrap_or_else(|_| "https://cargo.do/api".to_string());
-    let user_id = license_manager.get_or_create_user_id()?;
-    let idea_data = serde_json::json!(
-        { "user_id" : user_id, "idea" : idea, "timestamp" : chrono::Utc::now()
-        .to_rfc3339(), "source" : "cargo-mate-cli" }
-    );
-    let client = reqwest::blocking::Client::new();
-    let endpoint = format!("{}/idea/{}", api_base_url, urlencoding::encode(idea));
-    println!("📡 Sending to: {}", endpoint.cyan());
-    match client
-        .post(&endpoint)
-        .json(&idea_data)
-        .timeout(std::time::Duration::from_secs(10))
-        .send()
-    {
-        Ok(response) => {
-            if response.status().is_success() {
-                println!("✅ Idea submitted successfully!");
-                println!("🚀 API communication working perfectly");
-                println!(
-                    "📊 Response status: {}", response.status().to_string().green()
-                );
-                match response.json::<serde_json::Value>() {
-                    Ok(json_response) => {
-                        println!(
-                            "📋 Server response: {}", serde_json::to_string_pretty(&
-                            json_response).unwrap_or_else(| _ | "Unable to format JSON"
-                            .to_string())
-                        );
-                    }
-                    Err(_) => {
-                        println!("📋 Response received (non-JSON format)");
-                    }
-                }
-            } else {
-                println!(
-                    "⚠️  API responded with error: {}", response.status().to_string()
-                    .yellow()
+fn get_recent_errors(count: usize) -> Result<Vec<String>> {
+    if let Some(content) = project::read_text("errors/latest.txt")? {
+        let occurrences = fingerprint::error_occurrences(&history::load_visible_history());
+        let mut seen = Vec::new();
+        let mut errors = Vec::new();
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let id = fingerprint::of_stored(line);
+            if seen.contains(&id) {
+                continue;
+            }
+            seen.push(id);
+            errors
+                .push(
+                    match fingerprint::lookup(&occurrences, line) {
+                        Some(occurrence) if occurrence.builds > 1 => {
+                            format!("{} ({})", line, occurrence.summary())
+                        }
+                        _ => line.to_string(),
+                    },
                 );
-                match response.text() {
-                    Ok(error_text) => println!("   Error details: {}", error_text),
-                    Err(_) => println!("   Unable to read error details"),
-                }
+            if errors.len() >= count {
+                break;
             }
         }
-        Err(e) => {
-            crate::captain::wtf::display_api_failure_art();
-            println!("❌ API communication failed: {}", e.to_string().red());
-            println!("🔍 This could indicate:");
-            println!("   • Network connectivity issues");
-            println!("   • API server is down");
-            println!("   • Firewall blocking the request");
-            println!("   • DNS resolution problems");
-            println!("💡 Try testing with: curl -X POST {}", endpoint);
-        }
-    }
-    Ok(())
-}
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-struct IdeaHistoryEntry {
-    id: String,
-    idea: String,
-    timestamp: String,
-}
-fn save_idea_history(idea: &str) -> Result<()> {
-    let shipwreck = dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".shipwreck");
-    let history_dir = shipwreck.join("idea_history");
-    std::fs::create_dir_all(&history_dir)?;
-    let history_file = history_dir.join("history.json");
-    let mut history: Vec<IdeaHistoryEntry> = if history_file.exists() {
-        let content = std::fs::read_to_string(&history_file)?;
-        serde_json::from_str(&content).unwrap_or_default()
-    } else {
-        Vec::new()
-    };
-    let entry = IdeaHistoryEntry {
-        id: format!("idea_{}", chrono::Utc::now().timestamp()),
-        idea: idea.to_string(),
-        timestamp: chrono::Utc::now().to_rfc3339(),
-    };
-    history.insert(0, entry);
-    history.truncate(50);
-    let json = serde_json::to_string_pretty(&history)?;
-    std::fs::write(&history_file, json)?;
-    Ok(())
-}
-fn get_idea_history(limit: usize) -> Result<Vec<IdeaHistoryEntry>> {
-    let shipwreck = dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".shipwreck");
-    let history_file = shipwreck.join("idea_history").join("history.json");
-    if history_file.exists() {
-        let content = std::fs::read_to_string(&history_file)?;
-        let history: Vec<IdeaHistoryEntry> = serde_json::from_str(&content)
-            .unwrap_or_default();
-        Ok(history.into_iter().take(limit).collect())
-    } else {
-        Ok(Vec::new())
-    }
-}
-fn get_recent_errors(count: usize) -> Result<Vec<String>> {
-    let shipwreck = dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".shipwreck");
-    let error_file = shipwreck.join("errors").join("latest.txt");
-    if error_file.exists() {
-        let content = std::fs::read_to_string(&error_file)?;
-        let errors: Vec<String> = content
-            .lines()
-            .filter(|line| !line.trim().is_empty())
-            .take(count)
-            .map(|s| s.to_string())
-            .collect();
         if errors.is_empty() {
             Ok(vec!["No recent errors found in the error logs.".to_string()])
         } else {
@@ -1910,6 +2456,9 @@ fn get_recent_errors(count: usize) -> Result<Vec<String>> {
     }
 }
 fn show_loading_messages() {
+    if crate::ci::is_ci() {
+        return;
+    }
     let messages = [
         "⚓ Hoisting the sails... preparing to set sail for knowledge!",
         "🌊 Riding the waves... surfing through the digital ocean!",
@@ -1930,10 +2479,12 @@ fn show_loading_messages() {
         index = (index + 1) % messages.len();
     }
 }
-fn handle_user() -> Result<()> {
-    let license_manager = crate::captain::license::LicenseManager::new()?;
-    license_manager.show_user_info()?;
-    Ok(())
+fn handle_user(action: Option<crate::user::UserAction>) -> Result<()> {
+    if action.is_none() {
+        let license_manager = crate::captain::license::LicenseManager::new()?;
+        license_manager.show_user_info()?;
+    }
+    crate::user::handle_user_command(action)
 }
 fn handle_activate() -> Result<()> {
     println!("⚡ Activating Cargo Mate shell integration...");
@@ -2026,7 +2577,18 @@ fn handle_optimize(action: OptimizeAction) -> Result<()> {
 }
 fn handle_scrub(action: ScrubAction) -> Result<()> {
     match action {
-        ScrubAction::Run { dry_run, verbose, start, resume, min_depth, max_depth } => {
+        ScrubAction::Run {
+            dry_run,
+            verbose,
+            start,
+            resume,
+            min_depth,
+            max_depth,
+            interactive,
+            quiet,
+            if_free_below,
+        } => {
+            let config = crate::captain::config::ConfigManager::new().ok();
             let options = scrub::ScrubOptions {
                 dry_run,
                 verbose,
@@ -2034,10 +2596,33 @@ fn handle_scrub(action: ScrubAction) -> Result<()> {
                 resume_from: resume,
                 min_depth,
                 max_depth,
-            };
+                interactive,
+                quiet,
+                free_below_gb: if_free_below,
+                ..scrub::ScrubOptions::default()
+            }
+                .with_config(config.as_ref());
             let scrubber = scrub::CargoScrubber::new(options);
             scrubber.scrub()?;
         }
+        ScrubAction::Schedule { weekly, daily, below_gb, start, remove } => {
+            if remove {
+                return scrub_schedule::remove();
+            }
+            let frequency = match (weekly, daily, below_gb) {
+                (true, _, _) => scrub_schedule::Frequency::Weekly,
+                (_, true, _) => scrub_schedule::Frequency::Daily,
+                (_, _, Some(_)) => scrub_schedule::Frequency::Hourly,
+                _ => scrub_schedule::Frequency::Weekly,
+            };
+            scrub_schedule::install(
+                &scrub_schedule::ScheduleSpec {
+                    frequency,
+                    free_below_gb: below_gb,
+                    start: scrub::expand_home(&start),
+                },
+            )?;
+        }
         ScrubAction::Help => {
             println!("🧹 Cargo Scrub - System-wide Cargo Clean");
             println!();
@@ -2053,6 +2638,18 @@ fn handle_scrub(action: ScrubAction) -> Result<()> {
             println!("  -r, --resume    Resume from specific project directory");
             println!("  --min-depth N   Minimum depth to search (default: 1)");
             println!("  --max-depth N   Maximum depth to search (default: 10)");
+            println!("  -i, --interactive  Pick projects (and keep-release) before cleaning");
+            println!("  -q, --quiet        Only log reclaimed space to history");
+            println!("  --if-free-below GB Skip unless free disk space is below GB");
+            println!();
+            println!("SCHEDULING:");
+            println!("  cm scrub schedule --weekly           Weekly quiet scrub of your home directory");
+            println!("  cm scrub schedule --below-gb 20      Hourly check, scrub when free space < 20GB");
+            println!("  cm scrub schedule --remove           Remove the scheduled scrub");
+            println!();
+            println!("CONFIG:");
+            println!("  scrub.exclude = [\"~/work/big-monorepo\"]   Never scan these paths");
+            println!("  scrub.keep_release = [\"~/work/app\"]       Only clean debug artifacts");
             println!();
             println!("EXAMPLES:");
             println!(
@@ -2063,17 +2660,14 @@ fn handle_scrub(action: ScrubAction) -> Result<()> {
             println!(
                 "  cm scrub run -r my-project           # Resume from projects containing 'my-project'"
             );
+            println!("  cm scrub run -i -s ~/work            # Choose which projects to clean");
         }
     }
     Ok(())
 }
 fn handle_test() -> Result<()> {
     println!("🧪 Running test command that will generate and log an error...");
-    let shipwreck = dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".shipwreck");
-    std::fs::create_dir_all(shipwreck.join("errors"))?;
-    let error_file = shipwreck.join("errors").join("latest.txt");
+    let error_file = project::data_file("errors/latest.txt")?;
     let error_message = format!(
         "🧪 Test Error: This is a deliberate test error from the test command\nTime: {}\nCommand: cm test\nError: Test error - demonstrating error logging functionality\n",
         chrono::Utc::now().to_rfc3339()
@@ -2084,32 +2678,37 @@ fn handle_test() -> Result<()> {
     println!("💡 Now run 'cm view errors' to see this error");
     Ok(())
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ChecklistItem {
-    id: usize,
-    text: String,
-    done: bool,
-    created_at: String,
+fn show_global_checklist() -> Result<()> {
+    let files = project::read_files("checklists/items.json")?;
+    let mut shown = 0;
+    for (label, file) in files {
+        let items = checklist::load_items(&file)?;
+        if items.is_empty() {
+            continue;
+        }
+        println!("📋 {}", label.cyan().bold());
+        for item in &items {
+            let checkbox = if item.done { "☑️" } else { "☐" };
+            println!("  {}. {} {}", item.id, checkbox, item.text);
+        }
+        println!();
+        shown += 1;
+    }
+    if shown == 0 {
+        println!("📋 No checklist items in any project");
+    }
+    Ok(())
 }
 fn handle_checklist(action: ChecklistAction) -> Result<()> {
-    let shipwreck = dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".shipwreck");
-    let checklist_dir = shipwreck.join("checklists");
-    std::fs::create_dir_all(&checklist_dir)?;
-    let checklist_file = checklist_dir.join("items.json");
-    let mut items: Vec<ChecklistItem> = if checklist_file.exists() {
-        let content = std::fs::read_to_string(&checklist_file)?;
-        if content.trim().is_empty() {
-            Vec::new()
-        } else {
-            serde_json::from_str(&content).unwrap_or_else(|_| Vec::new())
-        }
-    } else {
-        Vec::new()
-    };
+    if project::global_view()
+        && matches!(action, ChecklistAction::Show | ChecklistAction::List)
+    {
+        return show_global_checklist();
+    }
+    let checklist_file = checklist::items_file()?;
     match action {
         ChecklistAction::Show | ChecklistAction::List => {
+            let items = checklist::load_items(&checklist_file)?;
             if items.is_empty() {
                 println!("📋 Checklist is empty");
                 println!("💡 Add items with: cm checklist add \"Your task here\"");
@@ -2129,16 +2728,7 @@ fn handle_checklist(action: ChecklistAction) -> Result<()> {
             }
         }
         ChecklistAction::Add { item } => {
-            let next_id = items.iter().map(|i| i.id).max().unwrap_or(0) + 1;
-            let new_item = ChecklistItem {
-                id: next_id,
-                text: item.clone(),
-                done: false,
-                created_at: chrono::Utc::now().to_rfc3339(),
-            };
-            items.push(new_item);
-            let content = serde_json::to_string_pretty(&items)?;
-            std::fs::write(&checklist_file, content)?;
+            let next_id = checklist::update_items(&checklist_file, |items| checklist::add_item(items, &item))?;
             println!("✅ Added item #{}: {}", next_id, item);
             println!("💡 Mark as done with: cm checklist done {}", next_id);
         }
@@ -2147,16 +2737,8 @@ fn handle_checklist(action: ChecklistAction) -> Result<()> {
                 .split(',')
                 .filter_map(|s| s.trim().parse::<usize>().ok())
                 .collect();
-            let mut marked_count = 0;
-            for item in &mut items {
-                if ids_to_mark.contains(&item.id) && !item.done {
-                    item.done = true;
-                    marked_count += 1;
-                }
-            }
+            let marked_count = checklist::update_items(&checklist_file, |items| checklist::mark_done(items, &ids_to_mark))?;
             if marked_count > 0 {
-                let content = serde_json::to_string_pretty(&items)?;
-                std::fs::write(&checklist_file, content)?;
                 println!(
                     "✅ Marked {} item(s) as completed: {}", marked_count, item_ids
                 );
@@ -2169,15 +2751,11 @@ fn handle_checklist(action: ChecklistAction) -> Result<()> {
         ChecklistAction::Clear { target } => {
             match target.as_str() {
                 "all" => {
-                    items.clear();
-                    let content = serde_json::to_string_pretty(&items)?;
-                    std::fs::write(&checklist_file, content)?;
+                    checklist::update_items(&checklist_file, |items| items.clear())?;
                     println!("🗑️  Cleared all checklist items");
                 }
                 "done" => {
-                    items.retain(|item| !item.done);
-                    let content = serde_json::to_string_pretty(&items)?;
-                    std::fs::write(&checklist_file, content)?;
+                    checklist::update_items(&checklist_file, |items| items.retain(|item| !item.done))?;
                     println!("🗑️  Removed completed items from checklist");
                 }
                 _ => {
@@ -2190,48 +2768,101 @@ fn handle_checklist(action: ChecklistAction) -> Result<()> {
     }
     Ok(())
 }
+/// Usage column and `help.commands` catalog key for each special command in `cm --help`
+const HELP_COMMANDS: &[(&str, &str)] = &[
+    ("cm wtf", "wtf"),
+    ("cm idea", "idea"),
+    ("cm journey", "journey"),
+    ("cm anchor", "anchor"),
+    ("cm log", "log"),
+    ("cm tide", "tide"),
+    ("cm tide graph", "tide_graph"),
+    ("cm map", "map"),
+    ("cm mutiny", "mutiny"),
+    ("cm config", "config"),
+    ("cm config sync --from", "config_sync"),
+    ("cm version", "version"),
+    ("cm view", "view"),
+    ("cm optimize", "optimize"),
+    ("cm checklist", "checklist"),
+    ("cm scrub", "scrub"),
+    ("cm history", "history"),
+    ("cm outdated", "outdated"),
+    ("cm features [--apply]", "features"),
+    ("cm lockdiff [<rev>]", "lockdiff"),
+    ("cm diff-build [a] [b]", "diff_build"),
+    ("cm run [bin] [-- args]", "run"),
+    ("cm tests flaky|slowest", "tests"),
+    ("cm profile [bin]", "profile"),
+    ("cm profile export <f>", "profile_export"),
+    ("cm cache push|pull", "cache"),
+    ("cm clean smart", "clean_smart"),
+    ("cm gc [--dry-run]", "gc"),
+    ("cm audit show", "audit"),
+    ("cm undo [list]", "undo"),
+    ("cm self update", "self_update"),
+    ("cm new <tpl> <name>", "new"),
+    ("cm ws [graph]", "ws"),
+    ("cm affected [test]", "affected"),
+    ("cm notify status|test", "notify"),
+    ("cm status --porcelain", "status"),
+    ("cm env set KEY VALUE", "env"),
+    ("cm secret set <name>", "secret"),
+    ("cm again", "again"),
+    ("cm recall <fuzzy>", "recall"),
+    ("cm stats", "stats"),
+    ("cm ui", "ui"),
+    ("cm serve --lsp", "serve"),
+    ("cm githooks install", "githooks"),
+    ("cm fmt-gate", "fmt_gate"),
+    ("cm publish-check", "publish_check"),
+    ("cm crates owners|yank", "crates"),
+    ("cm docs build|coverage", "docs"),
+    ("cm matrix", "matrix"),
+    ("cm release-harden", "release_harden"),
+    ("cm report [--gist]", "report"),
+    ("cm doctor network", "doctor_network"),
+    ("cm privacy audit", "privacy_audit"),
+    ("cm plugins", "plugins"),
+    ("cm install", "install"),
+    ("cm user", "user"),
+    ("cm affiliate", "affiliate"),
+];
+/// Command and `help.examples` catalog key for each example in `cm --help`
+const HELP_EXAMPLES: &[(&str, &str)] = &[
+    ("cm journey record build-flow", "journey_record"),
+    ("cm anchor save before-refactor", "anchor_save"),
+    ("cm mutiny allow-warnings", "mutiny_allow"),
+    ("cm map show", "map_show"),
+    ("cm wtf er 10", "wtf_errors"),
+    ("cm wtf checklist 5", "wtf_checklist"),
+    ("cm scrub run --dry-run", "scrub_dry_run"),
+    ("cm wtf ollama enable llama2", "wtf_ollama"),
+];
 fn show_help() {
-    println!("{}", "🚢 Cargo Mate (cm) - A Rustic Journey".bold());
+    println!("{}", i18n::t("help.title").bold());
     println!();
-    println!("{}", "USAGE:".yellow());
-    println!("  cm                      Auto-build or run default build");
-    println!("  cm <command>            Run cm command or pass to cargo");
+    println!("{}", i18n::t("help.usage_heading").yellow());
+    println!("  {:<24}{}", "cm", i18n::t("help.run.default"));
+    println!("  {:<24}{}", "cm <command>", i18n::t("help.run.command"));
     println!();
-    println!("{}", "SPECIAL COMMANDS:".yellow());
-    println!("  cm wtf                  🤖 Ask CargoMate AI a question");
-    println!("  cm idea                 💡 Submit an idea for Cargo Mate");
-    println!("  cm journey              🎬 Record and play command sequences");
-    println!("  cm anchor               ⚓ Save and restore project states");
-    println!("  cm log                  📝 Captain's log for build notes");
-    println!("  cm tide                 🌊 Performance tracking charts");
-    println!("  cm map                  🗺️  Dependency visualization");
-    println!("  cm mutiny               🏴‍☠️ Override cargo restrictions");
-    println!("  cm config               ⚙️  Configuration management");
-    println!("  cm version              🚢 Version management and auto-incrementing");
-    println!("  cm view                 🔍 View build results and artifacts");
-    println!("  cm optimize             🚀 Build performance optimization");
-    println!("  cm checklist            📋 Show error/warning checklist");
-    println!("  cm scrub                🧹 System-wide cargo clean");
-    println!("  cm history              📊 Show build history");
-    println!("  cm install              🔧 Install shell integration");
-    println!("  cm user                 👤 Show user information and license status");
-    println!(
-        "  cm affiliate            💰 Manage affiliate program & earning opportunities"
-    );
+    println!("{}", i18n::t("help.special_heading").yellow());
+    for (usage, key) in HELP_COMMANDS {
+        println!("  {:<24}{}", usage, i18n::t(&format!("help.commands.{}", key)));
+    }
     println!();
-    println!("{}", "EXAMPLES:".yellow());
-    println!("  cm journey record build-flow    # Record a build sequence");
-    println!("  cm anchor save before-refactor  # Save current state");
-    println!("  cm mutiny allow-warnings        # Temporarily allow warnings");
-    println!("  cm map show                      # Show dependency tree");
-    println!("  cm wtf er 10                     # Send recent errors to CargoMate AI");
-    println!(
-        "  cm wtf checklist 5               # Send 5 recent checklist items to CargoMate AI"
-    );
-    println!("  cm scrub run --dry-run           # Preview system-wide cargo clean");
-    println!("  cm wtf ollama enable llama2      # Configure local Ollama integration");
+    println!("{}", i18n::t("help.examples_heading").yellow());
+    for (example, key) in HELP_EXAMPLES {
+        println!("  {:<32} # {}", example, i18n::t(&format!("help.examples.{}", key)));
+    }
     println!();
-    println!("Run 'cm <command> --help' for more information on a command.");
+    println!("{}", i18n::t("help.more"));
+}
+/// `cm exec` can't route these yet; points at the direct form and exits
+fn not_routed(name: &str, command: &str) -> ! {
+    eprintln!("{}", i18n::t_with("exec.not_routed", &[("command", &name)]));
+    eprintln!("{}", i18n::t_with("exec.use_directly", &[("command", &command)]));
+    std::process::exit(1);
 }
 fn run_cargo_with_wrapper(args: &[&str]) {
     if !args.is_empty() {
@@ -2249,8 +2880,10 @@ fn run_cargo_with_wrapper(args: &[&str]) {
             std::process::exit(1);
         }
     }
-    if let Err(e) = version::pre_operation_hook(None) {
-        eprintln!("⚠️  Version auto-increment failed: {}", e);
+    match version::pre_operation_hook(None) {
+        Ok(Some(version)) => println!("🔢 Version bumped to {}", version.cyan()),
+        Ok(None) => {}
+        Err(e) => log::warn!("Version auto-increment failed: {}", e),
     }
     display::run_cargo_with_display(args);
     if let Ok(mut log) = captain_log::CaptainLog::new() {
@@ -2260,16 +2893,16 @@ fn run_cargo_with_wrapper(args: &[&str]) {
             warning_count: 0,
             duration_seconds: 0.0,
         };
-        if let Err(e) = log
-            .log_command(&format!("cargo {}", args.join(" ")), build_result)
-        {
-            eprintln!("⚠️  Captain's Log recording failed: {}", e);
+        match log.log_command(&format!("cargo {}", args.join(" ")), build_result) {
+            Ok(entry) => entry.display(),
+            Err(e) => log::warn!("Captain's Log recording failed: {}", e),
         }
         println!("\n📝 {}", "Captain's Log: Session recorded".dimmed());
     }
     if let Err(e) = version::post_operation_hook(None, true) {
-        eprintln!("⚠️  Version post-operation hook failed: {}", e);
+        log::warn!("Version post-operation hook failed: {}", e);
     }
+    gc::auto_compact();
 }
 fn run_tracked_command(command: &str, session_id: &str) -> Result<()> {
     use std::process::Command;
@@ -2299,7 +2932,7 @@ fn run_tracked_command(command: &str, session_id: &str) -> Result<()> {
                 if let Some(diagnostic) = msg.message {
                     let entry = parser
                         .create_log_entry_from_diagnostic(&diagnostic, session_id);
-                    log.log(&entry.message, entry.tags)?;
+                    log.log(&entry.message, entry.tags)?.display();
                 }
             }
         }
@@ -2313,7 +2946,7 @@ fn run_tracked_command(command: &str, session_id: &str) -> Result<()> {
                 if let Some(diagnostic) = msg.message {
                     let entry = parser
                         .create_log_entry_from_diagnostic(&diagnostic, session_id);
-                    log.log(&entry.message, entry.tags)?;
+                    log.log(&entry.message, entry.tags)?.display();
                 }
             }
         }
@@ -2326,7 +2959,7 @@ fn run_tracked_command(command: &str, session_id: &str) -> Result<()> {
         warning_count: 0,
         duration_seconds: duration.as_secs_f64(),
     };
-    log.log_command(command, build_result)?;
+    log.log_command(command, build_result)?.display();
     println!("\n🔍 Analysis:");
     let entries = log.get_recent(1000);
     let detector = captain_log::PatternDetector::new(
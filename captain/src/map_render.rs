@@ -0,0 +1,284 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+const NODE_HEIGHT: f64 = 36.0;
+const ROW_GAP: f64 = 14.0;
+const COLUMN_GAP: f64 = 60.0;
+const CHAR_WIDTH: f64 = 7.2;
+const PADDING: f64 = 20.0;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MapFormat {
+    Dot,
+    Mermaid,
+    Json,
+    Svg,
+}
+impl MapFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+            Some("mmd") | Some("mermaid") | Some("md") => MapFormat::Mermaid,
+            Some("json") => MapFormat::Json,
+            Some("svg") => MapFormat::Svg,
+            _ => MapFormat::Dot,
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    pub id: usize,
+    pub name: String,
+    pub version: String,
+    pub source: Option<String>,
+    pub license: Option<String>,
+    pub depth: usize,
+    pub dev: bool,
+    pub build: bool,
+    pub size_bytes: Option<u64>,
+    pub color: &'static str,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: &'static str,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphExport {
+    pub root: Option<String>,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+fn hex(color: &str) -> &'static str {
+    match color {
+        "green" => "#2e7d32",
+        "yellow" => "#b58900",
+        "red" => "#c62828",
+        "blue" => "#1565c0",
+        _ => "#757575",
+    }
+}
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+impl GraphExport {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for node in &self.nodes {
+            out.push_str(
+                &format!(
+                    "    n{}[\"{}<br/>v{}\"]\n", node.id, node.name.replace('"', "#quot;"),
+                    node.version
+                ),
+            );
+        }
+        for edge in &self.edges {
+            let arrow = match edge.kind {
+                "dev" => "-.->",
+                "build" => "==>",
+                _ => "-->",
+            };
+            out.push_str(&format!("    n{} {} n{}\n", edge.from, arrow, edge.to));
+        }
+        for color in ["green", "yellow", "red", "blue", "gray"] {
+            let members: Vec<String> = self
+                .nodes
+                .iter()
+                .filter(|n| n.color == color)
+                .map(|n| format!("n{}", n.id))
+                .collect();
+            if !members.is_empty() {
+                out.push_str(
+                    &format!("    classDef {} stroke:{},stroke-width:2px\n", color, hex(color)),
+                );
+                out.push_str(&format!("    class {} {}\n", members.join(","), color));
+            }
+        }
+        out
+    }
+    fn layers(&self) -> Vec<Vec<usize>> {
+        let mut incoming: HashMap<usize, usize> = self.nodes.iter().map(|n| (n.id, 0)).collect();
+        for edge in &self.edges {
+            *incoming.entry(edge.to).or_insert(0) += 1;
+        }
+        let mut layer_of: HashMap<usize, usize> = HashMap::new();
+        let mut done: HashSet<usize> = HashSet::new();
+        let mut queue: VecDeque<usize> = self
+            .nodes
+            .iter()
+            .filter(|n| incoming[&n.id] == 0)
+            .map(|n| n.id)
+            .collect();
+        loop {
+            while let Some(id) = queue.pop_front() {
+                done.insert(id);
+                let next_layer = layer_of.get(&id).copied().unwrap_or(0) + 1;
+                for edge in self.edges.iter().filter(|e| e.from == id) {
+                    if done.contains(&edge.to) {
+                        continue;
+                    }
+                    let layer = layer_of.entry(edge.to).or_insert(next_layer);
+                    *layer = (*layer).max(next_layer);
+                    let left = incoming.entry(edge.to).or_insert(0);
+                    if *left > 0 {
+                        *left -= 1;
+                        if *left == 0 {
+                            queue.push_back(edge.to);
+                        }
+                    }
+                }
+            }
+            let cycle_entry = self
+                .nodes
+                .iter()
+                .filter(|n| !done.contains(&n.id))
+                .min_by_key(|n| (incoming[&n.id], n.id));
+            match cycle_entry {
+                Some(node) => {
+                    incoming.insert(node.id, 0);
+                    queue.push_back(node.id);
+                }
+                None => break,
+            }
+        }
+        let mut layers: Vec<Vec<usize>> = Vec::new();
+        for node in &self.nodes {
+            let layer = layer_of.get(&node.id).copied().unwrap_or(0);
+            if layers.len() <= layer {
+                layers.resize(layer + 1, Vec::new());
+            }
+            layers[layer].push(node.id);
+        }
+        for index in 1..layers.len() {
+            let positions: HashMap<usize, f64> = layers[index - 1]
+                .iter()
+                .enumerate()
+                .map(|(pos, id)| (*id, pos as f64))
+                .collect();
+            let barycenter = |id: &usize| {
+                let parents: Vec<f64> = self
+                    .edges
+                    .iter()
+                    .filter(|e| e.to == *id)
+                    .filter_map(|e| positions.get(&e.from).copied())
+                    .collect();
+                if parents.is_empty() {
+                    f64::MAX
+                } else {
+                    parents.iter().sum::<f64>() / parents.len() as f64
+                }
+            };
+            layers[index].sort_by(|a, b| barycenter(a).total_cmp(&barycenter(b)));
+        }
+        layers
+    }
+    pub fn to_svg(&self) -> String {
+        let by_id: HashMap<usize, &GraphNode> = self.nodes.iter().map(|n| (n.id, n)).collect();
+        let label = |node: &GraphNode| format!("{} v{}", node.name, node.version);
+        let layers = self.layers();
+        let mut boxes: HashMap<usize, (f64, f64, f64)> = HashMap::new();
+        let mut x = PADDING;
+        let mut height: f64 = 0.0;
+        for layer in &layers {
+            let width = layer
+                .iter()
+                .map(|id| label(by_id[id]).chars().count() as f64 * CHAR_WIDTH + 20.0)
+                .fold(0.0, f64::max);
+            for (row, id) in layer.iter().enumerate() {
+                let y = PADDING + row as f64 * (NODE_HEIGHT + ROW_GAP);
+                boxes.insert(*id, (x, y, width));
+                height = height.max(y + NODE_HEIGHT);
+            }
+            x += width + COLUMN_GAP;
+        }
+        let total_width = (x - COLUMN_GAP + PADDING).max(2.0 * PADDING);
+        let total_height = height + PADDING;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"0 0 {:.0} {:.0}\" font-family=\"monospace\" font-size=\"12\">\n",
+            total_width, total_height, total_width, total_height
+        );
+        svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+        for edge in &self.edges {
+            let (Some(&(fx, fy, fw)), Some(&(tx, ty, _))) = (
+                boxes.get(&edge.from),
+                boxes.get(&edge.to),
+            ) else {
+                continue;
+            };
+            let (x1, y1) = (fx + fw, fy + NODE_HEIGHT / 2.0);
+            let (x2, y2) = (tx, ty + NODE_HEIGHT / 2.0);
+            let bend = ((x2 - x1).abs() / 2.0).max(COLUMN_GAP / 2.0);
+            let dash = match edge.kind {
+                "dev" => " stroke-dasharray=\"6,4\"",
+                "build" => " stroke-dasharray=\"2,3\"",
+                _ => "",
+            };
+            svg.push_str(
+                &format!(
+                    "<path d=\"M{:.1},{:.1} C{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\" fill=\"none\" stroke=\"#9e9e9e\"{}/>\n",
+                    x1, y1, x1 + bend, y1, x2 - bend, y2, x2, y2, dash
+                ),
+            );
+        }
+        for layer in &layers {
+            for id in layer {
+                let node = by_id[id];
+                let (x, y, width) = boxes[id];
+                svg.push_str(
+                    &format!(
+                        "<g><title>{}</title><rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.0}\" rx=\"4\" fill=\"#fafafa\" stroke=\"{}\" stroke-width=\"2\"/><text x=\"{:.1}\" y=\"{:.1}\">{}</text></g>\n",
+                        xml_escape(node.source.as_deref().unwrap_or("local")), x, y, width,
+                        NODE_HEIGHT, hex(node.color), x + 10.0, y + NODE_HEIGHT / 2.0 + 4.0,
+                        xml_escape(& label(node))
+                    ),
+                );
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn node(id: usize, name: &str) -> GraphNode {
+        GraphNode {
+            id,
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            source: None,
+            license: None,
+            depth: id,
+            dev: false,
+            build: false,
+            size_bytes: None,
+            color: "green",
+        }
+    }
+    #[test]
+    fn renders_mermaid_json_and_svg() {
+        let graph = GraphExport {
+            root: Some("app".to_string()),
+            nodes: vec![node(0, "app"), node(1, "serde"), node(2, "tempfile")],
+            edges: vec![
+                GraphEdge { from : 0, to : 1, kind : "normal" }, GraphEdge { from : 0, to :
+                2, kind : "dev" }, GraphEdge { from : 1, to : 0, kind : "normal" },
+            ],
+        };
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("n0 -.-> n2"));
+        let json: serde_json::Value = serde_json::from_str(&graph.to_json().unwrap())
+            .unwrap();
+        assert_eq!(json["edges"].as_array().unwrap().len(), 3);
+        assert_eq!(graph.layers(), vec![vec![0], vec![1, 2]]);
+        let svg = graph.to_svg();
+        assert_eq!(svg.matches("<rect x=").count(), 3);
+        assert!(svg.contains("stroke-dasharray=\"6,4\""));
+        assert_eq!(MapFormat::from_path(Path::new("deps.svg")), MapFormat::Svg);
+    }
+}
@@ -0,0 +1,330 @@
+use crate::captain::config::ConfigManager;
+use crate::history;
+use anyhow::{bail, Result};
+use clap::Args;
+use colored::*;
+use serde::Serialize;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+/// Every dimension falls back to `[matrix]` in .cg, then to the defaults below
+#[derive(Args, Debug, Default)]
+pub struct MatrixArgs {
+    /// Target triple (repeatable, default: the host)
+    #[arg(long = "target")]
+    pub targets: Vec<String>,
+    /// `default`, `no-default`, `all` or a comma-separated feature list (repeatable)
+    #[arg(long = "features")]
+    pub features: Vec<String>,
+    /// Cargo profile (repeatable, default: dev)
+    #[arg(long = "profile")]
+    pub profiles: Vec<String>,
+    /// Cargo subcommand to run in every cell (repeatable, default: check)
+    #[arg(long = "check")]
+    pub checks: Vec<String>,
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+    #[arg(long)]
+    pub json: bool,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CellStatus {
+    Pass,
+    Fail,
+    Skipped,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct Cell {
+    pub check: String,
+    pub target: String,
+    pub features: String,
+    pub profile: String,
+    pub status: CellStatus,
+    pub seconds: f64,
+    pub error: Option<String>,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    pub checks: Vec<String>,
+    pub targets: Vec<String>,
+    pub features: Vec<String>,
+    pub profiles: Vec<String>,
+}
+impl Matrix {
+    pub fn resolve(args: &MatrixArgs) -> Self {
+        let config = ConfigManager::new().ok();
+        let pick = |cli: &[String], key: &str, default: Vec<String>| {
+            if !cli.is_empty() {
+                return cli.to_vec();
+            }
+            let configured = config.as_ref().map(|c| c.get_list(key)).unwrap_or_default();
+            if configured.is_empty() { default } else { configured }
+        };
+        Self {
+            checks: pick(&args.checks, "matrix.checks", vec!["check".to_string()]),
+            targets: pick(&args.targets, "matrix.targets", vec![host_target()]),
+            features: pick(
+                &args.features,
+                "matrix.features",
+                vec!["default".to_string(), "no-default".to_string(), "all".to_string()],
+            ),
+            profiles: pick(&args.profiles, "matrix.profiles", vec!["dev".to_string()]),
+        }
+    }
+    /// (check, target, features, profile) in grid order
+    pub fn cells(&self) -> Vec<(String, String, String, String)> {
+        let mut cells = Vec::new();
+        for check in &self.checks {
+            for target in &self.targets {
+                for profile in &self.profiles {
+                    for features in &self.features {
+                        cells.push((check.clone(), target.clone(), features.clone(), profile.clone()));
+                    }
+                }
+            }
+        }
+        cells
+    }
+}
+fn host_target() -> String {
+    Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .ok()
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| line.strip_prefix("host: ").map(str::to_string))
+        })
+        .unwrap_or_else(|| "x86_64-unknown-linux-gnu".to_string())
+}
+/// None when rustup is unavailable, in which case every target is attempted
+fn installed_targets() -> Option<Vec<String>> {
+    let output = Command::new("rustup").args(["target", "list", "--installed"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).lines().map(|l| l.trim().to_string()).collect())
+}
+pub fn feature_args(features: &str) -> Vec<String> {
+    match features {
+        "default" => Vec::new(),
+        "no-default" => vec!["--no-default-features".to_string()],
+        "all" => vec!["--all-features".to_string()],
+        list => vec!["--features".to_string(), list.to_string()],
+    }
+}
+pub fn cargo_args(check: &str, target: &str, features: &str, profile: &str) -> Vec<String> {
+    let mut args: Vec<String> = check.split_whitespace().map(str::to_string).collect();
+    args.extend(["--target".to_string(), target.to_string(), "--profile".to_string(), profile.to_string()]);
+    args.extend(feature_args(features));
+    args
+}
+/// Each worker gets its own target dir so parallel cells do not queue on cargo's build lock
+fn run_cell(cell: &(String, String, String, String), worker: usize, installed: &Option<Vec<String>>) -> Cell {
+    let (check, target, features, profile) = cell;
+    let started = Instant::now();
+    let mut result = Cell {
+        check: check.clone(),
+        target: target.clone(),
+        features: features.clone(),
+        profile: profile.clone(),
+        status: CellStatus::Pass,
+        seconds: 0.0,
+        error: None,
+    };
+    if installed.as_ref().is_some_and(|targets| !targets.contains(target)) {
+        result.status = CellStatus::Skipped;
+        result.error = Some(format!("target not installed - rustup target add {}", target));
+        return result;
+    }
+    let output = Command::new("cargo")
+        .args(cargo_args(check, target, features, profile))
+        .env("CARGO_TARGET_DIR", format!("target/matrix/worker-{}", worker))
+        .env("CARGO_TERM_COLOR", "never")
+        .stdin(Stdio::null())
+        .output();
+    result.seconds = started.elapsed().as_secs_f64();
+    match output {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            result.status = CellStatus::Fail;
+            result.error = stderr
+                .lines()
+                .find(|line| line.starts_with("error"))
+                .or_else(|| stderr.lines().rev().find(|line| !line.trim().is_empty()))
+                .map(str::to_string);
+        }
+        Err(e) => {
+            result.status = CellStatus::Fail;
+            result.error = Some(format!("failed to run cargo: {}", e));
+        }
+    }
+    result
+}
+/// Runs cells on up to `jobs` threads, returning them in grid order
+pub fn run(matrix: &Matrix, jobs: usize, progress: bool) -> Vec<Cell> {
+    let cells = matrix.cells();
+    let installed = installed_targets();
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Cell>>> = Mutex::new(cells.iter().map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for worker in 0..jobs.clamp(1, cells.len().max(1)) {
+            let (cells, installed, next, results) = (&cells, &installed, &next, &results);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(cell) = cells.get(index) else {
+                    break;
+                };
+                let result = run_cell(cell, worker, installed);
+                if progress {
+                    let mark = match result.status {
+                        CellStatus::Pass => "✓".green(),
+                        CellStatus::Fail => "✗".red(),
+                        CellStatus::Skipped => "-".dimmed(),
+                    };
+                    println!(
+                        "  {} cargo {} [{} · {} · {}] {:.1}s",
+                        mark, result.check, result.target, result.features, result.profile, result.seconds
+                    );
+                }
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+    results.into_inner().unwrap().into_iter().flatten().collect()
+}
+fn print_grid(matrix: &Matrix, cells: &[Cell]) {
+    let row_width = matrix
+        .targets
+        .iter()
+        .flat_map(|t| matrix.profiles.iter().map(move |p| t.len() + p.len() + 3))
+        .max()
+        .unwrap_or(10);
+    let col_width = matrix.features.iter().map(|f| f.len()).max().unwrap_or(4).max(4) + 2;
+    for check in &matrix.checks {
+        println!("\n{}", format!("cargo {}", check).bold());
+        print!("  {:<width$}", "", width = row_width);
+        for features in &matrix.features {
+            print!("{:^width$}", features, width = col_width);
+        }
+        println!();
+        for target in &matrix.targets {
+            for profile in &matrix.profiles {
+                print!("  {:<width$}", format!("{} · {}", target, profile), width = row_width);
+                for features in &matrix.features {
+                    let cell = cells.iter().find(|c| {
+                        &c.check == check && &c.target == target && &c.profile == profile && &c.features == features
+                    });
+                    let mark = format!("{:^width$}", match cell.map(|c| c.status) {
+                        Some(CellStatus::Pass) => "✓",
+                        Some(CellStatus::Fail) => "✗",
+                        _ => "-",
+                    }, width = col_width);
+                    match cell.map(|c| c.status) {
+                        Some(CellStatus::Pass) => print!("{}", mark.green()),
+                        Some(CellStatus::Fail) => print!("{}", mark.red().bold()),
+                        _ => print!("{}", mark.dimmed()),
+                    }
+                }
+                println!();
+            }
+        }
+    }
+}
+pub fn handle_matrix(args: MatrixArgs) -> Result<()> {
+    let matrix = Matrix::resolve(&args);
+    let cells = matrix.cells();
+    if cells.is_empty() {
+        bail!("The matrix is empty - check [matrix] in .cg");
+    }
+    let jobs = args
+        .jobs
+        .or_else(|| ConfigManager::new().ok()?.get("matrix.jobs")?.trim().parse().ok())
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(2, |n| n.get() / 2).max(1));
+    if !args.json {
+        println!(
+            "{}",
+            format!(
+                "🧮 Build matrix: {} check(s) × {} target(s) × {} feature set(s) × {} profile(s) = {} cells, {} jobs",
+                matrix.checks.len(),
+                matrix.targets.len(),
+                matrix.features.len(),
+                matrix.profiles.len(),
+                cells.len(),
+                jobs
+            )
+            .bold()
+            .blue()
+        );
+    }
+    let results = run(&matrix, jobs, !args.json);
+    let failures: Vec<String> = results
+        .iter()
+        .filter(|c| c.status == CellStatus::Fail)
+        .map(|c| {
+            format!(
+                "cargo {} [{} · {} · {}]: {}",
+                c.check,
+                c.target,
+                c.features,
+                c.profile,
+                c.error.as_deref().unwrap_or("failed")
+            )
+        })
+        .collect();
+    let skipped: Vec<String> = results
+        .iter()
+        .filter(|c| c.status == CellStatus::Skipped)
+        .map(|c| format!("{}: {}", c.target, c.error.as_deref().unwrap_or("skipped")))
+        .collect();
+    history::record("cm matrix".to_string(), failures.clone(), skipped.clone());
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        print_grid(&matrix, &results);
+        let mut reasons = skipped.clone();
+        reasons.sort();
+        reasons.dedup();
+        for reason in &reasons {
+            println!("\n  {} {}", "⏭️ ".dimmed(), reason.dimmed());
+        }
+        if failures.is_empty() {
+            println!("\n✅ All {} cells passed", results.len() - skipped.len());
+        } else {
+            println!("\n❌ {} of {} cells failed:", failures.len(), results.len());
+            for failure in &failures {
+                println!("  {}", failure.red());
+            }
+        }
+    }
+    if !failures.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_cells_and_cargo_args() {
+        let matrix = Matrix {
+            checks: vec!["check".to_string(), "clippy".to_string()],
+            targets: vec!["x86_64-unknown-linux-gnu".to_string(), "wasm32-unknown-unknown".to_string()],
+            features: vec!["default".to_string(), "no-default".to_string(), "serde,tokio".to_string()],
+            profiles: vec!["dev".to_string()],
+        };
+        let cells = matrix.cells();
+        assert_eq!(cells.len(), 12);
+        assert_eq!(cells[0], ("check".into(), "x86_64-unknown-linux-gnu".into(), "default".into(), "dev".into()));
+        assert_eq!(
+            cargo_args("clippy", "wasm32-unknown-unknown", "serde,tokio", "release"),
+            vec!["clippy", "--target", "wasm32-unknown-unknown", "--profile", "release", "--features", "serde,tokio"]
+        );
+        assert_eq!(feature_args("no-default"), vec!["--no-default-features"]);
+        assert!(feature_args("default").is_empty());
+    }
+}
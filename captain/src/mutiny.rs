@@ -3,9 +3,10 @@ use colored::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use crate::captain::license;
+const GATE_OVERRIDE: &str = "fmt_gate";
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MutinyConfig {
     pub overrides: HashMap<String, Override>,
@@ -33,18 +34,25 @@ impl MutinyMode {
             .context("Could not find home directory")?
             .join(".shipwreck")
             .join("mutiny.toml");
-        let config = if config_file.exists() {
-            let content = fs::read_to_string(&config_file)?;
-            toml::from_str(&content)?
-        } else {
-            MutinyConfig::default()
-        };
+        let config = Self::load_config(&config_file)?;
         Ok(Self {
             config,
             config_file,
             active: false,
         })
     }
+    /// A missing file is the default config; so is an unreadable one, after it is quarantined
+    fn load_config(path: &Path) -> Result<MutinyConfig> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(MutinyConfig::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        Ok(toml::from_str(&content).unwrap_or_else(|e| {
+            crate::storage::quarantine(path, &e);
+            MutinyConfig::default()
+        }))
+    }
     pub fn activate(&mut self, reason: &str) -> Result<()> {
         self.active = true;
         println!("🏴‍☠️ {} activated!", "MUTINY MODE".red().bold());
@@ -54,8 +62,35 @@ impl MutinyMode {
         self.log_activation(reason)?;
         Ok(())
     }
+    pub fn activate_gate_bypass(&mut self, reason: &str) -> Result<()> {
+        if reason.trim().is_empty() {
+            anyhow::bail!("A reason is required to bypass the fmt/clippy gate");
+        }
+        self.activate(reason)?;
+        let override_config = Override {
+            enabled: true,
+            reason: reason.to_string(),
+            expires: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+            commands: vec!["publish".to_string(), "release".to_string()],
+        };
+        self.config.overrides.insert(GATE_OVERRIDE.to_string(), override_config);
+        self.save_config()?;
+        println!("🚧 The fmt/clippy gate is bypassed for the next hour");
+        Ok(())
+    }
+    pub fn gate_bypass(&self) -> Option<String> {
+        self.config
+            .overrides
+            .get(GATE_OVERRIDE)
+            .filter(|o| o.enabled && !o.reason.trim().is_empty())
+            .filter(|o| o.expires.is_none_or(|expires| expires > chrono::Utc::now()))
+            .map(|o| o.reason.clone())
+    }
     pub fn deactivate(&mut self) -> Result<()> {
         self.active = false;
+        if self.config.overrides.remove(GATE_OVERRIDE).is_some() {
+            self.save_config()?;
+        }
         println!("✅ Mutiny Mode deactivated");
         println!("🚢 Normal cargo operations restored");
         Ok(())
@@ -165,6 +200,9 @@ impl MutinyMode {
     fn should_skip_command(&self, command: &str) -> bool {
         self.config.skip_checks.contains(&command.to_string())
     }
+    pub fn skips_check(&self, check: &str) -> bool {
+        self.should_skip_command(check)
+    }
     pub fn add_custom_flag(&mut self, flag: &str, reason: &str) -> Result<()> {
         self.config.force_flags.push(flag.to_string());
         let override_config = Override {
@@ -257,9 +295,7 @@ impl MutinyMode {
         Ok(())
     }
     fn save_config(&self) -> Result<()> {
-        let toml = toml::to_string_pretty(&self.config)?;
-        fs::write(&self.config_file, toml)?;
-        Ok(())
+        crate::storage::write_atomic(&self.config_file, toml::to_string_pretty(&self.config)?)
     }
     fn log_activation(&self, reason: &str) -> Result<()> {
         let log_file = dirs::home_dir()
@@ -288,6 +324,145 @@ impl Default for MutinyConfig {
         }
     }
 }
+/// Restrictions a project declares in `.cg` (or its synced team layer) under `[mutiny]`.
+/// Global config and environment variables can't loosen them.
+#[derive(Debug, Clone, Default)]
+pub struct MutinyPolicy {
+    /// Actions refused outright (`yolo`, `skip-tests`, ... or `*`)
+    pub disabled: Vec<String>,
+    /// Actions that need `--reason`, which is reported to `report_url`
+    pub require_reason: Vec<String>,
+    /// git user.email values exempt from `disabled`. The email is self-asserted, so this is
+    /// advisory: a maintainer override always needs a reason and is reported like one
+    pub maintainers: Vec<String>,
+    /// When set, restrictions only apply on these branches
+    pub branches: Vec<String>,
+    pub report_url: Option<String>,
+}
+#[derive(Debug, Serialize)]
+pub struct MutinyReport {
+    pub action: String,
+    pub reason: String,
+    pub user: Option<String>,
+    pub branch: Option<String>,
+    pub project: Option<String>,
+    pub timestamp: String,
+}
+fn git_value(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).stderr(Stdio::null()).output().ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+impl MutinyPolicy {
+    pub fn load() -> Self {
+        crate::captain::config::ConfigManager::new()
+            .map(|config| Self::from_entries(&config.entries()))
+            .unwrap_or_default()
+    }
+    pub fn from_entries(entries: &[crate::captain::config::ConfigEntry]) -> Self {
+        use crate::captain::config::{ConfigManager, ConfigSource};
+        let project = |key: &str| {
+            entries
+                .iter()
+                .find(|e| e.key == key && matches!(e.source, ConfigSource::Local | ConfigSource::Team))
+                .map(|e| e.value.clone())
+        };
+        let list = |key: &str| project(key).map(|v| ConfigManager::parse_list(&v)).unwrap_or_default();
+        Self {
+            disabled: list("mutiny.disabled"),
+            require_reason: list("mutiny.require_reason"),
+            maintainers: list("mutiny.maintainers"),
+            branches: list("mutiny.branches"),
+            report_url: project("mutiny.report_url").filter(|url| !url.trim().is_empty()),
+        }
+    }
+    pub fn show(&self) {
+        if self.disabled.is_empty() && self.require_reason.is_empty() {
+            return;
+        }
+        println!("\n🛡️  Project policy (.cg):");
+        if !self.disabled.is_empty() {
+            println!("   Disabled: {}", self.disabled.join(", ").red());
+        }
+        if !self.require_reason.is_empty() {
+            println!("   Reason required: {}", self.require_reason.join(", ").yellow());
+        }
+        if !self.maintainers.is_empty() {
+            println!("   Maintainers: {}", self.maintainers.join(", "));
+        }
+        if !self.branches.is_empty() {
+            println!("   Applies on: {}", self.branches.join(", ").cyan());
+        }
+        if let Some(url) = &self.report_url {
+            println!("   Reasons reported to: {}", crate::net::redact(url));
+        }
+    }
+    fn lists(list: &[String], action: &str) -> bool {
+        list.iter().any(|entry| entry == "*" || entry == action)
+    }
+    /// Refuses the action or returns the report to send; `None` means no restriction applies
+    pub fn check(
+        &self,
+        action: &str,
+        reason: Option<&str>,
+        user: Option<&str>,
+        branch: Option<&str>,
+    ) -> Result<Option<MutinyReport>> {
+        if !self.branches.is_empty() && !branch.is_some_and(|b| self.branches.iter().any(|p| p == b)) {
+            return Ok(None);
+        }
+        let maintainer = user.is_some_and(|u| self.maintainers.iter().any(|m| m.eq_ignore_ascii_case(u)));
+        let disabled = Self::lists(&self.disabled, action);
+        if disabled && !maintainer {
+            anyhow::bail!(
+                "Mutiny '{}' is disabled by this project's .cg (mutiny.disabled){}",
+                action,
+                if self.maintainers.is_empty() { String::new() } else { " - ask a maintainer".to_string() }
+            );
+        }
+        if !disabled && !Self::lists(&self.require_reason, action) {
+            return Ok(None);
+        }
+        let reason = reason
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .with_context(|| format!("This project requires a reason for mutiny '{}' - pass --reason", action))?;
+        Ok(Some(MutinyReport {
+            action: action.to_string(),
+            reason: reason.to_string(),
+            user: user.map(str::to_string),
+            branch: branch.map(str::to_string),
+            project: crate::project::current_project().map(|bucket| bucket.name),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }))
+    }
+    /// Checks `action` for the current git user and branch, reporting the reason when required.
+    /// An override that must be reported is refused when the report can't be delivered.
+    pub fn enforce(&self, action: &str, reason: Option<&str>) -> Result<()> {
+        let user = git_value(&["config", "user.email"]);
+        let branch = git_value(&["rev-parse", "--abbrev-ref", "HEAD"]);
+        let Some(report) = self.check(action, reason, user.as_deref(), branch.as_deref())? else {
+            return Ok(());
+        };
+        if let Some(url) = &self.report_url {
+            crate::net::ensure_online("Reporting a mutiny override")?;
+            let client = crate::net::client(std::time::Duration::from_secs(15))?;
+            let response = crate::net::send(client.post(url).json(&report))
+                .with_context(|| format!("Could not report the override to {}", crate::net::redact(url)))?;
+            if !response.status().is_success() {
+                anyhow::bail!("{} refused the override report ({})", crate::net::redact(url), response.status());
+            }
+            println!("📣 Override reason reported to {}", crate::net::redact(url).dimmed());
+        }
+        MutinyMode::new()?.log_activation(&format!(
+            "{} by {} on {} - {}",
+            action,
+            report.user.as_deref().unwrap_or("unknown"),
+            report.branch.as_deref().unwrap_or("no branch"),
+            report.reason
+        ))
+    }
+}
 pub struct MutinyGuard {
     mode: MutinyMode,
 }
@@ -334,4 +509,42 @@ pub fn check_helmsman_direction(command: &str) -> Result<bool> {
             Ok(false)
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::captain::config::{ConfigEntry, ConfigSource};
+    #[test]
+    fn test_policy_from_project_layers_only() {
+        let entry = |key: &str, value: &str, source| ConfigEntry { key: key.to_string(), value: value.to_string(), source };
+        let policy = MutinyPolicy::from_entries(&[
+            entry("mutiny.disabled", "[\"yolo\", \"skip-tests\"]", ConfigSource::Local),
+            entry("mutiny.require_reason", "force", ConfigSource::Team),
+            entry("mutiny.maintainers", "lead@example.com", ConfigSource::Local),
+            entry("mutiny.branches", "main", ConfigSource::Global),
+        ]);
+        assert!(policy.branches.is_empty());
+        assert!(policy.check("yolo", None, Some("dev@example.com"), Some("feature")).is_err());
+        assert!(policy.check("yolo", None, Some("Lead@example.com"), Some("main")).is_err());
+        let maintainer = policy.check("yolo", Some("release fix"), Some("Lead@example.com"), Some("main")).unwrap();
+        assert_eq!(maintainer.unwrap().user.as_deref(), Some("Lead@example.com"));
+        assert!(policy.check("force", Some("  "), None, Some("main")).is_err());
+        let report = policy.check("force", Some("hotfix"), None, Some("main")).unwrap().unwrap();
+        assert_eq!((report.action.as_str(), report.reason.as_str()), ("force", "hotfix"));
+        assert!(policy.check("allow-warnings", None, None, None).unwrap().is_none());
+        let scoped = MutinyPolicy { branches: vec!["main".to_string()], ..policy };
+        assert!(scoped.check("yolo", None, None, Some("feature")).unwrap().is_none());
+        assert!(scoped.check("yolo", None, None, Some("main")).is_err());
+    }
+    #[test]
+    fn test_corrupt_config_is_quarantined() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("mutiny.toml");
+        assert!(MutinyMode::load_config(&path).unwrap().overrides.is_empty());
+        fs::write(&path, "overrides = [").unwrap();
+        assert!(!MutinyMode::load_config(&path).unwrap().allow_dirty);
+        assert!(!path.exists());
+        let aside: Vec<_> = fs::read_dir(temp.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert!(aside[0].to_string_lossy().starts_with("mutiny.toml.corrupt-"), "{:?}", aside);
+    }
+}
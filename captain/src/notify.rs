@@ -0,0 +1,247 @@
+use crate::captain::config::ConfigManager;
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use colored::*;
+use serde::Serialize;
+use std::time::Duration;
+const DEFAULT_MIN_SECONDS: u64 = 60;
+#[derive(Subcommand, Debug)]
+pub enum NotifyAction {
+    Status,
+    Test,
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotifySettings {
+    pub enabled: bool,
+    pub min_duration: Duration,
+    pub desktop: bool,
+    pub webhook: Option<String>,
+    pub slack: Option<String>,
+}
+impl Default for NotifySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_duration: Duration::from_secs(DEFAULT_MIN_SECONDS),
+            desktop: true,
+            webhook: None,
+            slack: None,
+        }
+    }
+}
+impl NotifySettings {
+    pub fn load() -> Self {
+        match ConfigManager::new() {
+            Ok(config) => Self::from_config(&config),
+            Err(_) => Self::default(),
+        }
+    }
+    pub fn from_config(config: &ConfigManager) -> Self {
+        // Webhook URLs embed credentials, so they are usually kept in the secrets vault
+        let url = |key: &str| {
+            config
+                .get(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .or_else(|| crate::secrets::lookup(key))
+        };
+        Self {
+            enabled: config.get_bool("notify.enabled", true),
+            min_duration: config
+                .get("notify.min_seconds")
+                .and_then(|v| v.trim().parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(Duration::from_secs(DEFAULT_MIN_SECONDS)),
+            desktop: config.get_bool("notify.desktop", true),
+            webhook: url("notify.webhook"),
+            slack: url("notify.slack"),
+        }
+    }
+    /// Desktop notifications are pointless without a desktop, so CI and SSH sessions only get webhooks
+    fn desktop_active(&self) -> bool {
+        self.desktop && !crate::ci::is_ci() && has_display()
+    }
+    /// Whether any channel would fire for a command that ran long enough
+    pub fn is_active(&self) -> bool {
+        self.enabled && (self.desktop_active() || self.webhook.is_some() || self.slack.is_some())
+    }
+    pub fn should_notify(&self, elapsed: Duration) -> bool {
+        self.is_active() && elapsed >= self.min_duration
+    }
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildOutcome {
+    pub project: String,
+    pub command: String,
+    pub success: bool,
+    pub exit_code: i32,
+    pub duration_seconds: f64,
+    pub errors: usize,
+    pub warnings: usize,
+}
+impl BuildOutcome {
+    pub fn new(args: &[&str], exit_code: i32, elapsed: Duration, errors: usize, warnings: usize) -> Self {
+        let args: Vec<&str> = args.iter().copied().skip_while(|arg| *arg == "cargo").collect();
+        Self {
+            project: crate::project::current_project()
+                .map(|bucket| bucket.name)
+                .unwrap_or_else(|| "cargo".to_string()),
+            command: format!("cargo {}", args.join(" ")),
+            success: exit_code == 0,
+            exit_code,
+            duration_seconds: elapsed.as_secs_f64(),
+            errors,
+            warnings,
+        }
+    }
+    pub fn title(&self) -> String {
+        let status = if self.success { "finished" } else { "failed" };
+        format!("{}: {} {}", self.project, self.command, status)
+    }
+    pub fn body(&self) -> String {
+        format!(
+            "{} after {}, {} errors, {} warnings",
+            if self.success { "Succeeded" } else { "Failed" },
+            format_duration(self.duration_seconds),
+            self.errors,
+            self.warnings
+        )
+    }
+    fn slack_payload(&self) -> serde_json::Value {
+        let icon = if self.success { ":white_check_mark:" } else { ":x:" };
+        serde_json::json!({ "text": format!("{} *{}*\n{}", icon, self.title(), self.body()) })
+    }
+}
+fn has_display() -> bool {
+    if cfg!(any(target_os = "macos", windows)) {
+        return true;
+    }
+    ["DISPLAY", "WAYLAND_DISPLAY"].iter().any(|var| std::env::var_os(var).is_some_and(|v| !v.is_empty()))
+}
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    match (seconds / 60, seconds % 60) {
+        (0, s) => format!("{}s", s),
+        (m, s) => format!("{}m {:02}s", m, s),
+    }
+}
+/// Never fails the build; delivery problems are only reported
+pub fn notify_completion(outcome: &BuildOutcome) {
+    let settings = NotifySettings::load();
+    if !settings.should_notify(Duration::from_secs_f64(outcome.duration_seconds)) {
+        return;
+    }
+    for error in send(&settings, outcome) {
+        log::warn!("Notification failed: {}", error);
+    }
+}
+/// A message that isn't about a finished build, e.g. from a script; sent whatever the duration
+pub fn notify_message(title: &str, body: &str) {
+    let settings = NotifySettings::load();
+    if !settings.enabled {
+        return;
+    }
+    let webhook = serde_json::json!({ "title": title, "body": body });
+    let slack = serde_json::json!({ "text": format!("*{}*\n{}", title, body) });
+    for error in deliver(&settings, title, body, webhook, slack) {
+        log::warn!("Notification failed: {}", error);
+    }
+}
+fn send(settings: &NotifySettings, outcome: &BuildOutcome) -> Vec<anyhow::Error> {
+    deliver(settings, &outcome.title(), &outcome.body(), serde_json::json!(outcome), outcome.slack_payload())
+}
+fn deliver(
+    settings: &NotifySettings,
+    title: &str,
+    body: &str,
+    webhook: serde_json::Value,
+    slack: serde_json::Value,
+) -> Vec<anyhow::Error> {
+    let (settings, title, body) = (settings.clone(), title.to_string(), body.to_string());
+    // The blocking HTTP and D-Bus clients must stay off the wrapper's tokio runtime thread
+    std::thread::spawn(move || {
+        let mut results = Vec::new();
+        if settings.desktop_active() {
+            results.push(send_desktop(&title, &body));
+        }
+        if let Some(url) = &settings.webhook {
+            results.push(post(url, &webhook));
+        }
+        if let Some(url) = &settings.slack {
+            results.push(post(url, &slack));
+        }
+        results.into_iter().filter_map(Result::err).collect()
+    })
+    .join()
+    .unwrap_or_else(|_| vec![anyhow::anyhow!("notification thread panicked")])
+}
+fn send_desktop(title: &str, body: &str) -> Result<()> {
+    notify_rust::Notification::new()
+        .appname("cargo-mate")
+        .summary(title)
+        .body(body)
+        .show()?;
+    Ok(())
+}
+fn post(url: &str, payload: &serde_json::Value) -> Result<()> {
+    let response = crate::net::send(crate::net::client(Duration::from_secs(10))?.post(url).json(payload))?;
+    if !response.status().is_success() {
+        bail!("POST {} returned HTTP {}", url, response.status());
+    }
+    Ok(())
+}
+pub fn handle_notify_command(action: NotifyAction) -> Result<()> {
+    let settings = NotifySettings::load();
+    match action {
+        NotifyAction::Status => {
+            let state = if settings.is_active() { "on".green() } else { "off".red() };
+            println!("🔔 Notifications: {}", state);
+            println!("  {:<12} {}s", "min_seconds".cyan(), settings.min_duration.as_secs());
+            println!("  {:<12} {}", "desktop".cyan(), settings.desktop_active());
+            println!("  {:<12} {}", "webhook".cyan(), settings.webhook.as_deref().unwrap_or("-"));
+            println!("  {:<12} {}", "slack".cyan(), settings.slack.as_deref().unwrap_or("-"));
+            println!();
+            println!("💡 Configure with 'cm config set notify.min_seconds 120' or 'cm secret set notify.slack'");
+        }
+        NotifyAction::Test => {
+            if !settings.is_active() {
+                bail!("No notification channel is enabled - see 'cm notify status'");
+            }
+            let outcome = BuildOutcome::new(&["build", "--release"], 0, Duration::from_secs(95), 0, 2);
+            let errors = send(&settings, &outcome);
+            if let Some(error) = errors.into_iter().next() {
+                return Err(error);
+            }
+            println!("✅ Test notification sent: {}", outcome.title());
+        }
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_threshold_and_message() {
+        let settings = NotifySettings {
+            desktop: false,
+            webhook: Some("https://example.com/hook".to_string()),
+            ..NotifySettings::default()
+        };
+        assert!(!settings.should_notify(Duration::from_secs(59)));
+        assert!(settings.should_notify(Duration::from_secs(60)));
+        let disabled = NotifySettings { enabled: false, ..settings.clone() };
+        assert!(!disabled.should_notify(Duration::from_secs(600)));
+        let outcome = BuildOutcome {
+            project: "demo".to_string(),
+            command: "cargo build --release".to_string(),
+            success: false,
+            exit_code: 101,
+            duration_seconds: 125.4,
+            errors: 3,
+            warnings: 1,
+        };
+        assert_eq!(outcome.title(), "demo: cargo build --release failed");
+        assert_eq!(outcome.body(), "Failed after 2m 05s, 3 errors, 1 warnings");
+        assert!(outcome.slack_payload()["text"].as_str().unwrap().starts_with(":x: *demo"));
+    }
+}
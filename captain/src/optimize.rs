@@ -79,7 +79,9 @@ impl BuildOptimizer {
         fs::copy(&cargo_toml_path, &backup_path)?;
         println!("📋 Backed up Cargo.toml to {}", backup_path.display());
         let optimized_content = toml::to_string_pretty(&config)?;
+        let undo = crate::undo::Recorder::capture("optimize", profile.to_string(), [cargo_toml_path.clone()])?;
         fs::write(&cargo_toml_path, optimized_content)?;
+        undo.commit();
         println!("✅ Applied {} optimizations to Cargo.toml", profile.to_string());
         self.show_optimization_summary(&config)?;
         Ok(())
@@ -282,7 +284,9 @@ impl BuildOptimizer {
         if !backup_path.exists() {
             return Err(anyhow::anyhow!("No backup found to restore"));
         }
+        let undo = crate::undo::Recorder::capture("optimize restore", "Cargo.toml from backup", [cargo_toml_path.clone()])?;
         fs::copy(&backup_path, &cargo_toml_path)?;
+        undo.commit();
         println!("✅ Restored Cargo.toml from backup");
         Ok(())
     }
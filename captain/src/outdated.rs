@@ -0,0 +1,477 @@
+use crate::anchor::AnchorManager;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use clap::ValueEnum;
+use colored::*;
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use toml_edit::DocumentMut;
+const INDEX_URL: &str = "https://index.crates.io";
+const OSV_BATCH_URL: &str = "https://api.osv.dev/v1/querybatch";
+const INDEX_CACHE_TTL: Duration = Duration::from_secs(3600);
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateKind {
+    Patch,
+    Minor,
+    Major,
+}
+impl UpdateKind {
+    fn label(&self) -> ColoredString {
+        match self {
+            UpdateKind::Patch => "patch".green(),
+            UpdateKind::Minor => "minor".yellow(),
+            UpdateKind::Major => "major".red(),
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestDependency {
+    pub name: String,
+    pub key: String,
+    pub table: Vec<String>,
+    pub requirement: String,
+}
+impl ManifestDependency {
+    fn table_name(&self) -> String {
+        self.table.join(".")
+    }
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct Update {
+    pub manifest: PathBuf,
+    pub dependency: ManifestDependency,
+    pub current: Version,
+    pub latest: Version,
+    pub kind: UpdateKind,
+    pub fixes: Vec<String>,
+}
+pub fn classify(current: &Version, latest: &Version) -> Option<UpdateKind> {
+    if latest <= current {
+        return None;
+    }
+    // Below 1.0 a minor bump is breaking, and below 0.1 every bump is
+    let breaking = latest.major != current.major
+        || (current.major == 0 && (latest.minor != current.minor || current.minor == 0));
+    let kind = if breaking {
+        UpdateKind::Major
+    } else if latest.minor != current.minor {
+        UpdateKind::Minor
+    } else {
+        UpdateKind::Patch
+    };
+    Some(kind)
+}
+fn table_dependencies(
+    table: &dyn toml_edit::TableLike,
+    path: Vec<String>,
+    out: &mut Vec<ManifestDependency>,
+) {
+    for (key, item) in table.iter() {
+        let (requirement, package) = match item {
+            toml_edit::Item::Value(toml_edit::Value::String(req)) => {
+                (req.value().clone(), None)
+            }
+            _ => {
+                let Some(spec) = item.as_table_like() else {
+                    continue;
+                };
+                let local = ["path", "git", "workspace", "registry"]
+                    .iter()
+                    .any(|field| spec.contains_key(field));
+                let Some(req) = spec.get("version").and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                if local {
+                    continue;
+                }
+                (
+                    req.to_string(),
+                    spec.get("package").and_then(|p| p.as_str()).map(String::from),
+                )
+            }
+        };
+        out.push(ManifestDependency {
+            name: package.unwrap_or_else(|| key.to_string()),
+            key: key.to_string(),
+            table: path.clone(),
+            requirement,
+        });
+    }
+}
+pub fn manifest_dependencies(doc: &DocumentMut) -> Vec<ManifestDependency> {
+    let mut out = Vec::new();
+    for name in DEPENDENCY_TABLES {
+        if let Some(table) = doc.get(name).and_then(|t| t.as_table_like()) {
+            table_dependencies(table, vec![name.to_string()], &mut out);
+        }
+    }
+    if let Some(targets) = doc.get("target").and_then(|t| t.as_table_like()) {
+        for (target, item) in targets.iter() {
+            for name in DEPENDENCY_TABLES {
+                if let Some(table) = item.get(name).and_then(|t| t.as_table_like()) {
+                    let path = vec!["target".to_string(), target.to_string(), name.to_string()];
+                    table_dependencies(table, path, &mut out);
+                }
+            }
+        }
+    }
+    if let Some(table) = doc
+        .get("workspace")
+        .and_then(|w| w.get("dependencies"))
+        .and_then(|t| t.as_table_like())
+    {
+        let path = vec!["workspace".to_string(), "dependencies".to_string()];
+        table_dependencies(table, path, &mut out);
+    }
+    out
+}
+pub fn bump_requirement(old: &str, latest: &Version) -> String {
+    let old = old.trim();
+    if old.contains(',') || old == "*" {
+        return latest.to_string();
+    }
+    let operator: String = old.chars().take_while(|c| "^~=<>".contains(*c)).collect();
+    format!("{}{}", operator, latest)
+}
+fn replace_string(value: &mut toml_edit::Value, new: &str) {
+    let decor = value.decor().clone();
+    *value = new.into();
+    *value.decor_mut() = decor;
+}
+pub fn set_requirement(doc: &mut DocumentMut, dependency: &ManifestDependency, new: &str) -> bool {
+    let mut item = doc.as_item_mut();
+    for key in &dependency.table {
+        match item.get_mut(key) {
+            Some(next) => item = next,
+            None => return false,
+        }
+    }
+    let Some(entry) = item.get_mut(&dependency.key) else {
+        return false;
+    };
+    if let Some(value) = entry.as_value_mut().filter(|v| v.is_str()) {
+        replace_string(value, new);
+        return true;
+    }
+    match entry.get_mut("version").and_then(|v| v.as_value_mut()) {
+        Some(value) => {
+            replace_string(value, new);
+            true
+        }
+        None => false,
+    }
+}
+pub fn locked_versions(lock: &str) -> HashMap<String, Vec<Version>> {
+    let mut versions: HashMap<String, Vec<Version>> = HashMap::new();
+    let Ok(lock) = lock.parse::<toml::Value>() else {
+        return versions;
+    };
+    for package in lock.get("package").and_then(|p| p.as_array()).into_iter().flatten() {
+        let name = package.get("name").and_then(|n| n.as_str());
+        let version = package
+            .get("version")
+            .and_then(|v| v.as_str())
+            .and_then(|v| Version::parse(v).ok());
+        if let (Some(name), Some(version)) = (name, version) {
+            versions.entry(name.to_string()).or_default().push(version);
+        }
+    }
+    versions
+}
+fn current_version(
+    requirement: &str,
+    locked: Option<&Vec<Version>>,
+) -> Option<Version> {
+    let req = VersionReq::parse(requirement).ok()?;
+    if let Some(version) = locked
+        .into_iter()
+        .flatten()
+        .filter(|v| req.matches(v))
+        .max()
+    {
+        return Some(version.clone());
+    }
+    let comparator = req.comparators.first()?;
+    Some(
+        Version::new(
+            comparator.major,
+            comparator.minor.unwrap_or(0),
+            comparator.patch.unwrap_or(0),
+        ),
+    )
+}
+pub fn index_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{}", name),
+        2 => format!("2/{}", name),
+        3 => format!("3/{}/{}", &name[..1], name),
+        _ => format!("{}/{}/{}", &name[..2], &name[2..4], name),
+    }
+}
+pub fn parse_index(body: &str) -> Vec<Version> {
+    body.lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|entry| !entry["yanked"].as_bool().unwrap_or(false))
+        .filter_map(|entry| entry["vers"].as_str().and_then(|v| Version::parse(v).ok()))
+        .filter(|version| version.pre.is_empty())
+        .collect()
+}
+pub fn client() -> Result<reqwest::blocking::Client> {
+    crate::net::client(Duration::from_secs(15))
+}
+/// Index answers are cached for an hour, which also lets `cm outdated` work offline
+pub fn index_body(client: &Result<reqwest::blocking::Client>, name: &str) -> Result<String> {
+    let url = format!("{}/{}", INDEX_URL, index_path(name));
+    crate::net::cached(&url, INDEX_CACHE_TTL, || {
+        let client = client.as_ref().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let response = crate::net::send(client.get(&url))?;
+        if !response.status().is_success() {
+            return Ok(String::new());
+        }
+        Ok(response.text()?)
+    })
+}
+fn latest_version(client: &Result<reqwest::blocking::Client>, name: &str) -> Result<Option<Version>> {
+    Ok(parse_index(&index_body(client, name)?).into_iter().max())
+}
+fn advisory_ids(result: &Value) -> BTreeSet<String> {
+    result["vulns"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v["id"].as_str())
+        .map(String::from)
+        .collect()
+}
+fn attach_fixed_advisories(
+    client: &reqwest::blocking::Client,
+    updates: &mut [Update],
+) -> Result<()> {
+    if updates.is_empty() {
+        return Ok(());
+    }
+    let query = |name: &str, version: &Version| {
+        json!({ "package": { "name": name, "ecosystem": "crates.io" }, "version": version.to_string() })
+    };
+    let queries: Vec<Value> = updates
+        .iter()
+        .flat_map(|u| {
+            [query(&u.dependency.name, &u.current), query(&u.dependency.name, &u.latest)]
+        })
+        .collect();
+    let response: Value = crate::net::send(client.post(OSV_BATCH_URL).json(&json!({ "queries": queries })))?
+        .error_for_status()?
+        .json()?;
+    let results = response["results"].as_array().cloned().unwrap_or_default();
+    for (update, pair) in updates.iter_mut().zip(results.chunks(2)) {
+        if let [current, latest] = pair {
+            let still_affected = advisory_ids(latest);
+            update.fixes = advisory_ids(current)
+                .into_iter()
+                .filter(|id| !still_affected.contains(id))
+                .collect();
+        }
+    }
+    Ok(())
+}
+fn workspace_manifests() -> Result<(PathBuf, Vec<PathBuf>)> {
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .no_deps()
+        .exec()
+        .context("Failed to read cargo metadata - run this inside a cargo project")?;
+    let root = PathBuf::from(metadata.workspace_root.as_std_path());
+    let mut manifests = vec![root.join("Cargo.toml")];
+    for package in &metadata.packages {
+        let manifest = PathBuf::from(package.manifest_path.as_std_path());
+        if !manifests.contains(&manifest) {
+            manifests.push(manifest);
+        }
+    }
+    Ok((root, manifests))
+}
+pub fn find_updates() -> Result<Vec<Update>> {
+    let (root, manifests) = workspace_manifests()?;
+    let locked = locked_versions(
+        &fs::read_to_string(root.join("Cargo.lock")).unwrap_or_default(),
+    );
+    let client = client();
+    let mut latest_cache: HashMap<String, Option<Version>> = HashMap::new();
+    let mut updates = Vec::new();
+    for manifest in manifests {
+        let Ok(content) = fs::read_to_string(&manifest) else {
+            continue;
+        };
+        let doc: DocumentMut = content
+            .parse()
+            .with_context(|| format!("Failed to parse {}", manifest.display()))?;
+        for dependency in manifest_dependencies(&doc) {
+            let Some(current) = current_version(
+                &dependency.requirement,
+                locked.get(&dependency.name),
+            ) else {
+                continue;
+            };
+            let latest = match latest_cache.get(&dependency.name) {
+                Some(latest) => latest.clone(),
+                None => {
+                    let latest = latest_version(&client, &dependency.name)
+                        .with_context(|| {
+                            format!("Failed to query the crates.io index for {}", dependency.name)
+                        })?;
+                    latest_cache.insert(dependency.name.clone(), latest.clone());
+                    latest
+                }
+            };
+            let Some(latest) = latest else {
+                continue;
+            };
+            if let Some(kind) = classify(&current, &latest) {
+                updates
+                    .push(Update {
+                        manifest: manifest.clone(),
+                        dependency,
+                        current,
+                        latest,
+                        kind,
+                        fixes: Vec::new(),
+                    });
+            }
+        }
+    }
+    let advisories = client
+        .as_ref()
+        .map_err(|e| anyhow::anyhow!("{}", e))
+        .and_then(|client| attach_fixed_advisories(client, &mut updates));
+    if let Err(e) = advisories {
+        log::warn!("Could not check RustSec advisories: {}", e);
+    }
+    Ok(updates)
+}
+fn print_updates(updates: &[Update], root: &Path) {
+    if updates.is_empty() {
+        println!("✅ All dependencies are up to date");
+        return;
+    }
+    println!("📦 {} ({})", "Outdated dependencies".bold().blue(), updates.len());
+    let width = updates.iter().map(|u| u.dependency.key.len()).max().unwrap_or(0);
+    for update in updates {
+        let manifest = update.manifest.strip_prefix(root).unwrap_or(&update.manifest);
+        let fixes = if update.fixes.is_empty() {
+            String::new()
+        } else {
+            format!("  🛡️  fixes {}", update.fixes.join(", ")).green().to_string()
+        };
+        println!(
+            "  {:width$}  {} → {}  {}  {}{}", update.dependency.key.cyan(), update.current,
+            update.latest.to_string().bold(), update.kind.label(), format!("{} [{}]",
+            manifest.display(), update.dependency.table_name()).dimmed(), fixes, width =
+            width
+        );
+    }
+}
+pub fn apply_updates(updates: &[&Update]) -> Result<usize> {
+    let anchor_name = format!("before-outdated-{}", Utc::now().format("%Y%m%d-%H%M%S"));
+    AnchorManager::new()?
+        .save(&anchor_name, "Snapshot before applying cm outdated bumps")?;
+    println!(
+        "⚓ Saved anchor {} (restore with 'cm anchor restore {}')", anchor_name.cyan(),
+        anchor_name
+    );
+    let mut by_manifest: HashMap<&Path, Vec<&Update>> = HashMap::new();
+    for update in updates {
+        by_manifest.entry(update.manifest.as_path()).or_default().push(update);
+    }
+    let mut applied = 0;
+    for (manifest, updates) in by_manifest {
+        let mut doc: DocumentMut = fs::read_to_string(manifest)?.parse()?;
+        for update in updates {
+            let requirement = bump_requirement(&update.dependency.requirement, &update.latest);
+            if set_requirement(&mut doc, &update.dependency, &requirement) {
+                println!(
+                    "  ⬆️  {} {} → {}", update.dependency.key.cyan(), update.dependency
+                    .requirement, requirement.green()
+                );
+                applied += 1;
+            }
+        }
+        fs::write(manifest, doc.to_string())?;
+    }
+    Ok(applied)
+}
+pub fn handle_outdated(apply: &[String], upgrade: Option<UpdateKind>, json: bool) -> Result<()> {
+    let (root, _) = workspace_manifests()?;
+    if !json {
+        println!("🔍 Checking the crates.io index...");
+    }
+    let updates = find_updates()?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&updates)?);
+    } else {
+        print_updates(&updates, &root);
+    }
+    let selected: Vec<&Update> = updates
+        .iter()
+        .filter(|u| {
+            apply.iter().any(|name| name == &u.dependency.key || name == &u.dependency.name)
+                || upgrade.is_some_and(|level| u.kind <= level)
+        })
+        .collect();
+    if apply.is_empty() && upgrade.is_none() {
+        if !updates.is_empty() && !json {
+            println!(
+                "\n💡 Apply with 'cm outdated --apply <crate>...' or 'cm outdated --upgrade minor'"
+            );
+        }
+        return Ok(());
+    }
+    if selected.is_empty() {
+        println!("Nothing selected to update.");
+        return Ok(());
+    }
+    let applied = apply_updates(&selected)?;
+    println!("✅ Updated {} requirement(s) - run 'cargo update' to refresh Cargo.lock", applied);
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn classifies_and_rewrites_requirements() {
+        let v = |s: &str| Version::parse(s).unwrap();
+        assert_eq!(classify(& v("1.2.3"), & v("1.2.9")), Some(UpdateKind::Patch));
+        assert_eq!(classify(& v("1.2.3"), & v("1.4.0")), Some(UpdateKind::Minor));
+        assert_eq!(classify(& v("0.3.1"), & v("0.4.0")), Some(UpdateKind::Major));
+        assert_eq!(classify(& v("1.2.3"), & v("1.2.3")), None);
+        assert_eq!(index_path("serde"), "se/rd/serde");
+        assert_eq!(index_path("syn"), "3/s/syn");
+        let index = "{\"name\":\"x\",\"vers\":\"1.0.0\",\"yanked\":false}\n{\"name\":\"x\",\"vers\":\"1.1.0\",\"yanked\":true}\n{\"name\":\"x\",\"vers\":\"2.0.0-rc.1\",\"yanked\":false}";
+        assert_eq!(parse_index(index), vec![v("1.0.0")]);
+        let manifest = "[dependencies]\nserde = \"1.0\" # keep\nlocal = { path = \"../local\" }\nrand = { version = \"=0.8.5\", features = [\"small_rng\"] }\n\n[target.'cfg(unix)'.dev-dependencies]\nnix = \"0.27\"\n";
+        let mut doc: DocumentMut = manifest.parse().unwrap();
+        let deps = manifest_dependencies(&doc);
+        assert_eq!(
+            deps.iter().map(| d | d.key.as_str()).collect::< Vec < _ >> (), ["serde",
+            "rand", "nix"]
+        );
+        assert_eq!(bump_requirement("=0.8.5", & v("0.9.1")), "=0.9.1");
+        assert!(set_requirement(& mut doc, & deps[0], "1.0.210"));
+        assert!(set_requirement(& mut doc, & deps[1], "=0.9.1"));
+        assert!(set_requirement(& mut doc, & deps[2], "0.29.0"));
+        let written = doc.to_string();
+        assert!(written.contains("serde = \"1.0.210\" # keep"));
+        assert!(written.contains("version = \"=0.9.1\""));
+        assert!(written.contains("nix = \"0.29.0\""));
+        let locked = locked_versions(
+            "[[package]]\nname = \"serde\"\nversion = \"1.0.190\"\n",
+        );
+        assert_eq!(current_version("1.0", locked.get("serde")), Some(v("1.0.190")));
+    }
+}
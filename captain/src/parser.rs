@@ -42,7 +42,15 @@ pub struct DiagnosticSpan {
     pub line_end: usize,
     pub column_start: usize,
     pub column_end: usize,
+    #[serde(default)]
+    pub byte_start: usize,
+    #[serde(default)]
+    pub byte_end: usize,
     pub text: Vec<SpanText>,
+    #[serde(default)]
+    pub suggested_replacement: Option<String>,
+    #[serde(default)]
+    pub suggestion_applicability: Option<String>,
 }
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SpanText {
@@ -65,6 +73,8 @@ pub struct CompilerArtifact {
     pub profile: ArtifactProfile,
     pub features: Vec<String>,
     pub filenames: Vec<String>,
+    #[serde(default)]
+    pub executable: Option<String>,
 }
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Target {
@@ -90,6 +100,22 @@ impl fmt::Display for ParsedError {
         write!(f, "[{}] {}:{} - {}", self.code, self.file, self.line, self.message)
     }
 }
+impl ParsedError {
+    pub fn parse_stored(line: &str) -> Option<Self> {
+        let (code, rest) = line.strip_prefix('[')?.split_once("] ")?;
+        let (location, message) = rest.split_once(" - ").unwrap_or((rest, ""));
+        let (file, line) = match location.rsplit_once(':') {
+            Some((file, line)) => (file, line.parse().unwrap_or(0)),
+            None => (location, 0),
+        };
+        Some(Self {
+            code: code.to_string(),
+            file: file.to_string(),
+            line,
+            message: message.to_string(),
+        })
+    }
+}
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ParsedWarning {
     pub code: String,
@@ -0,0 +1,152 @@
+use crate::checklist;
+use crate::history;
+use anyhow::{Context, Result};
+use colored::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+/// Bumped when the environment or the event format changes incompatibly
+pub const PROTOCOL_VERSION: &str = "1";
+const PREFIX: &str = "cm-";
+/// One JSON object per line in `$CM_PLUGIN_EVENTS`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PluginEvent {
+    History {
+        command: String,
+        #[serde(default)]
+        errors: Vec<String>,
+        #[serde(default)]
+        warnings: Vec<String>,
+    },
+    Checklist {
+        item: String,
+    },
+}
+fn executable_name(name: &str) -> String {
+    format!("{}{}{}", PREFIX, name, std::env::consts::EXE_SUFFIX)
+}
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata().is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+/// `cm-<name>` from the first PATH entry that has it, like cargo's external subcommands
+pub fn find(name: &str) -> Option<PathBuf> {
+    let file = executable_name(name);
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(&file))
+        .find(|path| is_executable(path))
+}
+/// Every plugin on PATH by name; earlier PATH entries shadow later ones
+pub fn discover() -> BTreeMap<String, PathBuf> {
+    let mut plugins = BTreeMap::new();
+    let Some(path) = std::env::var_os("PATH") else {
+        return plugins;
+    };
+    for dir in std::env::split_paths(&path) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file = entry.file_name().to_string_lossy().to_string();
+            let Some(name) = file.strip_prefix(PREFIX) else {
+                continue;
+            };
+            let name = name.strip_suffix(std::env::consts::EXE_SUFFIX).unwrap_or(name);
+            if !name.is_empty() && is_executable(&entry.path()) {
+                plugins.entry(name.to_string()).or_insert_with(|| entry.path());
+            }
+        }
+    }
+    plugins
+}
+pub fn parse_events(content: &str) -> Vec<Result<PluginEvent, String>> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|e| format!("{}: {}", e, line.trim())))
+        .collect()
+}
+fn apply(event: PluginEvent) -> Result<()> {
+    match event {
+        PluginEvent::History { command, errors, warnings } => history::record(command, errors, warnings),
+        PluginEvent::Checklist { item } => {
+            checklist::update_items(&checklist::items_file()?, |items| checklist::add_item(items, &item))?;
+        }
+    }
+    Ok(())
+}
+/// Runs the plugin with cargo-mate's context in its environment and applies the events it
+/// wrote; returns the plugin's exit code
+pub fn run(name: &str, plugin: &Path, args: &[String]) -> Result<i32> {
+    let events = std::env::temp_dir().join(format!("cm-plugin-{}-{}.jsonl", name, std::process::id()));
+    let project_dir = crate::project::current_project()
+        .and_then(|bucket| bucket.manifest.parent().map(Path::to_path_buf))
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
+    let mut command = Command::new(plugin);
+    command
+        .args(args)
+        .env("CM_PLUGIN_PROTOCOL", PROTOCOL_VERSION)
+        .env("CM_PLUGIN_NAME", name)
+        .env("CM_PLUGIN_EVENTS", &events)
+        .env("CM_PROJECT_DIR", project_dir)
+        .env("CM_SHIPWRECK_DIR", crate::project::shipwreck_dir()?)
+        .env("CM_DATA_DIR", crate::project::data_dir()?)
+        .env("CM_OUTPUT_FORMAT", if crate::ci::is_ci() { "ci" } else { "human" })
+        .env("CM_VERSION", env!("CARGO_PKG_VERSION"));
+    if let Ok(cm) = std::env::current_exe() {
+        command.env("CM_BIN", cm);
+    }
+    crate::net::NetworkSettings::load().apply_env(&mut command);
+    let status = command.status().with_context(|| format!("Failed to run plugin {}", plugin.display()))?;
+    if let Ok(content) = fs::read_to_string(&events) {
+        for event in parse_events(&content) {
+            match event.map_err(anyhow::Error::msg).and_then(apply) {
+                Ok(()) => {}
+                Err(e) => log::warn!("Ignored event from {}{}: {}", PREFIX, name, e),
+            }
+        }
+        let _ = fs::remove_file(&events);
+    }
+    Ok(status.code().unwrap_or(1))
+}
+pub fn handle_plugins() -> Result<()> {
+    let plugins = discover();
+    if plugins.is_empty() {
+        println!("No plugins found. Any executable named {}<name> on PATH runs as 'cm <name>'", PREFIX);
+        return Ok(());
+    }
+    println!("{}", "🔌 Plugins".bold().blue());
+    for (name, path) in plugins {
+        println!("  {:<16} {}", name.cyan(), path.display().to_string().dimmed());
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_parse_events() {
+        let events = parse_events(
+            "{\"type\": \"history\", \"command\": \"cm audit\", \"errors\": [\"E1\"]}\n\n{\"type\": \"checklist\", \"item\": \"Bump MSRV\"}\n{\"type\": \"nope\"}\n",
+        );
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            Ok(PluginEvent::History { command: "cm audit".into(), errors: vec!["E1".into()], warnings: vec![] })
+        );
+        assert_eq!(events[1], Ok(PluginEvent::Checklist { item: "Bump MSRV".into() }));
+        assert!(events[2].is_err());
+        assert_eq!(executable_name("audit"), format!("cm-audit{}", std::env::consts::EXE_SUFFIX));
+    }
+}
@@ -0,0 +1,242 @@
+use crate::captain::config::ConfigManager;
+use anyhow::Result;
+use clap::Subcommand;
+use colored::*;
+use serde::Serialize;
+#[derive(Subcommand, Debug)]
+pub enum PrivacyAction {
+    /// List every endpoint cargo-mate may contact, why, and whether it currently would
+    Audit {
+        #[arg(long)]
+        json: bool,
+    },
+}
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", content = "reason", rename_all = "kebab-case")]
+pub enum EndpointState {
+    /// Contacted in the background without being asked
+    Enabled,
+    /// Contacted only when you run the command that needs it
+    OnDemand,
+    Disabled(String),
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct Endpoint {
+    pub name: &'static str,
+    pub url: String,
+    pub purpose: &'static str,
+    /// Non-essential traffic stops when `privacy.telemetry` is off
+    pub essential: bool,
+    #[serde(flatten)]
+    pub state: EndpointState,
+}
+/// `privacy.telemetry`, on unless set to off
+pub fn telemetry_enabled() -> bool {
+    ConfigManager::new().map(|config| telemetry_in(&config)).unwrap_or(true)
+}
+pub fn telemetry_in(config: &ConfigManager) -> bool {
+    config.get_bool("privacy.telemetry", true)
+}
+fn configured(config: &Option<ConfigManager>, key: &str) -> Option<String> {
+    config.as_ref()?.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+pub fn endpoints() -> Vec<Endpoint> {
+    let config = ConfigManager::new().ok();
+    let telemetry = telemetry_enabled();
+    let api = crate::user::api_base_url();
+    let licensed = crate::user::license_key().is_some() || std::env::var("CARGO_MATE_LICENSE").is_ok();
+    let background = |on: bool, reason: &str| if on { EndpointState::Enabled } else { EndpointState::Disabled(reason.to_string()) };
+    let notify = crate::notify::NotifySettings::load();
+    let captain = crate::captain::manage::load_settings();
+    let mut endpoints = vec![
+        Endpoint {
+            name: "Admin messages",
+            url: format!("{}/admin/msg", api),
+            purpose: "announcements and security notices shown after commands",
+            essential: false,
+            state: if !telemetry {
+                EndpointState::Disabled("privacy.telemetry off".to_string())
+            } else {
+                background(config.as_ref().is_none_or(crate::admin_msg::messages_enabled), "messages.enabled off")
+            },
+        },
+        Endpoint {
+            name: "License usage counters",
+            url: format!("{}/license/sync", api),
+            purpose: "batched per-command counts sent with grant refreshes",
+            essential: false,
+            state: if !telemetry {
+                EndpointState::Disabled("privacy.telemetry off".to_string())
+            } else {
+                background(licensed, "no license registered")
+            },
+        },
+        Endpoint {
+            name: "License grant",
+            url: format!("{}/license/sync", api),
+            purpose: "refreshes the signed license grant before it expires",
+            essential: true,
+            state: background(licensed, "no license registered"),
+        },
+        Endpoint {
+            name: "Captain key",
+            url: "https://mate.cargo.do/overboard/key".to_string(),
+            purpose: "unlocks the captain binary; cached locally",
+            essential: true,
+            state: EndpointState::OnDemand,
+        },
+        Endpoint {
+            name: "Captain downloads",
+            url: crate::captain::manage::release_url(&captain),
+            purpose: "signed manifest and captain binary for install and 'cm captain update'",
+            essential: true,
+            state: match crate::captain::manage::auto_install_disabled_reason() {
+                Some(reason) => EndpointState::Disabled(format!("auto-install disabled by {}", reason)),
+                None => EndpointState::Enabled,
+            },
+        },
+        Endpoint {
+            name: "CargoMate AI",
+            url: format!("{} (via captain)", api),
+            purpose: "questions and errors sent by 'cm wtf'",
+            essential: true,
+            state: EndpointState::OnDemand,
+        },
+        Endpoint {
+            name: "Ideas",
+            url: format!("{}/idea", api),
+            purpose: "feature ideas sent by 'cm idea'",
+            essential: true,
+            state: EndpointState::OnDemand,
+        },
+        Endpoint {
+            name: "Affiliate program",
+            url: "https://cargo.do/api/create-affiliate".to_string(),
+            purpose: "registration by 'cm affiliate'",
+            essential: true,
+            state: EndpointState::OnDemand,
+        },
+        Endpoint {
+            name: "Journey marketplace",
+            url: "https://cargo.do/api/marketplace".to_string(),
+            purpose: "publishing, searching and downloading journeys",
+            essential: true,
+            state: EndpointState::OnDemand,
+        },
+        Endpoint {
+            name: "GitHub gists",
+            url: "https://api.github.com/gists (via gh)".to_string(),
+            purpose: "journey sharing, backups and 'cm report --gist'",
+            essential: true,
+            state: EndpointState::OnDemand,
+        },
+        Endpoint {
+            name: "crates.io",
+            url: "https://index.crates.io, https://crates.io/api/v1".to_string(),
+            purpose: "'cm outdated', publish checks and 'cm crates'",
+            essential: true,
+            state: EndpointState::OnDemand,
+        },
+        Endpoint {
+            name: "OSV advisories",
+            url: "https://api.osv.dev/v1/querybatch".to_string(),
+            purpose: "advisories fixed by updates in 'cm outdated'",
+            essential: true,
+            state: EndpointState::OnDemand,
+        },
+        Endpoint {
+            name: "Notification webhooks",
+            url: [&notify.webhook, &notify.slack]
+                .iter()
+                .filter_map(|url| url.as_deref().map(crate::net::redact))
+                .collect::<Vec<_>>()
+                .join(", "),
+            purpose: "build-finished notifications you configured",
+            essential: true,
+            state: background(
+                notify.enabled && (notify.webhook.is_some() || notify.slack.is_some()),
+                "no notify.webhook or notify.slack",
+            ),
+        },
+    ];
+    if let Some(backend) = configured(&config, "cache.backend").filter(|b| !b.starts_with("file://")) {
+        endpoints.push(Endpoint {
+            name: "Build cache",
+            url: backend,
+            purpose: "'cm cache push/pull' artifacts",
+            essential: true,
+            state: EndpointState::OnDemand,
+        });
+    }
+    if crate::net::is_offline() {
+        for endpoint in &mut endpoints {
+            endpoint.state = EndpointState::Disabled("offline".to_string());
+        }
+    }
+    endpoints
+}
+fn audit(json: bool) -> Result<()> {
+    let endpoints = endpoints();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&endpoints)?);
+        return Ok(());
+    }
+    println!("{}", "🔒 Privacy audit".bold().blue());
+    println!(
+        "   Telemetry: {}   Network: {}\n",
+        if telemetry_enabled() { "on".yellow() } else { "off".green() },
+        if crate::net::is_offline() { "offline".green() } else { "online".normal() }
+    );
+    for endpoint in &endpoints {
+        let state = match &endpoint.state {
+            EndpointState::Enabled => "● background".yellow(),
+            EndpointState::OnDemand => "○ on demand".cyan(),
+            EndpointState::Disabled(reason) => format!("✕ off ({})", reason).green(),
+        };
+        let kind = if endpoint.essential { "" } else { " [telemetry]" };
+        println!("  {}{}  {}", endpoint.name.bold(), kind.dimmed(), state);
+        if !endpoint.url.is_empty() {
+            println!("      {}", endpoint.url.dimmed());
+        }
+        println!("      {}", endpoint.purpose);
+    }
+    if telemetry_enabled() {
+        println!("\n💡 'cm config set privacy.telemetry off' stops the [telemetry] traffic; 'offline = true' stops all of it");
+    }
+    Ok(())
+}
+pub fn handle_privacy_command(action: PrivacyAction) -> Result<()> {
+    match action {
+        PrivacyAction::Audit { json } => audit(json),
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::captain::license_cache::{Grant, Usage};
+    use chrono::{Duration, Utc};
+    #[test]
+    fn test_telemetry_off_suppresses_usage_sync_and_admin_messages() {
+        let on = ConfigManager::from_layers("", "", "");
+        let off = ConfigManager::from_layers("", "", "[privacy]\ntelemetry = \"off\"");
+        assert!(telemetry_in(&on) && crate::admin_msg::messages_enabled(&on));
+        assert!(!telemetry_in(&off) && !crate::admin_msg::messages_enabled(&off));
+        let now = Utc::now();
+        let grant = Grant {
+            license_key_sha256: crate::captain::integrity::sha256_hex(b"CM-KEY"),
+            tier: "FREE".to_string(),
+            daily_limit: Some(100),
+            issued_at: now,
+            expires_at: now + Duration::days(30),
+        };
+        let mut usage = Usage::default();
+        for _ in 0..25 {
+            usage.record("build", now, telemetry_in(&on));
+        }
+        assert!(usage.needs_sync(&grant, now));
+        usage.record("build", now, telemetry_in(&off));
+        assert_eq!(usage.used_today(now), 26);
+        assert_eq!(usage.pending_total(), 0);
+        assert!(!usage.needs_sync(&grant, now));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::secrets;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::Subcommand;
+use colored::*;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+const FORMAT: u32 = 1;
+const MANIFEST_NAME: &str = "manifest.json";
+const SECRETS_NAME: &str = "secrets.json";
+const BACKUP_DIR: &str = "profile-backups";
+/// Used instead of prompting, e.g. when importing from a dotfiles script
+const PASSPHRASE_ENV: &str = "CM_PROFILE_PASSPHRASE";
+/// Global config (which holds shortcuts and hooks), journeys and checklists, relative to ~/.shipwreck
+const PROFILE_PATHS: &[&str] = &["config.toml", "journeys", "checklists"];
+#[derive(Subcommand, Debug)]
+pub enum ProfileAction {
+    /// Bundle config, shortcuts, hooks, journeys and checklists into one archive
+    Export {
+        file: PathBuf,
+        /// Also include the license and secrets, encrypted with a passphrase
+        #[arg(long)]
+        secrets: bool,
+    },
+    /// Restore a bundle; files it replaces are backed up to ~/.shipwreck/profile-backups
+    Import {
+        file: PathBuf,
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleManifest {
+    format: u32,
+    cm_version: String,
+    created_at: String,
+    files: Vec<String>,
+    #[serde(default)]
+    secrets: Vec<String>,
+}
+/// Every profile file under `root`, as forward-slash paths relative to it
+fn profile_files(root: &Path) -> Vec<String> {
+    let mut prefixes: Vec<PathBuf> = PROFILE_PATHS.iter().map(PathBuf::from).collect();
+    if let Ok(entries) = fs::read_dir(root.join("projects")) {
+        for bucket in entries.flatten().map(|e| e.path()) {
+            if bucket.join("checklists").is_dir() {
+                let relative = bucket.strip_prefix(root).unwrap_or(&bucket).to_path_buf();
+                prefixes.push(relative.join("project.json"));
+                prefixes.push(relative.join("checklists"));
+            }
+        }
+    }
+    let mut files: Vec<String> = prefixes
+        .iter()
+        .flat_map(|prefix| WalkDir::new(root.join(prefix)).into_iter().flatten())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| entry.path().extension().is_none_or(|ext| ext != "lock"))
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(root).ok()?;
+            Some(relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"))
+        })
+        .collect();
+    files.sort();
+    files
+}
+/// Archive paths must stay inside ~/.shipwreck
+fn is_safe(path: &str) -> bool {
+    !path.is_empty() && Path::new(path).components().all(|c| matches!(c, Component::Normal(_)))
+}
+fn passphrase(confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        if !passphrase.is_empty() {
+            return Ok(passphrase);
+        }
+    }
+    if !atty::is(atty::Stream::Stdin) {
+        bail!("Secrets in a profile bundle need a passphrase - set {}", PASSPHRASE_ENV);
+    }
+    let passphrase = rpassword::prompt_password("🔑 Bundle passphrase: ")?;
+    if passphrase.is_empty() {
+        bail!("The passphrase cannot be empty");
+    }
+    if confirm && rpassword::prompt_password("🔑 Repeat passphrase: ")? != passphrase {
+        bail!("Passphrases do not match");
+    }
+    Ok(passphrase)
+}
+fn append_bytes<W: std::io::Write>(builder: &mut tar::Builder<W>, name: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o600);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, name, data)?;
+    Ok(())
+}
+pub fn export(root: &Path, file: &Path, include_secrets: bool) -> Result<BundleSummary> {
+    let files = profile_files(root);
+    let mut sealed = BTreeMap::new();
+    if include_secrets {
+        let names = secrets::list()?;
+        if !names.is_empty() {
+            let cipher = secrets::SecretCipher::from_passphrase(&passphrase(true)?);
+            for name in names {
+                if let Some(value) = secrets::get(&name)? {
+                    sealed.insert(name, secrets::encrypt_with(&cipher, &value)?);
+                }
+            }
+        }
+    }
+    let manifest = BundleManifest {
+        format: FORMAT,
+        cm_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        files: files.clone(),
+        secrets: sealed.keys().cloned().collect(),
+    };
+    let mut builder = tar::Builder::new(GzEncoder::new(fs::File::create(file)?, Compression::default()));
+    append_bytes(&mut builder, MANIFEST_NAME, &serde_json::to_vec_pretty(&manifest)?)?;
+    if !sealed.is_empty() {
+        append_bytes(&mut builder, SECRETS_NAME, &serde_json::to_vec_pretty(&sealed)?)?;
+    }
+    for relative in &files {
+        builder.append_path_with_name(root.join(relative), format!("profile/{}", relative))?;
+    }
+    builder.into_inner()?.finish()?;
+    #[cfg(unix)]
+    if !sealed.is_empty() {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(file, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(BundleSummary { files, secrets: manifest.secrets, ..BundleSummary::default() })
+}
+#[derive(Debug, Default)]
+pub struct BundleSummary {
+    pub files: Vec<String>,
+    pub secrets: Vec<String>,
+    /// Files that already existed with different content
+    pub replaced: Vec<String>,
+    pub backup: Option<PathBuf>,
+}
+fn read_bundle(file: &Path) -> Result<BTreeMap<String, Vec<u8>>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(
+        fs::File::open(file).with_context(|| format!("Cannot open {}", file.display()))?,
+    ));
+    let mut entries = BTreeMap::new();
+    for entry in archive.entries().context("Not a cargo-mate profile bundle")? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().replace('\\', "/");
+        if !is_safe(&name) {
+            bail!("Refusing bundle entry outside ~/.shipwreck: {}", name);
+        }
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        entries.insert(name, data);
+    }
+    Ok(entries)
+}
+pub fn import(root: &Path, file: &Path, dry_run: bool) -> Result<BundleSummary> {
+    let mut entries = read_bundle(file)?;
+    let manifest: BundleManifest = serde_json::from_slice(
+        &entries.remove(MANIFEST_NAME).context("Not a cargo-mate profile bundle (no manifest.json)")?,
+    )?;
+    if manifest.format > FORMAT {
+        bail!(
+            "Bundle was exported by cargo-mate {} in a newer format - run 'cm self update' first",
+            manifest.cm_version
+        );
+    }
+    let mut summary = BundleSummary::default();
+    // Decrypt before writing anything, so a wrong passphrase leaves ~/.shipwreck untouched
+    let mut values = Vec::new();
+    if let Some(data) = entries.get(SECRETS_NAME) {
+        let sealed: BTreeMap<String, String> = serde_json::from_slice(data)?;
+        summary.secrets = sealed.keys().cloned().collect();
+        if !dry_run && !sealed.is_empty() {
+            let cipher = secrets::SecretCipher::from_passphrase(&passphrase(false)?);
+            for (name, value) in sealed {
+                values.push((name, secrets::decrypt_with(&cipher, &value).context("Wrong bundle passphrase")?));
+            }
+        }
+    }
+    let backup = root.join(BACKUP_DIR).join(Utc::now().format("%Y%m%d-%H%M%S").to_string());
+    for (name, data) in &entries {
+        let Some(relative) = name.strip_prefix("profile/") else {
+            continue;
+        };
+        let target = root.join(relative);
+        summary.files.push(relative.to_string());
+        match fs::read(&target) {
+            Ok(existing) if existing == *data => continue,
+            Ok(existing) => {
+                summary.replaced.push(relative.to_string());
+                if !dry_run {
+                    let saved = backup.join(relative);
+                    fs::create_dir_all(saved.parent().unwrap_or(&backup))?;
+                    fs::write(saved, existing)?;
+                    summary.backup = Some(backup.clone());
+                }
+            }
+            Err(_) => {}
+        }
+        if !dry_run {
+            fs::create_dir_all(target.parent().unwrap_or(root))?;
+            fs::write(&target, data)?;
+        }
+    }
+    for (name, value) in values {
+        secrets::set(&name, &value)?;
+    }
+    Ok(summary)
+}
+pub fn handle_profile_command(action: ProfileAction) -> Result<()> {
+    let root = crate::project::shipwreck_dir()?;
+    match action {
+        ProfileAction::Export { file, secrets } => {
+            let summary = export(&root, &file, secrets)?;
+            println!("📦 Exported {} file(s) to {}", summary.files.len(), file.display().to_string().cyan());
+            if !summary.secrets.is_empty() {
+                println!("🔐 Included {} secret(s), encrypted: {}", summary.secrets.len(), summary.secrets.join(", "));
+            } else if !secrets {
+                println!("{}", "💡 License and secrets were left out - add --secrets to include them".dimmed());
+            }
+        }
+        ProfileAction::Import { file, dry_run } => {
+            let summary = import(&root, &file, dry_run)?;
+            let verb = if dry_run { "Would import" } else { "Imported" };
+            println!("📥 {} {} file(s) into ~/.shipwreck", verb, summary.files.len());
+            for replaced in &summary.replaced {
+                println!("  ✏️  {}", replaced);
+            }
+            if let Some(backup) = &summary.backup {
+                println!("💾 Replaced files backed up to {}", backup.display());
+            }
+            if !summary.secrets.is_empty() {
+                println!("🔐 {} {} secret(s): {}", verb, summary.secrets.len(), summary.secrets.join(", "));
+            }
+        }
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_export_import_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let (source, target) = (base.join("source"), base.join("target"));
+        let bucket = source.join("projects/demo-0123456789ab");
+        fs::create_dir_all(bucket.join("checklists")).unwrap();
+        fs::create_dir_all(source.join("journeys")).unwrap();
+        fs::create_dir_all(source.join("history")).unwrap();
+        fs::write(source.join("config.toml"), "[shortcuts]\nb = \"build --release\"\n").unwrap();
+        fs::write(source.join("journeys/release.json"), "{}").unwrap();
+        fs::write(source.join("history/history.json"), "[]").unwrap();
+        fs::write(bucket.join("project.json"), "{}").unwrap();
+        fs::write(bucket.join("checklists/items.json"), "[]").unwrap();
+        fs::create_dir_all(source.join("projects/other-ba9876543210")).unwrap();
+        let bundle = base.join("profile.tar.gz");
+        let exported = export(&source, &bundle, false).unwrap();
+        assert_eq!(
+            exported.files,
+            vec![
+                "config.toml",
+                "journeys/release.json",
+                "projects/demo-0123456789ab/checklists/items.json",
+                "projects/demo-0123456789ab/project.json",
+            ]
+        );
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("config.toml"), "[ui]\ncolors = false\n").unwrap();
+        let preview = import(&target, &bundle, true).unwrap();
+        assert_eq!(preview.replaced, vec!["config.toml"]);
+        assert!(!target.join("journeys").exists());
+        let imported = import(&target, &bundle, false).unwrap();
+        assert_eq!(fs::read_to_string(target.join("config.toml")).unwrap(), "[shortcuts]\nb = \"build --release\"\n");
+        assert!(target.join("projects/demo-0123456789ab/checklists/items.json").exists());
+        let backup = imported.backup.unwrap();
+        assert_eq!(fs::read_to_string(backup.join("config.toml")).unwrap(), "[ui]\ncolors = false\n");
+        assert!(!is_safe("../etc/passwd") && !is_safe("/etc/passwd") && is_safe("profile/config.toml"));
+    }
+}
@@ -0,0 +1,369 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+const INDEX_FILE: &str = "index.json";
+const SAMPLE_HZ: &str = "997";
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profiler {
+    Perf,
+    Dtrace,
+    Instruments,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileEntry {
+    pub id: String,
+    pub target: String,
+    pub bench: bool,
+    pub profiler: Profiler,
+    pub project: String,
+    pub args: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub duration_ms: u128,
+    pub output: String,
+}
+impl Profiler {
+    pub fn for_os(os: &str, instruments: bool) -> Result<Self> {
+        match os {
+            "linux" => Ok(Profiler::Perf),
+            "macos" if instruments => Ok(Profiler::Instruments),
+            "macos" => Ok(Profiler::Dtrace),
+            other => bail!("Profiling is not supported on {} yet (perf on Linux, dtrace/Instruments on macOS)", other),
+        }
+    }
+    fn name(&self) -> &'static str {
+        match self {
+            Profiler::Perf => "perf",
+            Profiler::Dtrace => "dtrace",
+            Profiler::Instruments => "Instruments",
+        }
+    }
+    /// Program and arguments that sample `exe` and write raw stacks to `data`
+    pub fn record_command(&self, exe: &Path, args: &[String], data: &Path) -> Vec<String> {
+        let exe = exe.display().to_string();
+        let data = data.display().to_string();
+        let mut command: Vec<String> = match self {
+            Profiler::Perf => {
+                vec!["perf", "record", "-F", SAMPLE_HZ, "--call-graph", "dwarf", "-g", "-o", &data, "--"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            }
+            Profiler::Dtrace => {
+                let mut target = vec![exe.clone()];
+                target.extend(args.iter().cloned());
+                return vec![
+                    "dtrace".to_string(),
+                    "-x".to_string(),
+                    "ustackframes=100".to_string(),
+                    "-n".to_string(),
+                    format!("profile-{} /pid == $target/ {{ @[ustack()] = count(); }}", SAMPLE_HZ),
+                    "-o".to_string(),
+                    data,
+                    "-c".to_string(),
+                    shell_words::join(&target),
+                ];
+            }
+            Profiler::Instruments => {
+                vec!["xcrun", "xctrace", "record", "--template", "Time Profiler", "--output", &data, "--launch", "--"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            }
+        };
+        command.push(exe);
+        command.extend(args.iter().cloned());
+        command
+    }
+    fn collapse_tool(&self) -> Option<&'static str> {
+        match self {
+            Profiler::Perf => Some("inferno-collapse-perf"),
+            Profiler::Dtrace => Some("inferno-collapse-dtrace"),
+            Profiler::Instruments => None,
+        }
+    }
+    fn data_file(&self) -> &'static str {
+        match self {
+            Profiler::Perf => "perf.data",
+            Profiler::Dtrace => "dtrace.stacks",
+            Profiler::Instruments => "profile.trace",
+        }
+    }
+}
+fn is_available(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+fn validate(profiler: Profiler) -> Result<()> {
+    match profiler {
+        Profiler::Perf => {
+            if !is_available("perf", &["--version"]) {
+                bail!(
+                    "perf is not installed - try 'sudo apt install linux-tools-common linux-tools-$(uname -r)' or your distro's perf package"
+                );
+            }
+            let paranoid = fs::read_to_string("/proc/sys/kernel/perf_event_paranoid")
+                .ok()
+                .and_then(|v| v.trim().parse::<i32>().ok())
+                .unwrap_or(0);
+            if paranoid > 1 {
+                println!(
+                    "⚠️  kernel.perf_event_paranoid is {} - if perf fails, run {}", paranoid,
+                    "sudo sysctl kernel.perf_event_paranoid=1".yellow()
+                );
+            }
+        }
+        Profiler::Dtrace => {
+            if !is_available("dtrace", &["-V"]) {
+                bail!("dtrace is not available - use --instruments, or check System Integrity Protection settings");
+            }
+            println!("💡 dtrace usually needs root; run with sudo if it reports a permission error");
+        }
+        Profiler::Instruments => {
+            if !is_available("xcrun", &["xctrace", "version"]) {
+                bail!("xctrace not found - install Xcode and run 'xcode-select --install'");
+            }
+        }
+    }
+    if profiler.collapse_tool().is_some() && !is_available("inferno-flamegraph", &["--version"]) {
+        if !crate::ci::confirm("🔥 inferno (flamegraph renderer) is not installed. Install it with 'cargo install inferno'?")? {
+            bail!("inferno is required to render flamegraphs");
+        }
+        let status = Command::new("cargo").args(["install", "inferno"]).status()?;
+        if !status.success() {
+            bail!("cargo install inferno failed");
+        }
+    }
+    Ok(())
+}
+pub fn build_args(bin: Option<&str>, bench: Option<&str>) -> Vec<String> {
+    let mut args: Vec<String> = match bench {
+        Some(bench) => vec!["bench".into(), "--no-run".into(), "--bench".into(), bench.into()],
+        None => vec!["build".into(), "--release".into()],
+    };
+    if let Some(bin) = bin.filter(|_| bench.is_none()) {
+        args.push("--bin".into());
+        args.push(bin.into());
+    }
+    args.push("--message-format=json-render-diagnostics".into());
+    args
+}
+/// Find the executable cargo reported for the target in its JSON messages; without a
+/// target name the package must have exactly one binary
+pub fn find_executable(messages: &str, target: Option<&str>) -> Result<PathBuf> {
+    let mut found: Vec<(String, PathBuf)> = messages
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|msg| msg["reason"] == "compiler-artifact")
+        .filter(|msg| match target {
+            Some(target) => msg["target"]["name"] == target,
+            None => msg["target"]["kind"].as_array().is_some_and(|k| k.iter().any(|k| k == "bin")),
+        })
+        .filter_map(|msg| {
+            let name = msg["target"]["name"].as_str()?.to_string();
+            Some((name, PathBuf::from(msg["executable"].as_str()?)))
+        })
+        .collect();
+    found.dedup_by(|a, b| a.0 == b.0);
+    match found.len() {
+        0 => bail!("cargo did not produce an executable{}", target.map(|t| format!(" named '{}'", t)).unwrap_or_default()),
+        1 => Ok(found.remove(0).1),
+        _ if target.is_some() => Ok(found.pop().unwrap().1),
+        _ => {
+            let names: Vec<String> = found.into_iter().map(|(name, _)| name).collect();
+            bail!("Several binaries were built ({}) - pick one with 'cm profile <bin>'", names.join(", "))
+        }
+    }
+}
+fn build(bin: Option<&str>, bench: Option<&str>) -> Result<PathBuf> {
+    println!("🔨 Building {} with debug symbols...", bench.or(bin).unwrap_or("release binary").cyan());
+    let output = Command::new("cargo")
+        .args(build_args(bin, bench))
+        .env("CARGO_PROFILE_RELEASE_DEBUG", "true")
+        .env("CARGO_PROFILE_BENCH_DEBUG", "true")
+        .stderr(Stdio::inherit())
+        .output()
+        .context("Failed to start cargo")?;
+    if !output.status.success() {
+        bail!("Build failed");
+    }
+    find_executable(&String::from_utf8_lossy(&output.stdout), bench.or(bin))
+}
+/// Pipe `first` through each later command, writing the last one's output to `out`
+fn pipeline(commands: &[Vec<String>], out: &Path) -> Result<()> {
+    let mut previous: Option<std::process::ChildStdout> = None;
+    let mut children = Vec::new();
+    for (index, command) in commands.iter().enumerate() {
+        let mut cmd = Command::new(&command[0]);
+        cmd.args(&command[1..]);
+        if let Some(stdout) = previous.take() {
+            cmd.stdin(Stdio::from(stdout));
+        }
+        if index + 1 == commands.len() {
+            cmd.stdout(Stdio::from(fs::File::create(out)?));
+        } else {
+            cmd.stdout(Stdio::piped());
+        }
+        let mut child = cmd.spawn().with_context(|| format!("Failed to start {}", command[0]))?;
+        previous = child.stdout.take();
+        children.push((command[0].clone(), child));
+    }
+    for (name, mut child) in children {
+        if !child.wait()?.success() {
+            bail!("{} failed", name);
+        }
+    }
+    Ok(())
+}
+pub fn profiles_dir() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join("profiles"))
+}
+pub fn load_index() -> Result<Vec<ProfileEntry>> {
+    let path = profiles_dir()?.join(INDEX_FILE);
+    Ok(crate::storage::read_json_file(&path)?.unwrap_or_default())
+}
+fn save_entry(entry: ProfileEntry) -> Result<()> {
+    crate::storage::update_json(&profiles_dir()?.join(INDEX_FILE), |index: &mut Vec<ProfileEntry>| index.push(entry))
+}
+pub fn handle_profile(bin: Option<String>, bench: Option<String>, instruments: bool, args: Vec<String>) -> Result<()> {
+    let profiler = Profiler::for_os(std::env::consts::OS, instruments)?;
+    let project_dir = std::env::current_dir()?
+        .ancestors()
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(PathBuf::from)
+        .context("Not inside a cargo project")?;
+    let project = project_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    validate(profiler)?;
+    let exe = build(bin.as_deref(), bench.as_deref())?;
+    let target = bench.clone().or(bin).unwrap_or_else(|| {
+        exe.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+    });
+    let id = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let dir = profiles_dir()?.join(&id);
+    fs::create_dir_all(&dir)?;
+    let data = dir.join(profiler.data_file());
+    let mut run_args = args.clone();
+    if bench.is_some() && !run_args.iter().any(|arg| arg == "--bench") {
+        run_args.insert(0, "--bench".to_string());
+    }
+    let record = profiler.record_command(&exe, &run_args, &data);
+    println!("🔬 Profiling {} with {}...", exe.display().to_string().cyan(), profiler.name());
+    let started = Instant::now();
+    let status = Command::new(&record[0]).args(&record[1..]).status()
+        .with_context(|| format!("Failed to start {}", record[0]))?;
+    let duration_ms = started.elapsed().as_millis();
+    if !status.success() {
+        println!("⚠️  {} exited with {} - rendering whatever was sampled", profiler.name(), status);
+    }
+    let output = match profiler.collapse_tool() {
+        Some(collapse) => {
+            let svg = dir.join("flamegraph.svg");
+            let title = format!("{} ({})", target, id);
+            let data_arg = data.display().to_string();
+            let mut stages = Vec::new();
+            if profiler == Profiler::Perf {
+                stages.push(vec!["perf".to_string(), "script".to_string(), "-i".to_string(), data_arg]);
+                stages.push(vec![collapse.to_string()]);
+            } else {
+                stages.push(vec![collapse.to_string(), data_arg]);
+            }
+            stages.push(vec!["inferno-flamegraph".to_string(), "--title".to_string(), title]);
+            pipeline(&stages, &svg)?;
+            let _ = fs::remove_file(&data);
+            svg
+        }
+        None => data,
+    };
+    save_entry(ProfileEntry {
+        id: id.clone(),
+        target,
+        bench: bench.is_some(),
+        profiler,
+        project,
+        args,
+        created_at: Utc::now(),
+        duration_ms,
+        output: output.display().to_string(),
+    })?;
+    println!("🔥 Profile saved to {}", output.display().to_string().green());
+    println!("   Browse profiles with {}", "cm view profiles".yellow());
+    Ok(())
+}
+pub fn show_profiles(open: Option<&str>) -> Result<()> {
+    let mut index = load_index()?;
+    if let Some(id) = open {
+        let entry = index
+            .iter()
+            .rev()
+            .find(|e| id == "last" || e.id.starts_with(id))
+            .with_context(|| format!("No profile matches '{}'", id))?;
+        println!("🚀 Opening {}", entry.output.cyan());
+        let opener = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+        Command::new(opener).arg(&entry.output).spawn()
+            .map_err(|e| anyhow::anyhow!("Could not open {}: {}", entry.output, e))?;
+        return Ok(());
+    }
+    index.retain(|e| Path::new(&e.output).exists());
+    if index.is_empty() {
+        println!("🔥 No profiles yet - record one with 'cm profile <bin>'");
+        return Ok(());
+    }
+    println!("🔥 Profiles:");
+    println!("{}", "═".repeat(90).blue());
+    println!(
+        "{}", format!("{:<16}  {:<12} {:<20} {:<11} {:>8}  {}", "ID", "PROJECT", "TARGET",
+        "PROFILER", "TIME", "OUTPUT").bold()
+    );
+    for entry in index.iter().rev() {
+        let target = if entry.bench { format!("{} (bench)", entry.target) } else { entry.target.clone() };
+        println!(
+            "{:<16}  {:<12} {:<20} {:<11} {:>7.1}s  {}", entry.id.cyan(), entry.project, target,
+            entry.profiler.name(), entry.duration_ms as f64 / 1000.0, entry.output.dimmed()
+        );
+    }
+    println!();
+    println!("Open one with {}", "cm view profiles --open <id>".yellow());
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn picks_profiler_and_builds_commands() {
+        assert_eq!(Profiler::for_os("linux", false).unwrap(), Profiler::Perf);
+        assert_eq!(Profiler::for_os("macos", true).unwrap(), Profiler::Instruments);
+        assert!(Profiler::for_os("windows", false).is_err());
+        let args = vec!["--size".to_string(), "big".to_string()];
+        let perf = Profiler::Perf.record_command(Path::new("/t/app"), &args, Path::new("/p/perf.data"));
+        assert_eq!(perf[..2], ["perf", "record"]);
+        assert_eq!(perf[perf.len() - 3..], ["/t/app", "--size", "big"]);
+        let dtrace = Profiler::Dtrace.record_command(Path::new("/t/my app"), &args, Path::new("/p/s"));
+        assert_eq!(dtrace.last().unwrap(), "'/t/my app' --size big");
+        assert_eq!(build_args(Some("app"), Some("parse"))[..4], ["bench", "--no-run", "--bench", "parse"]);
+        assert_eq!(build_args(Some("app"), None)[..4], ["build", "--release", "--bin", "app"]);
+        let messages = concat!(
+            r#"{"reason":"compiler-artifact","target":{"name":"dep"},"executable":null}"#, "\n",
+            r#"{"reason":"compiler-artifact","target":{"name":"app"},"executable":"/t/release/app"}"#, "\n",
+            r#"{"reason":"build-finished","success":true}"#
+        );
+        assert_eq!(find_executable(messages, Some("app")).unwrap(), PathBuf::from("/t/release/app"));
+        assert!(find_executable(messages, Some("dep")).is_err());
+        let two = messages.replace(r#""name":"dep"},"executable":null"#, r#""name":"cli","kind":["bin"]},"executable":"/t/cli""#)
+            .replace(r#""name":"app"}"#, r#""name":"app","kind":["bin"]}"#);
+        assert!(find_executable(&two, None).unwrap_err().to_string().contains("cli, app"));
+    }
+}
@@ -0,0 +1,158 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+static GLOBAL_VIEW: AtomicBool = AtomicBool::new(false);
+const PROJECTS_DIR: &str = "projects";
+const MARKER_FILE: &str = "project.json";
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectBucket {
+    pub key: String,
+    pub name: String,
+    pub manifest: PathBuf,
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+pub fn set_global_view(global: bool) {
+    GLOBAL_VIEW.store(global, Ordering::Relaxed);
+}
+pub fn global_view() -> bool {
+    GLOBAL_VIEW.load(Ordering::Relaxed)
+}
+pub fn shipwreck_dir() -> Result<PathBuf> {
+    Ok(dirs::home_dir().context("Could not find home directory")?.join(".shipwreck"))
+}
+pub fn find_manifest(start: &Path) -> Option<PathBuf> {
+    let mut nearest = None;
+    for dir in start.ancestors() {
+        let manifest = dir.join("Cargo.toml");
+        if !manifest.is_file() {
+            continue;
+        }
+        if nearest.is_none() {
+            nearest = Some(manifest.clone());
+        }
+        let is_workspace = fs::read_to_string(&manifest)
+            .map(|content| content.lines().any(|line| line.trim() == "[workspace]"))
+            .unwrap_or(false);
+        if is_workspace {
+            return Some(manifest);
+        }
+    }
+    nearest
+}
+pub fn project_key(manifest: &Path) -> String {
+    let manifest = manifest.canonicalize().unwrap_or_else(|_| manifest.to_path_buf());
+    let hash = format!("{:x}", Sha256::digest(manifest.to_string_lossy().as_bytes()));
+    let name: String = project_name(&manifest)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}-{}", name, &hash[..12])
+}
+fn project_name(manifest: &Path) -> String {
+    manifest
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string())
+}
+pub fn current_project() -> Option<ProjectBucket> {
+    let manifest = find_manifest(&std::env::current_dir().ok()?)?;
+    let manifest = manifest.canonicalize().unwrap_or(manifest);
+    let key = project_key(&manifest);
+    Some(ProjectBucket {
+        dir: shipwreck_dir().ok()?.join(PROJECTS_DIR).join(&key),
+        name: project_name(&manifest),
+        key,
+        manifest,
+    })
+}
+pub fn data_dir() -> Result<PathBuf> {
+    match current_project() {
+        Some(bucket) => {
+            fs::create_dir_all(&bucket.dir)?;
+            let marker = bucket.dir.join(MARKER_FILE);
+            if !marker.exists() {
+                crate::storage::write_json_atomic(&marker, &bucket)?;
+            }
+            Ok(bucket.dir)
+        }
+        None => shipwreck_dir(),
+    }
+}
+pub fn data_file(relative: &str) -> Result<PathBuf> {
+    let path = data_dir()?.join(relative);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(path)
+}
+pub fn buckets() -> Result<Vec<ProjectBucket>> {
+    let projects = shipwreck_dir()?.join(PROJECTS_DIR);
+    let mut buckets = Vec::new();
+    if let Ok(entries) = fs::read_dir(&projects) {
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            let Ok(content) = fs::read_to_string(dir.join(MARKER_FILE)) else {
+                continue;
+            };
+            if let Ok(mut bucket) = serde_json::from_str::<ProjectBucket>(&content) {
+                bucket.dir = dir;
+                buckets.push(bucket);
+            }
+        }
+    }
+    buckets.sort_by(|a, b| a.name.cmp(&b.name).then(a.key.cmp(&b.key)));
+    Ok(buckets)
+}
+pub fn read_files(relative: &str) -> Result<Vec<(String, PathBuf)>> {
+    if !global_view() {
+        let path = data_dir()?.join(relative);
+        return Ok(if path.exists() { vec![(String::new(), path)] } else { Vec::new() });
+    }
+    let mut files = Vec::new();
+    for bucket in buckets()? {
+        let path = bucket.dir.join(relative);
+        if path.exists() {
+            files.push((format!("{} ({})", bucket.name, bucket.manifest.display()), path));
+        }
+    }
+    let legacy = shipwreck_dir()?.join(relative);
+    if legacy.exists() {
+        files.push(("outside any project".to_string(), legacy));
+    }
+    Ok(files)
+}
+pub fn read_text(relative: &str) -> Result<Option<String>> {
+    let mut sections = Vec::new();
+    for (label, path) in read_files(relative)? {
+        let content = fs::read_to_string(&path)?;
+        if label.is_empty() {
+            sections.push(content);
+        } else if !content.trim().is_empty() {
+            sections.push(format!("── {} ──\n{}", label, content));
+        }
+    }
+    Ok(if sections.is_empty() { None } else { Some(sections.join("\n")) })
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_manifest_lookup_prefers_workspace_root() {
+        let temp = tempfile::tempdir().unwrap();
+        let member = temp.path().join("crates").join("core");
+        fs::create_dir_all(member.join("src")).unwrap();
+        fs::write(member.join("Cargo.toml"), "[package]\nname = \"core\"\n").unwrap();
+        assert_eq!(find_manifest(&member.join("src")).unwrap(), member.join("Cargo.toml"));
+        fs::write(temp.path().join("Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\n")
+            .unwrap();
+        assert_eq!(find_manifest(&member.join("src")).unwrap(), temp.path().join("Cargo.toml"));
+        let key = project_key(&member.join("Cargo.toml"));
+        assert!(key.starts_with("core-"));
+        assert_ne!(key, project_key(&temp.path().join("Cargo.toml")));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::captain::config::{ConfigManager, ConfigSource};
+use crate::secrets::{self, encrypt_with, SecretCipher};
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use colored::*;
+use std::path::PathBuf;
+const SECTION: &str = "env";
+/// Overrides the per-user key file, so encrypted values can be shared with CI or teammates
+const KEY_ENV: &str = "CM_ENV_KEY";
+#[derive(Subcommand, Debug)]
+pub enum EnvAction {
+    Set {
+        key: String,
+        value: String,
+        #[arg(long)]
+        secret: bool,
+    },
+    Unset { key: String },
+    List {
+        #[arg(long)]
+        reveal: bool,
+    },
+    Import {
+        #[arg(default_value = ".env")]
+        path: PathBuf,
+        #[arg(long)]
+        secret: bool,
+    },
+}
+#[derive(Debug, Clone)]
+pub struct EnvVar {
+    pub name: String,
+    pub value: String,
+    pub secret: bool,
+    pub source: ConfigSource,
+}
+fn key_file() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join("env.key"))
+}
+fn cipher() -> Result<SecretCipher> {
+    match std::env::var(KEY_ENV) {
+        Ok(passphrase) if !passphrase.is_empty() => Ok(SecretCipher::from_passphrase(&passphrase)),
+        _ => Ok(SecretCipher::from_key(&secrets::load_or_create_key_file(&key_file()?)?)),
+    }
+}
+fn decrypt(cipher: &SecretCipher, stored: &str) -> Result<String> {
+    secrets::decrypt_with(cipher, stored)
+        .with_context(|| format!("Set {} or restore ~/.shipwreck/env.key", KEY_ENV))
+}
+fn validate_name(name: &str) -> Result<()> {
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        bail!("'{}' is not a valid environment variable name", name);
+    }
+    Ok(())
+}
+/// Project (`.cg`) variables override global ones; secrets stay encrypted
+pub fn stored_vars(config: &ConfigManager) -> Vec<EnvVar> {
+    let prefix = format!("{}.", SECTION);
+    config
+        .entries()
+        .into_iter()
+        .filter(|entry| entry.source != ConfigSource::Env)
+        .filter_map(|entry| {
+            let name = entry.key.strip_prefix(&prefix)?.to_string();
+            Some(EnvVar { secret: secrets::is_encrypted(&entry.value), name, value: entry.value, source: entry.source })
+        })
+        .collect()
+}
+/// Decrypted variables to inject into wrapped commands
+pub fn resolved_vars(config: &ConfigManager) -> Result<Vec<(String, String)>> {
+    let vars = stored_vars(config);
+    let cipher = if vars.iter().any(|var| var.secret) { Some(cipher()?) } else { None };
+    vars.into_iter()
+        .map(|var| {
+            let value = match &cipher {
+                Some(cipher) if var.secret => decrypt(cipher, &var.value)
+                    .with_context(|| format!("env.{}", var.name))?,
+                _ => var.value,
+            };
+            Ok((var.name, value))
+        })
+        .collect()
+}
+/// Exports project variables into this process so every command the wrapper spawns
+/// (cargo, hooks, journeys) inherits them. Variables already set in the shell win.
+pub fn apply(config: &ConfigManager) {
+    match resolved_vars(config) {
+        Ok(vars) => {
+            for (name, value) in vars {
+                if std::env::var_os(&name).is_none() {
+                    std::env::set_var(name, value);
+                }
+            }
+        }
+        Err(e) => log::warn!("Project environment not applied: {:#}", e),
+    }
+}
+fn set_var(config: &mut ConfigManager, name: &str, value: &str, secret: bool) -> Result<()> {
+    validate_name(name)?;
+    let stored = if secret { encrypt_with(&cipher()?, value)? } else { value.to_string() };
+    config.set_string(&format!("{}.{}", SECTION, name), &stored, true)
+}
+pub fn handle_env_command(action: EnvAction) -> Result<()> {
+    let mut config = ConfigManager::new()?;
+    match action {
+        EnvAction::Set { key, value, secret } => {
+            set_var(&mut config, &key, &value, secret)?;
+            let kind = if secret { " (encrypted)" } else { "" };
+            println!("✅ Set {}{} in .cg", key.cyan(), kind);
+        }
+        EnvAction::Unset { key } => {
+            if config.unset(&format!("{}.{}", SECTION, key), true)? {
+                println!("🗑️  Removed {} from .cg", key.cyan());
+            } else {
+                println!("⚠️  {} is not set in .cg", key);
+            }
+        }
+        EnvAction::List { reveal } => {
+            let vars = stored_vars(&config);
+            if vars.is_empty() {
+                println!("No project environment set. Use 'cm env set KEY VALUE'");
+                return Ok(());
+            }
+            let cipher = if reveal && vars.iter().any(|var| var.secret) { Some(cipher()?) } else { None };
+            println!("🌱 Environment injected into wrapped commands:");
+            for var in vars {
+                let value = match (&cipher, var.secret) {
+                    (Some(cipher), true) => decrypt(cipher, &var.value)
+                        .unwrap_or_else(|e| format!("<{}>", e)),
+                    (None, true) => "********".to_string(),
+                    _ => var.value,
+                };
+                let shadowed = if std::env::var_os(&var.name).is_some() {
+                    " shadowed by shell".yellow().to_string()
+                } else {
+                    String::new()
+                };
+                let source = if var.secret { format!("({}, secret)", var.source) } else { format!("({})", var.source) };
+                println!("  {}={} {}{}", var.name.cyan(), value, source.dimmed(), shadowed);
+            }
+        }
+        EnvAction::Import { path, secret } => {
+            let mut count = 0;
+            for item in dotenvy::from_path_iter(&path).with_context(|| format!("Failed to read {}", path.display()))? {
+                let (name, value) = item?;
+                set_var(&mut config, &name, &value, secret)?;
+                count += 1;
+            }
+            println!("✅ Imported {} variables from {} into .cg", count, path.display());
+        }
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_variable_names() {
+        assert!(validate_name("DATABASE_URL").is_ok());
+        assert!(validate_name("_PRIVATE").is_ok());
+        assert!(validate_name("1BAD").is_err());
+        assert!(validate_name("A-B").is_err());
+    }
+}
@@ -0,0 +1,618 @@
+use crate::captain::config::ConfigManager;
+use crate::git::git;
+use crate::history;
+use crate::display::format_size;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::*;
+use std::cmp::Reverse;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use walkdir::WalkDir;
+#[derive(Debug, Clone)]
+pub struct PublishPolicy {
+    pub allow_dirty: bool,
+    pub require_tests: bool,
+    pub require_version_bump: bool,
+    pub require_changelog: bool,
+    pub confirm: bool,
+}
+#[derive(Debug, Clone)]
+pub struct PublishCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub blocking: bool,
+    pub detail: String,
+}
+impl PublishPolicy {
+    pub fn from_config(config: &ConfigManager) -> Self {
+        PublishPolicy {
+            allow_dirty: config.get_bool("publish.allow_dirty", false),
+            require_tests: config.get_bool("publish.require_tests", true),
+            require_version_bump: config.get_bool("publish.require_version_bump", true),
+            require_changelog: config.get_bool("publish.require_changelog", true),
+            confirm: config.get_bool("publish.confirm", true),
+        }
+    }
+}
+pub fn package_identity() -> Option<(String, String)> {
+    let manifest: toml::Value = fs::read_to_string("Cargo.toml").ok()?.parse().ok()?;
+    let package = manifest.get("package")?;
+    let name = package.get("name")?.as_str()?.to_string();
+    let version = package
+        .get("version")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .or_else(|| {
+            git(&["rev-parse", "--show-toplevel"])
+                .and_then(|root| {
+                    fs::read_to_string(Path::new(root.trim()).join("Cargo.toml")).ok()
+                })
+                .and_then(|content| content.parse::<toml::Value>().ok())
+                .and_then(|root| {
+                    root.get("workspace")?
+                        .get("package")?
+                        .get("version")?
+                        .as_str()
+                        .map(str::to_string)
+                })
+        })?;
+    Some((name, version))
+}
+fn latest_source_change() -> Option<DateTime<Utc>> {
+    WalkDir::new(".")
+        .into_iter()
+        .filter_entry(|e| e.file_name() != "target" && e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.path().extension().is_some_and(|ext| ext == "rs")
+                || e.file_name() == "Cargo.toml"
+        })
+        .filter_map(|e| e.metadata().ok()?.modified().ok())
+        .max()
+        .map(DateTime::<Utc>::from)
+}
+fn check_clean_tree(policy: &PublishPolicy, explicit_allow: bool) -> PublishCheck {
+    let Some(status) = git(&["status", "--porcelain"]) else {
+        return PublishCheck {
+            name: "Clean working tree",
+            passed: true,
+            blocking: false,
+            detail: "not a git repository".to_string(),
+        };
+    };
+    let changed = status.lines().filter(|l| !l.trim().is_empty()).count();
+    let override_reason = if explicit_allow {
+        Some("--allow-dirty passed explicitly")
+    } else if policy.allow_dirty {
+        Some("publish.allow_dirty = true")
+    } else {
+        None
+    };
+    PublishCheck {
+        name: "Clean working tree",
+        passed: changed == 0,
+        blocking: changed > 0 && override_reason.is_none(),
+        detail: match (changed, override_reason) {
+            (0, _) => "no uncommitted changes".to_string(),
+            (n, Some(reason)) => format!("{} uncommitted changes ({})", n, reason),
+            (n, None) => format!("{} uncommitted changes; commit or stash them first", n),
+        },
+    }
+}
+fn check_tests_ran() -> PublishCheck {
+    let last_test = history::last_run_of("test");
+    let latest_change = latest_source_change();
+    let (passed, detail) = match (&last_test, latest_change) {
+        (None, _) => (false, "no recorded test run; run 'cm test' or 'cargo test'".to_string()),
+        (Some(entry), _) if entry.error_count > 0 => {
+            (false, format!("last test run had {} errors", entry.error_count))
+        }
+        (Some(entry), Some(changed)) if entry.timestamp < changed => {
+            (
+                false,
+                format!(
+                    "sources changed after the last test run ({})", entry.timestamp
+                    .format("%Y-%m-%d %H:%M")
+                ),
+            )
+        }
+        (Some(entry), _) => {
+            (true, format!("passed at {}", entry.timestamp.format("%Y-%m-%d %H:%M")))
+        }
+    };
+    PublishCheck {
+        name: "Tests ran",
+        passed,
+        blocking: !passed,
+        detail,
+    }
+}
+fn check_changelog(version: &str) -> PublishCheck {
+    let changelog = fs::read_dir(".")
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .find(|p| {
+            let file = p
+                .file_name()
+                .map(|n| n.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            p.is_file() && (file.starts_with("changelog") || file.starts_with("changes")
+                || file.starts_with("change.log") || file.starts_with("history"))
+        });
+    let (passed, detail) = match &changelog {
+        None => (false, "no CHANGELOG file found".to_string()),
+        Some(path) => {
+            let mentions = fs::read_to_string(path)
+                .map(|content| content.contains(version))
+                .unwrap_or(false);
+            let file = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if mentions {
+                (true, format!("{} mentions {}", file, version))
+            } else {
+                (false, format!("{} has no entry for {}", file, version))
+            }
+        }
+    };
+    PublishCheck {
+        name: "Changelog updated",
+        passed,
+        blocking: !passed,
+        detail,
+    }
+}
+pub fn run_checklist(policy: &PublishPolicy, args: &[&str]) -> Vec<PublishCheck> {
+    let explicit_allow = args.contains(&"--allow-dirty");
+    let mut checks = vec![check_clean_tree(policy, explicit_allow)];
+    if policy.require_tests {
+        checks.push(check_tests_ran());
+    }
+    if let Some((name, version)) = package_identity() {
+        if policy.require_version_bump {
+            checks.extend(version_checks(Path::new("."), &name, &version, published_versions(&name)));
+        }
+        if policy.require_changelog {
+            checks.push(check_changelog(&version));
+        }
+    }
+    checks
+}
+#[derive(Debug, PartialEq, Eq)]
+enum Confirmation {
+    Skip,
+    Ask,
+    /// Nobody to ask: a script or pipe that isn't a CI run
+    Refuse,
+}
+fn confirmation(policy: &PublishPolicy, dry_run: bool, assume_yes: bool, interactive: bool, ci: bool) -> Confirmation {
+    if !policy.confirm || dry_run || assume_yes {
+        Confirmation::Skip
+    } else if interactive || ci {
+        Confirmation::Ask
+    } else {
+        Confirmation::Refuse
+    }
+}
+pub fn prepare_publish(args: &[&str]) -> Result<Option<Vec<String>>> {
+    let config = ConfigManager::new()?;
+    let policy = PublishPolicy::from_config(&config);
+    let dry_run = args.contains(&"--dry-run");
+    let checks = run_checklist(&policy, args);
+    println!("📋 {}", "Pre-publish checklist".bold().blue());
+    for check in &checks {
+        let marker = if check.passed {
+            "✅"
+        } else if check.blocking {
+            "❌"
+        } else {
+            "⚠️ "
+        };
+        println!("  {} {} - {}", marker, check.name, check.detail.dimmed());
+    }
+    let blocking: Vec<&PublishCheck> = checks.iter().filter(|c| c.blocking).collect();
+    if !blocking.is_empty() && !dry_run {
+        println!(
+            "\n🛑 {} - {} checklist item(s) failed", "Publish blocked".red().bold(),
+            blocking.len()
+        );
+        println!(
+            "💡 Relax the policy with 'cm config set publish.<check> false --local' if this is intended"
+        );
+        return Ok(None);
+    }
+    // `--yes` is cm's, cargo publish doesn't know it
+    let assume_yes = args.contains(&"--yes");
+    let mut final_args: Vec<String> = args.iter().filter(|a| **a != "--yes").map(|s| s.to_string()).collect();
+    let dirty = checks.iter().any(|c| c.name == "Clean working tree" && !c.passed);
+    if dirty && policy.allow_dirty && !final_args.iter().any(|a| a == "--allow-dirty") {
+        final_args.insert(2, "--allow-dirty".to_string());
+    }
+    match confirmation(&policy, dry_run, assume_yes, atty::is(atty::Stream::Stdin), crate::ci::is_ci()) {
+        Confirmation::Skip => {}
+        Confirmation::Refuse => {
+            println!("\n🛑 {} - publish.confirm is set and there is no terminal to ask on", "Publish refused".red().bold());
+            println!("💡 Pass --yes, or run 'cm config set publish.confirm false --local' to publish unattended");
+            return Ok(None);
+        }
+        Confirmation::Ask => {
+            let target = package_identity()
+                .map(|(name, version)| format!("{} v{}", name, version))
+                .unwrap_or_else(|| "this crate".to_string());
+            println!();
+            if !crate::ci::confirm(&format!("🚀 Publish {} to the registry?", target.cyan()))? {
+                println!("Publish cancelled.");
+                if crate::ci::is_ci() {
+                    println!(
+                        "💡 Pass --yes, or run 'cm config set publish.confirm false --local' to publish from CI"
+                    );
+                }
+                return Ok(None);
+            }
+        }
+    }
+    Ok(Some(final_args))
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackagedFile {
+    pub path: String,
+    pub size: u64,
+}
+fn packaged_files() -> Result<Vec<PackagedFile>> {
+    let output = Command::new("cargo").args(["package", "--list", "--allow-dirty"]).output()?;
+    if !output.status.success() {
+        anyhow::bail!("cargo package --list failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|path| PackagedFile {
+            size: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            path: path.to_string(),
+        })
+        .collect())
+}
+/// Bytes per top-level directory (files in the crate root are grouped as `.`), largest first
+pub fn size_breakdown(files: &[PackagedFile]) -> Vec<(String, u64)> {
+    let mut groups: std::collections::BTreeMap<String, u64> = Default::default();
+    for file in files {
+        let group = match file.path.split_once('/') {
+            Some((dir, _)) => format!("{}/", dir),
+            None => ".".to_string(),
+        };
+        *groups.entry(group).or_default() += file.size;
+    }
+    let mut groups: Vec<(String, u64)> = groups.into_iter().collect();
+    groups.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    groups
+}
+fn show_contents(files: &[PackagedFile]) {
+    let total: u64 = files.iter().map(|f| f.size).sum();
+    println!("📦 {} ({} files, {})", "Package contents".bold().blue(), files.len(), format_size(total));
+    for (group, size) in size_breakdown(files) {
+        println!("  {:<16} {:>10}", group, format_size(size));
+    }
+    let mut largest: Vec<&PackagedFile> = files.iter().collect();
+    largest.sort_by_key(|file| Reverse(file.size));
+    println!("  {}", "Largest files:".dimmed());
+    for file in largest.iter().take(5) {
+        println!("    {:>10}  {}", format_size(file.size), file.path);
+    }
+    println!();
+}
+fn package_table() -> Option<toml::Value> {
+    let manifest: toml::Value = fs::read_to_string("Cargo.toml").ok()?.parse().ok()?;
+    manifest.get("package").cloned()
+}
+fn check_metadata(package: &toml::Value) -> Vec<PublishCheck> {
+    let text = |key: &str| package.get(key).and_then(|v| v.as_str()).filter(|v| !v.trim().is_empty());
+    let list = |key: &str| package.get(key).and_then(|v| v.as_array()).map_or(0, |a| a.len());
+    let readme = match package.get("readme") {
+        Some(toml::Value::Boolean(false)) => None,
+        Some(toml::Value::String(path)) => Path::new(path).exists().then(|| path.clone()),
+        _ => ["README.md", "README", "README.txt"]
+            .iter()
+            .find(|file| Path::new(file).exists())
+            .map(|file| file.to_string()),
+    };
+    let license = text("license")
+        .map(str::to_string)
+        .or_else(|| text("license-file").filter(|path| Path::new(path).exists()).map(str::to_string));
+    let mut checks = vec![
+        PublishCheck {
+            name: "Description",
+            passed: text("description").is_some(),
+            blocking: text("description").is_none(),
+            detail: if text("description").is_some() {
+                "set".to_string()
+            } else {
+                "crates.io rejects crates without a description".to_string()
+            },
+        },
+        PublishCheck {
+            name: "License",
+            passed: license.is_some(),
+            blocking: license.is_none(),
+            detail: license.unwrap_or_else(|| "set license or license-file".to_string()),
+        },
+        PublishCheck {
+            name: "README",
+            passed: readme.is_some(),
+            blocking: false,
+            detail: readme.unwrap_or_else(|| "no README; the crates.io page will be empty".to_string()),
+        },
+    ];
+    for (name, key) in [("Keywords", "keywords"), ("Categories", "categories")] {
+        let count = list(key);
+        checks.push(PublishCheck {
+            name,
+            passed: (1..=5).contains(&count),
+            blocking: count > 5,
+            detail: match count {
+                0 => format!("no {}; the crate is harder to find", key),
+                n if n > 5 => format!("{} {}, crates.io allows at most 5", n, key),
+                n => format!("{} {}", n, key),
+            },
+        });
+    }
+    checks
+}
+/// Every version ever uploaded, yanked or not, since none of them can be published again
+fn published_versions(name: &str) -> Result<Vec<String>> {
+    let url = format!("https://index.crates.io/{}", crate::outdated::index_path(name));
+    let response = crate::net::send(crate::outdated::client()?.get(&url))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(Vec::new());
+    }
+    if !response.status().is_success() {
+        anyhow::bail!("crates.io index returned HTTP {}", response.status());
+    }
+    Ok(response
+        .text()?
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter_map(|entry| entry["vers"].as_str().map(str::to_string))
+        .collect())
+}
+fn check_not_published(name: &str, version: &str, published: Result<Vec<String>>) -> PublishCheck {
+    let (passed, blocking, detail) = match published {
+        Ok(versions) if versions.iter().any(|v| v == version) => {
+            (false, true, format!("{} {} is already on crates.io", name, version))
+        }
+        Ok(versions) if versions.is_empty() => (true, false, format!("{} is a new crate", name)),
+        Ok(_) => (true, false, format!("{} is not on crates.io yet", version)),
+        Err(e) => (false, false, format!("could not reach crates.io: {}", e)),
+    };
+    PublishCheck { name: "Version unpublished", passed, blocking, detail }
+}
+/// Tags that name a release of this crate, e.g. `1.2.3`, `v1.2.3` or `name-v1.2.3`
+fn release_tag_version<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let version = tag.strip_prefix(&format!("{}-", name)).unwrap_or(tag);
+    let version = version.strip_prefix('v').unwrap_or(version);
+    semver::Version::parse(version).is_ok().then_some(version)
+}
+/// A tag on HEAD must name `version`; without one, `version` must be newer than every release tag
+pub fn check_tag(name: &str, version: &str, head_tags: &[String], all_tags: &[String]) -> PublishCheck {
+    let head: Vec<&String> = head_tags.iter().filter(|t| release_tag_version(t, name).is_some()).collect();
+    let newest = all_tags
+        .iter()
+        .filter_map(|t| Some((semver::Version::parse(release_tag_version(t, name)?).ok()?, t)))
+        .max();
+    let current = semver::Version::parse(version).ok();
+    let (passed, blocking, detail) = if let Some(tag) = head.iter().find(|t| release_tag_version(t, name) == Some(version)) {
+        (true, false, format!("HEAD is tagged {}", tag))
+    } else if let Some(tag) = head.first() {
+        (false, true, format!("HEAD is tagged {} but Cargo.toml says {}", tag, version))
+    } else if let Some(tag) = all_tags.iter().find(|t| release_tag_version(t, name) == Some(version)) {
+        (false, true, format!("{} points at another commit than HEAD; bump the version", tag))
+    } else if let Some((_, tag)) = newest.filter(|(newest, _)| current.as_ref().is_none_or(|c| c <= newest)) {
+        (false, true, format!("{} is not newer than the latest release {}", version, tag))
+    } else {
+        (false, false, format!("HEAD is not tagged; tag it v{} before publishing", version))
+    };
+    PublishCheck { name: "Git tag", passed, blocking, detail }
+}
+fn tag_lines(repo: &Path, args: &[&str]) -> Vec<String> {
+    let repo = repo.to_string_lossy();
+    let args: Vec<&str> = ["-C", repo.as_ref()].into_iter().chain(args.iter().copied()).collect();
+    git(&args).unwrap_or_default().lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect()
+}
+/// The version checks `cm publish-check` and the publish wrapper share, so they agree on a release
+fn version_checks(repo: &Path, name: &str, version: &str, published: Result<Vec<String>>) -> Vec<PublishCheck> {
+    let head = tag_lines(repo, &["tag", "--points-at", "HEAD"]);
+    let all = tag_lines(repo, &["tag", "--list"]);
+    vec![check_not_published(name, version, published), check_tag(name, version, &head, &all)]
+}
+/// Builds docs the way docs.rs does: `--cfg docsrs`, `DOCS_RS=1` and the features from
+/// `[package.metadata.docs.rs]`, on nightly when it is installed
+fn check_docs(package: &toml::Value) -> PublishCheck {
+    let docs_rs = package
+        .get("metadata")
+        .and_then(|m| m.get("docs").and_then(|d| d.get("rs")).or_else(|| m.get("docs.rs")));
+    let flag = |key: &str| docs_rs.and_then(|d| d.get(key)).and_then(|v| v.as_bool()).unwrap_or(false);
+    let strings = |key: &str| -> Vec<String> {
+        docs_rs
+            .and_then(|d| d.get(key))
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    };
+    let nightly = Command::new("cargo").args(["+nightly", "--version"]).output().is_ok_and(|o| o.status.success());
+    let mut command = Command::new("cargo");
+    if nightly {
+        command.arg("+nightly");
+    }
+    command.args(["doc", "--no-deps", "--lib"]);
+    if flag("all-features") {
+        command.arg("--all-features");
+    }
+    if flag("no-default-features") {
+        command.arg("--no-default-features");
+    }
+    let features = strings("features");
+    if !features.is_empty() {
+        command.args(["--features", &features.join(",")]);
+    }
+    let mut rustdoc_flags = vec!["--cfg".to_string(), "docsrs".to_string()];
+    rustdoc_flags.extend(strings("rustdoc-args"));
+    let toolchain = if nightly { "nightly" } else { "stable, nightly not installed" };
+    match command.env("RUSTDOCFLAGS", rustdoc_flags.join(" ")).env("DOCS_RS", "1").output() {
+        Ok(output) if output.status.success() => PublishCheck {
+            name: "Docs build",
+            passed: true,
+            blocking: false,
+            detail: format!("built with docs.rs flags ({})", toolchain),
+        },
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let first_error = stderr.lines().find(|l| l.starts_with("error")).unwrap_or("cargo doc failed");
+            PublishCheck {
+                name: "Docs build",
+                passed: false,
+                blocking: true,
+                detail: format!("{} ({})", first_error.trim(), toolchain),
+            }
+        }
+        Err(e) => PublishCheck { name: "Docs build", passed: false, blocking: true, detail: e.to_string() },
+    }
+}
+/// The actual `cargo package` run; it compiles the packaged sources unless `no_verify`
+fn check_package(name: &str, version: &str, no_verify: bool) -> PublishCheck {
+    let mut args = vec!["package", "--allow-dirty"];
+    if no_verify {
+        args.push("--no-verify");
+    }
+    let output = match Command::new("cargo").args(&args).output() {
+        Ok(output) => output,
+        Err(e) => return PublishCheck { name: "cargo package", passed: false, blocking: true, detail: e.to_string() },
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let first_error = stderr.lines().find(|l| l.starts_with("error")).unwrap_or("cargo package failed");
+        return PublishCheck { name: "cargo package", passed: false, blocking: true, detail: first_error.trim().to_string() };
+    }
+    let crate_file = cargo_metadata::MetadataCommand::new()
+        .no_deps()
+        .exec()
+        .ok()
+        .map(|m| m.target_directory.into_std_path_buf().join("package").join(format!("{}-{}.crate", name, version)));
+    let size = crate_file.and_then(|path| fs::metadata(path).ok()).map(|m| m.len());
+    // crates.io rejects uploads above 10 MB
+    let too_large = size.is_some_and(|s| s > 10 * 1024 * 1024);
+    PublishCheck {
+        name: "cargo package",
+        passed: !too_large,
+        blocking: too_large,
+        detail: match size {
+            Some(size) if too_large => format!("{} compressed, above the 10 MB crates.io limit", format_size(size)),
+            Some(size) => format!("{} compressed{}", format_size(size), if no_verify { ", not verified" } else { "" }),
+            None => "packaged".to_string(),
+        },
+    }
+}
+/// `cm publish-check`: everything `cargo publish` would need, without publishing
+pub fn handle_publish_check(no_verify: bool) -> Result<()> {
+    let config = ConfigManager::new()?;
+    let policy = PublishPolicy::from_config(&config);
+    let package = package_table().ok_or_else(|| anyhow::anyhow!("No [package] in ./Cargo.toml"))?;
+    let (name, version) = package_identity().ok_or_else(|| anyhow::anyhow!("Cargo.toml has no package name or version"))?;
+    println!("🔎 Checking {} v{} for publishing\n", name.cyan(), version);
+    show_contents(&packaged_files()?);
+    let mut checks = vec![check_clean_tree(&policy, false)];
+    if policy.require_tests {
+        checks.push(check_tests_ran());
+    }
+    if policy.require_changelog {
+        checks.push(check_changelog(&version));
+    }
+    checks.extend(check_metadata(&package));
+    checks.extend(version_checks(Path::new("."), &name, &version, published_versions(&name)));
+    println!("📚 Building docs...");
+    checks.push(check_docs(&package));
+    println!("📦 Running cargo package...\n");
+    checks.push(check_package(&name, &version, no_verify));
+    println!("📋 {}", "Publish check".bold().blue());
+    for check in &checks {
+        let marker = if check.passed {
+            "✅"
+        } else if check.blocking {
+            "❌"
+        } else {
+            "⚠️ "
+        };
+        println!("  {} {} - {}", marker, check.name, check.detail.dimmed());
+    }
+    let blocking = checks.iter().filter(|c| c.blocking).count();
+    if blocking == 0 {
+        println!("\n✅ {} - ready to publish", "PASS".green().bold());
+        return Ok(());
+    }
+    println!("\n❌ {} - {} blocking issue(s)", "FAIL".red().bold(), blocking);
+    std::process::exit(1);
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_size_breakdown_and_tag_check() {
+        let files = vec![
+            PackagedFile { path: "Cargo.toml".to_string(), size: 100 },
+            PackagedFile { path: "src/lib.rs".to_string(), size: 4000 },
+            PackagedFile { path: "src/a/b.rs".to_string(), size: 1000 },
+        ];
+        assert_eq!(size_breakdown(&files), vec![("src/".to_string(), 5000), (".".to_string(), 100)]);
+        let tags = |t: &[&str]| t.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(check_tag("demo", "1.2.3", &tags(&["v1.2.3"]), &[]).passed);
+        assert!(check_tag("demo", "1.2.3", &tags(&["demo-v1.2.3"]), &[]).passed);
+        assert!(check_tag("demo", "1.2.3", &tags(&["v1.2.2"]), &[]).blocking);
+        assert!(check_tag("demo", "1.2.3", &[], &tags(&["v1.2.3"])).blocking);
+        assert!(check_tag("demo", "1.2.3", &[], &tags(&["v1.2.4"])).blocking);
+        let untagged = check_tag("demo", "1.2.3", &tags(&["nightly"]), &tags(&["nightly", "v1.2.2", "other-v9.0.0"]));
+        assert!(!untagged.passed && !untagged.blocking);
+        let mut policy = PublishPolicy {
+            allow_dirty: false,
+            require_tests: false,
+            require_version_bump: false,
+            require_changelog: false,
+            confirm: true,
+        };
+        assert_eq!(confirmation(&policy, false, false, false, false), Confirmation::Refuse);
+        assert_eq!(confirmation(&policy, false, false, true, false), Confirmation::Ask);
+        assert_eq!(confirmation(&policy, false, false, false, true), Confirmation::Ask);
+        assert_eq!(confirmation(&policy, false, true, false, false), Confirmation::Skip);
+        assert_eq!(confirmation(&policy, true, false, false, false), Confirmation::Skip);
+        policy.confirm = false;
+        assert_eq!(confirmation(&policy, false, false, false, false), Confirmation::Skip);
+    }
+    #[test]
+    fn test_tagged_head_passes_publish_check_and_the_wrapper() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path();
+        let run = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=cm", "-c", "user.email=cm@example.com"])
+                .args(args)
+                .current_dir(repo)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        run(&["init", "-q"]);
+        run(&["commit", "-q", "--allow-empty", "-m", "1.2.2"]);
+        run(&["tag", "v1.2.2"]);
+        run(&["commit", "-q", "--allow-empty", "-m", "1.2.3"]);
+        let published = || Ok(vec!["1.2.2".to_string()]);
+        let untagged = version_checks(repo, "demo", "1.2.3", published());
+        assert!(untagged.iter().all(|c| !c.blocking), "{:?}", untagged);
+        run(&["tag", "v1.2.3"]);
+        // Both `cm publish-check` and the `cargo publish` wrapper gate on these
+        let tagged = version_checks(repo, "demo", "1.2.3", published());
+        assert!(tagged.iter().all(|c| c.passed && !c.blocking), "{:?}", tagged);
+        let stale = version_checks(repo, "demo", "1.2.2", published());
+        assert!(stale.iter().all(|c| c.blocking), "{:?}", stale);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::history::{self, HistoryEntry};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use std::io::Write;
+const PICKER_SIZE: usize = 9;
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub command: String,
+    pub runs: usize,
+    pub last_run: DateTime<Utc>,
+    pub last_failed: bool,
+    pub score: f64,
+}
+fn frecency_weight(age: chrono::Duration) -> f64 {
+    if age < chrono::Duration::hours(1) {
+        4.0
+    } else if age < chrono::Duration::days(1) {
+        2.0
+    } else if age < chrono::Duration::weeks(1) {
+        1.0
+    } else {
+        0.5
+    }
+}
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|c| chars.any(|h| h == c))
+}
+pub fn match_quality(query: &str, command: &str) -> Option<f64> {
+    let command = command.to_lowercase();
+    let mut quality = 1.0;
+    for term in query.to_lowercase().split_whitespace() {
+        if command.split_whitespace().any(|word| word.starts_with(term)) {
+            quality *= 3.0;
+        } else if command.contains(term) {
+            quality *= 2.0;
+        } else if !is_subsequence(term, &command) {
+            return None;
+        }
+    }
+    Some(quality)
+}
+pub fn rank(history: &[HistoryEntry], query: &str, now: DateTime<Utc>) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = Vec::new();
+    for entry in history {
+        let command = entry.command.trim();
+        if command.is_empty() {
+            continue;
+        }
+        let Some(quality) = match_quality(query, command) else {
+            continue;
+        };
+        let weight = frecency_weight(now - entry.timestamp) * quality;
+        match candidates.iter_mut().find(|c| c.command == command) {
+            Some(candidate) => {
+                candidate.runs += 1;
+                candidate.score += weight;
+                if entry.timestamp >= candidate.last_run {
+                    candidate.last_run = entry.timestamp;
+                    candidate.last_failed = entry.error_count > 0;
+                }
+            }
+            None => {
+                candidates
+                    .push(Candidate {
+                        command: command.to_string(),
+                        runs: 1,
+                        last_run: entry.timestamp,
+                        last_failed: entry.error_count > 0,
+                        score: weight,
+                    })
+            }
+        }
+    }
+    candidates
+        .sort_by(|a, b| {
+            b.score.total_cmp(&a.score).then_with(|| b.last_run.cmp(&a.last_run))
+        });
+    candidates
+}
+pub fn command_args(command: &str) -> Vec<String> {
+    let args = shell_words::split(command)
+        .unwrap_or_else(|_| command.split_whitespace().map(String::from).collect());
+    match args.first().map(String::as_str) {
+        Some("cargo") => args[1..].to_vec(),
+        _ => args,
+    }
+}
+pub fn last_command() -> Result<Vec<String>> {
+    let history = history::load_visible_history();
+    match history.iter().rev().find(|entry| !entry.command.trim().is_empty()) {
+        Some(entry) => {
+            println!("🔁 {}", format!("cargo {}", entry.command).cyan());
+            Ok(command_args(&entry.command))
+        }
+        None => bail!("No cargo commands in history yet - run a build first"),
+    }
+}
+fn print_candidate(index: usize, candidate: &Candidate) {
+    let status = if candidate.last_failed { "❌" } else { "✅" };
+    println!(
+        "  {} {} cargo {} {}", format!("{}.", index + 1).bold(), status, candidate
+        .command.cyan(), format!("({} run(s), last {})", candidate.runs, candidate
+        .last_run.format("%Y-%m-%d %H:%M")).dimmed()
+    );
+}
+fn pick(candidates: &[Candidate]) -> Result<Option<&Candidate>> {
+    let shown = &candidates[..candidates.len().min(PICKER_SIZE)];
+    println!("🔎 {} matching commands:", candidates.len());
+    for (index, candidate) in shown.iter().enumerate() {
+        print_candidate(index, candidate);
+    }
+    if crate::ci::is_ci() || !atty::is(atty::Stream::Stdin) {
+        bail!("Several commands match; refine the query to a single match");
+    }
+    print!("Run which? [1-{}, Enter = 1, q = cancel]: ", shown.len());
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(shown.first());
+    }
+    if input.eq_ignore_ascii_case("q") {
+        return Ok(None);
+    }
+    match input.parse::<usize>() {
+        Ok(n) if (1..=shown.len()).contains(&n) => Ok(Some(&shown[n - 1])),
+        _ => bail!("'{}' is not one of the listed commands", input),
+    }
+}
+pub fn recall(query: &str) -> Result<Option<Vec<String>>> {
+    let candidates = rank(&history::load_visible_history(), query, Utc::now());
+    let selected = match candidates.len() {
+        0 => bail!("Nothing in history matches '{}'", query),
+        1 => Some(&candidates[0]),
+        _ => pick(&candidates)?,
+    };
+    Ok(
+        selected
+            .map(|candidate| {
+                println!("🔁 {}", format!("cargo {}", candidate.command).cyan());
+                command_args(&candidate.command)
+            }),
+    )
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn entry(command: &str, hours_ago: i64, now: DateTime<Utc>) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: now - chrono::Duration::hours(hours_ago),
+            command: command.to_string(),
+            error_count: 0,
+            warning_count: 0,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+    #[test]
+    fn ranks_fuzzy_matches_by_frecency() {
+        let now = Utc::now();
+        let history = vec![
+            entry("test --release", 200, now), entry("test --release", 190, now),
+            entry("test --release", 180, now), entry("test -p core", 0, now),
+            entry("build --release", 0, now),
+        ];
+        let ranked = rank(&history, "tst", now);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].command, "test -p core");
+        assert_eq!(ranked[1].runs, 3);
+        let ranked = rank(&history, "rel", now);
+        assert_eq!(ranked[0].command, "build --release");
+        assert!(match_quality("bld rls", "build --release").is_some());
+        assert!(match_quality("doc", "build --release").is_none());
+        assert_eq!(command_args("cargo test -- --nocapture"), ["test", "--", "--nocapture"]);
+    }
+}
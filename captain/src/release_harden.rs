@@ -0,0 +1,273 @@
+use crate::captain::config::ConfigManager;
+use crate::scat::{FileFilter, FilterArgs, ObfuscationConfig, ObfuscationMapping, StringEncryptionConfig};
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+/// Strip, obfuscate and build a copy of the project; the source tree is never touched
+#[derive(Args, Debug)]
+pub struct ReleaseHardenArgs {
+    /// Project (or workspace) root to harden
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    #[arg(short, long, default_value = "target/hardened")]
+    pub output: PathBuf,
+    /// Mapping bundle (default: <output>.map.json, outside the hardened tree)
+    #[arg(long)]
+    pub map: Option<PathBuf>,
+    /// Named profile from `[harden.<name>]` in .cg
+    #[arg(long)]
+    pub profile: Option<String>,
+    /// Replace an existing output directory
+    #[arg(long)]
+    pub force: bool,
+    /// Skip the verification build
+    #[arg(long)]
+    pub no_verify: bool,
+}
+/// `[harden]` in .cg, overridden key by key by `[harden.<profile>]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HardenProfile {
+    pub name: String,
+    pub strip_docs: bool,
+    pub preserve_pub: bool,
+    pub min_len: usize,
+    pub algorithm: String,
+    pub seed: Option<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    /// Cargo arguments for the verification build; empty disables it
+    pub verify: Vec<String>,
+}
+impl HardenProfile {
+    pub fn from_config(config: &ConfigManager, profile: Option<&str>) -> Self {
+        let get = |key: &str| {
+            profile
+                .and_then(|name| config.get(&format!("harden.{}.{}", name, key)))
+                .or_else(|| config.get(&format!("harden.{}", key)))
+        };
+        let flag = |key: &str, default: bool| {
+            get(key)
+                .and_then(|value| match value.to_lowercase().as_str() {
+                    "true" | "1" | "yes" | "on" => Some(true),
+                    "false" | "0" | "no" | "off" => Some(false),
+                    _ => None,
+                })
+                .unwrap_or(default)
+        };
+        let list = |key: &str| get(key).map(|value| ConfigManager::parse_list(&value)).unwrap_or_default();
+        Self {
+            name: profile.unwrap_or("default").to_string(),
+            strip_docs: flag("strip_docs", true),
+            preserve_pub: flag("preserve_pub", true),
+            min_len: get("min_len").and_then(|v| v.trim().parse().ok()).unwrap_or(3),
+            algorithm: get("algorithm").unwrap_or_else(|| "aes".to_string()),
+            seed: get("seed"),
+            include: list("include"),
+            exclude: list("exclude"),
+            verify: get("verify")
+                .map(|v| v.split_whitespace().map(String::from).collect())
+                .unwrap_or_else(|| vec!["build".to_string(), "--release".to_string()]),
+        }
+    }
+    fn obfuscation_config(&self) -> ObfuscationConfig {
+        ObfuscationConfig {
+            preserve_pub: self.preserve_pub,
+            min_len: self.min_len,
+            control_flow: false,
+            string_encryption: StringEncryptionConfig {
+                algorithm: self.algorithm.clone(),
+                skip_format: true,
+                skip_errors: true,
+            },
+        }
+    }
+}
+/// One file per run: the scat mapping (so `scat unpack` and `scat verify` read it as is)
+/// plus what was stripped and whether the hardened tree built
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HardenMap {
+    pub source: String,
+    pub output: String,
+    pub profile: HardenProfile,
+    pub files: Vec<String>,
+    pub verified: Option<bool>,
+    #[serde(flatten)]
+    pub mapping: ObfuscationMapping,
+}
+/// Copies `source` into `output`, leaving out build output, VCS metadata and `output` itself
+fn copy_tree(source: &Path, output: &Path) -> Result<usize> {
+    let skip_output = output.canonicalize().ok();
+    let mut copied = 0;
+    let walker = WalkDir::new(source).into_iter().filter_entry(|entry| {
+        let name = entry.file_name().to_string_lossy();
+        if entry.depth() > 0 && entry.file_type().is_dir() && (name == "target" || name == ".git") {
+            return false;
+        }
+        skip_output.as_deref().is_none_or(|out| entry.path().canonicalize().ok().as_deref() != Some(out))
+    });
+    for entry in walker {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(source)?;
+        let destination = output.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&destination)?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), &destination)
+                .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+/// Comments and (optionally) docs go; `// scat:skip` markers and every attribute stay
+fn strip_file(path: &Path, strip_docs: bool) -> Result<()> {
+    let args = crate::strip::StripArgs {
+        input: path.to_path_buf(),
+        output: None,
+        target: None,
+        remove_blanks: true,
+        recursive: false,
+        force: false,
+        no_backup: true,
+        src: false,
+        max_depth: 10,
+        aggressive: false,
+        minify: false,
+        tease: false,
+        strip_attrs: false,
+        strip_docs,
+        inline_uses: false,
+    };
+    let content = fs::read_to_string(path)?;
+    let stripped = crate::strip::strip_rust(&crate::scat::mark_skip_comments(&content), &args)
+        .with_context(|| format!("Failed to strip {}", path.display()))?;
+    fs::write(path, crate::scat::unmark_skip_attrs(&stripped))?;
+    Ok(())
+}
+fn verify_build(output: &Path, verify: &[String]) -> Result<bool> {
+    let mut command = Command::new("cargo");
+    command.args(verify).current_dir(output);
+    crate::net::NetworkSettings::load().apply_env(&mut command);
+    Ok(command.status().context("Failed to run cargo")?.success())
+}
+fn prepare_output(output: &Path, force: bool) -> Result<()> {
+    if output.exists() && fs::read_dir(output)?.next().is_some() {
+        if !force {
+            bail!("{} is not empty - pass --force to replace it", output.display());
+        }
+        fs::remove_dir_all(output)?;
+    }
+    Ok(())
+}
+/// Strip, obfuscate and (unless disabled) build `source` into `output`
+pub fn harden(source: &Path, output: &Path, profile: &HardenProfile, verify: bool) -> Result<HardenMap> {
+    if !source.join("Cargo.toml").exists() {
+        bail!("No Cargo.toml in {}", source.display());
+    }
+    fs::create_dir_all(output)?;
+    let copied = copy_tree(source, output)?;
+    println!("📁 Copied {} file(s) to {}", copied, output.display());
+    let filter = FileFilter::new(&FilterArgs { include: profile.include.clone(), exclude: profile.exclude.clone() })?;
+    let files = filter.rust_files(output);
+    crate::scat::perform_safety_checks(&files)?;
+    for file in &files {
+        strip_file(file, profile.strip_docs)?;
+    }
+    println!("✂️  Stripped comments{} from {} file(s)", if profile.strip_docs { " and docs" } else { "" }, files.len());
+    let (transformer, mapping) =
+        crate::scat::obfuscate_code(output, &files, profile.obfuscation_config(), profile.seed.as_deref(), false)?;
+    println!(
+        "🔀 Renamed {} identifier(s), encrypted {} string(s)",
+        transformer.context.mappings.len(),
+        transformer.string_mappings.len()
+    );
+    let verified = if verify && !profile.verify.is_empty() {
+        println!("🔨 Verifying: cargo {}", profile.verify.join(" "));
+        Some(verify_build(output, &profile.verify)?)
+    } else {
+        None
+    };
+    Ok(HardenMap {
+        source: source.display().to_string(),
+        output: output.display().to_string(),
+        profile: profile.clone(),
+        files: files
+            .iter()
+            .filter_map(|f| f.strip_prefix(output).ok())
+            .map(|f| f.display().to_string())
+            .collect(),
+        verified,
+        mapping,
+    })
+}
+/// `cm release-harden`
+pub fn handle_release_harden(args: ReleaseHardenArgs) -> Result<()> {
+    let config = ConfigManager::new()?;
+    let profile = HardenProfile::from_config(&config, args.profile.as_deref());
+    println!("🛡️  {} {} → {} (profile: {})", "Hardening".bold().blue(), args.path.display(), args.output.display(), profile.name);
+    prepare_output(&args.output, args.force)?;
+    let map = harden(&args.path, &args.output, &profile, !args.no_verify)?;
+    let map_path = args.map.clone().unwrap_or_else(|| args.output.with_extension("map.json"));
+    crate::storage::write_json_atomic(&map_path, &map)?;
+    println!("🗺️  Mapping bundle: {} - keep it out of the release", map_path.display());
+    match map.verified {
+        Some(true) => println!("✅ {} - hardened tree builds", "PASS".green().bold()),
+        Some(false) => {
+            println!("❌ {} - hardened tree does not build", "FAIL".red().bold());
+            std::process::exit(1);
+        }
+        None => println!("⚠️  Verification build skipped"),
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_harden_strips_obfuscates_and_keeps_one_map() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("demo");
+        fs::create_dir_all(source.join("src")).unwrap();
+        fs::create_dir_all(source.join("target/debug")).unwrap();
+        fs::write(source.join("target/debug/stale"), "x").unwrap();
+        fs::write(source.join("Cargo.toml"), "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+        let main = "/// Greets\nfn compute_total(value: u32) -> u32 {\n    // secret sauce\n    value * 2\n}\n\
+                    // scat:skip\nfn keep_this_name() {}\n\
+                    fn main() {\n    keep_this_name();\n    let total = compute_total(2);\n    let note = \"hidden literal\";\n    println!(\"{} {}\", total, note.len());\n}\n";
+        fs::write(source.join("src/main.rs"), main).unwrap();
+        let output = source.join("hardened");
+        let profile = HardenProfile {
+            name: "test".to_string(),
+            strip_docs: true,
+            preserve_pub: true,
+            min_len: 3,
+            algorithm: "builtin".to_string(),
+            seed: Some("fixed".to_string()),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            verify: Vec::new(),
+        };
+        let map = harden(&source, &output, &profile, true).unwrap();
+        assert!(!output.join("target").exists());
+        assert!(!output.join("hardened").exists());
+        let hardened = fs::read_to_string(output.join("src/main.rs")).unwrap();
+        assert!(!hardened.contains("secret sauce") && !hardened.contains("Greets"));
+        assert!(!hardened.contains("compute_total") && !hardened.contains("hidden literal"));
+        assert!(hardened.contains("// scat:skip") && hardened.contains("fn keep_this_name"));
+        assert!(!hardened.contains("total,") && hardened.contains(".len()"));
+        assert!(output.join("src/scat_runtime.rs").exists());
+        assert_eq!(map.files, vec!["src/main.rs".to_string()]);
+        assert_eq!(map.verified, None);
+        assert!(map.mapping.key.is_some());
+        let map_path = dir.path().join("demo.map.json");
+        fs::write(&map_path, serde_json::to_string(&map).unwrap()).unwrap();
+        let reloaded = ObfuscationMapping::load_from_file(&map_path).unwrap();
+        assert_eq!(reloaded.original_to_obfuscated.get("compute_total"), map.mapping.original_to_obfuscated.get("compute_total"));
+        assert!(fs::read_to_string(source.join("src/main.rs")).unwrap().contains("secret sauce"));
+    }
+}
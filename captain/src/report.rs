@@ -0,0 +1,301 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use colored::*;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+/// Warnings are capped so a noisy build doesn't bury the errors
+const MAX_WARNINGS: usize = 30;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+impl ReportFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("html") | Some("htm") => ReportFormat::Html,
+            _ => ReportFormat::Markdown,
+        }
+    }
+    fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub project: String,
+    pub generated: String,
+    pub command: Option<String>,
+    pub outcome: Option<String>,
+    pub environment: Vec<(String, String)>,
+    pub lockfile: Option<(String, String)>,
+    pub errors: String,
+    pub warnings: Vec<String>,
+    pub build_scripts: String,
+}
+fn command_line(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !text.is_empty()).then_some(text)
+}
+/// Replaces the home directory with `~` so reports don't leak user names
+pub fn scrub(text: &str) -> String {
+    match dirs::home_dir().map(|home| home.to_string_lossy().to_string()) {
+        Some(home) if home.len() > 1 => text.replace(&home, "~"),
+        _ => text.to_string(),
+    }
+}
+fn is_sensitive(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    ["TOKEN", "SECRET", "PASSWORD", "PASSPHRASE", "KEY", "AUTH"].iter().any(|word| name.contains(word))
+}
+fn environment() -> Vec<(String, String)> {
+    let mut env = vec![
+        ("cargo-mate".to_string(), env!("CARGO_PKG_VERSION").to_string()),
+        ("os".to_string(), format!("{} {}", std::env::consts::OS, std::env::consts::ARCH)),
+    ];
+    if let Some(rustc) = command_line("rustc", &["-vV"]) {
+        for line in rustc.lines() {
+            if let Some((key, value)) = line.split_once(": ") {
+                if matches!(key, "release" | "commit-hash" | "host" | "LLVM version") {
+                    env.push((format!("rustc {}", key), value.to_string()));
+                }
+            }
+        }
+    }
+    env.extend(command_line("cargo", &["-V"]).map(|v| ("cargo".to_string(), v)));
+    env.extend(command_line("rustup", &["show", "active-toolchain"]).map(|v| ("toolchain".to_string(), v)));
+    if let Some(head) = command_line("git", &["rev-parse", "--short", "HEAD"]) {
+        let dirty = command_line("git", &["status", "--porcelain"]).is_some();
+        env.push(("git".to_string(), format!("{}{}", head, if dirty { " (uncommitted changes)" } else { "" })));
+    }
+    let mut vars: Vec<(String, String)> = std::env::vars()
+        .filter(|(key, _)| key.starts_with("CARGO_") || key.starts_with("RUST"))
+        .map(|(key, value)| {
+            let value = if is_sensitive(&key) { "<redacted>".to_string() } else { value };
+            (key, value)
+        })
+        .collect();
+    vars.sort();
+    env.extend(vars);
+    env.into_iter().map(|(key, value)| (key, scrub(&value))).collect()
+}
+fn lockfile() -> Option<(String, String)> {
+    let start = std::env::current_dir().ok()?;
+    let lock = start.ancestors().map(|dir| dir.join("Cargo.lock")).find(|path| path.exists())?;
+    let hash = format!("{:x}", Sha256::digest(fs::read(&lock).ok()?));
+    Some((scrub(&lock.display().to_string()), hash))
+}
+pub fn collect() -> Result<Report> {
+    let bucket = crate::project::current_project();
+    let mut report = Report {
+        project: bucket.as_ref().map(|b| b.name.clone()).unwrap_or_else(|| "unknown".to_string()),
+        generated: Local::now().format("%Y-%m-%d %H:%M:%S %Z").to_string(),
+        environment: environment(),
+        lockfile: lockfile(),
+        ..Default::default()
+    };
+    let manifest_dir = bucket.as_ref().and_then(|b| b.manifest.parent().map(PathBuf::from));
+    if let Some(status) = crate::status::load()?.filter(|s| s.manifest_dir == manifest_dir) {
+        let finished: DateTime<Local> = status.finished.into();
+        report.command = Some(status.command.clone());
+        report.outcome = Some(format!(
+            "exit code {}, {} error(s), {} warning(s), {:.1}s, finished {}",
+            status.exit_code,
+            status.errors,
+            status.warnings,
+            status.duration_seconds,
+            finished.format("%Y-%m-%d %H:%M:%S")
+        ));
+    }
+    let read = |relative: &str| -> Result<String> {
+        let path = crate::project::data_dir()?.join(relative);
+        Ok(if path.exists() { scrub(fs::read_to_string(path)?.trim_end()) } else { String::new() })
+    };
+    report.errors = read("errors/latest.txt")?;
+    report.warnings = read("warnings/latest.txt")?.lines().map(str::to_string).collect();
+    report.build_scripts = read("scripts/latest.txt")?;
+    Ok(report)
+}
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+/// Fence long enough that backticks inside compiler output can't close it early
+fn fence(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+impl Report {
+    fn warnings_shown(&self) -> (&[String], usize) {
+        let shown = &self.warnings[..self.warnings.len().min(MAX_WARNINGS)];
+        (shown, self.warnings.len() - shown.len())
+    }
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Build report: {}\n\n_Generated {} by cargo-mate_\n\n", self.project, self.generated);
+        out.push_str("## Latest build\n\n");
+        match (&self.command, &self.outcome) {
+            (Some(command), Some(outcome)) => out.push_str(&format!("- Command: `{}`\n- Result: {}\n\n", command, outcome)),
+            _ => out.push_str("No build recorded for this project yet.\n\n"),
+        }
+        out.push_str("## Environment\n\n| | |\n|---|---|\n");
+        for (key, value) in &self.environment {
+            out.push_str(&format!("| {} | `{}` |\n", key, value.replace('|', "\\|")));
+        }
+        if let Some((path, hash)) = &self.lockfile {
+            out.push_str(&format!("| Cargo.lock | `{}` ({}) |\n", hash, path));
+        }
+        out.push('\n');
+        let mut section = |title: &str, body: &str| {
+            if !body.is_empty() {
+                let fence = fence(body);
+                out.push_str(&format!("## {}\n\n{}text\n{}\n{}\n\n", title, fence, body, fence));
+            }
+        };
+        section("Errors", &self.errors);
+        let (warnings, hidden) = self.warnings_shown();
+        let mut warnings = warnings.join("\n");
+        if hidden > 0 {
+            warnings.push_str(&format!("\n... and {} more", hidden));
+        }
+        section("Warnings", &warnings);
+        section("Build scripts", &self.build_scripts);
+        out
+    }
+    pub fn to_html(&self) -> String {
+        let mut body = format!(
+            "<h1>Build report: {}</h1>\n<p><em>Generated {} by cargo-mate</em></p>\n<h2>Latest build</h2>\n",
+            html_escape(&self.project),
+            html_escape(&self.generated)
+        );
+        match (&self.command, &self.outcome) {
+            (Some(command), Some(outcome)) => body.push_str(&format!(
+                "<ul><li>Command: <code>{}</code></li><li>Result: {}</li></ul>\n",
+                html_escape(command),
+                html_escape(outcome)
+            )),
+            _ => body.push_str("<p>No build recorded for this project yet.</p>\n"),
+        }
+        body.push_str("<h2>Environment</h2>\n<table>\n");
+        let lock_row = self.lockfile.as_ref().map(|(path, hash)| ("Cargo.lock".to_string(), format!("{} ({})", hash, path)));
+        for (key, value) in self.environment.iter().cloned().chain(lock_row) {
+            body.push_str(&format!("<tr><th>{}</th><td><code>{}</code></td></tr>\n", html_escape(&key), html_escape(&value)));
+        }
+        body.push_str("</table>\n");
+        let (warnings, hidden) = self.warnings_shown();
+        let mut warnings = warnings.join("\n");
+        if hidden > 0 {
+            warnings.push_str(&format!("\n... and {} more", hidden));
+        }
+        for (title, text, class) in [
+            ("Errors", self.errors.as_str(), "errors"),
+            ("Warnings", warnings.as_str(), "warnings"),
+            ("Build scripts", self.build_scripts.as_str(), ""),
+        ] {
+            if !text.is_empty() {
+                body.push_str(&format!("<h2>{}</h2>\n<pre class=\"{}\">{}</pre>\n", title, class, html_escape(text)));
+            }
+        }
+        format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Build report: {}</title>\n<style>\
+             body{{font-family:sans-serif;max-width:960px;margin:2em auto;padding:0 1em}}\
+             pre{{background:#f6f8fa;padding:1em;overflow-x:auto}}pre.errors{{border-left:4px solid #c62828}}\
+             pre.warnings{{border-left:4px solid #b58900}}th{{text-align:left;padding-right:1em}}\
+             </style></head><body>\n{}</body></html>\n",
+            html_escape(&self.project),
+            body
+        )
+    }
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+}
+/// Secret gist through the GitHub CLI, same as journey publishing
+fn upload_gist(file_name: &str, content: &str, description: &str) -> Result<String> {
+    let payload = serde_json::json!({
+        "description": description,
+        "public": false,
+        "files": { file_name: { "content": content } },
+    });
+    let temp_file = std::env::temp_dir().join(format!("cargo-mate-report-{}.json", std::process::id()));
+    fs::write(&temp_file, payload.to_string())?;
+    let output = Command::new("gh")
+        .args(["api", "gists", "--method", "POST", "--input"])
+        .arg(&temp_file)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Uploading needs the GitHub CLI (gh) - install it and run 'gh auth login'");
+    let _ = fs::remove_file(&temp_file);
+    let output = output?;
+    if !output.status.success() {
+        bail!("GitHub CLI error: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let response: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    response["html_url"]
+        .as_str()
+        .map(str::to_string)
+        .context("GitHub did not return a gist URL")
+}
+pub fn handle_report(output: Option<PathBuf>, format: Option<ReportFormat>, gist: bool) -> Result<()> {
+    let format = format
+        .or_else(|| output.as_deref().map(ReportFormat::from_path))
+        .unwrap_or(ReportFormat::Markdown);
+    let report = collect()?;
+    let content = report.render(format);
+    let path = match output {
+        Some(path) => path,
+        None => crate::project::data_file(&format!(
+            "reports/report-{}.{}",
+            Local::now().format("%Y%m%d-%H%M%S"),
+            format.extension()
+        ))?,
+    };
+    fs::write(&path, &content)?;
+    println!("📝 Report written to {}", path.display().to_string().cyan());
+    if report.command.is_none() {
+        println!("{}", "   No build recorded for this project - run a cargo command through cm first".dimmed());
+    }
+    if gist {
+        let file_name = format!("{}-build-report.{}", report.project, format.extension());
+        let url = upload_gist(&file_name, &content, &format!("cargo-mate build report: {}", report.project))?;
+        println!("🔗 Secret gist: {}", url.cyan());
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_render_report() {
+        let report = Report {
+            project: "demo".to_string(),
+            generated: "2026-01-01 12:00:00".to_string(),
+            command: Some("cargo build".to_string()),
+            outcome: Some("exit code 101, 1 error(s)".to_string()),
+            environment: vec![("rustc release".to_string(), "1.80.0".to_string())],
+            lockfile: Some(("Cargo.lock".to_string(), "abc123".to_string())),
+            errors: "error[E0308]: mismatched types <T>\n```".to_string(),
+            warnings: (0..MAX_WARNINGS + 2).map(|i| format!("warning {}", i)).collect(),
+            build_scripts: String::new(),
+        };
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("- Command: `cargo build`"));
+        assert!(markdown.contains("| Cargo.lock | `abc123` (Cargo.lock) |"));
+        assert!(markdown.contains("````text\nerror[E0308]"));
+        assert!(markdown.contains("... and 2 more"));
+        assert!(!markdown.contains("## Build scripts"));
+        let html = report.to_html();
+        assert!(html.contains("mismatched types &lt;T&gt;"));
+        assert!(is_sensitive("CARGO_REGISTRY_TOKEN"));
+        assert!(!is_sensitive("CARGO_HOME"));
+    }
+}
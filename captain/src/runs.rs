@@ -0,0 +1,329 @@
+use crate::artifacts::{self, ArtifactRecord};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Instant;
+const RUN_FILE: &str = "run.json";
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: String,
+    pub bin: String,
+    pub binary: String,
+    pub release: bool,
+    pub args: Vec<String>,
+    pub cwd: String,
+    pub env: BTreeMap<String, String>,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u128,
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub replay_of: Option<String>,
+}
+pub fn runs_dir() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join("runs"))
+}
+fn new_id(dir: &Path, now: DateTime<Local>) -> String {
+    let base = now.format("%Y%m%d-%H%M%S").to_string();
+    let mut id = base.clone();
+    let mut n = 2;
+    while dir.join(&id).exists() {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+/// Accept a full id, a unique prefix of one, or `last`
+pub fn resolve_id(ids: &[String], wanted: &str) -> Result<String> {
+    if wanted == "last" {
+        return ids.iter().max().cloned().context("No recorded runs yet");
+    }
+    if ids.iter().any(|id| id == wanted) {
+        return Ok(wanted.to_string());
+    }
+    let matches: Vec<&String> = ids.iter().filter(|id| id.starts_with(wanted)).collect();
+    match matches.as_slice() {
+        [] => bail!("No run matches '{}' (see 'cm run --list')", wanted),
+        [id] => Ok(id.to_string()),
+        _ => bail!("'{}' matches {} runs - use a longer id", wanted, matches.len()),
+    }
+}
+pub fn build_args(bin: Option<&str>, release: bool) -> Vec<String> {
+    let mut args = vec!["build".to_string()];
+    if release {
+        args.push("--release".to_string());
+    }
+    if let Some(bin) = bin {
+        args.push("--bin".to_string());
+        args.push(bin.to_string());
+    }
+    args
+}
+/// Pick the freshest executable `bin` target for the profile, refusing to guess when a
+/// workspace has several binaries and none was named
+pub fn pick_binary<'a>(
+    records: &'a [ArtifactRecord],
+    bin: Option<&str>,
+    profile: &str,
+) -> Result<&'a ArtifactRecord> {
+    let candidates: Vec<&ArtifactRecord> = records
+        .iter()
+        .filter(|r| r.executable && !r.test && r.profile == profile)
+        .filter(|r| r.kind.split(',').any(|k| k == "bin"))
+        .filter(|r| bin.is_none_or(|b| r.name == b))
+        .collect();
+    if bin.is_none() {
+        let mut names: Vec<&str> = candidates.iter().map(|r| r.name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        if names.len() > 1 {
+            bail!("Several binaries were built ({}) - pick one with 'cm run <bin>'", names.join(", "));
+        }
+    }
+    candidates
+        .into_iter()
+        .max_by_key(|r| r.timestamp)
+        .with_context(|| match bin {
+            Some(bin) => format!("No {} binary named '{}' was built", profile, bin),
+            None => format!("No {} binary was built", profile),
+        })
+}
+fn snapshot_env() -> BTreeMap<String, String> {
+    std::env::vars_os()
+        .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+        .collect()
+}
+fn build(bin: Option<&str>, release: bool) -> Result<PathBuf> {
+    let args = build_args(bin, release);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if !crate::display::run_cargo_with_display(&args) {
+        bail!("Build failed - nothing to run");
+    }
+    let records = artifacts::load_catalog()?;
+    let profile = if release { "release" } else { "debug" };
+    let record = pick_binary(&records, bin, profile)?;
+    Ok(PathBuf::from(&record.path))
+}
+fn tee(mut source: impl Read, mut terminal: impl Write, log: PathBuf) -> io::Result<()> {
+    let mut file = fs::File::create(log)?;
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = source.read(&mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        let _ = terminal.write_all(&buf[..n]);
+        let _ = terminal.flush();
+        file.write_all(&buf[..n])?;
+    }
+}
+fn write_record(dir: &Path, record: &RunRecord) -> Result<()> {
+    let path = dir.join(RUN_FILE);
+    crate::storage::write_json_atomic(&path, record)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+fn execute(
+    binary: &Path,
+    args: &[String],
+    cwd: &Path,
+    env: &BTreeMap<String, String>,
+    dir: &Path,
+) -> Result<(Option<i32>, u128)> {
+    let started = Instant::now();
+    let mut child = Command::new(binary)
+        .args(args)
+        .current_dir(cwd)
+        .env_clear()
+        .envs(env)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start {}", binary.display()))?;
+    let stdout = child.stdout.take().context("stdout not captured")?;
+    let stderr = child.stderr.take().context("stderr not captured")?;
+    let out_log = dir.join("stdout.log");
+    let err_log = dir.join("stderr.log");
+    let out = thread::spawn(move || tee(stdout, io::stdout(), out_log));
+    let err = thread::spawn(move || tee(stderr, io::stderr(), err_log));
+    let status = child.wait()?;
+    let _ = out.join();
+    let _ = err.join();
+    Ok((status.code(), started.elapsed().as_millis()))
+}
+fn record_and_report(record: &RunRecord, dir: &Path) -> Result<()> {
+    write_record(dir, record)?;
+    let mut command = format!("run {}", record.bin);
+    if !record.args.is_empty() {
+        command.push_str(&format!(" -- {}", shell_words::join(&record.args)));
+    }
+    command.push_str(&format!(" [run {}]", record.id));
+    crate::history::save_to_history(command, Vec::new(), Vec::new());
+    let code = record.exit_code.map_or("signal".to_string(), |c| c.to_string());
+    let status = if record.exit_code == Some(0) { code.green() } else { code.red() };
+    println!();
+    println!(
+        "🏃 Run {} exited with {} after {:.1}s", record.id.cyan(), status,
+        record.duration_ms as f64 / 1000.0
+    );
+    println!("   Output saved to {}", dir.display().to_string().dimmed());
+    println!("   Replay with: {}", format!("cm run --replay {}", record.id).yellow());
+    Ok(())
+}
+pub fn handle_run(bin: Option<String>, release: bool, args: Vec<String>) -> Result<()> {
+    let binary = build(bin.as_deref(), release)?;
+    let root = runs_dir()?;
+    let id = new_id(&root, Local::now());
+    let dir = root.join(&id);
+    fs::create_dir_all(&dir)?;
+    let cwd = std::env::current_dir()?;
+    let env = snapshot_env();
+    let name = binary.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    println!("🏃 Running {} {}", name.cyan(), shell_words::join(&args).dimmed());
+    let started_at = Utc::now();
+    let (exit_code, duration_ms) = execute(&binary, &args, &cwd, &env, &dir)?;
+    let record = RunRecord {
+        id,
+        bin: bin.unwrap_or(name),
+        binary: binary.display().to_string(),
+        release,
+        args,
+        cwd: cwd.display().to_string(),
+        env,
+        started_at,
+        duration_ms,
+        exit_code,
+        replay_of: None,
+    };
+    record_and_report(&record, &dir)?;
+    if exit_code != Some(0) {
+        std::process::exit(exit_code.unwrap_or(1));
+    }
+    Ok(())
+}
+fn run_ids() -> Result<Vec<String>> {
+    let dir = runs_dir()?;
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut ids: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.path().join(RUN_FILE).is_file())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
+pub fn load_run(id: &str) -> Result<RunRecord> {
+    let id = resolve_id(&run_ids()?, id)?;
+    let path = runs_dir()?.join(&id).join(RUN_FILE);
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Could not read {}", path.display()))?;
+    Ok(serde_json::from_str(&content)?)
+}
+pub fn handle_replay(id: &str) -> Result<()> {
+    let original = load_run(id)?;
+    let cwd = PathBuf::from(&original.cwd);
+    if !cwd.is_dir() {
+        bail!("Working directory {} no longer exists", cwd.display());
+    }
+    println!(
+        "🔁 Replaying run {} ({} env vars, {} args) in {}", original.id.cyan(), original.env
+        .len(), original.args.len(), cwd.display()
+    );
+    std::env::set_current_dir(&cwd)?;
+    let binary = build(Some(&original.bin), original.release)?;
+    let root = runs_dir()?;
+    let id = new_id(&root, Local::now());
+    let dir = root.join(&id);
+    fs::create_dir_all(&dir)?;
+    let started_at = Utc::now();
+    let (exit_code, duration_ms) = execute(&binary, &original.args, &cwd, &original.env, &dir)?;
+    let record = RunRecord {
+        id,
+        binary: binary.display().to_string(),
+        started_at,
+        duration_ms,
+        exit_code,
+        replay_of: Some(original.id.clone()),
+        ..original.clone()
+    };
+    record_and_report(&record, &dir)?;
+    if exit_code != original.exit_code {
+        println!(
+            "⚠️  Exit code differs from the original run ({})", original.exit_code
+            .map_or("signal".to_string(), | c | c.to_string())
+        );
+    }
+    if exit_code != Some(0) {
+        std::process::exit(exit_code.unwrap_or(1));
+    }
+    Ok(())
+}
+pub fn list_runs() -> Result<()> {
+    let ids = run_ids()?;
+    if ids.is_empty() {
+        println!("No recorded runs. Start one with 'cm run [bin]'");
+        return Ok(());
+    }
+    println!("{}", "🏃 Recorded runs".bold().blue());
+    for id in ids.iter().rev().take(20) {
+        let Ok(run) = load_run(id) else {
+            continue;
+        };
+        let code = run.exit_code.map_or("signal".to_string(), |c| c.to_string());
+        let code = if run.exit_code == Some(0) { code.green() } else { code.red() };
+        let replay = run.replay_of.map(|o| format!(" (replay of {})", o)).unwrap_or_default();
+        println!(
+            "  {}  {:<16} exit {:<6} {:>7.1}s  {}{}", run.id.cyan(), run.bin, code,
+            run.duration_ms as f64 / 1000.0, shell_words::join(&run.args).dimmed(), replay
+            .dimmed()
+        );
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn record(name: &str, profile: &str, executable: bool) -> ArtifactRecord {
+        ArtifactRecord {
+            name: name.to_string(),
+            kind: "bin".to_string(),
+            package: name.to_string(),
+            path: format!("/p/target/{}/{}", profile, name),
+            size: 1,
+            profile: profile.to_string(),
+            test: false,
+            executable,
+            timestamp: Utc::now(),
+        }
+    }
+    #[test]
+    fn resolves_ids_and_picks_the_built_binary() {
+        let ids = vec!["20261017-101500".to_string(), "20261017-101500-2".to_string(), "20261018-090000".to_string()];
+        assert_eq!(resolve_id(&ids, "20261018").unwrap(), "20261018-090000");
+        assert_eq!(resolve_id(&ids, "20261017-101500").unwrap(), "20261017-101500");
+        assert_eq!(resolve_id(&ids, "last").unwrap(), "20261018-090000");
+        assert!(resolve_id(&ids, "20261017").is_err());
+        assert!(resolve_id(&ids, "1999").is_err());
+        assert_eq!(build_args(Some("app"), true), vec!["build", "--release", "--bin", "app"]);
+        let records = vec![record("app", "debug", true), record("app", "release", true), record("lib", "debug", false)];
+        assert_eq!(pick_binary(&records, None, "release").unwrap().path, "/p/target/release/app");
+        assert!(pick_binary(&records, Some("tool"), "debug").is_err());
+        let mut two = records.clone();
+        two.push(record("tool", "debug", true));
+        assert!(pick_binary(&two, None, "debug").is_err());
+        assert_eq!(pick_binary(&two, Some("tool"), "debug").unwrap().name, "tool");
+    }
+}
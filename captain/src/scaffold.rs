@@ -0,0 +1,353 @@
+use crate::anchor::AnchorManager;
+use crate::captain::config::ConfigManager;
+use crate::journey::{self, Journey, JourneyCommand};
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Utc};
+use colored::*;
+use handlebars::Handlebars;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use walkdir::WalkDir;
+/// A built-in template: description, files (path, handlebars source) and the commands
+/// of the project's default journey
+pub struct BuiltinTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub files: &'static [(&'static str, &'static str)],
+    pub journey: &'static [&'static str],
+}
+const GITIGNORE: &str = "/target\n";
+const README: &str = "# {{name}}\n\nCreated by {{author}} from the `{{template}}` template.\n";
+pub const BUILTIN_TEMPLATES: &[BuiltinTemplate] = &[
+    BuiltinTemplate {
+        name: "cli-app",
+        description: "Command-line app with clap and anyhow",
+        files: &[
+            ("Cargo.toml", r#"[package]
+name = "{{name}}"
+version = "0.1.0"
+edition = "2021"
+authors = ["{{author}}"]
+
+[dependencies]
+anyhow = "1"
+clap = { version = "4", features = ["derive"] }
+"#),
+            ("src/main.rs", r#"use anyhow::Result;
+use clap::Parser;
+
+/// {{name}} command-line interface
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// Who to greet
+    #[arg(short, long, default_value = "world")]
+    name: String,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    println!("Hello, {}!", args.name);
+    Ok(())
+}
+"#),
+            (".gitignore", GITIGNORE),
+            ("README.md", README),
+        ],
+        journey: &["cargo check", "cargo test", "cargo run -- --help"],
+    },
+    BuiltinTemplate {
+        name: "lib",
+        description: "Library crate with a unit test",
+        files: &[
+            ("Cargo.toml", r#"[package]
+name = "{{name}}"
+version = "0.1.0"
+edition = "2021"
+authors = ["{{author}}"]
+
+[dependencies]
+"#),
+            ("src/lib.rs", r#"//! {{name}}
+
+pub fn add(left: u64, right: u64) -> u64 {
+    left + right
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds() {
+        assert_eq!(add(2, 2), 4);
+    }
+}
+"#),
+            (".gitignore", GITIGNORE),
+            ("README.md", README),
+        ],
+        journey: &["cargo check", "cargo test", "cargo doc --no-deps"],
+    },
+    BuiltinTemplate {
+        name: "axum-service",
+        description: "HTTP service with axum, tokio and tracing",
+        files: &[
+            ("Cargo.toml", r#"[package]
+name = "{{name}}"
+version = "0.1.0"
+edition = "2021"
+authors = ["{{author}}"]
+
+[dependencies]
+axum = "0.7"
+tokio = { version = "1", features = ["full"] }
+tracing = "0.1"
+tracing-subscriber = { version = "0.3", features = ["env-filter"] }
+"#),
+            ("src/main.rs", r#"use axum::{routing::get, Router};
+
+async fn health() -> &'static str {
+    "ok"
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+    let app = Router::new().route("/health", get(health));
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    tracing::info!("{{name}} listening on {}", listener.local_addr().unwrap());
+    axum::serve(listener, app).await.unwrap();
+}
+"#),
+            (".gitignore", GITIGNORE),
+            ("README.md", README),
+        ],
+        journey: &["cargo check", "cargo test", "cargo build --release"],
+    },
+    BuiltinTemplate {
+        name: "wasm-lib",
+        description: "WebAssembly library with wasm-bindgen",
+        files: &[
+            ("Cargo.toml", r#"[package]
+name = "{{name}}"
+version = "0.1.0"
+edition = "2021"
+authors = ["{{author}}"]
+
+[lib]
+crate-type = ["cdylib", "rlib"]
+
+[dependencies]
+wasm-bindgen = "0.2"
+"#),
+            ("src/lib.rs", r#"use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub fn greet(name: &str) -> String {
+    format!("Hello from {{crate_name}}, {}!", name)
+}
+"#),
+            (".gitignore", GITIGNORE),
+            ("README.md", README),
+        ],
+        journey: &["cargo check", "cargo test", "cargo build --release --target wasm32-unknown-unknown"],
+    },
+];
+pub fn user_templates_dir() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join("templates"))
+}
+fn user_template(name: &str) -> Result<Option<PathBuf>> {
+    let dir = user_templates_dir()?.join(name);
+    Ok(dir.is_dir().then_some(dir))
+}
+fn default_author() -> String {
+    Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+pub fn variables(template: &str, name: &str, author: &str, overrides: &[String]) -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::from([
+        ("name".to_string(), name.to_string()),
+        ("crate_name".to_string(), name.replace('-', "_")),
+        ("author".to_string(), author.to_string()),
+        ("year".to_string(), Utc::now().year().to_string()),
+        ("template".to_string(), template.to_string()),
+    ]);
+    for pair in overrides {
+        let (key, value) = pair
+            .split_once('=')
+            .with_context(|| format!("Invalid --var '{}' - expected key=value", pair))?;
+        vars.insert(key.trim().to_string(), value.to_string());
+    }
+    Ok(vars)
+}
+fn renderer() -> Handlebars<'static> {
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars
+}
+/// A rendered template: its files by relative path, and the journey commands
+type Rendered = (Vec<(PathBuf, String)>, Vec<String>);
+/// Render every file (and path) of a template; user templates are directory trees under
+/// ~/.shipwreck/templates/<name>, with an optional `journey.txt` of one command per line
+pub fn render(template: &str, vars: &BTreeMap<String, String>) -> Result<Rendered> {
+    let handlebars = renderer();
+    let render = |source: &str, what: &str| {
+        handlebars
+            .render_template(source, vars)
+            .with_context(|| format!("Failed to render {}", what))
+    };
+    if let Some(dir) = user_template(template)? {
+        let mut files = Vec::new();
+        let mut journey = vec!["cargo check".to_string(), "cargo test".to_string()];
+        for entry in WalkDir::new(&dir).into_iter().flatten().filter(|e| e.file_type().is_file()) {
+            let relative = entry.path().strip_prefix(&dir)?.to_string_lossy().to_string();
+            let source = fs::read_to_string(entry.path())
+                .with_context(|| format!("{} is not a text file", entry.path().display()))?;
+            if relative == "journey.txt" {
+                journey = render(&source, &relative)?
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(String::from)
+                    .collect();
+                continue;
+            }
+            files.push((PathBuf::from(render(&relative, &relative)?), render(&source, &relative)?));
+        }
+        return Ok((files, journey));
+    }
+    let Some(builtin) = BUILTIN_TEMPLATES.iter().find(|t| t.name == template) else {
+        bail!("Unknown template '{}' - see 'cm new --list'", template);
+    };
+    let files = builtin
+        .files
+        .iter()
+        .map(|(path, source)| Ok((PathBuf::from(path), render(source, path)?)))
+        .collect::<Result<_>>()?;
+    Ok((files, builtin.journey.iter().map(|c| c.to_string()).collect()))
+}
+pub fn list_templates() -> Result<()> {
+    println!("{}", "📐 Project templates".bold().blue());
+    for template in BUILTIN_TEMPLATES {
+        println!("  {:<14} {}", template.name.green(), template.description);
+    }
+    let dir = user_templates_dir()?;
+    let mut user: Vec<String> = fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    user.sort();
+    if !user.is_empty() {
+        println!("\n{}", format!("  From {}", dir.display()).dimmed());
+        for name in user {
+            println!("  {:<14} {}", name.cyan(), "user template".dimmed());
+        }
+    }
+    Ok(())
+}
+/// Write the rendered files into a fresh `<name>/` directory and switch into it
+pub fn create(template: &str, name: &str, overrides: &[String]) -> Result<Vec<String>> {
+    if !is_valid_name(name) {
+        bail!("'{}' is not a valid package name - use letters, digits, '-' and '_'", name);
+    }
+    let dest = std::env::current_dir()?.join(name);
+    if dest.exists() {
+        bail!("{} already exists", dest.display());
+    }
+    let vars = variables(template, name, &default_author(), overrides)?;
+    let (files, journey) = render(template, &vars)?;
+    for (path, content) in &files {
+        let target = dest.join(path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, content)?;
+    }
+    println!("📐 Created {} from {} ({} files)", name.green().bold(), template.cyan(), files.len());
+    std::env::set_current_dir(&dest)?;
+    Ok(journey)
+}
+/// Record the default journey and initial anchor for the project in the current directory
+pub fn finish(name: &str, template: &str, journey_commands: &[String]) -> Result<()> {
+    let journey_name = format!("{}-dev", name);
+    let commands = journey_commands
+        .iter()
+        .map(|line| {
+            let parts = shell_words::split(line).with_context(|| format!("Invalid journey step '{}'", line))?;
+            let Some((command, args)) = parts.split_first() else {
+                bail!("Empty journey step");
+            };
+            Ok(JourneyCommand {
+                command: command.clone(),
+                args: args.to_vec(),
+                description: Some(line.clone()),
+                ..JourneyCommand::default()
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    journey::save_journey(&Journey {
+        name: journey_name.clone(),
+        description: format!("Default workflow for {} ({} template)", name, template),
+        created: Utc::now(),
+        commands,
+        variables: HashMap::new(),
+        checkpoints: Vec::new(),
+        environment: HashMap::new(),
+        success_rate: 100.0,
+        author: std::env::var("USER").ok(),
+        tags: vec!["template".to_string(), template.to_string()],
+        downloads: 0,
+        rating: 0.0,
+        max_parallel: None,
+    })?;
+    ConfigManager::new()?.set("project.default_journey", &journey_name, true)?;
+    println!("🗺️  Default journey {} ({})", journey_name.cyan(), journey_commands.join(" → ").dimmed());
+    let anchor_name = format!("{}-initial", name);
+    AnchorManager::new()?.save(&anchor_name, &format!("Fresh {} project from the {} template", name, template))?;
+    println!("⚓ Initial anchor {}", anchor_name.cyan());
+    println!();
+    println!("🚀 Next: {} then {} to run the default journey", format!("cd {}", name).yellow(), "cm".yellow());
+    Ok(())
+}
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    #[test]
+    fn renders_builtin_templates_with_variables() {
+        let vars = variables("wasm-lib", "my-widget", "Ada", &["license=MIT".to_string()]).unwrap();
+        assert_eq!(vars["crate_name"], "my_widget");
+        assert_eq!(vars["license"], "MIT");
+        assert!(variables("lib", "x", "Ada", &["oops".to_string()]).is_err());
+        for template in BUILTIN_TEMPLATES {
+            let (files, journey) = render(template.name, &vars).unwrap();
+            let manifest = &files.iter().find(|(p, _)| p == Path::new("Cargo.toml")).unwrap().1;
+            assert!(manifest.contains("name = \"my-widget\""), "{}", template.name);
+            assert!(manifest.parse::<toml::Table>().is_ok(), "{}", template.name);
+            assert!(!files.iter().any(|(_, c)| c.contains("{{")), "{}", template.name);
+            assert_eq!(journey[0], "cargo check");
+        }
+        let (files, _) = render("wasm-lib", &vars).unwrap();
+        assert!(files[1].1.contains("Hello from my_widget, {}!"));
+        assert!(render("nope", &vars).is_err());
+        assert!(is_valid_name("my-app") && !is_valid_name("1app") && !is_valid_name("a b"));
+    }
+}
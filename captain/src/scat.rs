@@ -1,12 +1,12 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use rand::{Rng, SeedableRng, rng};
-use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng, TryRngCore, rng};
+use rand::rngs::{OsRng, StdRng};
 use rand::distr::Alphanumeric;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use walkdir::WalkDir;
 use syn::{
@@ -14,6 +14,7 @@ use syn::{
     ExprMatch, Arm, PatPath, Member, ExprCall,
 };
 use syn::fold::Fold;
+use syn::visit::Visit;
 use sha2::{Sha256, Digest};
 use aes_gcm::{Aes256Gcm, Key, Nonce};
 use aes_gcm::aead::{Aead, KeyInit};
@@ -47,10 +48,22 @@ pub struct ScatArgs {
     #[command(subcommand)]
     pub command: ScatCommand,
 }
+/// Restricts a scat run to part of the tree; globs match paths relative to the input
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct FilterArgs {
+    /// Only touch files matching this glob (repeatable)
+    #[arg(long)]
+    pub include: Vec<String>,
+    /// Never touch files matching this glob (repeatable)
+    #[arg(long)]
+    pub exclude: Vec<String>,
+}
 #[derive(Subcommand, Debug)]
 pub enum ScatCommand {
     Names {
         path: PathBuf,
+        #[command(flatten)]
+        filter: FilterArgs,
         #[arg(long)]
         map: Option<PathBuf>,
         #[arg(long)]
@@ -58,6 +71,8 @@ pub enum ScatCommand {
     },
     Code {
         path: PathBuf,
+        #[command(flatten)]
+        filter: FilterArgs,
         #[arg(long)]
         preserve_pub: bool,
         #[arg(long, default_value = "3")]
@@ -75,6 +90,8 @@ pub enum ScatCommand {
     },
     Strings {
         path: PathBuf,
+        #[command(flatten)]
+        filter: FilterArgs,
         #[arg(long)]
         key: Option<String>,
         #[arg(long)]
@@ -86,8 +103,38 @@ pub enum ScatCommand {
         #[arg(long)]
         skip_errors: bool,
     },
-    Pack { input: PathBuf, output: PathBuf, #[arg(long)] compress: bool },
-    Unpack { input: PathBuf, map: PathBuf, #[arg(long)] output: Option<PathBuf> },
+    Pack {
+        input: PathBuf,
+        output: PathBuf,
+        #[arg(long)]
+        compress: bool,
+        /// Encrypt with a passphrase (prompted, or CM_SCAT_PASSPHRASE)
+        #[arg(long)]
+        encrypt: bool,
+        /// Write a script that unpacks itself instead of a raw bundle
+        #[arg(long, value_enum)]
+        self_extract: Option<crate::scat_bundle::StubKind>,
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    Unpack {
+        input: PathBuf,
+        /// Mapping file; not needed for bundles written by `scat pack`
+        map: Option<PathBuf>,
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    Verify {
+        path: PathBuf,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[arg(long)]
+        map: PathBuf,
+        #[arg(long)]
+        key: Option<String>,
+    },
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ObfuscationMapping {
@@ -97,6 +144,19 @@ pub struct ObfuscationMapping {
     pub method: String,
     pub seed: Option<String>,
     pub config: ObfuscationConfig,
+    #[serde(default)]
+    pub key: Option<KeyMetadata>,
+}
+/// How string literals were keyed. Enough for `scat verify` to re-derive and check the key,
+/// never the key itself
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeyMetadata {
+    pub algorithm: String,
+    pub kdf: String,
+    pub salt: String,
+    pub key_check: String,
+    pub default_key: bool,
+    pub strings: Vec<String>,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ObfuscationConfig {
@@ -117,6 +177,9 @@ pub struct RenameContext {
     pub scope_stack: Vec<Scope>,
     pub protected_identifiers: HashSet<String>,
     pub public_api: HashSet<String>,
+    /// When set, only names the project declares are renamed; everything else comes from
+    /// std or dependencies
+    pub declared: Option<HashSet<String>>,
     pub current_module: Vec<String>,
     pub rng: StdRng,
     pub config: ObfuscationConfig,
@@ -137,6 +200,7 @@ impl ObfuscationMapping {
             method: method.to_string(),
             seed: seed.map(|s| s.to_string()),
             config,
+            key: None,
         }
     }
     pub fn add_mapping(&mut self, original: String, obfuscated: String) {
@@ -144,8 +208,7 @@ impl ObfuscationMapping {
         self.obfuscated_to_original.insert(obfuscated, original);
     }
     pub fn save_to_file(&self, path: &PathBuf) -> Result<()> {
-        let json = serde_json::to_string_pretty(self)?;
-        fs::write(path, json)?;
+        crate::storage::write_json_atomic(path, self)?;
         println!("🔐 Mapping saved to: {}", path.display());
         Ok(())
     }
@@ -155,6 +218,548 @@ impl ObfuscationMapping {
         Ok(mapping)
     }
 }
+/// Compiled `--include`/`--exclude` globs; `target/` and `.git/` are always left alone
+#[derive(Debug, Clone, Default)]
+pub struct FileFilter {
+    include: Vec<glob::Pattern>,
+    exclude: Vec<glob::Pattern>,
+}
+impl FileFilter {
+    pub fn new(args: &FilterArgs) -> Result<Self> {
+        let compile = |globs: &[String]| -> Result<Vec<glob::Pattern>> {
+            globs
+                .iter()
+                .map(|g| {
+                    glob::Pattern::new(g)
+                        .map_err(|e| anyhow::anyhow!("Invalid glob '{}': {}", g, e))
+                })
+                .collect()
+        };
+        Ok(Self {
+            include: compile(&args.include)?,
+            exclude: compile(&args.exclude)?,
+        })
+    }
+    pub fn is_active(&self) -> bool {
+        !self.include.is_empty() || !self.exclude.is_empty()
+    }
+    /// A glob matches the relative path, any parent directory of it, or the file name
+    pub fn allows(&self, relative: &Path) -> bool {
+        let components: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        if components.iter().any(|c| c == "target" || c == ".git") {
+            return false;
+        }
+        let candidates: Vec<String> = (1..=components.len())
+            .map(|n| components[..n].join("/"))
+            .chain(components.last().cloned())
+            .collect();
+        let hit = |pattern: &glob::Pattern| candidates.iter().any(|c| pattern.matches(c));
+        (self.include.is_empty() || self.include.iter().any(hit))
+            && !self.exclude.iter().any(hit)
+    }
+    /// Rust files below `root` that pass the filter, or `root` itself when it is a file
+    pub fn rust_files(&self, root: &Path) -> Vec<PathBuf> {
+        if root.is_file() {
+            return vec![root.to_path_buf()];
+        }
+        WalkDir::new(root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.file_type().is_file()
+                    && e.path().extension().and_then(|s| s.to_str()) == Some("rs")
+            })
+            .filter(|e| self.allows(e.path().strip_prefix(root).unwrap_or(e.path())))
+            .map(|e| e.into_path())
+            .collect()
+    }
+}
+const SKIP_COMMENT: &str = "// scat:skip";
+const SKIP_ATTR: &str = "#[scat::skip]";
+fn is_skip_attr(attr: &syn::Attribute) -> bool {
+    let segments: Vec<String> = attr
+        .path()
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect();
+    segments == ["scat", "skip"]
+}
+fn has_skip_attr(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(is_skip_attr)
+}
+fn item_attrs(item: &Item) -> &[syn::Attribute] {
+    match item {
+        Item::Const(i) => &i.attrs,
+        Item::Enum(i) => &i.attrs,
+        Item::ExternCrate(i) => &i.attrs,
+        Item::Fn(i) => &i.attrs,
+        Item::ForeignMod(i) => &i.attrs,
+        Item::Impl(i) => &i.attrs,
+        Item::Macro(i) => &i.attrs,
+        Item::Mod(i) => &i.attrs,
+        Item::Static(i) => &i.attrs,
+        Item::Struct(i) => &i.attrs,
+        Item::Trait(i) => &i.attrs,
+        Item::TraitAlias(i) => &i.attrs,
+        Item::Type(i) => &i.attrs,
+        Item::Union(i) => &i.attrs,
+        Item::Use(i) => &i.attrs,
+        _ => &[],
+    }
+}
+fn impl_item_attrs(item: &syn::ImplItem) -> &[syn::Attribute] {
+    match item {
+        syn::ImplItem::Const(i) => &i.attrs,
+        syn::ImplItem::Fn(i) => &i.attrs,
+        syn::ImplItem::Type(i) => &i.attrs,
+        syn::ImplItem::Macro(i) => &i.attrs,
+        _ => &[],
+    }
+}
+fn trait_item_attrs(item: &syn::TraitItem) -> &[syn::Attribute] {
+    match item {
+        syn::TraitItem::Const(i) => &i.attrs,
+        syn::TraitItem::Fn(i) => &i.attrs,
+        syn::TraitItem::Type(i) => &i.attrs,
+        syn::TraitItem::Macro(i) => &i.attrs,
+        _ => &[],
+    }
+}
+/// syn drops comments, so `// scat:skip` lines become the attribute form before parsing
+pub(crate) fn mark_skip_comments(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| {
+            if line.trim() == SKIP_COMMENT {
+                line.replace(SKIP_COMMENT, SKIP_ATTR)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+/// `#[scat::skip]` would not compile, so output carries it as a comment again
+pub(crate) fn unmark_skip_attrs(content: &str) -> String {
+    let mut out = content
+        .lines()
+        .map(|line| {
+            if line.trim() == SKIP_ATTR {
+                line.replace(SKIP_ATTR, SKIP_COMMENT)
+            } else {
+                line.replace(&format!("{} ", SKIP_ATTR), "")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    out.push('\n');
+    out
+}
+/// Every identifier inside a skipped node. Renaming works by name across files, so these
+/// stay protected everywhere and references to skipped items keep resolving
+#[derive(Default)]
+struct SkipCollector {
+    depth: usize,
+    protected: HashSet<String>,
+}
+impl SkipCollector {
+    fn nested(&mut self, skip: bool, visit: impl FnOnce(&mut Self)) {
+        self.depth += skip as usize;
+        visit(self);
+        self.depth -= skip as usize;
+    }
+}
+impl<'ast> Visit<'ast> for SkipCollector {
+    fn visit_ident(&mut self, ident: &'ast Ident) {
+        if self.depth > 0 {
+            self.protected.insert(ident.to_string());
+        }
+    }
+    fn visit_item(&mut self, item: &'ast Item) {
+        self.nested(has_skip_attr(item_attrs(item)), |v| syn::visit::visit_item(v, item));
+    }
+    fn visit_impl_item(&mut self, item: &'ast syn::ImplItem) {
+        self.nested(
+            has_skip_attr(impl_item_attrs(item)),
+            |v| syn::visit::visit_impl_item(v, item),
+        );
+    }
+    fn visit_trait_item(&mut self, item: &'ast syn::TraitItem) {
+        self.nested(
+            has_skip_attr(trait_item_attrs(item)),
+            |v| syn::visit::visit_trait_item(v, item),
+        );
+    }
+    fn visit_field(&mut self, field: &'ast syn::Field) {
+        self.nested(has_skip_attr(&field.attrs), |v| syn::visit::visit_field(v, field));
+    }
+    fn visit_variant(&mut self, variant: &'ast syn::Variant) {
+        self.nested(
+            has_skip_attr(&variant.attrs),
+            |v| syn::visit::visit_variant(v, variant),
+        );
+    }
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        self.nested(has_skip_attr(&local.attrs), |v| syn::visit::visit_local(v, local));
+    }
+}
+/// Protect identifiers of skipped items in every file before any file is rewritten
+fn collect_skipped_identifiers(files: &[PathBuf], context: &mut RenameContext) -> Result<usize> {
+    let mut collector = SkipCollector::default();
+    for file in files {
+        let content = mark_skip_comments(&fs::read_to_string(file)?);
+        collector.visit_file(&syn::parse_file(&content)?);
+    }
+    let count = collector.protected.len();
+    context.protected_identifiers.extend(collector.protected);
+    Ok(count)
+}
+/// serde attribute values that name functions, modules or types as strings
+const SERDE_PATH_KEYS: &[&str] = &[
+    "default", "with", "serialize_with", "deserialize_with", "skip_serializing_if",
+    "getter", "from", "try_from", "into", "remote",
+];
+const BINDGEN_ATTRS: &[&str] = &["wasm_bindgen", "pyclass", "pyfunction", "pymethods", "napi"];
+/// The parts of `#[serde(...)]` that decide whether a name may change
+#[derive(Default)]
+struct SerdeAttrs {
+    keys: HashSet<String>,
+    /// `rename(serialize = "..")` may fix only one direction, so the Rust name still matters
+    partial_rename: bool,
+    paths: Vec<String>,
+}
+fn serde_attrs(attrs: &[syn::Attribute]) -> SerdeAttrs {
+    let mut parsed = SerdeAttrs::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        let _ = attr
+            .parse_nested_meta(|meta| {
+                let key = meta.path.get_ident().map(|i| i.to_string()).unwrap_or_default();
+                if meta.input.peek(syn::Token![=]) {
+                    let value: Expr = meta.value()?.parse()?;
+                    if let Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }) = &value {
+                        if SERDE_PATH_KEYS.contains(&key.as_str()) {
+                            parsed.paths.push(s.value());
+                        }
+                    }
+                } else if meta.input.peek(syn::token::Paren) {
+                    meta.input.parse::<proc_macro2::TokenTree>()?;
+                    parsed.partial_rename |= key == "rename";
+                }
+                parsed.keys.insert(key);
+                Ok(())
+            });
+    }
+    parsed
+}
+fn derives_serde(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("derive"))
+        .filter_map(|a| {
+            a.parse_args_with(
+                    syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+                )
+                .ok()
+        })
+        .flatten()
+        .filter_map(|path| path.segments.last().map(|s| s.ident.to_string()))
+        .any(|name| name == "Serialize" || name == "Deserialize")
+}
+fn is_repr_c(attrs: &[syn::Attribute]) -> bool {
+    let mut found = false;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        let _ = attr
+            .parse_nested_meta(|meta| {
+                found |= meta.path.is_ident("C");
+                if meta.input.peek(syn::token::Paren) {
+                    meta.input.parse::<proc_macro2::TokenTree>()?;
+                }
+                Ok(())
+            });
+    }
+    found
+}
+fn is_bindgen(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| {
+            let segments: Vec<String> = attr
+                .path()
+                .segments
+                .iter()
+                .map(|s| s.ident.to_string())
+                .collect();
+            segments == ["uniffi", "export"]
+                || segments.last().is_some_and(|s| BINDGEN_ATTRS.contains(&s.as_str()))
+        })
+}
+/// `#[no_mangle]`/`#[export_name]`, including the edition 2024 `#[unsafe(...)]` spelling
+fn exports_symbol(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| {
+            let path = if attr.path().is_ident("unsafe") {
+                match attr.parse_args::<syn::Meta>() {
+                    Ok(meta) => meta.path().clone(),
+                    Err(_) => return false,
+                }
+            } else {
+                attr.path().clone()
+            };
+            path.is_ident("no_mangle") || path.is_ident("export_name")
+        })
+}
+/// Names that other languages or serialized data see: extern blocks, exported symbols,
+/// `#[repr(C)]` types, bindgen exports, and serde names that no attribute pins down
+#[derive(Default)]
+struct InteropCollector {
+    protected: HashSet<String>,
+}
+impl InteropCollector {
+    fn protect(&mut self, ident: &Ident) {
+        self.protected.insert(ident.to_string());
+    }
+    fn protect_fields(&mut self, fields: &syn::Fields) {
+        for ident in fields.iter().filter_map(|f| f.ident.as_ref()) {
+            self.protect(ident);
+        }
+    }
+    fn protect_serde_paths(&mut self, serde: &SerdeAttrs) {
+        for path in &serde.paths {
+            self.protected.extend(path.split("::").map(|s| s.trim().to_string()));
+        }
+    }
+    fn protect_serde_fields(&mut self, fields: &syn::Fields, rename_all: bool) {
+        for field in fields {
+            let serde = serde_attrs(&field.attrs);
+            self.protect_serde_paths(&serde);
+            if let Some(ident) = &field.ident {
+                if rename_all || serde.partial_rename {
+                    self.protect(ident);
+                }
+            }
+        }
+    }
+}
+impl<'ast> Visit<'ast> for InteropCollector {
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        if is_repr_c(&item.attrs) || is_bindgen(&item.attrs) {
+            self.protect(&item.ident);
+            self.protect_fields(&item.fields);
+        }
+        if derives_serde(&item.attrs) {
+            let container = serde_attrs(&item.attrs);
+            self.protect_serde_paths(&container);
+            self.protect_serde_fields(&item.fields, container.keys.contains("rename_all"));
+        }
+        syn::visit::visit_item_struct(self, item);
+    }
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        if is_repr_c(&item.attrs) || is_bindgen(&item.attrs) {
+            self.protect(&item.ident);
+            for variant in &item.variants {
+                self.protect(&variant.ident);
+            }
+        }
+        if derives_serde(&item.attrs) {
+            let container = serde_attrs(&item.attrs);
+            self.protect_serde_paths(&container);
+            for variant in &item.variants {
+                let serde = serde_attrs(&variant.attrs);
+                self.protect_serde_paths(&serde);
+                if container.keys.contains("rename_all") || serde.partial_rename {
+                    self.protect(&variant.ident);
+                }
+                let rename_fields = container.keys.contains("rename_all_fields")
+                    || serde.keys.contains("rename_all");
+                self.protect_serde_fields(&variant.fields, rename_fields);
+            }
+        }
+        syn::visit::visit_item_enum(self, item);
+    }
+    fn visit_item_union(&mut self, item: &'ast syn::ItemUnion) {
+        if is_repr_c(&item.attrs) || is_bindgen(&item.attrs) {
+            self.protect(&item.ident);
+            for field in &item.fields.named {
+                if let Some(ident) = &field.ident {
+                    self.protect(ident);
+                }
+            }
+        }
+        syn::visit::visit_item_union(self, item);
+    }
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        if exports_symbol(&item.attrs) || is_bindgen(&item.attrs) {
+            self.protect(&item.sig.ident);
+        }
+        syn::visit::visit_item_fn(self, item);
+    }
+    fn visit_item_foreign_mod(&mut self, item: &'ast syn::ItemForeignMod) {
+        for foreign in &item.items {
+            match foreign {
+                syn::ForeignItem::Fn(f) => self.protect(&f.sig.ident),
+                syn::ForeignItem::Static(s) => self.protect(&s.ident),
+                syn::ForeignItem::Type(t) => self.protect(&t.ident),
+                _ => {}
+            }
+        }
+        syn::visit::visit_item_foreign_mod(self, item);
+    }
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        if is_bindgen(&item.attrs) {
+            if let syn::Type::Path(ty) = &*item.self_ty {
+                if let Some(segment) = ty.path.segments.last() {
+                    self.protect(&segment.ident);
+                }
+            }
+            for impl_item in &item.items {
+                match impl_item {
+                    syn::ImplItem::Fn(f) => self.protect(&f.sig.ident),
+                    syn::ImplItem::Const(c) => self.protect(&c.ident),
+                    _ => {}
+                }
+            }
+        }
+        syn::visit::visit_item_impl(self, item);
+    }
+}
+/// A renamed serde field or variant keeps its wire name unless it already sets one
+fn keep_serde_name(attrs: &mut Vec<syn::Attribute>, original: &Ident) {
+    if !serde_attrs(attrs).keys.contains("rename") {
+        let name = original.to_string();
+        attrs.push(parse_quote!(#[serde(rename = #name)]));
+    }
+}
+/// Protect FFI, bindgen and serde names in every file before any file is rewritten
+fn collect_interop_identifiers(files: &[PathBuf], context: &mut RenameContext) -> Result<usize> {
+    let before = context.protected_identifiers.len();
+    for file in files {
+        let content = mark_skip_comments(&fs::read_to_string(file)?);
+        context.protect_interop(&syn::parse_file(&content)?);
+    }
+    Ok(context.protected_identifiers.len() - before)
+}
+/// What the project itself declares. Type and trait names are kept everywhere, since
+/// declarations and uses of them are folded by different paths, and so are methods of
+/// trait impls, whose names belong to the trait
+#[derive(Default)]
+struct DeclarationCollector {
+    declared: HashSet<String>,
+    kept: HashSet<String>,
+    public: HashSet<String>,
+}
+impl DeclarationCollector {
+    fn declare(&mut self, ident: &Ident, vis: Option<&syn::Visibility>) {
+        self.declared.insert(ident.to_string());
+        if matches!(vis, Some(syn::Visibility::Public(_))) {
+            self.public.insert(ident.to_string());
+        }
+    }
+}
+impl<'ast> Visit<'ast> for DeclarationCollector {
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        self.declare(&item.sig.ident, Some(&item.vis));
+        syn::visit::visit_item_fn(self, item);
+    }
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        if item.trait_.is_some() {
+            for impl_item in &item.items {
+                match impl_item {
+                    syn::ImplItem::Fn(f) => self.kept.insert(f.sig.ident.to_string()),
+                    syn::ImplItem::Const(c) => self.kept.insert(c.ident.to_string()),
+                    syn::ImplItem::Type(t) => self.kept.insert(t.ident.to_string()),
+                    _ => false,
+                };
+            }
+        }
+        syn::visit::visit_item_impl(self, item);
+    }
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        self.declare(&item.sig.ident, Some(&item.vis));
+        syn::visit::visit_impl_item_fn(self, item);
+    }
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        self.declare(&item.sig.ident, None);
+        syn::visit::visit_trait_item_fn(self, item);
+    }
+    fn visit_field(&mut self, field: &'ast syn::Field) {
+        if let Some(ident) = &field.ident {
+            self.declare(ident, Some(&field.vis));
+        }
+        syn::visit::visit_field(self, field);
+    }
+    fn visit_variant(&mut self, variant: &'ast syn::Variant) {
+        self.declare(&variant.ident, None);
+        syn::visit::visit_variant(self, variant);
+    }
+    fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+        self.declare(&pat.ident, None);
+        syn::visit::visit_pat_ident(self, pat);
+    }
+    fn visit_item_const(&mut self, item: &'ast syn::ItemConst) {
+        self.declare(&item.ident, Some(&item.vis));
+        syn::visit::visit_item_const(self, item);
+    }
+    fn visit_item_static(&mut self, item: &'ast syn::ItemStatic) {
+        self.declare(&item.ident, Some(&item.vis));
+        syn::visit::visit_item_static(self, item);
+    }
+    fn visit_item_macro(&mut self, item: &'ast syn::ItemMacro) {
+        if let Some(ident) = &item.ident {
+            self.declare(ident, None);
+        }
+        syn::visit::visit_item_macro(self, item);
+    }
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        self.kept.insert(item.ident.to_string());
+        syn::visit::visit_item_struct(self, item);
+    }
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        self.kept.insert(item.ident.to_string());
+        syn::visit::visit_item_enum(self, item);
+    }
+    fn visit_item_union(&mut self, item: &'ast syn::ItemUnion) {
+        self.kept.insert(item.ident.to_string());
+        syn::visit::visit_item_union(self, item);
+    }
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.kept.insert(item.ident.to_string());
+        syn::visit::visit_item_trait(self, item);
+    }
+    fn visit_item_type(&mut self, item: &'ast syn::ItemType) {
+        self.kept.insert(item.ident.to_string());
+        syn::visit::visit_item_type(self, item);
+    }
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        // `mod name;` has to keep matching its file
+        self.kept.insert(item.ident.to_string());
+        syn::visit::visit_item_mod(self, item);
+    }
+}
+/// Limits renaming to names declared in `files`, with public ones known before the first use
+fn collect_declared_identifiers(files: &[PathBuf], context: &mut RenameContext) -> Result<usize> {
+    let mut collector = DeclarationCollector::default();
+    for file in files {
+        let content = mark_skip_comments(&fs::read_to_string(file)?);
+        collector.visit_file(&syn::parse_file(&content)?);
+    }
+    context.protected_identifiers.extend(collector.kept);
+    if context.config.preserve_pub {
+        context.public_api.extend(collector.public);
+    }
+    let count = collector.declared.len();
+    context.declared = Some(collector.declared);
+    Ok(count)
+}
+fn transform_source(content: &str, transformer: &mut ObfuscationTransformer) -> Result<String> {
+    let syntax_tree = syn::parse_file(&mark_skip_comments(content))?;
+    let transformed_tree = transformer.fold_file(syntax_tree);
+    Ok(unmark_skip_attrs(&prettyplease::unparse(&transformed_tree)))
+}
 impl RenameContext {
     pub fn new(config: ObfuscationConfig, seed: Option<&str>) -> Self {
         let rng_seed = if let Some(seed_str) = seed {
@@ -192,6 +797,22 @@ impl RenameContext {
             "HashMap",
             "HashSet",
             "format",
+            "Self",
+            "self",
+            "bool",
+            "char",
+            "u16",
+            "u32",
+            "u64",
+            "u128",
+            "usize",
+            "i16",
+            "i32",
+            "i64",
+            "i128",
+            "isize",
+            "f32",
+            "f64",
         ] {
             protected.insert(item.to_string());
         }
@@ -200,6 +821,7 @@ impl RenameContext {
             scope_stack: vec![Scope::new(0)],
             protected_identifiers: protected,
             public_api: HashSet::new(),
+            declared: None,
             current_module: vec![],
             rng,
             config,
@@ -229,12 +851,19 @@ impl RenameContext {
             scope.types.insert(name.to_string());
         }
     }
+    /// Names renaming would break outside Rust: C ABI, bindgen exports, serde wire names
+    pub fn protect_interop(&mut self, file: &syn::File) {
+        let mut collector = InteropCollector::default();
+        collector.visit_file(file);
+        self.protected_identifiers.extend(collector.protected);
+    }
     pub fn is_protected(&self, ident: &str) -> bool {
         self.protected_identifiers.contains(ident)
             || (self.config.preserve_pub && self.public_api.contains(ident))
     }
     pub fn should_rename(&self, ident: &str, min_len: usize) -> bool {
         !self.is_protected(ident) && ident.len() >= min_len
+            && self.declared.as_ref().is_none_or(|declared| declared.contains(ident))
             && ident.chars().next().unwrap().is_alphabetic() && !ident.starts_with('_')
     }
     pub fn get_or_create_mapping(&mut self, original: &str) -> String {
@@ -284,13 +913,28 @@ pub struct ObfuscationTransformer {
     pub context: RenameContext,
     pub string_mappings: HashMap<String, String>,
     pub dry_run: bool,
+    pub string_key: Option<String>,
+    /// Drawn from the OS on every run, even with `--seed`: the key is derived from it, so one
+    /// `--key` never encrypts two runs' strings under the same key and nonce sequence
+    pub key_salt: [u8; 16],
+    /// `scat strings` only encrypts literals and leaves every name alone
+    pub rename_identifiers: bool,
+    /// Literals in const and static initializers cannot become runtime calls
+    const_depth: usize,
 }
 impl ObfuscationTransformer {
     pub fn new(config: ObfuscationConfig, seed: Option<&str>, dry_run: bool) -> Self {
+        let context = RenameContext::new(config, seed);
+        let mut key_salt = [0u8; 16];
+        OsRng.try_fill_bytes(&mut key_salt).expect("OS random number generator unavailable");
         Self {
-            context: RenameContext::new(config, seed),
+            context,
             string_mappings: HashMap::new(),
             dry_run,
+            string_key: None,
+            key_salt,
+            rename_identifiers: true,
+            const_depth: 0,
         }
     }
     fn should_skip_string(&self, s: &str) -> bool {
@@ -313,53 +957,94 @@ impl ObfuscationTransformer {
         if let Some(encrypted) = self.string_mappings.get(original) {
             return encrypted.clone();
         }
+        let index = self.string_mappings.len() as u64;
         let encrypted = match self.context.config.string_encryption.algorithm.as_str() {
-            "aes" => self.encrypt_aes(original, key),
-            "chacha20" => self.encrypt_chacha20(original, key),
+            "aes" => self.encrypt_aes(original, key, index),
+            "chacha20" => self.encrypt_chacha20(original, key, index),
             "builtin" => self.encrypt_builtin(original, key),
             _ => self.simple_scramble(original),
         };
         self.string_mappings.insert(original.to_string(), encrypted.clone());
         encrypted
     }
-    fn encrypt_aes(&self, plaintext: &str, key_str: Option<&str>) -> String {
-        let key_str = key_str.unwrap_or("default-scat-key-for-aes-encryption");
-        let hash = Sha256::digest(key_str.as_bytes());
-        let key = Key::<Aes256Gcm>::from_slice(&hash[..32]);
+    fn encrypt_aes(&self, plaintext: &str, key_str: Option<&str>, index: u64) -> String {
+        let hash = derive_string_key("aes", key_str, &self.key_salt);
+        let key = Key::<Aes256Gcm>::from_slice(&hash);
         let cipher = Aes256Gcm::new(key);
-        let nonce = Nonce::from_slice(b"unique_nonce");
+        let nonce = derive_nonce(&hash, &self.key_salt, index);
         let ciphertext = cipher
-            .encrypt(nonce, plaintext.as_bytes())
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
             .expect("encryption failure");
-        general_purpose::STANDARD.encode(&ciphertext)
+        general_purpose::STANDARD.encode([nonce.as_slice(), &ciphertext].concat())
     }
     fn encrypt_builtin(&self, plaintext: &str, key_str: Option<&str>) -> String {
-        let key_str = key_str.unwrap_or("default-scat-key-for-builtin-encryption");
-        let key_bytes = Sha256::digest(key_str.as_bytes());
-        let mut result = String::new();
-        for (i, ch) in plaintext.chars().enumerate() {
-            let key_byte = key_bytes[i % 32];
-            let encrypted_char = ((ch as u8) ^ key_byte) as char;
-            result.push(encrypted_char);
-        }
-        general_purpose::STANDARD.encode(result.as_bytes())
-    }
-    fn encrypt_chacha20(&self, plaintext: &str, key_str: Option<&str>) -> String {
-        let key_str = key_str.unwrap_or("default-scat-key-for-chacha20-encryption");
-        let hash = Sha256::digest(key_str.as_bytes());
-        let key = ChaChaKey::from_slice(&hash[..32]);
+        let key_bytes = derive_string_key("builtin", key_str, &self.key_salt);
+        let result: Vec<u8> = plaintext
+            .bytes()
+            .enumerate()
+            .map(|(i, b)| b ^ key_bytes[i % 32])
+            .collect();
+        general_purpose::STANDARD.encode(result)
+    }
+    fn encrypt_chacha20(&self, plaintext: &str, key_str: Option<&str>, index: u64) -> String {
+        let hash = derive_string_key("chacha20", key_str, &self.key_salt);
+        let key = ChaChaKey::from_slice(&hash);
         let cipher = ChaCha20Poly1305::new(&key);
-        let nonce = ChaChaNonce::from_slice(b"unique_nonce");
+        let nonce = derive_nonce(&hash, &self.key_salt, index);
         let ciphertext = cipher
-            .encrypt(nonce, plaintext.as_bytes())
+            .encrypt(ChaChaNonce::from_slice(&nonce), plaintext.as_bytes())
             .expect("encryption failure");
-        general_purpose::STANDARD.encode(&ciphertext)
+        general_purpose::STANDARD.encode([nonce.as_slice(), &ciphertext].concat())
     }
     fn simple_scramble(&self, input: &str) -> String {
         input.chars().rev().collect::<String>()
     }
+    pub fn string_key_bytes(&self) -> [u8; 32] {
+        derive_string_key(
+            &self.context.config.string_encryption.algorithm,
+            self.string_key.as_deref(),
+            &self.key_salt,
+        )
+    }
+    pub fn key_metadata(&self) -> KeyMetadata {
+        KeyMetadata {
+            algorithm: self.context.config.string_encryption.algorithm.clone(),
+            kdf: STRING_KDF.to_string(),
+            salt: general_purpose::STANDARD.encode(self.key_salt),
+            key_check: key_check(&self.string_key_bytes()),
+            default_key: self.string_key.is_none(),
+            strings: self.string_mappings.values().cloned().collect(),
+        }
+    }
     fn should_obfuscate_ident(&self, ident: &Ident) -> bool {
-        self.context.should_rename(&ident.to_string(), self.context.config.min_len)
+        self.rename_identifiers
+            && self.context.should_rename(&ident.to_string(), self.context.config.min_len)
+    }
+    /// Macro arguments are plain tokens, so names in them follow the same mapping;
+    /// keywords and `$metavariables` are left alone
+    fn rename_tokens(&mut self, tokens: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        use proc_macro2::{Group, TokenTree};
+        let mut after_dollar = false;
+        tokens
+            .into_iter()
+            .map(|tree| {
+                let tree = match tree {
+                    TokenTree::Ident(ident)
+                        if !after_dollar && syn::parse_str::<Ident>(&ident.to_string()).is_ok() =>
+                    {
+                        TokenTree::Ident(self.fold_ident(ident))
+                    }
+                    TokenTree::Group(group) => {
+                        let mut renamed = Group::new(group.delimiter(), self.rename_tokens(group.stream()));
+                        renamed.set_span(group.span());
+                        TokenTree::Group(renamed)
+                    }
+                    other => other,
+                };
+                after_dollar = matches!(&tree, TokenTree::Punct(p) if p.as_char() == '$');
+                tree
+            })
+            .collect()
     }
 }
 impl ObfuscationTransformer {
@@ -466,11 +1151,74 @@ impl ObfuscationTransformer {
         )
     }
     fn inject_string_decryption(&mut self, encrypted: &str) -> Expr {
-        let decrypt_call: ExprCall = parse_quote!(decrypt_scat_string(# encrypted));
+        let decrypt_call: ExprCall = parse_quote!(
+            crate::scat_runtime::decrypt_scat_string(# encrypted)
+        );
         Expr::Call(decrypt_call)
     }
+    fn in_const<T>(&mut self, fold: impl FnOnce(&mut Self) -> T) -> T {
+        self.const_depth += 1;
+        let folded = fold(self);
+        self.const_depth -= 1;
+        folded
+    }
 }
 impl Fold for ObfuscationTransformer {
+    fn fold_item(&mut self, item: Item) -> Item {
+        if has_skip_attr(item_attrs(&item)) {
+            return item;
+        }
+        syn::fold::fold_item(self, item)
+    }
+    fn fold_impl_item(&mut self, item: syn::ImplItem) -> syn::ImplItem {
+        if has_skip_attr(impl_item_attrs(&item)) {
+            return item;
+        }
+        syn::fold::fold_impl_item(self, item)
+    }
+    fn fold_trait_item(&mut self, item: syn::TraitItem) -> syn::TraitItem {
+        if has_skip_attr(trait_item_attrs(&item)) {
+            return item;
+        }
+        syn::fold::fold_trait_item(self, item)
+    }
+    fn fold_item_const(&mut self, item: syn::ItemConst) -> syn::ItemConst {
+        self.in_const(|t| syn::fold::fold_item_const(t, item))
+    }
+    fn fold_item_static(&mut self, item: syn::ItemStatic) -> syn::ItemStatic {
+        self.in_const(|t| syn::fold::fold_item_static(t, item))
+    }
+    fn fold_impl_item_const(&mut self, item: syn::ImplItemConst) -> syn::ImplItemConst {
+        self.in_const(|t| syn::fold::fold_impl_item_const(t, item))
+    }
+    fn fold_impl_item_fn(&mut self, item: syn::ImplItemFn) -> syn::ImplItemFn {
+        if item.sig.constness.is_some() {
+            return self.in_const(|t| syn::fold::fold_impl_item_fn(t, item));
+        }
+        syn::fold::fold_impl_item_fn(self, item)
+    }
+    fn fold_trait_item_const(&mut self, item: syn::TraitItemConst) -> syn::TraitItemConst {
+        self.in_const(|t| syn::fold::fold_trait_item_const(t, item))
+    }
+    fn fold_expr_const(&mut self, expr: syn::ExprConst) -> syn::ExprConst {
+        self.in_const(|t| syn::fold::fold_expr_const(t, expr))
+    }
+    /// Attribute names and arguments belong to the compiler and to derive macros
+    fn fold_attribute(&mut self, attr: syn::Attribute) -> syn::Attribute {
+        attr
+    }
+    fn fold_macro(&mut self, mut mac: syn::Macro) -> syn::Macro {
+        if !mac.path.is_ident("macro_rules") {
+            mac.tokens = self.rename_tokens(mac.tokens);
+        }
+        mac
+    }
+    fn fold_local(&mut self, local: syn::Local) -> syn::Local {
+        if has_skip_attr(&local.attrs) {
+            return local;
+        }
+        syn::fold::fold_local(self, local)
+    }
     fn fold_ident(&mut self, ident: Ident) -> Ident {
         let name = ident.to_string();
         if self.should_obfuscate_ident(&ident) {
@@ -505,8 +1253,9 @@ impl Fold for ObfuscationTransformer {
             Expr::Lit(mut lit_expr) => {
                 if let syn::Lit::Str(ref lit_str) = lit_expr.lit {
                     let original = lit_str.value();
-                    if !self.should_skip_string(&original) {
-                        let encrypted = self.encrypt_string(&original, None);
+                    if self.const_depth == 0 && !self.should_skip_string(&original) {
+                        let key = self.string_key.clone();
+                        let encrypted = self.encrypt_string(&original, key.as_deref());
                         if encrypted != original && !self.dry_run {
                             return self.inject_string_decryption(&encrypted);
                         } else if self.dry_run {
@@ -539,7 +1288,14 @@ impl Fold for ObfuscationTransformer {
         }
         self.context.add_function(&item.sig.ident.to_string());
         item.sig = self.fold_signature(item.sig);
-        item.block = Box::new(self.fold_block(*item.block));
+        let block = *item.block;
+        item.block = Box::new(
+            if item.sig.constness.is_some() {
+                self.in_const(|t| t.fold_block(block))
+            } else {
+                self.fold_block(block)
+            },
+        );
         item
     }
     fn fold_item_struct(&mut self, mut item: syn::ItemStruct) -> syn::ItemStruct {
@@ -549,6 +1305,7 @@ impl Fold for ObfuscationTransformer {
             self.context.public_api.insert(item.ident.to_string());
         }
         self.context.add_type(&item.ident.to_string());
+        let serde_names = derives_serde(&item.attrs);
         for field in &mut item.fields {
             if let Some(ref mut ident) = &mut field.ident {
                 if self.should_obfuscate_ident(ident) {
@@ -558,6 +1315,9 @@ impl Fold for ObfuscationTransformer {
                     if self.dry_run {
                         println!("🔄 Would rename field: {} -> {}", ident, new_name);
                     } else {
+                        if serde_names {
+                            keep_serde_name(&mut field.attrs, ident);
+                        }
                         *ident = Ident::new(&new_name, ident.span());
                     }
                 }
@@ -572,6 +1332,7 @@ impl Fold for ObfuscationTransformer {
             self.context.public_api.insert(item.ident.to_string());
         }
         self.context.add_type(&item.ident.to_string());
+        let serde_names = derives_serde(&item.attrs);
         for variant in &mut item.variants {
             if self.should_obfuscate_ident(&variant.ident) {
                 let new_name = self
@@ -582,6 +1343,9 @@ impl Fold for ObfuscationTransformer {
                         "🔄 Would rename variant: {} -> {}", variant.ident, new_name
                     );
                 } else {
+                    if serde_names {
+                        keep_serde_name(&mut variant.attrs, &variant.ident);
+                    }
                     variant.ident = Ident::new(&new_name, variant.ident.span());
                 }
             }
@@ -628,11 +1392,15 @@ impl Fold for ObfuscationTransformer {
 }
 pub fn handle_scat_command(args: ScatArgs) -> Result<()> {
     match args.command {
-        ScatCommand::Names { path, map, sequential } => {
-            handle_names_obfuscation(&path, map.as_ref(), sequential)?;
+        ScatCommand::Names { path, filter, map, sequential } => {
+            let filter = FileFilter::new(&filter)?;
+            let undo = crate::undo::Recorder::capture("scat names", &path.display().to_string(), crate::audit::rust_sources(&path))?;
+            handle_names_obfuscation(&path, &filter, map.as_ref(), sequential)?;
+            undo.commit();
         }
         ScatCommand::Code {
             path,
+            filter,
             preserve_pub,
             min_len,
             map,
@@ -641,8 +1409,15 @@ pub fn handle_scat_command(args: ScatArgs) -> Result<()> {
             backup,
             seed,
         } => {
+            let filter = FileFilter::new(&filter)?;
+            let undo = if dry_run {
+                None
+            } else {
+                Some(crate::undo::Recorder::capture("scat code", &path.display().to_string(), crate::audit::rust_sources(&path))?)
+            };
             handle_code_obfuscation(
                 &path,
+                &filter,
                 preserve_pub,
                 min_len,
                 map.as_ref(),
@@ -651,28 +1426,54 @@ pub fn handle_scat_command(args: ScatArgs) -> Result<()> {
                 backup,
                 seed.as_deref(),
             )?;
+            if let Some(undo) = undo {
+                undo.commit();
+            }
         }
-        ScatCommand::Strings { path, key, map, algorithm, skip_format, skip_errors } => {
+        ScatCommand::Strings {
+            path,
+            filter,
+            key,
+            map,
+            algorithm,
+            skip_format,
+            skip_errors,
+        } => {
+            let filter = FileFilter::new(&filter)?;
+            let undo = crate::undo::Recorder::capture("scat strings", &path.display().to_string(), crate::audit::rust_sources(&path))?;
             handle_string_scrambling(
                 &path,
+                &filter,
                 key.as_deref(),
                 map.as_ref(),
                 &algorithm,
                 skip_format,
                 skip_errors,
             )?;
+            undo.commit();
+        }
+        ScatCommand::Pack { input, output, compress, encrypt, self_extract, filter } => {
+            handle_file_packing(
+                &input,
+                &output,
+                compress,
+                encrypt,
+                self_extract,
+                &FileFilter::new(&filter)?,
+            )?;
         }
-        ScatCommand::Pack { input, output, compress } => {
-            handle_file_packing(&input, &output, compress)?;
+        ScatCommand::Unpack { input, map, output, filter } => {
+            handle_unpack(&input, map.as_ref(), output.as_ref(), &FileFilter::new(&filter)?)?;
         }
-        ScatCommand::Unpack { input, map, output } => {
-            handle_unpack(&input, &map, output.as_ref())?;
+        ScatCommand::Verify { path, filter, map, key } => {
+            handle_verify(&path, &FileFilter::new(&filter)?, &map, key.as_deref())?;
         }
     }
     Ok(())
 }
 fn handle_names_obfuscation(
     path: &PathBuf,
+    filter: &FileFilter,
     map_path: Option<&PathBuf>,
     sequential: bool,
 ) -> Result<()> {
@@ -694,7 +1495,7 @@ fn handle_names_obfuscation(
         },
     };
     let mut mapping = ObfuscationMapping::new("names", config, None);
-    obfuscate_names_recursive(path, &mut mapping, sequential)?;
+    obfuscate_names_recursive(path, filter, &mut mapping, sequential)?;
     if let Some(map_file) = map_path {
         mapping.save_to_file(map_file)?;
     } else {
@@ -707,6 +1508,7 @@ fn handle_names_obfuscation(
 }
 fn obfuscate_names_recursive(
     dir: &PathBuf,
+    filter: &FileFilter,
     mapping: &mut ObfuscationMapping,
     sequential: bool,
 ) -> Result<()> {
@@ -714,7 +1516,13 @@ fn obfuscate_names_recursive(
     for entry in WalkDir::new(dir).min_depth(1) {
         let entry = entry?;
         let path = entry.path();
-        if path.is_file() || path.is_dir() {
+        // With filters, renaming a directory would move excluded files too; rename matching files only
+        let selected = if path.is_dir() {
+            !filter.is_active()
+        } else {
+            filter.allows(path.strip_prefix(dir)?)
+        };
+        if selected && (path.is_file() || path.is_dir()) {
             let file_name = path
                 .file_name()
                 .and_then(|n| n.to_str())
@@ -752,6 +1560,7 @@ fn obfuscate_names_recursive(
 }
 fn handle_code_obfuscation(
     path: &PathBuf,
+    filter: &FileFilter,
     preserve_pub: bool,
     min_len: usize,
     map_path: Option<&PathBuf>,
@@ -774,50 +1583,19 @@ fn handle_code_obfuscation(
             skip_errors: true,
         },
     };
+    if path.is_file() && path.extension().and_then(|s| s.to_str()) != Some("rs") {
+        return Err(anyhow::anyhow!("Not a Rust file: {}", path.display()));
+    }
+    let files = filter.rust_files(path);
     if !dry_run {
-        perform_safety_checks(&path)?;
+        perform_safety_checks(&files)?;
     }
     if backup && !dry_run {
         create_backup(path)?;
     }
-    let mut transformer = ObfuscationTransformer::new(config.clone(), seed, dry_run);
-    let mut processed_files = 0;
-    let mut total_mappings = 0;
-    if path.is_dir() {
-        for entry in WalkDir::new(path) {
-            let entry = entry?;
-            let file_path = entry.path();
-            if file_path.extension().and_then(|s| s.to_str()) == Some("rs")
-                && !file_path.to_string_lossy().contains("target/")
-                && !file_path.to_string_lossy().contains(".git/")
-            {
-                let mappings = obfuscate_rust_file_ast(
-                    &file_path.to_path_buf(),
-                    &mut transformer,
-                )?;
-                processed_files += 1;
-                total_mappings += mappings;
-            }
-        }
-    } else if path.extension().and_then(|s| s.to_str()) == Some("rs") {
-        let mappings = obfuscate_rust_file_ast(&path.to_path_buf(), &mut transformer)?;
-        processed_files += 1;
-        total_mappings += mappings;
-    } else {
-        return Err(anyhow::anyhow!("Not a Rust file: {}", path.display()));
-    }
-    if control_flow && !dry_run {
-        println!("🔄 Applying control flow obfuscation...");
-        apply_control_flow_obfuscation(path)?;
-    }
-    let mapping = ObfuscationMapping::new("code", config, seed);
-    let mut final_mapping = mapping.clone();
-    for (original, obfuscated) in &transformer.context.mappings {
-        final_mapping.add_mapping(original.clone(), obfuscated.clone());
-    }
-    for (original, encrypted) in &transformer.string_mappings {
-        final_mapping.add_mapping(original.clone(), encrypted.clone());
-    }
+    let (transformer, final_mapping) = obfuscate_code(path, &files, config, seed, dry_run)?;
+    let processed_files = files.len();
+    let total_mappings = final_mapping.original_to_obfuscated.len();
     if let Some(map_file) = map_path {
         final_mapping.save_to_file(map_file)?;
     } else {
@@ -842,7 +1620,7 @@ fn handle_code_obfuscation(
         println!("📊 Identifiers obfuscated: {}", transformer.context.mappings.len());
         println!("📊 Strings encrypted: {}", transformer.string_mappings.len());
         println!("📊 Total mappings created: {}", total_mappings);
-        if validate_obfuscated_code(path)? {
+        if validate_obfuscated_code(&files)? {
             println!("✅ Validation passed - obfuscated code compiles successfully!");
         } else {
             println!("⚠️  Warning: Obfuscated code may have compilation issues");
@@ -850,15 +1628,59 @@ fn handle_code_obfuscation(
     }
     Ok(())
 }
+/// Renames and encrypts `files` in place, wiring in the runtime when strings were encrypted.
+/// Shared by `scat code` and `cm release-harden`
+pub(crate) fn obfuscate_code(
+    path: &Path,
+    files: &[PathBuf],
+    config: ObfuscationConfig,
+    seed: Option<&str>,
+    dry_run: bool,
+) -> Result<(ObfuscationTransformer, ObfuscationMapping)> {
+    let mut transformer = ObfuscationTransformer::new(config.clone(), seed, dry_run);
+    let skipped = collect_skipped_identifiers(files, &mut transformer.context)?;
+    if skipped > 0 {
+        println!("⏭️  {} identifier(s) kept by scat:skip", skipped);
+    }
+    let interop = collect_interop_identifiers(files, &mut transformer.context)?;
+    if interop > 0 {
+        println!("🔌 {} identifier(s) kept for FFI, bindgen and serde", interop);
+    }
+    collect_declared_identifiers(files, &mut transformer.context)?;
+    for file_path in files {
+        obfuscate_rust_file_ast(file_path, &mut transformer)?;
+    }
+    if !transformer.string_mappings.is_empty() && !dry_run {
+        inject_decryption_runtime(
+            &path.to_path_buf(),
+            files,
+            &transformer.context.config.string_encryption.algorithm,
+            &transformer.string_key_bytes(),
+        )?;
+    }
+    if transformer.context.config.control_flow && !dry_run {
+        println!("🔄 Applying control flow obfuscation...");
+        apply_control_flow_obfuscation(files)?;
+    }
+    let mut final_mapping = ObfuscationMapping::new("code", config, seed);
+    for (original, obfuscated) in &transformer.context.mappings {
+        final_mapping.add_mapping(original.clone(), obfuscated.clone());
+    }
+    for (original, encrypted) in &transformer.string_mappings {
+        final_mapping.add_mapping(original.clone(), encrypted.clone());
+    }
+    if !transformer.string_mappings.is_empty() {
+        final_mapping.key = Some(transformer.key_metadata());
+    }
+    Ok((transformer, final_mapping))
+}
 fn obfuscate_rust_file_ast(
     path: &PathBuf,
     transformer: &mut ObfuscationTransformer,
 ) -> Result<usize> {
     let content = fs::read_to_string(path)?;
-    let syntax_tree = syn::parse_file(&content)?;
-    let transformed_tree = transformer.fold_file(syntax_tree);
+    let transformed_content = transform_source(&content, transformer)?;
     if !transformer.dry_run {
-        let transformed_content = prettyplease::unparse(&transformed_tree);
         fs::write(path, transformed_content)?;
     }
     Ok(transformer.context.mappings.len() + transformer.string_mappings.len())
@@ -901,17 +1723,9 @@ fn copy_dir_recursively(src: &PathBuf, dst: &PathBuf) -> Result<()> {
     }
     Ok(())
 }
-fn apply_control_flow_obfuscation(path: &PathBuf) -> Result<()> {
-    if path.is_dir() {
-        for entry in WalkDir::new(path) {
-            let entry = entry?;
-            let file_path = entry.path();
-            if file_path.extension().and_then(|s| s.to_str()) == Some("rs") {
-                apply_control_flow_to_file(&file_path.to_path_buf())?;
-            }
-        }
-    } else {
-        apply_control_flow_to_file(path)?;
+fn apply_control_flow_obfuscation(files: &[PathBuf]) -> Result<()> {
+    for file_path in files {
+        apply_control_flow_to_file(file_path)?;
     }
     Ok(())
 }
@@ -919,34 +1733,17 @@ fn apply_control_flow_to_file(path: &PathBuf) -> Result<()> {
     println!("⚠️  Control flow obfuscation skipped (not yet implemented)");
     Ok(())
 }
-fn perform_safety_checks(path: &PathBuf) -> Result<()> {
+pub(crate) fn perform_safety_checks(files: &[PathBuf]) -> Result<()> {
     println!("🔍 Performing safety checks...");
-    let dangerous_patterns = vec![
-        "std::mem::transmute", "#[no_mangle]", "extern \"C\"", "#[link", "asm!",
-        "global_asm!",
-    ];
+    // Exported symbols and extern blocks keep their names, so only code that depends on
+    // layout or raw assembly is refused
+    let dangerous_patterns = vec!["std::mem::transmute", "asm!", "global_asm!"];
     let mut found_dangerous = Vec::new();
-    if path.is_dir() {
-        for entry in WalkDir::new(path) {
-            let entry = entry?;
-            let file_path = entry.path();
-            if file_path.extension().and_then(|s| s.to_str()) == Some("rs")
-                && !file_path.to_string_lossy().contains("target/")
-            {
-                let content = fs::read_to_string(file_path)?;
-                for pattern in &dangerous_patterns {
-                    if content.contains(pattern) {
-                        found_dangerous
-                            .push(format!("{}: {}", file_path.display(), pattern));
-                    }
-                }
-            }
-        }
-    } else {
-        let content = fs::read_to_string(path)?;
+    for file_path in files {
+        let content = fs::read_to_string(file_path)?;
         for pattern in &dangerous_patterns {
             if content.contains(pattern) {
-                found_dangerous.push(format!("{}: {}", path.display(), pattern));
+                found_dangerous.push(format!("{}: {}", file_path.display(), pattern));
             }
         }
     }
@@ -957,27 +1754,16 @@ fn perform_safety_checks(path: &PathBuf) -> Result<()> {
         }
         return Err(
             anyhow::anyhow!(
-                "Refusing to obfuscate code with dangerous patterns. Use --dry-run to see what would be changed."
+                "Refusing to obfuscate code with dangerous patterns. Leave those files out with --exclude, or use --dry-run to see what would be changed."
             ),
         );
     }
     println!("✅ Safety checks passed!");
     Ok(())
 }
-fn validate_obfuscated_code(path: &PathBuf) -> Result<bool> {
-    if path.is_dir() {
-        for entry in WalkDir::new(path) {
-            let entry = entry?;
-            let file_path = entry.path();
-            if file_path.extension().and_then(|s| s.to_str()) == Some("rs") {
-                let content = fs::read_to_string(file_path)?;
-                if syn::parse_file(&content).is_err() {
-                    return Ok(false);
-                }
-            }
-        }
-    } else {
-        let content = fs::read_to_string(path)?;
+fn validate_obfuscated_code(files: &[PathBuf]) -> Result<bool> {
+    for file_path in files {
+        let content = fs::read_to_string(file_path)?;
         if syn::parse_file(&content).is_err() {
             return Ok(false);
         }
@@ -1030,6 +1816,7 @@ echo "📁 Obfuscated code backed up to: {original_path}.pre_reversal
 }
 fn handle_string_scrambling(
     path: &PathBuf,
+    filter: &FileFilter,
     key: Option<&str>,
     map_path: Option<&PathBuf>,
     algorithm: &str,
@@ -1052,41 +1839,32 @@ fn handle_string_scrambling(
         string_encryption: string_config,
     };
     config.string_encryption.algorithm = algorithm.to_string();
+    if path.is_file() && path.extension().and_then(|s| s.to_str()) != Some("rs") {
+        return Err(anyhow::anyhow!("Not a Rust file: {}", path.display()));
+    }
+    let files = filter.rust_files(path);
     let mut transformer = ObfuscationTransformer::new(config.clone(), None, false);
+    transformer.string_key = key.map(|k| k.to_string());
+    transformer.rename_identifiers = false;
+    collect_skipped_identifiers(&files, &mut transformer.context)?;
+    collect_interop_identifiers(&files, &mut transformer.context)?;
     let mut processed_files = 0;
-    if path.is_dir() {
-        for entry in WalkDir::new(path) {
-            let entry = entry?;
-            let file_path = entry.path();
-            if file_path.extension().and_then(|s| s.to_str()) == Some("rs")
-                && !file_path.to_string_lossy().contains("target/")
-            {
-                let content = fs::read_to_string(file_path)?;
-                let syntax_tree = syn::parse_file(&content)?;
-                let transformed_tree = transformer.fold_file(syntax_tree);
-                let transformed_content = prettyplease::unparse(&transformed_tree);
-                fs::write(file_path, transformed_content)?;
-                processed_files += 1;
-            }
-        }
-    } else if path.extension().and_then(|s| s.to_str()) == Some("rs") {
-        let content = fs::read_to_string(path)?;
-        let syntax_tree = syn::parse_file(&content)?;
-        let transformed_tree = transformer.fold_file(syntax_tree);
-        let transformed_content = prettyplease::unparse(&transformed_tree);
-        fs::write(path, transformed_content)?;
+    for file_path in &files {
+        let content = fs::read_to_string(file_path)?;
+        fs::write(file_path, transform_source(&content, &mut transformer)?)?;
         processed_files += 1;
-    } else {
-        return Err(anyhow::anyhow!("Not a Rust file: {}", path.display()));
     }
     if !transformer.string_mappings.is_empty() {
-        inject_decryption_runtime(path, algorithm, key)?;
+        inject_decryption_runtime(path, &files, algorithm, &transformer.string_key_bytes())?;
     }
     let mapping = ObfuscationMapping::new("strings", config.clone(), None);
     let mut final_mapping = mapping.clone();
     for (original, encrypted) in &transformer.string_mappings {
         final_mapping.add_mapping(original.clone(), encrypted.clone());
     }
+    if !transformer.string_mappings.is_empty() {
+        final_mapping.key = Some(transformer.key_metadata());
+    }
     if let Some(map_file) = map_path {
         final_mapping.save_to_file(map_file)?;
     } else {
@@ -1098,252 +1876,410 @@ fn handle_string_scrambling(
     println!("📊 Strings encrypted: {}", transformer.string_mappings.len());
     Ok(())
 }
+const RUNTIME_MODULE: &str = "scat_runtime";
+const STRING_KDF: &str = "hkdf-sha256";
+/// HKDF over the passphrase, salted per run; without `--key` a per-algorithm default stands in
+fn derive_string_key(algorithm: &str, key: Option<&str>, salt: &[u8]) -> [u8; 32] {
+    let key = key
+        .map(|k| k.to_string())
+        .unwrap_or_else(|| format!("default-scat-key-for-{}-encryption", algorithm));
+    let mut okm = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(salt), key.as_bytes())
+        .expand(format!("scat-string-key:{}", algorithm).as_bytes(), &mut okm)
+        .expect("32 bytes is a valid HKDF-SHA256 length");
+    okm
+}
+/// Nonce for the string at `index`; it travels in front of the ciphertext
+fn derive_nonce(key: &[u8; 32], salt: &[u8], index: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    Hkdf::<Sha256>::new(Some(salt), key)
+        .expand(&[b"scat-nonce:".as_slice(), &index.to_le_bytes()].concat(), &mut nonce)
+        .expect("12 bytes is a valid HKDF-SHA256 length");
+    nonce
+}
+/// Fingerprint that tells a wrong `--key` apart from corrupted ciphertext
+fn key_check(key: &[u8; 32]) -> String {
+    Sha256::digest([b"scat-key-check".as_slice(), key].concat())[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+fn decrypt_string(algorithm: &str, key: &[u8; 32], encrypted: &str) -> Result<String> {
+    if !matches!(algorithm, "aes" | "chacha20" | "builtin") {
+        return Ok(encrypted.chars().rev().collect());
+    }
+    let bytes = general_purpose::STANDARD.decode(encrypted)?;
+    let plain = if algorithm == "builtin" {
+        bytes.iter().enumerate().map(|(i, b)| b ^ key[i % 32]).collect()
+    } else {
+        if bytes.len() < 12 {
+            return Err(anyhow::anyhow!("ciphertext is shorter than its nonce"));
+        }
+        let (nonce, body) = bytes.split_at(12);
+        let decrypted = if algorithm == "aes" {
+            Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+                .decrypt(Nonce::from_slice(nonce), body)
+        } else {
+            ChaCha20Poly1305::new(ChaChaKey::from_slice(key))
+                .decrypt(ChaChaNonce::from_slice(nonce), body)
+        };
+        decrypted.map_err(|_| anyhow::anyhow!("authentication failed"))?
+    };
+    Ok(String::from_utf8(plain)?)
+}
+/// String literals passed to `decrypt_scat_string`, wherever the call sits
+#[derive(Default)]
+struct RuntimeCallCollector {
+    literals: Vec<String>,
+}
+impl<'ast> Visit<'ast> for RuntimeCallCollector {
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(func) = &*call.func {
+            if func.path.segments.last().is_some_and(|s| s.ident == "decrypt_scat_string") {
+                if let Some(Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. })) = call.args.first() {
+                    self.literals.push(s.value());
+                }
+            }
+        }
+        syn::visit::visit_expr_call(self, call);
+    }
+}
+fn handle_verify(
+    path: &Path,
+    filter: &FileFilter,
+    map: &PathBuf,
+    key: Option<&str>,
+) -> Result<()> {
+    println!("🔍 Verifying encrypted strings in: {}", path.display());
+    let mapping = ObfuscationMapping::load_from_file(map)?;
+    let Some(meta) = &mapping.key else {
+        return Err(
+            anyhow::anyhow!(
+                "{} has no key metadata; re-run scat strings to record it", map.display()
+            ),
+        );
+    };
+    if meta.kdf != STRING_KDF {
+        return Err(anyhow::anyhow!("Unsupported key derivation: {}", meta.kdf));
+    }
+    if key.is_none() && !meta.default_key {
+        return Err(anyhow::anyhow!("Strings were encrypted with --key; pass the same --key"));
+    }
+    let salt = general_purpose::STANDARD.decode(&meta.salt)?;
+    let key_bytes = derive_string_key(&meta.algorithm, key, &salt);
+    if key_check(&key_bytes) != meta.key_check {
+        return Err(anyhow::anyhow!("Key does not match the one recorded in {}", map.display()));
+    }
+    let mut failures = Vec::new();
+    for encrypted in &meta.strings {
+        match decrypt_string(&meta.algorithm, &key_bytes, encrypted) {
+            Ok(plain) if mapping.obfuscated_to_original.get(encrypted) == Some(&plain) => {}
+            Ok(_) => failures.push(format!("{}: decrypts to a different string", encrypted)),
+            Err(e) => failures.push(format!("{}: {}", encrypted, e)),
+        }
+    }
+    let mut calls = 0;
+    for file in filter.rust_files(path) {
+        let mut collector = RuntimeCallCollector::default();
+        collector.visit_file(&syn::parse_file(&fs::read_to_string(&file)?)?);
+        for encrypted in collector.literals {
+            calls += 1;
+            if let Err(e) = decrypt_string(&meta.algorithm, &key_bytes, &encrypted) {
+                failures.push(format!("{}: \"{}\": {}", file.display(), encrypted, e));
+            }
+        }
+    }
+    if !failures.is_empty() {
+        for failure in &failures {
+            println!("  ❌ {}", failure);
+        }
+        return Err(
+            anyhow::anyhow!("{} encrypted string(s) failed verification", failures.len()),
+        );
+    }
+    println!(
+        "✅ {} mapped string(s) and {} call(s) in source decrypt correctly", meta.strings
+        .len(), calls
+    );
+    Ok(())
+}
+/// Extra crates the generated runtime needs in the obfuscated project
+fn runtime_dependencies(algorithm: &str) -> &'static [(&'static str, &'static str)] {
+    match algorithm {
+        "aes" => &[("aes-gcm", "0.10")],
+        "chacha20" => &[("chacha20poly1305", "0.10")],
+        _ => &[],
+    }
+}
+/// Write `scat_runtime` into every crate root of the obfuscated tree and add the crates
+/// it needs, so the `crate::scat_runtime::decrypt_scat_string` calls resolve
 fn inject_decryption_runtime(
     path: &PathBuf,
+    files: &[PathBuf],
     algorithm: &str,
-    key: Option<&str>,
+    key: &[u8; 32],
 ) -> Result<()> {
-    if algorithm == "simple" {
+    let runtime = generate_decryption_runtime(algorithm, key);
+    let roots: Vec<&PathBuf> = if path.is_dir() {
+        files.iter().filter(|f| is_crate_root(f)).collect()
+    } else {
+        vec![path]
+    };
+    if roots.is_empty() {
+        println!("⚠️  Warning: No main.rs or lib.rs found to inject decryption runtime");
         return Ok(());
     }
-    let decryption_code = generate_decryption_runtime(algorithm, key);
-    if path.is_dir() {
-        let mut main_file = None;
-        for entry in WalkDir::new(path) {
-            let entry = entry?;
-            let file_path = entry.path();
-            if file_path.file_name().and_then(|n| n.to_str()) == Some("main.rs")
-                || file_path.file_name().and_then(|n| n.to_str()) == Some("lib.rs")
-            {
-                main_file = Some(file_path.to_path_buf());
-                break;
-            }
-        }
-        if let Some(main_file) = main_file {
-            inject_into_file(&main_file, &decryption_code)?;
+    for root in roots {
+        let named_root = matches!(
+            root.file_name().and_then(|n| n.to_str()), Some("main.rs" | "lib.rs")
+        );
+        let declaration = if named_root {
+            fs::write(root.with_file_name(format!("{}.rs", RUNTIME_MODULE)), &runtime)?;
+            format!("mod {};\n", RUNTIME_MODULE)
         } else {
-            println!(
-                "⚠️  Warning: No main.rs or lib.rs found to inject decryption runtime"
-            );
+            format!("mod {} {{\n{}}}\n", RUNTIME_MODULE, runtime)
+        };
+        let mut content = fs::read_to_string(root)?;
+        if !content.contains(&format!("mod {}", RUNTIME_MODULE)) {
+            content.insert_str(find_insertion_point(&content), &declaration);
+            fs::write(root, content)?;
         }
-    } else {
-        inject_into_file(path, &decryption_code)?;
+        println!("🔓 Decryption runtime injected into: {}", root.display());
     }
-    Ok(())
+    add_runtime_dependencies(path, algorithm)
 }
-fn inject_extern_crates(path: &PathBuf, algorithm: &str) -> Result<()> {
-    let _path = path;
-    let _algorithm = algorithm;
-    Ok(())
+/// `main.rs`/`lib.rs`, or a single-file binary under `src/bin`
+fn is_crate_root(file: &Path) -> bool {
+    let name = file.file_name().and_then(|n| n.to_str());
+    matches!(name, Some("main.rs" | "lib.rs"))
+        || file.parent().and_then(|p| p.file_name()).and_then(|n| n.to_str()) == Some("bin")
 }
-fn generate_decryption_runtime(algorithm: &str, key: Option<&str>) -> String {
-    let key = key.unwrap_or("default-scat-key-for-decryption");
-    match algorithm {
-        "aes" => {
-            format!(
-                r#"
-
-use aes_gcm::{{Aes256Gcm, Key, Nonce}};
-use aes_gcm::aead::{{Aead, NewAead}};
-use base64::{{Engine as _, engine::general_purpose}};
-
-fn decrypt_scat_string(encrypted: &str) -> String {{
-    let key_str = "{}";
-    let key = Key::from_slice(&sha2::Sha256::digest(key_str.as_bytes())[..32]);
-    let cipher = Aes256Gcm::new(key);
-    let nonce = Nonce::from_slice(b"unique_nonce");
-
-    if let Ok(ciphertext) = general_purpose::STANDARD.decode(encrypted) {{
-        if let Ok(plaintext) = cipher.decrypt(nonce, ciphertext.as_ref()) {{
-            String::from_utf8_lossy(&plaintext).to_string()
-        }} else {{
-            encrypted.to_string()
-        }}
-    }} else {{
-        encrypted.to_string()
-    }}
-}}
-
-fn scat_decrypt_macro(input: &str) -> String {{
-    decrypt_scat_string(input)
-}}
-"#,
-                key
-            )
+fn add_runtime_dependencies(path: &Path, algorithm: &str) -> Result<()> {
+    let dependencies = runtime_dependencies(algorithm);
+    if dependencies.is_empty() {
+        return Ok(());
+    }
+    let start = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+    let Some(manifest) = start
+        .ancestors()
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|candidate| candidate.exists()) else {
+        for (name, version) in dependencies {
+            println!("⚠️  Add {} = \"{}\" to the project's dependencies", name, version);
+        }
+        return Ok(());
+    };
+    let mut doc: toml_edit::DocumentMut = fs::read_to_string(&manifest)?.parse()?;
+    if !doc.contains_key("dependencies") {
+        doc["dependencies"] = toml_edit::table();
+    }
+    let mut added = Vec::new();
+    for (name, version) in dependencies {
+        if !doc["dependencies"].as_table_like().is_some_and(|t| t.contains_key(name)) {
+            doc["dependencies"][name] = toml_edit::value(*version);
+            added.push(*name);
         }
-        "chacha20" => {
+    }
+    if !added.is_empty() {
+        fs::write(&manifest, doc.to_string())?;
+        println!("📦 Added {} to {}", added.join(", "), manifest.display());
+    }
+    Ok(())
+}
+fn generate_decryption_runtime(algorithm: &str, key: &[u8; 32]) -> String {
+    let key_bytes = key
+        .iter()
+        .map(|b| b.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let decrypt = match algorithm {
+        "aes" | "chacha20" => {
+            let (krate, cipher, key_type) = if algorithm == "aes" {
+                ("aes_gcm", "Aes256Gcm", "Key::<Aes256Gcm>")
+            } else {
+                ("chacha20poly1305", "ChaCha20Poly1305", "Key")
+            };
             format!(
-                r#"
-
-use chacha20poly1305::{{ChaCha20Poly1305, Key, Nonce}};
-use chacha20poly1305::aead::{{Aead, NewAead}};
-use base64::{{Engine as _, engine::general_purpose}};
-
-fn decrypt_scat_string(encrypted: &str) -> String {{
-    let key_str = "{}";
-    let key = Key::from_slice(&sha2::Sha256::digest(key_str.as_bytes())[..32]);
-    let cipher = ChaCha20Poly1305::new(key);
-    let nonce = Nonce::from_slice(b"unique_nonce");
-
-    if let Ok(ciphertext) = general_purpose::STANDARD.decode(encrypted) {{
-        if let Ok(plaintext) = cipher.decrypt(nonce, ciphertext.as_ref()) {{
-            String::from_utf8_lossy(&plaintext).to_string()
-        }} else {{
-            encrypted.to_string()
-        }}
-    }} else {{
-        encrypted.to_string()
-    }}
-}}
-
-fn scat_decrypt_macro(input: &str) -> String {{
-    decrypt_scat_string(input)
+                r#"fn decrypt(encrypted: &str) -> String {{
+    use {krate}::aead::{{Aead, KeyInit}};
+    use {krate}::{{{cipher}, Key, Nonce}};
+    let cipher = {cipher}::new({key_type}::from_slice(&KEY));
+    decode_base64(encrypted)
+        .filter(|bytes| bytes.len() >= 12)
+        .and_then(|bytes| {{
+            let (nonce, body) = bytes.split_at(12);
+            cipher.decrypt(Nonce::from_slice(nonce), body).ok()
+        }})
+        .map(|plain| String::from_utf8_lossy(&plain).into_owned())
+        .unwrap_or_else(|| encrypted.to_string())
 }}
-"#,
-                key
+"#
             )
         }
         "builtin" => {
-            format!(
-                r#"
-
-use base64::{{Engine as _, engine::general_purpose}};
-
-fn decrypt_scat_string(encrypted: &str) -> String {{
-    let key_str = "{}";
-    let key_bytes = {{
-        let mut hasher = sha2::Sha256::new();
-        sha2::Digest::update(&mut hasher, key_str.as_bytes());
-        hasher.finalize()
-    }};
-
-    if let Ok(encrypted_bytes) = general_purpose::STANDARD.decode(encrypted) {{
-        if let Ok(encrypted_str) = std::str::from_utf8(&encrypted_bytes) {{
-            let mut result = String::new();
-            for (i, ch) in encrypted_str.chars().enumerate() {{
-                let key_byte = key_bytes[i % 32];
-                let decrypted_char = ((ch as u8) ^ key_byte) as char;
-                result.push(decrypted_char);
-            }}
-            result
-        }} else {{
-            encrypted.to_string()
-        }}
-    }} else {{
-        encrypted.to_string()
-    }}
-}}
-
-fn scat_decrypt_macro(input: &str) -> String {{
-    decrypt_scat_string(input)
-}}
-"#,
-                key
-            )
+            r#"fn decrypt(encrypted: &str) -> String {
+    decode_base64(encrypted)
+        .map(|bytes| {
+            let plain: Vec<u8> = bytes.iter().enumerate().map(|(i, b)| b ^ KEY[i % 32]).collect();
+            String::from_utf8_lossy(&plain).into_owned()
+        })
+        .unwrap_or_else(|| encrypted.to_string())
+}
+"#
+                .to_string()
         }
         _ => {
-            format!(
-                r#"
-
-fn decrypt_scat_string(encrypted: &str) -> String {{
+            r#"fn decrypt(encrypted: &str) -> String {
     encrypted.chars().rev().collect()
+}
+"#
+                .to_string()
+        }
+    };
+    format!(
+        r#"//! Generated by `cm scat`: decrypts the string literals it encrypted
+#![allow(dead_code)]
+use std::collections::HashMap;
+use std::sync::{{Mutex, OnceLock}};
+
+const KEY: [u8; 32] = [{key_bytes}];
+
+/// Stands in for the original literal, so it hands out a `&'static str` decrypted once
+pub fn decrypt_scat_string(encrypted: &'static str) -> &'static str {{
+    static CACHE: OnceLock<Mutex<HashMap<&'static str, &'static str>>> = OnceLock::new();
+    let mut cache = CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    cache.entry(encrypted).or_insert_with(|| Box::leak(decrypt(encrypted).into_boxed_str()))
 }}
 
-fn scat_decrypt_macro(input: &str) -> String {{
-    decrypt_scat_string(input)
+{decrypt}
+fn decode_base64(input: &str) -> Option<Vec<u8>> {{
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in input.bytes().filter(|&c| c != b'=') {{
+        buffer = ((buffer << 6) | ALPHABET.iter().position(|&a| a == c)? as u32) & 0xffff;
+        bits += 6;
+        if bits >= 8 {{
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }}
+    }}
+    Some(out)
 }}
 "#
-            )
-        }
-    }
-}
-fn inject_into_file(path: &PathBuf, code: &str) -> Result<()> {
-    let mut content = fs::read_to_string(path)?;
-    if let Some(insert_pos) = find_insertion_point(&content) {
-        content.insert_str(insert_pos, &format!("\n{}\n", code));
-        fs::write(path, content)?;
-        println!("🔓 Decryption runtime injected into: {}", path.display());
-    }
-    Ok(())
+    )
 }
-fn find_insertion_point(content: &str) -> Option<usize> {
-    let lines: Vec<&str> = content.lines().collect();
-    for (i, line) in lines.iter().enumerate() {
-        if line.trim().is_empty() && i > 0 {
-            let prev_line = lines[i - 1];
-            if prev_line.starts_with("use ") || prev_line.starts_with("extern crate ")
-                || prev_line.starts_with("#[")
-            {
-                continue;
-            } else {
-                return Some(lines[..i].iter().map(|l| l.len() + 1).sum());
-            }
+/// After any inner attributes and `//!` docs, which must open the file
+fn find_insertion_point(content: &str) -> usize {
+    let mut offset = 0;
+    // Brackets still open in an inner attribute that spans several lines
+    let mut depth = 0i32;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if depth > 0 || trimmed.starts_with("#![") {
+            depth += line.matches('[').count() as i32 - line.matches(']').count() as i32;
+            offset += line.len();
+        } else if trimmed.starts_with("//!") || trimmed.is_empty() {
+            offset += line.len();
+        } else {
+            break;
         }
     }
-    Some(content.lines().next().map(|l| l.len() + 1).unwrap_or(0))
+    offset
 }
-fn handle_file_packing(input: &PathBuf, output: &PathBuf, compress: bool) -> Result<()> {
+fn handle_file_packing(
+    input: &PathBuf,
+    output: &PathBuf,
+    compress: bool,
+    encrypt: bool,
+    self_extract: Option<crate::scat_bundle::StubKind>,
+    filter: &FileFilter,
+) -> Result<()> {
     println!("📦 Packing files from: {} to: {}", input.display(), output.display());
     if !input.exists() || !input.is_dir() {
         return Err(anyhow::anyhow!("Input must be an existing directory"));
     }
-    let mut tar_data = Vec::new();
-    {
-        let mut tar_builder = tar::Builder::new(&mut tar_data);
-        for entry in WalkDir::new(input) {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                let rel_path = path.strip_prefix(input)?;
-                tar_builder.append_path_with_name(path, rel_path)?;
-            }
+    let passphrase = if encrypt { Some(crate::scat_bundle::passphrase(true)?) } else { None };
+    let (bundle, count) = crate::scat_bundle::pack(input, filter, compress, passphrase.as_deref())?;
+    if let Some(kind) = self_extract {
+        let name = output
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("bundle");
+        fs::write(output, crate::scat_bundle::self_extracting_stub(kind, &bundle, name))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(output, fs::Permissions::from_mode(0o755))?;
         }
-        tar_builder.finish()?;
-    }
-    let archive_data = tar_data;
-    let final_data = if compress {
-        use flate2::write::GzEncoder;
-        use flate2::Compression;
-        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-        std::io::Write::write_all(&mut encoder, &archive_data)?;
-        encoder.finish()?
     } else {
-        archive_data
-    };
-    fs::write(output, final_data)?;
-    println!("✅ Files packed successfully!");
+        fs::write(output, bundle)?;
+    }
+    println!("✅ Packed {} file(s){}", count, if encrypt { ", encrypted" } else { "" });
+    if !encrypt {
+        println!("⚠️  Not encrypted: the bundle is only checksummed, which catches corruption but not tampering");
+        println!("💡 Use --encrypt to authenticate it with a passphrase the receiver must know");
+    }
     Ok(())
 }
 fn handle_unpack(
     input: &PathBuf,
-    map: &PathBuf,
+    map: Option<&PathBuf>,
     output: Option<&PathBuf>,
+    filter: &FileFilter,
 ) -> Result<()> {
-    println!(
-        "📦 Unpacking files from: {} using map: {}", input.display(), map.display()
-    );
     if !input.exists() {
         return Err(anyhow::anyhow!("Input file does not exist: {}", input.display()));
     }
     let output_dir = output
         .map(|p| p.clone())
         .unwrap_or_else(|| { input.with_extension("") });
+    if let Some(bundle) = crate::scat_bundle::read_bundle(input)? {
+        println!("📦 Unpacking bundle: {}", input.display());
+        let passphrase = if crate::scat_bundle::is_encrypted(&bundle) {
+            Some(crate::scat_bundle::passphrase(false)?)
+        } else {
+            None
+        };
+        let written = crate::scat_bundle::unpack(
+            &bundle,
+            &output_dir,
+            filter,
+            passphrase.as_deref(),
+        )?;
+        if passphrase.is_some() {
+            println!("✅ Bundle verified, {} file(s) written to {}", written.len(), output_dir.display());
+        } else {
+            println!("✅ Checksums match, {} file(s) written to {}", written.len(), output_dir.display());
+            println!("⚠️  Unencrypted bundle: its checksum proves it isn't corrupt, not who packed it");
+        }
+        return Ok(());
+    }
     fs::create_dir_all(&output_dir)?;
     if input.extension().and_then(|ext| ext.to_str()) == Some("tar")
         || input.to_string_lossy().contains("tar")
     {
-        unpack_tar_archive(input, &output_dir)?;
+        println!("📦 Unpacking files from: {}", input.display());
+        unpack_tar_archive(input, &output_dir, filter)?;
     } else {
+        let map = map
+            .ok_or_else(|| anyhow::anyhow!("A mapping file is required to reverse obfuscation"))?;
+        println!(
+            "📦 Unpacking files from: {} using map: {}", input.display(), map.display()
+        );
         let mapping = ObfuscationMapping::load_from_file(map)?;
         match mapping.method.as_str() {
             "names" => {
-                unpack_names(input, &mapping, &output_dir)?;
+                unpack_names(input, &mapping, &output_dir, filter)?;
             }
             "code" => {
-                unpack_code(input, &mapping, &output_dir)?;
+                unpack_code(input, &mapping, &output_dir, filter)?;
             }
             "strings" => {
-                unpack_strings(input, &mapping, &output_dir)?;
+                unpack_strings(input, &mapping, &output_dir, filter)?;
             }
             _ => {
                 return Err(
@@ -1355,15 +2291,19 @@ fn handle_unpack(
     println!("✅ Unpacking complete!");
     Ok(())
 }
-fn unpack_tar_archive(input: &PathBuf, output_dir: &PathBuf) -> Result<()> {
+fn unpack_tar_archive(
+    input: &PathBuf,
+    output_dir: &PathBuf,
+    filter: &FileFilter,
+) -> Result<()> {
     let file = fs::File::open(input)?;
     let reader = std::io::BufReader::new(file);
     let mut archive = Archive::new(reader);
-    if let Err(_) = archive.unpack(output_dir) {
-        let file = fs::File::open(input)?;
-        let reader = std::io::BufReader::new(file);
-        let mut tar_archive = Archive::new(reader);
-        tar_archive.unpack(output_dir)?;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if filter.allows(&entry.path()?) {
+            entry.unpack_in(output_dir)?;
+        }
     }
     Ok(())
 }
@@ -1371,8 +2311,12 @@ fn unpack_names(
     input: &PathBuf,
     mapping: &ObfuscationMapping,
     output: &PathBuf,
+    filter: &FileFilter,
 ) -> Result<()> {
     for (obfuscated, original) in &mapping.obfuscated_to_original {
+        if !filter.allows(Path::new(original)) {
+            continue;
+        }
         let obfuscated_path = input.join(obfuscated);
         let original_path = output.join(original);
         if obfuscated_path.exists() {
@@ -1386,6 +2330,7 @@ fn unpack_code(
     input: &PathBuf,
     mapping: &ObfuscationMapping,
     output: &PathBuf,
+    filter: &FileFilter,
 ) -> Result<()> {
     if input.is_dir() {
         fs::create_dir_all(output)?;
@@ -1393,6 +2338,7 @@ fn unpack_code(
             let entry = entry?;
             let path = entry.path();
             if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("rs")
+                && filter.allows(path.strip_prefix(input)?)
             {
                 let rel_path = path.strip_prefix(input)?;
                 let output_path = output.join(rel_path);
@@ -1415,6 +2361,7 @@ fn unpack_strings(
     input: &PathBuf,
     mapping: &ObfuscationMapping,
     output: &PathBuf,
+    filter: &FileFilter,
 ) -> Result<()> {
     if input.is_dir() {
         fs::create_dir_all(output)?;
@@ -1422,6 +2369,7 @@ fn unpack_strings(
             let entry = entry?;
             let path = entry.path();
             if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("rs")
+                && filter.allows(path.strip_prefix(input)?)
             {
                 let rel_path = path.strip_prefix(input)?;
                 let output_path = output.join(rel_path);
@@ -1429,6 +2377,13 @@ fn unpack_strings(
                 let mut content = fs::read_to_string(path)?;
                 for (original, obfuscated) in &mapping.original_to_obfuscated {
                     content = content
+                        .replace(
+                            &format!(
+                                "crate::{}::decrypt_scat_string(\"{}\")", RUNTIME_MODULE,
+                                obfuscated
+                            ),
+                            &format!("\"{}\"", original),
+                        )
                         .replace(
                             &format!("\"{}\"", obfuscated),
                             &format!("\"{}\"", original),
@@ -1442,4 +2397,153 @@ fn unpack_strings(
 }
 fn generate_random_name(length: usize) -> String {
     rng().sample_iter(Alphanumeric).take(length).map(char::from).collect()
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_filters_and_skip_markers_keep_items_intact() {
+        let filter = FileFilter::new(&FilterArgs {
+            include: vec!["src/**".to_string()],
+            exclude: vec!["src/ffi".to_string(), "generated_*.rs".to_string()],
+        })
+        .unwrap();
+        assert!(filter.allows(Path::new("src/lib.rs")));
+        assert!(!filter.allows(Path::new("src/ffi/mod.rs")));
+        assert!(!filter.allows(Path::new("src/generated_api.rs")));
+        assert!(!filter.allows(Path::new("benches/bench.rs")));
+        assert!(!FileFilter::default().allows(Path::new("target/debug/build.rs")));
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        let file = dir.join("lib.rs");
+        fs::write(
+            &file,
+            "// scat:skip\nfn exported_entry(counter: u32) -> u32 {\n    helper_value(counter)\n}\n\
+             fn helper_value(input: u32) -> u32 {\n    let doubled = input * 2;\n    doubled\n}\n\
+             #[scat::skip]\nstruct WireFormat {\n    field_name: u32,\n}\n",
+        )
+        .unwrap();
+        let config = ObfuscationConfig {
+            preserve_pub: false,
+            min_len: 3,
+            control_flow: false,
+            string_encryption: StringEncryptionConfig {
+                algorithm: "builtin".to_string(),
+                skip_format: true,
+                skip_errors: true,
+            },
+        };
+        let mut transformer = ObfuscationTransformer::new(config, Some("seed"), false);
+        let files = FileFilter::default().rust_files(&dir);
+        assert!(collect_skipped_identifiers(&files, &mut transformer.context).unwrap() > 0);
+        let output = transform_source(&fs::read_to_string(&file).unwrap(), &mut transformer).unwrap();
+        assert!(output.contains("// scat:skip\nfn exported_entry(counter: u32) -> u32 {"));
+        assert!(output.contains("fn helper_value(") && output.contains("field_name"));
+        assert!(!output.contains("doubled") && !output.contains(SKIP_ATTR));
+        syn::parse_file(&output).unwrap();
+    }
+    #[test]
+    fn test_interop_names_survive_renaming() {
+        let config = ObfuscationConfig {
+            preserve_pub: false,
+            min_len: 3,
+            control_flow: false,
+            string_encryption: StringEncryptionConfig {
+                algorithm: "builtin".to_string(),
+                skip_format: true,
+                skip_errors: true,
+            },
+        };
+        let source = "#[derive(Serialize, Deserialize)]\nstruct Settings {\n    \
+             #[serde(default = \"default_port\")]\n    listen_port: u16,\n    \
+             #[serde(rename = \"host\")]\n    host_name: String,\n}\n\
+             #[derive(Serialize)]\n#[serde(rename_all = \"camelCase\")]\nstruct Wire {\n    \
+             retry_count: u32,\n}\n\
+             fn default_port() -> u16 {\n    8080\n}\n\
+             #[repr(C)]\nstruct CPoint {\n    xpos: f64,\n}\n\
+             extern \"C\" {\n    fn c_callback(point: CPoint);\n}\n\
+             #[unsafe(no_mangle)]\npub extern \"C\" fn exported_hook() {}\n\
+             #[wasm_bindgen]\nimpl Greeter {\n    pub fn greet_user(&self) {}\n}\n";
+        let mut transformer = ObfuscationTransformer::new(config, Some("seed"), false);
+        transformer.context.protect_interop(&syn::parse_file(source).unwrap());
+        let output = transform_source(source, &mut transformer).unwrap();
+        for kept in [
+            "default_port", "retry_count", "CPoint", "xpos", "c_callback", "exported_hook",
+            "Greeter", "greet_user",
+        ] {
+            assert!(output.contains(kept), "{} was renamed:\n{}", kept, output);
+        }
+        assert!(output.contains("#[serde(rename = \"listen_port\")]"));
+        assert!(!output.contains("listen_port:"));
+        assert_eq!(output.matches("rename = \"host\"").count(), 1);
+        syn::parse_file(&output).unwrap();
+    }
+    #[test]
+    fn test_string_runtime_is_wired_into_crate_roots() {
+        let config = ObfuscationConfig {
+            preserve_pub: true,
+            min_len: 0,
+            control_flow: false,
+            string_encryption: StringEncryptionConfig {
+                algorithm: "aes".to_string(),
+                skip_format: false,
+                skip_errors: false,
+            },
+        };
+        let mut transformer = ObfuscationTransformer::new(config, None, false);
+        transformer.string_key = Some("contest".to_string());
+        transformer.rename_identifiers = false;
+        let source = "const LABEL: &str = \"kept\";\nfn main() {\n    let text = \"secret\";\n}\n";
+        let output = transform_source(source, &mut transformer).unwrap();
+        assert!(output.contains("const LABEL: &str = \"kept\";"));
+        assert!(output.contains("crate::scat_runtime::decrypt_scat_string("));
+        assert!(!output.contains("\"secret\""));
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        let main = dir.join("src").join("main.rs");
+        fs::write(&main, format!("#![allow(unused)]\n{}", output)).unwrap();
+        let files = FileFilter::default().rust_files(&dir);
+        inject_decryption_runtime(&dir, &files, "aes", &transformer.string_key_bytes()).unwrap();
+        let root = fs::read_to_string(&main).unwrap();
+        assert!(root.starts_with("#![allow(unused)]\nmod scat_runtime;\n"));
+        let runtime = fs::read_to_string(dir.join("src").join("scat_runtime.rs")).unwrap();
+        syn::parse_file(&runtime).unwrap();
+        assert!(runtime.contains("Aes256Gcm"));
+        let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("aes-gcm = \"0.10\""));
+    }
+    #[test]
+    fn test_string_nonces_are_unique_and_keys_verifiable() {
+        let config = ObfuscationConfig {
+            preserve_pub: true,
+            min_len: 0,
+            control_flow: false,
+            string_encryption: StringEncryptionConfig {
+                algorithm: "aes".to_string(),
+                skip_format: false,
+                skip_errors: false,
+            },
+        };
+        let mut transformer = ObfuscationTransformer::new(config, None, false);
+        transformer.string_key = Some("contest".to_string());
+        let first = transformer.encrypt_string("same text", Some("contest"));
+        let second = transformer.encrypt_string("other text", Some("contest"));
+        let nonce = |e: &str| general_purpose::STANDARD.decode(e).unwrap()[..12].to_vec();
+        assert_ne!(nonce(&first), nonce(&second));
+        let meta = transformer.key_metadata();
+        assert_eq!(meta.strings.len(), 2);
+        let salt = general_purpose::STANDARD.decode(&meta.salt).unwrap();
+        let key = derive_string_key("aes", Some("contest"), &salt);
+        assert_eq!(key_check(&key), meta.key_check);
+        assert_ne!(key_check(&derive_string_key("aes", Some("wrong"), &salt)), meta.key_check);
+        assert_eq!(decrypt_string("aes", &key, &first).unwrap(), "same text");
+        let mut tampered = general_purpose::STANDARD.decode(&second).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt_string("aes", &key, &general_purpose::STANDARD.encode(tampered)).is_err());
+        let seeded = |config: &ObfuscationConfig| ObfuscationTransformer::new(config.clone(), Some("fixed"), false);
+        let (a, b) = (seeded(&transformer.context.config), seeded(&transformer.context.config));
+        assert_ne!(a.key_salt, b.key_salt);
+    }
+}
@@ -0,0 +1,381 @@
+use crate::scat::FileFilter;
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path};
+use walkdir::WalkDir;
+const MAGIC: &[u8] = b"SCATBNDL1";
+const FORMAT: u32 = 1;
+const FLAG_COMPRESSED: u8 = 1;
+const FLAG_ENCRYPTED: u8 = 2;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const MAC_LEN: usize = 32;
+const PBKDF2_ROUNDS: u32 = 600_000;
+const MANIFEST_NAME: &str = ".scat-manifest.json";
+/// Separates a self-extracting stub from the base64 bundle appended to it
+const STUB_MARKER: &str = "__SCAT_BUNDLE__";
+/// Used instead of prompting, e.g. when packing in CI
+pub const PASSPHRASE_ENV: &str = "CM_SCAT_PASSPHRASE";
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum StubKind {
+    Sh,
+    Ps1,
+}
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleManifest {
+    format: u32,
+    cm_version: String,
+    created_at: String,
+    /// Relative path to SHA-256 of its contents
+    files: BTreeMap<String, String>,
+}
+/// Layout: `MAGIC | flags | [salt | nonce] | payload | trailer`.
+///
+/// The payload is a tar (gzipped with `FLAG_COMPRESSED`) holding the sources and a manifest
+/// of their hashes. With a passphrase, PBKDF2 yields both the AES-256-GCM key and the MAC
+/// key, and the trailer is an HMAC-SHA256 MAC; without one the HMAC key is empty, so the
+/// trailer is an unkeyed checksum that only catches corruption.
+struct Keys {
+    cipher: [u8; 32],
+    mac: Vec<u8>,
+}
+impl Keys {
+    fn derive(passphrase: Option<&str>, salt: &[u8]) -> Self {
+        match passphrase {
+            Some(passphrase) => {
+                let mut okm = [0u8; 64];
+                pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut okm);
+                let mut cipher = [0u8; 32];
+                cipher.copy_from_slice(&okm[..32]);
+                Self { cipher, mac: okm[32..].to_vec() }
+            }
+            None => Self { cipher: [0u8; 32], mac: Vec::new() },
+        }
+    }
+    fn mac(&self, data: &[u8]) -> Hmac<Sha256> {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.mac).expect("HMAC takes keys of any length");
+        mac.update(data);
+        mac
+    }
+}
+pub fn passphrase(confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        if !passphrase.is_empty() {
+            return Ok(passphrase);
+        }
+    }
+    if !atty::is(atty::Stream::Stdin) {
+        bail!("Encrypted scat bundles need a passphrase - set {}", PASSPHRASE_ENV);
+    }
+    let passphrase = rpassword::prompt_password("🔑 Bundle passphrase: ")?;
+    if passphrase.is_empty() {
+        bail!("The passphrase cannot be empty");
+    }
+    if confirm && rpassword::prompt_password("🔑 Repeat passphrase: ")? != passphrase {
+        bail!("Passphrases do not match");
+    }
+    Ok(passphrase)
+}
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+fn append_bytes<W: std::io::Write>(builder: &mut tar::Builder<W>, name: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, name, data)?;
+    Ok(())
+}
+/// Bundle every file under `input` that passes `filter`; returns the bundle and its file count
+pub fn pack(input: &Path, filter: &FileFilter, compress: bool, passphrase: Option<&str>) -> Result<(Vec<u8>, usize)> {
+    let mut files = BTreeMap::new();
+    for entry in WalkDir::new(input).into_iter().filter_map(|e| e.ok()) {
+        let relative = entry.path().strip_prefix(input)?;
+        if entry.file_type().is_file() && filter.allows(relative) {
+            let name = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+            files.insert(name, fs::read(entry.path())?);
+        }
+    }
+    let manifest = BundleManifest {
+        format: FORMAT,
+        cm_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        files: files.iter().map(|(name, data)| (name.clone(), sha256_hex(data))).collect(),
+    };
+    let mut builder = tar::Builder::new(Vec::new());
+    append_bytes(&mut builder, MANIFEST_NAME, &serde_json::to_vec_pretty(&manifest)?)?;
+    for (name, data) in &files {
+        append_bytes(&mut builder, name, data)?;
+    }
+    let mut payload = builder.into_inner()?;
+    if compress {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        std::io::Write::write_all(&mut encoder, &payload)?;
+        payload = encoder.finish()?;
+    }
+    let mut bundle = MAGIC.to_vec();
+    let mut flags = if compress { FLAG_COMPRESSED } else { 0 };
+    let salt = rand::random::<[u8; SALT_LEN]>();
+    let keys = Keys::derive(passphrase, &salt);
+    if passphrase.is_some() {
+        flags |= FLAG_ENCRYPTED;
+        let nonce = rand::random::<[u8; NONCE_LEN]>();
+        payload = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&keys.cipher))
+            .encrypt(Nonce::from_slice(&nonce), payload.as_ref())
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt the bundle"))?;
+        bundle.push(flags);
+        bundle.extend(salt);
+        bundle.extend(nonce);
+    } else {
+        bundle.push(flags);
+    }
+    bundle.extend(payload);
+    let mac = keys.mac(&bundle).finalize().into_bytes();
+    bundle.extend(mac);
+    Ok((bundle, files.len()))
+}
+pub fn is_encrypted(bundle: &[u8]) -> bool {
+    bundle.get(MAGIC.len()).is_some_and(|flags| flags & FLAG_ENCRYPTED != 0)
+}
+/// The bundle inside `path`, whether it is a raw bundle or a self-extracting stub
+pub fn read_bundle(path: &Path) -> Result<Option<Vec<u8>>> {
+    let data = fs::read(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    if data.starts_with(MAGIC) {
+        return Ok(Some(data));
+    }
+    let Ok(text) = std::str::from_utf8(&data) else {
+        return Ok(None);
+    };
+    let Some((_, encoded)) = text.split_once(&format!("\n{}\n", STUB_MARKER)) else {
+        return Ok(None);
+    };
+    let encoded: String = encoded.trim_end().trim_end_matches("#>").split_whitespace().collect();
+    let bundle = general_purpose::STANDARD.decode(encoded).context("Corrupt bundle in self-extracting stub")?;
+    Ok(bundle.starts_with(MAGIC).then_some(bundle))
+}
+/// Archive paths must stay inside the output directory
+fn is_safe(path: &str) -> bool {
+    !path.is_empty() && Path::new(path).components().all(|c| matches!(c, Component::Normal(_)))
+}
+/// Check the trailer (a MAC when encrypted, a checksum otherwise), decrypt, and check every file against the manifest before writing anything;
+/// returns the files written
+pub fn unpack(bundle: &[u8], output: &Path, filter: &FileFilter, passphrase: Option<&str>) -> Result<Vec<String>> {
+    if !bundle.starts_with(MAGIC) {
+        bail!("Not a scat bundle");
+    }
+    let flags = *bundle.get(MAGIC.len()).context("Bundle is truncated")?;
+    let encrypted = flags & FLAG_ENCRYPTED != 0;
+    let header_len = MAGIC.len() + 1 + if encrypted { SALT_LEN + NONCE_LEN } else { 0 };
+    if bundle.len() < header_len + MAC_LEN {
+        bail!("Bundle is truncated");
+    }
+    if encrypted && passphrase.is_none() {
+        bail!("Bundle is encrypted - a passphrase is required");
+    }
+    let (body, mac) = bundle.split_at(bundle.len() - MAC_LEN);
+    let salt = if encrypted { &body[MAGIC.len() + 1..MAGIC.len() + 1 + SALT_LEN] } else { &[][..] };
+    let keys = Keys::derive(passphrase.filter(|_| encrypted), salt);
+    if keys.mac(body).verify_slice(mac).is_err() {
+        if encrypted {
+            bail!("Bundle MAC mismatch - refusing a tampered bundle or wrong passphrase");
+        }
+        bail!("Bundle checksum mismatch - the bundle is corrupt");
+    }
+    let mut payload = body[header_len..].to_vec();
+    if encrypted {
+        let nonce = &body[header_len - NONCE_LEN..header_len];
+        payload = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&keys.cipher))
+            .decrypt(Nonce::from_slice(nonce), payload.as_ref())
+            .map_err(|_| anyhow::anyhow!("Cannot decrypt bundle - wrong passphrase"))?;
+    }
+    if flags & FLAG_COMPRESSED != 0 {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(payload.as_slice()).read_to_end(&mut decompressed)?;
+        payload = decompressed;
+    }
+    let mut entries = BTreeMap::new();
+    for entry in tar::Archive::new(payload.as_slice()).entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().replace('\\', "/");
+        if !is_safe(&name) {
+            bail!("Refusing bundle entry outside the output directory: {}", name);
+        }
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        entries.insert(name, data);
+    }
+    let manifest: BundleManifest =
+        serde_json::from_slice(&entries.remove(MANIFEST_NAME).context("Bundle has no manifest")?)?;
+    if manifest.format > FORMAT {
+        bail!("Bundle was packed by cargo-mate {} in a newer format - run 'cm self update' first", manifest.cm_version);
+    }
+    if entries.len() != manifest.files.len() {
+        bail!("Bundle holds {} file(s) but its manifest lists {}", entries.len(), manifest.files.len());
+    }
+    for (name, data) in &entries {
+        if manifest.files.get(name) != Some(&sha256_hex(data)) {
+            bail!("{} does not match the bundle manifest", name);
+        }
+    }
+    let mut written = Vec::new();
+    for (name, data) in entries {
+        if !filter.allows(Path::new(&name)) {
+            continue;
+        }
+        let target = output.join(&name);
+        fs::create_dir_all(target.parent().unwrap_or(output))?;
+        fs::write(&target, data)?;
+        written.push(name);
+    }
+    Ok(written)
+}
+fn base64_lines(bundle: &[u8]) -> String {
+    let encoded = general_purpose::STANDARD.encode(bundle);
+    encoded.as_bytes().chunks(76).map(|line| String::from_utf8_lossy(line).into_owned()).collect::<Vec<_>>().join("\n")
+}
+/// A script that unpacks through `cm` when available; unencrypted bundles also extract with
+/// plain `tar` after a checksum check, so reviewers need nothing installed
+pub fn self_extracting_stub(kind: StubKind, bundle: &[u8], name: &str) -> String {
+    let encrypted = is_encrypted(bundle);
+    let compressed = bundle.get(MAGIC.len()).is_some_and(|flags| flags & FLAG_COMPRESSED != 0);
+    let offset = MAGIC.len() + 1;
+    let length = bundle.len() - offset - MAC_LEN;
+    let checksum = sha256_hex(bundle);
+    let payload = base64_lines(bundle);
+    match kind {
+        StubKind::Sh => {
+            let fallback = if encrypted {
+                "echo \"This bundle is encrypted - install cargo-mate and run this script again\" >&2\nexit 1".to_string()
+            } else {
+                format!(
+                    r#"sum=$( (sha256sum "$tmp" 2>/dev/null || shasum -a 256 "$tmp") | cut -d' ' -f1)
+if [ "$sum" != "{checksum}" ]; then
+    echo "Bundle checksum mismatch - refusing to extract" >&2
+    exit 1
+fi
+mkdir -p "$dest"
+tail -c +{start} "$tmp" | head -c {length} | tar -x{z}f - -C "$dest" --exclude '{manifest}'
+echo "Extracted to $dest""#,
+                    start = offset + 1,
+                    z = if compressed { "z" } else { "" },
+                    manifest = MANIFEST_NAME,
+                )
+            };
+            format!(
+                r#"#!/bin/sh
+# Self-extracting cargo-mate scat bundle. Usage: sh {name}.sh [destination]
+set -e
+dest="${{1:-{name}}}"
+tmp=$(mktemp)
+trap 'rm -f "$tmp"' EXIT
+sed '1,/^{STUB_MARKER}$/d' "$0" | base64 -d > "$tmp"
+if command -v cm >/dev/null 2>&1; then
+    cm scat unpack "$tmp" --output "$dest"
+    exit $?
+fi
+{fallback}
+exit 0
+{STUB_MARKER}
+{payload}
+"#
+            )
+        }
+        StubKind::Ps1 => {
+            let fallback = if encrypted {
+                "    throw \"This bundle is encrypted - install cargo-mate and run this script again\"".to_string()
+            } else {
+                format!(
+                    r#"    $hash = (Get-FileHash -Algorithm SHA256 -LiteralPath $tmp).Hash.ToLower()
+    if ($hash -ne "{checksum}") {{ throw "Bundle checksum mismatch - refusing to extract" }}
+    $payload = New-Object byte[] {length}
+    [Array]::Copy($data, {offset}, $payload, 0, {length})
+    $archive = "$tmp.tar"
+    [IO.File]::WriteAllBytes($archive, $payload)
+    New-Item -ItemType Directory -Force -Path $Destination | Out-Null
+    tar -xf $archive -C $Destination --exclude "{manifest}"
+    Remove-Item $archive
+    Write-Host "Extracted to $Destination""#,
+                    manifest = MANIFEST_NAME,
+                )
+            };
+            format!(
+                r#"# Self-extracting cargo-mate scat bundle. Usage: powershell -File {name}.ps1 [destination]
+param([string]$Destination = "{name}")
+$ErrorActionPreference = "Stop"
+$lines = Get-Content -LiteralPath $PSCommandPath
+$start = [Array]::IndexOf($lines, "{STUB_MARKER}") + 1
+$data = [Convert]::FromBase64String(($lines[$start..($lines.Length - 2)] -join ""))
+$tmp = [IO.Path]::GetTempFileName()
+[IO.File]::WriteAllBytes($tmp, $data)
+try {{
+    if (Get-Command cm -ErrorAction SilentlyContinue) {{
+        & cm scat unpack $tmp --output $Destination
+        exit $LASTEXITCODE
+    }}
+{fallback}
+}} finally {{
+    Remove-Item $tmp -ErrorAction SilentlyContinue
+}}
+exit
+<#
+{STUB_MARKER}
+{payload}
+#>
+"#
+            )
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_bundle_round_trip_and_tamper_detection() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("main.rs"), "fn main() {}\n").unwrap();
+        fs::write(src.join("nested").join("mod.rs"), "pub fn x() {}\n").unwrap();
+        let filter = FileFilter::default();
+        let (bundle, count) = pack(&src, &filter, true, Some("hunter2")).unwrap();
+        assert_eq!(count, 2);
+        assert!(is_encrypted(&bundle));
+        let out = dir.path().join("out");
+        assert!(unpack(&bundle, &out, &filter, Some("wrong")).is_err());
+        let mut tampered = bundle.clone();
+        tampered[MAGIC.len() + 40] ^= 1;
+        assert!(unpack(&tampered, &out, &filter, Some("hunter2")).is_err());
+        assert!(!out.exists());
+        let written = unpack(&bundle, &out, &filter, Some("hunter2")).unwrap();
+        assert_eq!(written, vec!["main.rs".to_string(), "nested/mod.rs".to_string()]);
+        assert_eq!(fs::read_to_string(out.join("nested").join("mod.rs")).unwrap(), "pub fn x() {}\n");
+        let (plain, _) = pack(&src, &filter, false, None).unwrap();
+        let stub = dir.path().join("bundle.sh");
+        fs::write(&stub, self_extracting_stub(StubKind::Sh, &plain, "bundle")).unwrap();
+        assert_eq!(read_bundle(&stub).unwrap().unwrap(), plain);
+        let script = dir.path().join("bundle.ps1");
+        fs::write(&script, self_extracting_stub(StubKind::Ps1, &plain, "bundle")).unwrap();
+        assert_eq!(read_bundle(&script).unwrap().unwrap(), plain);
+        let mut corrupt = plain.clone();
+        corrupt[MAGIC.len() + 8] ^= 1;
+        let error = unpack(&corrupt, &dir.path().join("plain"), &filter, None).unwrap_err();
+        assert!(error.to_string().contains("checksum mismatch"), "{}", error);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::captain::config::ConfigManager;
+use crate::checklist;
+use crate::hooks::HookContext;
+use anyhow::{Context, Result};
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+/// Keeps a runaway loop in a script from hanging the build
+const MAX_OPERATIONS: u64 = 1_000_000;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptEvent {
+    Started,
+    Diagnostic,
+    Finished,
+}
+impl ScriptEvent {
+    pub fn handler(&self) -> &'static str {
+        match self {
+            ScriptEvent::Started => "on_started",
+            ScriptEvent::Diagnostic => "on_diagnostic",
+            ScriptEvent::Finished => "on_finished",
+        }
+    }
+}
+/// What a script asked for through `add_checklist`, `notify` and `veto`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptAction {
+    Checklist(String),
+    Notify { title: String, body: String },
+    Veto(String),
+}
+#[derive(Debug, Clone)]
+struct Script {
+    path: PathBuf,
+    ast: AST,
+}
+impl Script {
+    fn name(&self) -> String {
+        self.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+    }
+    fn handles(&self, event: ScriptEvent) -> bool {
+        self.ast.iter_functions().any(|f| f.name == event.handler() && f.params.len() == 1)
+    }
+}
+/// The `~/.shipwreck/scripts/*.rhai` hooks, compiled once per command
+#[derive(Debug, Clone, Default)]
+pub struct Scripts {
+    scripts: Vec<Script>,
+}
+pub fn scripts_dir() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join("scripts"))
+}
+fn engine(actions: &Arc<Mutex<Vec<ScriptAction>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    let sink = actions.clone();
+    engine.register_fn("add_checklist", move |text: &str| {
+        sink.lock().unwrap().push(ScriptAction::Checklist(text.to_string()));
+    });
+    let sink = actions.clone();
+    engine.register_fn("notify", move |title: &str, body: &str| {
+        sink.lock().unwrap().push(ScriptAction::Notify { title: title.to_string(), body: body.to_string() });
+    });
+    let sink = actions.clone();
+    engine.register_fn("veto", move |reason: &str| {
+        sink.lock().unwrap().push(ScriptAction::Veto(reason.to_string()));
+    });
+    engine.on_print(|text| eprintln!("📜 {}", text));
+    engine
+}
+impl Scripts {
+    pub fn load(config: &ConfigManager) -> Self {
+        if !config.get_bool("scripts.enabled", true) {
+            return Self::default();
+        }
+        match scripts_dir() {
+            Ok(dir) => Self::load_dir(&dir),
+            Err(_) => Self::default(),
+        }
+    }
+    /// Scripts that don't compile are reported and skipped
+    pub fn load_dir(dir: &Path) -> Self {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Self::default();
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        paths.sort();
+        let engine = Engine::new();
+        let scripts = paths
+            .into_iter()
+            .filter_map(|path| {
+                match std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))
+                    .and_then(|source| engine.compile(source).map_err(anyhow::Error::from))
+                {
+                    Ok(ast) => Some(Script { path, ast }),
+                    Err(e) => {
+                        log::warn!("Skipping script {}: {}", path.display(), e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { scripts }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.scripts.iter().map(|script| script.path.as_path())
+    }
+    /// Calls the event's handler in every script that defines one; a failing script is reported
+    /// and never fails the build
+    pub fn dispatch(&self, event: ScriptEvent, payload: &Map) -> Vec<ScriptAction> {
+        let actions = Arc::new(Mutex::new(Vec::new()));
+        let handlers: Vec<&Script> = self.scripts.iter().filter(|script| script.handles(event)).collect();
+        if handlers.is_empty() {
+            return Vec::new();
+        }
+        let engine = engine(&actions);
+        for script in handlers {
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                &script.ast,
+                event.handler(),
+                (payload.clone(),),
+            );
+            if let Err(e) = result {
+                log::warn!("Script {} failed in {}: {}", script.name(), event.handler(), e);
+            }
+        }
+        let collected = std::mem::take(&mut *actions.lock().unwrap());
+        collected
+    }
+    /// Runs checklist and notify actions; returns the first veto
+    pub fn fire(&self, event: ScriptEvent, payload: &Map) -> Option<String> {
+        let mut veto = None;
+        for action in self.dispatch(event, payload) {
+            match action {
+                ScriptAction::Checklist(text) => {
+                    if let Err(e) = add_checklist_item(&text) {
+                        log::warn!("Script could not add checklist item: {}", e);
+                    }
+                }
+                ScriptAction::Notify { title, body } => crate::notify::notify_message(&title, &body),
+                ScriptAction::Veto(reason) if event == ScriptEvent::Started => {
+                    veto.get_or_insert(reason);
+                }
+                ScriptAction::Veto(reason) => {
+                    log::warn!("Ignoring veto from {} (only on_started can veto): {}", event.handler(), reason);
+                }
+            }
+        }
+        veto
+    }
+    pub fn started(&self, context: &HookContext) -> Option<String> {
+        self.fire(ScriptEvent::Started, &base_payload(context))
+    }
+    pub fn finished(&self, context: &HookContext) {
+        if self.is_empty() {
+            return;
+        }
+        for message in &context.diagnostics.messages {
+            let mut payload = base_payload(context);
+            let level = if message.starts_with("warning") { "warning" } else { "error" };
+            payload.insert("level".into(), level.into());
+            payload.insert("message".into(), message.clone().into());
+            self.fire(ScriptEvent::Diagnostic, &payload);
+        }
+        let mut payload = base_payload(context);
+        let exit_code = context.exit_code.unwrap_or(0);
+        payload.insert("exit_code".into(), Dynamic::from(exit_code as i64));
+        payload.insert("success".into(), Dynamic::from(exit_code == 0 && context.diagnostics.errors == 0));
+        payload.insert("errors".into(), Dynamic::from(context.diagnostics.errors as i64));
+        payload.insert("warnings".into(), Dynamic::from(context.diagnostics.warnings as i64));
+        self.fire(ScriptEvent::Finished, &payload);
+    }
+}
+fn base_payload(context: &HookContext) -> Map {
+    let mut payload = Map::new();
+    let args: Array = context.cargo_args.iter().map(|arg| arg.clone().into()).collect();
+    payload.insert("command".into(), format!("cargo {}", context.cargo_args.join(" ")).into());
+    payload.insert("subcommand".into(), context.cargo_args.first().cloned().unwrap_or_default().into());
+    payload.insert("args".into(), args.into());
+    payload
+}
+fn add_checklist_item(text: &str) -> Result<()> {
+    checklist::update_items(&checklist::items_file()?, |items| {
+        if !items.iter().any(|item| !item.done && item.text == text) {
+            checklist::add_item(items, text);
+        }
+    })
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_dispatch_collects_actions() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        std::fs::write(
+            dir.join("guard.rhai"),
+            r#"
+                fn on_started(event) {
+                    if event.subcommand == "publish" && !event.args.contains("--dry-run") { veto("dry-run first"); }
+                }
+                fn on_finished(event) {
+                    if !event.success { add_checklist("Fix " + event.errors + " error(s)"); }
+                    notify("done", event.command);
+                }
+            "#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("broken.rhai"), "fn on_started(event) {").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+        let scripts = Scripts::load_dir(&dir);
+        assert_eq!(scripts.paths().count(), 1);
+        let mut context = HookContext::new(&["cargo", "publish"]);
+        let actions = scripts.dispatch(ScriptEvent::Started, &base_payload(&context));
+        assert_eq!(actions, vec![ScriptAction::Veto("dry-run first".into())]);
+        assert!(scripts.dispatch(ScriptEvent::Diagnostic, &base_payload(&context)).is_empty());
+        context.exit_code = Some(101);
+        let mut payload = base_payload(&context);
+        payload.insert("success".into(), false.into());
+        payload.insert("errors".into(), Dynamic::from(2_i64));
+        assert_eq!(
+            scripts.dispatch(ScriptEvent::Finished, &payload),
+            vec![
+                ScriptAction::Checklist("Fix 2 error(s)".into()),
+                ScriptAction::Notify { title: "done".into(), body: "cargo publish".into() },
+            ]
+        );
+    }
+}
@@ -1,6 +1,8 @@
-use anyhow::{Context, Result};
+use crate::captain::config::ConfigManager;
+use anyhow::{bail, Context, Result};
 use colored::*;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 #[derive(Debug, Clone)]
@@ -11,6 +13,78 @@ pub struct ScrubOptions {
     pub resume_from: Option<String>,
     pub min_depth: usize,
     pub max_depth: usize,
+    pub interactive: bool,
+    pub exclude: Vec<PathBuf>,
+    pub keep_release: Vec<PathBuf>,
+    pub quiet: bool,
+    pub free_below_gb: Option<u64>,
+}
+impl ScrubOptions {
+    pub fn with_config(mut self, config: Option<&ConfigManager>) -> Self {
+        if let Some(config) = config {
+            self.exclude.extend(config.get_list("scrub.exclude").iter().map(|p| expand_home(p)));
+            self.keep_release
+                .extend(config.get_list("scrub.keep_release").iter().map(|p| expand_home(p)));
+        }
+        self
+    }
+    fn keeps_release(&self, project: &Path) -> bool {
+        self.keep_release.iter().any(|keep| project == keep)
+    }
+}
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~") {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+#[derive(Debug, Clone)]
+pub struct ScrubTarget {
+    pub path: PathBuf,
+    pub size: u64,
+    pub selected: bool,
+    pub keep_release: bool,
+}
+#[derive(Debug, PartialEq, Eq)]
+pub enum Selection {
+    Continue,
+    Proceed,
+    Cancel,
+}
+pub fn apply_selection(targets: &mut [ScrubTarget], input: &str) -> Result<Selection> {
+    let input = input.trim();
+    let mut words = input.split_whitespace();
+    let (keep_release, numbers): (bool, Vec<&str>) = match words.next() {
+        None => return Ok(Selection::Proceed),
+        Some("q") => return Ok(Selection::Cancel),
+        Some("a") => {
+            targets.iter_mut().for_each(|t| t.selected = true);
+            return Ok(Selection::Continue);
+        }
+        Some("n") => {
+            targets.iter_mut().for_each(|t| t.selected = false);
+            return Ok(Selection::Continue);
+        }
+        Some("r") => (true, words.collect()),
+        Some(_) => (false, input.split_whitespace().collect()),
+    };
+    for number in numbers {
+        let index = match number.parse::<usize>() {
+            Ok(n) if (1..=targets.len()).contains(&n) => n - 1,
+            _ => bail!("'{}' is not one of the listed projects", number),
+        };
+        let target = &mut targets[index];
+        if keep_release {
+            target.keep_release = !target.keep_release;
+        } else {
+            target.selected = !target.selected;
+        }
+    }
+    Ok(Selection::Continue)
 }
 impl Default for ScrubOptions {
     fn default() -> Self {
@@ -21,6 +95,11 @@ impl Default for ScrubOptions {
             resume_from: None,
             min_depth: 1,
             max_depth: 10,
+            interactive: false,
+            exclude: Vec::new(),
+            keep_release: Vec::new(),
+            quiet: false,
+            free_below_gb: None,
         }
     }
 }
@@ -31,56 +110,88 @@ impl CargoScrubber {
     pub fn new(options: ScrubOptions) -> Self {
         Self { options }
     }
+    fn say(&self, line: impl std::fmt::Display) {
+        if !self.options.quiet {
+            println!("{}", line);
+        }
+    }
     pub fn scrub(&self) -> Result<()> {
+        if let Some(threshold) = self.options.free_below_gb {
+            let available = crate::scrub_schedule::available_bytes(&self.options.start_dir)?;
+            if available >= threshold * crate::scrub_schedule::GB {
+                self.say(format!(
+                    "Free space {} is above the {}GB threshold - nothing to do", self
+                    .format_bytes(available), threshold
+                ));
+                return Ok(());
+            }
+        }
         self.print_header();
         if self.options.dry_run {
-            println!(
-                "{}", "DRY RUN MODE - No actual cleaning will be performed".yellow()
-                .bold()
-            );
+            self.say("DRY RUN MODE - No actual cleaning will be performed".yellow().bold());
         }
         if let Some(ref resume) = self.options.resume_from {
-            println!(
+            self.say(format!(
                 "{} Starting from projects containing: {}", "RESUME MODE:".cyan(), resume
                 .cyan()
-            );
+            ));
         }
-        if self.is_running_as_root() {
-            println!(
-                "{}",
-                "WARNING: Running as root - this will clean ALL users' Rust projects"
-                .yellow()
-            );
-            println!("Continue? (y/N): ");
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input)?;
-            if !input.trim().to_lowercase().starts_with('y') {
-                println!("Operation cancelled.");
+        if self.is_running_as_root() && !self.options.quiet {
+            self.say("WARNING: Running as root - this will clean ALL users' Rust projects".yellow());
+            if !crate::ci::confirm("Continue?")? {
+                self.say("Operation cancelled.");
                 return Ok(());
             }
         }
         let projects = self.find_cargo_projects()?;
         if projects.is_empty() {
-            println!("No Rust projects found. Exiting.");
+            self.say("No Rust projects found. Exiting.");
+            return Ok(());
+        }
+        let mut targets = self.build_targets(self.filter_projects_for_resume(projects));
+        if self.options.interactive && !self.select_interactively(&mut targets)? {
+            self.say("Operation cancelled.");
+            return Ok(());
+        }
+        targets.retain(|t| t.selected);
+        if targets.is_empty() {
+            self.say("No projects selected. Exiting.");
             return Ok(());
         }
-        let total_space = self.calculate_total_space(&projects)?;
-        println!("Potential space to free: {}", self.format_bytes(total_space));
-        let results = self.process_projects(projects)?;
+        let total_space: u64 = targets.iter().map(|t| t.size).sum();
+        self.say(format!("Potential space to free: {}", self.format_bytes(total_space)));
+        let results = self.process_projects(targets)?;
         self.print_summary(&results);
+        if !self.options.dry_run && !results.changes.is_empty() {
+            crate::audit::log(
+                "scrub",
+                &format!("reclaimed {}", self.format_bytes(results.total_savings)),
+                results.changes,
+            );
+        }
+        if self.options.quiet && !self.options.dry_run {
+            crate::history::save_to_history(
+                format!(
+                    "scrub reclaimed {} from {} project(s)", self.format_bytes(results
+                    .total_savings), results.projects_cleaned
+                ),
+                Vec::new(),
+                Vec::new(),
+            );
+        }
         Ok(())
     }
     fn print_header(&self) {
-        println!("{}", "🧹 System-wide Cargo Clean".bold());
-        println!("{}", "=".repeat(50).cyan());
-        println!("Start directory: {}", self.options.start_dir.display());
+        self.say("🧹 System-wide Cargo Clean".bold());
+        self.say("=".repeat(50).cyan());
+        self.say(format!("Start directory: {}", self.options.start_dir.display()));
     }
     fn is_running_as_root(&self) -> bool {
         std::env::var("USER").map_or(false, |user| user == "root")
             || std::env::var("HOME").map_or(false, |home| home == "/root")
     }
     fn find_cargo_projects(&self) -> Result<Vec<PathBuf>> {
-        println!("Finding Rust projects...");
+        self.say("Finding Rust projects...");
         let mut projects = Vec::new();
         let output = Command::new("find")
             .arg(&self.options.start_dir)
@@ -108,26 +219,79 @@ impl CargoScrubber {
             if target_dir.exists() {
                 projects.push(project_dir.to_path_buf());
                 if self.options.verbose {
-                    println!("Found project: {}", project_dir.display());
+                    self.say(format!("Found project: {}", project_dir.display()));
                 }
             }
         }
-        println!("Found {} Rust projects with build artifacts", projects.len());
+        self.say(format!("Found {} Rust projects with build artifacts", projects.len()));
         Ok(projects)
     }
     fn should_exclude(&self, path: &Path) -> bool {
         let excluded = ["/proc", "/sys", "/dev", "/run", "/tmp", "/mnt", "/media"];
         let path_str = path.to_string_lossy();
         excluded.iter().any(|excl| path_str.starts_with(excl))
+            || self.options.exclude.iter().any(|excl| path.starts_with(excl))
+    }
+    fn build_targets(&self, projects: Vec<PathBuf>) -> Vec<ScrubTarget> {
+        self.say("Calculating potential space savings...");
+        projects
+            .into_iter()
+            .map(|path| {
+                let keep_release = self.options.keeps_release(&path);
+                ScrubTarget {
+                    size: self.cleanable_size(&path, keep_release),
+                    path,
+                    selected: true,
+                    keep_release,
+                }
+            })
+            .collect()
+    }
+    fn cleanable_size(&self, project: &Path, keep_release: bool) -> u64 {
+        let target_dir = project.join("target");
+        let total = self.get_dir_size(&target_dir);
+        if keep_release {
+            total.saturating_sub(self.get_dir_size(&target_dir.join("release")))
+        } else {
+            total
+        }
+    }
+    fn print_targets(&self, targets: &[ScrubTarget]) {
+        for (index, target) in targets.iter().enumerate() {
+            let checkbox = if target.selected { "[x]".green() } else { "[ ]".normal() };
+            let keep = if target.keep_release {
+                " (keep release)".yellow().to_string()
+            } else {
+                String::new()
+            };
+            println!(
+                "  {} {:>3}. {:>9}  {}{}", checkbox, index + 1, self.format_bytes(target
+                .size), target.path.display(), keep
+            );
+        }
     }
-    fn calculate_total_space(&self, projects: &[PathBuf]) -> Result<u64> {
-        println!("Calculating potential space savings...");
-        let mut total = 0u64;
-        for project in projects {
-            let target_size = self.get_dir_size(&project.join("target"));
-            total += target_size;
-        }
-        Ok(total)
+    fn select_interactively(&self, targets: &mut [ScrubTarget]) -> Result<bool> {
+        if crate::ci::is_ci() || !atty::is(atty::Stream::Stdin) {
+            bail!("Interactive scrub needs a terminal; use --dry-run to review instead");
+        }
+        loop {
+            println!();
+            self.print_targets(targets);
+            let selected: u64 = targets.iter().filter(|t| t.selected).map(|t| t.size).sum();
+            println!("Selected: {}", self.format_bytes(selected).bold());
+            print!(
+                "Toggle [numbers], keep release [r numbers], all [a], none [n], Enter = clean, q = cancel: "
+            );
+            std::io::stdout().flush()?;
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input)?;
+            match apply_selection(targets, &input) {
+                Ok(Selection::Continue) => {}
+                Ok(Selection::Proceed) => return Ok(true),
+                Ok(Selection::Cancel) => return Ok(false),
+                Err(e) => println!("{} {}", "⚠️".yellow(), e),
+            }
+        }
     }
     fn get_dir_size(&self, dir: &Path) -> u64 {
         if !dir.exists() {
@@ -163,44 +327,49 @@ impl CargoScrubber {
             format!("{:.1}{}", size, UNITS[unit_idx])
         }
     }
-    fn process_projects(&self, projects: Vec<PathBuf>) -> Result<ScrubResults> {
+    fn process_projects(&self, targets: Vec<ScrubTarget>) -> Result<ScrubResults> {
         let mut results = ScrubResults::default();
-        let filtered_projects = self.filter_projects_for_resume(projects);
-        for (i, project) in filtered_projects.iter().enumerate() {
-            println!(
-                "Processing [{}/{}]: {}", i + 1, filtered_projects.len(), project
-                .display()
-            );
+        for (i, target) in targets.iter().enumerate() {
+            let project = &target.path;
+            self.say(format!("Processing [{}/{}]: {}", i + 1, targets.len(), project.display()));
             if self.options.dry_run {
-                let target_size = self.get_dir_size(&project.join("target"));
-                println!(
-                    "Would clean: {} in {}/target", self.format_bytes(target_size),
-                    project.display()
-                );
-                results.total_savings += target_size;
+                let scope = if target.keep_release { " (keeping release)" } else { "" };
+                self.say(format!(
+                    "Would clean: {} in {}/target{}", self.format_bytes(target.size),
+                    project.display(), scope
+                ));
+                results.total_savings += target.size;
                 results.projects_processed += 1;
                 continue;
             }
-            match self.clean_project(project) {
-                Ok(saved) => {
+            match self.clean_project(project, target.keep_release) {
+                Ok((before, after)) => {
+                    let saved = before.saturating_sub(after);
+                    if before > 0 {
+                        results.changes.push(crate::audit::Change {
+                            path: project.join("target").display().to_string(),
+                            before: format!("dir:{}", before),
+                            after: format!("dir:{}", after),
+                        });
+                    }
                     if saved > 0 {
-                        println!(
+                        self.say(format!(
                             "Cleaned: {} from {}", self.format_bytes(saved), project
                             .display()
-                        );
+                        ));
                         results.total_savings += saved;
                         results.projects_cleaned += 1;
                     } else {
-                        println!(
+                        self.say(format!(
                             "No space saved in {} (already clean?)", project.display()
-                        );
+                        ));
                         results.projects_skipped += 1;
                     }
                     results.projects_processed += 1;
                 }
                 Err(e) => {
                     results.errors.push(format!("{}: {}", project.display(), e));
-                    println!("Failed to clean {} ({})", project.display(), e);
+                    self.say(format!("Failed to clean {} ({})", project.display(), e));
                 }
             }
         }
@@ -216,34 +385,34 @@ impl CargoScrubber {
             projects
         }
     }
-    fn clean_project(&self, project: &Path) -> Result<u64> {
+    /// Sizes of `target/` before and after `cargo clean`
+    fn clean_project(&self, project: &Path, keep_release: bool) -> Result<(u64, u64)> {
         let target_dir = project.join("target");
         if !target_dir.exists() {
-            return Ok(0);
+            return Ok((0, 0));
         }
         let size_before = self.get_dir_size(&target_dir);
-        let original_dir = std::env::current_dir()?;
-        std::env::set_current_dir(project)?;
+        let mut clean_args = vec!["300", "cargo", "clean"];
+        if keep_release {
+            clean_args.extend(["--profile", "dev"]);
+        }
         if self.options.verbose {
-            println!("Running: cargo clean in {}", project.display());
+            self.say(format!("Running: {} in {}", clean_args[1..].join(" "), project.display()));
         }
         if !self.is_cargo_available() {
             return Err(anyhow::anyhow!("cargo command not found"));
         }
         let clean_result = Command::new("timeout")
-            .arg("300")
-            .arg("cargo")
-            .arg("clean")
+            .args(&clean_args)
+            .current_dir(project)
             .output()
             .context("Failed to run cargo clean with timeout")?;
         if !clean_result.status.success() {
             let stderr = String::from_utf8_lossy(&clean_result.stderr);
             return Err(anyhow::anyhow!("cargo clean failed: {}", stderr));
         }
-        std::env::set_current_dir(original_dir)?;
         let size_after = self.get_dir_size(&target_dir);
-        let saved = size_before.saturating_sub(size_after);
-        Ok(saved)
+        Ok((size_before, size_after))
     }
     fn is_cargo_available(&self) -> bool {
         Command::new("cargo")
@@ -253,26 +422,23 @@ impl CargoScrubber {
             .unwrap_or(false)
     }
     fn print_summary(&self, results: &ScrubResults) {
-        println!();
-        println!("{}", "=== CLEANUP SUMMARY ===".bold());
-        println!(
+        self.say("");
+        self.say("=== CLEANUP SUMMARY ===".bold());
+        self.say(format!(
             "Projects processed: {}/{}", results.projects_processed, results
             .projects_processed + results.projects_skipped
-        );
-        println!("Space freed: {}", self.format_bytes(results.total_savings));
+        ));
+        self.say(format!("Space freed: {}", self.format_bytes(results.total_savings)));
         if !results.errors.is_empty() {
-            println!("Errors encountered:");
+            self.say("Errors encountered:");
             for error in &results.errors {
-                println!("  {} {}", "✗".red(), error);
+                self.say(format!("  {} {}", "✗".red(), error));
             }
         }
         if self.options.dry_run {
-            println!(
-                "{}", "This was a dry run. Use without --dry-run to actually clean."
-                .yellow()
-            );
+            self.say("This was a dry run. Use without --dry-run to actually clean.".yellow());
         }
-        println!("{}", "System-wide cargo clean completed!".green());
+        self.say("System-wide cargo clean completed!".green());
     }
 }
 #[derive(Default)]
@@ -282,4 +448,39 @@ struct ScrubResults {
     projects_skipped: usize,
     total_savings: u64,
     errors: Vec<String>,
-}
\ No newline at end of file
+    changes: Vec<crate::audit::Change>,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn target(path: &str) -> ScrubTarget {
+        ScrubTarget {
+            path: PathBuf::from(path),
+            size: 1024,
+            selected: true,
+            keep_release: false,
+        }
+    }
+    #[test]
+    fn selection_toggles_and_config_exclusions() {
+        let mut targets = vec![target("/work/a"), target("/work/b"), target("/work/c")];
+        assert_eq!(apply_selection(&mut targets, "1 3").unwrap(), Selection::Continue);
+        assert!(!targets[0].selected && targets[1].selected && !targets[2].selected);
+        apply_selection(&mut targets, "r 2").unwrap();
+        assert!(targets[1].keep_release);
+        apply_selection(&mut targets, "a").unwrap();
+        assert!(targets.iter().all(|t| t.selected));
+        assert!(apply_selection(&mut targets, "7").is_err());
+        assert_eq!(apply_selection(&mut targets, "\n").unwrap(), Selection::Proceed);
+        assert_eq!(apply_selection(&mut targets, "q").unwrap(), Selection::Cancel);
+        let scrubber = CargoScrubber::new(ScrubOptions {
+            exclude: vec![PathBuf::from("/work/big-monorepo")],
+            keep_release: vec![PathBuf::from("/work/app")],
+            ..ScrubOptions::default()
+        });
+        assert!(scrubber.should_exclude(Path::new("/work/big-monorepo/crates/core")));
+        assert!(!scrubber.should_exclude(Path::new("/work/big-monorepo-2")));
+        assert!(scrubber.options.keeps_release(Path::new("/work/app")));
+        assert_eq!(expand_home("~/work").file_name().unwrap(), "work");
+    }
+}
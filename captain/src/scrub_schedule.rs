@@ -0,0 +1,213 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+pub const GB: u64 = 1024 * 1024 * 1024;
+const UNIT_NAME: &str = "cargo-mate-scrub";
+const CRON_MARKER: &str = "# cargo-mate scrub";
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Hourly,
+    Daily,
+    Weekly,
+}
+impl Frequency {
+    fn calendar(self) -> &'static str {
+        match self {
+            Frequency::Hourly => "hourly",
+            Frequency::Daily => "daily",
+            Frequency::Weekly => "weekly",
+        }
+    }
+    fn cron(self) -> &'static str {
+        match self {
+            Frequency::Hourly => "0 * * * *",
+            Frequency::Daily => "30 3 * * *",
+            Frequency::Weekly => "30 3 * * 0",
+        }
+    }
+}
+#[derive(Debug, Clone)]
+pub struct ScheduleSpec {
+    pub frequency: Frequency,
+    pub free_below_gb: Option<u64>,
+    pub start: PathBuf,
+}
+impl ScheduleSpec {
+    pub fn command(&self, exe: &Path) -> String {
+        let mut command = format!(
+            "{} scrub run --quiet --start {}", shell_quote(&exe.to_string_lossy()),
+            shell_quote(&self.start.to_string_lossy())
+        );
+        if let Some(gb) = self.free_below_gb {
+            command.push_str(&format!(" --if-free-below {}", gb));
+        }
+        command
+    }
+    pub fn cron_line(&self, exe: &Path) -> String {
+        format!("{} {} {}", self.frequency.cron(), self.command(exe), CRON_MARKER)
+    }
+    pub fn systemd_units(&self, exe: &Path) -> (String, String) {
+        let service = format!(
+            "[Unit]\nDescription=cargo-mate scrub of Rust build artifacts\n\n[Service]\nType=oneshot\nExecStart={}\n",
+            self.command(exe)
+        );
+        let timer = format!(
+            "[Unit]\nDescription=Run cargo-mate scrub {}\n\n[Timer]\nOnCalendar={}\nPersistent=true\nRandomizedDelaySec=15m\n\n[Install]\nWantedBy=timers.target\n",
+            self.frequency.calendar(), self.frequency.calendar()
+        );
+        (service, timer)
+    }
+}
+fn shell_quote(text: &str) -> String {
+    if text.chars().all(|c| c.is_ascii_alphanumeric() || "/._-~".contains(c)) {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', "'\\''"))
+    }
+}
+pub fn parse_df_available(output: &str) -> Option<u64> {
+    let line = output.lines().nth(1)?;
+    let kilobytes: u64 = line.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+pub fn available_bytes(path: &Path) -> Result<u64> {
+    let output = Command::new("df")
+        .arg("-Pk")
+        .arg(path)
+        .output()
+        .context("Failed to run df")?;
+    if !output.status.success() {
+        bail!("df failed for {}", path.display());
+    }
+    parse_df_available(&String::from_utf8_lossy(&output.stdout))
+        .context("Could not read free space from df output")
+}
+fn systemd_dir() -> Option<PathBuf> {
+    let available = Command::new("systemctl")
+        .args(["--user", "show-environment"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false);
+    if !available {
+        return None;
+    }
+    Some(dirs::config_dir()?.join("systemd").join("user"))
+}
+fn systemctl(args: &[&str]) -> Result<()> {
+    let status = Command::new("systemctl").arg("--user").args(args).status()?;
+    if !status.success() {
+        bail!("systemctl --user {} failed", args.join(" "));
+    }
+    Ok(())
+}
+fn read_crontab() -> String {
+    Command::new("crontab")
+        .arg("-l")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default()
+}
+fn write_crontab(content: &str) -> Result<()> {
+    let mut child = Command::new("crontab")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("crontab not found - neither systemd user timers nor cron are available")?;
+    child.stdin.take().context("Failed to open crontab stdin")?.write_all(content.as_bytes())?;
+    if !child.wait()?.success() {
+        bail!("crontab rejected the new schedule");
+    }
+    Ok(())
+}
+fn without_marker(crontab: &str) -> String {
+    crontab
+        .lines()
+        .filter(|line| !line.ends_with(CRON_MARKER))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+pub fn install(spec: &ScheduleSpec) -> Result<()> {
+    let exe = std::env::current_exe().context("Cannot locate the cm binary")?;
+    if let Some(dir) = systemd_dir() {
+        let (service, timer) = spec.systemd_units(&exe);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(format!("{}.service", UNIT_NAME)), service)?;
+        fs::write(dir.join(format!("{}.timer", UNIT_NAME)), timer)?;
+        systemctl(&["daemon-reload"])?;
+        systemctl(&["enable", "--now", &format!("{}.timer", UNIT_NAME)])?;
+        println!("✅ Installed systemd user timer {}", format!("{}.timer", UNIT_NAME).cyan());
+    } else {
+        let mut crontab = without_marker(&read_crontab());
+        crontab.push_str(&spec.cron_line(&exe));
+        crontab.push('\n');
+        write_crontab(&crontab)?;
+        println!("✅ Installed cron entry: {}", spec.frequency.cron().cyan());
+    }
+    match spec.free_below_gb {
+        Some(gb) => {
+            println!(
+                "🧹 Scrub runs {} and only cleans when free space drops below {}GB", spec
+                .frequency.calendar(), gb
+            )
+        }
+        None => println!("🧹 Scrub runs {} in quiet mode", spec.frequency.calendar()),
+    }
+    println!("📜 Reclaimed space is logged to {}", "cm history".cyan());
+    Ok(())
+}
+pub fn remove() -> Result<()> {
+    let mut removed = false;
+    if let Some(dir) = systemd_dir() {
+        let timer = dir.join(format!("{}.timer", UNIT_NAME));
+        if timer.exists() {
+            let _ = systemctl(&["disable", "--now", &format!("{}.timer", UNIT_NAME)]);
+            fs::remove_file(&timer)?;
+            let _ = fs::remove_file(dir.join(format!("{}.service", UNIT_NAME)));
+            let _ = systemctl(&["daemon-reload"]);
+            removed = true;
+        }
+    }
+    let crontab = read_crontab();
+    let cleaned = without_marker(&crontab);
+    if cleaned.lines().count() != crontab.lines().count() {
+        write_crontab(&cleaned)?;
+        removed = true;
+    }
+    if removed {
+        println!("✅ Scheduled scrub removed");
+    } else {
+        println!("ℹ️  No scheduled scrub found");
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn renders_schedule_entries_and_reads_df() {
+        let spec = ScheduleSpec {
+            frequency: Frequency::Weekly,
+            free_below_gb: Some(20),
+            start: PathBuf::from("/home/me/my work"),
+        };
+        let exe = Path::new("/usr/local/bin/cm");
+        assert_eq!(
+            spec.cron_line(exe),
+            "30 3 * * 0 /usr/local/bin/cm scrub run --quiet --start '/home/me/my work' --if-free-below 20 # cargo-mate scrub"
+        );
+        let (service, timer) = spec.systemd_units(exe);
+        assert!(service.contains("ExecStart=/usr/local/bin/cm scrub run --quiet"));
+        assert!(timer.contains("OnCalendar=weekly"));
+        assert_eq!(without_marker("0 1 * * * backup\n"), "0 1 * * * backup\n");
+        assert_eq!(without_marker(&spec.cron_line(exe)), "");
+        let df = "Filesystem 1024-blocks Used Available Capacity Mounted on\n/dev/sda1 100 40 60 40% /\n";
+        assert_eq!(parse_df_available(df), Some(60 * 1024));
+    }
+}
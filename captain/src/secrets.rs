@@ -0,0 +1,315 @@
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use clap::Subcommand;
+use colored::*;
+use crate::anchor_crypto::{derive_key, SALT_LEN};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+const SECRET_PREFIX: &str = "enc:v1:";
+/// Passphrase-sealed values: `salt | nonce | ciphertext`, keyed with the anchor PBKDF2
+const SALTED_PREFIX: &str = "enc:v2:";
+const NONCE_LEN: usize = 12;
+const KEY_FILE: &str = ".key";
+const KEYRING_SERVICE: &str = "cargo-mate";
+const KEYRING_USER: &str = "secrets-key";
+/// Overrides the keyring / key file, e.g. for CI where neither exists
+const KEY_ENV: &str = "CM_SECRETS_KEY";
+/// Well-known names read by cargo-mate itself
+pub const LICENSE: &str = "license";
+pub const CACHE_TOKEN: &str = "cache.token";
+pub const MARKETPLACE_TOKEN: &str = "marketplace.token";
+pub const CRATES_TOKEN: &str = "crates.token";
+pub const NOTIFY_WEBHOOK: &str = "notify.webhook";
+pub const NOTIFY_SLACK: &str = "notify.slack";
+#[derive(Subcommand, Debug)]
+pub enum SecretAction {
+    /// Prompts for the value when it is omitted, keeping it out of shell history
+    Set { name: String, value: Option<String> },
+    Get { name: String },
+    Rm { name: String },
+    List,
+}
+/// The vault key, or a passphrase whose key is derived once per salt
+pub enum SecretCipher {
+    Key(ChaCha20Poly1305),
+    Passphrase {
+        passphrase: String,
+        /// Used for everything this cipher seals
+        salt: [u8; SALT_LEN],
+        keys: Mutex<HashMap<Vec<u8>, [u8; 32]>>,
+    },
+}
+impl SecretCipher {
+    pub fn from_key(key: &[u8]) -> Self {
+        SecretCipher::Key(ChaCha20Poly1305::new(Key::from_slice(key)))
+    }
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        SecretCipher::Passphrase {
+            passphrase: passphrase.to_string(),
+            salt: rand::random(),
+            keys: Mutex::new(HashMap::new()),
+        }
+    }
+    fn salted(&self, salt: &[u8]) -> Result<ChaCha20Poly1305> {
+        let SecretCipher::Passphrase { passphrase, keys, .. } = self else {
+            bail!("Value is sealed with a passphrase");
+        };
+        let mut keys = keys.lock().unwrap();
+        let key = keys.entry(salt.to_vec()).or_insert_with(|| derive_key(passphrase, salt));
+        Ok(ChaCha20Poly1305::new(Key::from_slice(key.as_slice())))
+    }
+    /// `enc:v1:` passphrase values predate the salted format and used a bare SHA-256
+    fn unsalted(&self) -> ChaCha20Poly1305 {
+        match self {
+            SecretCipher::Key(cipher) => cipher.clone(),
+            SecretCipher::Passphrase { passphrase, .. } => {
+                ChaCha20Poly1305::new(Key::from_slice(&Sha256::digest(passphrase.as_bytes())))
+            }
+        }
+    }
+}
+pub fn encrypt_with(cipher: &SecretCipher, plaintext: &str) -> Result<String> {
+    let (prefix, mut payload, aead) = match cipher {
+        SecretCipher::Key(aead) => (SECRET_PREFIX, Vec::new(), aead.clone()),
+        SecretCipher::Passphrase { salt, .. } => (SALTED_PREFIX, salt.to_vec(), cipher.salted(salt)?),
+    };
+    let nonce = rand::random::<[u8; NONCE_LEN]>();
+    let ciphertext = aead
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt value"))?;
+    payload.extend(nonce);
+    payload.extend(ciphertext);
+    Ok(format!("{}{}", prefix, general_purpose::STANDARD.encode(payload)))
+}
+pub fn decrypt_with(cipher: &SecretCipher, stored: &str) -> Result<String> {
+    let decode = |encoded: &str| general_purpose::STANDARD.decode(encoded).context("Invalid encrypted value");
+    let stored = stored.trim();
+    let (aead, payload) = if let Some(encoded) = stored.strip_prefix(SALTED_PREFIX) {
+        let payload = decode(encoded)?;
+        if payload.len() <= SALT_LEN {
+            bail!("Invalid encrypted value");
+        }
+        let (salt, rest) = payload.split_at(SALT_LEN);
+        (cipher.salted(salt)?, rest.to_vec())
+    } else {
+        (cipher.unsalted(), decode(stored.strip_prefix(SECRET_PREFIX).context("Value is not encrypted")?)?)
+    };
+    if payload.len() <= NONCE_LEN {
+        bail!("Invalid encrypted value");
+    }
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let plaintext = aead
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Cannot decrypt - the key does not match"))?;
+    Ok(String::from_utf8(plaintext)?)
+}
+pub fn is_encrypted(stored: &str) -> bool {
+    let stored = stored.trim();
+    stored.starts_with(SECRET_PREFIX) || stored.starts_with(SALTED_PREFIX)
+}
+fn decode_key(encoded: &str, origin: &str) -> Result<Vec<u8>> {
+    let key = general_purpose::STANDARD
+        .decode(encoded.trim())
+        .with_context(|| format!("Corrupt key in {}", origin))?;
+    if key.len() != 32 {
+        bail!("Key in {} must hold 32 bytes", origin);
+    }
+    Ok(key)
+}
+/// Reads a base64 key file, creating it (mode 0600) on first use
+pub fn load_or_create_key_file(path: &Path) -> Result<Vec<u8>> {
+    if path.exists() {
+        return decode_key(&fs::read_to_string(path)?, &path.display().to_string());
+    }
+    let key = rand::random::<[u8; 32]>().to_vec();
+    write_private(path, &general_purpose::STANDARD.encode(&key))?;
+    Ok(key)
+}
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    crate::storage::write_private(path, content)
+}
+fn secrets_dir() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join("secrets"))
+}
+/// [`secrets_dir`], created 0700 or tightened to it before anything is written there
+fn private_secrets_dir() -> Result<PathBuf> {
+    let dir = secrets_dir()?;
+    crate::storage::create_private_dir(&dir)?;
+    Ok(dir)
+}
+/// The vault key lives in the OS keyring when one is available, otherwise in
+/// `~/.shipwreck/secrets/.key`; an existing key file always wins so the key never moves
+fn master_key() -> Result<Vec<u8>> {
+    let key_file = private_secrets_dir()?.join(KEY_FILE);
+    if key_file.exists() {
+        return load_or_create_key_file(&key_file);
+    }
+    let Ok(entry) = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER) else {
+        return load_or_create_key_file(&key_file);
+    };
+    match entry.get_password() {
+        Ok(encoded) => decode_key(&encoded, "the OS keyring"),
+        Err(keyring::Error::NoEntry) => {
+            let key = rand::random::<[u8; 32]>().to_vec();
+            if entry.set_password(&general_purpose::STANDARD.encode(&key)).is_ok() {
+                Ok(key)
+            } else {
+                load_or_create_key_file(&key_file)
+            }
+        }
+        Err(_) => load_or_create_key_file(&key_file),
+    }
+}
+fn cipher() -> Result<SecretCipher> {
+    match std::env::var(KEY_ENV) {
+        Ok(passphrase) if !passphrase.is_empty() => Ok(SecretCipher::from_passphrase(&passphrase)),
+        _ => Ok(SecretCipher::from_key(&master_key()?)),
+    }
+}
+fn secret_path(name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if !valid {
+        bail!("Secret names may only contain letters, digits, '.', '-' and '_'");
+    }
+    Ok(secrets_dir()?.join(format!("{}.secret", name)))
+}
+pub fn set(name: &str, value: &str) -> Result<()> {
+    let path = secret_path(name)?;
+    private_secrets_dir()?;
+    write_private(&path, &encrypt_with(&cipher()?, value)?)
+}
+pub fn get(name: &str) -> Result<Option<String>> {
+    let path = secret_path(name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let value = decrypt_with(&cipher()?, &fs::read_to_string(&path)?)
+        .with_context(|| format!("Secret '{}' - set {} if it was stored elsewhere", name, KEY_ENV))?;
+    Ok(Some(value))
+}
+/// For callers that treat an unreadable secret like a missing one
+pub fn lookup(name: &str) -> Option<String> {
+    match get(name) {
+        Ok(value) => value.filter(|v| !v.trim().is_empty()),
+        Err(e) => {
+            eprintln!("⚠️  {:#}", e);
+            None
+        }
+    }
+}
+pub fn remove(name: &str) -> Result<bool> {
+    let path = secret_path(name)?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(path)?;
+    Ok(true)
+}
+pub fn list() -> Result<Vec<String>> {
+    let dir = secrets_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().to_str()?.strip_suffix(".secret").map(str::to_string))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+pub fn handle_secret_command(action: SecretAction) -> Result<()> {
+    match action {
+        SecretAction::Set { name, value } => {
+            let value = match value {
+                Some(value) => value,
+                None => rpassword::prompt_password(format!("🔑 Value for {}: ", name))?,
+            };
+            set(&name, &value)?;
+            println!("✅ Stored secret {}", name.cyan());
+        }
+        SecretAction::Get { name } => {
+            let value = get(&name)?.with_context(|| format!("No secret named '{}'", name))?;
+            let mut out = std::io::stdout();
+            writeln!(out, "{}", value)?;
+        }
+        SecretAction::Rm { name } => {
+            if remove(&name)? {
+                println!("🗑️  Removed secret {}", name.cyan());
+            } else {
+                println!("⚠️  No secret named '{}'", name);
+            }
+        }
+        SecretAction::List => {
+            let names = list()?;
+            if names.is_empty() {
+                println!("No secrets stored. Use 'cm secret set <name>'");
+                return Ok(());
+            }
+            println!("🔐 Stored secrets:");
+            for name in names {
+                println!("  {}", name.cyan());
+            }
+            println!();
+            println!(
+                "{}",
+                format!(
+                    "cargo-mate reads {}, {}, {}, {}, {} and {}",
+                    LICENSE, CACHE_TOKEN, MARKETPLACE_TOKEN, CRATES_TOKEN, NOTIFY_WEBHOOK, NOTIFY_SLACK
+                )
+                .dimmed()
+            );
+        }
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_encrypt_round_trip() {
+        let cipher = SecretCipher::from_passphrase("correct horse");
+        let first = encrypt_with(&cipher, "hunter2").unwrap();
+        assert!(is_encrypted(&first) && first.starts_with(SALTED_PREFIX));
+        assert_ne!(first, encrypt_with(&cipher, "hunter2").unwrap());
+        assert_eq!(decrypt_with(&cipher, &first).unwrap(), "hunter2");
+        let other = SecretCipher::from_passphrase("correct horse");
+        assert_eq!(decrypt_with(&other, &first).unwrap(), "hunter2");
+        assert_ne!(encrypt_with(&other, "hunter2").unwrap()[..40], first[..40]);
+        assert!(decrypt_with(&SecretCipher::from_passphrase("battery staple"), &first).is_err());
+        let vault = SecretCipher::from_key(&[7; 32]);
+        assert!(decrypt_with(&vault, &first).is_err());
+        assert_eq!(decrypt_with(&vault, &encrypt_with(&vault, "token").unwrap()).unwrap(), "token");
+        let legacy = {
+            let aead = ChaCha20Poly1305::new(Key::from_slice(&Sha256::digest(b"correct horse")));
+            let mut payload = vec![0u8; NONCE_LEN];
+            payload.extend(aead.encrypt(Nonce::from_slice(&[0u8; NONCE_LEN]), b"old".as_slice()).unwrap());
+            format!("{}{}", SECRET_PREFIX, general_purpose::STANDARD.encode(payload))
+        };
+        assert_eq!(decrypt_with(&cipher, &legacy).unwrap(), "old");
+        assert!(secret_path("notify.slack").is_ok());
+        assert!(secret_path("../escape").is_err());
+        assert!(secret_path(".key").is_err());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let temp = tempfile::tempdir().unwrap();
+            let dir = temp.path().join("secrets");
+            let key = load_or_create_key_file(&dir.join(KEY_FILE)).unwrap();
+            assert_eq!(load_or_create_key_file(&dir.join(KEY_FILE)).unwrap(), key);
+            let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&dir.join(KEY_FILE)), 0o600);
+            assert_eq!(mode(&dir), 0o700);
+            fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+            crate::storage::create_private_dir(&dir).unwrap();
+            assert_eq!(mode(&dir), 0o700);
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use crate::captain::integrity::{self, VerifiedManifest};
+use anyhow::{bail, Context, Result};
+use clap::{Subcommand, ValueEnum};
+use colored::*;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+const MANIFEST_NAME: &str = "cm-manifest.json";
+const SETTINGS_FILE: &str = "self_update.json";
+const DATA_VERSION_FILE: &str = "data_version";
+const BACKUP_DIR: &str = "migration-backups";
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    #[default]
+    Stable,
+    Beta,
+}
+impl Channel {
+    fn as_str(self) -> &'static str {
+        match self {
+            Channel::Stable => "stable",
+            Channel::Beta => "beta",
+        }
+    }
+}
+#[derive(Subcommand, Debug)]
+pub enum SelfAction {
+    /// Install the newest cargo-mate release on the channel
+    Update {
+        /// Switch to this channel and remember it
+        #[arg(long, value_enum)]
+        channel: Option<Channel>,
+        /// Only report whether an update is available
+        #[arg(long)]
+        check: bool,
+        /// Reinstall even when already on the newest release
+        #[arg(long)]
+        force: bool,
+    },
+    /// Restore the binary and data from before the last update
+    Rollback,
+    /// Apply pending ~/.shipwreck data migrations
+    Migrate,
+    /// Show the channel, version and data format
+    Status,
+}
+/// What the previous update replaced, so rollback can put it back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Previous {
+    version: String,
+    binary: String,
+    data_version: u32,
+}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Settings {
+    #[serde(default)]
+    channel: Channel,
+    #[serde(default)]
+    previous: Option<Previous>,
+}
+fn settings_path() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join(SETTINGS_FILE))
+}
+fn load_settings() -> Settings {
+    settings_path()
+        .ok()
+        .and_then(|path| crate::storage::read_json_file(&path).ok().flatten())
+        .unwrap_or_default()
+}
+fn save_settings(settings: &Settings) -> Result<()> {
+    crate::storage::write_json_atomic(&settings_path()?, settings)
+}
+/// One change to the layout of ~/.shipwreck; `touches` is backed up before `run`
+#[derive(Clone)]
+struct Migration {
+    version: u32,
+    description: &'static str,
+    touches: &'static [&'static str],
+    run: fn(&Path) -> Result<()>,
+}
+/// Append a step here whenever a release changes how ~/.shipwreck data is stored
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "Start tracking the data format version",
+    touches: &[],
+    run: |_| Ok(()),
+}];
+/// Data format this binary reads and writes
+pub fn latest_data_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+fn data_version(root: &Path) -> u32 {
+    fs::read_to_string(root.join(DATA_VERSION_FILE)).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(0)
+}
+fn set_data_version(root: &Path, version: u32) -> Result<()> {
+    crate::storage::write_atomic(&root.join(DATA_VERSION_FILE), format!("{}\n", version))
+}
+/// Runs every migration newer than the recorded data version, backing up what each touches
+fn migrate(root: &Path, migrations: &[Migration]) -> Result<Vec<&'static str>> {
+    let current = data_version(root);
+    let latest = migrations.last().map_or(0, |m| m.version);
+    if current > latest {
+        bail!(
+            "~/.shipwreck uses data format {} but this cargo-mate only knows {}; update cargo-mate or run 'cm self rollback'",
+            current,
+            latest
+        );
+    }
+    let mut applied = Vec::new();
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        let backup = root.join(BACKUP_DIR).join(migration.version.to_string());
+        // A leftover from an earlier attempt would restore files this run never touched
+        let _ = fs::remove_dir_all(&backup);
+        for file in migration.touches {
+            let source = root.join(file);
+            if source.is_file() {
+                let copy = backup.join(file);
+                fs::create_dir_all(copy.parent().unwrap_or(&backup))?;
+                fs::copy(&source, copy)?;
+            }
+        }
+        (migration.run)(root).with_context(|| format!("Data migration {} failed: {}", migration.version, migration.description))?;
+        set_data_version(root, migration.version)?;
+        applied.push(migration.description);
+    }
+    Ok(applied)
+}
+/// Restores what every migration after `version` backed up, newest first. Walks the backups on
+/// disk rather than this binary's table, so an older binary rolling back a failed update also
+/// undoes migrations it doesn't know about
+fn revert_migrations(root: &Path, version: u32) -> Result<()> {
+    let backups = root.join(BACKUP_DIR);
+    let mut newer: Vec<u32> = fs::read_dir(&backups)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+                .filter(|v| *v > version)
+                .collect()
+        })
+        .unwrap_or_default();
+    newer.sort_unstable_by(|a, b| b.cmp(a));
+    for migration in newer {
+        let backup = backups.join(migration.to_string());
+        for entry in walkdir::WalkDir::new(&backup) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let target = root.join(entry.path().strip_prefix(&backup)?);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(entry.path(), &target)?;
+            }
+        }
+        fs::remove_dir_all(&backup)?;
+    }
+    set_data_version(root, version)
+}
+fn release_url(channel: Channel) -> String {
+    let base = std::env::var("CM_UPDATE_URL").unwrap_or_else(|_| "https://get.cargo.do/mate".to_string());
+    format!("{}/{}", base.trim_end_matches('/'), channel.as_str())
+}
+/// The binary answers `--version` with the release it claims to be
+fn self_test(binary: &Path, version: &str) -> Result<()> {
+    let output = Command::new(binary)
+        .arg("--version")
+        .env("CM_NO_AUTO_INSTALL", "1")
+        .output()
+        .with_context(|| format!("Cannot run {}", binary.display()))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || !stdout.contains(version.trim_start_matches('v')) {
+        bail!("Self-test of {} failed: expected version {}, got '{}'", binary.display(), version, stdout.trim());
+    }
+    Ok(())
+}
+/// Swaps `binary` in for `target`; renaming keeps a running executable valid on unix
+fn replace_binary(binary: &[u8], target: &Path) -> Result<()> {
+    let staged = target.with_extension("new");
+    fs::write(&staged, binary).with_context(|| format!("Cannot write next to {}", target.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+    #[cfg(windows)]
+    {
+        let old = target.with_extension("old");
+        let _ = fs::remove_file(&old);
+        fs::rename(target, &old)?;
+    }
+    fs::rename(&staged, target)?;
+    Ok(())
+}
+/// The `cm` binary inside a release archive, read without touching the disk
+fn binary_from_archive(archive: &[u8], platform: &str) -> Result<Vec<u8>> {
+    let binary = Path::new(&format!("cm-{}", platform)).join(if cfg!(windows) { "cm.exe" } else { "cm" });
+    integrity::file_from_archive(archive, &binary)
+}
+/// Downloads the release and returns its `cm` binary; both hashes are checked on the bytes
+/// that get installed, never on a file that could change in between
+async fn download(client: &reqwest::Client, channel: Channel, verified: &VerifiedManifest) -> Result<Vec<u8>> {
+    let platform = crate::captain::manage::detect_platform()?;
+    let digest = verified.manifest.artifact(&platform)?.clone();
+    let archive_name = format!("cm-{}.tar.gz", platform);
+    let url = format!("{}/{}", release_url(channel), archive_name);
+    println!("📥 Downloading {}", url);
+    let response = client.get(&url).send().await?;
+    if !response.status().is_success() {
+        bail!("Failed to download {}: {}", url, response.status());
+    }
+    let bytes = response.bytes().await?;
+    integrity::check_sha256(&bytes, &digest.archive_sha256, &archive_name)?;
+    let binary = binary_from_archive(&bytes, &platform).with_context(|| format!("Failed to extract {}", archive_name))?;
+    integrity::check_sha256(&binary, &digest.binary_sha256, "cm")?;
+    Ok(binary)
+}
+async fn update(channel: Option<Channel>, check: bool, force: bool) -> Result<()> {
+    let mut settings = load_settings();
+    if let Some(channel) = channel {
+        settings.channel = channel;
+        save_settings(&settings)?;
+    }
+    let current = env!("CARGO_PKG_VERSION");
+    let client = crate::net::async_client(DOWNLOAD_TIMEOUT)?;
+    println!("🔏 Verifying signed {} manifest...", settings.channel.as_str().cyan());
+    let verified = integrity::fetch_signed_manifest(&client, &release_url(settings.channel), MANIFEST_NAME).await?;
+    println!("   {}", verified.trusted_comment.dimmed());
+    let latest = &verified.manifest.version;
+    let newer = match (Version::parse(latest.trim_start_matches('v')), Version::parse(current)) {
+        (Ok(latest), Ok(current)) => latest > current,
+        _ => latest.trim_start_matches('v') != current,
+    };
+    if !newer && !force {
+        println!("✅ cargo-mate {} is up to date on the {} channel", current.green().bold(), settings.channel.as_str());
+        return Ok(());
+    }
+    if check {
+        println!("⬆️  cargo-mate {} → {} is available ('cm self update' installs it)", current.yellow(), latest.green());
+        return Ok(());
+    }
+    let exe = std::env::current_exe()?.canonicalize()?;
+    let root = crate::project::shipwreck_dir()?;
+    // Private to this user, so nothing can swap the binary between self-test and install
+    let workdir = tempfile::TempDir::new()?;
+    let result = async {
+        let binary = download(&client, settings.channel, &verified).await?;
+        let candidate = workdir.path().join(if cfg!(windows) { "cm.exe" } else { "cm" });
+        fs::write(&candidate, &binary)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&candidate, fs::Permissions::from_mode(0o700))?;
+        }
+        self_test(&candidate, latest)?;
+        let previous = root.join("bin").join(format!("cm-{}", current));
+        fs::create_dir_all(root.join("bin"))?;
+        fs::copy(&exe, &previous)?;
+        settings.previous = Some(Previous {
+            version: current.to_string(),
+            binary: previous.to_string_lossy().to_string(),
+            data_version: data_version(&root),
+        });
+        save_settings(&settings)?;
+        replace_binary(&binary, &exe)?;
+        let migrated = Command::new(&exe).args(["self", "migrate"]).env("CM_NO_AUTO_INSTALL", "1").status();
+        if let Err(e) = self_test(&exe, latest).and_then(|_| match migrated {
+            Ok(status) if status.success() => Ok(()),
+            Ok(status) => bail!("'cm self migrate' exited with {}", status),
+            Err(e) => Err(e.into()),
+        }) {
+            eprintln!("❌ {} - rolling back to {}", e, current);
+            rollback()?;
+            bail!("Update to {} failed and was rolled back", latest);
+        }
+        Ok(())
+    }
+    .await;
+    drop(workdir);
+    result?;
+    println!("✅ cargo-mate updated {} → {} ({})", current.yellow(), latest.green().bold(), settings.channel.as_str());
+    println!("   'cm self rollback' restores {}", current);
+    Ok(())
+}
+fn rollback() -> Result<()> {
+    let mut settings = load_settings();
+    let previous = settings.previous.take().context("No previous cargo-mate to roll back to")?;
+    let binary = PathBuf::from(&previous.binary);
+    if !binary.is_file() {
+        bail!("The previous binary {} is missing", binary.display());
+    }
+    let exe = std::env::current_exe()?.canonicalize()?;
+    revert_migrations(&crate::project::shipwreck_dir()?, previous.data_version)?;
+    replace_binary(&fs::read(&binary)?, &exe)?;
+    save_settings(&settings)?;
+    println!("↩️  Rolled back to cargo-mate {}", previous.version.green().bold());
+    Ok(())
+}
+fn status() -> Result<()> {
+    let settings = load_settings();
+    let root = crate::project::shipwreck_dir()?;
+    println!("🧭 {}", "cargo-mate".bold());
+    println!("Version: {}", env!("CARGO_PKG_VERSION").cyan());
+    println!("Channel: {}", settings.channel.as_str());
+    println!("Data format: {} (this binary: {})", data_version(&root), latest_data_version());
+    match settings.previous {
+        Some(previous) => println!("Rollback to: {} ({})", previous.version, previous.binary.dimmed()),
+        None => println!("Rollback to: {}", "none".dimmed()),
+    }
+    Ok(())
+}
+pub async fn handle_self_command(action: SelfAction) -> Result<()> {
+    match action {
+        SelfAction::Update { channel, check, force } => update(channel, check, force).await?,
+        SelfAction::Rollback => rollback()?,
+        SelfAction::Migrate => {
+            let root = crate::project::shipwreck_dir()?;
+            let applied = migrate(&root, MIGRATIONS)?;
+            if applied.is_empty() {
+                println!("✅ ~/.shipwreck is at data format {}", data_version(&root));
+            }
+            for description in applied {
+                println!("🔧 {}", description);
+            }
+        }
+        SelfAction::Status => status()?,
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn rename_pin(root: &Path) -> Result<()> {
+        let path = root.join("settings.json");
+        fs::write(&path, fs::read_to_string(&path)?.replace("\"version\"", "\"pinned_version\""))?;
+        Ok(())
+    }
+    #[test]
+    fn test_migrations_apply_in_order_and_revert() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        fs::write(root.join("settings.json"), r#"{"version":"1.4.0"}"#).unwrap();
+        let migrations = [
+            MIGRATIONS[0].clone(),
+            Migration { version: 2, description: "Rename the pin", touches: &["settings.json"], run: rename_pin },
+        ];
+        assert_eq!(migrate(&root, &migrations).unwrap(), vec![MIGRATIONS[0].description, "Rename the pin"]);
+        assert_eq!(data_version(&root), 2);
+        assert_eq!(fs::read_to_string(root.join("settings.json")).unwrap(), r#"{"pinned_version":"1.4.0"}"#);
+        assert!(migrate(&root, &migrations).unwrap().is_empty());
+        revert_migrations(&root, 1).unwrap();
+        assert!(!root.join(BACKUP_DIR).join("2").exists());
+        assert_eq!(data_version(&root), 1);
+        assert_eq!(fs::read_to_string(root.join("settings.json")).unwrap(), r#"{"version":"1.4.0"}"#);
+        assert!(migrate(&root, MIGRATIONS).unwrap().is_empty());
+        set_data_version(&root, 3).unwrap();
+        assert!(migrate(&root, &migrations).is_err());
+    }
+    #[test]
+    fn test_binary_is_read_from_the_archive_in_memory() {
+        let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast()));
+        let binary = if cfg!(windows) { "cm.exe" } else { "cm" };
+        let mut header = tar::Header::new_gnu();
+        header.set_size(9);
+        header.set_mode(0o755);
+        header.set_cksum();
+        archive.append_data(&mut header, format!("cm-linux-x86_64/{}", binary), &b"#!cm 2.0\n"[..]).unwrap();
+        let bytes = archive.into_inner().unwrap().finish().unwrap();
+        assert_eq!(binary_from_archive(&bytes, "linux-x86_64").unwrap(), b"#!cm 2.0\n");
+        assert!(binary_from_archive(&bytes, "darwin-arm64").is_err());
+        assert!(binary_from_archive(b"not gzip", "linux-x86_64").is_err());
+    }
+}
@@ -0,0 +1,419 @@
+use crate::anchor::AnchorManager;
+use crate::checklist;
+use crate::parser::ParsedError;
+use crate::tide::{self, TideCharts};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const SEVERITY_ERROR: u8 = 1;
+const SEVERITY_WARNING: u8 = 2;
+pub struct DiagnosticStore {
+    root: PathBuf,
+    data_dir: PathBuf,
+}
+impl DiagnosticStore {
+    pub fn new(root: PathBuf, data_dir: PathBuf) -> Self {
+        Self { root, data_dir }
+    }
+    pub fn current() -> Result<Self> {
+        let root = match crate::project::current_project() {
+            Some(project) => {
+                project.manifest.parent().map(Path::to_path_buf).unwrap_or_default()
+            }
+            None => std::env::current_dir()?,
+        };
+        Ok(Self::new(root, crate::project::data_dir()?))
+    }
+    fn latest_files(&self) -> [(PathBuf, u8); 2] {
+        [
+            (self.data_dir.join("errors").join("latest.txt"), SEVERITY_ERROR),
+            (self.data_dir.join("warnings").join("latest.txt"), SEVERITY_WARNING),
+        ]
+    }
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.latest_files()
+            .iter()
+            .filter_map(|(path, _)| fs::metadata(path).and_then(|m| m.modified()).ok())
+            .max()
+    }
+    pub fn diagnostics(&self) -> BTreeMap<String, Vec<Value>> {
+        let mut by_file: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+        for (path, severity) in self.latest_files() {
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            for error in content.lines().filter_map(ParsedError::parse_stored) {
+                if error.file == "unknown" {
+                    continue;
+                }
+                let line = error.line.saturating_sub(1);
+                by_file
+                    .entry(file_uri(&self.root.join(&error.file)))
+                    .or_default()
+                    .push(
+                        json!({
+                            "range": {
+                                "start": { "line": line, "character": 0 },
+                                "end": { "line": line + 1, "character": 0 }
+                            },
+                            "severity": severity,
+                            "code": error.code,
+                            "source": "cargo-mate",
+                            "message": error.message,
+                        }),
+                    );
+            }
+        }
+        by_file
+    }
+    pub fn publish(&self, published: &mut BTreeSet<String>) -> Vec<Value> {
+        let diagnostics = self.diagnostics();
+        let mut notifications: Vec<Value> = published
+            .iter()
+            .filter(|uri| !diagnostics.contains_key(*uri))
+            .map(|uri| publish_notification(uri, Vec::new()))
+            .collect();
+        *published = diagnostics.keys().cloned().collect();
+        notifications
+            .extend(
+                diagnostics
+                    .into_iter()
+                    .map(|(uri, diagnostics)| publish_notification(&uri, diagnostics)),
+            );
+        notifications
+    }
+    pub fn checklist(&self) -> Value {
+        let items = checklist::load_items(&self.data_dir.join("checklists").join("items.json"))
+            .unwrap_or_default();
+        json!(items)
+    }
+    pub fn status(&self) -> Value {
+        let anchors = AnchorManager::new()
+            .and_then(|manager| manager.list())
+            .map(|anchors| {
+                anchors
+                    .into_iter()
+                    .map(|a| {
+                        json!({
+                            "name": a.name, "timestamp": a.timestamp, "files": a.files_count,
+                            "description": a.description
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let durations: Vec<f64> = TideCharts::new()
+            .map(|tide| tide.builds().iter().map(|b| b.duration_seconds).collect())
+            .unwrap_or_default();
+        let diagnostics = self.diagnostics();
+        let count = |severity: u8| {
+            diagnostics
+                .values()
+                .flatten()
+                .filter(|d| d["severity"] == json!(severity))
+                .count()
+        };
+        json!({
+            "root": self.root,
+            "errors": count(SEVERITY_ERROR),
+            "warnings": count(SEVERITY_WARNING),
+            "anchors": anchors,
+            "tide": tide_regression(&durations),
+        })
+    }
+}
+pub fn tide_regression(durations: &[f64]) -> Value {
+    let Some((last, previous)) = durations.split_last() else {
+        return json!({ "last": null, "baseline": null, "regression": false });
+    };
+    match tide::regression_baseline(previous) {
+        Some(baseline) => {
+            json!({
+                "last": last,
+                "baseline": baseline,
+                "regression": tide::is_regression(previous, *last),
+            })
+        }
+        None => json!({ "last": last, "baseline": null, "regression": false }),
+    }
+}
+fn file_uri(path: &Path) -> String {
+    let encoded: Vec<String> = path
+        .to_string_lossy()
+        .replace('\\', "/")
+        .split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect();
+    let path = encoded.join("/");
+    if path.starts_with('/') { format!("file://{}", path) } else { format!("file:///{}", path) }
+}
+fn publish_notification(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics }
+    })
+}
+fn response(id: &Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+pub enum Reply {
+    Messages(Vec<Value>),
+    Exit,
+}
+pub struct Session {
+    store: DiagnosticStore,
+    published: BTreeSet<String>,
+}
+impl Session {
+    pub fn new(store: DiagnosticStore) -> Self {
+        Self {
+            store,
+            published: BTreeSet::new(),
+        }
+    }
+    pub fn handle(&mut self, message: &Value) -> Reply {
+        let method = message["method"].as_str().unwrap_or_default();
+        let id = message.get("id");
+        let mut out = Vec::new();
+        match method {
+            "initialize" => {
+                if let Some(root) = message["params"]["rootUri"]
+                    .as_str()
+                    .and_then(|uri| uri.strip_prefix("file://"))
+                {
+                    let root = urlencoding::decode(root)
+                        .map(|r| r.into_owned())
+                        .unwrap_or_else(|_| root.to_string());
+                    self.store.root = PathBuf::from(root);
+                }
+                out.push(
+                    response(
+                        id.unwrap_or(&Value::Null),
+                        json!({
+                            "capabilities": { "textDocumentSync": { "openClose": true, "save": true } },
+                            "serverInfo": { "name": "cargo-mate", "version": env!("CARGO_PKG_VERSION") }
+                        }),
+                    ),
+                );
+            }
+            "initialized" | "textDocument/didOpen" | "textDocument/didSave"
+            | "cargoMate/refresh" => {
+                out.extend(self.store.publish(&mut self.published));
+                if let Some(id) = id {
+                    out.push(response(id, Value::Null));
+                }
+            }
+            "cargoMate/diagnostics" => {
+                out.push(response(id.unwrap_or(&Value::Null), json!(self.store.diagnostics())));
+            }
+            "cargoMate/checklist" => {
+                out.push(response(id.unwrap_or(&Value::Null), self.store.checklist()));
+            }
+            "cargoMate/status" => {
+                out.push(response(id.unwrap_or(&Value::Null), self.store.status()));
+            }
+            "shutdown" => out.push(response(id.unwrap_or(&Value::Null), Value::Null)),
+            "exit" => return Reply::Exit,
+            _ => {
+                if let Some(id) = id {
+                    out.push(error_response(id, -32601, &format!("Method not found: {}", method)));
+                }
+            }
+        }
+        Reply::Messages(out)
+    }
+    pub fn poll(&mut self) -> Vec<Value> {
+        self.store.publish(&mut self.published)
+    }
+}
+fn read_lsp_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = Some(value.trim().parse::<usize>().context("Invalid Content-Length")?);
+        }
+    }
+    let mut body = vec![0; length.context("Missing Content-Length header")?];
+    reader.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+fn write_lsp_message(out: &mut impl Write, message: &Value) -> Result<()> {
+    let body = serde_json::to_string(message)?;
+    write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    out.flush()?;
+    Ok(())
+}
+fn spawn_watcher<F>(session: Arc<Mutex<Session>>, mut send: F)
+where
+    F: FnMut(&Value) -> Result<()> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut last_seen = session.lock().ok().and_then(|s| s.store.modified());
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let Ok(mut session) = session.lock() else {
+                return;
+            };
+            let modified = session.store.modified();
+            if modified == last_seen {
+                continue;
+            }
+            last_seen = modified;
+            for message in session.poll() {
+                if send(&message).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+}
+pub fn serve_lsp() -> Result<()> {
+    let session = Arc::new(Mutex::new(Session::new(DiagnosticStore::current()?)));
+    let stdout = Arc::new(Mutex::new(std::io::stdout()));
+    let watcher_out = Arc::clone(&stdout);
+    spawn_watcher(
+        Arc::clone(&session),
+        move |message| {
+            let mut out = watcher_out.lock().map_err(|_| anyhow::anyhow!("stdout poisoned"))?;
+            write_lsp_message(&mut *out, message)
+        },
+    );
+    let stdin = std::io::stdin();
+    let mut reader = stdin.lock();
+    while let Some(message) = read_lsp_message(&mut reader)? {
+        let reply = session
+            .lock()
+            .map_err(|_| anyhow::anyhow!("session poisoned"))?
+            .handle(&message);
+        match reply {
+            Reply::Exit => break,
+            Reply::Messages(messages) => {
+                let mut out = stdout.lock().map_err(|_| anyhow::anyhow!("stdout poisoned"))?;
+                for message in messages {
+                    write_lsp_message(&mut *out, &message)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+pub fn serve_socket(port: u16) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .with_context(|| format!("Failed to bind 127.0.0.1:{}", port))?;
+    eprintln!("🔌 cargo-mate JSON-RPC listening on 127.0.0.1:{}", listener.local_addr()?.port());
+    for stream in listener.incoming() {
+        let stream = stream?;
+        std::thread::spawn(move || -> Result<()> {
+            let session = Arc::new(Mutex::new(Session::new(DiagnosticStore::current()?)));
+            let writer = Arc::new(Mutex::new(stream.try_clone()?));
+            let watcher_out = Arc::clone(&writer);
+            spawn_watcher(
+                Arc::clone(&session),
+                move |message| {
+                    let mut out = watcher_out
+                        .lock()
+                        .map_err(|_| anyhow::anyhow!("socket poisoned"))?;
+                    writeln!(out, "{}", serde_json::to_string(message)?)?;
+                    Ok(())
+                },
+            );
+            for line in BufReader::new(stream).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let message: Value = match serde_json::from_str(&line) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        let mut out = writer
+                            .lock()
+                            .map_err(|_| anyhow::anyhow!("socket poisoned"))?;
+                        writeln!(out, "{}", error_response(&Value::Null, -32700, &e.to_string()))?;
+                        continue;
+                    }
+                };
+                let reply = session
+                    .lock()
+                    .map_err(|_| anyhow::anyhow!("session poisoned"))?
+                    .handle(&message);
+                let Reply::Messages(messages) = reply else {
+                    break;
+                };
+                let mut out = writer.lock().map_err(|_| anyhow::anyhow!("socket poisoned"))?;
+                for message in messages {
+                    writeln!(out, "{}", message)?;
+                }
+            }
+            Ok(())
+        });
+    }
+    Ok(())
+}
+pub fn handle_serve(lsp: bool, port: Option<u16>) -> Result<()> {
+    match (lsp, port) {
+        (true, None) => serve_lsp(),
+        (false, Some(port)) => serve_socket(port),
+        (true, Some(_)) => anyhow::bail!("--lsp and --port cannot be combined"),
+        (false, None) => {
+            anyhow::bail!(
+                "Choose a transport: 'cm serve --lsp' (stdio) or 'cm serve --port <port>' (JSON-RPC socket)"
+            )
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_publish_diagnostics_from_latest_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let data = temp.path().join("data");
+        fs::create_dir_all(data.join("errors")).unwrap();
+        fs::create_dir_all(data.join("warnings")).unwrap();
+        fs::write(
+                data.join("errors").join("latest.txt"),
+                "[E0308] src/lib.rs:12 - mismatched types\n",
+            )
+            .unwrap();
+        fs::write(
+                data.join("warnings").join("latest.txt"),
+                "[unused_variables] src/main.rs:3 - unused variable: `x`\n",
+            )
+            .unwrap();
+        let mut session = Session::new(DiagnosticStore::new(PathBuf::from("/work/demo"), data.clone()));
+        let Reply::Messages(messages) = session
+            .handle(&json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} })) else {
+            panic!("unexpected exit");
+        };
+        assert_eq!(messages.len(), 2);
+        let lib = &messages[0]["params"];
+        assert_eq!(lib["uri"], "file:///work/demo/src/lib.rs");
+        assert_eq!(lib["diagnostics"][0]["range"]["start"]["line"], 11);
+        assert_eq!(lib["diagnostics"][0]["severity"], 1);
+        fs::write(data.join("errors").join("latest.txt"), "").unwrap();
+        let cleared = session.poll();
+        assert_eq!(cleared[0]["params"]["uri"], "file:///work/demo/src/lib.rs");
+        assert_eq!(cleared[0]["params"]["diagnostics"], json!([]));
+        assert_eq!(tide_regression(&[10.0, 11.0, 9.0, 20.0])["regression"], true);
+        assert_eq!(tide_regression(&[10.0, 11.0])["regression"], false);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::captain::config::ConfigManager;
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::collections::BTreeMap;
+const MAX_DEPTH: usize = 16;
+pub fn load(config: &ConfigManager) -> BTreeMap<String, String> {
+    config
+        .entries()
+        .into_iter()
+        .filter_map(|entry| {
+            let name = entry.key.strip_prefix("shortcuts.")?;
+            Some((name.to_string(), entry.value))
+        })
+        .collect()
+}
+fn subcommand_index(args: &[String]) -> Option<usize> {
+    args.iter().position(|arg| arg != "cargo" && !arg.starts_with('+'))
+}
+/// Expand a shortcut in subcommand position, following shortcuts that point at other
+/// shortcuts. A shortcut that starts with its own name (`build = "build --release"`)
+/// stops there like a shell alias; any longer cycle is an error.
+pub fn expand(args: &[String], shortcuts: &BTreeMap<String, String>) -> Result<Vec<String>> {
+    let mut expanded = args.to_vec();
+    let mut chain: Vec<String> = Vec::new();
+    while let Some(index) = subcommand_index(&expanded) {
+        let name = expanded[index].clone();
+        let Some(definition) = shortcuts.get(&name) else {
+            break;
+        };
+        if chain.contains(&name) {
+            if chain.last() == Some(&name) {
+                break;
+            }
+            chain.push(name);
+            bail!("Shortcut cycle: {}", chain.join(" → "));
+        }
+        if chain.len() >= MAX_DEPTH {
+            bail!("Shortcut '{}' expands more than {} levels deep", name, MAX_DEPTH);
+        }
+        let mut words = shell_words::split(definition)
+            .with_context(|| format!("Invalid shortcut '{}' = \"{}\"", name, definition))?;
+        if words.first().map(String::as_str) == Some("cargo") {
+            words.remove(0);
+        }
+        if words.is_empty() {
+            bail!("Shortcut '{}' is empty", name);
+        }
+        chain.push(name);
+        expanded.splice(index..=index, words);
+    }
+    Ok(expanded)
+}
+pub fn expand_from_config(args: &[String]) -> Result<Vec<String>> {
+    let Ok(config) = ConfigManager::new() else {
+        return Ok(args.to_vec());
+    };
+    let expanded = expand(args, &load(&config))?;
+    if expanded != args {
+        let shown: Vec<&str> = expanded
+            .iter()
+            .map(String::as_str)
+            .skip_while(|arg| *arg == "cargo")
+            .collect();
+        let name = subcommand_index(args).map(|i| args[i].as_str()).unwrap_or_default();
+        println!("🔗 {} → {}", name.cyan(), format!("cargo {}", shown.join(" ")).dimmed());
+    }
+    Ok(expanded)
+}
+pub fn list(config: &ConfigManager) {
+    let shortcuts = load(config);
+    if shortcuts.is_empty() {
+        println!("No shortcuts defined. Add one with 'cm config shortcut <name> \"<command>\"'");
+        return;
+    }
+    println!("{}", "🔗 Shortcuts".bold().blue());
+    let width = shortcuts.keys().map(|name| name.len()).max().unwrap_or(0);
+    for (name, definition) in &shortcuts {
+        println!("  cargo {}  → cargo {}", format!("{:<width$}", name, width = width).green(), definition);
+    }
+}
+pub fn show(config: &ConfigManager, name: &str) -> Result<()> {
+    let shortcuts = load(config);
+    if !shortcuts.contains_key(name) {
+        bail!("Shortcut '{}' is not defined", name);
+    }
+    let expanded = expand(&[name.to_string()], &shortcuts)?;
+    println!("cargo {} → cargo {}", name.green(), expanded.join(" "));
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+    #[test]
+    fn expands_nested_shortcuts_and_stops_cycles() {
+        let shortcuts = BTreeMap::from([
+            ("br".to_string(), "build --release --features full".to_string()),
+            ("ship".to_string(), "br --locked".to_string()),
+            ("build".to_string(), "build --timings".to_string()),
+            ("ping".to_string(), "pong".to_string()),
+            ("pong".to_string(), "ping -v".to_string()),
+        ]);
+        assert_eq!(
+            expand(&args("ship -p app"), &shortcuts).unwrap(),
+            args("build --timings --release --features full --locked -p app")
+        );
+        assert_eq!(
+            expand(&args("cargo +nightly br"), &shortcuts).unwrap(),
+            args("cargo +nightly build --timings --release --features full")
+        );
+        assert_eq!(expand(&args("test --all"), &shortcuts).unwrap(), args("test --all"));
+        let cycle = expand(&args("ping"), &shortcuts).unwrap_err().to_string();
+        assert_eq!(cycle, "Shortcut cycle: ping → pong → ping");
+    }
+}
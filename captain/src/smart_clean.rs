@@ -0,0 +1,249 @@
+use crate::display::format_size;
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Reason {
+    StaleRustc,
+    NotInLockfile,
+    OldVariant,
+}
+impl Reason {
+    fn describe(&self) -> &'static str {
+        match self {
+            Reason::StaleRustc => "built by an older rustc",
+            Reason::NotInLockfile => "no longer in Cargo.lock",
+            Reason::OldVariant => "superseded feature/profile variant",
+        }
+    }
+}
+/// One compilation unit, identified by its `.fingerprint/<package>-<hash>` directory
+#[derive(Debug, Clone)]
+pub struct Unit {
+    pub package: String,
+    pub hash: String,
+    pub rustc: Option<u64>,
+    /// Fingerprint file names such as `lib-serde` or `build-script-build-script-build`
+    pub targets: Vec<String>,
+    pub last_used: SystemTime,
+    pub paths: Vec<PathBuf>,
+}
+pub fn split_unit_name(name: &str) -> Option<(&str, &str)> {
+    let (package, hash) = name.rsplit_once('-')?;
+    (hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit())).then_some((package, hash))
+}
+fn normalize(name: &str) -> String {
+    name.replace('-', "_")
+}
+fn newest_time(path: &Path) -> SystemTime {
+    WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .flat_map(|meta| [meta.modified().ok(), meta.accessed().ok()])
+        .flatten()
+        .max()
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+fn size_of(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|meta| meta.is_file())
+        .map(|meta| meta.len())
+        .sum()
+}
+/// Profile directories (`debug`, `release`, `<triple>/debug`, ...) that hold fingerprints
+fn profile_dirs(target: &Path) -> Vec<PathBuf> {
+    WalkDir::new(target)
+        .min_depth(1)
+        .max_depth(2)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_dir() && entry.path().join(".fingerprint").is_dir())
+        .map(|entry| entry.into_path())
+        .collect()
+}
+fn scan_profile(profile: &Path) -> Vec<Unit> {
+    let mut outputs: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for sub in ["deps", "build", "examples"] {
+        for entry in fs::read_dir(profile.join(sub)).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stem = name.split('.').next().unwrap_or(&name);
+            if let Some((_, hash)) = split_unit_name(stem) {
+                outputs.entry(hash.to_string()).or_default().push(entry.path());
+            }
+        }
+    }
+    fs::read_dir(profile.join(".fingerprint"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let (package, hash) = split_unit_name(&name)?;
+            let mut targets = Vec::new();
+            let mut rustc = None;
+            for file in fs::read_dir(entry.path()).into_iter().flatten().flatten() {
+                let file_name = file.file_name().to_string_lossy().to_string();
+                let Some(target) = file_name.strip_suffix(".json") else {
+                    continue;
+                };
+                targets.push(target.to_string());
+                if rustc.is_none() {
+                    rustc = fs::read_to_string(file.path())
+                        .ok()
+                        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+                        .and_then(|value| value["rustc"].as_u64());
+                }
+            }
+            targets.sort();
+            let mut paths = vec![entry.path()];
+            paths.extend(outputs.remove(hash).unwrap_or_default());
+            let last_used = paths.iter().map(|p| newest_time(p)).max().unwrap_or(SystemTime::UNIX_EPOCH);
+            Some(Unit { package: package.to_string(), hash: hash.to_string(), rustc, targets, last_used, paths })
+        })
+        .collect()
+}
+/// Decide what can go. The rustc that built the most recently used unit is taken as
+/// current; among units of the same package and targets, the newest is kept and older
+/// variants go once they have been unused for `keep`.
+pub fn classify(
+    units: &[Unit],
+    lock_packages: Option<&HashSet<String>>,
+    now: SystemTime,
+    keep: Duration,
+) -> Vec<(usize, Reason)> {
+    let current_rustc = units.iter().max_by_key(|u| u.last_used).and_then(|u| u.rustc);
+    let mut stale = Vec::new();
+    let mut groups: BTreeMap<(String, Vec<String>), Vec<usize>> = BTreeMap::new();
+    for (index, unit) in units.iter().enumerate() {
+        if unit.rustc.is_some() && current_rustc.is_some() && unit.rustc != current_rustc {
+            stale.push((index, Reason::StaleRustc));
+        } else if lock_packages.is_some_and(|lock| !lock.contains(&normalize(&unit.package))) {
+            stale.push((index, Reason::NotInLockfile));
+        } else if !unit.targets.is_empty() {
+            groups.entry((unit.package.clone(), unit.targets.clone())).or_default().push(index);
+        }
+    }
+    for members in groups.values().filter(|members| members.len() > 1) {
+        let newest = members.iter().map(|&i| units[i].last_used).max().unwrap_or(now);
+        for &index in members {
+            let used = units[index].last_used;
+            let idle = now.duration_since(used).unwrap_or_default();
+            if used < newest && idle > keep {
+                stale.push((index, Reason::OldVariant));
+            }
+        }
+    }
+    stale.sort_by_key(|(index, reason)| (*reason, *index));
+    stale
+}
+fn lock_packages(root: &Path) -> Option<HashSet<String>> {
+    let text = fs::read_to_string(root.join("Cargo.lock")).ok()?;
+    let packages = crate::lockdiff::parse_lock(&text).ok()?;
+    Some(packages.iter().map(|p| normalize(&p.name)).collect())
+}
+pub fn handle_smart_clean(dry_run: bool, keep_days: u64) -> Result<()> {
+    let manifest = crate::project::find_manifest(&std::env::current_dir()?)
+        .context("Not inside a cargo project")?;
+    let root = manifest.parent().map(PathBuf::from).unwrap_or_default();
+    let target = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| root.join("target"));
+    let profiles = profile_dirs(&target);
+    if profiles.is_empty() {
+        bail!("No build fingerprints in {} - nothing to clean", target.display());
+    }
+    let lock = lock_packages(&root);
+    if lock.is_none() {
+        println!("{} No readable Cargo.lock - skipping the removed-package check", "⚠️".yellow());
+    }
+    let keep = Duration::from_secs(keep_days * 24 * 60 * 60);
+    let now = SystemTime::now();
+    let mut reclaimed = 0;
+    let mut removed = 0;
+    println!("{}", format!("🧹 Smart clean of {}", target.display()).bold().blue());
+    for profile in &profiles {
+        let units = scan_profile(profile);
+        let stale = classify(&units, lock.as_ref(), now, keep);
+        if stale.is_empty() {
+            continue;
+        }
+        let label = profile.strip_prefix(&target).unwrap_or(profile).display().to_string();
+        println!("\n📁 {} ({} of {} units stale)", label.cyan(), stale.len(), units.len());
+        let mut last_reason = None;
+        for (index, reason) in stale {
+            let unit = &units[index];
+            if last_reason != Some(reason) {
+                println!("  {}", reason.describe().yellow());
+                last_reason = Some(reason);
+            }
+            let size: u64 = unit.paths.iter().map(|p| size_of(p)).sum();
+            println!("    {:<32} {:>9}  {}", unit.package, format_size(size), unit.hash.dimmed());
+            if !dry_run {
+                for path in &unit.paths {
+                    let result = if path.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+                    if let Err(e) = result {
+                        println!("    {} {}: {}", "⚠️".yellow(), path.display(), e);
+                    }
+                }
+            }
+            reclaimed += size;
+            removed += 1;
+        }
+    }
+    println!();
+    if removed == 0 {
+        println!("✨ Nothing stale - target/ only holds artifacts for the current toolchain and lockfile");
+    } else if dry_run {
+        println!("🔍 Would remove {} units and reclaim {} (run without --dry-run to clean)", removed, format_size(reclaimed).green());
+    } else {
+        println!("✅ Removed {} units, reclaimed {}", removed, format_size(reclaimed).green().bold());
+        crate::history::save_to_history(
+            format!("clean smart reclaimed {} from {} units", format_size(reclaimed), removed),
+            Vec::new(),
+            Vec::new(),
+        );
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn unit(package: &str, rustc: u64, days_ago: u64, target: &str) -> Unit {
+        Unit {
+            package: package.to_string(),
+            hash: "0123456789abcdef".to_string(),
+            rustc: Some(rustc),
+            targets: vec![target.to_string()],
+            last_used: SystemTime::UNIX_EPOCH + Duration::from_secs((100 - days_ago) * 86_400),
+            paths: Vec::new(),
+        }
+    }
+    #[test]
+    fn classifies_stale_rustc_removed_packages_and_old_variants() {
+        assert_eq!(split_unit_name("serde_json-0123456789abcdef"), Some(("serde_json", "0123456789abcdef")));
+        assert_eq!(split_unit_name("proc-macro2-0123456789abcdef").unwrap().0, "proc-macro2");
+        assert!(split_unit_name("build-script-build").is_none());
+        let units = vec![
+            unit("serde", 1, 0, "lib-serde"),
+            unit("serde", 1, 30, "lib-serde"),
+            unit("serde", 1, 2, "lib-serde"),
+            unit("serde", 1, 40, "build-script-build-script-build"),
+            unit("old-dep", 1, 0, "lib-old_dep"),
+            unit("serde", 7, 50, "lib-serde"),
+        ];
+        let lock: HashSet<String> = ["serde".to_string()].into();
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100 * 86_400);
+        let stale = classify(&units, Some(&lock), now, Duration::from_secs(7 * 86_400));
+        assert_eq!(stale, vec![(5, Reason::StaleRustc), (4, Reason::NotInLockfile), (1, Reason::OldVariant)]);
+        let without_lock = classify(&units, None, now, Duration::from_secs(7 * 86_400));
+        assert!(!without_lock.iter().any(|(_, reason)| *reason == Reason::NotInLockfile));
+    }
+}
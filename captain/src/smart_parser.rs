@@ -3,9 +3,10 @@ use colored::*;
 use regex::Regex;
 use reqwest;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use crate::parser::DiagnosticMessage;
 #[derive(Debug, Clone)]
 pub struct SmartError {
     pub code: String,
@@ -49,12 +50,8 @@ impl SmartParser {
             .context("Could not find home directory")?
             .join(".shipwreck")
             .join("error_db.json");
-        let error_db = if db_file.exists() {
-            let content = fs::read_to_string(&db_file)?;
-            serde_json::from_str(&content)?
-        } else {
-            Self::create_default_database()
-        };
+        let error_db = crate::storage::read_json_file(&db_file)?
+            .unwrap_or_else(Self::create_default_database);
         Ok(Self {
             error_db,
             db_file,
@@ -365,8 +362,282 @@ impl SmartParser {
         }
     }
     fn save_database(&self) -> Result<()> {
-        let json = serde_json::to_string_pretty(&self.error_db)?;
-        fs::write(&self.db_file, json)?;
-        Ok(())
+        crate::storage::write_json_atomic(&self.db_file, &self.error_db)
+    }
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixEdit {
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub replacement: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixSuggestion {
+    pub file: String,
+    pub line: usize,
+    pub code: String,
+    pub message: String,
+    pub help: String,
+    pub applicability: String,
+    pub edits: Vec<FixEdit>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixSet {
+    pub root: PathBuf,
+    pub captured_at: chrono::DateTime<chrono::Utc>,
+    pub suggestions: Vec<FixSuggestion>,
+}
+#[derive(Debug, Default)]
+pub struct ApplyReport {
+    pub applied: Vec<FixSuggestion>,
+    pub skipped: Vec<(FixSuggestion, String)>,
+    pub files_changed: usize,
+}
+impl FixSuggestion {
+    pub fn is_machine_applicable(&self) -> bool {
+        self.applicability == "MachineApplicable"
+    }
+}
+pub fn extract_suggestions(message: &DiagnosticMessage) -> Vec<FixSuggestion> {
+    let code = message
+        .code
+        .as_ref()
+        .map(|c| c.code.clone())
+        .unwrap_or_else(|| "unknown".to_string());
+    std::iter::once(message)
+        .chain(message.children.iter())
+        .filter_map(|diagnostic| {
+            let spans: Vec<_> = diagnostic
+                .spans
+                .iter()
+                .filter(|span| span.suggested_replacement.is_some())
+                .collect();
+            let first = spans.first()?;
+            if spans.iter().any(|span| span.file_name != first.file_name) {
+                return None;
+            }
+            Some(FixSuggestion {
+                file: first.file_name.clone(),
+                line: first.line_start,
+                code: code.clone(),
+                message: message.message.clone(),
+                help: diagnostic.message.clone(),
+                applicability: first
+                    .suggestion_applicability
+                    .clone()
+                    .unwrap_or_else(|| "Unspecified".to_string()),
+                edits: spans
+                    .iter()
+                    .map(|span| FixEdit {
+                        byte_start: span.byte_start,
+                        byte_end: span.byte_end,
+                        line: span.line_start,
+                        column: span.column_start,
+                        replacement: span.suggested_replacement.clone().unwrap_or_default(),
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+fn fix_set_path() -> Result<PathBuf> {
+    Ok(crate::project::data_dir()?.join("fixes").join("latest.json"))
+}
+pub fn save_fix_set(suggestions: &[FixSuggestion]) -> Result<()> {
+    let path = fix_set_path()?;
+    let fix_set = FixSet {
+        root: std::env::current_dir()?,
+        captured_at: chrono::Utc::now(),
+        suggestions: suggestions.to_vec(),
+    };
+    crate::storage::write_json_atomic(&path, &fix_set)
+}
+pub fn load_fix_set() -> Result<Option<FixSet>> {
+    crate::storage::read_json_file(&fix_set_path()?)
+}
+pub fn group_by_file(suggestions: &[FixSuggestion]) -> BTreeMap<String, Vec<&FixSuggestion>> {
+    let mut grouped: BTreeMap<String, Vec<&FixSuggestion>> = BTreeMap::new();
+    for suggestion in suggestions {
+        grouped.entry(suggestion.file.clone()).or_default().push(suggestion);
+    }
+    for fixes in grouped.values_mut() {
+        fixes.sort_by_key(|f| f.line);
+        fixes.dedup_by(|a, b| a.line == b.line && a.help == b.help && a.edits.len() == b.edits.len());
+    }
+    grouped
+}
+pub fn apply_edits(content: &str, suggestions: &[&FixSuggestion]) -> (String, Vec<usize>) {
+    let mut edits: Vec<(usize, &FixEdit)> = suggestions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, s)| s.edits.iter().map(move |e| (index, e)))
+        .collect();
+    edits.sort_by_key(|e| std::cmp::Reverse(e.1.byte_start));
+    let mut rejected: Vec<usize> = suggestions
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| {
+            s.edits.iter().any(|e| {
+                e.byte_start > e.byte_end || e.byte_end > content.len()
+                    || !content.is_char_boundary(e.byte_start)
+                    || !content.is_char_boundary(e.byte_end)
+            })
+        })
+        .map(|(index, _)| index)
+        .collect();
+    let mut claimed: Vec<(usize, usize, usize)> = Vec::new();
+    for (index, edit) in &edits {
+        if rejected.contains(index) {
+            continue;
+        }
+        let overlaps = claimed
+            .iter()
+            .any(|(owner, start, end)| {
+                owner != index && edit.byte_start < *end && *start < edit.byte_end.max(edit.byte_start + 1)
+            });
+        if overlaps {
+            rejected.push(*index);
+        } else {
+            claimed.push((*index, edit.byte_start, edit.byte_end.max(edit.byte_start + 1)));
+        }
+    }
+    let mut result = content.to_string();
+    for (index, edit) in edits {
+        if !rejected.contains(&index) {
+            result.replace_range(edit.byte_start..edit.byte_end, &edit.replacement);
+        }
+    }
+    rejected.sort_unstable();
+    rejected.dedup();
+    (result, rejected)
+}
+pub fn apply_fix_set(
+    fix_set: &FixSet,
+    include_maybe_incorrect: bool,
+    only_file: Option<&str>,
+) -> Result<ApplyReport> {
+    let mut report = ApplyReport::default();
+    let mut changed_files = Vec::new();
+    let mut undo = crate::undo::Recorder::begin("fixes apply", "suggestions from the last build")?;
+    for (file, suggestions) in group_by_file(&fix_set.suggestions) {
+        if only_file.is_some_and(|only| !file.ends_with(only)) {
+            continue;
+        }
+        let path = if Path::new(&file).is_absolute() {
+            PathBuf::from(&file)
+        } else {
+            fix_set.root.join(&file)
+        };
+        let (eligible, ineligible): (Vec<&FixSuggestion>, Vec<&FixSuggestion>) = suggestions
+            .into_iter()
+            .partition(|s| {
+                s.is_machine_applicable()
+                    || (include_maybe_incorrect && s.applicability == "MaybeIncorrect")
+            });
+        for suggestion in ineligible {
+            report
+                .skipped
+                .push((
+                    suggestion.clone(),
+                    format!("{} suggestion", suggestion.applicability),
+                ));
+        }
+        if eligible.is_empty() {
+            continue;
+        }
+        let modified = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .map(chrono::DateTime::<chrono::Utc>::from)
+            .with_context(|| format!("Cannot read {}", path.display()))?;
+        if modified >= fix_set.captured_at {
+            for suggestion in eligible {
+                report
+                    .skipped
+                    .push((
+                        suggestion.clone(),
+                        "file changed since the build; rebuild to refresh fixes".to_string(),
+                    ));
+            }
+            continue;
+        }
+        let content = fs::read_to_string(&path)?;
+        let (updated, rejected) = apply_edits(&content, &eligible);
+        for (index, suggestion) in eligible.into_iter().enumerate() {
+            if rejected.contains(&index) {
+                report
+                    .skipped
+                    .push((suggestion.clone(), "overlaps another fix".to_string()));
+            } else {
+                report.applied.push(suggestion.clone());
+            }
+        }
+        if updated != content {
+            undo.track(&path)?;
+            crate::storage::write_atomic(&path, updated)?;
+            report.files_changed += 1;
+            changed_files.push(file);
+        }
+    }
+    undo.commit();
+    if !changed_files.is_empty() {
+        let remaining = FixSet {
+            root: fix_set.root.clone(),
+            captured_at: fix_set.captured_at,
+            suggestions: fix_set
+                .suggestions
+                .iter()
+                .filter(|s| !changed_files.contains(&s.file))
+                .cloned()
+                .collect(),
+        };
+        crate::storage::write_json_atomic(&fix_set_path()?, &remaining)?;
+    }
+    if !report.applied.is_empty() {
+        if let Ok(mut parser) = SmartParser::new() {
+            for suggestion in &report.applied {
+                let _ = parser.record_fix(&suggestion.code, &suggestion.help, true);
+            }
+        }
     }
-}
\ No newline at end of file
+    Ok(report)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn span(start: usize, end: usize, replacement: &str) -> serde_json::Value {
+        serde_json::json!(
+            { "file_name" : "src/main.rs", "line_start" : 1, "line_end" : 1,
+            "column_start" : start + 1, "column_end" : end + 1, "byte_start" : start,
+            "byte_end" : end, "text" : [], "suggested_replacement" : replacement,
+            "suggestion_applicability" : "MachineApplicable" }
+        )
+    }
+    #[test]
+    fn test_extract_and_apply_suggestions() {
+        let content = "use a::b;\nlet mut x = 5;\n";
+        let message: DiagnosticMessage = serde_json::from_value(
+                serde_json::json!(
+                    { "message" : "unused", "code" : { "code" : "unused_imports",
+                    "explanation" : null }, "level" : "warning", "spans" : [],
+                    "rendered" : null, "children" : [{ "message" : "remove the import",
+                    "code" : null, "level" : "help", "spans" : [span(0, 10, "")],
+                    "children" : [], "rendered" : null }, { "message" :
+                    "remove mut", "code" : null, "level" : "help", "spans" : [span(14,
+                    18, "")], "children" : [], "rendered" : null }, { "message" :
+                    "conflicting", "code" : null, "level" : "help", "spans" : [span(12,
+                    16, "var")], "children" : [], "rendered" : null }] }
+                ),
+            )
+            .unwrap();
+        let suggestions = extract_suggestions(&message);
+        assert_eq!(suggestions.len(), 3);
+        assert_eq!(suggestions[0].code, "unused_imports");
+        let refs: Vec<&FixSuggestion> = suggestions.iter().collect();
+        let (updated, rejected) = apply_edits(content, &refs);
+        assert_eq!(updated, "let x = 5;\n");
+        assert_eq!(rejected, vec![2]);
+        assert_eq!(group_by_file(&suggestions)["src/main.rs"].len(), 3);
+    }
+}
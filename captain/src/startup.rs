@@ -0,0 +1,195 @@
+use crate::i18n::{t, t_with};
+use anyhow::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+const SESSION_FILE: &str = "session.json";
+const CAPTAIN_DOWNLOAD: &str = "https://get.cargo.do/captain/";
+/// How long one shell reuses the protection key, captain mode and message check
+const SESSION_TTL_SECS: i64 = 60 * 60;
+/// What the first command in a shell found out, so later ones start cold.
+/// Keyed by `CM_SESSION` when set, otherwise by the parent (shell) process
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub shell: u32,
+    pub started: i64,
+    #[serde(default)]
+    pub protection_key: Option<String>,
+    /// `sober`, `cached` or `drunk`, as reported by the protection module
+    #[serde(default)]
+    pub key_source: Option<String>,
+    /// `full` or `limited`
+    #[serde(default)]
+    pub captain_mode: Option<String>,
+    #[serde(default)]
+    pub messages_checked: bool,
+}
+static CAPTAIN_READY: AtomicBool = AtomicBool::new(false);
+fn session_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".shipwreck")
+        .join(SESSION_FILE)
+}
+fn shell_id() -> u32 {
+    if let Some(id) = std::env::var("CM_SESSION").ok().and_then(|v| v.trim().parse().ok()) {
+        return id;
+    }
+    #[cfg(unix)]
+    {
+        std::os::unix::process::parent_id()
+    }
+    #[cfg(not(unix))]
+    {
+        0
+    }
+}
+impl Session {
+    pub fn is_current(&self, shell: u32, now: i64) -> bool {
+        self.shell == shell && now - self.started < SESSION_TTL_SECS && now >= self.started
+    }
+    /// This shell's session, or a fresh one when it is missing, stale or another shell's
+    pub fn load() -> Self {
+        let (shell, now) = (shell_id(), Utc::now().timestamp());
+        crate::storage::read_json_file::<Session>(&session_path())
+            .ok()
+            .flatten()
+            .filter(|session| session.is_current(shell, now))
+            .unwrap_or(Session { shell, started: now, ..Default::default() })
+    }
+    pub fn save(&self) {
+        let _ = crate::storage::write_json_atomic(&session_path(), self);
+    }
+}
+/// Resolves the protection key once per session; the remote lookup can take seconds
+fn protection_key(session: &mut Session) -> (String, String) {
+    use crate::captain::protection;
+    if let (Some(key), Some(source)) = (&session.protection_key, &session.key_source) {
+        // Later checks read the captain's state from these
+        match source.as_str() {
+            "sober" => std::env::set_var("CAPTAIN_SOBER", "1"),
+            "cached" => std::env::set_var("CAPTAIN_CACHE", "1"),
+            _ => std::env::set_var("CAPTAIN_DRUNK", protection::get_fallback_key()),
+        }
+        return (key.clone(), source.clone());
+    }
+    let key = protection::get_protection_key();
+    let source = if protection::is_captain_drunk() {
+        "drunk"
+    } else if protection::is_captain_sober() {
+        "sober"
+    } else {
+        "cached"
+    };
+    session.protection_key = Some(key.clone());
+    session.key_source = Some(source.to_string());
+    (key, source.to_string())
+}
+fn set_mode(session: &mut Session, mode: &str) {
+    let var = if mode == "full" { "CARGO_MATE_FULL_MODE" } else { "CARGO_MATE_LIMITED_MODE" };
+    std::env::set_var(var, "1");
+    session.captain_mode = Some(mode.to_string());
+}
+/// Finds (or installs) the captain binary and sets full or limited mode. Only commands that
+/// forward to or report on captain call this; the outcome holds for the rest of the session
+pub async fn ensure_captain(ci_mode: bool, managing_captain: bool) -> Result<()> {
+    if CAPTAIN_READY.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let mut session = Session::load();
+    if let Some(mode) = session.captain_mode.clone() {
+        protection_key(&mut session);
+        set_mode(&mut session, &mode);
+        return Ok(());
+    }
+    let (protection_key, source) = protection_key(&mut session);
+    match source.as_str() {
+        "drunk" => log::debug!("CAPTAIN_DRUNK: Using embedded fallback protection key ({}...)", &protection_key[..8]),
+        "sober" => log::debug!("CAPTAIN_SOBER: Using remote protection key ({}...)", &protection_key[..8]),
+        _ => log::debug!("CAPTAIN_CACHE: Using cached protection key ({}...)", &protection_key[..8]),
+    }
+    let captain_available = crate::captain::captain_status::is_captain_available();
+    if !captain_available && ci_mode {
+        set_mode(&mut session, "limited");
+    } else if !captain_available && !managing_captain {
+        if let Some(captain_path) = crate::captain::captain_status::find_captain_binary() {
+            log::warn!("Captain binary found at: {}", captain_path);
+            eprintln!("{}", t("captain.verification_failed"));
+            let key = std::env::var("PROTECT_KEY").unwrap_or_else(|_| t("captain.not_set"));
+            eprintln!("{}", t_with("captain.current_key", &[("key", &key)]));
+            eprintln!();
+            eprintln!("{}", t("captain.try_key"));
+            eprintln!("{}", t_with("captain.download", &[("url", &CAPTAIN_DOWNLOAD)]));
+            eprintln!();
+        } else {
+            log::warn!("Captain binary not found");
+        }
+        if let Some(reason) = crate::captain::manage::auto_install_disabled_reason() {
+            eprintln!("{}", t_with("captain.auto_install_disabled", &[("reason", &reason)]));
+            eprintln!("{}", t("captain.features_unavailable"));
+            eprintln!();
+            eprintln!("{}", t("captain.install_manually"));
+            eprintln!("{}", t_with("captain.download", &[("url", &CAPTAIN_DOWNLOAD)]));
+            eprintln!();
+            set_mode(&mut session, "limited");
+            crate::initialize_fallback_mode()?;
+        } else {
+            eprintln!("{}", t("captain.installing"));
+            match crate::captain::manage::install_captain().await {
+                Ok(_) => {
+                    eprintln!("{}", t("captain.installed"));
+                    set_mode(&mut session, "full");
+                    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+                    let current_path = std::env::var("PATH").unwrap_or_default();
+                    std::env::set_var("PATH", format!("{}/.shipwreck/bin:{}", home, current_path));
+                }
+                Err(e) => {
+                    log::warn!("Failed to auto-install captain: {}", e);
+                    eprintln!("{}", t("captain.limited_mode"));
+                    eprintln!();
+                    eprintln!("{}", t("captain.install_manually_short"));
+                    eprintln!("{}", t_with("captain.download", &[("url", &"https://get.cargo.do/mate")]));
+                    eprintln!();
+                    set_mode(&mut session, "limited");
+                    crate::initialize_fallback_mode()?;
+                }
+            }
+        }
+    } else if captain_available {
+        log::debug!("Captain binary detected - full functionality enabled");
+        set_mode(&mut session, "full");
+    }
+    session.save();
+    Ok(())
+}
+/// Fetches the admin message in the background, at most once per session
+pub fn check_messages() {
+    let mut session = Session::load();
+    if session.messages_checked {
+        return;
+    }
+    session.messages_checked = true;
+    session.save();
+    std::thread::spawn(|| {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let _ = crate::admin_msg::check_and_display_message().await;
+        });
+    });
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_session_is_per_shell_and_expires() {
+        let session = Session { shell: 42, started: 1_000, ..Default::default() };
+        assert!(session.is_current(42, 1_000));
+        assert!(session.is_current(42, 1_000 + SESSION_TTL_SECS - 1));
+        assert!(!session.is_current(42, 1_000 + SESSION_TTL_SECS));
+        assert!(!session.is_current(7, 1_000));
+        assert!(!session.is_current(42, 999));
+        let restored: Session = serde_json::from_str(r#"{"shell": 42, "started": 1000}"#).unwrap();
+        assert_eq!(restored, session);
+    }
+}
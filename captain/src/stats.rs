@@ -0,0 +1,374 @@
+use crate::captain_log::{CaptainLog, LogEntry};
+use crate::history::{self, HistoryEntry};
+use crate::parser::ParsedError;
+use crate::tide::{BuildMetrics, TideCharts};
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use colored::*;
+use crossterm::{
+    event::{self, Event, KeyCode},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    },
+    ExecutableCommand,
+};
+use ratatui::{
+    backend::CrosstermBackend, layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph, Row, Sparkline, Table},
+    Frame, Terminal,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+const TOP_N: usize = 10;
+#[derive(Debug, Clone, Serialize)]
+pub struct DayStats {
+    pub date: NaiveDate,
+    pub builds: usize,
+    pub failed: usize,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct Ranked {
+    pub name: String,
+    pub count: usize,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct Streaks {
+    pub current_days: usize,
+    pub longest_days: usize,
+    pub green_builds: usize,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildStats {
+    pub days: i64,
+    pub generated_at: DateTime<Utc>,
+    pub total_builds: usize,
+    pub successful_builds: usize,
+    pub success_rate: f64,
+    pub mean_build_seconds: Option<f64>,
+    pub log_entries: usize,
+    pub per_day: Vec<DayStats>,
+    pub top_errors: Vec<Ranked>,
+    pub busiest_files: Vec<Ranked>,
+    pub streaks: Streaks,
+}
+fn rank(counts: HashMap<String, usize>) -> Vec<Ranked> {
+    let mut ranked: Vec<Ranked> = counts
+        .into_iter()
+        .filter(|(name, _)| !name.is_empty())
+        .map(|(name, count)| Ranked { name, count })
+        .collect();
+    ranked.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+    ranked.truncate(TOP_N);
+    ranked
+}
+fn streaks(history: &[HistoryEntry], today: NaiveDate) -> Streaks {
+    let mut days: Vec<NaiveDate> = history.iter().map(|e| e.timestamp.date_naive()).collect();
+    days.sort();
+    days.dedup();
+    let mut longest_days = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in &days {
+        run = match previous {
+            Some(prev) if *day - prev == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest_days = longest_days.max(run);
+        previous = Some(*day);
+    }
+    let mut current_days = 0;
+    let mut expected = if days.last() == Some(&today) { today } else { today - Duration::days(1) };
+    for day in days.iter().rev() {
+        if *day != expected {
+            break;
+        }
+        current_days += 1;
+        expected -= Duration::days(1);
+    }
+    let green_builds = history.iter().rev().take_while(|e| e.error_count == 0).count();
+    Streaks {
+        current_days,
+        longest_days,
+        green_builds,
+    }
+}
+pub fn compute(
+    history: &[HistoryEntry],
+    builds: &[BuildMetrics],
+    log: &[LogEntry],
+    days: i64,
+    now: DateTime<Utc>,
+) -> BuildStats {
+    let days = days.max(1);
+    let today = now.date_naive();
+    let start = today - Duration::days(days - 1);
+    let in_window = |timestamp: &DateTime<Utc>| timestamp.date_naive() >= start;
+    let recent: Vec<&HistoryEntry> = history
+        .iter()
+        .filter(|e| in_window(&e.timestamp))
+        .collect();
+    let mut per_day: BTreeMap<NaiveDate, DayStats> = (0..days)
+        .map(|offset| {
+            let date = start + Duration::days(offset);
+            (date, DayStats { date, builds: 0, failed: 0 })
+        })
+        .collect();
+    let mut errors = HashMap::new();
+    let mut files = HashMap::new();
+    for entry in &recent {
+        if let Some(day) = per_day.get_mut(&entry.timestamp.date_naive()) {
+            day.builds += 1;
+            if entry.error_count > 0 {
+                day.failed += 1;
+            }
+        }
+        for error in entry.errors.iter().filter_map(|line| ParsedError::parse_stored(line)) {
+            *errors.entry(error.code).or_insert(0) += 1;
+            *files.entry(error.file).or_insert(0) += 1;
+        }
+        for warning in entry.warnings.iter().filter_map(|line| ParsedError::parse_stored(line)) {
+            *files.entry(warning.file).or_insert(0) += 1;
+        }
+    }
+    let log: Vec<&LogEntry> = log.iter().filter(|e| in_window(&e.timestamp)).collect();
+    for entry in &log {
+        if let Some(code) = &entry.error_code {
+            *errors.entry(code.clone()).or_insert(0) += 1;
+        }
+        if let Some(file) = &entry.file_path {
+            *files.entry(file.clone()).or_insert(0) += 1;
+        }
+    }
+    let durations: Vec<f64> = builds
+        .iter()
+        .filter(|b| in_window(&b.timestamp))
+        .map(|b| b.duration_seconds)
+        .chain(
+            log
+                .iter()
+                .filter_map(|e| e.build_result.as_ref())
+                .map(|r| r.duration_seconds),
+        )
+        .collect();
+    let total_builds = recent.len();
+    let successful_builds = recent.iter().filter(|e| e.error_count == 0).count();
+    BuildStats {
+        days,
+        generated_at: now,
+        total_builds,
+        successful_builds,
+        success_rate: if total_builds == 0 {
+            0.0
+        } else {
+            successful_builds as f64 / total_builds as f64 * 100.0
+        },
+        mean_build_seconds: if durations.is_empty() {
+            None
+        } else {
+            Some(durations.iter().sum::<f64>() / durations.len() as f64)
+        },
+        log_entries: log.len(),
+        per_day: per_day.into_values().collect(),
+        top_errors: rank(errors),
+        busiest_files: rank(files),
+        streaks: streaks(history, today),
+    }
+}
+pub fn collect(days: i64) -> Result<BuildStats> {
+    let history = history::load_visible_history();
+    let tide = TideCharts::new()?;
+    let log = CaptainLog::new()?;
+    Ok(compute(&history, tide.builds(), log.entries(), days, Utc::now()))
+}
+fn mean_label(stats: &BuildStats) -> String {
+    stats
+        .mean_build_seconds
+        .map(|s| format!("{:.2}s", s))
+        .unwrap_or_else(|| "n/a".to_string())
+}
+fn sparkline(values: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().max().unwrap_or(0).max(1);
+    values
+        .iter()
+        .map(|v| if *v == 0 { ' ' } else { BARS[((*v * 7) / max) as usize] })
+        .collect()
+}
+pub fn print_text(stats: &BuildStats) {
+    println!(
+        "{}", crate::ci::decorate(&format!("📊 Build Stats - last {} days", stats.days))
+        .blue().bold()
+    );
+    println!("{}", "═".repeat(60).blue());
+    println!(
+        "Builds: {} ({} successful, {:.1}% success rate)", stats.total_builds, stats
+        .successful_builds.to_string().green(), stats.success_rate
+    );
+    println!("Mean build time: {}", mean_label(stats));
+    println!("Captain's log entries: {}", stats.log_entries);
+    let builds: Vec<u64> = stats.per_day.iter().map(|d| d.builds as u64).collect();
+    let failed: Vec<u64> = stats.per_day.iter().map(|d| d.failed as u64).collect();
+    println!("Builds/day:   {}", sparkline(&builds).cyan());
+    println!("Failures/day: {}", sparkline(&failed).red());
+    println!(
+        "Streaks: {} day(s) current, {} day(s) longest, {} green build(s) in a row",
+        stats.streaks.current_days, stats.streaks.longest_days, stats.streaks
+        .green_builds
+    );
+    for (title, ranked) in [
+        ("Top recurring errors", &stats.top_errors),
+        ("Busiest files", &stats.busiest_files),
+    ] {
+        println!();
+        println!("{}", title.bold());
+        if ranked.is_empty() {
+            println!("  {}", "none".dimmed());
+        }
+        for item in ranked {
+            println!("  {:>4}  {}", item.count, item.name);
+        }
+    }
+}
+fn ui(frame: &mut Frame, stats: &BuildStats) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(7),
+            Constraint::Length(5),
+            Constraint::Length(5),
+            Constraint::Min(5),
+            Constraint::Length(3),
+        ])
+        .split(frame.size());
+    let summary = [
+        format!(
+            "Builds: {}   Success Rate: {:.1}%   Mean Build Time: {}", stats.total_builds,
+            stats.success_rate, mean_label(stats)
+        ),
+        format!("Captain's Log Entries: {}", stats.log_entries),
+        format!(
+            "Current Streak: {} day(s)   Longest Streak: {} day(s)", stats.streaks
+            .current_days, stats.streaks.longest_days
+        ),
+        format!("Green Builds In A Row: {}", stats.streaks.green_builds),
+    ];
+    let summary = Paragraph::new(summary.join("\n"))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("📊 Build Stats - last {} days", stats.days)),
+        );
+    frame.render_widget(summary, chunks[0]);
+    let builds: Vec<u64> = stats.per_day.iter().map(|d| d.builds as u64).collect();
+    let failed: Vec<u64> = stats.per_day.iter().map(|d| d.failed as u64).collect();
+    let builds_line = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title("Builds per day"))
+        .data(&builds)
+        .style(Style::default().fg(Color::Cyan));
+    frame.render_widget(builds_line, chunks[1]);
+    let failed_line = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title("Failures per day"))
+        .data(&failed)
+        .style(Style::default().fg(Color::Red));
+    frame.render_widget(failed_line, chunks[2]);
+    let tables = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(chunks[3]);
+    for (area, title, ranked) in [
+        (tables[0], "Top Recurring Errors", &stats.top_errors),
+        (tables[1], "Busiest Files", &stats.busiest_files),
+    ] {
+        let rows = ranked
+            .iter()
+            .map(|item| Row::new(vec![item.count.to_string(), item.name.clone()]));
+        let table = Table::new(rows, [Constraint::Length(6), Constraint::Min(10)])
+            .header(
+                Row::new(vec!["Count", "Name"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(Block::default().borders(Borders::ALL).title(title));
+        frame.render_widget(table, area);
+    }
+    let help = Paragraph::new("Press q to quit | --output json for machine-readable stats")
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    frame.render_widget(help, chunks[4]);
+}
+fn show_dashboard(stats: &BuildStats) -> Result<()> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    stdout.execute(EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    let res = (|| -> Result<()> {
+        loop {
+            terminal.draw(|f| ui(f, stats))?;
+            if event::poll(std::time::Duration::from_millis(100))? {
+                if let Event::Key(key) = event::read()? {
+                    if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    })();
+    disable_raw_mode()?;
+    terminal.backend_mut().execute(LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    res
+}
+pub fn handle_stats(days: i64, output: &str) -> Result<()> {
+    let stats = collect(days)?;
+    match output {
+        "json" => println!("{}", serde_json::to_string_pretty(&stats)?),
+        "text" => print_text(&stats),
+        "tui" => {
+            if crate::ci::is_ci() || !atty::is(atty::Stream::Stdout) {
+                print_text(&stats);
+            } else {
+                show_dashboard(&stats)?;
+            }
+        }
+        other => anyhow::bail!("Unknown output format '{}' (expected tui, text or json)", other),
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn entry(days_ago: i64, errors: &[&str]) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: Utc::now() - Duration::days(days_ago),
+            command: "cargo build".to_string(),
+            error_count: errors.len(),
+            warning_count: 0,
+            errors: errors.iter().map(|e| e.to_string()).collect(),
+            warnings: Vec::new(),
+        }
+    }
+    #[test]
+    fn test_compute_stats() {
+        let history = vec![
+            entry(40, &["[E0425] src/old.rs:1 - cannot find value"]),
+            entry(3, &[]),
+            entry(1, &["[E0308] src/lib.rs:10 - mismatched types"]),
+            entry(1, &["[E0308] src/lib.rs:12 - mismatched types", "[E0425] src/main.rs:3 - x"]),
+            entry(0, &[]),
+            entry(0, &[]),
+        ];
+        let stats = compute(&history, &[], &[], 7, Utc::now());
+        assert_eq!(stats.total_builds, 5);
+        assert_eq!(stats.successful_builds, 3);
+        assert_eq!(stats.per_day.len(), 7);
+        assert_eq!(stats.per_day.last().unwrap().builds, 2);
+        assert_eq!(stats.top_errors[0].name, "E0308");
+        assert_eq!(stats.top_errors[0].count, 2);
+        assert_eq!(stats.busiest_files[0].name, "src/lib.rs");
+        assert!(stats.mean_build_seconds.is_none());
+        assert_eq!(stats.streaks.current_days, 2);
+        assert_eq!(stats.streaks.green_builds, 2);
+    }
+}
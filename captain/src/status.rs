@@ -0,0 +1,113 @@
+use crate::notify::{self, BuildOutcome};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+/// Last wrapped cargo command, read by shell prompt segments on every render
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildStatus {
+    pub project: String,
+    pub manifest_dir: Option<PathBuf>,
+    pub command: String,
+    pub exit_code: i32,
+    pub errors: usize,
+    pub warnings: usize,
+    pub duration_seconds: f64,
+    pub finished: DateTime<Utc>,
+}
+impl BuildStatus {
+    pub fn from_outcome(outcome: &BuildOutcome) -> Self {
+        Self {
+            project: outcome.project.clone(),
+            manifest_dir: crate::project::current_project()
+                .and_then(|bucket| bucket.manifest.parent().map(PathBuf::from)),
+            command: outcome.command.clone(),
+            exit_code: outcome.exit_code,
+            errors: outcome.errors,
+            warnings: outcome.warnings,
+            duration_seconds: outcome.duration_seconds,
+            finished: Utc::now(),
+        }
+    }
+    /// One stable, space separated line: `<ok|fail> <errors> <warnings> <seconds> <exit code> <project>`
+    pub fn porcelain(&self) -> String {
+        format!(
+            "{} {} {} {:.1} {} {}",
+            if self.exit_code == 0 { "ok" } else { "fail" },
+            self.errors,
+            self.warnings,
+            self.duration_seconds,
+            self.exit_code,
+            self.project
+        )
+    }
+}
+fn status_file() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join("status").join("current.json"))
+}
+pub fn save(status: &BuildStatus) -> Result<()> {
+    // Prompts read this concurrently, so never let them see a half-written file
+    crate::storage::write_json_atomic(&status_file()?, status)
+}
+pub fn load() -> Result<Option<BuildStatus>> {
+    crate::storage::read_json_file(&status_file()?)
+}
+/// Called by the wrapper once a cargo command exits: updates the prompt status and sends notifications
+pub fn command_finished(args: &[&str], exit_code: i32, elapsed: Duration, errors: usize, warnings: usize) {
+    let outcome = BuildOutcome::new(args, exit_code, elapsed, errors, warnings);
+    if let Err(e) = save(&BuildStatus::from_outcome(&outcome)) {
+        log::warn!("Could not update build status: {}", e);
+    }
+    notify::notify_completion(&outcome);
+}
+/// `here` limits output to the project containing the current directory, so a prompt
+/// doesn't show another project's build
+pub fn handle_status(porcelain: bool, here: bool) -> Result<()> {
+    let Some(status) = load()? else {
+        if !porcelain {
+            println!("No build recorded yet");
+        }
+        return Ok(());
+    };
+    if here {
+        let cwd = std::env::current_dir()?;
+        if !status.manifest_dir.as_ref().is_some_and(|dir| cwd.starts_with(dir)) {
+            return Ok(());
+        }
+    }
+    if porcelain {
+        println!("{}", status.porcelain());
+        return Ok(());
+    }
+    let state = if status.exit_code == 0 { "✅ ok".green() } else { "❌ failed".red() };
+    println!("{} {} ({})", state, status.command.cyan(), status.project);
+    println!(
+        "   {} errors, {} warnings, {:.1}s, exit code {}",
+        status.errors, status.warnings, status.duration_seconds, status.exit_code
+    );
+    println!("   finished {}", status.finished.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S"));
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_porcelain_line() {
+        let mut status = BuildStatus {
+            project: "my app".to_string(),
+            manifest_dir: None,
+            command: "cargo build".to_string(),
+            exit_code: 0,
+            errors: 0,
+            warnings: 3,
+            duration_seconds: 12.34,
+            finished: Utc::now(),
+        };
+        assert_eq!(status.porcelain(), "ok 0 3 12.3 0 my app");
+        status.exit_code = 101;
+        status.errors = 2;
+        assert_eq!(status.porcelain(), "fail 2 3 12.3 101 my app");
+    }
+}
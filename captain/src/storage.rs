@@ -0,0 +1,536 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+#[cfg(any(test, target_arch = "wasm32"))]
+use {base64::Engine, std::collections::BTreeMap, std::sync::Mutex};
+pub trait Storage: Send + Sync {
+    fn read(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    fn write(&self, key: &str, data: &[u8]) -> Result<()>;
+    fn remove(&self, key: &str) -> Result<()>;
+    fn list(&self, dir: &str) -> Result<Vec<String>>;
+    #[cfg(test)]
+    fn exists(&self, key: &str) -> bool {
+        self.read(key).ok().flatten().is_some()
+    }
+}
+/// A key that no longer parses is moved aside (see [`quarantine`]) and reads as missing
+pub fn read_json<T: DeserializeOwned>(storage: &dyn Storage, key: &str) -> Result<Option<T>> {
+    let Some(data) = storage.read(key)? else {
+        return Ok(None);
+    };
+    match serde_json::from_slice(&data) {
+        Ok(value) => Ok(Some(value)),
+        Err(e) => {
+            let aside = quarantine_name(key);
+            storage.write(&aside, &data)?;
+            storage.remove(key)?;
+            log::warn!("{} was unreadable ({}); kept as {}", key, e, aside);
+            Ok(None)
+        }
+    }
+}
+pub fn write_json<T: Serialize>(storage: &dyn Storage, key: &str, value: &T) -> Result<()> {
+    storage.write(key, serde_json::to_string_pretty(value)?.as_bytes())
+}
+fn quarantine_name(name: &str) -> String {
+    format!("{}.corrupt-{}", name, chrono::Utc::now().format("%Y%m%d%H%M%S"))
+}
+/// Temp files [`write_atomic`] leaves behind when it is killed mid-write end in this;
+/// `cm gc` sweeps the stale ones
+pub const TEMP_SUFFIX: &str = ".tmp";
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+/// Writes through a synced temp file renamed over `path`, so a crash leaves the old
+/// contents or the new ones and never half a file. An existing file keeps its permissions
+pub fn write_atomic(path: &Path, data: impl AsRef<[u8]>) -> Result<()> {
+    write_atomic_as(path, data.as_ref(), false)
+}
+/// [`write_atomic`] for keys and secrets: the temp file is 0600 before anything is written
+/// to it, and missing parent directories are created 0700
+pub fn write_private(path: &Path, data: impl AsRef<[u8]>) -> Result<()> {
+    write_atomic_as(path, data.as_ref(), true)
+}
+/// Creates `dir` 0700, or restricts it to 0700 if it already exists
+pub fn create_private_dir(dir: &Path) -> Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        builder.mode(0o700);
+        builder.create(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    #[cfg(not(unix))]
+    builder.create(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    Ok(())
+}
+fn write_atomic_as(path: &Path, data: &[u8], private: bool) -> Result<()> {
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut dirs = fs::DirBuilder::new();
+    dirs.recursive(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::DirBuilderExt;
+        dirs.mode(0o700);
+    }
+    dirs.create(parent)?;
+    let name = path.file_name().with_context(|| format!("Not a file path: {}", path.display()))?;
+    // Unique per process and per call, so threads writing the same file never share one
+    let tmp = parent.join(format!(
+        ".{}.{}-{}{}",
+        name.to_string_lossy(),
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed),
+        TEMP_SUFFIX
+    ));
+    let result = (|| -> std::io::Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        if private {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp)?;
+        if let Some(existing) = fs::metadata(path).ok().filter(|_| !private) {
+            file.set_permissions(existing.permissions())?;
+        }
+        file.write_all(data)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        // The rename is only durable once the directory entry is synced as well
+        #[cfg(unix)]
+        if let Ok(dir) = fs::File::open(parent) {
+            let _ = dir.sync_all();
+        }
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.with_context(|| format!("Failed to write {}", path.display()))
+}
+pub fn write_json_atomic<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    write_atomic(path, serde_json::to_string_pretty(value)?)
+}
+/// Moves an unreadable state file to `<name>.corrupt-<timestamp>` instead of letting the next
+/// save overwrite it, and says where it went
+pub fn quarantine(path: &Path, reason: &dyn std::fmt::Display) -> Option<PathBuf> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let aside = path.with_file_name(quarantine_name(&name));
+    fs::rename(path, &aside).ok()?;
+    log::warn!("{} was unreadable ({}); moved to {}", path.display(), reason, aside.display());
+    Some(aside)
+}
+/// Exclusive advisory lock on `path` (through `.<name>.lock` beside it), released on drop.
+/// Parallel cm invocations queue here instead of overwriting each other's updates
+#[derive(Debug)]
+pub struct FileLock {
+    _file: fs::File,
+}
+pub fn lock(path: &Path) -> Result<FileLock> {
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    fs::create_dir_all(parent)?;
+    let name = path.file_name().with_context(|| format!("Not a file path: {}", path.display()))?;
+    let lock_path = parent.join(format!(".{}.lock", name.to_string_lossy()));
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+    file.lock().with_context(|| format!("Failed to lock {}", lock_path.display()))?;
+    Ok(FileLock { _file: file })
+}
+/// Read-modify-write of a JSON state file under its lock, so concurrent updates add up
+pub fn update_json<T, R>(path: &Path, update: impl FnOnce(&mut T) -> R) -> Result<R>
+where
+    T: DeserializeOwned + Serialize + Default,
+{
+    let _lock = lock(path)?;
+    let mut value = read_json_file(path)?.unwrap_or_default();
+    let result = update(&mut value);
+    write_json_atomic(path, &value)?;
+    Ok(result)
+}
+/// State files read as `None` when missing or empty, and when they do not parse, after
+/// being quarantined. Other I/O errors are returned
+pub fn read_json_file<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+    match serde_json::from_str(&content) {
+        Ok(value) => Ok(Some(value)),
+        Err(e) => {
+            quarantine(path, &e);
+            Ok(None)
+        }
+    }
+}
+fn normalize_key(key: &str) -> Result<String> {
+    let parts: Vec<&str> = key.split('/').filter(|p| !p.is_empty() && *p != ".").collect();
+    if parts.is_empty() || parts.contains(&"..") {
+        anyhow::bail!("Invalid storage key '{}'", key);
+    }
+    Ok(parts.join("/"))
+}
+pub struct FsStorage {
+    root: PathBuf,
+}
+impl FsStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+    pub fn home() -> Result<Self> {
+        Ok(
+            Self::new(
+                dirs::home_dir().context("Could not find home directory")?.join(".shipwreck"),
+            ),
+        )
+    }
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        Ok(self.root.join(normalize_key(key)?))
+    }
+}
+impl Storage for FsStorage {
+    fn read(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.path(key)?;
+        match fs::read(&path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+    fn write(&self, key: &str, data: &[u8]) -> Result<()> {
+        write_atomic(&self.path(key)?, data)
+    }
+    fn remove(&self, key: &str) -> Result<()> {
+        let path = self.path(key)?;
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        Ok(())
+    }
+    fn list(&self, dir: &str) -> Result<Vec<String>> {
+        let path = if dir.is_empty() { self.root.clone() } else { self.path(dir)? };
+        if !path.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut names: Vec<String> = fs::read_dir(&path)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+}
+/// Backs the wasm build and the tests
+#[cfg(any(test, target_arch = "wasm32"))]
+#[derive(Default)]
+pub struct MemoryStorage {
+    files: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+#[cfg(any(test, target_arch = "wasm32"))]
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn snapshot(&self) -> BTreeMap<String, Vec<u8>> {
+        self.files.lock().unwrap().clone()
+    }
+    pub fn restore_snapshot(&self, files: BTreeMap<String, Vec<u8>>) {
+        *self.files.lock().unwrap() = files;
+    }
+    pub fn export_json(&self) -> Result<String> {
+        let encoded: BTreeMap<String, String> = self
+            .snapshot()
+            .into_iter()
+            .map(|(key, data)| (key, base64::engine::general_purpose::STANDARD.encode(data)))
+            .collect();
+        Ok(serde_json::to_string(&encoded)?)
+    }
+    pub fn import_json(&self, json: &str) -> Result<()> {
+        let encoded: BTreeMap<String, String> = serde_json::from_str(json)?;
+        let mut files = BTreeMap::new();
+        for (key, data) in encoded {
+            files
+                .insert(
+                    normalize_key(&key)?,
+                    base64::engine::general_purpose::STANDARD.decode(data)?,
+                );
+        }
+        self.restore_snapshot(files);
+        Ok(())
+    }
+}
+#[cfg(any(test, target_arch = "wasm32"))]
+impl Storage for MemoryStorage {
+    fn read(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.files.lock().unwrap().get(&normalize_key(key)?).cloned())
+    }
+    fn write(&self, key: &str, data: &[u8]) -> Result<()> {
+        self.files.lock().unwrap().insert(normalize_key(key)?, data.to_vec());
+        Ok(())
+    }
+    fn remove(&self, key: &str) -> Result<()> {
+        let key = normalize_key(key)?;
+        let prefix = format!("{}/", key);
+        self.files.lock().unwrap().retain(|k, _| *k != key && !k.starts_with(&prefix));
+        Ok(())
+    }
+    fn list(&self, dir: &str) -> Result<Vec<String>> {
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", normalize_key(dir)?)
+        };
+        let mut names: Vec<String> = self
+            .files
+            .lock()
+            .unwrap()
+            .keys()
+            .filter_map(|key| key.strip_prefix(&prefix))
+            .filter_map(|rest| rest.split('/').next())
+            .map(str::to_string)
+            .collect();
+        names.dedup();
+        Ok(names)
+    }
+}
+static DEFAULT_STORAGE: OnceLock<Arc<dyn Storage>> = OnceLock::new();
+pub fn default_storage() -> Result<Arc<dyn Storage>> {
+    if let Some(storage) = DEFAULT_STORAGE.get() {
+        return Ok(storage.clone());
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let storage: Arc<dyn Storage> = Arc::new(FsStorage::home()?);
+    #[cfg(target_arch = "wasm32")]
+    let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+    Ok(DEFAULT_STORAGE.get_or_init(|| storage).clone())
+}
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub mod indexed_db {
+    use super::{normalize_key, MemoryStorage, Storage};
+    use anyhow::{anyhow, Result};
+    use std::collections::BTreeSet;
+    use std::sync::Mutex;
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{IdbDatabase, IdbOpenDbRequest, IdbRequest, IdbTransactionMode};
+    const STORE: &str = "shipwreck";
+    fn js_err(value: JsValue) -> anyhow::Error {
+        anyhow!("IndexedDB error: {:?}", value)
+    }
+    async fn await_request(request: &IdbRequest) -> Result<JsValue> {
+        let promise = js_sys::Promise::new(
+            &mut |resolve, reject| {
+                let ok = Closure::once_into_js(move || {
+                    let _ = resolve.call0(&JsValue::NULL);
+                });
+                let fail = Closure::once_into_js(move || {
+                    let _ = reject.call0(&JsValue::NULL);
+                });
+                request.set_onsuccess(Some(ok.unchecked_ref()));
+                request.set_onerror(Some(fail.unchecked_ref()));
+            },
+        );
+        JsFuture::from(promise).await.map_err(js_err)?;
+        request.result().map_err(js_err)
+    }
+    pub struct IndexedDbStorage {
+        db: IdbDatabase,
+        cache: MemoryStorage,
+        dirty: Mutex<BTreeSet<String>>,
+        removed: Mutex<BTreeSet<String>>,
+    }
+    unsafe impl Send for IndexedDbStorage {}
+    unsafe impl Sync for IndexedDbStorage {}
+    impl IndexedDbStorage {
+        pub async fn open(name: &str) -> Result<Self> {
+            let factory = web_sys::window()
+                .ok_or_else(|| anyhow!("No window available"))?
+                .indexed_db()
+                .map_err(js_err)?
+                .ok_or_else(|| anyhow!("IndexedDB is not available"))?;
+            let request: IdbOpenDbRequest = factory.open_with_u32(name, 1).map_err(js_err)?;
+            let upgrade = Closure::once_into_js(move |event: web_sys::Event| {
+                let request: IdbOpenDbRequest = event.target().unwrap().unchecked_into();
+                let db: IdbDatabase = request.result().unwrap().unchecked_into();
+                if !db.object_store_names().contains(STORE) {
+                    let _ = db.create_object_store(STORE);
+                }
+            });
+            request.set_onupgradeneeded(Some(upgrade.unchecked_ref()));
+            let db: IdbDatabase = await_request(&request).await?.unchecked_into();
+            let storage = Self {
+                db,
+                cache: MemoryStorage::new(),
+                dirty: Mutex::new(BTreeSet::new()),
+                removed: Mutex::new(BTreeSet::new()),
+            };
+            storage.load().await?;
+            Ok(storage)
+        }
+        async fn load(&self) -> Result<()> {
+            let store = self
+                .db
+                .transaction_with_str(STORE)
+                .map_err(js_err)?
+                .object_store(STORE)
+                .map_err(js_err)?;
+            let keys = await_request(&store.get_all_keys().map_err(js_err)?).await?;
+            let values = await_request(&store.get_all().map_err(js_err)?).await?;
+            let keys: js_sys::Array = keys.unchecked_into();
+            let values: js_sys::Array = values.unchecked_into();
+            for (key, value) in keys.iter().zip(values.iter()) {
+                if let Some(key) = key.as_string() {
+                    let data = js_sys::Uint8Array::new(&value).to_vec();
+                    self.cache.write(&key, &data)?;
+                }
+            }
+            Ok(())
+        }
+        pub async fn flush(&self) -> Result<()> {
+            let dirty: Vec<String> = std::mem::take(&mut *self.dirty.lock().unwrap())
+                .into_iter()
+                .collect();
+            let removed: Vec<String> = std::mem::take(&mut *self.removed.lock().unwrap())
+                .into_iter()
+                .collect();
+            if dirty.is_empty() && removed.is_empty() {
+                return Ok(());
+            }
+            let transaction = self
+                .db
+                .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)
+                .map_err(js_err)?;
+            let store = transaction.object_store(STORE).map_err(js_err)?;
+            for key in removed {
+                await_request(&store.delete(&JsValue::from_str(&key)).map_err(js_err)?)
+                    .await?;
+            }
+            for key in dirty {
+                if let Some(data) = self.cache.read(&key)? {
+                    let value = js_sys::Uint8Array::from(data.as_slice());
+                    await_request(
+                            &store
+                                .put_with_key(&value, &JsValue::from_str(&key))
+                                .map_err(js_err)?,
+                        )
+                        .await?;
+                }
+            }
+            Ok(())
+        }
+    }
+    impl Storage for IndexedDbStorage {
+        fn read(&self, key: &str) -> Result<Option<Vec<u8>>> {
+            self.cache.read(key)
+        }
+        fn write(&self, key: &str, data: &[u8]) -> Result<()> {
+            let key = normalize_key(key)?;
+            self.cache.write(&key, data)?;
+            self.removed.lock().unwrap().remove(&key);
+            self.dirty.lock().unwrap().insert(key);
+            Ok(())
+        }
+        fn remove(&self, key: &str) -> Result<()> {
+            let key = normalize_key(key)?;
+            let prefix = format!("{}/", key);
+            let gone: Vec<String> = self
+                .cache
+                .snapshot()
+                .into_keys()
+                .filter(|k| *k == key || k.starts_with(&prefix))
+                .collect();
+            self.cache.remove(&key)?;
+            let mut dirty = self.dirty.lock().unwrap();
+            let mut removed = self.removed.lock().unwrap();
+            for k in gone {
+                dirty.remove(&k);
+                removed.insert(k);
+            }
+            Ok(())
+        }
+        fn list(&self, dir: &str) -> Result<Vec<String>> {
+            self.cache.list(dir)
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_memory_storage_layout() {
+        let storage = MemoryStorage::new();
+        storage.write("anchors/a.json", b"{}").unwrap();
+        storage.write("snapshots/a/src/main.rs", b"fn main() {}").unwrap();
+        storage.write("snapshots/a/Cargo.toml", b"[package]").unwrap();
+        assert_eq!(storage.list("snapshots/a").unwrap(), vec!["Cargo.toml", "src"]);
+        assert_eq!(storage.list("").unwrap(), vec!["anchors", "snapshots"]);
+        assert!(storage.write("../escape", b"x").is_err());
+        let exported = storage.export_json().unwrap();
+        storage.remove("snapshots").unwrap();
+        assert!(!storage.exists("snapshots/a/Cargo.toml"));
+        storage.import_json(&exported).unwrap();
+        assert_eq!(storage.read("./snapshots//a/src/main.rs").unwrap().unwrap(), b"fn main() {}");
+    }
+    #[test]
+    fn test_atomic_write_and_corrupt_files_are_quarantined() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("history.json");
+        write_json_atomic(&path, &vec![1, 2]).unwrap();
+        write_json_atomic(&path, &vec![3]).unwrap();
+        assert_eq!(read_json_file::<Vec<u32>>(&path).unwrap(), Some(vec![3]));
+        let names = |dir: &Path| -> Vec<String> {
+            let mut names: Vec<String> =
+                fs::read_dir(dir).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().to_string()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(path.parent().unwrap()), vec!["history.json"]);
+        fs::write(&path, "[1, 2").unwrap();
+        assert_eq!(read_json_file::<Vec<u32>>(&path).unwrap(), None);
+        let left = names(path.parent().unwrap());
+        assert_eq!(left.len(), 1);
+        assert!(left[0].starts_with("history.json.corrupt-"));
+        assert_eq!(fs::read_to_string(path.with_file_name(&left[0])).unwrap(), "[1, 2");
+        let storage = MemoryStorage::new();
+        storage.write("log.json", b"{oops").unwrap();
+        assert_eq!(read_json::<Vec<u32>>(&storage, "log.json").unwrap(), None);
+        assert!(!storage.exists("log.json"));
+        assert_eq!(storage.list("").unwrap().len(), 1);
+    }
+    #[test]
+    fn test_concurrent_updates_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for entry in 0..25 {
+                        update_json(&path, |history: &mut Vec<u32>| history.push(writer * 100 + entry)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(read_json_file::<Vec<u32>>(&path).unwrap().unwrap().len(), 200);
+    }
+}
@@ -147,7 +147,7 @@ fn create_backup(original_path: &PathBuf, backup_dir: &PathBuf) -> Result<PathBu
     println!("🔄 Backup created: {}", backup_path.display());
     Ok(backup_path)
 }
-fn strip_rust(source: &str, args: &StripArgs) -> Result<String> {
+pub(crate) fn strip_rust(source: &str, args: &StripArgs) -> Result<String> {
     let source_to_parse = if args.tease {
         strip_all_comments_manual(source)
     } else {
@@ -266,7 +266,24 @@ fn strip_doc_comments(syntax_tree: &mut syn::File) {
             }
             visit_mut::visit_item_mut(self, item);
         }
+        fn visit_field_mut(&mut self, field: &mut syn::Field) {
+            field.attrs.retain(|attr| !is_doc_attr(attr));
+            visit_mut::visit_field_mut(self, field);
+        }
+        fn visit_variant_mut(&mut self, variant: &mut syn::Variant) {
+            variant.attrs.retain(|attr| !is_doc_attr(attr));
+            visit_mut::visit_variant_mut(self, variant);
+        }
+        fn visit_impl_item_fn_mut(&mut self, item: &mut syn::ImplItemFn) {
+            item.attrs.retain(|attr| !is_doc_attr(attr));
+            visit_mut::visit_impl_item_fn_mut(self, item);
+        }
+        fn visit_trait_item_fn_mut(&mut self, item: &mut syn::TraitItemFn) {
+            item.attrs.retain(|attr| !is_doc_attr(attr));
+            visit_mut::visit_trait_item_fn_mut(self, item);
+        }
     }
+    syntax_tree.attrs.retain(|attr| !is_doc_attr(attr));
     DocStripper.visit_file_mut(syntax_tree);
 }
 fn inline_use_statements(_syntax_tree: &mut syn::File) {}
@@ -528,6 +545,7 @@ fn main() {
             max_depth: 10,
             aggressive: false,
             minify: false,
+            tease: false,
             strip_attrs: false,
             strip_docs: false,
             inline_uses: false,
@@ -560,6 +578,7 @@ fn main() {
             max_depth: 10,
             aggressive: true,
             minify: false,
+            tease: false,
             strip_attrs: false,
             strip_docs: false,
             inline_uses: false,
@@ -583,6 +602,7 @@ fn main() {
             max_depth: 10,
             aggressive: false,
             minify: false,
+            tease: false,
             strip_attrs: false,
             strip_docs: false,
             inline_uses: false,
@@ -590,6 +610,7 @@ fn main() {
         assert_eq!(determine_input_path(& args).unwrap(), PathBuf::from("test.rs"));
         args.src = true;
         assert_eq!(determine_input_path(& args).unwrap(), PathBuf::from("src"));
+        args.src = false;
         args.target = Some(PathBuf::from("target/dir"));
         assert_eq!(determine_input_path(& args).unwrap(), PathBuf::from("target/dir"));
     }
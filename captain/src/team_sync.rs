@@ -0,0 +1,293 @@
+use crate::captain::config::ConfigManager;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use toml::Table;
+/// The synced team layer and its state live in the project's ~/.shipwreck bucket
+const TEAM_DIR: &str = "team";
+pub const TEAM_CONFIG: &str = "config.toml";
+const STATE_FILE: &str = "sync.json";
+/// Team config files looked for at the root of a synced source, in order
+const CONFIG_NAMES: &[&str] = &[".cg", "cg.toml"];
+const JOURNEYS_DIR: &str = "journeys";
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    source: String,
+    #[serde(default)]
+    git_ref: Option<String>,
+    synced_at: String,
+    /// Hash of each journey as last written by a sync, to tell local edits apart
+    #[serde(default)]
+    journeys: BTreeMap<String, String>,
+}
+/// What a source provides: the team .cg and any shared journeys
+#[derive(Debug, Default)]
+pub struct TeamBundle {
+    pub config: Option<String>,
+    pub journeys: BTreeMap<String, String>,
+}
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub config_keys: usize,
+    /// Team keys that the project's own .cg sets to something else
+    pub local_overrides: Vec<String>,
+    pub hook_changes: Vec<(String, String)>,
+    pub journeys_updated: Vec<String>,
+    pub journeys_kept: Vec<String>,
+}
+pub fn team_dir(bucket: &Path) -> PathBuf {
+    bucket.join(TEAM_DIR)
+}
+fn hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+fn is_git_source(source: &str) -> bool {
+    source.starts_with("git@")
+        || source.starts_with("ssh://")
+        || source.starts_with("git://")
+        || source.starts_with("git+")
+        || source.ends_with(".git")
+}
+/// Reads a checked-out or local source directory
+pub fn read_dir_source(root: &Path) -> Result<TeamBundle> {
+    let mut bundle = TeamBundle {
+        config: CONFIG_NAMES.iter().find_map(|name| fs::read_to_string(root.join(name)).ok()),
+        ..TeamBundle::default()
+    };
+    for entry in fs::read_dir(root.join(JOURNEYS_DIR)).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            if let Some(name) = path.file_stem() {
+                bundle.journeys.insert(name.to_string_lossy().to_string(), fs::read_to_string(&path)?);
+            }
+        }
+    }
+    if bundle.config.is_none() && bundle.journeys.is_empty() {
+        bail!("{} has no .cg, cg.toml or journeys/ to sync", root.display());
+    }
+    Ok(bundle)
+}
+/// A scratch directory for a fetched source, removed once it has been read
+fn scratch(kind: &str) -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("cm-team-{}-{}", kind, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+fn fetch_git(source: &str, git_ref: Option<&str>) -> Result<TeamBundle> {
+    let checkout = scratch("git")?.join("source");
+    let url = source.strip_prefix("git+").unwrap_or(source);
+    let mut command = Command::new("git");
+    command.args(["clone", "--quiet", "--depth", "1"]);
+    if let Some(git_ref) = git_ref {
+        command.args(["--branch", git_ref]);
+    }
+    crate::net::NetworkSettings::load().apply_env(&mut command);
+    let status = command.arg(url).arg(&checkout).status().context("Failed to run git");
+    let bundle = match status {
+        Ok(status) if status.success() => read_dir_source(&checkout),
+        Ok(_) => Err(anyhow::anyhow!("git clone {} failed", crate::net::redact(url))),
+        Err(e) => Err(e),
+    };
+    let _ = fs::remove_dir_all(checkout.parent().unwrap_or(&checkout));
+    bundle
+}
+/// An HTTP source serves either the team .cg itself or a .tar.gz laid out like the git repo
+fn fetch_http(url: &str) -> Result<TeamBundle> {
+    crate::net::ensure_online("Team config sync")?;
+    let response = crate::net::send(crate::net::client(Duration::from_secs(30))?.get(url))?;
+    if !response.status().is_success() {
+        bail!("{} returned {}", crate::net::redact(url), response.status());
+    }
+    let body = response.bytes()?;
+    if body.starts_with(&[0x1f, 0x8b]) {
+        let unpacked = scratch("http")?;
+        let bundle = tar::Archive::new(flate2::read::GzDecoder::new(&body[..]))
+            .unpack(&unpacked)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| read_dir_source(&unpacked));
+        let _ = fs::remove_dir_all(&unpacked);
+        return bundle;
+    }
+    Ok(TeamBundle { config: Some(String::from_utf8(body.to_vec())?), ..TeamBundle::default() })
+}
+pub fn fetch(source: &str, git_ref: Option<&str>) -> Result<TeamBundle> {
+    let is_http = source.starts_with("http://") || source.starts_with("https://");
+    if is_http && !source.ends_with(".git") {
+        return fetch_http(source);
+    }
+    if is_http || is_git_source(source) {
+        return fetch_git(source, git_ref);
+    }
+    if Path::new(source).is_dir() {
+        return read_dir_source(Path::new(source));
+    }
+    bail!("Don't know how to sync from '{}' (expected a git URL, an http(s) URL or a directory)", source)
+}
+fn flatten(table: &Table) -> BTreeMap<String, String> {
+    let mut out = std::collections::HashMap::new();
+    ConfigManager::flatten("", table, &mut out);
+    out.into_iter().collect()
+}
+fn load_state(dir: &Path) -> SyncState {
+    crate::storage::read_json_file(&dir.join(STATE_FILE)).ok().flatten().unwrap_or_default()
+}
+/// Works out what a sync changes without touching anything
+pub fn plan(
+    bundle: &TeamBundle,
+    team_dir: &Path,
+    journeys_dir: &Path,
+    local: &BTreeMap<String, String>,
+) -> Result<SyncReport> {
+    let mut report = SyncReport::default();
+    if let Some(config) = &bundle.config {
+        let incoming = flatten(&config.parse::<Table>().context("Team config is not valid TOML")?);
+        let current = fs::read_to_string(team_dir.join(TEAM_CONFIG))
+            .ok()
+            .and_then(|content| content.parse::<Table>().ok())
+            .map(|table| flatten(&table))
+            .unwrap_or_default();
+        report.config_keys = incoming.len();
+        for (key, value) in &incoming {
+            if local.get(key).is_some_and(|local| local != value) {
+                report.local_overrides.push(key.clone());
+            }
+            if key.starts_with("hooks.") && current.get(key) != Some(value) {
+                report.hook_changes.push((key.clone(), value.clone()));
+            }
+        }
+    }
+    let state = load_state(team_dir);
+    for (name, content) in &bundle.journeys {
+        serde_json::from_str::<crate::journey::Journey>(content)
+            .with_context(|| format!("Team journey '{}' is not a valid journey", name))?;
+        match fs::read_to_string(journeys_dir.join(format!("{}.json", name))) {
+            Ok(existing) if existing == *content => {}
+            // Edited here since the last sync, or never came from the team
+            Ok(existing) if state.journeys.get(name) != Some(&hash(&existing)) => {
+                report.journeys_kept.push(name.clone())
+            }
+            _ => report.journeys_updated.push(name.clone()),
+        }
+    }
+    Ok(report)
+}
+pub fn apply(
+    bundle: &TeamBundle,
+    report: &SyncReport,
+    team_dir: &Path,
+    journeys_dir: &Path,
+    source: &str,
+    git_ref: Option<&str>,
+) -> Result<()> {
+    fs::create_dir_all(team_dir)?;
+    if let Some(config) = &bundle.config {
+        crate::storage::write_atomic(&team_dir.join(TEAM_CONFIG), config)?;
+    }
+    let mut state = load_state(team_dir);
+    fs::create_dir_all(journeys_dir)?;
+    for name in &report.journeys_updated {
+        let content = &bundle.journeys[name];
+        crate::storage::write_atomic(&journeys_dir.join(format!("{}.json", name)), content)?;
+        state.journeys.insert(name.clone(), hash(content));
+    }
+    state.source = source.to_string();
+    state.git_ref = git_ref.map(str::to_string);
+    state.synced_at = Utc::now().to_rfc3339();
+    crate::storage::write_json_atomic(&team_dir.join(STATE_FILE), &state)
+}
+pub fn handle_sync(from: Option<String>, git_ref: Option<String>, dry_run: bool, yes: bool) -> Result<()> {
+    let bucket = crate::project::current_project()
+        .context("Team config sync works per project - run it inside a cargo project")?;
+    let team_dir = team_dir(&bucket.dir);
+    let state = load_state(&team_dir);
+    let config = ConfigManager::new()?;
+    let source = from
+        .or_else(|| Some(state.source.clone()).filter(|s| !s.is_empty()))
+        .or_else(|| config.get("team.source"))
+        .context("No team source - pass --from <git url|https url|dir> or set team.source in .cg")?;
+    let git_ref = git_ref.or(state.git_ref).or_else(|| config.get("team.ref"));
+    println!("🔄 Syncing team config from {}", crate::net::redact(&source).cyan());
+    let bundle = fetch(&source, git_ref.as_deref())?;
+    let local: BTreeMap<String, String> = config
+        .entries()
+        .into_iter()
+        .filter(|entry| entry.source == crate::captain::config::ConfigSource::Local)
+        .map(|entry| (entry.key, entry.value))
+        .collect();
+    let journeys_dir = crate::project::shipwreck_dir()?.join(JOURNEYS_DIR);
+    let report = plan(&bundle, &team_dir, &journeys_dir, &local)?;
+    if bundle.config.is_some() {
+        println!("  ⚙️  {} team setting(s)", report.config_keys);
+    }
+    for key in &report.local_overrides {
+        println!("  📌 {} keeps its value from this project's .cg", key.cyan());
+    }
+    for (key, command) in &report.hook_changes {
+        println!("  🪝 {} = {}", key.yellow(), command);
+    }
+    for name in &report.journeys_updated {
+        println!("  🗺️  journey {}", name.green());
+    }
+    for name in &report.journeys_kept {
+        println!("  ✋ journey {} has local changes - kept", name.cyan());
+    }
+    if dry_run {
+        println!("{}", "Dry run - nothing written".dimmed());
+        return Ok(());
+    }
+    if !report.hook_changes.is_empty()
+        && !yes
+        && !crate::ci::confirm("⚠️  The team config sets hooks that run shell commands on every build. Accept them?")?
+    {
+        bail!("Sync cancelled - hooks were not accepted (pass --yes to accept non-interactively)");
+    }
+    apply(&bundle, &report, &team_dir, &journeys_dir, &source, git_ref.as_deref())?;
+    println!("✅ Team config synced; this project's .cg still overrides it");
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_sync_preserves_local_journeys_and_overrides() {
+        let base = std::env::temp_dir().join(format!("cm-team-sync-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let (source, team, journeys) = (base.join("source"), base.join("team"), base.join("journeys"));
+        fs::create_dir_all(source.join(JOURNEYS_DIR)).unwrap();
+        fs::create_dir_all(&journeys).unwrap();
+        fs::write(source.join(".cg"), "[publish]\nrequire_tests = true\n[hooks]\npre_build = \"cargo fmt --check\"\n").unwrap();
+        let journey = |name: &str, description: &str| {
+            serde_json::json!({
+                "name": name, "description": description, "created": "2026-01-01T00:00:00Z",
+                "commands": [], "variables": {}, "checkpoints": [], "environment": {}, "success_rate": 1.0
+            })
+            .to_string()
+        };
+        fs::write(source.join("journeys/release.json"), journey("release", "team")).unwrap();
+        fs::write(source.join("journeys/mine.json"), journey("mine", "team")).unwrap();
+        fs::write(journeys.join("mine.json"), journey("mine", "local")).unwrap();
+        let bundle = read_dir_source(&source).unwrap();
+        let local = BTreeMap::from([("publish.require_tests".to_string(), "false".to_string())]);
+        let report = plan(&bundle, &team, &journeys, &local).unwrap();
+        assert_eq!(report.local_overrides, vec!["publish.require_tests"]);
+        assert_eq!(report.hook_changes.len(), 1);
+        assert_eq!(report.journeys_updated, vec!["release"]);
+        assert_eq!(report.journeys_kept, vec!["mine"]);
+        apply(&bundle, &report, &team, &journeys, "source", None).unwrap();
+        assert!(team.join(TEAM_CONFIG).exists());
+        // A team update reaches journeys that were left untouched since the last sync
+        fs::write(source.join("journeys/release.json"), journey("release", "team v2")).unwrap();
+        let report = plan(&read_dir_source(&source).unwrap(), &team, &journeys, &local).unwrap();
+        assert_eq!(report.journeys_updated, vec!["release"]);
+        assert!(report.hook_changes.is_empty());
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
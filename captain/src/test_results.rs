@@ -0,0 +1,424 @@
+use crate::captain::config::ConfigManager;
+use crate::hooks::{HookContext, HookSet};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+pub const RUNS_FILE: &str = "tests/runs.json";
+const MAX_RUNS: usize = 100;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestResult {
+    pub name: String,
+    pub outcome: TestOutcome,
+    pub duration_ms: Option<f64>,
+    /// Failed first, then passed when the wrapper retried it
+    #[serde(default)]
+    pub flaky: bool,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestRun {
+    pub timestamp: DateTime<Utc>,
+    pub command: String,
+    pub results: Vec<TestResult>,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlakyTest {
+    pub name: String,
+    pub passes: usize,
+    pub failures: usize,
+    pub flips: usize,
+    pub retried: usize,
+    pub pattern: String,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowTest {
+    pub name: String,
+    pub runs: usize,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    pub last_ms: f64,
+}
+pub fn is_test_command(args: &[&str]) -> bool {
+    args.iter().find(|arg| **arg != "cargo" && !arg.starts_with('+')) == Some(&"test")
+}
+/// Understands both the human libtest format (`test a::b ... ok <0.012s>` when
+/// `--report-time` is on) and `--format json` events
+pub fn parse_line(line: &str) -> Option<TestResult> {
+    let line = line.trim();
+    if line.starts_with('{') {
+        let event: Value = serde_json::from_str(line).ok()?;
+        if event["type"] != "test" {
+            return None;
+        }
+        let outcome = match event["event"].as_str()? {
+            "ok" => TestOutcome::Passed,
+            "failed" | "timeout" => TestOutcome::Failed,
+            "ignored" => TestOutcome::Ignored,
+            _ => return None,
+        };
+        return Some(TestResult {
+            name: event["name"].as_str()?.to_string(),
+            outcome,
+            duration_ms: event["exec_time"].as_f64().map(|secs| secs * 1000.0),
+            flaky: false,
+        });
+    }
+    let (name, status) = line.strip_prefix("test ")?.rsplit_once(" ... ")?;
+    let (word, rest) = status.split_once(' ').unwrap_or((status, ""));
+    let outcome = match word.trim_end_matches(',') {
+        "ok" => TestOutcome::Passed,
+        "FAILED" => TestOutcome::Failed,
+        "ignored" => TestOutcome::Ignored,
+        _ => return None,
+    };
+    let duration_ms = rest
+        .trim()
+        .strip_prefix('<')
+        .and_then(|t| t.strip_suffix("s>"))
+        .and_then(|secs| secs.parse::<f64>().ok())
+        .map(|secs| secs * 1000.0);
+    Some(TestResult { name: name.to_string(), outcome, duration_ms, flaky: false })
+}
+pub fn load_runs() -> Vec<TestRun> {
+    crate::project::data_file(RUNS_FILE)
+        .ok()
+        .and_then(|path| crate::storage::read_json_file(&path).ok().flatten())
+        .unwrap_or_default()
+}
+fn save_run(run: TestRun) -> Result<()> {
+    let path = crate::project::data_file(RUNS_FILE)?;
+    let _lock = crate::storage::lock(&path)?;
+    let mut runs = load_runs();
+    runs.push(run);
+    if runs.len() > MAX_RUNS {
+        runs.drain(..runs.len() - MAX_RUNS);
+    }
+    crate::storage::write_atomic(&path, serde_json::to_string(&runs)?)
+}
+fn retry_limit() -> u32 {
+    ConfigManager::new()
+        .ok()
+        .and_then(|config| config.get("test.retry_flaky"))
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+/// Retries need every test binary to have run, so cargo must not stop at the first
+/// failing one
+pub fn with_no_fail_fast(args: &[&str]) -> Vec<String> {
+    let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    let cargo_args = args.iter().position(|arg| arg == "--").unwrap_or(args.len());
+    if !args[..cargo_args].iter().any(|arg| arg == "--no-fail-fast") {
+        if let Some(test) = args[..cargo_args].iter().position(|arg| arg == "test") {
+            args.insert(test + 1, "--no-fail-fast".to_string());
+        }
+    }
+    args
+}
+/// Keep the cargo-side arguments and libtest options, then select exactly the failed tests
+pub fn retry_args(args: &[String], failed: &[String]) -> Vec<String> {
+    let split = args.iter().position(|arg| arg == "--").unwrap_or(args.len());
+    let mut retry: Vec<String> = args[..split].to_vec();
+    retry.push("--".to_string());
+    retry.extend(args[split..].iter().skip(1).filter(|arg| *arg != "--exact").cloned());
+    retry.push("--exact".to_string());
+    retry.extend(failed.iter().cloned());
+    retry
+}
+fn run_and_parse(cargo_path: &str, args: &[String]) -> (i32, Vec<TestResult>) {
+    let mut child = match Command::new(cargo_path).args(args).stdout(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Failed to start cargo: {}", e);
+            return (1, Vec::new());
+        }
+    };
+    let mut results = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        let mut out = std::io::stdout();
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let _ = writeln!(out, "{}", line);
+            results.extend(parse_line(&line));
+        }
+    }
+    let code = child.wait().ok().and_then(|status| status.code()).unwrap_or(1);
+    (code, results)
+}
+fn failed_names(results: &[TestResult]) -> Vec<String> {
+    let mut names: Vec<String> = results
+        .iter()
+        .filter(|r| r.outcome == TestOutcome::Failed)
+        .map(|r| r.name.clone())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+/// Re-run the failed tests up to `limit` times, marking the ones that recover as flaky.
+/// Returns the exit code of the last attempt, or the original one if nothing was retried.
+fn retry_failures(cargo_path: &str, args: &[String], results: &mut [TestResult], code: i32, limit: u32) -> i32 {
+    let mut failed = failed_names(results);
+    if failed.is_empty() {
+        return code;
+    }
+    let mut code = code;
+    for attempt in 1..=limit {
+        println!(
+            "\n{}", format!("🔁 Retrying {} failed test(s) (attempt {}/{})", failed.len(),
+            attempt, limit).yellow()
+        );
+        let (retry_code, retried) = run_and_parse(cargo_path, &retry_args(args, &failed));
+        for result in retried.iter().filter(|r| r.outcome == TestOutcome::Passed) {
+            for original in results.iter_mut().filter(|r| r.name == result.name && r.outcome == TestOutcome::Failed) {
+                original.outcome = TestOutcome::Passed;
+                original.flaky = true;
+                original.duration_ms = result.duration_ms.or(original.duration_ms);
+            }
+        }
+        let still_failing = failed_names(results);
+        code = if still_failing.is_empty() { retry_code } else { retry_code.max(1) };
+        failed = still_failing;
+        if failed.is_empty() || retried.is_empty() {
+            break;
+        }
+    }
+    let recovered: Vec<&str> = results
+        .iter()
+        .filter(|r| r.flaky)
+        .map(|r| r.name.as_str())
+        .collect();
+    if !recovered.is_empty() {
+        println!("{} {}", "🎲 Passed on retry (marked flaky):".yellow().bold(), recovered.join(", "));
+    }
+    if !failed.is_empty() {
+        println!("{} {}", "❌ Still failing:".red().bold(), failed.join(", "));
+    }
+    code
+}
+/// Run `cargo test`, echoing libtest output while recording each test's outcome
+pub fn run_cargo_test(cargo_path: &str, args: &[&str]) -> i32 {
+    let hooks = HookSet::load();
+    let mut context = HookContext::new(args);
+    if let Err(e) = hooks.run_pre_build(&context) {
+        eprintln!("❌ {}", e);
+        return 1;
+    }
+    let start = std::time::Instant::now();
+    let limit = retry_limit();
+    let args = if limit > 0 {
+        with_no_fail_fast(args)
+    } else {
+        args.iter().map(|arg| arg.to_string()).collect()
+    };
+    let (mut code, mut results) = run_and_parse(cargo_path, &args);
+    if code != 0 && limit > 0 {
+        code = retry_failures(cargo_path, &args, &mut results, code, limit);
+    }
+    let failed = results.iter().filter(|r| r.outcome == TestOutcome::Failed).count();
+    if !results.is_empty() {
+        let run = TestRun {
+            timestamp: Utc::now(),
+            command: args.iter().skip_while(|arg| *arg == "cargo").cloned().collect::<Vec<_>>().join(" "),
+            results,
+        };
+        if let Err(e) = save_run(run) {
+            log::warn!("Could not record test results: {}", e);
+        }
+    }
+    context.exit_code = Some(code);
+    hooks.run_post_build(&context);
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    crate::status::command_finished(&args, code, start.elapsed(), failed, 0);
+    code
+}
+fn outcomes_by_test(runs: &[TestRun]) -> BTreeMap<&str, Vec<&TestResult>> {
+    let mut by_test: BTreeMap<&str, Vec<&TestResult>> = BTreeMap::new();
+    for run in runs {
+        for result in &run.results {
+            by_test.entry(result.name.as_str()).or_default().push(result);
+        }
+    }
+    by_test
+}
+/// A test is flaky when it passed on a retry, or flipped between pass and fail at least
+/// twice; a single flip is just a test that broke or got fixed
+pub fn flaky_tests(runs: &[TestRun], window: usize) -> Vec<FlakyTest> {
+    let recent = &runs[runs.len().saturating_sub(window)..];
+    let mut flaky: Vec<FlakyTest> = outcomes_by_test(recent)
+        .into_iter()
+        .filter_map(|(name, results)| {
+            let outcomes: Vec<TestOutcome> = results
+                .iter()
+                .map(|r| r.outcome)
+                .filter(|o| *o != TestOutcome::Ignored)
+                .collect();
+            let flips = outcomes.windows(2).filter(|pair| pair[0] != pair[1]).count();
+            let retried = results.iter().filter(|r| r.flaky).count();
+            if flips < 2 && retried == 0 {
+                return None;
+            }
+            Some(FlakyTest {
+                name: name.to_string(),
+                passes: outcomes.iter().filter(|o| **o == TestOutcome::Passed).count(),
+                failures: outcomes.iter().filter(|o| **o == TestOutcome::Failed).count(),
+                flips,
+                retried,
+                pattern: results
+                    .iter()
+                    .filter(|r| r.outcome != TestOutcome::Ignored)
+                    .map(|r| match (r.outcome, r.flaky) {
+                        (_, true) => '↻',
+                        (TestOutcome::Passed, _) => '✓',
+                        _ => '✗',
+                    })
+                    .collect(),
+            })
+        })
+        .collect();
+    flaky.sort_by(|a, b| {
+        (b.flips + b.retried).cmp(&(a.flips + a.retried)).then(a.name.cmp(&b.name))
+    });
+    flaky
+}
+pub fn slowest_tests(runs: &[TestRun]) -> Vec<SlowTest> {
+    let mut slow: Vec<SlowTest> = outcomes_by_test(runs)
+        .into_iter()
+        .filter_map(|(name, results)| {
+            let durations: Vec<f64> = results.iter().filter_map(|r| r.duration_ms).collect();
+            let last_ms = *durations.last()?;
+            Some(SlowTest {
+                name: name.to_string(),
+                runs: durations.len(),
+                mean_ms: durations.iter().sum::<f64>() / durations.len() as f64,
+                max_ms: durations.iter().cloned().fold(0.0, f64::max),
+                last_ms,
+            })
+        })
+        .collect();
+    slow.sort_by(|a, b| b.mean_ms.total_cmp(&a.mean_ms).then(a.name.cmp(&b.name)));
+    slow
+}
+pub fn show_flaky(window: usize) -> Result<()> {
+    let runs = load_runs();
+    if runs.is_empty() {
+        println!("🧪 No test runs recorded yet - run 'cargo test' through cargo-mate first");
+        return Ok(());
+    }
+    let flaky = flaky_tests(&runs, window);
+    let considered = runs.len().min(window);
+    if flaky.is_empty() {
+        println!("✅ No flaky tests in the last {} runs", considered);
+        return Ok(());
+    }
+    println!("{}", format!("🎲 Flaky tests (last {} runs)", considered).bold().yellow());
+    let width = flaky.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    for test in &flaky {
+        println!(
+            "  {}  {} passed, {} failed, {} flips, {} passed on retry  {}", format!("{:<width$}",
+            test.name, width = width).cyan(), test.passes.to_string().green(), test.failures
+            .to_string().red(), test.flips, test.retried, test.pattern.dimmed()
+        );
+    }
+    Ok(())
+}
+pub fn show_slowest(limit: usize) -> Result<()> {
+    let runs = load_runs();
+    let slow = slowest_tests(&runs);
+    if slow.is_empty() {
+        println!("⏱️  No test durations recorded yet");
+        println!(
+            "   libtest only reports times with {} or {} (nightly)", "--report-time"
+            .yellow(), "--format json".yellow()
+        );
+        println!("   e.g. cargo +nightly test -- -Z unstable-options --report-time");
+        return Ok(());
+    }
+    println!("{}", "🐢 Slowest tests".bold().blue());
+    let shown = &slow[..slow.len().min(limit)];
+    let width = shown.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    println!(
+        "{}", format!("  {:<width$}  {:>10} {:>10} {:>10} {:>5}", "TEST", "MEAN", "MAX", "LAST",
+        "RUNS", width = width).bold()
+    );
+    for test in shown {
+        println!(
+            "  {}  {:>8.1}ms {:>8.1}ms {:>8.1}ms {:>5}", format!("{:<width$}", test.name, width =
+            width).cyan(), test.mean_ms, test.max_ms, test.last_ms, test.runs
+        );
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn run(results: &[(&str, TestOutcome, Option<f64>)]) -> TestRun {
+        TestRun {
+            timestamp: Utc::now(),
+            command: "test".to_string(),
+            results: results
+                .iter()
+                .map(|(name, outcome, duration_ms)| TestResult {
+                    name: name.to_string(),
+                    outcome: *outcome,
+                    duration_ms: *duration_ms,
+                    flaky: false,
+                })
+                .collect(),
+        }
+    }
+    #[test]
+    fn parses_libtest_and_finds_flaky_and_slow_tests() {
+        use TestOutcome::*;
+        assert_eq!(parse_line("test net::retry ... ok").unwrap().outcome, Passed);
+        assert_eq!(parse_line("test net::retry ... FAILED <1.500s>").unwrap().duration_ms, Some(1500.0));
+        assert_eq!(parse_line("test slow ... ignored, needs network").unwrap().outcome, Ignored);
+        assert_eq!(parse_line("test src/lib.rs - add (line 5) ... ok").unwrap().name, "src/lib.rs - add (line 5)");
+        assert!(parse_line("test result: ok. 3 passed; 0 failed").is_none());
+        let json = parse_line(r#"{ "type": "test", "name": "a::b", "event": "failed", "exec_time": 0.25 }"#).unwrap();
+        assert_eq!((json.outcome, json.duration_ms), (Failed, Some(250.0)));
+        assert!(parse_line(r#"{ "type": "test", "event": "started", "name": "a::b" }"#).is_none());
+        assert!(is_test_command(&["cargo", "+nightly", "test", "--lib"]));
+        assert!(!is_test_command(&["build", "--tests"]));
+        let runs = vec![
+            run(&[("flaky", Passed, Some(10.0)), ("fixed", Failed, None), ("slow", Passed, Some(900.0))]),
+            run(&[("flaky", Failed, Some(30.0)), ("fixed", Passed, None)]),
+            run(&[("flaky", Ignored, None), ("fixed", Passed, None)]),
+            run(&[("flaky", Passed, Some(20.0)), ("slow", Passed, Some(1100.0))]),
+        ];
+        let flaky = flaky_tests(&runs, 10);
+        assert_eq!(flaky.len(), 1);
+        assert_eq!((flaky[0].name.as_str(), flaky[0].flips, flaky[0].pattern.as_str()), ("flaky", 2, "✓✗✓"));
+        assert!(flaky_tests(&runs, 2).is_empty());
+        let slow = slowest_tests(&runs);
+        assert_eq!(slow[0].name, "slow");
+        assert_eq!((slow[0].mean_ms, slow[0].max_ms, slow[0].last_ms), (1000.0, 1100.0, 1100.0));
+        assert_eq!(slow[1].runs, 3);
+    }
+    #[test]
+    fn retries_only_the_failed_tests_and_marks_recoveries() {
+        use TestOutcome::*;
+        let args = with_no_fail_fast(&["test", "--lib", "--", "--test-threads", "1", "--exact"]);
+        assert_eq!(args, vec!["test", "--no-fail-fast", "--lib", "--", "--test-threads", "1", "--exact"]);
+        assert_eq!(with_no_fail_fast(&["test", "--no-fail-fast"]), vec!["test", "--no-fail-fast"]);
+        assert_eq!(
+            retry_args(&args, &["a::b".to_string(), "c".to_string()]),
+            vec!["test", "--no-fail-fast", "--lib", "--", "--test-threads", "1", "--exact", "a::b", "c"]
+        );
+        assert_eq!(retry_args(&["test".to_string()], &["x".to_string()]), vec!["test", "--", "--exact", "x"]);
+        let mut marked = run(&[("net", Passed, None), ("net", Failed, None)]);
+        marked.results[0].flaky = true;
+        let runs = vec![run(&[("net", Passed, None)]), marked];
+        let flaky = flaky_tests(&runs, 10);
+        assert_eq!((flaky[0].retried, flaky[0].pattern.as_str()), (1, "✓↻✗"));
+    }
+}
@@ -25,6 +25,8 @@ use std::io;
 use std::path::PathBuf;
 use std::process::Command;
 use crate::captain::license;
+pub const REGRESSION_WINDOW: usize = 10;
+pub const REGRESSION_FACTOR: f64 = 1.5;
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BuildMetrics {
     pub timestamp: DateTime<Utc>,
@@ -40,6 +42,8 @@ pub struct BuildMetrics {
     pub crate_units_compiled: usize,
     pub memory_peak_mb: Option<f64>,
     pub cpu_usage_percent: Option<f64>,
+    #[serde(flatten)]
+    pub environment: crate::diff_build::BuildEnvironment,
 }
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DependencyMetrics {
@@ -66,6 +70,11 @@ pub struct DailySummary {
     pub total_errors: usize,
     pub total_warnings: usize,
 }
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyScan {
+    pub timings_collected: bool,
+    pub dependencies: usize,
+}
 pub struct TideCharts {
     data: TideData,
     data_file: PathBuf,
@@ -77,12 +86,7 @@ impl TideCharts {
             .context("Could not find home directory")?
             .join(".shipwreck")
             .join("tide_data.json");
-        let data = if data_file.exists() {
-            let content = fs::read_to_string(&data_file)?;
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            TideData::default()
-        };
+        let data = crate::storage::read_json_file(&data_file)?.unwrap_or_default();
         Ok(Self {
             data,
             data_file,
@@ -90,6 +94,9 @@ impl TideCharts {
         })
     }
     pub fn record_build(&mut self, metrics: BuildMetrics) -> Result<()> {
+        // Builds in other terminals may have recorded since this was loaded
+        let _lock = crate::storage::lock(&self.data_file)?;
+        self.data = crate::storage::read_json_file(&self.data_file)?.unwrap_or_default();
         self.data.builds.push(metrics.clone());
         let date = metrics.timestamp.date_naive().to_string();
         let summary = self
@@ -126,14 +133,19 @@ impl TideCharts {
         self.save()?;
         Ok(())
     }
-    pub fn analyze_dependencies(&mut self) -> Result<()> {
-        println!("🔍 Analyzing dependency compile times...");
+    pub fn builds(&self) -> &[BuildMetrics] {
+        &self.data.builds
+    }
+    pub fn daily_summaries(&self) -> Vec<&DailySummary> {
+        let mut summaries: Vec<&DailySummary> = self.data.daily_summary.values().collect();
+        summaries.sort_by(|a, b| a.date.cmp(&b.date));
+        summaries
+    }
+    pub fn dependencies(&self) -> &HashMap<String, DependencyMetrics> {
+        &self.data.dependencies
+    }
+    pub fn analyze_dependencies(&mut self) -> Result<DependencyScan> {
         let output = Command::new("cargo").args(&["build", "--timings"]).output()?;
-        if output.status.success() {
-            println!(
-                "✅ Timing data collected. Check target/cargo-timings/ for detailed report."
-            );
-        }
         let metadata = cargo_metadata::MetadataCommand::new().exec()?;
         for package in metadata.packages {
             if package.source.is_some() {
@@ -148,7 +160,10 @@ impl TideCharts {
             }
         }
         self.save()?;
-        Ok(())
+        Ok(DependencyScan {
+            timings_collected: output.status.success(),
+            dependencies: self.data.dependencies.len(),
+        })
     }
     pub fn show_interactive(&mut self) -> Result<()> {
         enable_raw_mode()?;
@@ -228,7 +243,7 @@ impl TideCharts {
                 Constraint::Min(5),
             ])
             .split(area);
-        let recent_builds = self.data.builds.iter().rev().take(50).collect::<Vec<_>>();
+        let recent_builds = self.builds().iter().rev().take(50).collect::<Vec<_>>();
         let success_rate = if !recent_builds.is_empty() {
             let successful = recent_builds.iter().filter(|b| b.success).count();
             (successful as f64 / recent_builds.len() as f64) * 100.0
@@ -236,10 +251,10 @@ impl TideCharts {
             0.0
         };
         let stats = vec![
-            format!("Total Builds: {}", self.data.builds.len()),
+            format!("Total Builds: {}", self.builds().len()),
             format!("Success Rate: {:.1}%", success_rate),
             format!("Avg Build Time: {:.2}s", self.get_avg_build_time()),
-            format!("Dependencies: {}", self.data.dependencies.len()),
+            format!("Dependencies: {}", self.dependencies().len()),
         ];
         let stats_widget = Paragraph::new(stats.join("\n"))
             .block(Block::default().borders(Borders::ALL).title("📊 Statistics"))
@@ -315,8 +330,7 @@ impl TideCharts {
                 .style(Style::default().fg(Color::Gray));
             frame.render_widget(placeholder, chunks[0]);
         }
-        let mut daily_summaries: Vec<_> = self.data.daily_summary.values().collect();
-        daily_summaries.sort_by(|a, b| b.date.cmp(&a.date));
+        let daily_summaries: Vec<_> = self.daily_summaries().into_iter().rev().collect();
         if !daily_summaries.is_empty() {
             let formatted_dates: Vec<String> = daily_summaries
                 .iter()
@@ -519,11 +533,9 @@ impl TideCharts {
         }
     }
     fn save(&self) -> Result<()> {
-        let json = serde_json::to_string_pretty(&self.data)?;
-        fs::write(&self.data_file, json)?;
-        Ok(())
+        crate::storage::write_json_atomic(&self.data_file, &self.data)
     }
-    pub fn export_csv(&self, path: &PathBuf) -> Result<()> {
+    pub fn export_csv(&self, path: &PathBuf) -> Result<usize> {
         let mut csv = String::new();
         csv.push_str("timestamp,command,duration,success,errors,warnings\n");
         for build in &self.data.builds {
@@ -536,9 +548,29 @@ impl TideCharts {
             );
         }
         fs::write(path, csv)?;
-        println!("✅ Build metrics exported to {}", path.display());
-        Ok(())
+        Ok(self.data.builds.len())
     }
+    pub fn export_html(&self, path: &PathBuf) -> Result<usize> {
+        let runs = crate::tide_report::read_timing_runs(&crate::tide_report::timings_dir());
+        fs::write(path, crate::tide_report::render_html(&self.data.builds, &runs))?;
+        Ok(self.data.builds.len())
+    }
+}
+pub fn regression_baseline(previous: &[f64]) -> Option<f64> {
+    let mut window: Vec<f64> = previous
+        .iter()
+        .rev()
+        .take(REGRESSION_WINDOW)
+        .copied()
+        .collect();
+    if window.is_empty() {
+        return None;
+    }
+    window.sort_by(|a, b| a.total_cmp(b));
+    Some(window[window.len() / 2])
+}
+pub fn is_regression(previous: &[f64], duration: f64) -> bool {
+    regression_baseline(previous).is_some_and(|baseline| duration > baseline * REGRESSION_FACTOR)
 }
 impl Default for TideData {
     fn default() -> Self {
@@ -0,0 +1,568 @@
+use crate::tide_report::{escape, timings_dir};
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+/// Suggestions listed per category
+const MAX_SUGGESTIONS: usize = 5;
+/// Rows drawn in the HTML chart; critical units are always kept
+const MAX_ROWS: usize = 400;
+const ROW_HEIGHT: f64 = 14.0;
+const CHART_WIDTH: f64 = 900.0;
+const LABEL_WIDTH: f64 = 220.0;
+/// One compilation unit from a `cargo build --timings` report
+#[derive(Debug, Clone, Serialize)]
+pub struct Unit {
+    pub index: usize,
+    pub name: String,
+    pub version: String,
+    pub target: String,
+    pub start: f64,
+    pub duration: f64,
+    pub rmeta_time: Option<f64>,
+    pub features: Vec<String>,
+    #[serde(skip)]
+    unblocked: Vec<usize>,
+    #[serde(skip)]
+    unblocked_rmeta: Vec<usize>,
+}
+impl Unit {
+    pub fn end(&self) -> f64 {
+        self.start + self.duration
+    }
+    pub fn label(&self) -> String {
+        if self.target.is_empty() {
+            format!("{} {}", self.name, self.version)
+        } else {
+            format!("{} {}{}", self.name, self.version, self.target)
+        }
+    }
+    fn key(&self) -> (String, String) {
+        (self.name.clone(), self.version.clone())
+    }
+}
+/// What `cargo build --unit-graph` knows about a package that the timings don't
+#[derive(Debug, Clone, Default)]
+pub struct PackageInfo {
+    pub kinds: HashSet<String>,
+    pub external: bool,
+    pub features: Vec<String>,
+    /// Units that depend on this package, directly or not
+    pub dependents: usize,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion {
+    pub unit: String,
+    pub seconds: f64,
+    pub reason: String,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphReport {
+    pub label: String,
+    pub wall_seconds: f64,
+    pub units: Vec<Unit>,
+    /// Indices into `units`, from the first unit of the chain to the one that finished last
+    pub critical_path: Vec<usize>,
+    /// Time a unit on the critical path was ready to start but waited for a free job slot
+    pub waiting_seconds: f64,
+    pub unit_graph: bool,
+    pub pipelining: Vec<Suggestion>,
+    pub feature_pruning: Vec<Suggestion>,
+}
+fn indices(value: &Value, keys: &[&str]) -> Vec<usize> {
+    keys.iter()
+        .find_map(|key| value[*key].as_array())
+        .map(|items| items.iter().filter_map(|i| i.as_u64().map(|i| i as usize)).collect())
+        .unwrap_or_default()
+}
+/// Units from the `UNIT_DATA` table of a timing report, indexed by their `i` field
+pub fn parse_units(html: &str) -> Option<Vec<Unit>> {
+    let start = html.find("const UNIT_DATA = ")? + "const UNIT_DATA = ".len();
+    let data: Value = serde_json::Deserializer::from_str(&html[start..]).into_iter::<Value>().next()?.ok()?;
+    let mut units: Vec<Unit> = data
+        .as_array()?
+        .iter()
+        .filter_map(|unit| {
+            Some(Unit {
+                index: unit["i"].as_u64()? as usize,
+                name: unit["name"].as_str()?.to_string(),
+                version: unit["version"].as_str().unwrap_or_default().to_string(),
+                target: unit["target"].as_str().unwrap_or_default().trim_end().to_string(),
+                start: unit["start"].as_f64()?,
+                duration: unit["duration"].as_f64()?,
+                rmeta_time: unit["rmeta_time"].as_f64(),
+                features: unit["features"]
+                    .as_array()
+                    .map(|f| f.iter().filter_map(|f| f.as_str().map(str::to_string)).collect())
+                    .unwrap_or_default(),
+                // Older cargo releases spell it "unlocked"
+                unblocked: indices(unit, &["unblocked_units", "unlocked_units"]),
+                unblocked_rmeta: indices(unit, &["unblocked_rmeta_units", "unlocked_rmeta_units"]),
+            })
+        })
+        .collect();
+    units.sort_by_key(|unit| unit.index);
+    Some(units)
+}
+/// Walks back from the unit that finished last: cargo records which finishing unit let each
+/// waiting unit start, so that chain is what gated the total build time. The flag is true
+/// when the next unit waited for full codegen rather than just metadata
+pub fn critical_path(units: &[Unit]) -> Vec<(usize, bool)> {
+    let mut unblocked_by: HashMap<usize, (usize, bool)> = HashMap::new();
+    for (pos, unit) in units.iter().enumerate() {
+        for next in &unit.unblocked {
+            unblocked_by.insert(*next, (pos, true));
+        }
+        for next in &unit.unblocked_rmeta {
+            unblocked_by.entry(*next).or_insert((pos, false));
+        }
+    }
+    let Some(last) = (0..units.len()).max_by(|a, b| units[*a].end().total_cmp(&units[*b].end())) else {
+        return Vec::new();
+    };
+    let mut path = vec![(last, false)];
+    let mut seen = HashSet::from([last]);
+    while let Some(&(previous, full)) = unblocked_by.get(&units[path.last().unwrap().0].index) {
+        if !seen.insert(previous) {
+            break;
+        }
+        path.push((previous, full));
+    }
+    path.reverse();
+    path
+}
+/// `(name, version, external)` from either the package id spec cargo prints today
+/// (`registry+https://...#serde@1.0.0`, `path+file:///x/foo#0.1.0`) or the older
+/// `serde 1.0.0 (registry+https://...)`
+pub fn parse_pkg_id(id: &str) -> Option<(String, String, bool)> {
+    if let Some((source, spec)) = id.rsplit_once('#') {
+        let external = !source.starts_with("path+");
+        return Some(match spec.split_once('@') {
+            Some((name, version)) => (name.to_string(), version.to_string(), external),
+            None => {
+                let name = source.trim_end_matches('/').rsplit('/').next()?.to_string();
+                (name, spec.to_string(), external)
+            }
+        });
+    }
+    let mut parts = id.split_whitespace();
+    let (name, version) = (parts.next()?, parts.next()?);
+    Some((name.to_string(), version.to_string(), !id.contains("(path+")))
+}
+/// Per-package kinds, source, features and dependent counts from `--unit-graph` output
+pub fn parse_unit_graph(json: &str) -> Option<HashMap<(String, String), PackageInfo>> {
+    let graph: Value = serde_json::from_str(json).ok()?;
+    let units = graph["units"].as_array()?;
+    let keys: Vec<Option<(String, String, bool)>> =
+        units.iter().map(|unit| unit["pkg_id"].as_str().and_then(parse_pkg_id)).collect();
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); units.len()];
+    for (index, unit) in units.iter().enumerate() {
+        for dependency in indices_of(&unit["dependencies"]) {
+            if let Some(list) = dependents.get_mut(dependency) {
+                list.push(index);
+            }
+        }
+    }
+    let mut packages: HashMap<(String, String), PackageInfo> = HashMap::new();
+    let mut reach: HashMap<(String, String), HashSet<usize>> = HashMap::new();
+    for (index, unit) in units.iter().enumerate() {
+        let Some((name, version, external)) = keys[index].clone() else {
+            continue;
+        };
+        let key = (name, version);
+        let info = packages.entry(key.clone()).or_default();
+        info.external = external;
+        for kind in unit["target"]["kind"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+            info.kinds.insert(kind.to_string());
+        }
+        for feature in unit["features"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+            if !info.features.iter().any(|f| f == feature) {
+                info.features.push(feature.to_string());
+            }
+        }
+        let reached = reach.entry(key).or_default();
+        let mut stack = dependents[index].clone();
+        while let Some(next) = stack.pop() {
+            if reached.insert(next) {
+                stack.extend(dependents[next].iter().copied());
+            }
+        }
+    }
+    for (key, reached) in reach {
+        if let Some(info) = packages.get_mut(&key) {
+            info.dependents =
+                reached.iter().filter(|i| keys[**i].as_ref().map(|(n, v, _)| (n, v)) != Some((&key.0, &key.1))).count();
+        }
+    }
+    Some(packages)
+}
+fn indices_of(value: &Value) -> Vec<usize> {
+    value
+        .as_array()
+        .map(|deps| deps.iter().filter_map(|d| d["index"].as_u64().map(|i| i as usize)).collect())
+        .unwrap_or_default()
+}
+fn is_build_script(unit: &Unit) -> bool {
+    ["build script", "build-script", "(run)"].iter().any(|t| unit.target.contains(t))
+}
+impl GraphReport {
+    pub fn new(label: &str, units: Vec<Unit>, packages: Option<&HashMap<(String, String), PackageInfo>>) -> Self {
+        let path = critical_path(&units);
+        let wall_seconds = units.iter().map(Unit::end).fold(0.0, f64::max);
+        let waiting_seconds = path
+            .windows(2)
+            .map(|w| {
+                let ((pos, full), (next, _)) = (w[0], w[1]);
+                let unit = &units[pos];
+                let ready = if full { unit.end() } else { unit.start + unit.rmeta_time.unwrap_or(unit.duration) };
+                (units[next].start - ready).max(0.0)
+            })
+            .sum();
+        let info = |unit: &Unit| packages.and_then(|p| p.get(&unit.key()));
+        let mut pipelining = Vec::new();
+        for window in path.windows(2) {
+            let ((pos, full), (next, _)) = (window[0], window[1]);
+            let (unit, next) = (&units[pos], &units[next]);
+            if !full {
+                continue;
+            }
+            let waited = unit.duration - unit.rmeta_time.unwrap_or(0.0);
+            let proc_macro = info(unit).is_some_and(|i| i.kinds.contains("proc-macro"));
+            let reason = if is_build_script(unit) {
+                format!("{} can't start until this build script has run; see if it can be avoided or its work cached", next.name)
+            } else if proc_macro {
+                format!("proc-macro: {} waits for its full build, not just metadata", next.name)
+            } else {
+                format!("{} needs the full artifact, so it can't be pipelined on metadata", next.label())
+            };
+            pipelining.push(Suggestion { unit: unit.label(), seconds: waited, reason });
+        }
+        pipelining.sort_by(|a, b| b.seconds.total_cmp(&a.seconds));
+        pipelining.truncate(MAX_SUGGESTIONS);
+        let mut feature_pruning: Vec<Suggestion> = path
+            .iter()
+            .map(|(pos, _)| &units[*pos])
+            .filter(|unit| !is_build_script(unit))
+            .filter(|unit| info(unit).is_none_or(|i| i.external))
+            .filter_map(|unit| {
+                let features = info(unit).map(|i| i.features.clone()).unwrap_or_else(|| unit.features.clone());
+                if features.iter().all(|f| f == "default") {
+                    return None;
+                }
+                let dependents = info(unit).map(|i| format!(", gates {} unit(s)", i.dependents)).unwrap_or_default();
+                Some(Suggestion {
+                    unit: unit.label(),
+                    seconds: unit.duration,
+                    reason: format!(
+                        "features [{}]{}; check `cargo tree -e features -i {}`",
+                        features.join(", "),
+                        dependents,
+                        unit.name
+                    ),
+                })
+            })
+            .collect();
+        feature_pruning.sort_by(|a, b| b.seconds.total_cmp(&a.seconds));
+        feature_pruning.truncate(MAX_SUGGESTIONS);
+        Self {
+            label: label.to_string(),
+            wall_seconds,
+            critical_path: path.iter().map(|(pos, _)| *pos).collect(),
+            waiting_seconds,
+            unit_graph: packages.is_some(),
+            units,
+            pipelining,
+            feature_pruning,
+        }
+    }
+    pub fn display(&self) {
+        println!(
+            "{}",
+            format!("🌊 Critical path of build {} ({:.1}s, {} units)", self.label, self.wall_seconds, self.units.len())
+                .bold()
+                .blue()
+        );
+        if self.critical_path.is_empty() {
+            println!("No units were compiled in this build; touch a file or run `cargo clean -p <crate>` first");
+            return;
+        }
+        println!("   {:>7} {:>7} {:>6}  unit", "start", "time", "share");
+        for pos in &self.critical_path {
+            let unit = &self.units[*pos];
+            let share = if self.wall_seconds > 0.0 { unit.duration * 100.0 / self.wall_seconds } else { 0.0 };
+            println!("   {:>6.1}s {:>6.1}s {:>5.0}%  {}", unit.start, unit.duration, share, unit.label());
+        }
+        println!(
+            "\n⛓️  {} unit(s) gate the build: {:.1}s compiling, {:.1}s waiting for a free job slot",
+            self.critical_path.len(),
+            self.wall_seconds - self.waiting_seconds,
+            self.waiting_seconds
+        );
+        if !self.pipelining.is_empty() {
+            println!("\n{}", "⚡ Pipelining".bold());
+            for suggestion in &self.pipelining {
+                println!("   {:>6.1}s {} - {}", suggestion.seconds, suggestion.unit.yellow(), suggestion.reason);
+            }
+        }
+        if !self.feature_pruning.is_empty() {
+            println!("\n{}", "✂️  Feature pruning candidates".bold());
+            for suggestion in &self.feature_pruning {
+                println!("   {:>6.1}s {} - {}", suggestion.seconds, suggestion.unit.yellow(), suggestion.reason);
+            }
+        }
+        if !self.unit_graph {
+            println!("\n💡 cargo's unit graph wasn't available; proc-macros and workspace crates could not be told apart");
+        }
+    }
+}
+/// Timing reports in `dir`, oldest first
+pub fn timing_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with("cargo-timing") && n.ends_with(".html"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    // cargo-timing.html is a copy of the newest timestamped report
+    if files.len() > 1 {
+        files.retain(|path| path.file_name().is_some_and(|n| n != "cargo-timing.html"));
+    }
+    files
+}
+/// `cargo build --unit-graph` is unstable; RUSTC_BOOTSTRAP lets a stable cargo print it
+fn unit_graph(cargo_args: &[String]) -> Option<HashMap<(String, String), PackageInfo>> {
+    let mut command = Command::new("cargo");
+    command
+        .args(["build", "--unit-graph", "-Z", "unstable-options"])
+        .args(cargo_args)
+        .env("RUSTC_BOOTSTRAP", "1");
+    crate::net::NetworkSettings::load().apply_env(&mut command);
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_unit_graph(&String::from_utf8_lossy(&output.stdout))
+}
+pub fn handle_graph(html: Option<PathBuf>, build: bool, json: bool, cargo_args: &[String]) -> Result<()> {
+    if build {
+        let mut command = Command::new("cargo");
+        command.args(["build", "--timings"]).args(cargo_args);
+        crate::net::NetworkSettings::load().apply_env(&mut command);
+        let status = command.status().context("Failed to run cargo build --timings")?;
+        if !status.success() {
+            bail!("cargo build --timings failed");
+        }
+    }
+    let dir = timings_dir();
+    let Some(file) = timing_files(&dir).pop() else {
+        bail!("No cargo timing reports in {}; run `cm tide graph --build` or `cargo build --timings`", dir.display());
+    };
+    let units = parse_units(&fs::read_to_string(&file)?)
+        .with_context(|| format!("{} has no unit data", file.display()))?;
+    let label = file
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.strip_prefix("cargo-timing-"))
+        .unwrap_or("latest")
+        .to_string();
+    let report = GraphReport::new(&label, units, unit_graph(cargo_args).as_ref());
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        report.display();
+    }
+    if let Some(path) = html {
+        fs::write(&path, render_html(&report))?;
+        println!("✅ Build graph written to {}", path.display());
+    }
+    Ok(())
+}
+fn gantt(report: &GraphReport) -> String {
+    let critical: HashSet<usize> = report.critical_path.iter().copied().collect();
+    let mut rows: Vec<usize> = (0..report.units.len()).collect();
+    rows.sort_by(|a, b| report.units[*a].start.total_cmp(&report.units[*b].start));
+    if rows.len() > MAX_ROWS {
+        let mut longest = rows.clone();
+        longest.sort_by(|a, b| report.units[*b].duration.total_cmp(&report.units[*a].duration));
+        let kept: HashSet<usize> = longest.into_iter().take(MAX_ROWS).chain(critical.iter().copied()).collect();
+        rows.retain(|row| kept.contains(row));
+    }
+    let scale = (CHART_WIDTH - LABEL_WIDTH - 10.0) / report.wall_seconds.max(0.001);
+    let height = rows.len() as f64 * ROW_HEIGHT + 30.0;
+    let mut svg = format!(
+        r#"<svg class="chart" viewBox="0 0 {CHART_WIDTH} {height}" xmlns="http://www.w3.org/2000/svg">"#
+    );
+    for (row, pos) in rows.iter().enumerate() {
+        let unit = &report.units[*pos];
+        let y = row as f64 * ROW_HEIGHT + 4.0;
+        let x = LABEL_WIDTH + unit.start * scale;
+        let class = if critical.contains(pos) { "unit critical" } else { "unit" };
+        let label = escape(&unit.label());
+        let rmeta = unit.rmeta_time.map(|t| format!(", metadata after {:.2}s", t)).unwrap_or_default();
+        svg.push_str(&format!(
+            r#"<g class="{class}" data-name="{name}" data-tip="{label}: {start:.2}s + {duration:.2}s{rmeta}"><text class="label" x="{lx}" y="{ty:.1}" text-anchor="end">{label}</text><rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h}"/>"#,
+            name = escape(&unit.name),
+            start = unit.start,
+            duration = unit.duration,
+            lx = LABEL_WIDTH - 6.0,
+            ty = y + ROW_HEIGHT - 5.0,
+            w = (unit.duration * scale).max(1.0),
+            h = ROW_HEIGHT - 3.0,
+        ));
+        if let Some(rmeta_time) = unit.rmeta_time.filter(|t| *t < unit.duration) {
+            svg.push_str(&format!(
+                r#"<rect class="codegen" x="{:.1}" y="{:.1}" width="{:.1}" height="{}"/>"#,
+                x + rmeta_time * scale,
+                y,
+                (unit.duration - rmeta_time) * scale,
+                ROW_HEIGHT - 3.0
+            ));
+        }
+        svg.push_str("</g>");
+    }
+    let axis_y = rows.len() as f64 * ROW_HEIGHT + 18.0;
+    for step in 0..=4 {
+        let seconds = report.wall_seconds * step as f64 / 4.0;
+        svg.push_str(&format!(
+            r#"<text class="label" x="{:.1}" y="{:.1}" text-anchor="middle">{:.1}s</text>"#,
+            LABEL_WIDTH + seconds * scale,
+            axis_y,
+            seconds
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+fn suggestion_table(suggestions: &[Suggestion], empty: &str) -> String {
+    if suggestions.is_empty() {
+        return format!("<p class=\"empty\">{}</p>", empty);
+    }
+    let rows: String = suggestions
+        .iter()
+        .map(|s| format!("<tr><td>{}</td><td>{:.1}s</td><td>{}</td></tr>", escape(&s.unit), s.seconds, escape(&s.reason)))
+        .collect();
+    format!("<table><thead><tr><th>Unit</th><th>Time</th><th>Why</th></tr></thead><tbody>{}</tbody></table>", rows)
+}
+pub fn render_html(report: &GraphReport) -> String {
+    let project = crate::project::current_project().map(|p| p.name).unwrap_or_else(|| "cargo-mate".to_string());
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Build graph - {project}</title>
+<style>
+body {{ font-family: -apple-system, "Segoe UI", sans-serif; margin: 2rem auto; max-width: 960px; color: #1f2933; }}
+h1 {{ font-size: 1.5rem; }} h2 {{ font-size: 1.1rem; margin-top: 2rem; }}
+.chart {{ width: 100%; height: auto; }} .label {{ font-size: 10px; fill: #616e7c; }}
+.unit rect {{ fill: #4e79a7; }} .unit rect.codegen {{ fill: #a0cbe8; }}
+.unit.critical rect {{ fill: #e15759; }} .unit.critical rect.codegen {{ fill: #ff9da7; }} .unit.critical .label {{ fill: #c0392b; font-weight: bold; }}
+.unit.dim {{ opacity: .15; }} .only-critical .unit:not(.critical) {{ display: none; }}
+table {{ border-collapse: collapse; width: 100%; font-size: .9rem; }} td, th {{ border-bottom: 1px solid #e4e7eb; padding: 4px 8px; text-align: left; }}
+.empty {{ color: #7b8794; }}
+#tip {{ position: fixed; pointer-events: none; background: #1f2933; color: #fff; padding: 4px 8px; border-radius: 4px; font-size: .8rem; display: none; }}
+</style>
+</head>
+<body>
+<h1>🌊 Build graph - {project}</h1>
+<p>Build {label}: {wall:.1}s wall time, {count} units. The {chain_len} red units are the critical path: {chain:.1}s compiling and {waiting:.1}s waiting for a free job slot. Lighter segments are codegen after metadata was ready.</p>
+<p><input id="filter" placeholder="Highlight crates…"> <label><input id="critical" type="checkbox"> Critical path only</label></p>
+<div id="graph">{gantt}</div>
+<h2>⚡ Pipelining</h2>
+{pipelining}
+<h2>✂️ Feature pruning candidates</h2>
+{pruning}
+<div id="tip"></div>
+<script>
+const tip = document.getElementById("tip");
+const units = document.querySelectorAll(".unit");
+units.forEach(el => {{
+  el.addEventListener("mousemove", e => {{ tip.textContent = el.dataset.tip; tip.style.display = "block"; tip.style.left = (e.clientX + 12) + "px"; tip.style.top = (e.clientY + 12) + "px"; }});
+  el.addEventListener("mouseleave", () => {{ tip.style.display = "none"; }});
+}});
+document.getElementById("filter").addEventListener("input", e => {{
+  const needle = e.target.value.trim().toLowerCase();
+  units.forEach(el => el.classList.toggle("dim", needle !== "" && !el.dataset.name.toLowerCase().includes(needle)));
+}});
+document.getElementById("critical").addEventListener("change", e => {{
+  document.getElementById("graph").classList.toggle("only-critical", e.target.checked);
+}});
+</script>
+</body>
+</html>
+"#,
+        project = escape(&project),
+        label = escape(&report.label),
+        wall = report.wall_seconds,
+        count = report.units.len(),
+        chain_len = report.critical_path.len(),
+        chain = report.wall_seconds - report.waiting_seconds,
+        waiting = report.waiting_seconds,
+        gantt = gantt(report),
+        pipelining = suggestion_table(&report.pipelining, "Nothing on the critical path waits for a full build."),
+        pruning = suggestion_table(&report.feature_pruning, "No dependency with non-default features gates the build."),
+    )
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const TIMINGS: &str = r#"<script>
+const UNIT_DATA = [
+  {"i": 0, "name": "proc-macro2", "version": "1.0.86", "mode": "todo", "target": " build script", "features": [], "start": 0.0, "duration": 0.5, "unblocked_units": [1], "unblocked_rmeta_units": [], "sections": null},
+  {"i": 1, "name": "proc-macro2", "version": "1.0.86", "mode": "todo", "target": " (run)", "features": [], "start": 0.5, "duration": 0.2, "unblocked_units": [2], "unblocked_rmeta_units": [], "sections": null},
+  {"i": 2, "name": "proc-macro2", "version": "1.0.86", "mode": "todo", "target": "", "features": ["proc-macro"], "start": 0.7, "duration": 2.0, "rmeta_time": 1.0, "unblocked_units": [], "unblocked_rmeta_units": [3], "sections": null},
+  {"i": 3, "name": "serde_derive", "version": "1.0.200", "mode": "todo", "target": "", "features": ["default"], "start": 1.8, "duration": 4.0, "unblocked_units": [4], "unblocked_rmeta_units": [], "sections": null},
+  {"i": 4, "name": "app", "version": "0.1.0", "mode": "todo", "target": " app \"bin\"", "features": [], "start": 6.0, "duration": 1.5, "unblocked_units": [], "unblocked_rmeta_units": [], "sections": null},
+  {"i": 5, "name": "log", "version": "0.4.22", "mode": "todo", "target": "", "features": ["std"], "start": 0.0, "duration": 0.3, "unblocked_units": [], "unblocked_rmeta_units": [], "sections": null}
+];
+const CONCURRENCY_DATA = [];
+</script>"#;
+    #[test]
+    fn test_critical_path_and_suggestions() {
+        let units = parse_units(TIMINGS).unwrap();
+        assert_eq!(units.len(), 6);
+        let path = critical_path(&units);
+        assert_eq!(path.iter().map(|(pos, _)| *pos).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        assert!(path[0].1 && !path[2].1 && path[3].1);
+        let report = GraphReport::new("test", units, None);
+        assert_eq!(report.wall_seconds, 7.5);
+        assert!((report.waiting_seconds - 0.3).abs() < 1e-9);
+        assert_eq!(report.pipelining[0].unit, "serde_derive 1.0.200");
+        assert!(report.pipelining.iter().any(|s| s.unit == "proc-macro2 1.0.86 build script"));
+        assert_eq!(report.feature_pruning.len(), 1);
+        assert_eq!(report.feature_pruning[0].unit, "proc-macro2 1.0.86");
+        let html = render_html(&report);
+        assert_eq!(html.matches("unit critical").count(), 5);
+    }
+    #[test]
+    fn test_unit_graph() {
+        assert_eq!(
+            parse_pkg_id("registry+https://github.com/rust-lang/crates.io-index#serde@1.0.200"),
+            Some(("serde".into(), "1.0.200".into(), true))
+        );
+        assert_eq!(parse_pkg_id("path+file:///work/app#0.1.0"), Some(("app".into(), "0.1.0".into(), false)));
+        assert_eq!(parse_pkg_id("log 0.4.22 (registry+https://x)"), Some(("log".into(), "0.4.22".into(), true)));
+        let graph = r#"{"version":1,"units":[
+            {"pkg_id":"registry+https://x#serde_derive@1.0.200","target":{"kind":["proc-macro"]},"features":["default"],"dependencies":[]},
+            {"pkg_id":"registry+https://x#serde@1.0.200","target":{"kind":["lib"]},"features":["derive","std"],"dependencies":[{"index":0}]},
+            {"pkg_id":"path+file:///work/app#0.1.0","target":{"kind":["bin"]},"features":[],"dependencies":[{"index":1}]}
+        ],"roots":[2]}"#;
+        let packages = parse_unit_graph(graph).unwrap();
+        let derive = &packages[&("serde_derive".to_string(), "1.0.200".to_string())];
+        assert!(derive.kinds.contains("proc-macro") && derive.external);
+        assert_eq!(derive.dependents, 2);
+        assert!(!packages[&("app".to_string(), "0.1.0".to_string())].external);
+    }
+}
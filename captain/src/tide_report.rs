@@ -0,0 +1,346 @@
+use crate::tide::{self, BuildMetrics};
+use chrono::Utc;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+const MAX_BUILDS: usize = 500;
+const MAX_RUNS: usize = 20;
+const TOP_CRATES: usize = 8;
+const CHART_WIDTH: f64 = 900.0;
+const CHART_HEIGHT: f64 = 260.0;
+const MARGIN: f64 = 40.0;
+const PALETTE: [&str; TOP_CRATES + 1] = [
+    "#4e79a7", "#f28e2b", "#59a14f", "#e15759", "#76b7b2", "#edc948", "#b07aa1",
+    "#ff9da7", "#bab0ac",
+];
+#[derive(Debug, Clone)]
+pub struct TimingRun {
+    pub label: String,
+    pub crates: BTreeMap<String, f64>,
+}
+pub fn timings_dir() -> PathBuf {
+    let target = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .or_else(|| {
+            crate::project::current_project()
+                .and_then(|p| p.manifest.parent().map(|root| root.join("target")))
+        })
+        .unwrap_or_else(|| PathBuf::from("target"));
+    target.join("cargo-timings")
+}
+pub fn parse_timing_html(label: &str, html: &str) -> Option<TimingRun> {
+    let start = html.find("const UNIT_DATA = ")? + "const UNIT_DATA = ".len();
+    let units: Value = serde_json::Deserializer::from_str(&html[start..])
+        .into_iter::<Value>()
+        .next()?
+        .ok()?;
+    let mut crates = BTreeMap::new();
+    for unit in units.as_array()? {
+        let (Some(name), Some(duration)) = (
+            unit["name"].as_str(),
+            unit["duration"].as_f64(),
+        ) else {
+            continue;
+        };
+        *crates.entry(name.to_string()).or_insert(0.0) += duration;
+    }
+    Some(TimingRun {
+        label: label.to_string(),
+        crates,
+    })
+}
+pub fn read_timing_runs(dir: &Path) -> Vec<TimingRun> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("cargo-timing-") && n.ends_with(".html"))
+        })
+        .collect();
+    files.sort();
+    if files.is_empty() && dir.join("cargo-timing.html").exists() {
+        files.push(dir.join("cargo-timing.html"));
+    }
+    let skip = files.len().saturating_sub(MAX_RUNS);
+    files
+        .iter()
+        .skip(skip)
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?;
+            let label = name.strip_prefix("cargo-timing-").unwrap_or("latest");
+            parse_timing_html(label, &fs::read_to_string(path).ok()?)
+        })
+        .collect()
+}
+pub fn regressions(builds: &[BuildMetrics]) -> Vec<usize> {
+    let durations: Vec<f64> = builds.iter().map(|b| b.duration_seconds).collect();
+    (1..durations.len())
+        .filter(|&i| tide::is_regression(&durations[..i], durations[i]))
+        .collect()
+}
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+fn duration_chart(builds: &[BuildMetrics], regressed: &[usize]) -> String {
+    if builds.is_empty() {
+        return "<p class=\"empty\">No builds recorded yet.</p>".to_string();
+    }
+    let max = builds.iter().map(|b| b.duration_seconds).fold(1.0, f64::max);
+    let step = (CHART_WIDTH - 2.0 * MARGIN) / (builds.len().max(2) - 1) as f64;
+    let point = |i: usize, seconds: f64| {
+        (
+            MARGIN + step * i as f64,
+            CHART_HEIGHT - MARGIN - (seconds / max) * (CHART_HEIGHT - 2.0 * MARGIN),
+        )
+    };
+    let path: Vec<String> = builds
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            let (x, y) = point(i, b.duration_seconds);
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+    let mut svg = format!(
+        "<svg viewBox=\"0 0 {w} {h}\" class=\"chart\">\
+         <line x1=\"{m}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" class=\"axis\"/>\
+         <line x1=\"{m}\" y1=\"{m}\" x2=\"{m}\" y2=\"{b}\" class=\"axis\"/>\
+         <text x=\"4\" y=\"{m}\" class=\"label\">{max:.0}s</text>\
+         <text x=\"4\" y=\"{b}\" class=\"label\">0s</text>\
+         <polyline points=\"{points}\" class=\"line\"/>",
+        w = CHART_WIDTH, h = CHART_HEIGHT, m = MARGIN, b = CHART_HEIGHT - MARGIN, r =
+        CHART_WIDTH - MARGIN, max = max, points = path.join(" ")
+    );
+    for (i, build) in builds.iter().enumerate() {
+        let (x, y) = point(i, build.duration_seconds);
+        let class = if regressed.contains(&i) {
+            "regression"
+        } else if build.success {
+            "ok"
+        } else {
+            "failed"
+        };
+        let tip = format!(
+            "{} · cargo {} · {:.1}s{}", build.timestamp.format("%Y-%m-%d %H:%M"), build
+            .command, build.duration_seconds, if regressed.contains(& i) {
+            " · regression" } else { "" }
+        );
+        svg.push_str(
+            &format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" class=\"{}\" data-tip=\"{}\"/>",
+                x, y, if class == "regression" { 6 } else { 3 }, class, escape(&tip)
+            ),
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+fn crate_chart(runs: &[TimingRun]) -> String {
+    if runs.is_empty() {
+        return "<p class=\"empty\">No per-crate timings found. Run <code>cargo build --timings</code> (or <code>cm tide analyze</code>) to collect them.</p>"
+            .to_string();
+    }
+    let mut totals: BTreeMap<&str, f64> = BTreeMap::new();
+    for run in runs {
+        for (name, seconds) in &run.crates {
+            *totals.entry(name.as_str()).or_insert(0.0) += seconds;
+        }
+    }
+    let mut ranked: Vec<(&str, f64)> = totals.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let top: Vec<&str> = ranked.iter().take(TOP_CRATES).map(|(name, _)| *name).collect();
+    let series = |run: &TimingRun| -> Vec<(String, f64)> {
+        let mut values: Vec<(String, f64)> = top
+            .iter()
+            .map(|name| (name.to_string(), run.crates.get(*name).copied().unwrap_or(0.0)))
+            .collect();
+        let other: f64 = run
+            .crates
+            .iter()
+            .filter(|(name, _)| !top.contains(&name.as_str()))
+            .map(|(_, seconds)| seconds)
+            .sum();
+        values.push(("other".to_string(), other));
+        values
+    };
+    let max = runs
+        .iter()
+        .map(|run| run.crates.values().sum::<f64>())
+        .fold(1.0, f64::max);
+    let slot = (CHART_WIDTH - 2.0 * MARGIN) / runs.len() as f64;
+    let bar = (slot * 0.7).min(60.0);
+    let mut svg = format!(
+        "<svg viewBox=\"0 0 {w} {h}\" class=\"chart\">\
+         <line x1=\"{m}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" class=\"axis\"/>\
+         <text x=\"4\" y=\"{m}\" class=\"label\">{max:.0}s</text>",
+        w = CHART_WIDTH, h = CHART_HEIGHT, m = MARGIN, b = CHART_HEIGHT - MARGIN, r =
+        CHART_WIDTH - MARGIN, max = max
+    );
+    for (i, run) in runs.iter().enumerate() {
+        let x = MARGIN + slot * i as f64 + (slot - bar) / 2.0;
+        let mut y = CHART_HEIGHT - MARGIN;
+        for (index, (name, seconds)) in series(run).into_iter().enumerate() {
+            let height = seconds / max * (CHART_HEIGHT - 2.0 * MARGIN);
+            y -= height;
+            svg.push_str(
+                &format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\" data-crate=\"{}\" data-tip=\"{}\"/>",
+                    x, y, bar, height, PALETTE[index], escape(& name), escape(&
+                    format!("{} · {} · {:.2}s", run.label, name, seconds))
+                ),
+            );
+        }
+    }
+    svg.push_str("</svg><div class=\"legend\">");
+    for (index, name) in top.iter().map(|n| n.to_string()).chain(["other".to_string()]).enumerate() {
+        svg.push_str(
+            &format!(
+                "<span data-toggle=\"{}\"><i style=\"background:{}\"></i>{}</span>",
+                escape(& name), PALETTE[index], escape(& name)
+            ),
+        );
+    }
+    svg.push_str("</div>");
+    svg
+}
+fn regression_table(builds: &[BuildMetrics], regressed: &[usize]) -> String {
+    if regressed.is_empty() {
+        return "<p class=\"empty\">No regressions detected.</p>".to_string();
+    }
+    let durations: Vec<f64> = builds.iter().map(|b| b.duration_seconds).collect();
+    let mut rows = String::new();
+    for &i in regressed.iter().rev() {
+        let baseline = tide::regression_baseline(&durations[..i]).unwrap_or(0.0);
+        rows.push_str(
+            &format!(
+                "<tr><td>{}</td><td><code>cargo {}</code></td><td>{:.1}s</td><td>{:.1}s</td><td>×{:.1}</td></tr>",
+                builds[i].timestamp.format("%Y-%m-%d %H:%M"), escape(& builds[i]
+                .command), builds[i].duration_seconds, baseline, builds[i]
+                .duration_seconds / baseline.max(0.001)
+            ),
+        );
+    }
+    format!(
+        "<table><thead><tr><th>When</th><th>Command</th><th>Duration</th><th>Baseline</th><th>Slowdown</th></tr></thead><tbody>{}</tbody></table>",
+        rows
+    )
+}
+pub fn render_html(builds: &[BuildMetrics], runs: &[TimingRun]) -> String {
+    let builds = &builds[builds.len().saturating_sub(MAX_BUILDS)..];
+    let regressed = regressions(builds);
+    let successful = builds.iter().filter(|b| b.success).count();
+    let mean = if builds.is_empty() {
+        0.0
+    } else {
+        builds.iter().map(|b| b.duration_seconds).sum::<f64>() / builds.len() as f64
+    };
+    let project = crate::project::current_project()
+        .map(|p| p.name)
+        .unwrap_or_else(|| "cargo-mate".to_string());
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Tide report - {project}</title>
+<style>
+body {{ font-family: -apple-system, "Segoe UI", sans-serif; margin: 2rem auto; max-width: 960px; color: #1f2933; }}
+h1 {{ font-size: 1.5rem; }} h2 {{ font-size: 1.1rem; margin-top: 2rem; }}
+.cards {{ display: flex; gap: 1rem; }} .card {{ flex: 1; background: #f5f7fa; border-radius: 6px; padding: .75rem; }}
+.card b {{ display: block; font-size: 1.4rem; }}
+.chart {{ width: 100%; height: auto; }} .axis {{ stroke: #9aa5b1; }} .label {{ font-size: 11px; fill: #616e7c; }}
+.line {{ fill: none; stroke: #4e79a7; stroke-width: 1.5; }}
+circle.ok {{ fill: #59a14f; }} circle.failed {{ fill: #e15759; }} circle.regression {{ fill: #f28e2b; stroke: #c0392b; stroke-width: 2; }}
+.legend span {{ cursor: pointer; margin-right: 1rem; font-size: .85rem; }} .legend span.off {{ opacity: .35; }}
+.legend i {{ display: inline-block; width: 10px; height: 10px; margin-right: 4px; }}
+table {{ border-collapse: collapse; width: 100%; font-size: .9rem; }} td, th {{ border-bottom: 1px solid #e4e7eb; padding: 4px 8px; text-align: left; }}
+.empty {{ color: #7b8794; }}
+#tip {{ position: fixed; pointer-events: none; background: #1f2933; color: #fff; padding: 4px 8px; border-radius: 4px; font-size: .8rem; display: none; }}
+</style>
+</head>
+<body>
+<h1>🌊 Tide report - {project}</h1>
+<p>Generated {generated} from {count} build(s).</p>
+<div class="cards">
+<div class="card"><b>{count}</b>builds</div>
+<div class="card"><b>{rate:.0}%</b>successful</div>
+<div class="card"><b>{mean:.1}s</b>mean duration</div>
+<div class="card"><b>{regression_count}</b>regressions</div>
+</div>
+<h2>Build duration over time</h2>
+{duration_chart}
+<h2>Per-crate compile time</h2>
+{crate_chart}
+<h2>Regressions</h2>
+<p>A build is a regression when it takes more than {factor}× the median of the previous {window} builds.</p>
+{regression_table}
+<div id="tip"></div>
+<script>
+const tip = document.getElementById("tip");
+document.querySelectorAll("[data-tip]").forEach(el => {{
+  el.addEventListener("mousemove", e => {{ tip.textContent = el.dataset.tip; tip.style.display = "block"; tip.style.left = (e.clientX + 12) + "px"; tip.style.top = (e.clientY + 12) + "px"; }});
+  el.addEventListener("mouseleave", () => {{ tip.style.display = "none"; }});
+}});
+document.querySelectorAll("[data-toggle]").forEach(el => {{
+  el.addEventListener("click", () => {{
+    el.classList.toggle("off");
+    document.querySelectorAll('rect[data-crate="' + CSS.escape(el.dataset.toggle) + '"]').forEach(r => {{ r.style.visibility = el.classList.contains("off") ? "hidden" : "visible"; }});
+  }});
+}});
+</script>
+</body>
+</html>
+"#,
+        project = escape(& project), generated = Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        count = builds.len(), rate = if builds.is_empty() { 0.0 } else { successful as
+        f64 * 100.0 / builds.len() as f64 }, mean = mean, regression_count = regressed
+        .len(), duration_chart = duration_chart(builds, & regressed), crate_chart =
+        crate_chart(runs), factor = tide::REGRESSION_FACTOR, window =
+        tide::REGRESSION_WINDOW, regression_table = regression_table(builds, &
+        regressed)
+    )
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn build(seconds: f64, success: bool) -> BuildMetrics {
+        BuildMetrics {
+            timestamp: Utc::now(),
+            command: "build --release".to_string(),
+            duration_seconds: seconds,
+            success,
+            error_count: 0,
+            warning_count: 0,
+            incremental: true,
+            profile: "release".to_string(),
+            features: Vec::new(),
+            dependencies_compiled: 0,
+            crate_units_compiled: 0,
+            memory_peak_mb: None,
+            cpu_usage_percent: None,
+            environment: Default::default(),
+        }
+    }
+    #[test]
+    fn renders_regressions_and_crate_timings() {
+        let builds = vec![
+            build(10.0, true), build(11.0, true), build(9.0, false), build(30.0, true),
+        ];
+        assert_eq!(regressions(& builds), vec![3]);
+        let html = "<script>const UNIT_DATA = [{\"name\":\"serde\",\"duration\":1.5},{\"name\":\"serde\",\"duration\":0.5},{\"name\":\"tokio\",\"duration\":3.0}];\nconst CONCURRENCY_DATA = [];</script>";
+        let run = parse_timing_html("20261017T120000Z", html).unwrap();
+        assert_eq!(run.crates["serde"], 2.0);
+        let report = render_html(&builds, &[run]);
+        assert!(report.contains("class=\"regression\""));
+        assert!(report.contains("data-crate=\"tokio\""));
+        assert!(! report.contains("cdn"));
+    }
+}
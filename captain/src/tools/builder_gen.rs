@@ -20,6 +20,7 @@ struct StructInfo {
 struct FieldInfo {
     name: String,
     ty: String,
+    setter_ty: String,
     is_optional: bool,
     has_default: bool,
     validation_rules: Vec<String>,
@@ -63,34 +64,59 @@ impl BuilderGenTool {
         let name = field.ident.as_ref()?.to_string();
         let ty = self.type_to_string(&field.ty);
         let is_optional = self.is_optional_type(&field.ty);
+        let setter_ty = self
+            .option_inner_type(&field.ty)
+            .map(|inner| self.type_to_string(inner))
+            .unwrap_or_else(|| ty.clone());
         let has_default = self.has_default_value(field);
         let validation_rules = self.extract_validation_rules(field);
         Some(FieldInfo {
             name,
             ty,
+            setter_ty,
             is_optional,
             has_default,
             validation_rules,
         })
     }
     fn type_to_string(&self, ty: &Type) -> String {
-        match ty {
-            Type::Path(type_path) => {
-                let mut segments = Vec::new();
-                for segment in &type_path.path.segments {
-                    segments.push(segment.ident.to_string());
-                }
-                segments.join("::")
+        let tokens = quote!(# ty).to_string();
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let chars: Vec<char> = tokens.chars().collect();
+        let mut result = String::new();
+        for (index, c) in chars.iter().enumerate() {
+            if *c != ' ' {
+                result.push(*c);
+                continue;
             }
-            Type::Reference(type_ref) => {
-                let mut result = "&".to_string();
-                if type_ref.mutability.is_some() {
-                    result.push_str("mut ");
-                }
-                result.push_str(&self.type_to_string(&*type_ref.elem));
-                result
+            let previous = result.chars().last();
+            let next = chars.get(index + 1).copied();
+            if previous == Some(',')
+                || (previous.is_some_and(is_word) && next.is_some_and(is_word))
+            {
+                result.push(' ');
+            }
+        }
+        result
+    }
+    fn option_inner_type<'a>(&self, ty: &'a Type) -> Option<&'a Type> {
+        let Type::Path(type_path) = ty else {
+            return None;
+        };
+        let segment = type_path.path.segments.last()?;
+        if segment.ident != "Option" {
+            return None;
+        }
+        match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => {
+                args.args
+                    .iter()
+                    .find_map(|arg| match arg {
+                        syn::GenericArgument::Type(inner) => Some(inner),
+                        _ => None,
+                    })
             }
-            _ => "Unknown".to_string(),
+            _ => None,
         }
     }
     fn is_optional_type(&self, ty: &Type) -> bool {
@@ -178,14 +204,75 @@ impl BuilderGenTool {
             _ => "unknown".to_string(),
         }
     }
+    fn generate_builder_docs(
+        &self,
+        struct_info: &StructInfo,
+        method_prefix: &str,
+        style: &str,
+    ) -> String {
+        let required: Vec<String> = struct_info
+            .fields
+            .iter()
+            .filter(|f| self.is_required(f))
+            .map(|f| format!("`{}`", f.name))
+            .collect();
+        let mut docs = format!("/// Builder for [`{}`].\n///\n", struct_info.name);
+        if required.is_empty() {
+            docs.push_str("/// All fields are optional.\n");
+        } else if style == "typestate" {
+            docs.push_str(
+                &format!(
+                    "/// Required fields: {}. `build()` only exists once every required field is set,\n/// so a missing field is a compile-time error.\n",
+                    required.join(", ")
+                ),
+            );
+        } else {
+            docs.push_str(
+                &format!(
+                    "/// Required fields: {}. Missing fields are reported by `build()` as\n/// [`BuilderError::MissingField`].\n",
+                    required.join(", ")
+                ),
+            );
+        }
+        docs.push_str("///\n/// ```ignore\n");
+        for line in self.example_chain(struct_info, method_prefix, style).lines() {
+            docs.push_str(&format!("/// {}\n", line));
+        }
+        docs.push_str("/// ```\n");
+        docs
+    }
+    fn example_chain(
+        &self,
+        struct_info: &StructInfo,
+        method_prefix: &str,
+        style: &str,
+    ) -> String {
+        let mut chain = format!("let value = {}::builder()\n", struct_info.name);
+        for field in struct_info.fields.iter().filter(|f| self.is_required(f)) {
+            chain.push_str(
+                &format!(
+                    "    .{}({})\n", self.setter_name(method_prefix, & field.name),
+                    self.sample_value_for_type(& field.setter_ty)
+                ),
+            );
+        }
+        if style == "typestate" && !self.has_validation(struct_info) {
+            chain.push_str("    .build();\n");
+        } else {
+            chain.push_str("    .build()\n    .expect(\"all required fields are set\");\n");
+        }
+        chain
+    }
     fn generate_builder_struct(
         &self,
         struct_info: &StructInfo,
         derive_attrs: &str,
+        method_prefix: &str,
     ) -> Result<String> {
         let struct_name = &struct_info.name;
         let builder_name = format!("{}Builder", struct_name);
         let mut code = format!("// Builder for {}\n", struct_name);
+        code.push_str(&self.generate_builder_docs(struct_info, method_prefix, "runtime"));
         if !derive_attrs.is_empty() {
             code.push_str(&format!("#[derive({})]\n", derive_attrs));
         } else {
@@ -210,38 +297,33 @@ impl BuilderGenTool {
     ) -> Result<String> {
         let struct_name = &struct_info.name;
         let builder_name = format!("{}Builder", struct_name);
-        let mut code = format!("impl {} {{\n", builder_name);
+        let mut code = format!("impl {} {{\n", struct_name);
+        code.push_str(&format!("    pub fn builder() -> {} {{\n", builder_name));
+        code.push_str(&format!("        {}::new()\n", builder_name));
+        code.push_str("    }\n");
+        code.push_str("}\n\n");
+        code.push_str(&format!("impl {} {{\n", builder_name));
         code.push_str("    pub fn new() -> Self {\n");
         code.push_str(&format!("        {} {{\n", builder_name));
         for field in &struct_info.fields {
-            let default_value = if field.has_default {
-                self.get_default_value_for_type(&field.ty)
-            } else if field.is_optional {
-                "None".to_string()
+            let default_value = if field.has_default && !field.is_optional {
+                format!("Some({})", self.get_default_value_for_type(&field.ty))
             } else {
-                format!("None::<{}>", field.ty)
+                "None".to_string()
             };
             code.push_str(&format!("            {}: {},\n", field.name, default_value));
         }
         code.push_str("        }\n");
         code.push_str("    }\n\n");
         for field in &struct_info.fields {
-            let method_name = format!("{}{}", method_prefix, field.name);
-            let param_type = field.ty.clone();
-            let field_assignment = if field.is_optional {
-                format!("Some({})", field.name)
-            } else {
-                field.name.clone()
-            };
+            let method_name = self.setter_name(method_prefix, &field.name);
             code.push_str(
                 &format!(
                     "    pub fn {}(mut self, {}: {}) -> Self {{\n", method_name, field
-                    .name, param_type
+                    .name, field.setter_ty
                 ),
             );
-            code.push_str(
-                &format!("        self.{} = {};\n", field.name, field_assignment),
-            );
+            code.push_str(&format!("        self.{} = Some({});\n", field.name, field.name));
             code.push_str("        self\n");
             code.push_str("    }\n\n");
         }
@@ -249,7 +331,7 @@ impl BuilderGenTool {
         code.push_str(struct_name);
         code.push_str(", BuilderError> {\n");
         for field in &struct_info.fields {
-            if !field.is_optional && !field.has_default {
+            if self.is_required(field) {
                 code.push_str(
                     &format!(
                         "        let {} = self.{}.ok_or(BuilderError::MissingField(\"{}\"))?;\n",
@@ -283,7 +365,234 @@ impl BuilderGenTool {
         code.push_str("}\n\n");
         Ok(code)
     }
-    fn generate_validation_code(&self, struct_info: &StructInfo) -> Result<String> {
+    fn generate_typestate_markers(&self) -> String {
+        let mut code = "// Typestate markers shared by generated builders\n\n".to_string();
+        code.push_str("/// Marks a required builder field that has not been set yet.\n");
+        code.push_str("#[derive(Debug, Clone, Copy, Default)]\n");
+        code.push_str("pub struct Missing;\n\n");
+        code.push_str("/// Marks a required builder field that has been set.\n");
+        code.push_str("#[derive(Debug, Clone)]\n");
+        code.push_str("pub struct Set<T>(pub T);\n\n");
+        code
+    }
+    fn generate_typestate_builder(
+        &self,
+        struct_info: &StructInfo,
+        derive_attrs: &str,
+        method_prefix: &str,
+    ) -> Result<String> {
+        let struct_name = &struct_info.name;
+        let builder_name = format!("{}Builder", struct_name);
+        let required: Vec<&FieldInfo> = struct_info
+            .fields
+            .iter()
+            .filter(|f| self.is_required(f))
+            .collect();
+        let params: Vec<String> = required
+            .iter()
+            .map(|f| format!("{}State", self.to_pascal_case(&f.name)))
+            .collect();
+        let state_of = |field: &FieldInfo| {
+            required.iter().position(|r| r.name == field.name)
+        };
+        let builder_type = |states: &[String]| {
+            if states.is_empty() {
+                builder_name.clone()
+            } else {
+                format!("{}<{}>", builder_name, states.join(", "))
+            }
+        };
+        let generics = |names: &[String]| {
+            if names.is_empty() { String::new() } else { format!("<{}>", names.join(", ")) }
+        };
+        let mut code = format!("// Typestate builder for {}\n", struct_name);
+        code.push_str(&self.generate_builder_docs(struct_info, method_prefix, "typestate"));
+        if !derive_attrs.is_empty() {
+            code.push_str(&format!("#[derive({})]\n", derive_attrs));
+        } else {
+            code.push_str("#[derive(Debug, Clone)]\n");
+        }
+        let declared: Vec<String> = params
+            .iter()
+            .map(|p| format!("{} = Missing", p))
+            .collect();
+        code.push_str(&format!("pub struct {}{} {{\n", builder_name, generics(&declared)));
+        for field in &struct_info.fields {
+            let field_type = match state_of(field) {
+                Some(index) => params[index].clone(),
+                None => field.ty.clone(),
+            };
+            code.push_str(&format!("    {}: {},\n", field.name, field_type));
+        }
+        code.push_str("}\n\n");
+        code.push_str(&format!("impl {} {{\n", struct_name));
+        code.push_str(&format!("    pub fn builder() -> {} {{\n", builder_name));
+        code.push_str(&format!("        {}::new()\n", builder_name));
+        code.push_str("    }\n");
+        code.push_str("}\n\n");
+        code.push_str(&format!("impl {} {{\n", builder_name));
+        code.push_str("    pub fn new() -> Self {\n");
+        code.push_str(&format!("        {} {{\n", builder_name));
+        for field in &struct_info.fields {
+            let initial = if state_of(field).is_some() {
+                "Missing".to_string()
+            } else if field.is_optional {
+                "None".to_string()
+            } else {
+                self.get_default_value_for_type(&field.ty)
+            };
+            code.push_str(&format!("            {}: {},\n", field.name, initial));
+        }
+        code.push_str("        }\n");
+        code.push_str("    }\n");
+        code.push_str("}\n\n");
+        code.push_str(&format!("impl Default for {} {{\n", builder_name));
+        code.push_str("    fn default() -> Self {\n");
+        code.push_str("        Self::new()\n");
+        code.push_str("    }\n");
+        code.push_str("}\n\n");
+        for (index, field) in required.iter().enumerate() {
+            let others: Vec<String> = params
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != index)
+                .map(|(_, p)| p.clone())
+                .collect();
+            let mut from_states = params.clone();
+            from_states[index] = "Missing".to_string();
+            let mut to_states = params.clone();
+            to_states[index] = format!("Set<{}>", field.ty);
+            code.push_str(
+                &format!("impl{} {} {{\n", generics(&others), builder_type(&from_states)),
+            );
+            code.push_str(
+                &format!(
+                    "    pub fn {}(self, {}: {}) -> {} {{\n", self.setter_name(method_prefix,
+                    & field.name), field.name, field.setter_ty, builder_type(& to_states)
+                ),
+            );
+            code.push_str(&format!("        {} {{\n", builder_name));
+            for other in &struct_info.fields {
+                if other.name == field.name {
+                    code.push_str(
+                        &format!("            {}: Set({}),\n", other.name, other.name),
+                    );
+                } else {
+                    code.push_str(
+                        &format!("            {}: self.{},\n", other.name, other.name),
+                    );
+                }
+            }
+            code.push_str("        }\n");
+            code.push_str("    }\n");
+            code.push_str("}\n\n");
+        }
+        let optional: Vec<&FieldInfo> = struct_info
+            .fields
+            .iter()
+            .filter(|f| !self.is_required(f))
+            .collect();
+        if !optional.is_empty() {
+            code.push_str(
+                &format!("impl{} {} {{\n", generics(&params), builder_type(&params)),
+            );
+            for field in &optional {
+                let assignment = if field.is_optional {
+                    format!("Some({})", field.name)
+                } else {
+                    field.name.clone()
+                };
+                code.push_str(
+                    &format!(
+                        "    pub fn {}(mut self, {}: {}) -> Self {{\n", self
+                        .setter_name(method_prefix, & field.name), field.name, field
+                        .setter_ty
+                    ),
+                );
+                code.push_str(&format!("        self.{} = {};\n", field.name, assignment));
+                code.push_str("        self\n");
+                code.push_str("    }\n\n");
+            }
+            code.push_str("}\n\n");
+        }
+        let complete: Vec<String> = required
+            .iter()
+            .map(|f| format!("Set<{}>", f.ty))
+            .collect();
+        let validated = self.has_validation(struct_info);
+        code.push_str(&format!("impl {} {{\n", builder_type(&complete)));
+        if validated {
+            code.push_str(
+                &format!("    pub fn build(self) -> Result<{}, BuilderError> {{\n", struct_name),
+            );
+        } else {
+            code.push_str(&format!("    pub fn build(self) -> {} {{\n", struct_name));
+        }
+        for field in &struct_info.fields {
+            let value = if state_of(field).is_some() {
+                format!("self.{}.0", field.name)
+            } else {
+                format!("self.{}", field.name)
+            };
+            code.push_str(&format!("        let {} = {};\n", field.name, value));
+            if !field.validation_rules.is_empty() {
+                code.push_str(&format!("        validate_{}(&{})?;\n", field.name, field.name));
+            }
+        }
+        let fields: Vec<&str> = struct_info.fields.iter().map(|f| f.name.as_str()).collect();
+        let construct = format!("{} {{ {} }}", struct_name, fields.join(", "));
+        if validated {
+            code.push_str(&format!("        Ok({})\n", construct));
+        } else {
+            code.push_str(&format!("        {}\n", construct));
+        }
+        code.push_str("    }\n");
+        code.push_str("}\n\n");
+        Ok(code)
+    }
+    fn generate_builder_tests(
+        &self,
+        struct_info: &StructInfo,
+        method_prefix: &str,
+        style: &str,
+    ) -> String {
+        let module = format!("{}_builder_tests", self.to_snake_case(&struct_info.name));
+        let mut code = format!("#[cfg(test)]\nmod {} {{\n    use super::*;\n\n", module);
+        code.push_str("    #[test]\n");
+        code.push_str("    fn builds_with_required_fields() {\n");
+        for line in self.example_chain(struct_info, method_prefix, style).lines() {
+            code.push_str(&format!("        {}\n", line));
+        }
+        for field in &struct_info.fields {
+            let sample = self.sample_value_for_type(&field.setter_ty);
+            if field.is_optional {
+                code.push_str(&format!("        assert!(value.{}.is_none());\n", field.name));
+            } else if self.is_required(field) && sample != "Default::default()" {
+                code.push_str(
+                    &format!("        assert_eq!(value.{}, {});\n", field.name, sample),
+                );
+            }
+        }
+        code.push_str("    }\n");
+        let first_required = struct_info.fields.iter().find(|f| self.is_required(f));
+        if let (Some(field), "runtime") = (first_required, style) {
+            code.push_str("\n    #[test]\n");
+            code.push_str("    fn reports_missing_required_field() {\n");
+            code.push_str(
+                &format!("        let result = {}Builder::new().build();\n", struct_info.name),
+            );
+            code.push_str(
+                &format!(
+                    "        assert!(matches!(result, Err(BuilderError::MissingField(\"{}\"))));\n",
+                    field.name
+                ),
+            );
+            code.push_str("    }\n");
+        }
+        code.push_str("}\n\n");
+        code
+    }
+    fn generate_error_type(&self) -> String {
         let mut code = "// Builder validation error types\n\n".to_string();
         code.push_str("#[derive(Debug, Clone)]\n");
         code.push_str("pub enum BuilderError {\n");
@@ -309,6 +618,10 @@ impl BuilderGenTool {
         code.push_str("    }\n");
         code.push_str("}\n\n");
         code.push_str("impl std::error::Error for BuilderError {}\n\n");
+        code
+    }
+    fn generate_validation_code(&self, struct_info: &StructInfo) -> Result<String> {
+        let mut code = String::new();
         for field in &struct_info.fields {
             if !field.validation_rules.is_empty() {
                 code.push_str(&format!("// Validation for {} field\n", field.name));
@@ -341,13 +654,6 @@ impl BuilderGenTool {
         let struct_name = &struct_info.name;
         let builder_name = format!("{}Builder", struct_name);
         let mut code = format!("// Conversion implementations for {}\n\n", struct_name);
-        code.push_str(&format!("impl From<{}> for {} {{\n", builder_name, struct_name));
-        code.push_str(&format!("    fn from(builder: {}) -> Self {{\n", builder_name));
-        code.push_str(
-            "        builder.build().unwrap_or_else(|_| panic!(\"Failed to build from valid builder\"))\n",
-        );
-        code.push_str("    }\n");
-        code.push_str("}\n\n");
         code.push_str(
             &format!("impl TryFrom<{}> for {} {{\n", builder_name, struct_name),
         );
@@ -379,29 +685,90 @@ impl BuilderGenTool {
         struct_info: &StructInfo,
         derive_attrs: &str,
         method_prefix: &str,
+        style: &str,
     ) -> Result<String> {
         let mut code = format!(
             "// Comprehensive Builder Pattern for {}\n\n", struct_info.name
         );
         let validation_code = self.generate_validation_code(struct_info)?;
         code.push_str(&validation_code);
-        let builder_struct = self.generate_builder_struct(struct_info, derive_attrs)?;
-        code.push_str(&builder_struct);
-        let builder_impl = self.generate_builder_impl(struct_info, method_prefix)?;
-        code.push_str(&builder_impl);
-        let conversion_impl = self.generate_conversion_impl(struct_info)?;
-        code.push_str(&conversion_impl);
+        if style == "typestate" {
+            let builder = self
+                .generate_typestate_builder(struct_info, derive_attrs, method_prefix)?;
+            code.push_str(&builder);
+        } else {
+            let builder_struct = self
+                .generate_builder_struct(struct_info, derive_attrs, method_prefix)?;
+            code.push_str(&builder_struct);
+            let builder_impl = self.generate_builder_impl(struct_info, method_prefix)?;
+            code.push_str(&builder_impl);
+            let conversion_impl = self.generate_conversion_impl(struct_info)?;
+            code.push_str(&conversion_impl);
+        }
+        code.push_str(&self.generate_builder_tests(struct_info, method_prefix, style));
         Ok(code)
     }
+    fn setter_name(&self, method_prefix: &str, field_name: &str) -> String {
+        if method_prefix.is_empty() {
+            return field_name.to_string();
+        }
+        let field_name = field_name.trim_start_matches("r#");
+        if method_prefix.ends_with('_') {
+            format!("{}{}", method_prefix, field_name)
+        } else {
+            format!("{}_{}", method_prefix, field_name)
+        }
+    }
+    fn is_required(&self, field: &FieldInfo) -> bool {
+        !field.is_optional && !field.has_default
+    }
+    fn has_validation(&self, struct_info: &StructInfo) -> bool {
+        struct_info.fields.iter().any(|f| !f.validation_rules.is_empty())
+    }
+    fn sample_value_for_type(&self, ty: &str) -> String {
+        match ty {
+            "String" => "\"example\".to_string()".to_string(),
+            "&str" | "&'static str" => "\"example\"".to_string(),
+            "bool" => "true".to_string(),
+            "char" => "'x'".to_string(),
+            "f32" | "f64" => "1.5".to_string(),
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32"
+            | "u64" | "u128" | "usize" => "42".to_string(),
+            _ => "Default::default()".to_string(),
+        }
+    }
+    fn to_pascal_case(&self, name: &str) -> String {
+        name.trim_start_matches("r#")
+            .split('_')
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let mut chars = part.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+    fn to_snake_case(&self, name: &str) -> String {
+        let mut snake = String::new();
+        for (index, c) in name.chars().enumerate() {
+            if c.is_uppercase() && index > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        }
+        snake
+    }
     fn get_default_value_for_type(&self, ty: &str) -> String {
         match ty {
             "String" => "String::new()".to_string(),
             "i32" | "i64" | "u32" | "u64" => "0".to_string(),
             "bool" => "false".to_string(),
             "f32" | "f64" => "0.0".to_string(),
-            "Vec<T>" => "Vec::new()".to_string(),
-            "Option<T>" => "None".to_string(),
-            _ => format!("{}::default()", ty),
+            _ if ty.starts_with("Vec<") => "Vec::new()".to_string(),
+            _ if ty.starts_with("Option<") => "None".to_string(),
+            _ => "Default::default()".to_string(),
         }
     }
     fn generate_nested_builders(&self, struct_info: &StructInfo) -> Result<String> {
@@ -441,7 +808,15 @@ impl Tool for BuilderGenTool {
         Command::new(self.name())
             .about(self.description())
             .long_about(
-                "Analyze struct definitions and generate comprehensive builder patterns with validation, optional fields, fluent API methods, and support for nested builders. Perfect for complex structs with many optional fields.",
+                "Analyze struct definitions and generate comprehensive builder patterns with validation, optional fields, fluent API methods, and support for nested builders. Perfect for complex structs with many optional fields.
+
+STYLES:
+    runtime    build() returns Result and reports missing required fields
+    typestate  build() only compiles once every required field is set
+
+EXAMPLES:
+    cm tool builder-gen --input src/config.rs
+    cm tool builder-gen --input src/config.rs --style typestate",
             )
             .args(
                 &[
@@ -450,9 +825,8 @@ impl Tool for BuilderGenTool {
                         .short('i')
                         .help("Input Rust file containing struct definitions")
                         .required(true),
-                    Arg::new("output")
-                        .long("output")
-                        .short('o')
+                    Arg::new("builder-output")
+                        .long("builder-output")
                         .help("Output file for generated builders")
                         .default_value("generated/builders.rs"),
                     Arg::new("derive")
@@ -473,17 +847,23 @@ impl Tool for BuilderGenTool {
                         .short('p')
                         .help("Method prefix for builder methods")
                         .default_value("with"),
+                    Arg::new("style")
+                        .long("style")
+                        .help("Builder style: runtime-checked or compile-time typestate")
+                        .value_parser(["runtime", "typestate"])
+                        .default_value("runtime"),
                 ],
             )
             .args(&common_options())
     }
     fn execute(&self, matches: &ArgMatches) -> Result<()> {
         let input = matches.get_one::<String>("input").unwrap();
-        let output = matches.get_one::<String>("output").unwrap();
+        let output = matches.get_one::<String>("builder-output").unwrap();
         let derive_attrs = matches.get_one::<String>("derive").unwrap();
         let validation = matches.get_flag("validation");
         let nested = matches.get_flag("nested");
         let method_prefix = matches.get_one::<String>("prefix").unwrap();
+        let style = matches.get_one::<String>("style").unwrap();
         let dry_run = matches.get_flag("dry-run");
         let verbose = matches.get_flag("verbose");
         let output_format = parse_output_format(matches);
@@ -502,6 +882,12 @@ impl Tool for BuilderGenTool {
             return Ok(());
         }
         let mut all_code = String::new();
+        if style == "typestate" {
+            all_code.push_str(&self.generate_typestate_markers());
+        }
+        if style == "runtime" || structs.iter().any(|s| self.has_validation(s)) {
+            all_code.push_str(&self.generate_error_type());
+        }
         for struct_info in &structs {
             println!("📝 Processing struct: {}", struct_info.name.bold());
             if verbose {
@@ -517,6 +903,7 @@ impl Tool for BuilderGenTool {
                     struct_info,
                     derive_attrs,
                     method_prefix,
+                    style,
                 )?;
             all_code.push_str(&builder_code);
             if nested {
@@ -528,7 +915,8 @@ impl Tool for BuilderGenTool {
         match output_format {
             OutputFormat::Human => {
                 println!(
-                    "  ✅ Generated builder patterns for {} structs", structs.len()
+                    "  ✅ Generated {} builder patterns for {} structs", style, structs
+                    .len()
                 );
                 println!("     → {}", output.cyan());
                 if validation {
@@ -565,7 +953,7 @@ impl Tool for BuilderGenTool {
             OutputFormat::Json => {
                 let result = serde_json::json!(
                     { "input" : input, "output" : output, "structs_processed" : structs
-                    .len(), "validation_enabled" : validation, "nested_enabled" : nested,
+                    .len(), "validation_enabled" : validation, "nested_enabled" : nested, "style" : style,
                     "method_prefix" : method_prefix, "derive_attrs" : derive_attrs,
                     "struct_names" : structs.iter().map(| s | s.name.clone()).collect::<
                     Vec < _ >> (), "code_preview" : all_code.lines().take(10).collect::<
@@ -575,16 +963,16 @@ impl Tool for BuilderGenTool {
             }
             OutputFormat::Table => {
                 println!(
-                    "{:<20} {:<15} {:<8} {:<8} {:<10}", "Struct", "Input", "Valid",
-                    "Nested", "Prefix"
+                    "{:<20} {:<15} {:<8} {:<8} {:<10} {:<10}", "Struct", "Input",
+                    "Valid", "Nested", "Prefix", "Style"
                 );
-                println!("{}", "─".repeat(65));
+                println!("{}", "─".repeat(76));
                 for struct_info in &structs {
                     println!(
-                        "{:<20} {:<15} {:<8} {:<8} {:<10}", struct_info.name,
+                        "{:<20} {:<15} {:<8} {:<8} {:<10} {:<10}", struct_info.name,
                         Path::new(input).file_name().unwrap_or_default()
                         .to_string_lossy(), if validation { "Yes" } else { "No" }, if
-                        nested { "Yes" } else { "No" }, method_prefix
+                        nested { "Yes" } else { "No" }, method_prefix, style
                     );
                 }
             }
@@ -597,4 +985,34 @@ impl Default for BuilderGenTool {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn parse(source: &str) -> StructInfo {
+        let item: ItemStruct = syn::parse_str(source).unwrap();
+        BuilderGenTool::new().parse_struct_item(&item).unwrap()
+    }
+    #[test]
+    fn test_typestate_builder_requires_fields_at_compile_time() {
+        let info = parse("pub struct Server { host: String, port: u16, label: Option<String> }");
+        let code = BuilderGenTool::new()
+            .generate_typestate_builder(&info, "Debug", "with")
+            .unwrap();
+        assert!(code.contains("pub struct ServerBuilder<HostState = Missing, PortState = Missing>"));
+        assert!(code.contains("impl<PortState> ServerBuilder<Missing, PortState>"));
+        assert!(code.contains("pub fn with_label(mut self, label: String) -> Self"));
+        assert!(code.contains("impl ServerBuilder<Set<String>, Set<u16>>"));
+        assert!(code.contains("    pub fn build(self) -> Server {"));
+    }
+    #[test]
+    fn test_runtime_builder_wraps_optional_setters() {
+        let info = parse("pub struct Server { host: String, label: Option<String> }");
+        let code = BuilderGenTool::new()
+            .generate_builder_impl(&info, "with")
+            .unwrap();
+        assert!(code.contains("pub fn with_label(mut self, label: String) -> Self"));
+        assert!(code.contains("self.label = Some(label);"));
+        assert!(code.contains("BuilderError::MissingField(\"host\")"));
+    }
+}
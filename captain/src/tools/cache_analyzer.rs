@@ -1,8 +1,10 @@
 use super::{Tool, ToolError, Result, OutputFormat, parse_output_format};
 use clap::{Arg, ArgMatches, Command};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command as ProcessCommand;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
+use std::time::UNIX_EPOCH;
+use sha2::{Digest, Sha256};
 use colored::*;
 use serde::{Serialize, Deserialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +56,63 @@ pub struct CodePattern {
     pub line_number: usize,
     pub severity: String,
 }
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UnitFingerprint {
+    pub id: String,
+    pub key: String,
+    pub package: String,
+    pub unit: String,
+    pub profile_dir: String,
+    pub built_at: u64,
+    pub rustc: u64,
+    pub features: String,
+    pub profile: u64,
+    pub config: u64,
+    pub rustflags: Vec<String>,
+    pub deps: BTreeMap<String, u64>,
+    pub env: BTreeMap<String, Option<String>>,
+    pub sources: Vec<String>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebuildCause {
+    pub kind: String,
+    pub detail: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebuildEvent {
+    pub package: String,
+    pub unit: String,
+    pub profile_dir: String,
+    pub built_at: u64,
+    pub causes: Vec<RebuildCause>,
+}
+impl RebuildEvent {
+    pub fn is_cascade(&self) -> bool {
+        self.causes.iter().all(|cause| cause.kind == "dependency")
+    }
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildRecord {
+    pub recorded_at: u64,
+    pub events: Vec<RebuildEvent>,
+}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RebuildHistory {
+    pub project: String,
+    pub last_scan: u64,
+    pub units: BTreeMap<String, UnitFingerprint>,
+    pub builds: Vec<BuildRecord>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CauseSummary {
+    pub kind: String,
+    pub detail: String,
+    pub rebuilds: usize,
+    pub builds: usize,
+    pub units: Vec<String>,
+}
+const REBUILD_CLUSTER_GAP_MS: u64 = 120_000;
+const MAX_RECORDED_BUILDS: usize = 200;
 pub struct CacheAnalyzerTool;
 impl CacheAnalyzerTool {
     pub fn new() -> Self {
@@ -332,6 +391,532 @@ impl CacheAnalyzerTool {
             sequential_access_ratio: 92.3,
         })
     }
+    fn modified_millis(path: &Path) -> Option<u64> {
+        let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+        modified.duration_since(UNIX_EPOCH).ok().map(|d| d.as_millis() as u64)
+    }
+    fn default_target_dir() -> String {
+        std::env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_string())
+    }
+    fn project_root_for(target_dir: &Path) -> PathBuf {
+        target_dir
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+    fn fingerprint_roots(&self, target_dir: &Path) -> Vec<(String, PathBuf)> {
+        let mut roots = Vec::new();
+        let Ok(entries) = std::fs::read_dir(target_dir) else {
+            return roots;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.join(".fingerprint").is_dir() {
+                roots.push((name, path.join(".fingerprint")));
+                continue;
+            }
+            if let Ok(nested) = std::fs::read_dir(&path) {
+                for sub in nested.flatten() {
+                    if sub.path().join(".fingerprint").is_dir() {
+                        roots
+                            .push((
+                                format!("{}/{}", name, sub.file_name().to_string_lossy()),
+                                sub.path().join(".fingerprint"),
+                            ));
+                    }
+                }
+            }
+        }
+        roots.sort();
+        roots
+    }
+    fn dep_info_path(&self, profile_dir: &Path, unit: &str, hash: &str) -> Option<PathBuf> {
+        let mut rest = unit;
+        loop {
+            let candidate = profile_dir
+                .join("deps")
+                .join(format!("{}-{}.d", rest.replace('-', "_"), hash));
+            if candidate.exists() {
+                return Some(candidate);
+            }
+            rest = rest.split_once('-')?.1;
+        }
+    }
+    fn parse_dep_info(&self, content: &str, unit: &mut UnitFingerprint) {
+        for line in content.lines() {
+            if let Some(dep) = line.strip_prefix("# env-dep:") {
+                match dep.split_once('=') {
+                    Some((key, value)) => {
+                        unit.env.insert(key.to_string(), Some(value.to_string()));
+                    }
+                    None => {
+                        unit.env.insert(dep.to_string(), None);
+                    }
+                }
+            } else if let Some(path) = line.strip_suffix(':') {
+                let path = path.replace("\\ ", " ");
+                let external = path.contains("/.cargo/registry/")
+                    || path.contains("/.cargo/git/") || path.contains("/.rustup/");
+                if !path.is_empty() && !external && !unit.sources.contains(&path) {
+                    unit.sources.push(path);
+                }
+            }
+        }
+    }
+    fn parse_fingerprint_unit(
+        &self,
+        profile_label: &str,
+        unit_dir: &Path,
+        json_path: &Path,
+    ) -> Option<UnitFingerprint> {
+        let value: serde_json::Value = serde_json::from_str(
+                &std::fs::read_to_string(json_path).ok()?,
+            )
+            .ok()?;
+        let dir_name = unit_dir.file_name()?.to_string_lossy().to_string();
+        let (package, hash) = dir_name.rsplit_once('-')?;
+        let unit_name = json_path.file_stem()?.to_string_lossy().to_string();
+        let key = format!("{}/{}/{}", profile_label, package, unit_name);
+        let built_at = Self::modified_millis(&unit_dir.join("invoked.timestamp"))
+            .or_else(|| Self::modified_millis(json_path))?;
+        let mut unit = UnitFingerprint {
+            id: format!("{}@{}", key, hash),
+            key,
+            package: package.to_string(),
+            unit: unit_name,
+            profile_dir: profile_label.to_string(),
+            built_at,
+            rustc: value["rustc"].as_u64().unwrap_or(0),
+            features: value["features"].as_str().unwrap_or("").to_string(),
+            profile: value["profile"].as_u64().unwrap_or(0),
+            config: value["config"].as_u64().unwrap_or(0),
+            ..Default::default()
+        };
+        if let Some(flags) = value["rustflags"].as_array() {
+            unit.rustflags = flags
+                .iter()
+                .filter_map(|flag| flag.as_str().map(str::to_string))
+                .collect();
+        }
+        for dep in value["deps"].as_array().into_iter().flatten() {
+            if let (Some(name), Some(dep_hash)) = (dep[1].as_str(), dep[3].as_u64()) {
+                unit.deps.insert(name.to_string(), dep_hash);
+            }
+        }
+        for local in value["local"].as_array().into_iter().flatten() {
+            if let Some(env) = local.get("RerunIfEnvChanged") {
+                if let Some(var) = env["var"].as_str() {
+                    unit.env
+                        .insert(var.to_string(), env["val"].as_str().map(str::to_string));
+                }
+            }
+        }
+        if let Some(dep_info) = unit_dir
+            .parent()
+            .and_then(Path::parent)
+            .and_then(|profile_dir| self.dep_info_path(profile_dir, &unit.unit, hash))
+        {
+            if let Ok(content) = std::fs::read_to_string(dep_info) {
+                self.parse_dep_info(&content, &mut unit);
+            }
+        }
+        Some(unit)
+    }
+    fn scan_fingerprints(&self, target_dir: &Path) -> Vec<UnitFingerprint> {
+        let mut units = Vec::new();
+        for (label, root) in self.fingerprint_roots(target_dir) {
+            let Ok(entries) = std::fs::read_dir(&root) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let unit_dir = entry.path();
+                let Ok(files) = std::fs::read_dir(&unit_dir) else {
+                    continue;
+                };
+                for file in files.flatten() {
+                    let path = file.path();
+                    if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                        continue;
+                    }
+                    if let Some(unit) = self.parse_fingerprint_unit(&label, &unit_dir, &path)
+                    {
+                        units.push(unit);
+                    }
+                }
+            }
+        }
+        units
+    }
+    fn explain_rebuild(
+        &self,
+        current: &UnitFingerprint,
+        previous: Option<&UnitFingerprint>,
+        variant: Option<&UnitFingerprint>,
+        since: u64,
+        project_root: &Path,
+    ) -> Vec<RebuildCause> {
+        let mut causes = Vec::new();
+        let mut cause = |kind: &str, detail: String| {
+            causes
+                .push(RebuildCause {
+                    kind: kind.to_string(),
+                    detail,
+                })
+        };
+        let baseline = previous.or(variant);
+        if let Some(base) = baseline {
+            if base.rustc != current.rustc {
+                cause("compiler", "rustc version changed".to_string());
+            }
+            if base.profile != current.profile {
+                cause("profile", format!("profile settings changed ({})", current.profile_dir));
+            }
+            if base.features != current.features {
+                cause("features", format!("{} → {}", base.features, current.features));
+            }
+            if base.rustflags != current.rustflags {
+                cause(
+                    "rustflags",
+                    format!(
+                        "[{}] → [{}]", base.rustflags.join(" "), current.rustflags.join(" ")
+                    ),
+                );
+            }
+            if base.config != current.config {
+                cause("config", "cargo config or -C options changed".to_string());
+            }
+            let vars: std::collections::BTreeSet<&String> = base
+                .env
+                .keys()
+                .chain(current.env.keys())
+                .collect();
+            for var in vars {
+                let old = base.env.get(var).cloned().flatten();
+                let new = current.env.get(var).cloned().flatten();
+                if (base.env.contains_key(var) && current.env.contains_key(var)) && old != new {
+                    let show = |value: Option<String>| {
+                        value.unwrap_or_else(|| "<unset>".to_string())
+                    };
+                    cause("env", format!("{}: {} → {}", var, show(old), show(new)));
+                }
+            }
+        }
+        if baseline.is_some() || since > 0 {
+            for source in &current.sources {
+                let path = if Path::new(source).is_absolute() {
+                    PathBuf::from(source)
+                } else {
+                    project_root.join(source)
+                };
+                if let Some(mtime) = Self::modified_millis(&path) {
+                    if mtime > since && mtime <= current.built_at {
+                        cause("file", source.clone());
+                    }
+                }
+            }
+        }
+        if let Some(base) = baseline {
+            let changed: Vec<&str> = current
+                .deps
+                .iter()
+                .filter(|(name, hash)| base.deps.get(*name).is_some_and(|old| old != *hash))
+                .map(|(name, _)| name.as_str())
+                .collect();
+            if !changed.is_empty() {
+                cause("dependency", format!("rebuilt after {}", changed.join(", ")));
+            }
+        }
+        if causes.is_empty() {
+            let (kind, detail) = if baseline.is_none() {
+                ("new", "no earlier build of this unit on record")
+            } else {
+                ("unknown", "fingerprint refreshed without a visible change")
+            };
+            causes
+                .push(RebuildCause {
+                    kind: kind.to_string(),
+                    detail: detail.to_string(),
+                });
+        }
+        causes
+    }
+    pub fn analyze_rebuilds(
+        &self,
+        target_dir: &Path,
+        history: &RebuildHistory,
+    ) -> (Vec<RebuildEvent>, Vec<UnitFingerprint>) {
+        let project_root = Self::project_root_for(target_dir);
+        let mut units = self.scan_fingerprints(target_dir);
+        units.sort_by(|a, b| b.built_at.cmp(&a.built_at).then_with(|| a.id.cmp(&b.id)));
+        let (cutoff, since) = if history.last_scan > 0 {
+            (history.last_scan, history.last_scan)
+        } else {
+            let mut cutoff = units.first().map(|unit| unit.built_at).unwrap_or(0);
+            let mut since = 0;
+            for unit in &units {
+                if cutoff.saturating_sub(unit.built_at) > REBUILD_CLUSTER_GAP_MS {
+                    since = unit.built_at;
+                    break;
+                }
+                cutoff = unit.built_at;
+            }
+            (cutoff.saturating_sub(1), since)
+        };
+        let mut events = Vec::new();
+        for current in units.iter().filter(|unit| unit.built_at > cutoff) {
+            let previous = history
+                .units
+                .get(&current.id)
+                .filter(|unit| unit.built_at < current.built_at);
+            let variant = units
+                .iter()
+                .chain(history.units.values())
+                .filter(|unit| {
+                    unit.key == current.key && unit.id != current.id
+                        && unit.built_at < current.built_at
+                })
+                .max_by_key(|unit| unit.built_at);
+            let since = previous.map(|unit| unit.built_at).unwrap_or(since);
+            events
+                .push(RebuildEvent {
+                    package: current.package.clone(),
+                    unit: current.unit.clone(),
+                    profile_dir: current.profile_dir.clone(),
+                    built_at: current.built_at,
+                    causes: self
+                        .explain_rebuild(current, previous, variant, since, &project_root),
+                });
+        }
+        events
+            .sort_by(|a, b| {
+                a.is_cascade()
+                    .cmp(&b.is_cascade())
+                    .then_with(|| a.package.cmp(&b.package))
+                    .then_with(|| a.unit.cmp(&b.unit))
+            });
+        (events, units)
+    }
+    pub fn aggregate_rebuild_causes(&self, builds: &[BuildRecord]) -> Vec<CauseSummary> {
+        let mut summaries: BTreeMap<(String, String), CauseSummary> = BTreeMap::new();
+        for build in builds {
+            let mut counted = HashSet::new();
+            for event in &build.events {
+                for cause in &event.causes {
+                    if cause.kind == "dependency" || cause.kind == "new" {
+                        continue;
+                    }
+                    let detail = match cause.kind.as_str() {
+                        "env" => cause.detail.split(':').next().unwrap_or_default(),
+                        _ => cause.detail.as_str(),
+                    };
+                    let key = (cause.kind.clone(), detail.to_string());
+                    let entry = summaries
+                        .entry(key.clone())
+                        .or_insert_with(|| CauseSummary {
+                            kind: key.0.clone(),
+                            detail: key.1.clone(),
+                            rebuilds: 0,
+                            builds: 0,
+                            units: Vec::new(),
+                        });
+                    entry.rebuilds += 1;
+                    if counted.insert(key) {
+                        entry.builds += 1;
+                    }
+                    let unit = format!("{} {}", event.package, event.unit);
+                    if !entry.units.contains(&unit) {
+                        entry.units.push(unit);
+                    }
+                }
+            }
+        }
+        let mut result: Vec<CauseSummary> = summaries.into_values().collect();
+        result.sort_by(|a, b| b.rebuilds.cmp(&a.rebuilds).then_with(|| b.builds.cmp(&a.builds)));
+        result
+    }
+    fn rebuild_history_path(&self, project_root: &Path) -> Option<PathBuf> {
+        let canonical = project_root
+            .canonicalize()
+            .unwrap_or_else(|_| project_root.to_path_buf());
+        let digest = format!(
+            "{:x}", Sha256::digest(canonical.to_string_lossy().as_bytes())
+        );
+        Some(
+            dirs::home_dir()?
+                .join(".shipwreck")
+                .join("rebuilds")
+                .join(format!("{}.json", &digest[..16])),
+        )
+    }
+    fn cause_hint(kind: &str) -> &'static str {
+        match kind {
+            "env" => "Export the variable identically in every shell, IDE and CI step",
+            "rustflags" => {
+                "RUSTFLAGS differ between invocations (IDE vs terminal); move them into .cargo/config.toml"
+            }
+            "features" => {
+                "Feature sets flip between `-p` and workspace builds; align features or use a workspace-hack crate"
+            }
+            "profile" => "Profile settings differ between invocations; check [profile] overrides",
+            "config" => "Cargo config differs between invocations; check CARGO_* variables",
+            "compiler" => "The toolchain changed; pin it with rust-toolchain.toml",
+            "file" => {
+                "A tool keeps touching this file; check formatters, code generators and build.rs writes"
+            }
+            _ => "Inspect the fingerprint with CARGO_LOG=cargo::core::compiler::fingerprint=info",
+        }
+    }
+    fn run_rebuild_forensics(
+        &self,
+        target_dir: &Path,
+        top: usize,
+        dry_run: bool,
+        verbose: bool,
+        output_format: OutputFormat,
+    ) -> Result<()> {
+        if !target_dir.is_dir() {
+            return Err(
+                ToolError::InvalidArguments(
+                    format!("Target directory not found: {}", target_dir.display()),
+                ),
+            );
+        }
+        let project_root = Self::project_root_for(target_dir);
+        let history_path = self.rebuild_history_path(&project_root);
+        let mut history: RebuildHistory = history_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let (events, units) = self.analyze_rebuilds(target_dir, &history);
+        history.project = project_root
+            .canonicalize()
+            .unwrap_or_else(|_| project_root.clone())
+            .display()
+            .to_string();
+        history.last_scan = units
+            .iter()
+            .map(|unit| unit.built_at)
+            .max()
+            .unwrap_or(history.last_scan);
+        history.units = units.into_iter().map(|unit| (unit.id.clone(), unit)).collect();
+        if !events.is_empty() {
+            history
+                .builds
+                .push(BuildRecord {
+                    recorded_at: chrono::Utc::now().timestamp_millis() as u64,
+                    events: events.clone(),
+                });
+            let excess = history.builds.len().saturating_sub(MAX_RECORDED_BUILDS);
+            history.builds.drain(..excess);
+        }
+        if !dry_run {
+            if let Some(path) = &history_path {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)
+                        .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+                }
+                std::fs::write(path, serde_json::to_string(&history).unwrap_or_default())
+                    .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+            }
+        }
+        let summaries = self.aggregate_rebuild_causes(&history.builds);
+        let roots: Vec<&RebuildEvent> = events.iter().filter(|e| !e.is_cascade()).collect();
+        let cascaded = events.len() - roots.len();
+        match output_format {
+            OutputFormat::Json => {
+                let json = serde_json::json!(
+                    { "target_dir" : target_dir.display().to_string(), "rebuilt" : events
+                    .len(), "root_causes" : roots.len(), "cascaded" : cascaded, "events"
+                    : events, "recorded_builds" : history.builds.len(), "top_causes" :
+                    summaries.iter().take(top).collect::< Vec < _ >> (), }
+                );
+                println!("{}", serde_json::to_string_pretty(& json).unwrap());
+            }
+            OutputFormat::Table => {
+                println!("{:<28} {:<34} {:<10} DETAIL", "PACKAGE", "UNIT", "CAUSE");
+                println!("{}", "─".repeat(100));
+                for event in &events {
+                    for cause in &event.causes {
+                        println!(
+                            "{:<28} {:<34} {:<10} {}", event.package, event.unit, cause
+                            .kind, cause.detail
+                        );
+                    }
+                }
+                println!();
+                println!("{:<4} {:<10} {:>8} {:>7}  DETAIL", "#", "CAUSE", "REBUILDS", "BUILDS");
+                for (index, summary) in summaries.iter().take(top).enumerate() {
+                    println!(
+                        "{:<4} {:<10} {:>8} {:>7}  {}", index + 1, summary.kind, summary
+                        .rebuilds, summary.builds, summary.detail
+                    );
+                }
+            }
+            OutputFormat::Human => {
+                println!(
+                    "🔍 {} - {}", "Rebuild Forensics".bold(), target_dir.display()
+                    .to_string().cyan()
+                );
+                if events.is_empty() {
+                    println!("\n✅ Nothing was rebuilt since the last scan");
+                } else {
+                    println!(
+                        "\n🔨 Last build: {} units rebuilt ({} with a root cause, {} cascaded)",
+                        events.len().to_string().bold(), roots.len().to_string().yellow(),
+                        cascaded
+                    );
+                    for event in events.iter().filter(|e| verbose || !e.is_cascade()) {
+                        let causes: Vec<String> = event
+                            .causes
+                            .iter()
+                            .map(|cause| format!("{} {}", cause.kind.yellow(), cause.detail))
+                            .collect();
+                        println!(
+                            "  • {} {} ({}): {}", event.package.bold(), event.unit
+                            .dimmed(), event.profile_dir, causes.join("; ")
+                        );
+                    }
+                    if cascaded > 0 && !verbose {
+                        println!("  {} cascaded rebuilds hidden (use -v)", cascaded);
+                    }
+                }
+                if summaries.is_empty() {
+                    println!("\n📈 No spurious rebuild causes recorded yet");
+                } else {
+                    println!(
+                        "\n📈 Top rebuild causes across {} recorded builds:", history.builds
+                        .len()
+                    );
+                    for (index, summary) in summaries.iter().take(top).enumerate() {
+                        println!(
+                            "  {}. {} {} - {} rebuilds in {} builds", index + 1, summary.kind
+                            .yellow(), summary.detail, summary.rebuilds, summary.builds
+                        );
+                        if verbose {
+                            println!("     units: {}", summary.units.join(", "));
+                        }
+                    }
+                    let mut hinted = HashSet::new();
+                    println!("\n💡 Suggestions:");
+                    for summary in summaries.iter().take(top) {
+                        if hinted.insert(summary.kind.clone()) {
+                            println!("  • {}: {}", summary.kind, Self::cause_hint(&summary.kind));
+                        }
+                    }
+                }
+                if dry_run {
+                    println!("\n🔍 Dry run: rebuild history was not updated");
+                }
+            }
+        }
+        Ok(())
+    }
     fn check_tool_availability(&self, tool_name: &str) -> bool {
         ProcessCommand::new(tool_name)
             .arg("--version")
@@ -374,7 +959,8 @@ impl Tool for CacheAnalyzerTool {
                  EXAMPLES:\n\
                  cm tool cache-analyzer --target target/release/myapp --functions process_data,handle_request\n\
                  cm tool cache-analyzer --target src/main.rs --data-structures --false-sharing\n\
-                 cm tool cache-analyzer --target target/release/myapp --perf --threshold 10.0",
+                 cm tool cache-analyzer --target target/release/myapp --perf --threshold 10.0\n\
+                 cm tool cache-analyzer --rebuilds --top 5",
             )
             .args(
                 &[
@@ -382,7 +968,7 @@ impl Tool for CacheAnalyzerTool {
                         .long("target")
                         .short('t')
                         .help("Target binary or source file to analyze")
-                        .required(true),
+                        .required_unless_present("rebuilds"),
                     Arg::new("functions")
                         .long("functions")
                         .short('f')
@@ -412,11 +998,39 @@ impl Tool for CacheAnalyzerTool {
                         .short('r')
                         .help("Cache miss rate threshold (%)")
                         .default_value("5.0"),
+                    Arg::new("rebuilds")
+                        .long("rebuilds")
+                        .help(
+                            "Explain why crates were rebuilt in the last build using cargo fingerprints",
+                        )
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("target-dir")
+                        .long("target-dir")
+                        .help("Cargo target directory to inspect (defaults to CARGO_TARGET_DIR or target)"),
+                    Arg::new("top")
+                        .long("top")
+                        .help("Number of aggregated rebuild causes to show")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("10"),
                 ],
             )
             .args(&super::common_options())
     }
     fn execute(&self, matches: &ArgMatches) -> Result<()> {
+        if matches.get_flag("rebuilds") {
+            let target_dir = matches
+                .get_one::<String>("target-dir")
+                .cloned()
+                .unwrap_or_else(Self::default_target_dir);
+            return self
+                .run_rebuild_forensics(
+                    Path::new(&target_dir),
+                    *matches.get_one::<usize>("top").unwrap(),
+                    matches.get_flag("dry-run"),
+                    matches.get_flag("verbose"),
+                    parse_output_format(matches),
+                );
+        }
         let target = matches.get_one::<String>("target").unwrap();
         let functions_str = matches.get_one::<String>("functions");
         let use_perf = matches.get_flag("perf");
@@ -637,4 +1251,53 @@ impl Tool for CacheAnalyzerTool {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn write_unit(target: &Path, dir: &str, features: &str, rustflags: &str) {
+        let unit_dir = target.join("debug/.fingerprint").join(dir);
+        std::fs::create_dir_all(&unit_dir).unwrap();
+        let json = format!(
+            r#"{{"rustc":1,"features":"{}","profile":7,"path":1,"deps":[[1,"serde",false,42]],"local":[{{"RerunIfEnvChanged":{{"var":"DEMO_MODE","val":null}}}}],"rustflags":{},"config":3,"compile_kind":0}}"#,
+            features, rustflags
+        );
+        std::fs::write(unit_dir.join("lib-demo.json"), json).unwrap();
+    }
+    #[test]
+    fn test_rebuild_causes_from_fingerprints() {
+        let temp = tempfile::tempdir().unwrap();
+        let target = temp.path().join("target");
+        let tool = CacheAnalyzerTool::new();
+        write_unit(&target, "demo-0000000000000001", "[\\\"std\\\"]", "[]");
+        let (events, units) = tool.analyze_rebuilds(&target, &RebuildHistory::default());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].causes[0].kind, "new");
+        let history = RebuildHistory {
+            last_scan: units[0].built_at,
+            units: units
+                .into_iter()
+                .map(|unit| (unit.id.clone(), unit))
+                .collect(),
+            ..Default::default()
+        };
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        write_unit(
+            &target,
+            "demo-0000000000000002",
+            "[\\\"std\\\", \\\"derive\\\"]",
+            "[\"-Cdebuginfo=0\"]",
+        );
+        let (events, _) = tool.analyze_rebuilds(&target, &history);
+        assert_eq!(events.len(), 1);
+        let kinds: Vec<&str> = events[0].causes.iter().map(|c| c.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["features", "rustflags"]);
+        let build = BuildRecord {
+            recorded_at: 0,
+            events,
+        };
+        let summaries = tool.aggregate_rebuild_causes(&[build.clone(), build]);
+        assert_eq!(summaries[0].rebuilds, 2);
+        assert_eq!(summaries[0].builds, 2);
+    }
+}
@@ -2,8 +2,10 @@ use super::{Tool, Result, ToolError, common_options, parse_output_format, Output
 use clap::{Arg, ArgMatches, Command};
 use colored::*;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::Command as ProcessCommand;
 use std::fs;
+use sha2::{Digest, Sha256};
 use regex::Regex;
 use walkdir::WalkDir;
 use serde::{Serialize, Deserialize};
@@ -30,6 +32,36 @@ pub struct CodeAnalysis {
     pub largest_function: Option<FunctionMetrics>,
     pub functions: Vec<FunctionMetrics>,
 }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplexitySnapshot {
+    pub commit: String,
+    pub dirty: bool,
+    pub timestamp: String,
+    pub functions: HashMap<String, f64>,
+}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComplexityHistory {
+    pub project: String,
+    pub snapshots: Vec<ComplexitySnapshot>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplexityTrend {
+    pub function: String,
+    pub first: Option<f64>,
+    pub previous: Option<f64>,
+    pub current: Option<f64>,
+    pub delta: f64,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hotspot {
+    pub file: String,
+    pub churn: usize,
+    pub functions: usize,
+    pub total_complexity: f64,
+    pub max_complexity: f64,
+    pub score: f64,
+}
+const MAX_COMPLEXITY_SNAPSHOTS: usize = 100;
 impl CodeAnalyzer {
     pub fn new() -> Self {
         Self
@@ -118,6 +150,254 @@ impl CodeAnalyzer {
             functions: all_functions,
         })
     }
+    fn git(&self, dir: &Path, args: &[&str]) -> Option<String> {
+        let output = ProcessCommand::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+    fn repo_root(&self, path: &str) -> Result<PathBuf> {
+        let dir = if Path::new(path).is_file() {
+            Path::new(path).parent().unwrap_or(Path::new(".")).to_path_buf()
+        } else {
+            PathBuf::from(path)
+        };
+        self.git(&dir, &["rev-parse", "--show-toplevel"])
+            .map(PathBuf::from)
+            .ok_or_else(|| ToolError::ExecutionFailed(
+                format!("{} is not inside a git repository", path),
+            ))
+    }
+    fn relative_file(root: &Path, file: &str) -> String {
+        let canonical = Path::new(file)
+            .canonicalize()
+            .unwrap_or_else(|_| PathBuf::from(file));
+        canonical
+            .strip_prefix(root)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| file.to_string())
+    }
+    fn complexity_history_path(&self, root: &Path) -> Option<PathBuf> {
+        let digest = format!("{:x}", Sha256::digest(root.to_string_lossy().as_bytes()));
+        Some(
+            dirs::home_dir()?
+                .join(".shipwreck")
+                .join("complexity")
+                .join(format!("{}.json", &digest[..16])),
+        )
+    }
+    fn load_history(&self, root: &Path) -> ComplexityHistory {
+        self.complexity_history_path(root)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(|| ComplexityHistory {
+                project: root.to_string_lossy().to_string(),
+                snapshots: Vec::new(),
+            })
+    }
+    fn take_snapshot(&self, root: &Path, analysis: &CodeAnalysis) -> ComplexitySnapshot {
+        let commit = self
+            .git(root, &["rev-parse", "HEAD"])
+            .unwrap_or_else(|| "unknown".to_string());
+        let dirty = self
+            .git(root, &["status", "--porcelain", "--untracked-files=no"])
+            .is_some_and(|status| !status.is_empty());
+        let functions = analysis
+            .functions
+            .iter()
+            .map(|f| {
+                (
+                    format!("{}::{}", Self::relative_file(root, & f.file), f.name),
+                    (f.complexity * 100.0).round() / 100.0,
+                )
+            })
+            .collect();
+        ComplexitySnapshot {
+            commit,
+            dirty,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            functions,
+        }
+    }
+    fn record_snapshot(
+        &self,
+        root: &Path,
+        history: &mut ComplexityHistory,
+        snapshot: ComplexitySnapshot,
+    ) -> Result<()> {
+        history.snapshots.retain(|s| s.commit != snapshot.commit);
+        history.snapshots.push(snapshot);
+        let excess = history.snapshots.len().saturating_sub(MAX_COMPLEXITY_SNAPSHOTS);
+        history.snapshots.drain(..excess);
+        let path = self
+            .complexity_history_path(root)
+            .ok_or_else(|| ToolError::ConfigError(
+                "Could not determine home directory".to_string(),
+            ))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(history)?)?;
+        Ok(())
+    }
+    fn complexity_trends(
+        &self,
+        history: &ComplexityHistory,
+        current: &ComplexitySnapshot,
+    ) -> Vec<ComplexityTrend> {
+        let earlier: Vec<&ComplexitySnapshot> = history
+            .snapshots
+            .iter()
+            .filter(|s| s.commit != current.commit)
+            .collect();
+        let (Some(first), Some(previous)) = (earlier.first(), earlier.last()) else {
+            return Vec::new();
+        };
+        let mut names: Vec<&String> = current.functions.keys().collect();
+        names.extend(previous.functions.keys());
+        names.sort();
+        names.dedup();
+        let mut trends: Vec<ComplexityTrend> = names
+            .into_iter()
+            .map(|name| {
+                let first = first.functions.get(name).copied();
+                let previous = previous.functions.get(name).copied();
+                let current = current.functions.get(name).copied();
+                let baseline = first.or(previous).unwrap_or(0.0);
+                ComplexityTrend {
+                    function: name.clone(),
+                    delta: current.unwrap_or(0.0) - baseline,
+                    first,
+                    previous,
+                    current,
+                }
+            })
+            .filter(|t| t.delta.abs() >= 0.01)
+            .collect();
+        trends
+            .sort_by(|a, b| {
+                b.delta.abs().partial_cmp(&a.delta.abs()).unwrap_or(std::cmp::Ordering::Equal)
+            });
+        trends
+    }
+    fn file_churn(&self, root: &Path, since: &str) -> HashMap<String, usize> {
+        let mut churn = HashMap::new();
+        let log = self
+            .git(
+                root,
+                &["log", &format!("--since={}", since), "--name-only", "--pretty=format:"],
+            )
+            .unwrap_or_default();
+        for file in log.lines().map(str::trim).filter(|l| l.ends_with(".rs")) {
+            *churn.entry(file.to_string()).or_insert(0) += 1;
+        }
+        churn
+    }
+    fn compute_hotspots(
+        &self,
+        functions: &[(String, f64)],
+        churn: &HashMap<String, usize>,
+    ) -> Vec<Hotspot> {
+        let mut by_file: HashMap<&str, (usize, f64, f64)> = HashMap::new();
+        for (file, complexity) in functions {
+            let entry = by_file.entry(file.as_str()).or_insert((0, 0.0, 0.0));
+            entry.0 += 1;
+            entry.1 += complexity;
+            entry.2 = entry.2.max(*complexity);
+        }
+        let max_churn = churn.values().copied().max().unwrap_or(0).max(1) as f64;
+        let max_total = by_file.values().map(|v| v.1).fold(0.0, f64::max).max(1.0);
+        let mut hotspots: Vec<Hotspot> = by_file
+            .into_iter()
+            .filter_map(|(file, (count, total, max))| {
+                let changes = churn.get(file).copied().unwrap_or(0);
+                (changes > 0)
+                    .then(|| Hotspot {
+                        file: file.to_string(),
+                        churn: changes,
+                        functions: count,
+                        total_complexity: (total * 100.0).round() / 100.0,
+                        max_complexity: (max * 100.0).round() / 100.0,
+                        score: ((changes as f64 / max_churn) * (total / max_total) * 1000.0)
+                            .round() / 10.0,
+                    })
+            })
+            .collect();
+        hotspots
+            .sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        hotspots
+    }
+    fn display_trends(
+        &self,
+        history: &ComplexityHistory,
+        trends: &[ComplexityTrend],
+        top: usize,
+    ) {
+        println!("\n📈 {} - Complexity Trends", "CargoMate CodeAnalyzer".bold().blue());
+        println!("{}", "═".repeat(50).blue());
+        if history.snapshots.len() < 2 {
+            println!(
+                "  💡 Only {} snapshot(s) recorded; run with --track on more commits to see trends",
+                history.snapshots.len()
+            );
+        }
+        for snapshot in history.snapshots.iter().rev().take(5) {
+            let total: f64 = snapshot.functions.values().sum();
+            println!(
+                "  • {}{} {} functions, total complexity {:.1}", & snapshot.commit[..snapshot
+                .commit.len().min(10)], if snapshot.dirty { "*" } else { "" }, snapshot
+                .functions.len(), total
+            );
+        }
+        if trends.is_empty() {
+            println!("\n✅ No complexity changes since the first recorded snapshot");
+            return;
+        }
+        println!("\n🔍 Biggest changes:");
+        for trend in trends.iter().take(top) {
+            let delta = format!("{:+.2}", trend.delta);
+            let status = match (trend.first.or(trend.previous), trend.current) {
+                (None, Some(_)) => "new".cyan(),
+                (Some(_), None) => "removed".dimmed(),
+                _ if trend.delta > 0.0 => delta.red(),
+                _ => delta.green(),
+            };
+            println!(
+                "  • {} {:.2} → {:.2} ({})", trend.function, trend.first.or(trend.previous)
+                .unwrap_or(0.0), trend.current.unwrap_or(0.0), status
+            );
+        }
+    }
+    fn display_hotspots(&self, hotspots: &[Hotspot], since: &str, top: usize) {
+        println!("\n🔥 {} - Hotspots", "CargoMate CodeAnalyzer".bold().blue());
+        println!("{}", "═".repeat(50).blue());
+        println!("  Complexity × churn since {}\n", since);
+        if hotspots.is_empty() {
+            println!("  No analyzed files changed in this period");
+            return;
+        }
+        for (index, hotspot) in hotspots.iter().take(top).enumerate() {
+            let score = format!("{:.1}", hotspot.score);
+            let score = if hotspot.score > 50.0 {
+                score.red()
+            } else if hotspot.score > 20.0 {
+                score.yellow()
+            } else {
+                score.green()
+            };
+            println!(
+                "  {}. {} (risk {}, {} changes, {} functions, max complexity {:.2})",
+                index + 1, hotspot.file.cyan(), score, hotspot.churn, hotspot.functions,
+                hotspot.max_complexity
+            );
+        }
+    }
     fn display_analysis(&self, analysis: &CodeAnalysis, verbose: bool) {
         println!(
             "\n📊 {} - Code Analysis Report", "CargoMate CodeAnalyzer".bold().blue()
@@ -154,6 +434,75 @@ impl CodeAnalyzer {
         }
         println!("\n✅ Analysis complete!");
     }
+    fn run_history_reports(
+        &self,
+        path: &str,
+        analysis: &CodeAnalysis,
+        matches: &ArgMatches,
+        output_format: &str,
+    ) -> Result<()> {
+        let root = self.repo_root(path)?;
+        let top = *matches.get_one::<usize>("top").unwrap();
+        let since = matches.get_one::<String>("since").unwrap();
+        let mut history = self.load_history(&root);
+        let snapshot = self.take_snapshot(&root, analysis);
+        let mut report = serde_json::Map::new();
+        if matches.get_flag("trend") {
+            let mut trends = self.complexity_trends(&history, &snapshot);
+            trends.truncate(top);
+            if output_format == "json" {
+                report.insert("trends".to_string(), serde_json::to_value(&trends)?);
+            } else {
+                self.display_trends(&history, &trends, top);
+            }
+        }
+        if matches.get_flag("hotspots") {
+            let functions: Vec<(String, f64)> = analysis
+                .functions
+                .iter()
+                .map(|f| (Self::relative_file(&root, &f.file), f.complexity))
+                .collect();
+            let mut hotspots = self
+                .compute_hotspots(&functions, &self.file_churn(&root, since));
+            hotspots.truncate(top);
+            if output_format == "json" {
+                report.insert("hotspots".to_string(), serde_json::to_value(&hotspots)?);
+            } else if output_format == "table" {
+                println!(
+                    "{:<50} {:<8} {:<8} {:<10} {:<8}", "File", "Churn", "Fns", "MaxCmplx",
+                    "Risk"
+                );
+                println!("{}", "─".repeat(88));
+                for hotspot in hotspots.iter().take(top) {
+                    println!(
+                        "{:<50} {:<8} {:<8} {:<10.2} {:<8.1}", hotspot.file, hotspot.churn,
+                        hotspot.functions, hotspot.max_complexity, hotspot.score
+                    );
+                }
+            } else {
+                self.display_hotspots(&hotspots, since, top);
+            }
+        }
+        if matches.get_flag("track") {
+            let commit = snapshot.commit.clone();
+            let dry_run = matches.get_flag("dry-run");
+            if !dry_run {
+                self.record_snapshot(&root, &mut history, snapshot)?;
+            }
+            if output_format == "json" {
+                report.insert("tracked_commit".to_string(), serde_json::json!(commit));
+            } else {
+                println!(
+                    "\n💾 {} complexity snapshot for {}", if dry_run { "Would record" } else {
+                    "Recorded" }, & commit[..commit.len().min(10)]
+                );
+            }
+        }
+        if output_format == "json" {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        Ok(())
+    }
 }
 impl Tool for CodeAnalyzer {
     fn name(&self) -> &'static str {
@@ -180,6 +529,27 @@ impl Tool for CodeAnalyzer {
                         .short('f')
                         .help("Output format (human, json, table)")
                         .default_value("human"),
+                    Arg::new("track")
+                        .long("track")
+                        .help("Record per-function complexity for the current git commit")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("trend")
+                        .long("trend")
+                        .help("Show complexity changes across recorded commits")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("hotspots")
+                        .long("hotspots")
+                        .help("Rank files by complexity combined with git churn")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("since")
+                        .long("since")
+                        .help("Git history window for churn")
+                        .default_value("6 months ago"),
+                    Arg::new("top")
+                        .long("top")
+                        .help("Number of entries to show in trend and hotspot reports")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("10"),
                 ],
             )
             .args(&common_options())
@@ -201,6 +571,12 @@ impl Tool for CodeAnalyzer {
             println!("{}", "No Rust functions found to analyze".yellow());
             return Ok(());
         }
+        let track = matches.get_flag("track");
+        let trend = matches.get_flag("trend");
+        let hotspots = matches.get_flag("hotspots");
+        if track || trend || hotspots {
+            return self.run_history_reports(path, &analysis, matches, output_format);
+        }
         match output_format.as_str() {
             "json" => {
                 let json = serde_json::to_string_pretty(&analysis)?;
@@ -232,4 +608,54 @@ impl Default for CodeAnalyzer {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_trends_and_hotspots() {
+        let analyzer = CodeAnalyzer::new();
+        let snapshot = |commit: &str, values: &[(&str, f64)]| ComplexitySnapshot {
+            commit: commit.to_string(),
+            dirty: false,
+            timestamp: String::new(),
+            functions: values.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        };
+        let history = ComplexityHistory {
+            project: "demo".to_string(),
+            snapshots: vec![
+                snapshot(
+                    "a1",
+                    &[("src/lib.rs::parse", 2.0), ("src/lib.rs::old", 1.0)],
+                ),
+                snapshot("b2", &[("src/lib.rs::parse", 4.0)]),
+            ],
+        };
+        let current = snapshot(
+            "c3",
+            &[("src/lib.rs::parse", 7.5), ("src/lib.rs::render", 1.5)],
+        );
+        let trends = analyzer.complexity_trends(&history, &current);
+        assert_eq!(trends[0].function, "src/lib.rs::parse");
+        assert_eq!(trends[0].delta, 5.5);
+        assert_eq!(trends[0].previous, Some(4.0));
+        assert!(trends.iter().any(|t| t.function == "src/lib.rs::render"));
+        let functions = vec![
+            ("src/lib.rs".to_string(), 7.5),
+            ("src/lib.rs".to_string(), 1.5),
+            ("src/util.rs".to_string(), 9.0),
+            ("src/stable.rs".to_string(), 20.0),
+        ];
+        let churn: HashMap<String, usize> = [
+            ("src/lib.rs".to_string(), 10),
+            ("src/util.rs".to_string(), 2),
+        ]
+        .into_iter()
+        .collect();
+        let hotspots = analyzer.compute_hotspots(&functions, &churn);
+        assert_eq!(hotspots.len(), 2);
+        assert_eq!(hotspots[0].file, "src/lib.rs");
+        assert_eq!(hotspots[0].functions, 2);
+        assert_eq!(hotspots[0].score, 45.0);
+    }
+}
@@ -0,0 +1,419 @@
+use super::{Tool, Result, ToolError, common_options, parse_output_format, OutputFormat};
+use crate::lockdiff::{self, LockPackage};
+use crate::outdated::{classify, UpdateKind};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+#[derive(Debug, Clone)]
+pub struct DedupeTool;
+/// How to get rid of one older copy of a duplicated crate
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Fix {
+    /// A workspace crate asks for the old version itself
+    BumpOwn { member: String, requirement: String },
+    /// A newer release of a direct dependency accepts the newest version
+    BumpDirect { name: String, from: String, to: String },
+    /// A semver-compatible release of a transitive dependency does, so only Cargo.lock changes
+    Update { name: String, from: String, to: String },
+    /// A release exists, but an intermediate crate pins the old one
+    Blocked { name: String, to: String, by: String },
+    /// No release accepts the newest version yet
+    Patch { name: String, snippet: String },
+    /// The crates.io index could not be read for the parent
+    Unknown { name: String },
+}
+#[derive(Debug, Clone, Serialize)]
+struct OldCopy {
+    version: String,
+    /// From the crate that depends on this copy up to the workspace member
+    chain: Vec<String>,
+    fix: Fix,
+    build_seconds: Option<f64>,
+    rlib_bytes: Option<u64>,
+}
+#[derive(Debug, Clone, Serialize)]
+struct Duplicate {
+    name: String,
+    versions: Vec<String>,
+    copies: Vec<OldCopy>,
+}
+/// One published version from a crates.io index file
+#[derive(Debug, Clone)]
+struct IndexRelease {
+    version: Version,
+    /// Real crate name to requirement, normal and build dependencies only
+    requirements: HashMap<String, String>,
+}
+fn parse_releases(body: &str) -> Vec<IndexRelease> {
+    body.lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|entry| !entry["yanked"].as_bool().unwrap_or(false))
+        .filter_map(|entry| {
+            let version = Version::parse(entry["vers"].as_str()?).ok()?;
+            if !version.pre.is_empty() {
+                return None;
+            }
+            let requirements = entry["deps"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|dep| dep["kind"].as_str() != Some("dev"))
+                .filter_map(|dep| {
+                    let name = dep["package"].as_str().or_else(|| dep["name"].as_str())?;
+                    Some((name.to_string(), dep["req"].as_str()?.to_string()))
+                })
+                .collect();
+            Some(IndexRelease { version, requirements })
+        })
+        .collect()
+}
+fn split_id(id: &str) -> (&str, &str) {
+    id.split_once(' ').unwrap_or((id, ""))
+}
+/// The oldest release of `parent` newer than `current` whose requirement on `name` accepts `newest`
+fn accepting_release(releases: &[IndexRelease], current: &Version, name: &str, newest: &Version) -> Option<Version> {
+    releases
+        .iter()
+        .filter(|release| &release.version > current)
+        .filter(|release| {
+            release
+                .requirements
+                .get(name)
+                .and_then(|req| VersionReq::parse(req).ok())
+                .is_some_and(|req| req.matches(newest))
+        })
+        .map(|release| release.version.clone())
+        .min()
+}
+fn find_fix(
+    name: &str,
+    newest: &Version,
+    chain: &[String],
+    releases: &mut dyn FnMut(&str) -> Vec<IndexRelease>,
+    repositories: &HashMap<String, String>,
+) -> Fix {
+    let Some(parent) = chain.first() else {
+        return Fix::Patch { name: name.to_string(), snippet: String::new() };
+    };
+    let (parent_name, parent_version) = split_id(parent);
+    if chain.len() == 1 {
+        return Fix::BumpOwn { member: parent_name.to_string(), requirement: newest.to_string() };
+    }
+    let published = releases(parent_name);
+    if published.is_empty() {
+        return Fix::Unknown { name: parent_name.to_string() };
+    }
+    let current = Version::parse(parent_version).ok();
+    let found = current
+        .as_ref()
+        .and_then(|current| accepting_release(&published, current, name, newest).map(|v| (current, v)));
+    match found {
+        Some((_, to)) if chain.len() == 2 => Fix::BumpDirect {
+            name: parent_name.to_string(),
+            from: parent_version.to_string(),
+            to: to.to_string(),
+        },
+        Some((current, to)) if classify(current, &to).is_some_and(|kind| kind != UpdateKind::Major) => Fix::Update {
+            name: parent_name.to_string(),
+            from: parent_version.to_string(),
+            to: to.to_string(),
+        },
+        Some((_, to)) => {
+            // The crate pinning the parent may itself have a release that takes the newer parent
+            match find_fix(parent_name, &to, &chain[1..], releases, repositories) {
+                fix @ (Fix::BumpDirect { .. } | Fix::Update { .. }) => fix,
+                _ => Fix::Blocked { name: parent_name.to_string(), to: to.to_string(), by: chain[1].clone() },
+            }
+        }
+        None => {
+            let source = repositories
+                .get(parent_name)
+                .map(|repo| format!("git = \"{}\"", repo))
+                .unwrap_or_else(|| "path = \"../".to_string() + parent_name + "\"");
+            Fix::Patch {
+                name: parent_name.to_string(),
+                snippet: format!(
+                    "[patch.crates-io]\n{} = {{ {} }} # a branch where {} is bumped to {}",
+                    parent_name, source, name, newest
+                ),
+            }
+        }
+    }
+}
+/// `name-1.2.3` from a registry source directory
+fn split_source_dir(dir: &str) -> Option<(String, String)> {
+    dir.match_indices('-').find_map(|(index, _)| {
+        let version = &dir[index + 1..];
+        Version::parse(version).ok().map(|_| (dir[..index].to_string(), version.to_string()))
+    })
+}
+/// Size of each registry crate's rlib in target/, matched to its version through the
+/// dep-info file rustc writes next to it
+fn rlib_sizes(target_dir: &Path) -> HashMap<(String, String), u64> {
+    let mut sizes = HashMap::new();
+    for profile in ["debug", "release"] {
+        let Ok(entries) = fs::read_dir(target_dir.join(profile).join("deps")) else {
+            continue;
+        };
+        for path in entries.flatten().map(|e| e.path()).filter(|p| p.extension().is_some_and(|e| e == "d")) {
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let Ok(size) = fs::metadata(path.with_file_name(format!("lib{}.rlib", stem))).map(|m| m.len()) else {
+                continue;
+            };
+            let Ok(info) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let package = info
+                .split_whitespace()
+                .find_map(|token| token.split_once("/registry/src/").map(|(_, rest)| rest.to_string()))
+                .and_then(|rest| rest.split('/').nth(1).and_then(split_source_dir));
+            if let Some(key) = package {
+                let entry = sizes.entry(key).or_insert(0);
+                *entry = (*entry).max(size);
+            }
+        }
+    }
+    sizes
+}
+/// Seconds per (name, version) from the newest `cargo build --timings` report
+fn unit_timings() -> HashMap<(String, String), f64> {
+    let mut timings = HashMap::new();
+    let newest = crate::tide_graph::timing_files(&crate::tide_report::timings_dir()).pop();
+    let units = newest.and_then(|file| fs::read_to_string(file).ok()).and_then(|html| crate::tide_graph::parse_units(&html));
+    for unit in units.unwrap_or_default() {
+        *timings.entry((unit.name, unit.version)).or_insert(0.0) += unit.duration;
+    }
+    timings
+}
+fn find_duplicates(packages: &[LockPackage], only: Option<&str>) -> BTreeMap<String, Vec<Version>> {
+    let mut versions: BTreeMap<String, Vec<Version>> = BTreeMap::new();
+    for package in packages.iter().filter(|p| p.source.is_some()) {
+        if let Ok(version) = Version::parse(&package.version) {
+            versions.entry(package.name.clone()).or_default().push(version);
+        }
+    }
+    versions.retain(|name, versions| versions.len() > 1 && only.is_none_or(|only| only == name));
+    for versions in versions.values_mut() {
+        versions.sort();
+    }
+    versions
+}
+impl DedupeTool {
+    pub fn new() -> Self {
+        Self
+    }
+    fn analyze(&self, only: Option<&str>) -> anyhow::Result<Vec<Duplicate>> {
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .exec()
+            .map_err(|e| anyhow::anyhow!("Failed to read cargo metadata: {}", e))?;
+        let root = metadata.workspace_root.as_std_path();
+        let packages = lockdiff::parse_lock(&fs::read_to_string(root.join("Cargo.lock"))?)?;
+        let repositories: HashMap<String, String> = metadata
+            .packages
+            .iter()
+            .filter_map(|p| p.repository.clone().map(|repo| (p.name.clone(), repo)))
+            .collect();
+        let sizes = rlib_sizes(metadata.target_directory.as_std_path());
+        let timings = unit_timings();
+        let client = crate::outdated::client();
+        let mut index_cache: HashMap<String, Vec<IndexRelease>> = HashMap::new();
+        let mut releases = |name: &str| -> Vec<IndexRelease> {
+            index_cache
+                .entry(name.to_string())
+                .or_insert_with(|| match crate::outdated::index_body(&client, name) {
+                    Ok(body) => parse_releases(&body),
+                    Err(e) => {
+                        eprintln!("⚠️  Could not read the crates.io index for {}: {}", name, e);
+                        Vec::new()
+                    }
+                })
+                .clone()
+        };
+        let mut duplicates = Vec::new();
+        for (name, versions) in find_duplicates(&packages, only) {
+            let newest = versions.last().cloned().expect("duplicates have two or more versions");
+            let copies = versions[..versions.len() - 1]
+                .iter()
+                .filter_map(|version| {
+                    let version = version.to_string();
+                    let package = packages.iter().find(|p| p.name == name && p.version == version)?;
+                    let chain = lockdiff::introduction_path(&packages, package);
+                    let key = (name.clone(), version.clone());
+                    Some(OldCopy {
+                        fix: find_fix(&name, &newest, &chain, &mut releases, &repositories),
+                        chain,
+                        build_seconds: timings.get(&key).copied(),
+                        rlib_bytes: sizes.get(&key).copied(),
+                        version,
+                    })
+                })
+                .collect();
+            duplicates.push(Duplicate { versions: versions.iter().map(Version::to_string).collect(), name, copies });
+        }
+        Ok(duplicates)
+    }
+    fn describe(fix: &Fix) -> String {
+        match fix {
+            Fix::BumpOwn { member, requirement } => {
+                format!("⬆️  Raise {}'s own requirement to \"{}\" in its Cargo.toml", member, requirement)
+            }
+            Fix::BumpDirect { name, from, to } => format!("⬆️  Bump {} {} → {} in Cargo.toml", name, from, to),
+            Fix::Update { name, from, to } => {
+                format!("🔄 cargo update -p {}@{} --precise {}", name, from, to)
+            }
+            Fix::Blocked { name, to, by } => format!("⏳ {} {} would converge it, but {} pins the older one", name, to, by),
+            Fix::Patch { snippet, .. } if snippet.is_empty() => "🩹 No release converges it yet".to_string(),
+            Fix::Patch { snippet, .. } => format!("🩹 No release converges it yet; patch it:\n         {}", snippet.replace('\n', "\n         ")),
+            Fix::Unknown { name } => format!("❔ Could not read the crates.io index for {}; rerun online", name),
+        }
+    }
+    fn display(&self, duplicates: &[Duplicate], format: OutputFormat) -> Result<()> {
+        if let OutputFormat::Json = format {
+            println!("{}", serde_json::to_string_pretty(duplicates)?);
+            return Ok(());
+        }
+        if duplicates.is_empty() {
+            println!("{}", "✅ Every crate appears at a single version".green());
+            return Ok(());
+        }
+        let extra: usize = duplicates.iter().map(|d| d.copies.len()).sum();
+        println!(
+            "{}",
+            format!("🧬 Duplicate dependencies ({} crates, {} extra copies)", duplicates.len(), extra).bold().blue()
+        );
+        println!("{}", "═".repeat(50).blue());
+        let (mut seconds, mut bytes) = (0.0, 0u64);
+        for duplicate in duplicates {
+            println!("📦 {} {}", duplicate.name.bold(), duplicate.versions.join(", "));
+            for copy in &duplicate.copies {
+                println!("   {} ← {}", copy.version.yellow(), copy.chain.join(" ← "));
+                println!("      {}", Self::describe(&copy.fix));
+                let mut savings = Vec::new();
+                if let Some(s) = copy.build_seconds {
+                    savings.push(format!("~{:.1}s build", s));
+                    seconds += s;
+                }
+                if let Some(b) = copy.rlib_bytes {
+                    savings.push(format!("{:.1} MB rlib", b as f64 / 1_048_576.0));
+                    bytes += b;
+                }
+                if !savings.is_empty() {
+                    println!("      💾 {}", savings.join(", "));
+                }
+            }
+        }
+        println!();
+        println!(
+            "Converging everything saves about {:.1}s of build time and {:.1} MB of rlibs (binaries shrink by what the linker keeps)",
+            seconds,
+            bytes as f64 / 1_048_576.0
+        );
+        if seconds == 0.0 {
+            println!("💡 Run 'cargo build --timings' once for per-crate build time estimates");
+        }
+        Ok(())
+    }
+}
+impl Tool for DedupeTool {
+    fn name(&self) -> &'static str {
+        "dedupe"
+    }
+    fn description(&self) -> &'static str {
+        "Find crates built at several versions and what it takes to converge them"
+    }
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about(self.description())
+            .long_about(
+                "List crates that appear in Cargo.lock at more than one version, the dependency chain that forces each older copy, and the Cargo.toml bump, cargo update or [patch] entry that would remove it",
+            )
+            .args(
+                &[
+                    Arg::new("crate")
+                        .help("Only look at this crate")
+                        .index(1),
+                ],
+            )
+            .args(common_options())
+    }
+    fn execute(&self, matches: &ArgMatches) -> Result<()> {
+        let only = matches.get_one::<String>("crate").map(String::as_str);
+        let format = parse_output_format(matches);
+        if !matches!(format, OutputFormat::Json) {
+            println!("🔍 {} - Looking for duplicate crates", "CargoMate Dedupe".bold().blue());
+        }
+        let duplicates = self
+            .analyze(only)
+            .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+        self.display(&duplicates, format)
+    }
+}
+impl Default for DedupeTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn package(name: &str, version: &str, deps: &[&str], registry: bool) -> LockPackage {
+        LockPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: registry.then(|| "registry+https://github.com/rust-lang/crates.io-index".to_string()),
+            dependencies: deps.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+    #[test]
+    fn test_chain_and_fixes() {
+        let packages = vec![
+            package("app", "0.1.0", &["darling", "syn 2.0.72", "thiserror"], false),
+            package("darling", "0.13.4", &["darling_core"], true),
+            package("darling_core", "0.13.4", &["syn 1.0.109"], true),
+            package("thiserror", "1.0.60", &["syn 2.0.72"], true),
+            package("syn", "1.0.109", &[], true),
+            package("syn", "2.0.72", &[], true),
+        ];
+        let duplicates = find_duplicates(&packages, None);
+        assert_eq!(duplicates.keys().collect::<Vec<_>>(), vec!["syn"]);
+        let old = packages.iter().find(|p| p.name == "syn" && p.version == "1.0.109").unwrap();
+        let chain = lockdiff::introduction_path(&packages, old);
+        assert_eq!(chain, vec!["darling_core 0.13.4", "darling 0.13.4", "app 0.1.0"]);
+        let newest = Version::parse("2.0.72").unwrap();
+        let index = r#"{"vers":"0.13.4","deps":[{"name":"syn","req":"^1.0.91","kind":"normal"}],"yanked":false}
+{"vers":"0.14.0","deps":[{"name":"syn","req":"^1.0.91","kind":"normal"}],"yanked":false}
+{"vers":"0.20.0","deps":[{"name":"syn","req":"^2.0.15","kind":"normal"}],"yanked":false}
+{"vers":"0.20.1","deps":[{"name":"syn","req":"^2.0.15","kind":"normal"}],"yanked":false}"#;
+        let mut releases = |_: &str| parse_releases(index);
+        assert_eq!(
+            find_fix("syn", &newest, &chain, &mut releases, &HashMap::new()),
+            Fix::Blocked { name: "darling_core".into(), to: "0.20.0".into(), by: "darling 0.13.4".into() }
+        );
+        let darling = r#"{"vers":"0.20.0","deps":[{"name":"darling_core","req":"=0.20.0","kind":"normal"}],"yanked":false}"#;
+        let mut upstream = |name: &str| parse_releases(if name == "darling" { darling } else { index });
+        assert_eq!(
+            find_fix("syn", &newest, &chain, &mut upstream, &HashMap::new()),
+            Fix::BumpDirect { name: "darling".into(), from: "0.13.4".into(), to: "0.20.0".into() }
+        );
+        assert_eq!(
+            find_fix("syn", &newest, &chain[1..], &mut releases, &HashMap::new()),
+            Fix::BumpDirect { name: "darling".into(), from: "0.13.4".into(), to: "0.20.0".into() }
+        );
+        let mut none = |_: &str| Vec::new();
+        assert_eq!(find_fix("syn", &newest, &chain[1..], &mut none, &HashMap::new()), Fix::Unknown { name: "darling".into() });
+        let repositories = HashMap::from([("darling".to_string(), "https://github.com/TedDriggs/darling".to_string())]);
+        let Fix::Patch { snippet, .. } = find_fix("quote", &newest, &chain[1..], &mut releases, &repositories) else {
+            panic!("expected a patch");
+        };
+        assert!(snippet.starts_with("[patch.crates-io]\ndarling = { git = \"https://github.com/TedDriggs/darling\" } # a branch where quote"));
+        assert_eq!(split_source_dir("wasm-bindgen-0.2.92"), Some(("wasm-bindgen".into(), "0.2.92".into())));
+    }
+}
@@ -1,11 +1,17 @@
 use super::{Tool, Result, ToolError, common_options, parse_output_format, OutputFormat};
 use clap::{Arg, ArgMatches, Command};
 use colored::*;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
+use std::ops::Range;
 use std::path::Path;
-use syn::{parse_file, File, Item, ItemFn, ItemImpl, ReturnType, Type, visit::Visit};
+use syn::{
+    parse_file, File, Item, ItemFn, ItemImpl, ReturnType, Type, visit::Visit, Expr,
+    ExprLit, GenericArgument, ImplItemFn, Lit, Macro, PathArguments, Signature,
+    TraitItemFn, UseTree, punctuated::Punctuated, spanned::Spanned,
+};
 use quote::quote;
-use proc_macro2::TokenStream;
+use proc_macro2::Span;
 #[derive(Debug, Clone)]
 pub struct ErrorDeriveTool;
 #[derive(Debug, Clone)]
@@ -20,6 +26,66 @@ struct ErrorAnalysis {
     error_types: Vec<String>,
     error_handling_patterns: Vec<String>,
 }
+#[derive(Debug, Clone)]
+struct MigrationSite {
+    file: String,
+    line: usize,
+    function: String,
+    kind: String,
+    category: String,
+    message: String,
+}
+#[derive(Debug, Clone)]
+struct TextEdit {
+    range: Range<usize>,
+    replacement: String,
+}
+#[derive(Debug, Clone)]
+struct FileMigration {
+    source: String,
+    edits: Vec<TextEdit>,
+}
+#[derive(Debug, Clone, Default)]
+struct MigrationPlan {
+    sites: Vec<MigrationSite>,
+    files: BTreeMap<String, FileMigration>,
+    manual: Vec<String>,
+}
+const CATEGORY_KEYWORDS: &[(&str, &[&str])] = &[
+    (
+        "NotFound",
+        &["not found", "no such", "missing", "does not exist", "doesn't exist", "unknown"],
+    ),
+    ("Permission", &["permission", "denied", "unauthorized", "forbidden"]),
+    ("Timeout", &["timeout", "timed out"]),
+    (
+        "Network",
+        &["connect", "request", "http", "download", "upload", "network", "url", "fetch"],
+    ),
+    (
+        "Parse",
+        &["parse", "deserialize", "serialize", "decode", "encode", "json", "toml", "yaml"],
+    ),
+    (
+        "Io",
+        &[
+            "read", "write", "open", "create", "file", "directory", "path", "copy",
+            "remove", "delete", "rename",
+        ],
+    ),
+    ("Config", &["config", "setting", "environment", "env var"]),
+    (
+        "Invalid",
+        &["invalid", "must", "expected", "cannot", "can't", "unsupported", "not allowed", "empty"],
+    ),
+];
+const SOURCE_CONVERSIONS: &[(&str, &str, &str)] = &[
+    ("std", "std::io::Error", "Io"),
+    ("serde_json", "serde_json::Error", "Parse"),
+    ("toml", "toml::de::Error", "Parse"),
+    ("serde_yaml", "serde_yaml::Error", "Parse"),
+    ("reqwest", "reqwest::Error", "Network"),
+];
 impl ErrorDeriveTool {
     pub fn new() -> Self {
         Self
@@ -367,6 +433,473 @@ impl ErrorDeriveTool {
         code.push_str("}\n\n");
         Ok(code)
     }
+    fn classify_message(message: &str) -> &'static str {
+        let lower = message.to_lowercase();
+        CATEGORY_KEYWORDS
+            .iter()
+            .find(|(_, keywords)| keywords.iter().any(|k| lower.contains(k)))
+            .map(|(category, _)| *category)
+            .unwrap_or("Other")
+    }
+    fn find_crate_root(input: &Path) -> std::path::PathBuf {
+        let start = if input.is_dir() { input } else { input.parent().unwrap_or(input) };
+        start
+            .ancestors()
+            .find(|dir| dir.join("Cargo.toml").exists())
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+    }
+    fn module_path_for(output: &Path, src_root: &Path) -> String {
+        let relative = output.strip_prefix(src_root).unwrap_or(output).with_extension("");
+        let mut parts: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .filter(|c| c != "." && c != "src")
+            .collect();
+        if parts.len() > 1 && parts.last().map(|p| p == "mod").unwrap_or(false) {
+            parts.pop();
+        }
+        format!("crate::{}", parts.join("::"))
+    }
+    fn collect_library_files(&self, input: &Path, output: &Path) -> Vec<std::path::PathBuf> {
+        let skipped_dirs = ["bin", "tests", "examples", "benches", "target"];
+        let mut files: Vec<std::path::PathBuf> = walkdir::WalkDir::new(input)
+            .into_iter()
+            .filter_entry(|entry| {
+                !(entry.file_type().is_dir() && entry.depth() > 0
+                    && skipped_dirs.iter().any(|d| entry.file_name() == *d))
+            })
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.into_path())
+            .filter(|path| path.extension().map(|e| e == "rs").unwrap_or(false))
+            .filter(|path| {
+                let name = path.file_name().unwrap_or_default();
+                name != "main.rs" && name != "build.rs"
+            })
+            .filter(|path| !Self::same_file(path, output))
+            .collect();
+        files.sort();
+        files
+    }
+    fn same_file(a: &Path, b: &Path) -> bool {
+        match (a.canonicalize(), b.canonicalize()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => a == b,
+        }
+    }
+    fn source_conversions(manifest: &str) -> Vec<(&'static str, &'static str)> {
+        let dependencies: BTreeSet<String> = manifest
+            .parse::<toml::Value>()
+            .ok()
+            .and_then(|value| value.get("dependencies").and_then(|d| d.as_table()).cloned())
+            .map(|table| table.keys().cloned().collect())
+            .unwrap_or_default();
+        SOURCE_CONVERSIONS
+            .iter()
+            .filter(|(krate, _, _)| *krate == "std" || dependencies.contains(*krate))
+            .map(|(_, ty, category)| (*ty, *category))
+            .collect()
+    }
+    fn plan_source(
+        &self,
+        source: &str,
+        display: &str,
+        error_name: &str,
+        module: &str,
+        plan: &mut MigrationPlan,
+    ) {
+        let file = match parse_file(source) {
+            Ok(file) => file,
+            Err(e) => {
+                plan.manual.push(format!("{}: could not parse ({}), skipped", display, e));
+                return;
+            }
+        };
+        let mut visitor = AnyhowMigrationVisitor::new(source, display, error_name);
+        let imports = visitor.scan_imports(&file);
+        visitor.visit_file(&file);
+        plan.sites.append(&mut visitor.sites);
+        plan.manual.append(&mut visitor.manual);
+        if visitor.edits.is_empty() {
+            return;
+        }
+        let mut names = vec![error_name.to_string()];
+        if visitor.uses_context {
+            names.push("ErrorContext".to_string());
+        }
+        let import = if names.len() == 1 {
+            format!("use {}::{};", module, names[0])
+        } else {
+            format!("use {}::{{{}}};", module, names.join(", "))
+        };
+        let mut edits = std::mem::take(&mut visitor.edits);
+        match imports.split_first() {
+            Some((first, rest)) if visitor.leftover == 0 => {
+                edits
+                    .push(TextEdit {
+                        range: first.clone(),
+                        replacement: import,
+                    });
+                for range in rest {
+                    let mut end = range.end;
+                    if source[end..].starts_with('\n') {
+                        end += 1;
+                    }
+                    edits
+                        .push(TextEdit {
+                            range: range.start..end,
+                            replacement: String::new(),
+                        });
+                }
+            }
+            _ => {
+                let at = imports
+                    .first()
+                    .map(|range| range.start)
+                    .or_else(|| file.items.first().map(|item| visitor.range_of(item).start))
+                    .unwrap_or(0);
+                edits
+                    .push(TextEdit {
+                        range: at..at,
+                        replacement: format!("{}\n", import),
+                    });
+            }
+        }
+        plan.files
+            .insert(
+                display.to_string(),
+                FileMigration {
+                    source: source.to_string(),
+                    edits,
+                },
+            );
+    }
+    fn apply_edits(source: &str, edits: &[TextEdit]) -> String {
+        let mut edits = edits.to_vec();
+        edits
+            .sort_by(|a, b| {
+                b.range.start.cmp(&a.range.start).then(b.range.end.cmp(&a.range.end))
+            });
+        let mut result = source.to_string();
+        let mut floor = source.len();
+        for edit in edits {
+            if edit.range.end > floor {
+                continue;
+            }
+            result.replace_range(edit.range.clone(), &edit.replacement);
+            floor = edit.range.start;
+        }
+        result
+    }
+    fn generate_migration_module(
+        &self,
+        plan: &MigrationPlan,
+        error_name: &str,
+        conversions: &[(&str, &str)],
+    ) -> String {
+        let mut by_category: BTreeMap<&str, Vec<&MigrationSite>> = BTreeMap::new();
+        for site in plan.sites.iter().filter(|s| s.kind != "signature") {
+            by_category.entry(site.category.as_str()).or_default().push(site);
+        }
+        let mut variants: Vec<&str> = CATEGORY_KEYWORDS
+            .iter()
+            .map(|(category, _)| *category)
+            .filter(|category| {
+                by_category.contains_key(category)
+                    || conversions.iter().any(|(_, c)| c == category)
+            })
+            .collect();
+        variants.push("Other");
+        let mut code = String::new();
+        code.push_str(
+            "//! Error type generated by `cm tool error-derive --migrate` from existing anyhow usage.\n\n",
+        );
+        code.push_str(
+            "pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;\n\n",
+        );
+        code.push_str("#[derive(Debug, thiserror::Error)]\n");
+        code.push_str(&format!("pub enum {} {{\n", error_name));
+        for variant in &variants {
+            if let Some(sites) = by_category.get(variant) {
+                code.push_str(
+                    &format!(
+                        "    /// {} call site{}, e.g. {:?}\n", sites.len(), if sites.len() ==
+                        1 { "" } else { "s" }, sites[0].message
+                    ),
+                );
+            }
+            code.push_str("    #[error(\"{message}\")]\n");
+            code.push_str(&format!("    {} {{\n", variant));
+            code.push_str("        message: String,\n");
+            code.push_str("        #[source]\n");
+            code.push_str("        source: Option<BoxError>,\n");
+            code.push_str("    },\n");
+        }
+        code.push_str("}\n\n");
+        code.push_str(
+            &format!(
+                "/// Replacement for `anyhow::Context`: attaches a typed variant instead of a string.
+pub trait ErrorContext<T> {{
+    fn or_error<F>(self, make: F) -> Result<T, {name}>
+    where
+        F: FnOnce(Option<BoxError>) -> {name};
+}}
+
+impl<T, E> ErrorContext<T> for Result<T, E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{{
+    fn or_error<F>(self, make: F) -> Result<T, {name}>
+    where
+        F: FnOnce(Option<BoxError>) -> {name},
+    {{
+        self.map_err(|err| make(Some(Box::new(err))))
+    }}
+}}
+
+impl<T> ErrorContext<T> for Option<T> {{
+    fn or_error<F>(self, make: F) -> Result<T, {name}>
+    where
+        F: FnOnce(Option<BoxError>) -> {name},
+    {{
+        self.ok_or_else(|| make(None))
+    }}
+}}
+",
+                name = error_name
+            ),
+        );
+        for (ty, category) in conversions {
+            code.push_str(
+                &format!(
+                    "
+impl From<{ty}> for {name} {{
+    fn from(err: {ty}) -> Self {{
+        {name}::{category} {{
+            message: err.to_string(),
+            source: Some(Box::new(err)),
+        }}
+    }}
+}}
+",
+                    ty = ty, name = error_name, category = category
+                ),
+            );
+        }
+        code
+    }
+    fn ensure_module_declared(root_file: &Path, module: &str) -> Result<bool> {
+        let source = fs::read_to_string(root_file)?;
+        let declaration = format!("mod {};", module);
+        if source.lines().any(|line| line.trim().trim_start_matches("pub ") == declaration) {
+            return Ok(false);
+        }
+        let mut offset = 0;
+        for line in source.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("//!") || trimmed.starts_with("#![") {
+                offset += line.len();
+            } else {
+                break;
+            }
+        }
+        let mut updated = source.clone();
+        updated.insert_str(offset, &format!("pub {}\n", declaration));
+        fs::write(root_file, updated)?;
+        Ok(true)
+    }
+    fn apply_migration(
+        &self,
+        plan: &MigrationPlan,
+        module_code: &str,
+        module: &str,
+        output: &Path,
+        crate_root: &Path,
+        manifest: &str,
+    ) -> Result<(String, Vec<String>)> {
+        let anchor = format!(
+            "error-derive-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")
+        );
+        crate::anchor::AnchorManager::new()
+            .and_then(|manager| {
+                manager.save(&anchor, "Backup before anyhow → thiserror migration")
+            })
+            .map_err(|e| ToolError::ExecutionFailed(
+                format!("Failed to create anchor backup: {}", e),
+            ))?;
+        let mut follow_ups = Vec::new();
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(output, module_code)?;
+        for (path, migration) in &plan.files {
+            let rewritten = Self::apply_edits(&migration.source, &migration.edits);
+            fs::write(path, &rewritten)?;
+            let remaining = rewritten.matches("anyhow").count();
+            if remaining > 0 {
+                follow_ups
+                    .push(
+                        format!("{}: {} remaining mention(s) of anyhow", path, remaining),
+                    );
+            }
+        }
+        let module_name = module.trim_start_matches("crate::");
+        let root_file = ["src/lib.rs", "src/main.rs"]
+            .iter()
+            .map(|f| crate_root.join(f))
+            .find(|f| f.exists());
+        match root_file {
+            Some(root_file) if !module_name.contains("::") => {
+                Self::ensure_module_declared(&root_file, module_name)?;
+            }
+            _ => {
+                follow_ups.push(format!("declare the `{}` module by hand", module));
+            }
+        }
+        let has_thiserror = manifest
+            .parse::<toml::Value>()
+            .ok()
+            .and_then(|value| value.get("dependencies").and_then(|d| d.get("thiserror")).cloned())
+            .is_some();
+        if !has_thiserror {
+            let added = std::process::Command::new("cargo")
+                .args(["add", "thiserror"])
+                .current_dir(crate_root)
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false);
+            if !added {
+                follow_ups.push("add `thiserror` to [dependencies] in Cargo.toml".to_string());
+            }
+        }
+        follow_ups.push("run `cargo check` and fix remaining `?` conversions".to_string());
+        Ok((anchor, follow_ups))
+    }
+    fn run_migration(&self, matches: &ArgMatches) -> Result<()> {
+        let input = matches.get_one::<String>("input").unwrap();
+        let output = matches.get_one::<String>("error-output").unwrap();
+        let name = matches.get_one::<String>("name").unwrap();
+        let apply = matches.get_flag("apply");
+        let dry_run = matches.get_flag("dry-run");
+        let verbose = matches.get_flag("verbose");
+        let output_format = parse_output_format(matches);
+        let crate_root = Self::find_crate_root(Path::new(input));
+        let module = Self::module_path_for(Path::new(output), &crate_root.join("src"));
+        let mut plan = MigrationPlan::default();
+        for path in self.collect_library_files(Path::new(input), Path::new(output)) {
+            let display = path.display().to_string();
+            match fs::read_to_string(&path) {
+                Ok(source) => self.plan_source(&source, &display, name, &module, &mut plan),
+                Err(e) => plan.manual.push(format!("{}: could not read ({})", display, e)),
+            }
+        }
+        let manifest = fs::read_to_string(crate_root.join("Cargo.toml")).unwrap_or_default();
+        let conversions = Self::source_conversions(&manifest);
+        let module_code = self.generate_migration_module(&plan, name, &conversions);
+        let signatures = plan.sites.iter().filter(|s| s.kind == "signature").count();
+        let mut categories: BTreeMap<&str, Vec<&MigrationSite>> = BTreeMap::new();
+        for site in plan.sites.iter().filter(|s| s.kind != "signature") {
+            categories.entry(site.category.as_str()).or_default().push(site);
+        }
+        let applied = if apply && !dry_run && !plan.files.is_empty() {
+            Some(
+                self
+                    .apply_migration(
+                        &plan,
+                        &module_code,
+                        &module,
+                        Path::new(output),
+                        &crate_root,
+                        &manifest,
+                    )?,
+            )
+        } else {
+            None
+        };
+        match output_format {
+            OutputFormat::Human => {
+                println!(
+                    "🔧 {} - {}", "CargoMate ErrorDerive".bold().blue(),
+                    "anyhow → thiserror migration".cyan()
+                );
+                println!(
+                    "  📊 {} files, {} signatures, {} call sites", plan.files.len(),
+                    signatures, plan.sites.len() - signatures
+                );
+                for (category, sites) in &categories {
+                    println!(
+                        "  • {}::{} ({} sites) e.g. {:?}", name, category.bold(), sites
+                        .len(), sites[0].message
+                    );
+                    if verbose {
+                        for site in sites {
+                            println!(
+                                "      {}:{} {} in {}", site.file, site.line, site.kind
+                                .dimmed(), site.function
+                            );
+                        }
+                    }
+                }
+                if !plan.manual.is_empty() {
+                    println!("\n  ⚠️  {}", "Needs manual attention:".yellow().bold());
+                    for item in &plan.manual {
+                        println!("     • {}", item);
+                    }
+                }
+                match &applied {
+                    Some((anchor, follow_ups)) => {
+                        println!("\n  💾 Wrote {} and rewrote {} files", output.cyan(), plan.files.len());
+                        println!("  ⚓ Restore with: cm anchor restore {}", anchor);
+                        for item in follow_ups {
+                            println!("     • {}", item);
+                        }
+                    }
+                    None if plan.files.is_empty() => {
+                        println!("\n  ✅ No anyhow usage found in library code");
+                    }
+                    None if verbose || dry_run => {
+                        println!("\n   📋 {}", "Generated error module:".bold());
+                        println!("   {}", "─".repeat(50));
+                        for line in module_code.lines() {
+                            println!("   {}", line);
+                        }
+                    }
+                    None => {
+                        println!(
+                            "\n  💡 Re-run with {} to rewrite the crate (an anchor backup is taken first)",
+                            "--apply".bold()
+                        );
+                    }
+                }
+            }
+            OutputFormat::Json => {
+                let result = serde_json::json!(
+                    { "error_name" : name, "module" : module, "output" : output,
+                    "files" : plan.files.keys().collect::< Vec < _ >> (), "signatures" :
+                    signatures, "sites" : plan.sites.iter().map(| s | serde_json::json!({
+                    "file" : s.file, "line" : s.line, "function" : s.function, "kind" : s
+                    .kind, "category" : s.category, "message" : s.message })).collect::<
+                    Vec < _ >> (), "manual" : plan.manual, "applied" : applied.is_some(),
+                    "anchor" : applied.as_ref().map(| (anchor, _) | anchor), "follow_ups" :
+                    applied.as_ref().map(| (_, f) | f.clone()).unwrap_or_default(),
+                    "module_code" : module_code }
+                );
+                println!("{}", serde_json::to_string_pretty(& result).unwrap());
+            }
+            OutputFormat::Table => {
+                println!("{:<12} {:<6} {:<50}", "Category", "Sites", "Example");
+                println!("{}", "─".repeat(70));
+                for (category, sites) in &categories {
+                    println!(
+                        "{:<12} {:<6} {:<50}", category, sites.len(), sites[0].message
+                        .chars().take(50).collect::< String > ()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
 }
 struct ErrorUsageVisitor {
     error_patterns: Vec<ErrorPattern>,
@@ -415,6 +948,374 @@ impl ErrorUsageVisitor {
         }
     }
 }
+struct AnyhowMigrationVisitor<'a> {
+    source: &'a str,
+    base: usize,
+    file: String,
+    error_name: String,
+    result_imported: bool,
+    error_imported: bool,
+    current_fn: Option<String>,
+    migrating: bool,
+    closure_depth: usize,
+    uses_context: bool,
+    leftover: usize,
+    sites: Vec<MigrationSite>,
+    edits: Vec<TextEdit>,
+    manual: Vec<String>,
+}
+impl<'a> AnyhowMigrationVisitor<'a> {
+    fn new(source: &'a str, file: &str, error_name: &str) -> Self {
+        Self {
+            source,
+            base: if source.starts_with('\u{feff}') { 3 } else { 0 },
+            file: file.to_string(),
+            error_name: error_name.to_string(),
+            result_imported: false,
+            error_imported: false,
+            current_fn: None,
+            migrating: false,
+            closure_depth: 0,
+            uses_context: false,
+            leftover: 0,
+            sites: Vec::new(),
+            edits: Vec::new(),
+            manual: Vec::new(),
+        }
+    }
+    fn span_range(&self, span: Span) -> Range<usize> {
+        let range = span.byte_range();
+        range.start + self.base..range.end + self.base
+    }
+    fn range_of<T: Spanned>(&self, node: &T) -> Range<usize> {
+        self.span_range(node.span())
+    }
+    fn text_of<T: Spanned>(&self, node: &T) -> &'a str {
+        &self.source[self.range_of(node)]
+    }
+    fn scan_imports(&mut self, file: &File) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        for item in &file.items {
+            let Item::Use(item_use) = item else { continue };
+            let UseTree::Path(root) = &item_use.tree else { continue };
+            if root.ident != "anyhow" {
+                continue;
+            }
+            let mut names = Vec::new();
+            Self::collect_use_names(&root.tree, &mut names);
+            let glob = names.iter().any(|n| n == "*");
+            self.result_imported |= glob || names.iter().any(|n| n == "Result");
+            self.error_imported |= glob || names.iter().any(|n| n == "Error");
+            if matches!(item_use.vis, syn::Visibility::Inherited) {
+                ranges.push(self.range_of(item_use));
+            } else {
+                self.leftover += 1;
+                self.manual
+                    .push(
+                        format!(
+                            "{}:{}: public re-export `{}` is part of the API; replace it by hand",
+                            self.file, item_use.use_token.span.start().line, self
+                            .text_of(item_use)
+                        ),
+                    );
+            }
+        }
+        ranges
+    }
+    fn collect_use_names(tree: &UseTree, names: &mut Vec<String>) {
+        match tree {
+            UseTree::Path(path) => Self::collect_use_names(&path.tree, names),
+            UseTree::Name(name) => names.push(name.ident.to_string()),
+            UseTree::Rename(rename) if rename.rename == "_" => {
+                names.push(rename.ident.to_string())
+            }
+            UseTree::Rename(rename) => names.push(rename.rename.to_string()),
+            UseTree::Glob(_) => names.push("*".to_string()),
+            UseTree::Group(group) => {
+                for item in &group.items {
+                    Self::collect_use_names(item, names);
+                }
+            }
+        }
+    }
+    fn is_anyhow_error(&self, ty: &Type) -> bool {
+        let Type::Path(type_path) = ty else { return false };
+        let segments: Vec<String> = type_path
+            .path
+            .segments
+            .iter()
+            .map(|s| s.ident.to_string())
+            .collect();
+        segments == ["anyhow", "Error"] || (self.error_imported && segments == ["Error"])
+    }
+    fn anyhow_ok_type<'t>(&self, ty: &'t Type) -> Option<&'t Type> {
+        let Type::Path(type_path) = ty else { return None };
+        if type_path.qself.is_some() {
+            return None;
+        }
+        let segments: Vec<String> = type_path
+            .path
+            .segments
+            .iter()
+            .map(|s| s.ident.to_string())
+            .collect();
+        let last = type_path.path.segments.last()?;
+        let PathArguments::AngleBracketed(args) = &last.arguments else { return None };
+        let types: Vec<&Type> = args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect();
+        match types.as_slice() {
+            [ok] if segments == ["anyhow", "Result"]
+                || (self.result_imported && segments == ["Result"]) => Some(ok),
+            [ok, err] if segments.last().map(|s| s == "Result").unwrap_or(false)
+                && self.is_anyhow_error(err) => Some(ok),
+            _ => None,
+        }
+    }
+    fn enter_fn<F: FnOnce(&mut Self)>(&mut self, sig: &Signature, body: F) {
+        let mut migrating = false;
+        if let ReturnType::Type(_, ty) = &sig.output {
+            if let Some(ok) = self.anyhow_ok_type(ty) {
+                let replacement = format!("Result<{}, {}>", self.text_of(ok), self.error_name);
+                self.edits
+                    .push(TextEdit {
+                        range: self.range_of(ty.as_ref()),
+                        replacement,
+                    });
+                self.sites
+                    .push(MigrationSite {
+                        file: self.file.clone(),
+                        line: sig.ident.span().start().line,
+                        function: sig.ident.to_string(),
+                        kind: "signature".to_string(),
+                        category: String::new(),
+                        message: String::new(),
+                    });
+                migrating = true;
+            }
+        }
+        let saved = (
+            self.current_fn.replace(sig.ident.to_string()),
+            self.migrating,
+            self.closure_depth,
+        );
+        self.migrating = migrating;
+        self.closure_depth = 0;
+        body(self);
+        (self.current_fn, self.migrating, self.closure_depth) = saved;
+    }
+    fn record(&mut self, span: Span, kind: &str, category: &str, message: String) {
+        self.sites
+            .push(MigrationSite {
+                file: self.file.clone(),
+                line: span.start().line,
+                function: self.current_fn.clone().unwrap_or_default(),
+                kind: kind.to_string(),
+                category: category.to_string(),
+                message,
+            });
+    }
+    fn message_from_display(&self, expr: &Expr) -> (String, String) {
+        let text = self.text_of(expr);
+        match expr {
+            Expr::Lit(ExprLit { lit: Lit::Str(lit), .. }) => {
+                (format!("{}.to_string()", text), lit.value())
+            }
+            Expr::Macro(mac) if mac.mac.path.is_ident("format") => {
+                let literal = mac
+                    .mac
+                    .parse_body_with(Punctuated::<Expr, syn::Token![,]>::parse_terminated)
+                    .ok()
+                    .and_then(|args| match args.first() {
+                        Some(Expr::Lit(ExprLit { lit: Lit::Str(lit), .. })) => {
+                            Some(lit.value())
+                        }
+                        _ => None,
+                    })
+                    .unwrap_or_else(|| text.to_string());
+                (text.to_string(), literal)
+            }
+            _ => (format!("({}).to_string()", text), text.to_string()),
+        }
+    }
+    fn message_from_closure(&self, expr: &Expr) -> (String, String) {
+        match expr {
+            Expr::Closure(closure) => self.message_from_display(&closure.body),
+            _ => {
+                let text = self.text_of(expr);
+                (format!("({})().to_string()", text), text.to_string())
+            }
+        }
+    }
+    fn message_from_macro_args(&self, args: &[&Expr]) -> (String, String) {
+        match args {
+            [] => ("String::new()".to_string(), String::new()),
+            [Expr::Lit(ExprLit { lit: Lit::Str(lit), .. })] if !lit.value().contains('{') => {
+                (format!("{}.to_string()", self.text_of(args[0])), lit.value())
+            }
+            [single] if !matches!(single, Expr::Lit(_)) => {
+                let text = self.text_of(*single);
+                (format!("({}).to_string()", text), text.to_string())
+            }
+            [first, ..] => {
+                let start = self.range_of(*first).start;
+                let end = self.range_of(*args.last().unwrap()).end;
+                let literal = match first {
+                    Expr::Lit(ExprLit { lit: Lit::Str(lit), .. }) => lit.value(),
+                    _ => self.source[start..end].to_string(),
+                };
+                (format!("format!({})", &self.source[start..end]), literal)
+            }
+        }
+    }
+    fn anyhow_macro_name(mac: &Macro) -> Option<&'static str> {
+        let segments: Vec<String> = mac
+            .path
+            .segments
+            .iter()
+            .map(|s| s.ident.to_string())
+            .collect();
+        let name = match segments.as_slice() {
+            [name] => name,
+            [krate, name] if krate == "anyhow" => name,
+            _ => return None,
+        };
+        ["bail", "anyhow", "ensure"].into_iter().find(|candidate| name == candidate)
+    }
+}
+impl<'a, 'ast> syn::visit::Visit<'ast> for AnyhowMigrationVisitor<'a> {
+    fn visit_item_fn(&mut self, node: &'ast ItemFn) {
+        self.enter_fn(&node.sig, |v| v.visit_block(&node.block));
+    }
+    fn visit_impl_item_fn(&mut self, node: &'ast ImplItemFn) {
+        self.enter_fn(&node.sig, |v| v.visit_block(&node.block));
+    }
+    fn visit_trait_item_fn(&mut self, node: &'ast TraitItemFn) {
+        self.enter_fn(
+            &node.sig,
+            |v| {
+                if let Some(block) = &node.default {
+                    v.visit_block(block);
+                }
+            },
+        );
+    }
+    fn visit_expr_closure(&mut self, node: &'ast syn::ExprClosure) {
+        self.closure_depth += 1;
+        syn::visit::visit_expr_closure(self, node);
+        self.closure_depth -= 1;
+    }
+    fn visit_expr_async(&mut self, node: &'ast syn::ExprAsync) {
+        self.closure_depth += 1;
+        syn::visit::visit_expr_async(self, node);
+        self.closure_depth -= 1;
+    }
+    fn visit_type(&mut self, node: &'ast Type) {
+        if self.anyhow_ok_type(node).is_some() {
+            self.leftover += 1;
+            self.manual
+                .push(
+                    format!(
+                        "{}:{}: `{}` outside a function signature; migrate it by hand",
+                        self.file, node.span().start().line, self.text_of(node)
+                    ),
+                );
+        }
+        syn::visit::visit_type(self, node);
+    }
+    fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+        let method = node.method.to_string();
+        if !self.migrating || node.args.len() != 1
+            || (method != "context" && method != "with_context")
+        {
+            syn::visit::visit_expr_method_call(self, node);
+            return;
+        }
+        let (message, literal) = if method == "context" {
+            self.message_from_display(&node.args[0])
+        } else {
+            self.message_from_closure(&node.args[0])
+        };
+        let category = ErrorDeriveTool::classify_message(&literal);
+        let start = self.range_of(&node.method).start;
+        let end = self.span_range(node.paren_token.span.close()).end;
+        self.edits
+            .push(TextEdit {
+                range: start..end,
+                replacement: format!(
+                    "or_error(|source| {}::{} {{ message: {}, source }})", self
+                    .error_name, category, message
+                ),
+            });
+        self.uses_context = true;
+        self.record(node.method.span(), &method, category, literal);
+        self.visit_expr(&node.receiver);
+    }
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        let Some(name) = Self::anyhow_macro_name(mac) else {
+            syn::visit::visit_macro(self, mac);
+            return;
+        };
+        let line = mac.path.span().start().line;
+        if !self.migrating || (name != "anyhow" && self.closure_depth > 0) {
+            self.leftover += 1;
+            let reason = if self.migrating {
+                "inside a closure or async block"
+            } else {
+                "outside a function returning anyhow::Result"
+            };
+            self.manual.push(format!("{}:{}: `{}!` {}; rewrite by hand", self.file, line, name, reason));
+            return;
+        }
+        let parsed = mac
+            .parse_body_with(Punctuated::<Expr, syn::Token![,]>::parse_terminated);
+        let Ok(parsed) = parsed else {
+            self.leftover += 1;
+            self.manual.push(format!("{}:{}: could not parse `{}!` arguments", self.file, line, name));
+            return;
+        };
+        let args: Vec<&Expr> = parsed.iter().collect();
+        let (condition, message_args) = match (name, args.split_first()) {
+            ("ensure", Some((condition, rest))) => (Some(*condition), rest),
+            _ => (None, args.as_slice()),
+        };
+        let (message, literal) = match (condition, message_args) {
+            (Some(condition), []) => {
+                let literal = format!("Condition failed: `{}`", self.text_of(condition));
+                (format!("{:?}.to_string()", literal), literal)
+            }
+            _ => self.message_from_macro_args(message_args),
+        };
+        let category = ErrorDeriveTool::classify_message(&literal);
+        let value = format!(
+            "{}::{} {{ message: {}, source: None }}", self.error_name, category, message
+        );
+        let mut range = self.range_of(mac);
+        let replacement = match (name, condition) {
+            ("bail", _) => format!("return Err({})", value),
+            ("ensure", Some(condition)) => {
+                if self.source[range.end..].starts_with(';') {
+                    range.end += 1;
+                }
+                let negated = match condition {
+                    Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Not(_)) => {
+                        self.text_of(&unary.expr).to_string()
+                    }
+                    _ => format!("!({})", self.text_of(condition)),
+                };
+                format!("if {} {{ return Err({}); }}", negated, value)
+            }
+            _ => value,
+        };
+        self.edits.push(TextEdit { range, replacement });
+        self.record(mac.path.span(), name, category, literal);
+    }
+}
 impl Tool for ErrorDeriveTool {
     fn name(&self) -> &'static str {
         "error-derive"
@@ -426,7 +1327,7 @@ impl Tool for ErrorDeriveTool {
         Command::new(self.name())
             .about(self.description())
             .long_about(
-                "Analyze existing error handling patterns in your codebase and generate comprehensive error types with proper Display, Error, From traits, context methods, and error handling patterns. Supports thiserror derive macros and backtrace support.",
+                "Analyze existing error handling patterns in your codebase and generate comprehensive error types with proper Display, Error, From traits, context methods, and error handling patterns. Supports thiserror derive macros and backtrace support. With --migrate it infers error categories from anyhow context/bail messages and plans a typed thiserror enum; --apply rewrites the crate after dropping an anchor backup.",
             )
             .args(
                 &[
@@ -435,9 +1336,8 @@ impl Tool for ErrorDeriveTool {
                         .short('i')
                         .help("Input directory or file to analyze")
                         .default_value("src/"),
-                    Arg::new("output")
-                        .long("output")
-                        .short('o')
+                    Arg::new("error-output")
+                        .long("error-output")
                         .help("Output file for generated error types")
                         .default_value("src/errors.rs"),
                     Arg::new("name")
@@ -461,13 +1361,24 @@ impl Tool for ErrorDeriveTool {
                         .long("patterns")
                         .help("Generate error handling patterns")
                         .action(clap::ArgAction::SetTrue),
+                    Arg::new("migrate")
+                        .long("migrate")
+                        .help("Plan a migration of library code from anyhow to a thiserror enum")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("apply")
+                        .long("apply")
+                        .help("Rewrite signatures and call sites (implies --migrate, anchors first)")
+                        .action(clap::ArgAction::SetTrue),
                 ],
             )
             .args(&common_options())
     }
     fn execute(&self, matches: &ArgMatches) -> Result<()> {
+        if matches.get_flag("migrate") || matches.get_flag("apply") {
+            return self.run_migration(matches);
+        }
         let input = matches.get_one::<String>("input").unwrap();
-        let output = matches.get_one::<String>("output").unwrap();
+        let output = matches.get_one::<String>("error-output").unwrap();
         let name = matches.get_one::<String>("name").unwrap();
         let thiserror = matches.get_flag("thiserror");
         let backtrace = matches.get_flag("backtrace");
@@ -600,4 +1511,38 @@ impl Default for ErrorDeriveTool {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_plan_rewrites_anyhow_usage() {
+        let source = r#"use anyhow::{bail, Context, Result};
+
+pub fn load(path: &str) -> Result<String> {
+    if path.is_empty() {
+        bail!("name must not be empty");
+    }
+    std::fs::read_to_string(path).context("Failed to read config file")
+}
+"#;
+        let tool = ErrorDeriveTool::new();
+        let mut plan = MigrationPlan::default();
+        tool.plan_source(
+            source,
+            "src/config.rs",
+            "AppError",
+            "crate::errors",
+            &mut plan,
+        );
+        let migration = &plan.files["src/config.rs"];
+        let rewritten = ErrorDeriveTool::apply_edits(source, &migration.edits);
+        assert!(rewritten.starts_with("use crate::errors::{AppError, ErrorContext};"));
+        assert!(rewritten.contains("-> Result<String, AppError>"));
+        assert!(
+            rewritten.contains("return Err(AppError::Invalid { message: \"name must not be empty\".to_string(), source: None })")
+        );
+        assert!(rewritten.contains(".or_error(|source| AppError::Io {"));
+        assert!(plan.manual.is_empty());
+    }
+}
@@ -1,14 +1,15 @@
 use super::{Tool, Result, ToolError, common_options, parse_output_format, OutputFormat};
 use clap::{Arg, ArgMatches, Command};
 use colored::*;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::Path;
 use syn::{
     parse_file, File, Item, ItemFn, Fields, Field, Type, PathSegment, Ident,
     visit::Visit, spanned::Spanned,
 };
-use quote::quote;
-use proc_macro2::TokenStream;
+use quote::ToTokens;
+use proc_macro2::Span;
 use serde::{Serialize, Deserialize};
 #[derive(Debug, Clone)]
 pub struct ExampleGenTool;
@@ -40,6 +41,35 @@ enum ExampleType {
     DocTest,
     ErrorHandling,
 }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TestUsage {
+    test_name: String,
+    source_file: String,
+    module_path: String,
+    imports: Vec<String>,
+    body: String,
+    return_type: Option<String>,
+    calls: Vec<String>,
+}
+#[derive(Debug, Clone)]
+struct ApiItem {
+    name: String,
+    file: String,
+    doc_insert_at: usize,
+    indent: String,
+    has_docs: bool,
+    has_example: bool,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExtractedExample {
+    name: String,
+    path: String,
+    test_name: String,
+    source_file: String,
+    api_calls: Vec<String>,
+    verified: bool,
+    error: Option<String>,
+}
 impl ExampleGenTool {
     pub fn new() -> Self {
         Self
@@ -521,6 +551,585 @@ impl ExampleGenTool {
         }
         Ok(markdown)
     }
+    fn find_crate_root(input: &Path) -> std::path::PathBuf {
+        let start = if input.is_dir() { input } else { input.parent().unwrap_or(input) };
+        start
+            .ancestors()
+            .find(|dir| dir.join("Cargo.toml").exists())
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+    }
+    fn crate_ident(crate_root: &Path) -> Option<String> {
+        let manifest = fs::read_to_string(crate_root.join("Cargo.toml")).ok()?;
+        let value: toml::Value = manifest.parse().ok()?;
+        let name = value
+            .get("lib")
+            .and_then(|lib| lib.get("name"))
+            .or_else(|| value.get("package").and_then(|p| p.get("name")))?
+            .as_str()?;
+        Some(name.replace('-', "_"))
+    }
+    fn module_path_for(relative: &Path) -> String {
+        let mut parts: Vec<String> = relative
+            .with_extension("")
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        if parts.first().map(|p| p == "src").unwrap_or(false) {
+            parts.remove(0);
+        }
+        if matches!(parts.last().map(String::as_str), Some("lib" | "main" | "mod")) {
+            parts.pop();
+        }
+        parts.join("::")
+    }
+    fn analyze_source(
+        &self,
+        source: &str,
+        relative: &str,
+        module_path: &str,
+        integration: bool,
+    ) -> Result<(Vec<TestUsage>, Vec<ApiItem>)> {
+        let ast = parse_file(source)
+            .map_err(|e| ToolError::ExecutionFailed(
+                format!("Failed to parse {}: {}", relative, e),
+            ))?;
+        let mut visitor = TestUsageVisitor {
+            source,
+            file: relative.to_string(),
+            module_path: module_path.to_string(),
+            in_tests: integration,
+            imports: Vec::new(),
+            usages: Vec::new(),
+            api: Vec::new(),
+        };
+        if integration {
+            visitor.imports = TestUsageVisitor::collect_imports(source, &ast.items);
+        }
+        visitor.visit_file(&ast);
+        Ok((visitor.usages, visitor.api))
+    }
+    fn rewrite_paths(code: &str, crate_ident: &str, module_path: &str) -> String {
+        let parent = if module_path.is_empty() {
+            crate_ident.to_string()
+        } else {
+            format!("{}::{}", crate_ident, module_path)
+        };
+        let super_re = regex::Regex::new(r"\bsuper::").unwrap();
+        let crate_re = regex::Regex::new(r"\bcrate::").unwrap();
+        let code = super_re.replace_all(code, format!("{}::", parent).as_str());
+        crate_re.replace_all(&code, format!("{}::", crate_ident).as_str()).to_string()
+    }
+    fn usage_prelude(&self, usage: &TestUsage, crate_ident: &str) -> Vec<String> {
+        usage
+            .imports
+            .iter()
+            .map(|import| Self::rewrite_paths(import, crate_ident, &usage.module_path))
+            .collect()
+    }
+    fn render_example_file(&self, usage: &TestUsage, crate_ident: &str) -> String {
+        let mut code = format!(
+            "//! Extracted by `cm tool example-gen --from-tests` from `{}` in {}.\n\n",
+            usage.test_name, usage.source_file
+        );
+        for import in self.usage_prelude(usage, crate_ident) {
+            code.push_str(&import);
+            code.push('\n');
+        }
+        code.push('\n');
+        match &usage.return_type {
+            Some(ty) => code.push_str(&format!("fn main() -> {} {{\n", ty)),
+            None => code.push_str("fn main() {\n"),
+        }
+        for line in Self::rewrite_paths(&usage.body, crate_ident, &usage.module_path).lines() {
+            if line.is_empty() {
+                code.push('\n');
+            } else {
+                code.push_str(&format!("    {}\n", line));
+            }
+        }
+        code.push_str("}\n");
+        code
+    }
+    fn render_doc_example(&self, usage: &TestUsage, item: &ApiItem, crate_ident: &str) -> String {
+        let mut lines = Vec::new();
+        if item.has_docs {
+            lines.push(String::new());
+        }
+        lines.push("# Examples".to_string());
+        lines.push(String::new());
+        lines.push("```".to_string());
+        lines.extend(self.usage_prelude(usage, crate_ident));
+        lines.push(String::new());
+        lines
+            .extend(
+                Self::rewrite_paths(&usage.body, crate_ident, &usage.module_path)
+                    .lines()
+                    .map(|l| l.to_string()),
+            );
+        lines.push("```".to_string());
+        lines
+            .iter()
+            .map(|line| {
+                if line.is_empty() {
+                    format!("{}///\n", item.indent)
+                } else {
+                    format!("{}/// {}\n", item.indent, line)
+                }
+            })
+            .collect()
+    }
+    fn example_file_name(test_name: &str, taken: &mut BTreeSet<String>) -> String {
+        let base = test_name.strip_prefix("test_").unwrap_or(test_name).to_string();
+        let mut name = base.clone();
+        let mut n = 2;
+        while !taken.insert(name.clone()) {
+            name = format!("{}_{}", base, n);
+            n += 1;
+        }
+        name
+    }
+    fn check_examples(&self, crate_root: &Path) -> Result<BTreeMap<String, String>> {
+        let output = std::process::Command::new("cargo")
+            .args(["check", "--examples", "--keep-going", "--message-format=json"])
+            .current_dir(crate_root)
+            .output()
+            .map_err(|e| ToolError::ExecutionFailed(
+                format!("Failed to run cargo check --examples: {}", e),
+            ))?;
+        let mut failures = BTreeMap::new();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(line) else {
+                continue;
+            };
+            if json.get("reason").and_then(|r| r.as_str()) != Some("compiler-message") {
+                continue;
+            }
+            let message = &json["message"];
+            if message.get("level").and_then(|l| l.as_str()) != Some("error") {
+                continue;
+            }
+            if let Some(src_path) = json["target"]["src_path"].as_str() {
+                let name = Path::new(src_path)
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                failures
+                    .entry(name)
+                    .or_insert_with(|| {
+                        message["message"].as_str().unwrap_or("compile error").to_string()
+                    });
+            }
+        }
+        Ok(failures)
+    }
+    fn insert_doc_examples(
+        &self,
+        crate_root: &Path,
+        items: &[(&ApiItem, String)],
+    ) -> Result<usize> {
+        let mut by_file: BTreeMap<&str, Vec<(usize, &str)>> = BTreeMap::new();
+        for (item, docs) in items {
+            by_file.entry(item.file.as_str()).or_default().push((item.doc_insert_at, docs));
+        }
+        let mut inserted = 0;
+        for (file, mut inserts) in by_file {
+            let path = crate_root.join(file);
+            let mut source = fs::read_to_string(&path)?;
+            inserts.sort_by_key(|insert| std::cmp::Reverse(insert.0));
+            for (at, docs) in inserts {
+                source.insert_str(at, docs);
+                inserted += 1;
+            }
+            fs::write(&path, source)?;
+        }
+        Ok(inserted)
+    }
+    fn run_from_tests(&self, matches: &ArgMatches) -> Result<()> {
+        let input = matches.get_one::<String>("input").unwrap();
+        let function_filter = matches.get_one::<String>("function");
+        let insert_docs = matches.get_flag("insert-docs");
+        let dry_run = matches.get_flag("dry-run");
+        let verbose = matches.get_flag("verbose");
+        let output_format = parse_output_format(matches);
+        let crate_root = Self::find_crate_root(Path::new(input));
+        let crate_ident = Self::crate_ident(&crate_root)
+            .ok_or_else(|| ToolError::InvalidArguments(
+                format!("No package name found in {}", crate_root.join("Cargo.toml").display()),
+            ))?;
+        let has_lib = crate_root.join("src/lib.rs").exists();
+        let mut usages = Vec::new();
+        let mut api = Vec::new();
+        for dir in ["src", "tests"] {
+            for entry in walkdir::WalkDir::new(crate_root.join(dir))
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().extension().map(|x| x == "rs").unwrap_or(false))
+            {
+                let relative = entry
+                    .path()
+                    .strip_prefix(&crate_root)
+                    .unwrap_or(entry.path())
+                    .to_path_buf();
+                let source = fs::read_to_string(entry.path())?;
+                let integration = dir == "tests";
+                let module_path = if integration {
+                    String::new()
+                } else {
+                    Self::module_path_for(&relative)
+                };
+                match self
+                    .analyze_source(
+                        &source,
+                        &relative.to_string_lossy(),
+                        &module_path,
+                        integration,
+                    )
+                {
+                    Ok((mut found, mut items)) => {
+                        usages.append(&mut found);
+                        api.append(&mut items);
+                    }
+                    Err(e) if verbose => println!("   ⚠️  {}", e),
+                    Err(_) => {}
+                }
+            }
+        }
+        let api_names: BTreeSet<&str> = api.iter().map(|item| item.name.as_str()).collect();
+        let mut candidates: Vec<TestUsage> = usages
+            .into_iter()
+            .filter_map(|mut usage| {
+                usage.calls.retain(|call| api_names.contains(call.as_str()));
+                usage.calls.sort();
+                usage.calls.dedup();
+                let wanted = function_filter
+                    .map(|f| usage.calls.iter().any(|c| c == f))
+                    .unwrap_or(true);
+                (!usage.calls.is_empty() && wanted).then_some(usage)
+            })
+            .collect();
+        candidates.sort_by_key(|usage| usage.body.len());
+        let examples_dir = crate_root.join("examples");
+        let mut taken: BTreeSet<String> = fs::read_dir(&examples_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter_map(|e| {
+                        e.path().file_stem().map(|s| s.to_string_lossy().to_string())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut examples: Vec<(ExtractedExample, &TestUsage)> = candidates
+            .iter()
+            .map(|usage| {
+                let name = Self::example_file_name(&usage.test_name, &mut taken);
+                (
+                    ExtractedExample {
+                        path: format!("examples/{}.rs", name),
+                        name,
+                        test_name: usage.test_name.clone(),
+                        source_file: usage.source_file.clone(),
+                        api_calls: usage.calls.clone(),
+                        verified: false,
+                        error: None,
+                    },
+                    usage,
+                )
+            })
+            .collect();
+        let mut doc_count = 0;
+        if !dry_run && !examples.is_empty() {
+            fs::create_dir_all(&examples_dir)?;
+            for (example, usage) in &examples {
+                fs::write(
+                    crate_root.join(&example.path),
+                    self.render_example_file(usage, &crate_ident),
+                )?;
+            }
+            let failures = self.check_examples(&crate_root)?;
+            for (example, _) in examples.iter_mut() {
+                match failures.get(&example.name) {
+                    Some(error) => {
+                        example.error = Some(error.clone());
+                        let _ = fs::remove_file(crate_root.join(&example.path));
+                    }
+                    None => example.verified = true,
+                }
+            }
+            if insert_docs && has_lib {
+                let mut documented = BTreeSet::new();
+                let mut docs = Vec::new();
+                for (example, usage) in &examples {
+                    if !example.verified || usage.return_type.is_some() {
+                        continue;
+                    }
+                    for call in &usage.calls {
+                        let defs: Vec<&ApiItem> = api
+                            .iter()
+                            .filter(|item| &item.name == call)
+                            .collect();
+                        if let [item] = defs.as_slice() {
+                            if !item.has_example && documented.insert(call.clone()) {
+                                docs.push((*item, self.render_doc_example(usage, item, &crate_ident)));
+                            }
+                        }
+                    }
+                }
+                doc_count = self.insert_doc_examples(&crate_root, &docs)?;
+            }
+        }
+        let verified = examples.iter().filter(|(e, _)| e.verified).count();
+        match output_format {
+            OutputFormat::Human => {
+                println!(
+                    "📚 {} - {}", "CargoMate ExampleGen".bold().blue(),
+                    "examples extracted from tests".cyan()
+                );
+                println!(
+                    "  📊 {} tests use the public API of {}", examples.len(), crate_ident
+                    .bold()
+                );
+                for (example, usage) in &examples {
+                    let status = if dry_run {
+                        "•".normal()
+                    } else if example.verified {
+                        "✅".normal()
+                    } else {
+                        "❌".normal()
+                    };
+                    println!(
+                        "  {} {} ← {} ({})", status, example.path.cyan(), example
+                        .test_name, example.source_file
+                    );
+                    if verbose {
+                        println!("      calls: {}", usage.calls.join(", "));
+                    }
+                    if let Some(error) = &example.error {
+                        println!("      {}", error.red());
+                    }
+                }
+                if dry_run {
+                    println!(
+                        "\n  💡 Dry run: nothing written; examples are checked with {} when written",
+                        "cargo check --examples".bold()
+                    );
+                } else {
+                    println!(
+                        "\n  💾 Kept {} of {} examples that pass cargo check --examples",
+                        verified, examples.len()
+                    );
+                    if insert_docs {
+                        println!("  📝 Inserted {} doc-test examples", doc_count);
+                    }
+                }
+            }
+            OutputFormat::Json => {
+                let result = serde_json::json!(
+                    { "crate" : crate_ident, "examples" : examples.iter().map(| (e, _) |
+                    e).collect::< Vec < _ >> (), "verified" : verified, "doc_tests_inserted"
+                    : doc_count, "dry_run" : dry_run }
+                );
+                println!("{}", serde_json::to_string_pretty(& result).unwrap());
+            }
+            OutputFormat::Table => {
+                println!("{:<30} {:<30} {:<10}", "Example", "Test", "Status");
+                println!("{}", "─".repeat(70));
+                for (example, _) in &examples {
+                    println!(
+                        "{:<30} {:<30} {:<10}", example.path, example.test_name, if dry_run
+                        { "planned" } else if example.verified { "ok" } else { "failed" }
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+struct TestUsageVisitor<'a> {
+    source: &'a str,
+    file: String,
+    module_path: String,
+    in_tests: bool,
+    imports: Vec<String>,
+    usages: Vec<TestUsage>,
+    api: Vec<ApiItem>,
+}
+impl<'a> TestUsageVisitor<'a> {
+    fn has_attr(attrs: &[syn::Attribute], name: &str) -> bool {
+        attrs.iter().any(|attr| attr.path().is_ident(name))
+    }
+    fn is_cfg_test(attrs: &[syn::Attribute]) -> bool {
+        attrs
+            .iter()
+            .any(|attr| {
+                attr.path().is_ident("cfg")
+                    && attr
+                        .parse_args::<syn::Ident>()
+                        .map(|ident| ident == "test")
+                        .unwrap_or(false)
+            })
+    }
+    fn collect_imports(source: &str, items: &[Item]) -> Vec<String> {
+        items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Use(item_use) => Some(source[item_use.span().byte_range()].to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+    fn dedent(text: &str) -> String {
+        let lines: Vec<&str> = text.lines().collect();
+        let indent = lines
+            .iter()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.len() - l.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        lines
+            .iter()
+            .map(|l| if l.trim().is_empty() { "" } else { &l[indent..] })
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim_matches('\n')
+            .to_string()
+    }
+    fn record_api(&mut self, name: String, attrs: &[syn::Attribute], first: Span) {
+        let docs: Vec<&syn::Attribute> = attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"))
+            .collect();
+        let has_example = docs
+            .iter()
+            .any(|attr| attr.to_token_stream().to_string().contains("```"));
+        let anchor = match docs.last() {
+            Some(doc) => doc.span().byte_range().end,
+            None => first.byte_range().start,
+        };
+        let line_start = self.source[..anchor].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let doc_insert_at = if docs.is_empty() {
+            line_start
+        } else {
+            self.source[anchor..].find('\n').map(|i| anchor + i + 1).unwrap_or(self.source.len())
+        };
+        let indent: String = self.source[line_start..]
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        self.api
+            .push(ApiItem {
+                name,
+                file: self.file.clone(),
+                doc_insert_at,
+                indent,
+                has_docs: !docs.is_empty(),
+                has_example,
+            });
+    }
+    fn first_span(attrs: &[syn::Attribute], fallback: Span) -> Span {
+        attrs.first().map(|attr| attr.span()).unwrap_or(fallback)
+    }
+}
+impl<'a, 'ast> Visit<'ast> for TestUsageVisitor<'a> {
+    fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
+        let Some((_, items)) = &node.content else { return };
+        let saved = (self.in_tests, self.imports.clone(), self.module_path.clone());
+        if Self::is_cfg_test(&node.attrs) {
+            self.in_tests = true;
+            self.imports = Self::collect_imports(self.source, items);
+        } else if !self.in_tests {
+            self.module_path = if self.module_path.is_empty() {
+                node.ident.to_string()
+            } else {
+                format!("{}::{}", self.module_path, node.ident)
+            };
+        }
+        for item in items {
+            self.visit_item(item);
+        }
+        (self.in_tests, self.imports, self.module_path) = saved;
+    }
+    fn visit_item_fn(&mut self, node: &'ast ItemFn) {
+        if !self.in_tests {
+            if matches!(node.vis, syn::Visibility::Public(_)) {
+                let first = Self::first_span(&node.attrs, node.vis.span());
+                self.record_api(node.sig.ident.to_string(), &node.attrs, first);
+            }
+            return;
+        }
+        if !Self::has_attr(&node.attrs, "test") || Self::has_attr(&node.attrs, "should_panic")
+            || Self::has_attr(&node.attrs, "ignore") || node.sig.asyncness.is_some()
+        {
+            return;
+        }
+        let open = node.block.brace_token.span.open().byte_range().end;
+        let close = node.block.brace_token.span.close().byte_range().start;
+        let mut calls = CallCollector::default();
+        calls.visit_block(&node.block);
+        self.usages
+            .push(TestUsage {
+                test_name: node.sig.ident.to_string(),
+                source_file: self.file.clone(),
+                module_path: self.module_path.clone(),
+                imports: self.imports.clone(),
+                body: Self::dedent(&self.source[open..close]),
+                return_type: match &node.sig.output {
+                    syn::ReturnType::Default => None,
+                    syn::ReturnType::Type(_, ty) => {
+                        Some(self.source[ty.span().byte_range()].to_string())
+                    }
+                },
+                calls: calls.names,
+            });
+    }
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        if self.in_tests {
+            return;
+        }
+        if node.trait_.is_some() {
+            return;
+        }
+        for item in &node.items {
+            if let syn::ImplItem::Fn(method) = item {
+                if matches!(method.vis, syn::Visibility::Public(_)) {
+                    let first = Self::first_span(&method.attrs, method.vis.span());
+                    self.record_api(method.sig.ident.to_string(), &method.attrs, first);
+                }
+            }
+        }
+    }
+}
+#[derive(Default)]
+struct CallCollector {
+    names: Vec<String>,
+}
+impl<'ast> Visit<'ast> for CallCollector {
+    fn visit_expr_call(&mut self, node: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(path) = &*node.func {
+            if let Some(segment) = path.path.segments.last() {
+                self.names.push(segment.ident.to_string());
+            }
+        }
+        syn::visit::visit_expr_call(self, node);
+    }
+    fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+        self.names.push(node.method.to_string());
+        syn::visit::visit_expr_method_call(self, node);
+    }
+    fn visit_macro(&mut self, node: &'ast syn::Macro) {
+        let args = node
+            .parse_body_with(
+                syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated,
+            );
+        if let Ok(args) = args {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+    }
 }
 impl Tool for ExampleGenTool {
     fn name(&self) -> &'static str {
@@ -533,7 +1142,7 @@ impl Tool for ExampleGenTool {
         Command::new(self.name())
             .about(self.description())
             .long_about(
-                "Automatically generate runnable examples from function signatures, helping developers understand how to use APIs. Creates unit tests, integration tests, documentation examples, and error handling patterns.",
+                "Automatically generate runnable examples from function signatures, helping developers understand how to use APIs. Creates unit tests, integration tests, documentation examples, and error handling patterns. With --from-tests, public API usage found in the crate's tests becomes examples/*.rs files (kept only if `cargo check --examples` passes) and optionally doc-test snippets on the items they exercise.",
             )
             .args(
                 &[
@@ -551,9 +1160,8 @@ impl Tool for ExampleGenTool {
                         .short('t')
                         .help("Example types: unit, integration, doc, error-handling")
                         .default_value("unit,doc"),
-                    Arg::new("output")
-                        .long("output")
-                        .short('o')
+                    Arg::new("example-output")
+                        .long("example-output")
                         .help("Output directory for generated examples")
                         .default_value("examples/generated/"),
                     Arg::new("format")
@@ -576,11 +1184,22 @@ impl Tool for ExampleGenTool {
                         .long("validate")
                         .help("Validate that generated examples compile")
                         .action(clap::ArgAction::SetTrue),
+                    Arg::new("from-tests")
+                        .long("from-tests")
+                        .help("Turn unit and integration tests that use the public API into examples/*.rs")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("insert-docs")
+                        .long("insert-docs")
+                        .help("With --from-tests, add verified examples as doc-tests on the items they call")
+                        .action(clap::ArgAction::SetTrue),
                 ],
             )
             .args(&common_options())
     }
     fn execute(&self, matches: &ArgMatches) -> Result<()> {
+        if matches.get_flag("from-tests") {
+            return self.run_from_tests(matches);
+        }
         let input = matches.get_one::<String>("input").unwrap();
         let function_filter = matches.get_one::<String>("function");
         let example_types: Vec<String> = matches
@@ -589,7 +1208,7 @@ impl Tool for ExampleGenTool {
             .split(',')
             .map(|s| s.trim().to_string())
             .collect();
-        let output = matches.get_one::<String>("output").unwrap();
+        let output = matches.get_one::<String>("example-output").unwrap();
         let format = matches.get_one::<String>("format").unwrap();
         let dry_run = matches.get_flag("dry-run");
         let verbose = matches.get_flag("verbose");
@@ -725,4 +1344,35 @@ impl Default for ExampleGenTool {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_extracts_public_api_usage_from_tests() {
+        let source = r#"pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        assert_eq!(add(2, 3), 5);
+    }
+}
+"#;
+        let tool = ExampleGenTool::new();
+        let (usages, api) = tool
+            .analyze_source(source, "src/math.rs", "math", false)
+            .unwrap();
+        assert_eq!(api.len(), 1);
+        assert_eq!(api[0].doc_insert_at, 0);
+        assert_eq!(usages[0].calls, vec!["add".to_string()]);
+        let example = tool.render_example_file(&usages[0], "demo");
+        assert!(example.contains("use demo::math::*;"));
+        assert!(example.contains("fn main() {\n    assert_eq!(add(2, 3), 5);\n}"));
+    }
+}
@@ -1,10 +1,12 @@
 use super::{Tool, Result, ToolError, common_options, parse_output_format, OutputFormat};
 use clap::{Arg, ArgMatches, Command};
 use colored::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::Command as ProcessCommand;
 use std::fs;
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 #[derive(Debug, Clone)]
 pub struct InstallerGenTool;
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +15,11 @@ struct InstallerConfig {
     version: String,
     description: String,
     author: String,
+    license: String,
+    homepage: String,
+    binaries: Vec<String>,
+    services: Vec<String>,
+    manifest_dir: PathBuf,
     platforms: Vec<String>,
     files: Vec<String>,
     dependencies: Vec<String>,
@@ -64,11 +71,51 @@ impl InstallerGenTool {
             .and_then(|first| first.as_str())
             .unwrap_or("Unknown Author")
             .to_string();
+        let text = |key: &str| {
+            package.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+        };
+        let license = text("license");
+        let homepage = match text("homepage") {
+            homepage if homepage.is_empty() => text("repository"),
+            homepage => homepage,
+        };
+        let manifest_dir = Path::new(manifest_path)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        let mut binaries: Vec<String> = cargo_toml
+            .get("bin")
+            .and_then(|b| b.as_array())
+            .map(|bins| {
+                bins.iter()
+                    .filter_map(|bin| bin.get("name").and_then(|n| n.as_str()))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if binaries.is_empty() && manifest_dir.join("src/main.rs").exists() {
+            binaries.push(name.clone());
+        }
+        let services = package
+            .get("metadata")
+            .and_then(|m| m.get("installer"))
+            .and_then(|i| i.get("services"))
+            .and_then(|s| s.as_array())
+            .map(|services| {
+                services.iter().filter_map(|s| s.as_str()).map(str::to_string).collect()
+            })
+            .unwrap_or_default();
         Ok(InstallerConfig {
             name,
             version,
             description,
             author: authors,
+            license,
+            homepage,
+            binaries,
+            services,
+            manifest_dir,
             platforms: vec![
                 "windows".to_string(), "macos".to_string(), "linux".to_string()
             ],
@@ -705,6 +752,611 @@ echo "AppImage created: ${{APPIMAGE_NAME}}"
             ),
         )
     }
+    fn apply_cargo_metadata(&self, config_file: &str, config: &mut InstallerConfig) {
+        let Ok(output) = ProcessCommand::new("cargo")
+            .args(["metadata", "--no-deps", "--format-version", "1", "--manifest-path"])
+            .arg(config_file)
+            .output() else {
+            return;
+        };
+        if !output.status.success() {
+            return;
+        }
+        let Ok(metadata) = serde_json::from_slice::<serde_json::Value>(&output.stdout)
+        else {
+            return;
+        };
+        let manifest = fs::canonicalize(config_file).unwrap_or_default();
+        let Some(package) = metadata["packages"]
+            .as_array()
+            .and_then(|packages| {
+                packages
+                    .iter()
+                    .find(|p| {
+                        p["manifest_path"].as_str().map(PathBuf::from)
+                            == Some(manifest.clone())
+                    })
+                    .or_else(|| packages.first())
+            }) else {
+            return;
+        };
+        let binaries: Vec<String> = package["targets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|target| {
+                target["kind"].as_array().is_some_and(|kinds| kinds.iter().any(|k| k == "bin"))
+            })
+            .filter_map(|target| target["name"].as_str().map(str::to_string))
+            .collect();
+        if !binaries.is_empty() {
+            config.binaries = binaries;
+        }
+        if let Some(license) = package["license"].as_str() {
+            config.license = license.to_string();
+        }
+    }
+    fn detect_services(&self, config: &mut InstallerConfig, explicit: Option<&String>) {
+        if let Some(list) = explicit {
+            config.services = list
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        } else if config.services.is_empty() {
+            config.services = config
+                .binaries
+                .iter()
+                .filter(|bin| {
+                    ["daemon", "server", "service"].iter().any(|word| bin.contains(word))
+                })
+                .cloned()
+                .collect();
+        }
+    }
+    fn deb_arch() -> &'static str {
+        match std::env::consts::ARCH {
+            "x86_64" => "amd64",
+            "aarch64" => "arm64",
+            "arm" => "armhf",
+            "x86" => "i386",
+            other => other,
+        }
+    }
+    fn generate_systemd_unit(&self, config: &InstallerConfig, binary: &str) -> String {
+        format!(
+            r#"[Unit]
+Description={description} ({binary})
+After=network-online.target
+Wants=network-online.target
+
+[Service]
+Type=simple
+ExecStart=/usr/bin/{binary}
+EnvironmentFile=-/etc/default/{binary}
+Restart=on-failure
+RestartSec=5
+DynamicUser=yes
+NoNewPrivileges=yes
+ProtectSystem=strict
+ProtectHome=yes
+
+[Install]
+WantedBy=multi-user.target
+"#,
+            description = config.description, binary = binary
+        )
+    }
+    fn generate_deb_package_control(&self, config: &InstallerConfig) -> String {
+        let mut control = format!(
+            "Package: {}\nVersion: {}\nSection: utils\nPriority: optional\nArchitecture: {}\nMaintainer: {}\nDescription: {}\n",
+            config.name, config.version, Self::deb_arch(), config.author, config
+            .description
+        );
+        if !config.homepage.is_empty() {
+            control.push_str(&format!("Homepage: {}\n", config.homepage));
+        }
+        control
+    }
+    fn generate_deb_service_scripts(&self, config: &InstallerConfig) -> (String, String) {
+        let units: Vec<String> = config
+            .services
+            .iter()
+            .map(|service| format!("{}.service", service))
+            .collect();
+        let units = units.join(" ");
+        let postinst = format!(
+            r#"#!/bin/sh
+set -e
+if [ "$1" = "configure" ] && [ -d /run/systemd/system ]; then
+    systemctl daemon-reload
+    systemctl enable --now {units} || true
+fi
+exit 0
+"#
+        );
+        let prerm = format!(
+            r#"#!/bin/sh
+set -e
+if [ -d /run/systemd/system ]; then
+    systemctl disable --now {units} || true
+fi
+exit 0
+"#
+        );
+        (postinst, prerm)
+    }
+    fn generate_deb_package(
+        &self,
+        config: &InstallerConfig,
+        output_dir: &str,
+    ) -> Result<GeneratedInstaller> {
+        let package_name = format!(
+            "{}_{}_{}", config.name, config.version, Self::deb_arch()
+        );
+        let root = Path::new(output_dir).join(&package_name);
+        let debian = root.join("DEBIAN");
+        fs::create_dir_all(&debian)?;
+        let mut files_created = Vec::new();
+        let mut write = |path: PathBuf, content: String| -> Result<()> {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, content)?;
+            files_created.push(path.to_string_lossy().to_string());
+            Ok(())
+        };
+        write(debian.join("control"), self.generate_deb_package_control(config))?;
+        if !config.services.is_empty() {
+            let (postinst, prerm) = self.generate_deb_service_scripts(config);
+            write(debian.join("postinst"), postinst)?;
+            write(debian.join("prerm"), prerm)?;
+            for service in &config.services {
+                write(
+                    root.join("lib/systemd/system").join(format!("{}.service", service)),
+                    self.generate_systemd_unit(config, service),
+                )?;
+            }
+        }
+        let mut metadata = HashMap::new();
+        metadata.insert("installer_type".to_string(), "Debian package".to_string());
+        metadata.insert("staging_dir".to_string(), root.to_string_lossy().to_string());
+        Ok(GeneratedInstaller {
+            platform: "deb".to_string(),
+            files_created,
+            installer_size: 0,
+            installer_path: Path::new(output_dir)
+                .join(format!("{}.deb", package_name))
+                .to_string_lossy()
+                .to_string(),
+            metadata,
+        })
+    }
+    fn generate_rpm_package_spec(&self, config: &InstallerConfig) -> String {
+        let mut spec = format!(
+            r#"%{{!?cargo_target: %global cargo_target target/release}}
+%{{!?unit_dir: %global unit_dir .}}
+
+Name:           {name}
+Version:        {version}
+Release:        1%{{?dist}}
+Summary:        {description}
+License:        {license}
+"#,
+            name = config.name, version = config.version, description = config
+            .description, license = if config.license.is_empty() { "Unknown" } else {
+            config.license.as_str() }
+        );
+        if !config.homepage.is_empty() {
+            spec.push_str(&format!("URL:            {}\n", config.homepage));
+        }
+        if !config.services.is_empty() {
+            spec.push_str("BuildRequires:  systemd-rpm-macros\n");
+        }
+        spec.push_str(&format!("\n%description\n{}\n\n%install\n", config.description));
+        for binary in &config.binaries {
+            spec.push_str(
+                &format!(
+                    "install -Dm755 %{{cargo_target}}/{0} %{{buildroot}}%{{_bindir}}/{0}\n",
+                    binary
+                ),
+            );
+        }
+        for service in &config.services {
+            spec.push_str(
+                &format!(
+                    "install -Dm644 %{{unit_dir}}/{0}.service %{{buildroot}}%{{_unitdir}}/{0}.service\n",
+                    service
+                ),
+            );
+        }
+        if !config.services.is_empty() {
+            let units: Vec<String> = config
+                .services
+                .iter()
+                .map(|service| format!("{}.service", service))
+                .collect();
+            let units = units.join(" ");
+            spec.push_str(
+                &format!(
+                    "\n%post\n%systemd_post {units}\n\n%preun\n%systemd_preun {units}\n\n%postun\n%systemd_postun_with_restart {units}\n"
+                ),
+            );
+        }
+        spec.push_str("\n%files\n");
+        for binary in &config.binaries {
+            spec.push_str(&format!("%{{_bindir}}/{}\n", binary));
+        }
+        for service in &config.services {
+            spec.push_str(&format!("%{{_unitdir}}/{}.service\n", service));
+        }
+        spec.push_str(
+            &format!(
+                "\n%changelog\n* {} {} - {}-1\n- Package generated by CargoMate InstallerGen\n",
+                chrono::Utc::now().format("%a %b %d %Y"), config.author, config.version
+            ),
+        );
+        spec
+    }
+    fn generate_rpm_package(
+        &self,
+        config: &InstallerConfig,
+        output_dir: &str,
+    ) -> Result<GeneratedInstaller> {
+        fs::create_dir_all(output_dir)?;
+        let spec_path = Path::new(output_dir).join(format!("{}.spec", config.name));
+        fs::write(&spec_path, self.generate_rpm_package_spec(config))?;
+        let mut files_created = vec![spec_path.to_string_lossy().to_string()];
+        for service in &config.services {
+            let unit_path = Path::new(output_dir).join(format!("{}.service", service));
+            fs::write(&unit_path, self.generate_systemd_unit(config, service))?;
+            files_created.push(unit_path.to_string_lossy().to_string());
+        }
+        let mut metadata = HashMap::new();
+        metadata.insert("installer_type".to_string(), "RPM package".to_string());
+        metadata.insert("spec".to_string(), spec_path.to_string_lossy().to_string());
+        Ok(GeneratedInstaller {
+            platform: "rpm".to_string(),
+            files_created,
+            installer_size: 0,
+            installer_path: Path::new(output_dir)
+                .join(
+                    format!(
+                        "{}-{}-1.{}.rpm", config.name, config.version,
+                        std::env::consts::ARCH
+                    ),
+                )
+                .to_string_lossy()
+                .to_string(),
+            metadata,
+        })
+    }
+    fn formula_class_name(name: &str) -> String {
+        name.split(['-', '_'])
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let mut chars = part.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+    fn generate_homebrew_formula(
+        &self,
+        config: &InstallerConfig,
+        sha256: Option<&str>,
+    ) -> String {
+        let mut formula = format!(
+            "class {} < Formula\n  desc \"{}\"\n", Self::formula_class_name(& config
+            .name), config.description.replace('"', "\\\"")
+        );
+        if !config.homepage.is_empty() {
+            formula.push_str(&format!("  homepage \"{}\"\n", config.homepage));
+        }
+        formula.push_str(
+            &format!(
+                "  url \"https://static.crates.io/crates/{0}/{0}-{1}.crate\"\n", config
+                .name, config.version
+            ),
+        );
+        match sha256 {
+            Some(sha) => formula.push_str(&format!("  sha256 \"{}\"\n", sha)),
+            None => {
+                formula
+                    .push_str(
+                        "  sha256 \"\" # run `cm tool installer-gen --platforms homebrew --build` after publishing\n",
+                    )
+            }
+        }
+        if !config.license.is_empty() {
+            let licenses: Vec<String> = config
+                .license
+                .split(" OR ")
+                .map(|license| format!("\"{}\"", license.trim()))
+                .collect();
+            if licenses.len() > 1 {
+                formula.push_str(&format!("  license any_of: [{}]\n", licenses.join(", ")));
+            } else {
+                formula.push_str(&format!("  license {}\n", licenses[0]));
+            }
+        }
+        formula.push_str("\n  depends_on \"rust\" => :build\n\n  def install\n");
+        formula.push_str("    system \"cargo\", \"install\", *std_cargo_args\n  end\n");
+        if let Some(service) = config.services.first() {
+            formula.push_str(
+                &format!(
+                    "\n  service do\n    run [opt_bin/\"{}\"]\n    keep_alive true\n    log_path var/\"log/{}.log\"\n    error_log_path var/\"log/{}.log\"\n  end\n",
+                    service, service, service
+                ),
+            );
+        }
+        if let Some(binary) = config.binaries.first() {
+            formula.push_str(
+                &format!(
+                    "\n  test do\n    system bin/\"{}\", \"--version\"\n  end\n", binary
+                ),
+            );
+        }
+        formula.push_str("end\n");
+        formula
+    }
+    fn generate_homebrew_package(
+        &self,
+        config: &InstallerConfig,
+        output_dir: &str,
+        sha256: Option<&str>,
+    ) -> Result<GeneratedInstaller> {
+        fs::create_dir_all(output_dir)?;
+        let formula_path = Path::new(output_dir).join(format!("{}.rb", config.name));
+        fs::write(&formula_path, self.generate_homebrew_formula(config, sha256))?;
+        let mut metadata = HashMap::new();
+        metadata.insert("installer_type".to_string(), "Homebrew formula".to_string());
+        Ok(GeneratedInstaller {
+            platform: "homebrew".to_string(),
+            files_created: vec![formula_path.to_string_lossy().to_string()],
+            installer_size: 0,
+            installer_path: formula_path.to_string_lossy().to_string(),
+            metadata,
+        })
+    }
+    fn stable_guid(seed: &str) -> String {
+        let hash = format!("{:x}", Sha256::digest(seed.as_bytes())).to_uppercase();
+        format!(
+            "{}-{}-{}-{}-{}", & hash[0..8], & hash[8..12], & hash[12..16], & hash[16..20],
+            & hash[20..32]
+        )
+    }
+    fn xml_escape(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+    fn generate_wix_source(&self, config: &InstallerConfig) -> String {
+        let version: Vec<&str> = config
+            .version
+            .split(['-', '+'])
+            .next()
+            .unwrap_or("0.1.0")
+            .split('.')
+            .collect();
+        let msi_version = format!(
+            "{}.{}.{}", version.first().unwrap_or(& "0"), version.get(1).unwrap_or(&
+            "0"), version.get(2).unwrap_or(& "0")
+        );
+        let components: String = config
+            .binaries
+            .iter()
+            .map(|binary| {
+                format!(
+                    "                        <Component Id=\"{id}\" Guid=\"{guid}\">\n                            <File Id=\"{id}Exe\" Name=\"{binary}.exe\" DiskId=\"1\" Source=\"$(var.CargoTargetBinDir)\\{binary}.exe\" KeyPath=\"yes\"/>\n                        </Component>\n",
+                    id = Self::formula_class_name(binary), guid = Self::stable_guid(&
+                    format!("{}:{}", config.name, binary)), binary = binary
+                )
+            })
+            .collect();
+        let refs: String = config
+            .binaries
+            .iter()
+            .map(|binary| {
+                format!(
+                    "            <ComponentRef Id=\"{}\"/>\n", Self::formula_class_name(binary)
+                )
+            })
+            .collect();
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<!-- WiX source for {name}, generated by CargoMate InstallerGen -->
+<!-- Build: candle -dCargoTargetBinDir=target\release main.wxs && light main.wixobj -o {name}-{version}.msi -->
+<Wix xmlns="http://schemas.microsoft.com/wix/2006/wi">
+    <Product Id="*" Name="{name}" Language="1033" Version="{msi_version}" Manufacturer="{author}" UpgradeCode="{upgrade}">
+        <Package InstallerVersion="450" Compressed="yes" InstallScope="perMachine" Description="{description}"/>
+        <MajorUpgrade DowngradeErrorMessage="A newer version of {name} is already installed."/>
+        <Media Id="1" Cabinet="media1.cab" EmbedCab="yes"/>
+        <Directory Id="TARGETDIR" Name="SourceDir">
+            <Directory Id="ProgramFiles64Folder">
+                <Directory Id="APPLICATIONFOLDER" Name="{name}">
+                    <Directory Id="Bin" Name="bin">
+{components}                        <Component Id="Path" Guid="{path_guid}" KeyPath="yes">
+                            <Environment Id="PATH" Name="PATH" Value="[Bin]" Permanent="no" Part="last" Action="set" System="yes"/>
+                        </Component>
+                    </Directory>
+                </Directory>
+            </Directory>
+        </Directory>
+        <Feature Id="Binaries" Title="{name}" Level="1">
+{refs}            <ComponentRef Id="Path"/>
+        </Feature>
+    </Product>
+</Wix>
+"#,
+            name = config.name, version = config.version, msi_version = msi_version,
+            author = Self::xml_escape(& config.author), description = Self::xml_escape(&
+            config.description), upgrade =
+            Self::stable_guid(& format!("{}:upgrade", config.name)), path_guid =
+            Self::stable_guid(& format!("{}:path", config.name)), components =
+            components, refs = refs
+        )
+    }
+    fn generate_msi_package(
+        &self,
+        config: &InstallerConfig,
+        output_dir: &str,
+    ) -> Result<GeneratedInstaller> {
+        fs::create_dir_all(output_dir)?;
+        let wxs_path = Path::new(output_dir).join("main.wxs");
+        fs::write(&wxs_path, self.generate_wix_source(config))?;
+        let mut metadata = HashMap::new();
+        metadata.insert("installer_type".to_string(), "MSI (WiX)".to_string());
+        metadata.insert("upgrade_code".to_string(), Self::stable_guid(&format!("{}:upgrade", config.name)));
+        Ok(GeneratedInstaller {
+            platform: "msi".to_string(),
+            files_created: vec![wxs_path.to_string_lossy().to_string()],
+            installer_size: 0,
+            installer_path: Path::new(output_dir)
+                .join(format!("{}-{}.msi", config.name, config.version))
+                .to_string_lossy()
+                .to_string(),
+            metadata,
+        })
+    }
+    fn release_binary_dir(&self, config: &InstallerConfig, verbose: bool) -> Result<PathBuf> {
+        let target_dir = std::env::var("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| config.manifest_dir.join("target"))
+            .join("release");
+        let suffix = std::env::consts::EXE_SUFFIX;
+        let missing = config
+            .binaries
+            .iter()
+            .any(|binary| !target_dir.join(format!("{}{}", binary, suffix)).exists());
+        if missing {
+            if verbose {
+                println!("🔨 Building release binaries...");
+            }
+            let status = ProcessCommand::new("cargo")
+                .args(["build", "--release", "--bins", "--manifest-path"])
+                .arg(config.manifest_dir.join("Cargo.toml"))
+                .status()?;
+            if !status.success() {
+                return Err(
+                    ToolError::ExecutionFailed("cargo build --release failed".to_string()),
+                );
+            }
+        }
+        Ok(fs::canonicalize(&target_dir)?)
+    }
+    fn run_packager(&self, command: &mut ProcessCommand) -> Result<()> {
+        let output = command.output()?;
+        if !output.status.success() {
+            return Err(
+                ToolError::ExecutionFailed(
+                    format!(
+                        "{:?} failed: {}", command.get_program(), String::from_utf8_lossy(&
+                        output.stderr)
+                    ),
+                ),
+            );
+        }
+        Ok(())
+    }
+    fn build_native_package(
+        &self,
+        config: &InstallerConfig,
+        installer: &mut GeneratedInstaller,
+        output_dir: &str,
+        verbose: bool,
+    ) -> Result<bool> {
+        let available = |tool: &str| which::which(tool).is_ok();
+        match installer.platform.as_str() {
+            "deb" if available("dpkg-deb") => {
+                let staging = PathBuf::from(&installer.metadata["staging_dir"]);
+                let bin_dir = self.release_binary_dir(config, verbose)?;
+                for binary in &config.binaries {
+                    let target = staging.join("usr/bin").join(binary);
+                    fs::create_dir_all(staging.join("usr/bin"))?;
+                    fs::copy(bin_dir.join(binary), &target)?;
+                }
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    for script in ["postinst", "prerm"] {
+                        let path = staging.join("DEBIAN").join(script);
+                        if path.exists() {
+                            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+                        }
+                    }
+                }
+                self.run_packager(
+                    ProcessCommand::new("dpkg-deb")
+                        .args(["--build", "--root-owner-group"])
+                        .arg(&staging)
+                        .arg(&installer.installer_path),
+                )?;
+            }
+            "rpm" if available("rpmbuild") => {
+                let bin_dir = self.release_binary_dir(config, verbose)?;
+                let out = fs::canonicalize(output_dir)?;
+                self.run_packager(
+                    ProcessCommand::new("rpmbuild")
+                        .arg("-bb")
+                        .arg("--define")
+                        .arg(format!("_topdir {}", out.join("rpmbuild").display()))
+                        .arg("--define")
+                        .arg(format!("_rpmdir {}", out.display()))
+                        .arg("--define")
+                        .arg("_build_name_fmt %%{NAME}-%%{VERSION}-%%{RELEASE}.%%{ARCH}.rpm")
+                        .arg("--define")
+                        .arg(format!("cargo_target {}", bin_dir.display()))
+                        .arg("--define")
+                        .arg(format!("unit_dir {}", out.display()))
+                        .arg(&installer.metadata["spec"]),
+                )?;
+            }
+            "homebrew" => {
+                self.run_packager(
+                    ProcessCommand::new("cargo")
+                        .args(["package", "--no-verify", "--allow-dirty", "--manifest-path"])
+                        .arg(config.manifest_dir.join("Cargo.toml")),
+                )?;
+                let target_dir = std::env::var("CARGO_TARGET_DIR")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| config.manifest_dir.join("target"));
+                let crate_file = target_dir
+                    .join("package")
+                    .join(format!("{}-{}.crate", config.name, config.version));
+                let sha = format!("{:x}", Sha256::digest(fs::read(&crate_file)?));
+                self.generate_homebrew_package(config, output_dir, Some(&sha))?;
+                installer.metadata.insert("sha256".to_string(), sha);
+                return Ok(true);
+            }
+            "msi" if available("candle") && available("light") => {
+                let bin_dir = self.release_binary_dir(config, verbose)?;
+                let wixobj = Path::new(output_dir).join("main.wixobj");
+                self.run_packager(
+                    ProcessCommand::new("candle")
+                        .arg(format!("-dCargoTargetBinDir={}", bin_dir.display()))
+                        .arg("-out")
+                        .arg(&wixobj)
+                        .arg(Path::new(output_dir).join("main.wxs")),
+                )?;
+                self.run_packager(
+                    ProcessCommand::new("light")
+                        .arg(&wixobj)
+                        .arg("-out")
+                        .arg(&installer.installer_path),
+                )?;
+            }
+            _ => return Ok(false),
+        }
+        installer.installer_size = fs::metadata(&installer.installer_path)
+            .map(|m| m.len())
+            .unwrap_or(0);
+        Ok(true)
+    }
     fn display_report(
         &self,
         installers: &[GeneratedInstaller],
@@ -758,6 +1410,13 @@ echo "AppImage created: ${{APPIMAGE_NAME}}"
                             println!("  • RPM: rpmbuild -ba package.spec");
                             println!("  • AppImage: bash create_appimage.sh");
                         }
+                        "deb" | "rpm" | "homebrew" | "msi"
+                            if !installer.metadata.contains_key("built") => {
+                            println!(
+                                "  • {}: cm tool installer-gen --platforms {} --build",
+                                installer.platform.to_uppercase(), installer.platform
+                            );
+                        }
                         _ => {}
                     }
                 }
@@ -799,11 +1458,13 @@ impl Tool for InstallerGenTool {
             .long_about(
                 "Generate platform-specific installers for your Rust application. \
                         Supports Windows (NSIS/Inno Setup/PowerShell), macOS (.pkg/.dmg), \
-                        and Linux (DEB/RPM/AppImage) installers.
+                        and Linux (DEB/RPM/AppImage) installers, plus native deb, rpm, \
+                        Homebrew and MSI/WiX packages built from Cargo metadata.
 
 EXAMPLES:
     cm tool installer-gen --platforms windows,macos
-    cm tool installer-gen --config installer.toml --output installers/
+    cm tool installer-gen --config installer.toml --installer-output installers/
+    cm tool installer-gen --platforms deb,rpm,homebrew,msi --services my-server --build
     cm tool installer-gen --sign --notarize",
             )
             .args(
@@ -811,16 +1472,15 @@ EXAMPLES:
                     Arg::new("platforms")
                         .long("platforms")
                         .short('p')
-                        .help("Target platforms (windows,macos,linux)")
+                        .help("Target platforms (windows,macos,linux,deb,rpm,homebrew,msi)")
                         .default_value("windows,macos,linux"),
                     Arg::new("config")
                         .long("config")
                         .short('c')
                         .help("Configuration file (TOML)")
                         .default_value("Cargo.toml"),
-                    Arg::new("output")
-                        .long("output")
-                        .short('o')
+                    Arg::new("installer-output")
+                        .long("installer-output")
                         .help("Output directory for installers")
                         .default_value("installers/"),
                     Arg::new("name")
@@ -829,8 +1489,16 @@ EXAMPLES:
                         .help("Application name (from Cargo.toml)"),
                     Arg::new("version")
                         .long("version")
-                        .short('v')
                         .help("Application version (from Cargo.toml)"),
+                    Arg::new("services")
+                        .long("services")
+                        .help(
+                            "Comma-separated binaries that get systemd units (default: package.metadata.installer.services or *server/*daemon/*service binaries)",
+                        ),
+                    Arg::new("build")
+                        .long("build")
+                        .help("Build packages with the native packagers where available")
+                        .action(clap::ArgAction::SetTrue),
                     Arg::new("sign")
                         .long("sign")
                         .help("Sign installers (requires certificates)")
@@ -851,7 +1519,8 @@ EXAMPLES:
             .map(|s| s.trim().to_string())
             .collect();
         let config_file = matches.get_one::<String>("config").unwrap();
-        let output_dir = matches.get_one::<String>("output").unwrap();
+        let output_dir = matches.get_one::<String>("installer-output").unwrap();
+        let build = matches.get_flag("build");
         let sign = matches.get_flag("sign");
         let notarize = matches.get_flag("notarize");
         let output_format = parse_output_format(matches);
@@ -875,6 +1544,8 @@ EXAMPLES:
         if let Some(version) = matches.get_one::<String>("version") {
             config.version = version.clone();
         }
+        self.apply_cargo_metadata(config_file, &mut config);
+        self.detect_services(&mut config, matches.get_one::<String>("services"));
         fs::create_dir_all(output_dir)?;
         let mut generated_installers = Vec::new();
         for platform in &platforms {
@@ -935,6 +1606,46 @@ EXAMPLES:
                         }
                     }
                 }
+                "deb" | "rpm" | "homebrew" | "msi" => {
+                    let dir = platform_output.to_string_lossy().to_string();
+                    let generated = match platform.as_str() {
+                        "deb" => self.generate_deb_package(&config, &dir),
+                        "rpm" => self.generate_rpm_package(&config, &dir),
+                        "homebrew" => self.generate_homebrew_package(&config, &dir, None),
+                        _ => self.generate_msi_package(&config, &dir),
+                    };
+                    match generated {
+                        Ok(mut installer) => {
+                            if build {
+                                match self
+                                    .build_native_package(&config, &mut installer, &dir, verbose)
+                                {
+                                    Ok(true) => {
+                                        installer
+                                            .metadata
+                                            .insert("built".to_string(), "yes".to_string());
+                                        println!(
+                                            "✅ Built {}", installer.installer_path.green()
+                                        );
+                                    }
+                                    Ok(false) => {
+                                        println!(
+                                            "⚠️  No native packager for {} found - generated definitions only",
+                                            platform
+                                        );
+                                    }
+                                    Err(e) => {
+                                        println!("❌ Failed to build {} package: {}", platform, e);
+                                    }
+                                }
+                            }
+                            generated_installers.push(installer);
+                        }
+                        Err(e) => {
+                            println!("❌ Failed to generate {} package: {}", platform, e);
+                        }
+                    }
+                }
                 _ => {
                     println!("⚠️  Unsupported platform: {}", platform);
                 }
@@ -965,4 +1676,41 @@ impl Default for InstallerGenTool {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_native_package_definitions() {
+        let tool = InstallerGenTool::new();
+        let config = InstallerConfig {
+            name: "my-app".to_string(),
+            version: "1.2.3-beta.1".to_string(),
+            description: "Fast \"tool\"".to_string(),
+            author: "Jane <jane@example.com>".to_string(),
+            license: "MIT OR Apache-2.0".to_string(),
+            homepage: "https://example.com".to_string(),
+            binaries: vec!["my-app".to_string(), "my-app-server".to_string()],
+            services: vec!["my-app-server".to_string()],
+            manifest_dir: PathBuf::from("."),
+            platforms: vec![],
+            files: vec![],
+            dependencies: vec![],
+            scripts: HashMap::new(),
+            metadata: HashMap::new(),
+        };
+        let spec = tool.generate_rpm_package_spec(&config);
+        assert!(spec.contains("%systemd_post my-app-server.service"));
+        assert!(spec.contains("%{_bindir}/my-app-server"));
+        let formula = tool.generate_homebrew_formula(&config, None);
+        assert!(formula.starts_with("class MyApp < Formula"));
+        assert!(formula.contains("license any_of: [\"MIT\", \"Apache-2.0\"]"));
+        assert!(formula.contains("run [opt_bin/\"my-app-server\"]"));
+        let wxs = tool.generate_wix_source(&config);
+        assert!(wxs.contains("Version=\"1.2.3\""));
+        assert!(wxs.contains("Manufacturer=\"Jane &lt;jane@example.com&gt;\""));
+        assert!(tool
+            .generate_systemd_unit(&config, "my-app-server")
+            .contains("ExecStart=/usr/bin/my-app-server"));
+    }
+}
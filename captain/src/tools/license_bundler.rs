@@ -2,10 +2,13 @@ use super::{Tool, Result, ToolError, common_options, parse_output_format, Output
 use clap::{Arg, ArgMatches, Command};
 use colored::*;
 use std::path::Path;
+use std::process::Command as ProcessCommand;
 use std::fs;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
 use toml::{self, Value};
 #[derive(Debug, Clone)]
 pub struct LicenseBundlerTool;
@@ -49,6 +52,39 @@ struct LicenseStatistics {
     most_common_license: String,
     compatibility_score: f64,
 }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SbomPackage {
+    id: String,
+    name: String,
+    version: String,
+    license: Option<String>,
+    source_url: Option<String>,
+    repository: Option<String>,
+    checksum: Option<String>,
+    purl: String,
+    is_root: bool,
+    dependencies: Vec<String>,
+}
+#[derive(Debug, Clone, Default, Deserialize)]
+struct LicensePolicy {
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    exceptions: Vec<String>,
+    #[serde(default)]
+    unknown: Option<String>,
+    #[serde(default)]
+    profiles: HashMap<String, LicensePolicy>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PolicyViolation {
+    package: String,
+    version: String,
+    license: String,
+    reason: String,
+}
 impl LicenseBundlerTool {
     pub fn new() -> Self {
         Self
@@ -472,6 +508,521 @@ Generated on: {}
             );
         recommendations
     }
+    fn lock_checksums(&self, lock_path: &str) -> HashMap<(String, String), String> {
+        let mut checksums = HashMap::new();
+        let Ok(lock) = fs::read_to_string(lock_path)
+            .map_err(|_| ())
+            .and_then(|content| toml::from_str::<Value>(&content).map_err(|_| ())) else {
+            return checksums;
+        };
+        for package in lock.get("package").and_then(|p| p.as_array()).into_iter().flatten() {
+            let field = |key: &str| package.get(key).and_then(|v| v.as_str());
+            if let (Some(name), Some(version), Some(checksum)) = (
+                field("name"),
+                field("version"),
+                field("checksum"),
+            ) {
+                checksums
+                    .insert((name.to_string(), version.to_string()), checksum.to_string());
+            }
+        }
+        checksums
+    }
+    fn source_url(name: &str, version: &str, source: Option<&str>) -> Option<String> {
+        let source = source?;
+        if source.starts_with("registry+https://github.com/rust-lang/crates.io-index")
+            || source.starts_with("sparse+https://index.crates.io/")
+        {
+            Some(format!("https://crates.io/api/v1/crates/{}/{}/download", name, version))
+        } else if let Some(git) = source.strip_prefix("git+") {
+            Some(git.to_string())
+        } else {
+            Some(source.split_once('+').map(|(_, url)| url).unwrap_or(source).to_string())
+        }
+    }
+    fn collect_sbom_packages(&self, exclude_dev: bool) -> Result<Vec<SbomPackage>> {
+        let output = ProcessCommand::new("cargo")
+            .args(["metadata", "--format-version", "1"])
+            .output()
+            .map_err(|e| ToolError::ExecutionFailed(
+                format!("Failed to run cargo metadata: {}", e),
+            ))?;
+        if !output.status.success() {
+            return Err(
+                ToolError::ExecutionFailed(
+                    format!(
+                        "cargo metadata failed: {}", String::from_utf8_lossy(& output
+                        .stderr)
+                    ),
+                ),
+            );
+        }
+        let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        let roots: Vec<String> = metadata["workspace_members"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| id.as_str().map(str::to_string))
+            .collect();
+        let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+        for node in metadata["resolve"]["nodes"].as_array().into_iter().flatten() {
+            let Some(id) = node["id"].as_str() else {
+                continue;
+            };
+            let deps = node["deps"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|dep| {
+                    !exclude_dev
+                        || dep["dep_kinds"]
+                            .as_array()
+                            .is_some_and(|kinds| kinds.iter().any(|k| k["kind"] != "dev"))
+                })
+                .filter_map(|dep| dep["pkg"].as_str().map(str::to_string))
+                .collect();
+            edges.insert(id.to_string(), deps);
+        }
+        let mut reachable: HashSet<String> = HashSet::new();
+        let mut queue: VecDeque<String> = roots.iter().cloned().collect();
+        while let Some(id) = queue.pop_front() {
+            if reachable.insert(id.clone()) {
+                queue.extend(edges.get(&id).cloned().unwrap_or_default());
+            }
+        }
+        let checksums = self.lock_checksums("Cargo.lock");
+        let mut packages: Vec<SbomPackage> = metadata["packages"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|package| {
+                let id = package["id"].as_str()?.to_string();
+                if !reachable.contains(&id) {
+                    return None;
+                }
+                let name = package["name"].as_str()?.to_string();
+                let version = package["version"].as_str()?.to_string();
+                let source = package["source"].as_str();
+                Some(SbomPackage {
+                    license: package["license"]
+                        .as_str()
+                        .map(Self::normalize_license_expression),
+                    source_url: Self::source_url(&name, &version, source),
+                    repository: package["repository"].as_str().map(str::to_string),
+                    checksum: checksums.get(&(name.clone(), version.clone())).cloned(),
+                    purl: format!("pkg:cargo/{}@{}", name, version),
+                    is_root: roots.contains(&id),
+                    dependencies: edges.get(&id).cloned().unwrap_or_default(),
+                    id,
+                    name,
+                    version,
+                })
+            })
+            .collect();
+        packages
+            .sort_by(|a, b| {
+                b.is_root
+                    .cmp(&a.is_root)
+                    .then_with(|| a.name.cmp(&b.name))
+                    .then_with(|| a.version.cmp(&b.version))
+            });
+        Ok(packages)
+    }
+    fn normalize_license_expression(license: &str) -> String {
+        license
+            .split('/')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    }
+    fn license_pattern_matches(pattern: &str, license: &str) -> bool {
+        match pattern.strip_suffix('*') {
+            Some(prefix) => license.to_lowercase().starts_with(&prefix.to_lowercase()),
+            None => pattern.eq_ignore_ascii_case(license),
+        }
+    }
+    fn check_license_leaf(
+        license: &str,
+        policy: &LicensePolicy,
+    ) -> std::result::Result<(), String> {
+        if policy.deny.iter().any(|p| Self::license_pattern_matches(p, license)) {
+            return Err(format!("{} is denied by policy", license));
+        }
+        if !policy.allow.is_empty()
+            && !policy.allow.iter().any(|p| Self::license_pattern_matches(p, license))
+        {
+            return Err(format!("{} is not in the allow list", license));
+        }
+        Ok(())
+    }
+    fn evaluate_license_expression(
+        tokens: &[String],
+        pos: &mut usize,
+        policy: &LicensePolicy,
+    ) -> std::result::Result<(), String> {
+        let mut any_ok = false;
+        let mut reasons = Vec::new();
+        loop {
+            let mut all_ok = true;
+            loop {
+                let result = match tokens.get(*pos).map(String::as_str) {
+                    Some("(") => {
+                        *pos += 1;
+                        let inner = Self::evaluate_license_expression(tokens, pos, policy);
+                        *pos += 1;
+                        inner
+                    }
+                    Some(license) => {
+                        *pos += 1;
+                        if tokens.get(*pos).map(String::as_str) == Some("WITH") {
+                            *pos += 2;
+                        }
+                        Self::check_license_leaf(license, policy)
+                    }
+                    None => Err("empty license expression".to_string()),
+                };
+                if let Err(reason) = result {
+                    all_ok = false;
+                    reasons.push(reason);
+                }
+                if tokens.get(*pos).map(String::as_str) != Some("AND") {
+                    break;
+                }
+                *pos += 1;
+            }
+            any_ok |= all_ok;
+            if tokens.get(*pos).map(String::as_str) != Some("OR") {
+                break;
+            }
+            *pos += 1;
+        }
+        if any_ok { Ok(()) } else { Err(reasons.join("; ")) }
+    }
+    fn check_license_expression(
+        expression: &str,
+        policy: &LicensePolicy,
+    ) -> std::result::Result<(), String> {
+        let tokens: Vec<String> = expression
+            .replace('(', " ( ")
+            .replace(')', " ) ")
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        Self::evaluate_license_expression(&tokens, &mut 0, policy)
+    }
+    fn load_policy(&self, path: &str, profile: Option<&String>) -> Result<LicensePolicy> {
+        let content = fs::read_to_string(path)?;
+        let mut policy: LicensePolicy = toml::from_str(&content)?;
+        if let Some(name) = profile {
+            let overlay = policy
+                .profiles
+                .remove(name)
+                .ok_or_else(|| ToolError::InvalidArguments(
+                    format!("Policy profile '{}' not found in {}", name, path),
+                ))?;
+            policy.deny.extend(overlay.deny);
+            if !overlay.allow.is_empty() {
+                policy.allow = overlay.allow;
+            }
+            policy.exceptions.extend(overlay.exceptions);
+            if overlay.unknown.is_some() {
+                policy.unknown = overlay.unknown;
+            }
+        }
+        Ok(policy)
+    }
+    fn evaluate_policy(
+        &self,
+        packages: &[SbomPackage],
+        policy: &LicensePolicy,
+    ) -> Vec<PolicyViolation> {
+        packages
+            .iter()
+            .filter(|package| !package.is_root)
+            .filter(|package| !policy.exceptions.contains(&package.name))
+            .filter_map(|package| {
+                let reason = match &package.license {
+                    Some(expression) => {
+                        Self::check_license_expression(expression, policy).err()?
+                    }
+                    None if policy.unknown.as_deref() == Some("deny") => {
+                        "no license expression declared".to_string()
+                    }
+                    None => return None,
+                };
+                Some(PolicyViolation {
+                    package: package.name.clone(),
+                    version: package.version.clone(),
+                    license: package
+                        .license
+                        .clone()
+                        .unwrap_or_else(|| "NOASSERTION".to_string()),
+                    reason,
+                })
+            })
+            .collect()
+    }
+    fn spdx_id(package: &SbomPackage) -> String {
+        let raw = format!("SPDXRef-Package-{}-{}", package.name, package.version);
+        raw.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '-' })
+            .collect()
+    }
+    fn document_uuid(seed: &str) -> String {
+        let hash = format!("{:x}", Sha256::digest(seed.as_bytes()));
+        format!(
+            "{}-{}-4{}-a{}-{}", & hash[0..8], & hash[8..12], & hash[13..16], & hash[17..20],
+            & hash[20..32]
+        )
+    }
+    fn generate_spdx_document(&self, packages: &[SbomPackage], created: &str) -> serde_json::Value {
+        let root = packages.iter().find(|p| p.is_root);
+        let doc_name = root
+            .map(|p| format!("{}-{}", p.name, p.version))
+            .unwrap_or_else(|| "cargo-project".to_string());
+        let ids: HashMap<&str, String> = packages
+            .iter()
+            .map(|p| (p.id.as_str(), Self::spdx_id(p)))
+            .collect();
+        let spdx_packages: Vec<serde_json::Value> = packages
+            .iter()
+            .map(|package| {
+                let mut entry = json!(
+                    { "name" : package.name, "SPDXID" : ids[package.id.as_str()],
+                    "versionInfo" : package.version, "downloadLocation" : package
+                    .source_url.clone().unwrap_or_else(|| "NOASSERTION".to_string()),
+                    "filesAnalyzed" : false, "licenseConcluded" : "NOASSERTION",
+                    "licenseDeclared" : package.license.clone().unwrap_or_else(||
+                    "NOASSERTION".to_string()), "copyrightText" : "NOASSERTION",
+                    "externalRefs" : [{ "referenceCategory" : "PACKAGE-MANAGER",
+                    "referenceType" : "purl", "referenceLocator" : package.purl }], }
+                );
+                if let Some(checksum) = &package.checksum {
+                    entry["checksums"] = json!(
+                        [{ "algorithm" : "SHA256", "checksumValue" : checksum }]
+                    );
+                }
+                if let Some(repository) = &package.repository {
+                    entry["homepage"] = json!(repository);
+                }
+                entry
+            })
+            .collect();
+        let mut relationships: Vec<serde_json::Value> = packages
+            .iter()
+            .filter(|p| p.is_root)
+            .map(|p| {
+                json!(
+                    { "spdxElementId" : "SPDXRef-DOCUMENT", "relationshipType" :
+                    "DESCRIBES", "relatedSpdxElement" : ids[p.id.as_str()] }
+                )
+            })
+            .collect();
+        for package in packages {
+            for dep in &package.dependencies {
+                if let Some(dep_id) = ids.get(dep.as_str()) {
+                    relationships
+                        .push(
+                            json!(
+                                { "spdxElementId" : ids[package.id.as_str()],
+                                "relationshipType" : "DEPENDS_ON", "relatedSpdxElement" :
+                                dep_id }
+                            ),
+                        );
+                }
+            }
+        }
+        json!(
+            { "spdxVersion" : "SPDX-2.3", "dataLicense" : "CC0-1.0", "SPDXID" :
+            "SPDXRef-DOCUMENT", "name" : doc_name, "documentNamespace" :
+            format!("https://spdx.org/spdxdocs/{}-{}", doc_name, Self::document_uuid(&
+            format!("{}{}", doc_name, created))), "creationInfo" : { "created" : created,
+            "creators" : [format!("Tool: cargo-mate-{}", env!("CARGO_PKG_VERSION"))] },
+            "packages" : spdx_packages, "relationships" : relationships, }
+        )
+    }
+    fn generate_cyclonedx_document(
+        &self,
+        packages: &[SbomPackage],
+        created: &str,
+    ) -> serde_json::Value {
+        let purls: HashMap<&str, &str> = packages
+            .iter()
+            .map(|p| (p.id.as_str(), p.purl.as_str()))
+            .collect();
+        let component = |package: &SbomPackage| {
+            let mut entry = json!(
+                { "type" : if package.is_root { "application" } else { "library" },
+                "bom-ref" : package.purl, "name" : package.name, "version" : package
+                .version, "purl" : package.purl, }
+            );
+            if let Some(license) = &package.license {
+                entry["licenses"] = json!([{ "expression" : license }]);
+            }
+            if let Some(checksum) = &package.checksum {
+                entry["hashes"] = json!([{ "alg" : "SHA-256", "content" : checksum }]);
+            }
+            let mut references = Vec::new();
+            if let Some(url) = &package.source_url {
+                references.push(json!({ "type" : "distribution", "url" : url }));
+            }
+            if let Some(repository) = &package.repository {
+                references.push(json!({ "type" : "vcs", "url" : repository }));
+            }
+            if !references.is_empty() {
+                entry["externalReferences"] = json!(references);
+            }
+            entry
+        };
+        let root = packages.iter().find(|p| p.is_root);
+        let components: Vec<serde_json::Value> = packages
+            .iter()
+            .filter(|p| Some(p.id.as_str()) != root.map(|r| r.id.as_str()))
+            .map(component)
+            .collect();
+        let dependencies: Vec<serde_json::Value> = packages
+            .iter()
+            .map(|package| {
+                json!(
+                    { "ref" : package.purl, "dependsOn" : package.dependencies.iter()
+                    .filter_map(| dep | purls.get(dep.as_str()).copied()).collect::< Vec
+                    < _ >> () }
+                )
+            })
+            .collect();
+        let mut metadata = json!(
+            { "timestamp" : created, "tools" : { "components" : [{ "type" :
+            "application", "name" : "cargo-mate", "version" : env!("CARGO_PKG_VERSION")
+            }] } }
+        );
+        if let Some(root) = root {
+            metadata["component"] = component(root);
+        }
+        json!(
+            { "bomFormat" : "CycloneDX", "specVersion" : "1.5", "serialNumber" :
+            format!("urn:uuid:{}", Self::document_uuid(& format!("{:?}{}", root.map(| r |
+            & r.purl), created))), "version" : 1, "metadata" : metadata, "components" :
+            components, "dependencies" : dependencies, }
+        )
+    }
+    fn run_sbom(&self, matches: &ArgMatches) -> Result<()> {
+        let output_dir = matches.get_one::<String>("license-output").unwrap();
+        let check = matches.get_flag("check");
+        let ci_mode = matches.get_flag("ci-mode");
+        let output_format = parse_output_format(matches);
+        let packages = self.collect_sbom_packages(matches.get_flag("exclude-dev"))?;
+        let policy_path = matches.get_one::<String>("policy").unwrap();
+        let policy = if Path::new(policy_path).exists() {
+            Some(self.load_policy(policy_path, matches.get_one::<String>("policy-profile"))?)
+        } else if check || matches.value_source("policy")
+            == Some(clap::parser::ValueSource::CommandLine)
+        {
+            return Err(
+                ToolError::InvalidArguments(
+                    format!("License policy file {} not found", policy_path),
+                ),
+            );
+        } else {
+            None
+        };
+        let violations = policy
+            .as_ref()
+            .map(|policy| self.evaluate_policy(&packages, policy))
+            .unwrap_or_default();
+        let mut files_created = Vec::new();
+        if !check {
+            let created = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            let format = matches.get_one::<String>("sbom").map(String::as_str).unwrap_or("both");
+            fs::create_dir_all(output_dir)?;
+            if format == "spdx" || format == "both" {
+                let path = Path::new(output_dir).join("sbom.spdx.json");
+                fs::write(
+                    &path,
+                    serde_json::to_string_pretty(&self.generate_spdx_document(&packages, &created))?,
+                )?;
+                files_created.push(path.to_string_lossy().to_string());
+            }
+            if format == "cyclonedx" || format == "both" {
+                let path = Path::new(output_dir).join("sbom.cdx.json");
+                fs::write(
+                    &path,
+                    serde_json::to_string_pretty(
+                        &self.generate_cyclonedx_document(&packages, &created),
+                    )?,
+                )?;
+                files_created.push(path.to_string_lossy().to_string());
+            }
+        }
+        if ci_mode {
+            for violation in &violations {
+                println!(
+                    "::error title=License Policy::{} {} ({}): {}", violation.package,
+                    violation.version, violation.license, violation.reason
+                );
+            }
+        }
+        match output_format {
+            OutputFormat::Json => {
+                println!(
+                    "{}", serde_json::to_string_pretty(& json!({ "packages" : packages
+                    .len(), "files_created" : files_created, "policy" : policy.is_some(),
+                    "violations" : violations, })) ?
+                );
+            }
+            OutputFormat::Table => {
+                println!("{:<30} {:<15} {:<30} Policy", "Package", "Version", "License");
+                println!("{}", "─".repeat(95));
+                for package in packages.iter().filter(|p| !p.is_root) {
+                    let violation = violations.iter().any(|v| v.package == package.name && v.version == package.version);
+                    println!(
+                        "{:<30} {:<15} {:<30} {}", package.name, package.version, package
+                        .license.as_deref().unwrap_or("NOASSERTION"), if policy.is_none() {
+                        "-" } else if violation { "❌" } else { "✅" }
+                    );
+                }
+            }
+            OutputFormat::Human => {
+                println!(
+                    "\n📄 {} - {} packages in the dependency tree", "SBOM".bold().blue(),
+                    packages.len()
+                );
+                let missing = packages.iter().filter(|p| p.license.is_none()).count();
+                if missing > 0 {
+                    println!("  ⚠️  {} packages declare no license expression", missing);
+                }
+                for file in &files_created {
+                    println!("  📁 {}", file.green());
+                }
+                match &policy {
+                    None => println!("\n💡 Add {} to enforce a license policy", policy_path),
+                    Some(_) if violations.is_empty() => {
+                        println!("\n✅ License policy {} passed", policy_path);
+                    }
+                    Some(_) => {
+                        println!(
+                            "\n🚨 {} license policy violations ({}):", violations.len(),
+                            policy_path
+                        );
+                        for violation in &violations {
+                            println!(
+                                "  ❌ {} v{} - {}: {}", violation.package.bold(), violation
+                                .version, violation.license, violation.reason.red()
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        if check && !violations.is_empty() {
+            return Err(
+                ToolError::ExecutionFailed(
+                    format!("{} license policy violations", violations.len()),
+                ),
+            );
+        }
+        Ok(())
+    }
     fn display_report(
         &self,
         report: &LicenseBundleReport,
@@ -600,13 +1151,21 @@ impl Tool for LicenseBundlerTool {
 EXAMPLES:
     cm tool license-bundler --output licenses/
     cm tool license-bundler --check-compatibility --format json
-    cm tool license-bundler --include-license-text --third-party-notices",
+    cm tool license-bundler --include-license-text --third-party-notices
+    cm tool license-bundler --sbom cyclonedx --license-output sbom/
+    cm tool license-bundler --check --policy license-policy.toml --policy-profile proprietary
+
+POLICY FILE (license-policy.toml):
+    deny = [\"AGPL-*\"]
+    unknown = \"deny\"
+    exceptions = [\"ring\"]
+    [profiles.proprietary]
+    deny = [\"GPL-*\", \"LGPL-*\"]",
             )
             .args(
                 &[
-                    Arg::new("output")
-                        .long("output")
-                        .short('o')
+                    Arg::new("license-output")
+                        .long("license-output")
                         .help("Output directory for license files")
                         .default_value("licenses/"),
                     Arg::new("check-compatibility")
@@ -635,12 +1194,32 @@ EXAMPLES:
                         .long("ci-mode")
                         .help("CI-friendly output with exit codes")
                         .action(clap::ArgAction::SetTrue),
+                    Arg::new("sbom")
+                        .long("sbom")
+                        .help("Emit SBOM documents for the full dependency tree")
+                        .num_args(0..=1)
+                        .default_missing_value("both")
+                        .value_parser(["spdx", "cyclonedx", "both"]),
+                    Arg::new("policy")
+                        .long("policy")
+                        .help("License policy file (TOML)")
+                        .default_value("license-policy.toml"),
+                    Arg::new("policy-profile")
+                        .long("policy-profile")
+                        .help("Policy profile to apply on top of the base policy (e.g. proprietary)"),
+                    Arg::new("check")
+                        .long("check")
+                        .help("Check the dependency tree against the license policy and fail on violations")
+                        .action(clap::ArgAction::SetTrue),
                 ],
             )
             .args(&common_options())
     }
     fn execute(&self, matches: &ArgMatches) -> Result<()> {
-        let output_dir = matches.get_one::<String>("output").unwrap();
+        if matches.get_one::<String>("sbom").is_some() || matches.get_flag("check") {
+            return self.run_sbom(matches);
+        }
+        let output_dir = matches.get_one::<String>("license-output").unwrap();
         let check_compatibility = matches.get_flag("check-compatibility");
         let include_license_text = matches.get_flag("include-license-text");
         let third_party_notices = matches.get_flag("third-party-notices");
@@ -768,4 +1347,89 @@ impl Default for LicenseBundlerTool {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn package(name: &str, license: Option<&str>, deps: &[&str]) -> SbomPackage {
+        SbomPackage {
+            id: format!("{} 1.0.0", name),
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            license: license.map(str::to_string),
+            source_url: Some(format!(
+                "https://crates.io/api/v1/crates/{}/1.0.0/download",
+                name
+            )),
+            repository: None,
+            checksum: Some("abc123".to_string()),
+            purl: format!("pkg:cargo/{}@1.0.0", name),
+            is_root: name == "app",
+            dependencies: deps.iter().map(|d| format!("{} 1.0.0", d)).collect(),
+        }
+    }
+    #[test]
+    fn test_policy_and_sbom_documents() {
+        let tool = LicenseBundlerTool::new();
+        let policy: LicensePolicy = toml::from_str(
+            r#"
+            deny = ["AGPL-*"]
+            unknown = "deny"
+            exceptions = ["ring"]
+            [profiles.proprietary]
+            deny = ["GPL-*"]
+        "#,
+        )
+        .unwrap();
+        assert_eq!(
+            LicenseBundlerTool::normalize_license_expression("MIT/Apache-2.0"),
+            "MIT OR Apache-2.0"
+        );
+        assert!(LicenseBundlerTool::check_license_expression(
+            "MIT OR GPL-3.0-only",
+            &policy.profiles["proprietary"]
+        )
+        .is_ok());
+        assert!(LicenseBundlerTool::check_license_expression(
+            "(MIT OR Apache-2.0) AND GPL-3.0-only",
+            &policy.profiles["proprietary"]
+        )
+        .is_err());
+        let packages = vec![
+            package(
+                "app",
+                Some("MIT"),
+                &["serde", "copyleft", "ring", "mystery"],
+            ),
+            package("serde", Some("MIT OR Apache-2.0"), &[]),
+            package("copyleft", Some("AGPL-3.0-only"), &[]),
+            package("ring", None, &[]),
+            package("mystery", None, &[]),
+        ];
+        let violations = tool.evaluate_policy(&packages, &policy);
+        let names: Vec<&str> = violations.iter().map(|v| v.package.as_str()).collect();
+        assert_eq!(names, vec!["copyleft", "mystery"]);
+        let spdx = tool.generate_spdx_document(&packages, "2024-01-01T00:00:00Z");
+        assert_eq!(spdx["spdxVersion"], "SPDX-2.3");
+        assert_eq!(spdx["packages"][1]["SPDXID"], "SPDXRef-Package-serde-1.0.0");
+        assert_eq!(
+            spdx["packages"][1]["checksums"][0]["checksumValue"],
+            "abc123"
+        );
+        assert_eq!(spdx["relationships"][0]["relationshipType"], "DESCRIBES");
+        let cdx = tool.generate_cyclonedx_document(&packages, "2024-01-01T00:00:00Z");
+        assert_eq!(cdx["metadata"]["component"]["name"], "app");
+        assert_eq!(cdx["components"].as_array().unwrap().len(), 4);
+        assert_eq!(
+            cdx["components"][0]["licenses"][0]["expression"],
+            "MIT OR Apache-2.0"
+        );
+        assert_eq!(
+            cdx["dependencies"][0]["dependsOn"]
+                .as_array()
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+}
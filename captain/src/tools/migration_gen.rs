@@ -2,8 +2,10 @@ use super::{Tool, Result, ToolError, common_options, parse_output_format, Output
 use clap::{Arg, ArgMatches, Command};
 use colored::*;
 use std::path::Path;
+use std::process::Command as ProcessCommand;
 use std::fs;
 use std::collections::HashMap;
+use regex::Regex;
 use syn::{parse_file, Item, ItemStruct, Fields, Field, Type, Path as SynPath};
 use quote::ToTokens;
 use serde::{Deserialize, Serialize};
@@ -12,6 +14,8 @@ pub struct MigrationGenTool;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct StructDefinition {
     name: String,
+    table_name: Option<String>,
+    is_model: bool,
     fields: Vec<FieldDefinition>,
     file_path: String,
     line_number: usize,
@@ -24,6 +28,28 @@ struct FieldDefinition {
     attributes: Vec<String>,
     comment: Option<String>,
 }
+#[derive(Debug, Clone, PartialEq)]
+struct ColumnSpec {
+    name: String,
+    sql_type: String,
+    nullable: bool,
+}
+#[derive(Debug, Clone)]
+enum ColumnChange {
+    Add(ColumnSpec),
+    Drop(ColumnSpec),
+    Rename { from: String, to: String },
+    Alter { old: ColumnSpec, new: ColumnSpec },
+}
+const MODEL_DERIVES: &[&str] = &[
+    "Queryable",
+    "Insertable",
+    "Identifiable",
+    "Selectable",
+    "AsChangeset",
+    "FromRow",
+    "DeriveEntityModel",
+];
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct MigrationPlan {
     up_sql: String,
@@ -47,7 +73,14 @@ impl MigrationGenTool {
     }
     fn parse_rust_structs(&self, file_path: &str) -> Result<Vec<StructDefinition>> {
         let content = fs::read_to_string(file_path)?;
-        let syntax = parse_file(&content)?;
+        self.parse_rust_source(&content, file_path)
+    }
+    fn parse_rust_source(
+        &self,
+        content: &str,
+        file_path: &str,
+    ) -> Result<Vec<StructDefinition>> {
+        let syntax = parse_file(content)?;
         let mut structs = Vec::new();
         for (i, item) in syntax.items.iter().enumerate() {
             if let Item::Struct(struct_def) = item {
@@ -64,6 +97,28 @@ impl MigrationGenTool {
         line_number: usize,
     ) -> Result<StructDefinition> {
         let name = struct_def.ident.to_string();
+        let attrs: Vec<String> = struct_def
+            .attrs
+            .iter()
+            .map(|attr| attr.to_token_stream().to_string())
+            .collect();
+        let table_name = Regex::new(r#"table_name\s*=\s*"?([A-Za-z0-9_:\s]+?)"?\s*[,)\]]"#)
+            .ok()
+            .and_then(|re| {
+                attrs
+                    .iter()
+                    .find_map(|attr| re.captures(attr))
+                    .and_then(|caps| {
+                        caps[1].rsplit("::").next().map(|name| name.trim().to_string())
+                    })
+            });
+        let is_model = table_name.is_some()
+            || attrs
+                .iter()
+                .any(|attr| {
+                    attr.starts_with("# [derive")
+                        && MODEL_DERIVES.iter().any(|derive| attr.contains(derive))
+                });
         let mut fields = Vec::new();
         if let Fields::Named(named_fields) = &struct_def.fields {
             for field in &named_fields.named {
@@ -73,6 +128,8 @@ impl MigrationGenTool {
         }
         Ok(StructDefinition {
             name,
+            table_name,
+            is_model,
             fields,
             file_path: file_path.to_string(),
             line_number,
@@ -163,11 +220,11 @@ impl MigrationGenTool {
         }
     }
     fn generate_create_table_sql(&self, struct_def: &StructDefinition) -> String {
-        let table_name = self.struct_name_to_table_name(&struct_def.name);
+        let table_name = self.table_name_for(struct_def);
         let mut sql = format!("CREATE TABLE {} (\n", table_name);
         sql.push_str("    id SERIAL PRIMARY KEY,\n");
         for (i, field) in struct_def.fields.iter().enumerate() {
-            let column_name = self.field_name_to_column_name(&field.name);
+            let column_name = self.column_for(field);
             let sql_type = &field.ty;
             let nullable = if field.is_optional { "" } else { " NOT NULL" };
             let comma = if i < struct_def.fields.len() - 1 { "," } else { "" };
@@ -181,7 +238,7 @@ impl MigrationGenTool {
         sql
     }
     fn generate_migration_sql(&self, struct_def: &StructDefinition) -> MigrationPlan {
-        let table_name = self.struct_name_to_table_name(&struct_def.name);
+        let table_name = self.table_name_for(struct_def);
         let up_sql = self.generate_create_table_sql(struct_def);
         let down_sql = format!("DROP TABLE IF EXISTS {};\n", table_name);
         let description = format!(
@@ -348,6 +405,284 @@ impl MigrationGenTool {
             changes: changes.to_vec(),
         }
     }
+    fn table_name_for(&self, struct_def: &StructDefinition) -> String {
+        struct_def
+            .table_name
+            .clone()
+            .unwrap_or_else(|| self.struct_name_to_table_name(&struct_def.name))
+    }
+    fn column_for(&self, field: &FieldDefinition) -> String {
+        let re = Regex::new(r#"(?:column_name|rename)\s*=\s*"([^"]+)""#).unwrap();
+        field
+            .attributes
+            .iter()
+            .filter(|attr| {
+                ["# [diesel", "# [sqlx", "# [sea_orm", "# [column"]
+                    .iter()
+                    .any(|prefix| attr.starts_with(prefix))
+            })
+            .find_map(|attr| re.captures(attr).map(|caps| caps[1].to_string()))
+            .unwrap_or_else(|| self.field_name_to_column_name(&field.name))
+    }
+    fn column_specs(&self, struct_def: &StructDefinition) -> Vec<ColumnSpec> {
+        struct_def
+            .fields
+            .iter()
+            .map(|field| ColumnSpec {
+                name: self.column_for(field),
+                sql_type: field.ty.clone(),
+                nullable: field.is_optional,
+            })
+            .collect()
+    }
+    fn models_only(structs: Vec<StructDefinition>) -> Vec<StructDefinition> {
+        if structs.iter().any(|s| s.is_model) {
+            structs.into_iter().filter(|s| s.is_model).collect()
+        } else {
+            structs
+        }
+    }
+    fn previous_model_source(&self, file_path: &str, rev: &str) -> Result<Option<String>> {
+        let path = Path::new(file_path);
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let output = ProcessCommand::new("git")
+            .arg("-C")
+            .arg(dir)
+            .arg("show")
+            .arg(format!("{}:./{}", rev, file_name))
+            .output()
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to run git: {}", e)))?;
+        if output.status.success() {
+            return Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("exists on disk, but not in") || stderr.contains("does not exist in")
+        {
+            Ok(None)
+        } else {
+            Err(ToolError::ExecutionFailed(format!("git show failed: {}", stderr.trim())))
+        }
+    }
+    fn column_definition(column: &ColumnSpec) -> String {
+        format!(
+            "{} {}{}", column.name, column.sql_type, if column.nullable { "" } else {
+            " NOT NULL" }
+        )
+    }
+    fn column_change_sql(
+        &self,
+        database: &str,
+        table: &str,
+        change: &ColumnChange,
+    ) -> (String, String) {
+        let add = |column: &ColumnSpec| match database {
+            "mssql" => format!("ALTER TABLE {} ADD {};\n", table, Self::column_definition(column)),
+            _ => {
+                format!(
+                    "ALTER TABLE {} ADD COLUMN {};\n", table, Self::column_definition(column)
+                )
+            }
+        };
+        let drop = |column: &ColumnSpec| {
+            format!("ALTER TABLE {} DROP COLUMN {};\n", table, column.name)
+        };
+        let rename = |from: &str, to: &str| match database {
+            "mssql" => format!("EXEC sp_rename '{}.{}', '{}', 'COLUMN';\n", table, from, to),
+            _ => format!("ALTER TABLE {} RENAME COLUMN {} TO {};\n", table, from, to),
+        };
+        let alter = |from: &ColumnSpec, to: &ColumnSpec| match database {
+            "postgres" => {
+                let mut sql = String::new();
+                if from.sql_type != to.sql_type {
+                    sql.push_str(
+                        &format!(
+                            "ALTER TABLE {} ALTER COLUMN {} TYPE {} USING {}::{};\n", table, to
+                            .name, to.sql_type, to.name, to.sql_type
+                        ),
+                    );
+                }
+                if from.nullable != to.nullable {
+                    sql.push_str(
+                        &format!(
+                            "ALTER TABLE {} ALTER COLUMN {} {} NOT NULL;\n", table, to.name, if
+                            to.nullable { "DROP" } else { "SET" }
+                        ),
+                    );
+                }
+                sql
+            }
+            "mysql" => {
+                format!("ALTER TABLE {} MODIFY COLUMN {};\n", table, Self::column_definition(to))
+            }
+            "mssql" => {
+                format!("ALTER TABLE {} ALTER COLUMN {};\n", table, Self::column_definition(to))
+            }
+            _ => {
+                format!(
+                    "-- SQLite cannot alter columns in place: rebuild {} to change {} to {}\n",
+                    table, to.name, Self::column_definition(to)
+                )
+            }
+        };
+        match change {
+            ColumnChange::Add(column) => {
+                let mut up = String::new();
+                if !column.nullable {
+                    up.push_str(
+                        &format!(
+                            "-- NOTE: existing rows need a DEFAULT for NOT NULL column {}\n",
+                            column.name
+                        ),
+                    );
+                }
+                up.push_str(&add(column));
+                (up, drop(column))
+            }
+            ColumnChange::Drop(column) => (drop(column), add(column)),
+            ColumnChange::Rename { from, to } => (rename(from, to), rename(to, from)),
+            ColumnChange::Alter { old, new } => (alter(old, new), alter(new, old)),
+        }
+    }
+    fn diff_struct_versions(
+        &self,
+        previous: &[StructDefinition],
+        current: &[StructDefinition],
+        database: &str,
+        timestamp: &str,
+        confirm_rename: &mut dyn FnMut(&str, &ColumnSpec, &ColumnSpec) -> bool,
+    ) -> Vec<MigrationPlan> {
+        let mut plans = Vec::new();
+        let previous_tables: HashMap<String, &StructDefinition> = previous
+            .iter()
+            .map(|s| (self.table_name_for(s), s))
+            .collect();
+        for struct_def in current {
+            let table = self.table_name_for(struct_def);
+            let Some(old_struct) = previous_tables.get(&table) else {
+                let mut plan = self.generate_migration_sql(struct_def);
+                plan.timestamp = timestamp.to_string();
+                plans.push(plan);
+                continue;
+            };
+            let old_columns = self.column_specs(old_struct);
+            let new_columns = self.column_specs(struct_def);
+            let mut added: Vec<&ColumnSpec> = new_columns
+                .iter()
+                .filter(|c| !old_columns.iter().any(|o| o.name == c.name))
+                .collect();
+            let mut changes = Vec::new();
+            for old in &old_columns {
+                match new_columns.iter().find(|c| c.name == old.name) {
+                    Some(new) if new != old => {
+                        changes
+                            .push(ColumnChange::Alter {
+                                old: old.clone(),
+                                new: new.clone(),
+                            });
+                    }
+                    Some(_) => {}
+                    None => {
+                        let candidate = added
+                            .iter()
+                            .position(|a| {
+                                a.sql_type == old.sql_type && a.nullable == old.nullable
+                            });
+                        match candidate {
+                            Some(index) if confirm_rename(&table, old, added[index]) => {
+                                let new = added.remove(index);
+                                changes
+                                    .push(ColumnChange::Rename {
+                                        from: old.name.clone(),
+                                        to: new.name.clone(),
+                                    });
+                            }
+                            _ => changes.push(ColumnChange::Drop(old.clone())),
+                        }
+                    }
+                }
+            }
+            changes.extend(added.into_iter().map(|c| ColumnChange::Add(c.clone())));
+            if changes.is_empty() {
+                continue;
+            }
+            let mut up_sql = format!("-- Alter {} table\n", table);
+            let mut down_sql = format!("-- Revert {} table changes\n", table);
+            let mut down_parts = Vec::new();
+            let mut descriptions = Vec::new();
+            for change in &changes {
+                let (up, down) = self.column_change_sql(database, &table, change);
+                up_sql.push_str(&up);
+                down_parts.push(down);
+                descriptions
+                    .push(
+                        match change {
+                            ColumnChange::Add(c) => {
+                                format!("ADD COLUMN {} {}", c.name, c.sql_type)
+                            }
+                            ColumnChange::Drop(c) => format!("DROP COLUMN {}", c.name),
+                            ColumnChange::Rename { from, to } => {
+                                format!("RENAME COLUMN {} TO {}", from, to)
+                            }
+                            ColumnChange::Alter { old, new } => {
+                                format!(
+                                    "ALTER COLUMN {} {} -> {}", new.name, Self::column_definition(old),
+                                    Self::column_definition(new)
+                                )
+                            }
+                        },
+                    );
+            }
+            for down in down_parts.iter().rev() {
+                down_sql.push_str(down);
+            }
+            plans
+                .push(MigrationPlan {
+                    up_sql,
+                    down_sql,
+                    description: format!(
+                        "Alter {} table - {} changes", table, descriptions.len()
+                    ),
+                    timestamp: timestamp.to_string(),
+                    table_name: table,
+                    changes: descriptions,
+                });
+        }
+        let current_tables: Vec<String> = current
+            .iter()
+            .map(|s| self.table_name_for(s))
+            .collect();
+        for old_struct in previous {
+            let table = self.table_name_for(old_struct);
+            if current_tables.contains(&table) {
+                continue;
+            }
+            plans
+                .push(MigrationPlan {
+                    up_sql: format!("DROP TABLE IF EXISTS {};\n", table),
+                    down_sql: self.generate_create_table_sql(old_struct),
+                    description: format!(
+                        "Drop {} table ({} removed)", table, old_struct.name
+                    ),
+                    timestamp: timestamp.to_string(),
+                    changes: vec![format!("DROP TABLE {}", table)],
+                    table_name: table,
+                });
+        }
+        plans
+    }
+    fn prompt_rename(table: &str, from: &ColumnSpec, to: &ColumnSpec) -> bool {
+        println!(
+            "❓ {}: was column {} renamed to {}? (y/N): ", table.cyan(), from.name
+            .yellow(), to.name.green()
+        );
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input).is_ok()
+            && input.trim().to_lowercase().starts_with('y')
+    }
     fn display_report(
         &self,
         report: &MigrationReport,
@@ -448,7 +783,9 @@ impl Tool for MigrationGenTool {
 EXAMPLES:
     cm tool migration-gen --input src/models.rs --output migrations/
     cm tool migration-gen --input src/user.rs --database postgres
-    cm tool migration-gen --existing-schema schema.sql --diff",
+    cm tool migration-gen --existing-schema schema.sql --diff
+    cm tool migration-gen --input src/models.rs --against HEAD
+    cm tool migration-gen --input src/models.rs --against main --yes --database mysql",
             )
             .args(
                 &[
@@ -457,9 +794,8 @@ EXAMPLES:
                         .short('i')
                         .help("Input Rust file containing struct definitions")
                         .required(true),
-                    Arg::new("output")
-                        .long("output")
-                        .short('o')
+                    Arg::new("migration-output")
+                        .long("migration-output")
                         .help("Output directory for migration files")
                         .default_value("migrations/"),
                     Arg::new("database")
@@ -483,9 +819,17 @@ EXAMPLES:
                         .short('f')
                         .help("Migration framework (diesel, seaorm, raw)")
                         .default_value("raw"),
-                    Arg::new("dry-run")
-                        .long("dry-run")
-                        .help("Show what would be generated without creating files")
+                    Arg::new("against")
+                        .long("against")
+                        .help(
+                            "Diff model structs against this git revision and emit ALTER TABLE migrations",
+                        )
+                        .num_args(0..=1)
+                        .default_missing_value("HEAD"),
+                    Arg::new("yes")
+                        .long("yes")
+                        .short('y')
+                        .help("Accept every detected column rename without prompting")
                         .action(clap::ArgAction::SetTrue),
                 ],
             )
@@ -493,7 +837,7 @@ EXAMPLES:
     }
     fn execute(&self, matches: &ArgMatches) -> Result<()> {
         let input_file = matches.get_one::<String>("input").unwrap();
-        let output_dir = matches.get_one::<String>("output").unwrap();
+        let output_dir = matches.get_one::<String>("migration-output").unwrap();
         let database = matches.get_one::<String>("database").unwrap();
         let existing_schema = matches.get_one::<String>("existing-schema");
         let diff = matches.get_flag("diff");
@@ -532,31 +876,64 @@ EXAMPLES:
         }
         let mut migration_plans = Vec::new();
         let mut files_created = Vec::new();
-        for struct_def in &structs {
-            if diff {
-                let table_name = self.struct_name_to_table_name(&struct_def.name);
-                if let Some(existing_migration) = self
-                    .find_existing_migration(&table_name, migrations_dir)
-                {
-                    if let Ok(changes) = self
-                        .compare_struct_with_existing(struct_def, &existing_migration)
+        if let Some(rev) = matches.get_one::<String>("against") {
+            let current = Self::models_only(structs.clone());
+            let previous = match self.previous_model_source(input_file, rev)? {
+                Some(source) => {
+                    Self::models_only(self.parse_rust_source(&source, input_file)?)
+                }
+                None => {
+                    if verbose {
+                        println!("📄 {} is new since {} - creating tables", input_file, rev);
+                    }
+                    Vec::new()
+                }
+            };
+            let accept_all = matches.get_flag("yes");
+            let interactive = atty::is(atty::Stream::Stdin) && !dry_run && !crate::ci::is_ci();
+            let mut confirm = |table: &str, from: &ColumnSpec, to: &ColumnSpec| {
+                if accept_all {
+                    true
+                } else if interactive {
+                    Self::prompt_rename(table, from, to)
+                } else {
+                    println!(
+                        "⚠️  {}: {} → {} looks like a rename; treating it as drop + add (use --yes to accept renames)",
+                        table, from.name, to.name
+                    );
+                    false
+                }
+            };
+            let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S").to_string();
+            migration_plans = self
+                .diff_struct_versions(&previous, &current, database, &timestamp, &mut confirm);
+        } else {
+            for struct_def in &structs {
+                if diff {
+                    let table_name = self.struct_name_to_table_name(&struct_def.name);
+                    if let Some(existing_migration) = self
+                        .find_existing_migration(&table_name, migrations_dir)
                     {
-                        if !changes.is_empty() {
-                            let timestamp = chrono::Utc::now()
-                                .format("%Y%m%d_%H%M%S")
-                                .to_string();
-                            let plan = self
-                                .generate_alter_migration(struct_def, &changes, &timestamp);
-                            migration_plans.push(plan);
+                        if let Ok(changes) = self
+                            .compare_struct_with_existing(struct_def, &existing_migration)
+                        {
+                            if !changes.is_empty() {
+                                let timestamp = chrono::Utc::now()
+                                    .format("%Y%m%d_%H%M%S")
+                                    .to_string();
+                                let plan = self
+                                    .generate_alter_migration(struct_def, &changes, &timestamp);
+                                migration_plans.push(plan);
+                            }
                         }
+                    } else {
+                        let plan = self.generate_migration_sql(struct_def);
+                        migration_plans.push(plan);
                     }
                 } else {
                     let plan = self.generate_migration_sql(struct_def);
                     migration_plans.push(plan);
                 }
-            } else {
-                let plan = self.generate_migration_sql(struct_def);
-                migration_plans.push(plan);
             }
         }
         if migration_plans.is_empty() {
@@ -604,4 +981,60 @@ impl Default for MigrationGenTool {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_diff_struct_versions_detects_column_changes() {
+        let tool = MigrationGenTool::new();
+        let before = r#"
+            #[derive(Queryable)]
+            #[diesel(table_name = crate::schema::users)]
+            pub struct User { pub name: String, pub email: String, pub legacy: bool }
+            pub struct Helper { pub x: u8 }
+        "#;
+        let after = r#"
+            #[derive(Queryable)]
+            #[diesel(table_name = crate::schema::users)]
+            pub struct User {
+                pub full_name: String,
+                pub email: Option<String>,
+                #[diesel(column_name = "created")]
+                pub created_at: NaiveDateTime,
+            }
+        "#;
+        let previous =
+            MigrationGenTool::models_only(tool.parse_rust_source(before, "models.rs").unwrap());
+        let current =
+            MigrationGenTool::models_only(tool.parse_rust_source(after, "models.rs").unwrap());
+        assert_eq!(previous.len(), 1);
+        let mut confirm = |_: &str, from: &ColumnSpec, to: &ColumnSpec| {
+            from.name == "name" && to.name == "full_name"
+        };
+        let plans = tool.diff_struct_versions(
+            &previous,
+            &current,
+            "postgres",
+            "20240101_000000",
+            &mut confirm,
+        );
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].table_name, "users");
+        assert!(plans[0]
+            .up_sql
+            .contains("ALTER TABLE users RENAME COLUMN name TO full_name;"));
+        assert!(plans[0]
+            .up_sql
+            .contains("ALTER TABLE users ALTER COLUMN email DROP NOT NULL;"));
+        assert!(plans[0]
+            .up_sql
+            .contains("ALTER TABLE users DROP COLUMN legacy;"));
+        assert!(plans[0]
+            .up_sql
+            .contains("ADD COLUMN created TIMESTAMP NOT NULL;"));
+        assert!(plans[0]
+            .down_sql
+            .contains("ALTER TABLE users RENAME COLUMN full_name TO name;"));
+    }
+}
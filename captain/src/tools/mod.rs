@@ -1,10 +1,16 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use clap::{ArgMatches, Command};
 use colored::*;
 use thiserror::Error;
 use quote::ToTokens;
 pub mod bench_diff;
 pub mod dep_audit;
+pub mod dedupe;
+pub mod trust_score;
+pub mod repro_check;
 pub mod test_gen;
 pub mod workspace_sync;
 pub mod panic_analyzer;
@@ -20,6 +26,7 @@ pub mod proto_bind;
 pub mod error_derive;
 pub mod builder_gen;
 pub mod example_gen;
+pub mod readme_sync;
 pub mod api_changelog;
 pub mod trait_explorer;
 pub mod env_check;
@@ -40,6 +47,7 @@ pub mod secret_scanner;
 pub mod unsafe_analyzer;
 pub mod license_bundler;
 pub mod code_analyzer;
+pub mod pipeline;
 #[derive(Error, Debug)]
 pub enum ToolError {
     #[error("Tool '{0}' not found")]
@@ -70,7 +78,7 @@ impl From<syn::Error> for ToolError {
     }
 }
 pub type Result<T> = std::result::Result<T, ToolError>;
-pub trait Tool {
+pub trait Tool: Send + Sync {
     fn name(&self) -> &'static str;
     /// Returns a description of what the tool does
     fn description(&self) -> &'static str;
@@ -89,7 +97,7 @@ impl ToolRegistry {
         self
     }
     pub fn get(&self, name: &str) -> Option<&Box<dyn Tool>> {
-        self.tools.get(name)
+        self.tools.get(&name.replace('_', "-"))
     }
     pub fn list_tools(&self) -> Vec<(&str, &str)> {
         let mut tools: Vec<_> = self
@@ -101,7 +109,7 @@ impl ToolRegistry {
         tools
     }
     pub fn has_tool(&self, name: &str) -> bool {
-        self.tools.contains_key(name)
+        self.get(name).is_some()
     }
 }
 /// Configuration for tools
@@ -132,6 +140,9 @@ pub fn create_registry() -> ToolRegistry {
     registry
         .register(bench_diff::BenchDiffTool::new())
         .register(dep_audit::DepAuditTool::new())
+        .register(dedupe::DedupeTool::new())
+        .register(trust_score::TrustScoreTool::new())
+        .register(repro_check::ReproCheckTool::new())
         .register(test_gen::TestGenTool::new())
         .register(workspace_sync::WorkspaceSyncTool::new())
         .register(panic_analyzer::PanicAnalyzerTool::new())
@@ -142,6 +153,7 @@ pub fn create_registry() -> ToolRegistry {
         .register(error_derive::ErrorDeriveTool::new())
         .register(builder_gen::BuilderGenTool::new())
         .register(example_gen::ExampleGenTool::new())
+        .register(readme_sync::ReadmeSyncTool::new())
         .register(api_changelog::ApiChangelogTool::new())
         .register(trait_explorer::TraitExplorerTool::new())
         .register(rust_mentor::RustMentorTool::new())
@@ -168,15 +180,110 @@ pub fn create_registry() -> ToolRegistry {
         .register(license_bundler::LicenseBundlerTool::new())
         .register(code_analyzer::CodeAnalyzer::new())
 }
-static mut REGISTRY: Option<ToolRegistry> = None;
+static REGISTRY: OnceLock<ToolRegistry> = OnceLock::new();
 /// Get the global tool registry (lazy initialized)
 pub fn get_registry() -> &'static ToolRegistry {
-    unsafe {
-        if REGISTRY.is_none() {
-            REGISTRY = Some(create_registry());
+    REGISTRY.get_or_init(create_registry)
+}
+/// Built-in tool sets for `cm tool run-all --set <name>`; `.cg` can override them
+/// with `[tool_sets] <name> = ["tool", ...]`
+const TOOL_SETS: &[(&str, &[&str])] = &[
+    (
+        "lint",
+        &["async-lint", "panic-analyzer", "unsafe-analyzer", "serde-validator", "sql-macro-check"],
+    ),
+    ("audit", &["dep-audit", "secret-scanner", "unsafe-analyzer", "env-check"]),
+];
+pub fn tool_set(name: &str) -> Option<Vec<String>> {
+    let configured = crate::captain::config::ConfigManager::new()
+        .map(|config| config.get_list(&format!("tool_sets.{}", name)))
+        .unwrap_or_default();
+    if !configured.is_empty() {
+        return Some(configured);
+    }
+    TOOL_SETS
+        .iter()
+        .find(|(set, _)| *set == name)
+        .map(|(_, tools)| tools.iter().map(|t| t.to_string()).collect())
+}
+pub struct BatchResult {
+    pub name: String,
+    pub duration: Duration,
+    pub outcome: Result<()>,
+}
+/// Run independent tools concurrently on up to `jobs` threads, returning results in input order
+pub fn run_batch(names: &[String], jobs: usize) -> Result<Vec<BatchResult>> {
+    let registry = get_registry();
+    if let Some(missing) = names.iter().find(|name| !registry.has_tool(name)) {
+        return Err(ToolError::ToolNotFound(missing.clone()));
+    }
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<BatchResult>>> = Mutex::new(
+        names.iter().map(|_| None).collect(),
+    );
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, names.len().max(1)) {
+            scope
+                .spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(name) = names.get(index) else {
+                        break;
+                    };
+                    let started = Instant::now();
+                    let outcome = run_tool(name, &[]);
+                    let result = BatchResult {
+                        name: name.clone(),
+                        duration: started.elapsed(),
+                        outcome,
+                    };
+                    results.lock().unwrap()[index] = Some(result);
+                });
+        }
+    });
+    Ok(results.into_inner().unwrap().into_iter().flatten().collect())
+}
+pub fn run_set(set: &str, jobs: Option<usize>) -> Result<()> {
+    let names = tool_set(set)
+        .ok_or_else(|| {
+            let known: Vec<&str> = TOOL_SETS.iter().map(|(name, _)| *name).collect();
+            ToolError::ConfigError(
+                format!("Unknown tool set '{}' (built-in sets: {})", set, known.join(", ")),
+            )
+        })?;
+    let jobs = jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(4, |n| n.get()));
+    println!(
+        "{}", format!("🔧 Running tool set '{}' ({} tools, {} jobs)", set, names.len(), jobs)
+        .bold().blue()
+    );
+    let results = run_batch(&names, jobs)?;
+    println!();
+    println!("{}", "═".repeat(50).blue());
+    let mut failed = 0;
+    for result in &results {
+        match &result.outcome {
+            Ok(()) => {
+                println!(
+                    "  {} {} ({:.1}s)", "✅".green(), result.name.green(), result.duration
+                    .as_secs_f64()
+                )
+            }
+            Err(e) => {
+                failed += 1;
+                println!(
+                    "  {} {} ({:.1}s): {}", "❌".red(), result.name.red(), result.duration
+                    .as_secs_f64(), e
+                );
+            }
         }
-        REGISTRY.as_ref().unwrap()
     }
+    if failed > 0 {
+        return Err(
+            ToolError::ExecutionFailed(format!("{} of {} tools failed", failed, results.len())),
+        );
+    }
+    println!("{}", format!("✅ All {} tools passed", results.len()).green());
+    Ok(())
 }
 pub fn list_tools() {
     let registry = get_registry();
@@ -197,6 +304,8 @@ pub fn list_tools() {
     println!("  cm tool help <name>                   # Show help for a tool");
     println!("  cm tool <name> [options]              # Run a tool");
     println!("  cm tool run <name> [options]          # Run a tool (explicit)");
+    println!("  cm tool run-all --set lint            # Run a tool set concurrently");
+    println!("  cm tool pipeline <name>               # Run a .cg pipeline with a merged report");
 }
 pub fn show_tool_help(name: &str) {
     let registry = get_registry();
@@ -256,6 +365,20 @@ mod tests {
         assert!(! registry.has_tool("nonexistent"));
     }
     #[test]
+    fn test_registry_is_shared_across_threads() {
+        let addresses: Vec<usize> = (0..4)
+            .map(|_| std::thread::spawn(|| get_registry() as *const ToolRegistry as usize))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+        assert!(addresses.windows(2).all(|pair| pair[0] == pair[1]));
+        assert!(tool_set("lint").unwrap().iter().all(|name| get_registry().has_tool(name)));
+        assert!(matches!(
+            run_batch(& ["no-such-tool".to_string()], 2), Err(ToolError::ToolNotFound(_))
+        ));
+    }
+    #[test]
     fn test_list_tools_empty() {
         let registry = create_registry();
         let tools = registry.list_tools();
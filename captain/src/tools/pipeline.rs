@@ -0,0 +1,254 @@
+use super::{get_registry, Result, ToolError};
+use crate::captain::config::ConfigManager;
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Instant;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineStep {
+    pub tool: String,
+    pub args: Vec<String>,
+}
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    pub name: String,
+    pub steps: Vec<PipelineStep>,
+    pub report: Option<PathBuf>,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct StepReport {
+    pub tool: String,
+    pub args: Vec<String>,
+    pub passed: bool,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u128,
+    pub output: Option<Value>,
+    pub log: Option<String>,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineReport {
+    pub pipeline: String,
+    pub started_at: DateTime<Utc>,
+    pub passed: bool,
+    pub failed_steps: Vec<String>,
+    pub steps: Vec<StepReport>,
+}
+impl PipelineStep {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut words = shell_words::split(spec)
+            .map_err(|e| ToolError::ConfigError(format!("Invalid pipeline step '{}': {}", spec, e)))?;
+        if words.is_empty() {
+            return Err(ToolError::ConfigError("Empty pipeline step".to_string()));
+        }
+        let tool = words.remove(0);
+        if !get_registry().has_tool(&tool) {
+            return Err(ToolError::ToolNotFound(tool));
+        }
+        Ok(PipelineStep { tool, args: words })
+    }
+    fn describe(&self) -> String {
+        format!("{} {}", self.tool, self.args.join(" ")).trim_end().to_string()
+    }
+}
+impl Pipeline {
+    pub fn from_config(config: &ConfigManager, name: &str) -> Result<Self> {
+        let specs = config.get_list(&format!("pipelines.{}.steps", name));
+        if specs.is_empty() {
+            return Err(
+                ToolError::ConfigError(
+                    format!(
+                        "Pipeline '{}' is not defined - add [pipelines.{}] steps = [...] to .cg",
+                        name, name
+                    ),
+                ),
+            );
+        }
+        Ok(Pipeline {
+            name: name.to_string(),
+            steps: specs.iter().map(|spec| PipelineStep::parse(spec)).collect::<Result<_>>()?,
+            report: config.get(&format!("pipelines.{}.report", name)).map(PathBuf::from),
+        })
+    }
+    fn report_path(&self) -> PathBuf {
+        self.report.clone().unwrap_or_else(|| {
+            let root = std::env::current_dir()
+                .ok()
+                .and_then(|dir| crate::project::find_manifest(&dir))
+                .and_then(|manifest| manifest.parent().map(PathBuf::from))
+                .unwrap_or_else(|| PathBuf::from("."));
+            root.join("target").join(format!("cm-pipeline-{}.json", self.name))
+        })
+    }
+}
+pub fn configured_pipelines(config: &ConfigManager) -> Vec<String> {
+    config
+        .entries()
+        .into_iter()
+        .filter_map(|entry| {
+            entry.key.strip_prefix("pipelines.")?.strip_suffix(".steps").map(String::from)
+        })
+        .collect()
+}
+/// Tools print banners around their JSON, so take the whole output if it parses,
+/// otherwise the last top-level JSON object or array in it
+pub fn extract_json(output: &str) -> Option<Value> {
+    if let Ok(value) = serde_json::from_str(output.trim()) {
+        return Some(value);
+    }
+    output
+        .char_indices()
+        .filter(|(_, c)| *c == '{' || *c == '[')
+        .filter_map(|(start, _)| {
+            let mut stream = serde_json::Deserializer::from_str(&output[start..])
+                .into_iter::<Value>();
+            match stream.next() {
+                Some(Ok(value)) if value.is_object() || value.is_array() => {
+                    Some((start + stream.byte_offset(), value))
+                }
+                _ => None,
+            }
+        })
+        .max_by_key(|(end, _)| *end)
+        .map(|(_, value)| value)
+}
+fn run_step(step: &PipelineStep) -> Result<StepReport> {
+    let started = Instant::now();
+    let output = Command::new(std::env::current_exe()?)
+        .args(["tool", "run", &step.tool])
+        .args(&step.args)
+        .env("CM_CI", "1")
+        .stdin(Stdio::null())
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let json = extract_json(&stdout);
+    let log = if json.is_none() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Some(format!("{}{}", stdout, stderr).trim().to_string()).filter(|l| !l.is_empty())
+    } else {
+        None
+    };
+    Ok(StepReport {
+        tool: step.tool.clone(),
+        args: step.args.clone(),
+        passed: output.status.success(),
+        exit_code: output.status.code(),
+        duration_ms: started.elapsed().as_millis(),
+        output: json,
+        log,
+    })
+}
+pub fn merge(name: &str, started_at: DateTime<Utc>, steps: Vec<StepReport>) -> PipelineReport {
+    let failed_steps: Vec<String> = steps
+        .iter()
+        .filter(|s| !s.passed)
+        .map(|s| s.tool.clone())
+        .collect();
+    PipelineReport {
+        pipeline: name.to_string(),
+        started_at,
+        passed: failed_steps.is_empty(),
+        failed_steps,
+        steps,
+    }
+}
+pub fn run_pipeline(name: &str, report: Option<PathBuf>, fail_fast: bool) -> Result<()> {
+    let config = ConfigManager::new().map_err(|e| ToolError::ConfigError(e.to_string()))?;
+    let mut pipeline = Pipeline::from_config(&config, name)?;
+    if report.is_some() {
+        pipeline.report = report;
+    }
+    println!(
+        "{}", format!("🔗 Pipeline '{}' ({} steps)", pipeline.name, pipeline.steps.len())
+        .bold().blue()
+    );
+    let started_at = Utc::now();
+    let mut steps = Vec::new();
+    for (index, step) in pipeline.steps.iter().enumerate() {
+        println!("  [{}/{}] {}", index + 1, pipeline.steps.len(), step.describe().cyan());
+        let result = run_step(step)?;
+        let seconds = result.duration_ms as f64 / 1000.0;
+        if result.passed {
+            println!("        {} passed ({:.1}s)", "✅".green(), seconds);
+        } else {
+            println!(
+                "        {} failed with exit code {} ({:.1}s)", "❌".red(), result.exit_code
+                .map_or("?".to_string(), | c | c.to_string()), seconds
+            );
+        }
+        let stop = fail_fast && !result.passed;
+        steps.push(result);
+        if stop {
+            println!("  {} stopping: --fail-fast", "⏹".yellow());
+            break;
+        }
+    }
+    let merged = merge(&pipeline.name, started_at, steps);
+    let path = pipeline.report_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(&merged)?)?;
+    println!("📄 Report written to {}", path.display().to_string().cyan());
+    if !merged.passed {
+        return Err(
+            ToolError::ExecutionFailed(
+                format!("pipeline '{}' failed: {}", merged.pipeline, merged.failed_steps.join(", ")),
+            ),
+        );
+    }
+    println!("{}", format!("✅ Pipeline '{}' passed", merged.pipeline).green());
+    Ok(())
+}
+pub fn list_pipelines() -> Result<()> {
+    let config = ConfigManager::new().map_err(|e| ToolError::ConfigError(e.to_string()))?;
+    let names = configured_pipelines(&config);
+    if names.is_empty() {
+        println!("No pipelines defined. Add one to .cg:");
+        println!("  [pipelines.ci]");
+        println!("  steps = [\"secret_scanner --format json\", \"unsafe_analyzer\", \"coverage_guard\"]");
+        return Ok(());
+    }
+    println!("{}", "🔗 Tool pipelines".bold().blue());
+    for name in names {
+        let steps = config.get_list(&format!("pipelines.{}.steps", name));
+        println!("  {} - {}", name.green().bold(), steps.join(" → "));
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn step(tool: &str, passed: bool, output: Option<Value>) -> StepReport {
+        StepReport {
+            tool: tool.to_string(),
+            args: Vec::new(),
+            passed,
+            exit_code: Some(if passed { 0 } else { 1 }),
+            duration_ms: 10,
+            output,
+            log: None,
+        }
+    }
+    #[test]
+    fn parses_steps_extracts_json_and_merges() {
+        let parsed = PipelineStep::parse("secret_scanner --format json --confidence 'high'").unwrap();
+        assert_eq!(parsed.args, vec!["--format", "json", "--confidence", "high"]);
+        assert!(PipelineStep::parse("not-a-tool").is_err());
+        let noisy = "🔍 Scanning...\n{\"findings\": [1, 2]}\nDone {not json}\n";
+        assert_eq!(extract_json(noisy).unwrap()["findings"].as_array().unwrap().len(), 2);
+        assert!(extract_json("no json here").is_none());
+        let report = merge(
+            "ci",
+            Utc::now(),
+            vec![step("secret_scanner", true, extract_json(noisy)), step("coverage_guard", false, None)],
+        );
+        assert!(!report.passed);
+        assert_eq!(report.failed_steps, vec!["coverage_guard"]);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["steps"][0]["output"]["findings"][1], 2);
+    }
+}
@@ -0,0 +1,415 @@
+use super::{Tool, Result, ToolError, common_options, parse_output_format, OutputFormat};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+#[derive(Debug, Clone)]
+pub struct ReadmeSyncTool;
+/// A fenced ```rust block; `example` is set when the block is preceded by
+/// `<!-- example: examples/foo.rs -->` and should mirror that file
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    pub fence_line: usize,
+    pub info: String,
+    pub code: String,
+    pub example: Option<String>,
+    body: std::ops::Range<usize>,
+}
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockStatus {
+    Ok,
+    Rotted,
+    Ignored,
+    Drifted,
+    Updated,
+    Unchecked,
+}
+#[derive(Debug, Clone, Serialize)]
+struct BlockResult {
+    file: String,
+    line: usize,
+    example: Option<String>,
+    status: BlockStatus,
+    error: Option<String>,
+}
+impl ReadmeSyncTool {
+    pub fn new() -> Self {
+        Self
+    }
+    pub fn extract_blocks(content: &str) -> Vec<CodeBlock> {
+        let marker = Regex::new(r"^<!--\s*example:\s*(\S+)\s*-->$").unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        let mut blocks = Vec::new();
+        let mut i = 0;
+        while i < lines.len() {
+            let trimmed = lines[i].trim_start();
+            let fence = if trimmed.starts_with("```") { "```" } else if trimmed.starts_with("~~~") { "~~~" } else { "" };
+            if fence.is_empty() {
+                i += 1;
+                continue;
+            }
+            let info = trimmed.trim_start_matches(fence).trim().to_string();
+            let close = (i + 1..lines.len()).find(|&j| lines[j].trim() == fence).unwrap_or(lines.len());
+            let is_rust = info.split([',', ' ']).next() == Some("rust");
+            if is_rust {
+                let example = lines[..i]
+                    .iter()
+                    .rev()
+                    .find(|line| !line.trim().is_empty())
+                    .and_then(|line| marker.captures(line.trim()))
+                    .map(|captures| captures[1].to_string());
+                blocks.push(CodeBlock {
+                    fence_line: i + 1,
+                    info,
+                    code: lines[i + 1..close].join("\n"),
+                    example,
+                    body: i + 1..close,
+                });
+            }
+            i = close + 1;
+        }
+        blocks
+    }
+    /// Replaces the body of every block whose example file differs, returning the new
+    /// document and the indices of the blocks that changed
+    pub fn rewrite_from_examples(content: &str, blocks: &[CodeBlock], root: &Path) -> Result<(String, Vec<usize>)> {
+        let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let mut changed = Vec::new();
+        for (index, block) in blocks.iter().enumerate().rev() {
+            let Some(example) = &block.example else {
+                continue;
+            };
+            let source = fs::read_to_string(root.join(example)).map_err(|e| {
+                ToolError::ExecutionFailed(format!("Cannot read {} (line {}): {}", example, block.fence_line, e))
+            })?;
+            if source.trim_end() == block.code.trim_end() {
+                continue;
+            }
+            lines.splice(block.body.clone(), source.trim_end().lines().map(str::to_string));
+            changed.push(index);
+        }
+        changed.reverse();
+        let mut rewritten = lines.join("\n");
+        if content.ends_with('\n') {
+            rewritten.push('\n');
+        }
+        Ok((rewritten, changed))
+    }
+    /// Per fence line: `Ok(())`, `Err(first error line)` or ignored (`None`), from
+    /// `rustdoc --test` output for a markdown file
+    pub fn parse_rustdoc_output(output: &str) -> BTreeMap<usize, Option<std::result::Result<(), String>>> {
+        let result = Regex::new(r"^test .* \(line (\d+)\) \.\.\. (ok|FAILED|ignored)").unwrap();
+        let failure = Regex::new(r"^---- .* \(line (\d+)\) stdout ----$").unwrap();
+        let mut outcomes = BTreeMap::new();
+        let mut errors: BTreeMap<usize, String> = BTreeMap::new();
+        let mut current = None;
+        for line in output.lines() {
+            if let Some(captures) = result.captures(line) {
+                let line_no: usize = captures[1].parse().unwrap_or(0);
+                let outcome = match &captures[2] {
+                    "ok" => Some(Ok(())),
+                    "FAILED" => Some(Err(String::new())),
+                    _ => None,
+                };
+                outcomes.insert(line_no, outcome);
+            } else if let Some(captures) = failure.captures(line) {
+                current = captures[1].parse().ok();
+            } else if let Some(line_no) = current {
+                if line.starts_with("error") && !errors.contains_key(&line_no) {
+                    errors.insert(line_no, line.to_string());
+                }
+            }
+        }
+        for (line_no, error) in errors {
+            if let Some(Some(Err(message))) = outcomes.get_mut(&line_no) {
+                *message = error;
+            }
+        }
+        outcomes
+    }
+    fn default_files() -> Vec<PathBuf> {
+        let mut files = vec![PathBuf::from("README.md")];
+        let mut stack = vec![PathBuf::from("docs")];
+        while let Some(dir) = stack.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    stack.push(path);
+                } else if path.extension().is_some_and(|ext| ext == "md") {
+                    files.push(path);
+                }
+            }
+        }
+        files.retain(|file| file.exists());
+        files.sort();
+        files
+    }
+    /// Builds the library and returns the rustdoc flags that make it and its direct
+    /// dependencies available to the examples
+    fn rustdoc_flags(&self) -> Result<Vec<String>> {
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .no_deps()
+            .exec()
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to read cargo metadata: {}", e)))?;
+        let package = metadata
+            .root_package()
+            .ok_or_else(|| ToolError::ExecutionFailed("No package in the current directory".to_string()))?;
+        let mut direct: Vec<String> = package
+            .dependencies
+            .iter()
+            .map(|dep| dep.name.replace('-', "_"))
+            .collect();
+        let root_lib = package
+            .targets
+            .iter()
+            .find(|t| t.kind.iter().any(|k| k == "lib" || k == "proc-macro"))
+            .map(|t| t.name.replace('-', "_"));
+        let mut args = vec!["build", "--message-format=json"];
+        if root_lib.is_some() {
+            args.push("--lib");
+        }
+        let output = std::process::Command::new("cargo")
+            .args(&args)
+            .stderr(std::process::Stdio::inherit())
+            .output()
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to run cargo build: {}", e)))?;
+        if !output.status.success() {
+            return Err(ToolError::ExecutionFailed("cargo build failed - fix the crate before checking examples".to_string()));
+        }
+        direct.extend(root_lib.clone());
+        let mut externs = BTreeMap::new();
+        let mut deps_dir = None;
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(line) else {
+                continue;
+            };
+            if json["reason"] != "compiler-artifact" {
+                continue;
+            }
+            let name = json["target"]["name"].as_str().unwrap_or_default().replace('-', "_");
+            let is_root = json["package_id"].as_str() == Some(package.id.repr.as_str());
+            if !direct.contains(&name) || (Some(&name) == root_lib.as_ref() && !is_root) {
+                continue;
+            }
+            let artifact = json["filenames"].as_array().into_iter().flatten().filter_map(|f| f.as_str()).find(|f| {
+                [".rlib", ".so", ".dylib", ".dll"].iter().any(|ext| f.ends_with(ext))
+            });
+            if let Some(artifact) = artifact {
+                let parent = Path::new(artifact).parent().unwrap_or(Path::new("."));
+                deps_dir = Some(if parent.ends_with("deps") { parent.to_path_buf() } else { parent.join("deps") });
+                externs.entry(name).or_insert_with(|| artifact.to_string());
+            }
+        }
+        let mut flags = vec!["--edition".to_string(), package.edition.to_string()];
+        if let Some(dir) = deps_dir {
+            flags.push("-L".to_string());
+            flags.push(format!("dependency={}", dir.display()));
+        }
+        for (name, path) in externs {
+            flags.push("--extern".to_string());
+            flags.push(format!("{}={}", name, path));
+        }
+        Ok(flags)
+    }
+    fn compile(&self, file: &Path, flags: &[String]) -> Result<BTreeMap<usize, Option<std::result::Result<(), String>>>> {
+        let output = std::process::Command::new("rustdoc")
+            .arg("--test")
+            .arg(file)
+            .args(flags)
+            .output()
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to run rustdoc: {}", e)))?;
+        let text = format!("{}\n{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+        Ok(Self::parse_rustdoc_output(&text))
+    }
+    fn display_report(&self, results: &[BlockResult], output_format: OutputFormat, verbose: bool) {
+        match output_format {
+            OutputFormat::Human => {
+                println!("\n{}", "📖 README Example Report".bold().blue());
+                println!("{}", "═".repeat(50).blue());
+                for result in results {
+                    let icon = match result.status {
+                        BlockStatus::Ok => "✅",
+                        BlockStatus::Rotted => "❌",
+                        BlockStatus::Ignored => "⏭️ ",
+                        BlockStatus::Drifted => "⚠️ ",
+                        BlockStatus::Updated => "🔄",
+                        BlockStatus::Unchecked => "•",
+                    };
+                    if result.status == BlockStatus::Ok && !verbose {
+                        continue;
+                    }
+                    let source = result.example.as_ref().map(|e| format!(" ← {}", e)).unwrap_or_default();
+                    println!("  {} {}:{}{}", icon, result.file, result.line, source.dimmed());
+                    if let Some(error) = &result.error {
+                        println!("      {}", error.red());
+                    }
+                }
+                let count = |status: BlockStatus| results.iter().filter(|r| r.status == status).count();
+                println!("\n📊 Summary:");
+                println!("  • Examples: {}", results.len());
+                println!("  • Compiling: {}", count(BlockStatus::Ok).to_string().green());
+                println!("  • Rotted: {}", count(BlockStatus::Rotted).to_string().red());
+                println!("  • Out of sync with examples/: {}", count(BlockStatus::Drifted).to_string().yellow());
+                println!("  • Rewritten: {}", count(BlockStatus::Updated).to_string().cyan());
+                if count(BlockStatus::Drifted) > 0 {
+                    println!("\n💡 Run with --update to copy the example files into the markdown");
+                }
+            }
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(results).unwrap_or_else(|_| "[]".to_string()));
+            }
+            OutputFormat::Table => {
+                println!("{:<40} {:<10} {:<30}", "Block", "Status", "Example");
+                println!("{}", "─".repeat(80));
+                for result in results {
+                    println!(
+                        "{:<40} {:<10} {:<30}",
+                        format!("{}:{}", result.file, result.line),
+                        format!("{:?}", result.status).to_uppercase(),
+                        result.example.as_deref().unwrap_or("-")
+                    );
+                }
+            }
+        }
+    }
+}
+impl Tool for ReadmeSyncTool {
+    fn name(&self) -> &'static str {
+        "readme-sync"
+    }
+    fn description(&self) -> &'static str {
+        "Compile README code examples as doc-tests and sync them from examples/"
+    }
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about(self.description())
+            .long_about(
+                "Extracts ```rust blocks from README.md and docs/**/*.md, compiles them with \
+                        rustdoc --test against the current crate and reports the ones that rotted. \
+                        A block preceded by <!-- example: examples/foo.rs --> must match that file; \
+                        --update rewrites it from the file.
+
+EXAMPLES:
+    cm tool readme-sync
+    cm tool readme-sync --file README.md --file docs/guide.md
+    cm tool readme-sync --update --no-compile",
+            )
+            .args(
+                &[
+                    Arg::new("files")
+                        .long("file")
+                        .short('f')
+                        .help("Markdown file to check (repeatable, default README.md and docs/**/*.md)")
+                        .action(clap::ArgAction::Append),
+                    Arg::new("update")
+                        .long("update")
+                        .short('u')
+                        .help("Rewrite designated blocks from their examples/*.rs files")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("no-compile")
+                        .long("no-compile")
+                        .help("Only check designated blocks against their example files")
+                        .action(clap::ArgAction::SetTrue),
+                ],
+            )
+            .args(common_options())
+    }
+    fn execute(&self, matches: &ArgMatches) -> Result<()> {
+        let files: Vec<PathBuf> = match matches.get_many::<String>("files") {
+            Some(files) => files.map(PathBuf::from).collect(),
+            None => Self::default_files(),
+        };
+        let update = matches.get_flag("update");
+        let compile = !matches.get_flag("no-compile");
+        let dry_run = matches.get_flag("dry-run");
+        let verbose = matches.get_flag("verbose");
+        let output_format = parse_output_format(matches);
+        if files.is_empty() {
+            println!("{}", "No markdown files found".yellow());
+            return Ok(());
+        }
+        let flags = if compile { Some(self.rustdoc_flags()?) } else { None };
+        let root = std::env::current_dir()?;
+        let mut results = Vec::new();
+        for file in &files {
+            let mut content = fs::read_to_string(file)?;
+            let mut blocks = Self::extract_blocks(&content);
+            let (rewritten, changed) = Self::rewrite_from_examples(&content, &blocks, &root)?;
+            if update && !changed.is_empty() && !dry_run {
+                fs::write(file, &rewritten)?;
+                content = rewritten;
+                blocks = Self::extract_blocks(&content);
+            }
+            let outcomes = match &flags {
+                Some(flags) if !blocks.is_empty() => self.compile(file, flags)?,
+                _ => BTreeMap::new(),
+            };
+            for (index, block) in blocks.into_iter().enumerate() {
+                let mut error = None;
+                let status = if changed.contains(&index) && !update {
+                    error = Some(format!("differs from {}", block.example.as_deref().unwrap_or_default()));
+                    BlockStatus::Drifted
+                } else {
+                    match outcomes.get(&block.fence_line) {
+                        Some(Some(Err(message))) => {
+                            error = Some(message.clone()).filter(|m| !m.is_empty());
+                            BlockStatus::Rotted
+                        }
+                        Some(None) => BlockStatus::Ignored,
+                        _ if changed.contains(&index) => BlockStatus::Updated,
+                        Some(Some(Ok(()))) => BlockStatus::Ok,
+                        None => BlockStatus::Unchecked,
+                    }
+                };
+                results.push(BlockResult {
+                    file: file.display().to_string(),
+                    line: block.fence_line,
+                    example: block.example,
+                    status,
+                    error,
+                });
+            }
+        }
+        self.display_report(&results, output_format, verbose);
+        let failed = results.iter().filter(|r| matches!(r.status, BlockStatus::Rotted | BlockStatus::Drifted)).count();
+        if failed > 0 {
+            return Err(ToolError::ExecutionFailed(format!("{} README example(s) need attention", failed)));
+        }
+        Ok(())
+    }
+}
+impl Default for ReadmeSyncTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_extract_and_rewrite_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("examples")).unwrap();
+        fs::write(dir.path().join("examples/hello.rs"), "fn main() {\n    hello();\n}\n").unwrap();
+        let readme = "# Demo\n\n```rust\nlet x = 1;\n```\n\n```bash\ncm build\n```\n\n<!-- example: examples/hello.rs -->\n```rust,no_run\nfn main() {}\n```\n";
+        let blocks = ReadmeSyncTool::extract_blocks(readme);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!((blocks[0].fence_line, blocks[0].code.as_str()), (3, "let x = 1;"));
+        assert_eq!(blocks[1].example.as_deref(), Some("examples/hello.rs"));
+        assert_eq!(blocks[1].info, "rust,no_run");
+        let (rewritten, changed) = ReadmeSyncTool::rewrite_from_examples(readme, &blocks, dir.path()).unwrap();
+        assert_eq!(changed, vec![1]);
+        assert!(rewritten.ends_with("```rust,no_run\nfn main() {\n    hello();\n}\n```\n"));
+        let output = "test README.md - Demo (line 3) ... FAILED\ntest README.md - Demo (line 12) ... ok\n\nfailures:\n\n---- README.md - Demo (line 3) stdout ----\nerror[E0425]: cannot find value `y`\n";
+        let outcomes = ReadmeSyncTool::parse_rustdoc_output(output);
+        assert_eq!(outcomes[&3], Some(Err("error[E0425]: cannot find value `y`".to_string())));
+        assert_eq!(outcomes[&12], Some(Ok(())));
+    }
+}
@@ -2,7 +2,7 @@ use super::{Tool, Result, ToolError, common_options, parse_output_format, Output
 use clap::{Arg, ArgMatches, Command};
 use colored::*;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::process::Command as ProcessCommand;
 use chrono;
@@ -1473,10 +1473,12 @@ impl RefactorEngineTool {
     ) -> Result<()> {
         let file_path = &transformation.location.file;
         let content = fs::read_to_string(file_path)?;
-        let backup_path = format!(
-            "{}.backup.{}", file_path, chrono::Utc::now().timestamp()
-        );
-        fs::write(&backup_path, &content)?;
+        let undo = crate::undo::Recorder::capture(
+            "refactor",
+            &transformation.description,
+            [PathBuf::from(file_path)],
+        )
+            .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
         let modified_content = match transformation.transformation_type {
             TransformationType::FunctionExtraction => {
                 self.apply_function_extraction(&content, transformation)?
@@ -1487,6 +1489,7 @@ impl RefactorEngineTool {
             _ => content.clone(),
         };
         fs::write(file_path, &modified_content)?;
+        undo.commit();
         Ok(())
     }
     fn apply_function_extraction(
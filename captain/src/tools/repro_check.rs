@@ -0,0 +1,341 @@
+use super::{Tool, Result, ToolError, common_options, parse_output_format, OutputFormat};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use regex::Regex;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command as ProcessCommand;
+use walkdir::WalkDir;
+/// Shortest printable run compared between two copies of an artifact
+const MIN_STRING: usize = 8;
+const ARTIFACT_EXTENSIONS: &[&str] = &["rlib", "so", "a", "dylib", "dll", "exe", "wasm", "lib"];
+#[derive(Debug, Clone)]
+pub struct ReproCheckTool;
+/// How one of the two builds differs from the other, besides its directory
+struct BuildEnv {
+    label: &'static str,
+    locale: &'static str,
+    timezone: &'static str,
+}
+const ENVS: [BuildEnv; 2] = [
+    BuildEnv { label: "a", locale: "C", timezone: "UTC" },
+    BuildEnv { label: "b-second-build", locale: "en_US.UTF-8", timezone: "Pacific/Kiritimati" },
+];
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Cause {
+    EmbeddedPath,
+    Timestamp,
+    BuildScript,
+    Other,
+}
+impl Cause {
+    fn remedy(self) -> &'static str {
+        match self {
+            Cause::EmbeddedPath => "Remap the build directory with RUSTFLAGS=\"--remap-path-prefix=$PWD=.\" (or -Ztrim-paths), and keep file!()/CARGO_MANIFEST_DIR out of shipped strings",
+            Cause::Timestamp => "Take build dates from SOURCE_DATE_EPOCH instead of the clock, or drop them from the artifact",
+            Cause::BuildScript => "Make the build script's output deterministic: sort directory listings and map iteration, and avoid clocks and absolute paths",
+            Cause::Other => "Look for HashMap iteration order, randomness, or locale/TZ-dependent formatting (LC_ALL and TZ differed between the builds)",
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize)]
+struct Difference {
+    artifact: String,
+    cause: Cause,
+    samples: Vec<String>,
+}
+#[derive(Debug, Clone, Serialize)]
+struct ReproReport {
+    profile: String,
+    source_date_epoch: Option<String>,
+    artifacts: usize,
+    identical: Vec<String>,
+    differences: Vec<Difference>,
+    only_in_one: Vec<String>,
+}
+/// Printable ASCII runs of at least `MIN_STRING` bytes
+fn printable_strings(bytes: &[u8]) -> BTreeSet<String> {
+    bytes
+        .split(|b| !(0x20..0x7f).contains(b))
+        .filter(|run| run.len() >= MIN_STRING)
+        .map(|run| String::from_utf8_lossy(run).into_owned())
+        .collect()
+}
+/// Sorts the strings found in only one of two artifacts by the nondeterminism they point at
+fn classify(unique: &BTreeSet<String>, roots: &[String]) -> BTreeMap<Cause, Vec<String>> {
+    let timestamp = Regex::new(
+        r"\b(19|20)\d\d-[01]\d-[0-3]\d[ T][0-2]\d:[0-5]\d|\b(Mon|Tue|Wed|Thu|Fri|Sat|Sun),? [0-3]?\d? ?(Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec)|\b1[6-9]\d{8}(\d{3}){0,3}\b",
+    )
+    .expect("valid timestamp pattern");
+    let mut causes: BTreeMap<Cause, Vec<String>> = BTreeMap::new();
+    for string in unique {
+        let cause = if roots.iter().any(|root| string.contains(root.as_str())) {
+            Cause::EmbeddedPath
+        } else if timestamp.is_match(string) {
+            Cause::Timestamp
+        } else {
+            Cause::Other
+        };
+        causes.entry(cause).or_default().push(string.clone());
+    }
+    causes
+}
+fn is_artifact(path: &Path) -> bool {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(extension) => ARTIFACT_EXTENSIONS.contains(&extension),
+        None => is_executable(path),
+    }
+}
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    false
+}
+fn hash(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|bytes| format!("{:x}", Sha256::digest(&bytes)))
+}
+/// Final artifacts plus build-script output, keyed by their path under the profile directory
+fn outputs(profile_dir: &Path) -> BTreeMap<String, PathBuf> {
+    let mut out = BTreeMap::new();
+    for entry in fs::read_dir(profile_dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_file() && is_artifact(&path) {
+            out.insert(entry.file_name().to_string_lossy().to_string(), path);
+        }
+    }
+    for entry in WalkDir::new(profile_dir.join("build")).into_iter().flatten() {
+        let Ok(relative) = entry.path().strip_prefix(profile_dir) else {
+            continue;
+        };
+        let mut parts = relative.components().skip(2);
+        let in_output = match parts.next().map(|c| c.as_os_str().to_string_lossy().to_string()) {
+            Some(first) => first == "output" || first == "out",
+            None => false,
+        };
+        if entry.file_type().is_file() && in_output {
+            out.insert(relative.to_string_lossy().replace('\\', "/"), entry.path().to_path_buf());
+        }
+    }
+    out
+}
+fn source_date_epoch(matches: &ArgMatches) -> Option<String> {
+    if matches.get_flag("no-epoch") {
+        return None;
+    }
+    if let Some(epoch) = matches.get_one::<String>("epoch") {
+        return Some(epoch.clone());
+    }
+    let output = ProcessCommand::new("git").args(["log", "-1", "--format=%ct"]).output().ok()?;
+    let epoch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(if output.status.success() && !epoch.is_empty() { epoch } else { "1".to_string() })
+}
+impl ReproCheckTool {
+    pub fn new() -> Self {
+        Self
+    }
+    fn build(&self, root: &Path, env: &BuildEnv, workdir: &Path, matches: &ArgMatches, epoch: &Option<String>) -> Result<PathBuf> {
+        let source = workdir.join("src");
+        crate::journey_sandbox::copy_tree(root, &source).map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+        let target = workdir.join("target");
+        let mut command = ProcessCommand::new("cargo");
+        command.arg("build").current_dir(&source);
+        if matches.get_flag("release") {
+            command.arg("--release");
+        }
+        if let Some(package) = matches.get_one::<String>("package") {
+            command.args(["--package", package]);
+        }
+        if source.join("Cargo.lock").exists() {
+            command.arg("--locked");
+        }
+        command
+            .env("CARGO_TARGET_DIR", &target)
+            .env("CARGO_INCREMENTAL", "0")
+            .env("LC_ALL", env.locale)
+            .env("LANG", env.locale)
+            .env("TZ", env.timezone);
+        match epoch {
+            Some(epoch) => command.env("SOURCE_DATE_EPOCH", epoch),
+            None => command.env_remove("SOURCE_DATE_EPOCH"),
+        };
+        crate::net::NetworkSettings::load().apply_env(&mut command);
+        eprintln!("🔨 Build {} in {} (LC_ALL={}, TZ={})", env.label, workdir.display(), env.locale, env.timezone);
+        let status = command.status()?;
+        if !status.success() {
+            return Err(ToolError::ExecutionFailed(format!("Build {} failed in {}", env.label, workdir.display())));
+        }
+        Ok(target.join(if matches.get_flag("release") { "release" } else { "debug" }))
+    }
+    fn compare(&self, a: &Path, b: &Path, roots: &[String]) -> (Vec<String>, Vec<Difference>, Vec<String>, usize) {
+        let (left, right) = (outputs(a), outputs(b));
+        let (mut identical, mut differences) = (Vec::new(), Vec::new());
+        let only_in_one: Vec<String> = left.keys().filter(|k| !right.contains_key(*k)).chain(right.keys().filter(|k| !left.contains_key(*k))).cloned().collect();
+        for (name, left_path) in &left {
+            let Some(right_path) = right.get(name) else {
+                continue;
+            };
+            if hash(left_path) == hash(right_path) {
+                identical.push(name.clone());
+                continue;
+            }
+            let (l, r) = (printable_strings(&fs::read(left_path).unwrap_or_default()), printable_strings(&fs::read(right_path).unwrap_or_default()));
+            let unique: BTreeSet<String> = l.symmetric_difference(&r).cloned().collect();
+            let mut causes = classify(&unique, roots);
+            if name.starts_with("build/") {
+                let samples = causes.values().flatten().take(3).cloned().collect();
+                causes = BTreeMap::from([(Cause::BuildScript, samples)]);
+            }
+            if causes.is_empty() {
+                causes.insert(Cause::Other, Vec::new());
+            }
+            for (cause, samples) in causes {
+                differences.push(Difference { artifact: name.clone(), cause, samples: samples.into_iter().take(3).collect() });
+            }
+        }
+        (identical, differences, only_in_one, left.len().max(right.len()))
+    }
+    fn display(&self, report: &ReproReport, format: OutputFormat) -> Result<()> {
+        if matches!(format, OutputFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(report)?);
+            return Ok(());
+        }
+        println!("\n{}", format!("🔁 Reproducibility ({} profile)", report.profile).bold().blue());
+        println!("{}", "═".repeat(50).blue());
+        match &report.source_date_epoch {
+            Some(epoch) => println!("SOURCE_DATE_EPOCH={} in both builds", epoch),
+            None => println!("SOURCE_DATE_EPOCH unset in both builds"),
+        }
+        println!("✅ {} of {} output(s) identical", report.identical.len(), report.artifacts);
+        for name in &report.only_in_one {
+            println!("  ❓ {} exists in only one build", name.yellow());
+        }
+        let mut causes = BTreeSet::new();
+        for difference in &report.differences {
+            println!("  ❌ {} - {:?}", difference.artifact.red(), difference.cause);
+            for sample in &difference.samples {
+                let sample: String = sample.chars().take(100).collect();
+                println!("       {}", sample.dimmed());
+            }
+            causes.insert(difference.cause);
+        }
+        if !causes.is_empty() {
+            println!("\n💡 {}", "Remediation".bold());
+            for cause in causes {
+                println!("  • {:?}: {}", cause, cause.remedy());
+            }
+        }
+        Ok(())
+    }
+}
+impl Tool for ReproCheckTool {
+    fn name(&self) -> &'static str {
+        "repro-check"
+    }
+    fn description(&self) -> &'static str {
+        "Build twice in separate clean environments and compare artifact hashes"
+    }
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about(self.description())
+            .long_about(
+                "Copy the workspace into two temporary directories, build each with its own target dir, locale and time zone, then hash the artifacts and build-script output. Differences are traced to embedded paths, timestamps, build scripts or other nondeterminism, with a remedy for each",
+            )
+            .args(
+                &[
+                    Arg::new("release")
+                        .long("release")
+                        .help("Compare release builds")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("package")
+                        .long("package")
+                        .short('p')
+                        .help("Build only this package"),
+                    Arg::new("epoch")
+                        .long("epoch")
+                        .help("SOURCE_DATE_EPOCH for both builds (default: the last commit's time)"),
+                    Arg::new("no-epoch")
+                        .long("no-epoch")
+                        .help("Leave SOURCE_DATE_EPOCH unset to expose clock-dependent output")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("epoch"),
+                    Arg::new("keep")
+                        .long("keep")
+                        .help("Keep both build directories for inspection")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("strict")
+                        .long("strict")
+                        .help("Fail when any output differs")
+                        .action(clap::ArgAction::SetTrue),
+                ],
+            )
+            .args(common_options())
+    }
+    fn execute(&self, matches: &ArgMatches) -> Result<()> {
+        let format = parse_output_format(matches);
+        let manifest = crate::project::find_manifest(&std::env::current_dir()?)
+            .ok_or_else(|| ToolError::ExecutionFailed("No Cargo.toml found - run this inside a cargo project".to_string()))?;
+        let root = manifest.parent().unwrap_or(Path::new(".")).to_path_buf();
+        let epoch = source_date_epoch(matches);
+        let workdirs: Vec<PathBuf> = ENVS
+            .iter()
+            .map(|env| std::env::temp_dir().join(format!("cm-repro-{}-{}", env.label, std::process::id())))
+            .collect();
+        let result = (|| {
+            let mut profiles = Vec::new();
+            for (env, workdir) in ENVS.iter().zip(&workdirs) {
+                profiles.push(self.build(&root, env, workdir, matches, &epoch)?);
+            }
+            let roots: Vec<String> = workdirs.iter().map(|w| w.to_string_lossy().to_string()).collect();
+            let (identical, differences, only_in_one, artifacts) = self.compare(&profiles[0], &profiles[1], &roots);
+            Ok::<_, ToolError>(ReproReport {
+                profile: if matches.get_flag("release") { "release" } else { "debug" }.to_string(),
+                source_date_epoch: epoch.clone(),
+                artifacts,
+                identical,
+                differences,
+                only_in_one,
+            })
+        })();
+        if matches.get_flag("keep") {
+            eprintln!("📁 Builds kept in {}", workdirs.iter().map(|w| w.display().to_string()).collect::<Vec<_>>().join(" and "));
+        } else {
+            for workdir in &workdirs {
+                let _ = fs::remove_dir_all(workdir);
+            }
+        }
+        let report = result?;
+        self.display(&report, format)?;
+        if matches.get_flag("strict") && !(report.differences.is_empty() && report.only_in_one.is_empty()) {
+            return Err(ToolError::ExecutionFailed(format!("{} output(s) differ between the builds", report.differences.iter().map(|d| &d.artifact).collect::<BTreeSet<_>>().len() + report.only_in_one.len())));
+        }
+        Ok(())
+    }
+}
+impl Default for ReproCheckTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_classifies_differing_strings() {
+        let bytes = b"\x00\x01/tmp/cm-repro-a-42/src/main.rs\x00short\x00built 2026-10-17 09:15:02\x7fseed=8f3a9c2b\x00nanos 1792238483244247918\x00";
+        let strings = printable_strings(bytes);
+        assert!(strings.contains("/tmp/cm-repro-a-42/src/main.rs"));
+        assert!(!strings.contains("short"));
+        let causes = classify(&strings, &["/tmp/cm-repro-a-42".to_string()]);
+        assert_eq!(causes[&Cause::EmbeddedPath], vec!["/tmp/cm-repro-a-42/src/main.rs"]);
+        assert_eq!(causes[&Cause::Timestamp], vec!["built 2026-10-17 09:15:02", "nanos 1792238483244247918"]);
+        assert_eq!(causes[&Cause::Other], vec!["seed=8f3a9c2b"]);
+    }
+}
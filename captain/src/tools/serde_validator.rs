@@ -3,10 +3,12 @@ use clap::{Arg, ArgMatches, Command};
 use colored::*;
 use std::path::Path;
 use std::fs;
-use std::collections::HashMap;
-use syn::{parse_file, parse2, Item, ItemStruct, Fields, Field, Type, Attribute, Meta};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use syn::{
+    parse_file, Item, ItemStruct, ItemEnum, Fields, Field, Type, Attribute, Ident,
+    Generics, GenericArgument, PathArguments,
+};
 use quote::quote;
-use serde_json::{Value, Map};
 use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone)]
 pub struct SerdeValidatorTool;
@@ -16,6 +18,7 @@ struct ValidationReport {
     fields_analyzed: usize,
     serialization_issues: Vec<SerializationIssue>,
     deserialization_issues: Vec<DeserializationIssue>,
+    round_trip_issues: Vec<RoundTripIssue>,
     suggestions: Vec<String>,
     test_cases_generated: Vec<String>,
     timestamp: String,
@@ -38,15 +41,64 @@ struct DeserializationIssue {
     severity: String,
     suggestion: String,
 }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RoundTripIssue {
+    type_name: String,
+    field_name: String,
+    attribute: String,
+    kind: String,
+    description: String,
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TypeShape {
+    Named,
+    Tuple,
+    Unit,
+}
+#[derive(Debug, Clone, Default)]
+struct ContainerAttrs {
+    rename_all: Option<String>,
+    rename_all_deserialize: Option<String>,
+    rename_all_fields: Option<String>,
+    untagged: bool,
+    tag: Option<String>,
+    content: Option<String>,
+    default: bool,
+    deny_unknown_fields: bool,
+    transparent: bool,
+}
+#[derive(Debug, Clone)]
+struct SerdeAttr {
+    key: String,
+    value: Option<String>,
+    nested: Vec<(String, String)>,
+}
 #[derive(Debug, Clone)]
 struct StructAnalysis {
     name: String,
+    module_path: Vec<String>,
+    crate_name: String,
+    is_enum: bool,
+    shape: TypeShape,
     fields: Vec<FieldAnalysis>,
+    variants: Vec<VariantAnalysis>,
+    container: ContainerAttrs,
+    has_generics: bool,
+    has_partial_eq: bool,
     has_derive_serde: bool,
     has_serialize: bool,
     has_deserialize: bool,
 }
 #[derive(Debug, Clone)]
+struct VariantAnalysis {
+    name: String,
+    shape: TypeShape,
+    fields: Vec<FieldAnalysis>,
+    rename: Option<String>,
+    rename_deserialize: Option<String>,
+    skipped: bool,
+}
+#[derive(Debug, Clone)]
 struct FieldAnalysis {
     name: String,
     ty: String,
@@ -54,8 +106,27 @@ struct FieldAnalysis {
     is_optional: bool,
     default_value: Option<String>,
     rename: Option<String>,
+    rename_deserialize: Option<String>,
+    aliases: Vec<String>,
     skip_serializing: bool,
     skip_deserializing: bool,
+    skip_serializing_if: Option<String>,
+    flatten: bool,
+}
+#[derive(Debug, Clone, PartialEq)]
+enum VariantSignature {
+    Unit,
+    Newtype(Option<&'static str>, String),
+    Tuple(usize),
+    Struct(BTreeSet<String>),
+}
+impl StructAnalysis {
+    fn field_count(&self) -> usize {
+        self.fields.len() + self.variants.iter().map(|v| v.fields.len()).sum::<usize>()
+    }
+    fn kind(&self) -> &'static str {
+        if self.is_enum { "enum" } else { "struct" }
+    }
 }
 impl SerdeValidatorTool {
     pub fn new() -> Self {
@@ -63,62 +134,200 @@ impl SerdeValidatorTool {
     }
     fn analyze_rust_code(&self, file_path: &str) -> Result<Vec<StructAnalysis>> {
         let content = fs::read_to_string(file_path)?;
-        let syntax = parse_file(&content)?;
-        let mut structs = Vec::new();
-        for item in syntax.items {
-            if let Item::Struct(struct_def) = item {
-                let analysis = self.analyze_struct(&struct_def)?;
-                structs.push(analysis);
-            }
-        }
-        Ok(structs)
+        self.analyze_source(&content, Path::new(file_path))
+    }
+    fn analyze_source(
+        &self,
+        content: &str,
+        file_path: &Path,
+    ) -> Result<Vec<StructAnalysis>> {
+        let syntax = parse_file(content)?;
+        let crate_name = crate_name_for(file_path);
+        let module_path = module_path_for(file_path);
+        let mut types = Vec::new();
+        self.collect_items(&syntax.items, &module_path, &crate_name, &mut types)?;
+        Ok(types)
     }
-    fn analyze_struct(&self, struct_def: &syn::ItemStruct) -> Result<StructAnalysis> {
-        let name = struct_def.ident.to_string();
-        let mut has_derive_serde = false;
-        let mut has_serialize = false;
-        let mut has_deserialize = false;
-        for attr in &struct_def.attrs {
-            if attr.path().segments.last().unwrap().ident == "derive" {
-                if let Ok(Meta::List(meta_list)) = attr.parse_args::<syn::Meta>() {
-                    has_serialize = true;
-                    has_deserialize = true;
+    fn collect_items(
+        &self,
+        items: &[Item],
+        module_path: &[String],
+        crate_name: &str,
+        types: &mut Vec<StructAnalysis>,
+    ) -> Result<()> {
+        for item in items {
+            let analysis = match item {
+                Item::Struct(struct_def) => Some(self.analyze_struct(struct_def)?),
+                Item::Enum(enum_def) => Some(self.analyze_enum(enum_def)?),
+                Item::Mod(module) => {
+                    let is_test_module = module
+                        .attrs
+                        .iter()
+                        .any(|a| {
+                            a.path().is_ident("cfg")
+                                && quote!(# a).to_string().contains("test")
+                        });
+                    if let (Some((_, content)), false) = (&module.content, is_test_module)
+                    {
+                        let mut nested_path = module_path.to_vec();
+                        nested_path.push(module.ident.to_string());
+                        self.collect_items(content, &nested_path, crate_name, types)?;
+                    }
+                    None
+                }
+                _ => None,
+            };
+            if let Some(mut analysis) = analysis {
+                if analysis.has_serialize || analysis.has_deserialize {
+                    analysis.module_path = module_path.to_vec();
+                    analysis.crate_name = crate_name.to_string();
+                    types.push(analysis);
                 }
             }
         }
-        let mut fields = Vec::new();
-        if let syn::Fields::Named(named_fields) = &struct_def.fields {
-            for field in &named_fields.named {
-                let field_analysis = self.analyze_field(field)?;
-                fields.push(field_analysis);
+        Ok(())
+    }
+    fn base_analysis(
+        &self,
+        ident: &Ident,
+        attrs: &[Attribute],
+        generics: &Generics,
+    ) -> StructAnalysis {
+        let derives = derived_traits(attrs);
+        let has_serialize = derives.iter().any(|d| d == "Serialize");
+        let has_deserialize = derives.iter().any(|d| d == "Deserialize");
+        let mut container = ContainerAttrs::default();
+        for attr in parse_serde_attrs(attrs) {
+            match attr.key.as_str() {
+                "rename_all" => {
+                    let (serialize, deserialize) = rename_pair(&attr);
+                    container.rename_all = serialize;
+                    container.rename_all_deserialize = deserialize;
+                }
+                "rename_all_fields" => container.rename_all_fields = attr.value.clone(),
+                "untagged" => container.untagged = true,
+                "tag" => container.tag = attr.value.clone(),
+                "content" => container.content = attr.value.clone(),
+                "default" => container.default = true,
+                "deny_unknown_fields" => container.deny_unknown_fields = true,
+                "transparent" => container.transparent = true,
+                _ => {}
             }
         }
-        Ok(StructAnalysis {
-            name,
-            fields,
-            has_derive_serde,
+        StructAnalysis {
+            name: ident.to_string(),
+            module_path: Vec::new(),
+            crate_name: String::new(),
+            is_enum: false,
+            shape: TypeShape::Unit,
+            fields: Vec::new(),
+            variants: Vec::new(),
+            container,
+            has_generics: !generics.params.is_empty(),
+            has_partial_eq: derives.iter().any(|d| d == "PartialEq"),
+            has_derive_serde: has_serialize && has_deserialize,
             has_serialize,
             has_deserialize,
-        })
+        }
+    }
+    fn analyze_struct(&self, struct_def: &ItemStruct) -> Result<StructAnalysis> {
+        let mut analysis = self
+            .base_analysis(&struct_def.ident, &struct_def.attrs, &struct_def.generics);
+        let (shape, fields) = self.analyze_fields(&struct_def.fields)?;
+        analysis.shape = shape;
+        analysis.fields = fields;
+        Ok(analysis)
+    }
+    fn analyze_enum(&self, enum_def: &ItemEnum) -> Result<StructAnalysis> {
+        let mut analysis = self
+            .base_analysis(&enum_def.ident, &enum_def.attrs, &enum_def.generics);
+        analysis.is_enum = true;
+        for variant in &enum_def.variants {
+            let (shape, fields) = self.analyze_fields(&variant.fields)?;
+            let mut rename = None;
+            let mut rename_deserialize = None;
+            let mut skipped = false;
+            for attr in parse_serde_attrs(&variant.attrs) {
+                match attr.key.as_str() {
+                    "rename" => {
+                        let (serialize, deserialize) = rename_pair(&attr);
+                        rename = serialize;
+                        rename_deserialize = deserialize;
+                    }
+                    "skip" | "skip_serializing" | "skip_deserializing" => skipped = true,
+                    _ => {}
+                }
+            }
+            analysis
+                .variants
+                .push(VariantAnalysis {
+                    name: variant.ident.to_string(),
+                    shape,
+                    fields,
+                    rename,
+                    rename_deserialize,
+                    skipped,
+                });
+        }
+        Ok(analysis)
+    }
+    fn analyze_fields(&self, fields: &Fields) -> Result<(TypeShape, Vec<FieldAnalysis>)> {
+        let shape = match fields {
+            Fields::Named(_) => TypeShape::Named,
+            Fields::Unnamed(_) => TypeShape::Tuple,
+            Fields::Unit => TypeShape::Unit,
+        };
+        let mut analyzed = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            analyzed.push(self.analyze_field(field, index)?);
+        }
+        Ok((shape, analyzed))
     }
-    fn analyze_field(&self, field: &Field) -> Result<FieldAnalysis> {
-        let name = field.ident.as_ref().unwrap().to_string();
-        let ty = quote!(# field.ty).to_string();
+    fn analyze_field(&self, field: &Field, index: usize) -> Result<FieldAnalysis> {
+        let name = field
+            .ident
+            .as_ref()
+            .map(|ident| ident.to_string())
+            .unwrap_or_else(|| index.to_string());
+        let field_ty = &field.ty;
+        let ty = compact_type(&quote!(# field_ty).to_string());
+        let is_optional = outer_type_name(field_ty).as_deref() == Some("Option");
         let mut has_serde_attrs = Vec::new();
-        let mut is_optional = false;
         let mut default_value = None;
         let mut rename = None;
+        let mut rename_deserialize = None;
+        let mut aliases = Vec::new();
         let mut skip_serializing = false;
         let mut skip_deserializing = false;
-        if ty.starts_with("Option <") || ty.starts_with("std::option::Option<") {
-            is_optional = true;
-        }
-        for attr in &field.attrs {
-            if attr.path().segments.last().unwrap().ident == "serde" {
-                has_serde_attrs.push(quote!(# attr).to_string());
-                if let Ok(Meta::List(meta_list)) = attr.parse_args::<syn::Meta>() {
-                    has_serde_attrs.push("serde_attr_detected".to_string());
+        let mut skip_serializing_if = None;
+        let mut flatten = false;
+        for attr in parse_serde_attrs(&field.attrs) {
+            has_serde_attrs.push(attr.key.clone());
+            match attr.key.as_str() {
+                "skip" => {
+                    skip_serializing = true;
+                    skip_deserializing = true;
+                }
+                "skip_serializing" => skip_serializing = true,
+                "skip_deserializing" => skip_deserializing = true,
+                "skip_serializing_if" => skip_serializing_if = attr.value.clone(),
+                "default" => {
+                    default_value = Some(
+                        attr
+                            .value
+                            .as_ref()
+                            .map(|path| format!("{}()", path))
+                            .unwrap_or_else(|| "Default::default()".to_string()),
+                    );
                 }
+                "rename" => {
+                    let (serialize, deserialize) = rename_pair(&attr);
+                    rename = serialize;
+                    rename_deserialize = deserialize;
+                }
+                "alias" => aliases.extend(attr.value.clone()),
+                "flatten" => flatten = true,
+                _ => {}
             }
         }
         Ok(FieldAnalysis {
@@ -128,8 +337,12 @@ impl SerdeValidatorTool {
             is_optional,
             default_value,
             rename,
+            rename_deserialize,
+            aliases,
             skip_serializing,
             skip_deserializing,
+            skip_serializing_if,
+            flatten,
         })
     }
     fn validate_serialization(
@@ -167,8 +380,8 @@ impl SerdeValidatorTool {
                                 .to_string(),
                         });
                 }
-                if field.skip_serializing && !field.is_optional
-                    && field.default_value.is_none()
+                if field.skip_serializing && !field.skip_deserializing
+                    && !field.is_optional && field.default_value.is_none()
                 {
                     issues
                         .push(SerializationIssue {
@@ -235,21 +448,6 @@ impl SerdeValidatorTool {
                                 .to_string(),
                         });
                 }
-                if !field.is_optional && field.default_value.is_none()
-                    && field.skip_deserializing
-                {
-                    issues
-                        .push(DeserializationIssue {
-                            struct_name: struct_analysis.name.clone(),
-                            field_name: field.name.clone(),
-                            issue_type: "required_field_skipped".to_string(),
-                            description: "Required field is skipped during deserialization"
-                                .to_string(),
-                            severity: "error".to_string(),
-                            suggestion: "Add a default value or make the field optional"
-                                .to_string(),
-                        });
-                }
                 if let Some(rename_val) = &field.rename {
                     if rename_val.contains(" ") || rename_val.contains("-") {
                         issues
@@ -270,22 +468,24 @@ impl SerdeValidatorTool {
         }
         issues
     }
-    fn generate_test_cases(&self, structs: &[StructAnalysis]) -> Vec<String> {
+    fn generate_test_cases(
+        &self,
+        structs: &[StructAnalysis],
+        format: &str,
+    ) -> Vec<String> {
         let mut test_cases = Vec::new();
         for struct_analysis in structs {
-            if !struct_analysis.has_serialize || !struct_analysis.has_deserialize {
+            if !struct_analysis.has_derive_serde || struct_analysis.is_enum
+                || struct_analysis.has_generics
+                || struct_analysis.shape != TypeShape::Named
+            {
                 continue;
             }
-            let test_name = format!(
-                "test_{}_serde", struct_analysis.name.to_lowercase()
-            );
+            let test_name = format!("test_{}_serde", to_snake_case(&struct_analysis.name));
             let mut test_code = format!("#[test]\nfn {}() {{\n", test_name);
             test_code
                 .push_str(&format!("    let test_data = {} {{\n", struct_analysis.name));
             for field in &struct_analysis.fields {
-                if field.skip_serializing || field.skip_deserializing {
-                    continue;
-                }
                 let test_value = self.generate_test_value(field);
                 test_code
                     .push_str(&format!("        {}: {},\n", field.name, test_value));
@@ -294,32 +494,55 @@ impl SerdeValidatorTool {
             test_code.push_str("    // Test serialization\n");
             test_code
                 .push_str(
-                    "    let serialized = serde_json::to_string(&test_data).unwrap();\n",
+                    &format!(
+                        "    let serialized = {}.unwrap();\n", encode_expr(format,
+                        "test_data")
+                    ),
                 );
-            test_code.push_str("    println!(\"Serialized: {{}}\", serialized);\n\n");
+            test_code.push_str("    println!(\"Serialized: {:?}\", serialized);\n\n");
             test_code.push_str("    // Test deserialization\n");
             test_code
                 .push_str(
                     &format!(
-                        "    let deserialized: {} = serde_json::from_str(&serialized).unwrap();\n",
-                        struct_analysis.name
+                        "    let deserialized: {} = {}.unwrap();\n", struct_analysis
+                        .name, decode_expr(format, "serialized")
                     ),
                 );
-            test_code.push_str("    assert_eq!(test_data, deserialized);\n");
+            if struct_analysis.has_partial_eq {
+                test_code.push_str("    assert_eq!(test_data, deserialized);\n");
+            } else {
+                test_code
+                    .push_str(
+                        &format!(
+                            "    assert_eq!(serialized, {}.unwrap());\n",
+                            encode_expr(format, "deserialized")
+                        ),
+                    );
+            }
             test_code.push_str("}\n\n");
             test_cases.push(test_code);
         }
         test_cases
     }
     fn generate_test_value(&self, field: &FieldAnalysis) -> String {
+        if field.skip_serializing || field.skip_deserializing {
+            return field
+                .default_value
+                .clone()
+                .unwrap_or_else(|| "Default::default()".to_string());
+        }
         if field.is_optional {
             return "None".to_string();
         }
         if let Some(default) = &field.default_value {
             return default.clone();
         }
-        if field.ty.contains("String") {
-            "\"test_value\"".to_string()
+        if field.ty.starts_with("Vec<") {
+            "vec![]".to_string()
+        } else if field.ty.contains("HashMap") {
+            "std::collections::HashMap::new()".to_string()
+        } else if field.ty == "String" {
+            "\"test_value\".to_string()".to_string()
         } else if field.ty.contains("i32") || field.ty.contains("i64") {
             "42".to_string()
         } else if field.ty.contains("u32") || field.ty.contains("u64") {
@@ -327,13 +550,636 @@ impl SerdeValidatorTool {
         } else if field.ty.contains("bool") {
             "true".to_string()
         } else if field.ty.contains("f32") || field.ty.contains("f64") {
-            "3.14".to_string()
-        } else if field.ty.contains("Vec") {
-            "vec![]".to_string()
-        } else if field.ty.contains("HashMap") {
-            "std::collections::HashMap::new()".to_string()
+            "3.25".to_string()
         } else {
-            format!("{}::default()", field.ty)
+            format!("<{}>::default()", field.ty)
+        }
+    }
+    fn detect_round_trip_issues(
+        &self,
+        types: &[StructAnalysis],
+        format: &str,
+    ) -> Vec<RoundTripIssue> {
+        let mut issues = Vec::new();
+        for analysis in types.iter().filter(|t| t.has_derive_serde) {
+            let container = &analysis.container;
+            if container.rename_all != container.rename_all_deserialize {
+                issues
+                    .push(RoundTripIssue {
+                        type_name: analysis.name.clone(),
+                        field_name: analysis.kind().to_string(),
+                        attribute: "rename_all".to_string(),
+                        kind: "broken".to_string(),
+                        description: format!(
+                            "rename_all serializes with {} but deserializes with {}",
+                            container.rename_all.as_deref().unwrap_or("field names"),
+                            container.rename_all_deserialize.as_deref()
+                            .unwrap_or("field names")
+                        ),
+                    });
+            }
+            if !analysis.is_enum {
+                issues
+                    .extend(
+                        self
+                            .check_fields(
+                                &analysis.name,
+                                None,
+                                analysis.shape,
+                                &analysis.fields,
+                                container,
+                                format,
+                            ),
+                    );
+                continue;
+            }
+            let variant_container = ContainerAttrs {
+                rename_all: container.rename_all_fields.clone(),
+                rename_all_deserialize: container.rename_all_fields.clone(),
+                ..container.clone()
+            };
+            let mut variant_names: HashMap<String, String> = HashMap::new();
+            for variant in &analysis.variants {
+                if variant.skipped {
+                    issues
+                        .push(RoundTripIssue {
+                            type_name: analysis.name.clone(),
+                            field_name: variant.name.clone(),
+                            attribute: "skip".to_string(),
+                            kind: "lossy".to_string(),
+                            description: "Variant is skipped; values of it cannot be round-tripped"
+                                .to_string(),
+                        });
+                    continue;
+                }
+                let serialized = variant
+                    .rename
+                    .clone()
+                    .unwrap_or_else(|| {
+                        apply_rename_rule(container.rename_all.as_deref(), &variant.name, true)
+                    });
+                let deserialized = variant
+                    .rename_deserialize
+                    .clone()
+                    .unwrap_or_else(|| {
+                        apply_rename_rule(
+                            container.rename_all_deserialize.as_deref(),
+                            &variant.name,
+                            true,
+                        )
+                    });
+                if serialized != deserialized && variant.rename != variant.rename_deserialize {
+                    issues
+                        .push(RoundTripIssue {
+                            type_name: analysis.name.clone(),
+                            field_name: variant.name.clone(),
+                            attribute: "rename".to_string(),
+                            kind: "broken".to_string(),
+                            description: format!(
+                                "Serialized as `{}` but deserialized from `{}`", serialized,
+                                deserialized
+                            ),
+                        });
+                }
+                if !container.untagged {
+                    if let Some(other) = variant_names
+                        .insert(serialized.clone(), variant.name.clone())
+                    {
+                        issues
+                            .push(RoundTripIssue {
+                                type_name: analysis.name.clone(),
+                                field_name: variant.name.clone(),
+                                attribute: "rename_all".to_string(),
+                                kind: "ambiguous".to_string(),
+                                description: format!(
+                                    "Serializes to the same tag `{}` as variant {}",
+                                    serialized, other
+                                ),
+                            });
+                    }
+                }
+                if container.tag.is_some() && container.content.is_none()
+                    && !container.untagged && variant.shape == TypeShape::Tuple
+                    && variant.fields.len() == 1
+                {
+                    let category = type_category(&variant.fields[0].ty);
+                    if category.is_some_and(|c| c != "map" && c != "option") {
+                        issues
+                            .push(RoundTripIssue {
+                                type_name: analysis.name.clone(),
+                                field_name: variant.name.clone(),
+                                attribute: "tag".to_string(),
+                                kind: "broken".to_string(),
+                                description: format!(
+                                    "Internally tagged newtype variant holding {} fails to serialize",
+                                    variant.fields[0].ty
+                                ),
+                            });
+                    }
+                }
+                issues
+                    .extend(
+                        self
+                            .check_fields(
+                                &analysis.name,
+                                Some(&variant.name),
+                                variant.shape,
+                                &variant.fields,
+                                &variant_container,
+                                format,
+                            ),
+                    );
+            }
+            if container.untagged {
+                issues.extend(self.check_untagged_variants(analysis));
+            }
+        }
+        issues
+    }
+    fn check_fields(
+        &self,
+        type_name: &str,
+        scope: Option<&str>,
+        shape: TypeShape,
+        fields: &[FieldAnalysis],
+        container: &ContainerAttrs,
+        format: &str,
+    ) -> Vec<RoundTripIssue> {
+        let mut issues = Vec::new();
+        let mut serialized_names: HashMap<String, String> = HashMap::new();
+        let mut accepted_names: HashMap<String, String> = HashMap::new();
+        let mut push = |field: &str, attribute: &str, kind: &str, description: String| {
+            issues
+                .push(RoundTripIssue {
+                    type_name: type_name.to_string(),
+                    field_name: field.to_string(),
+                    attribute: attribute.to_string(),
+                    kind: kind.to_string(),
+                    description,
+                });
+        };
+        for field in fields {
+            let label = scope
+                .map(|s| format!("{}.{}", s, field.name))
+                .unwrap_or_else(|| field.name.clone());
+            let has_default = field.default_value.is_some() || container.default;
+            if field.skip_serializing && field.skip_deserializing {
+                push(
+                    &label,
+                    "skip",
+                    "lossy",
+                    "Never serialized; always comes back as its default value".to_string(),
+                );
+                continue;
+            }
+            if field.skip_serializing {
+                if has_default || field.is_optional {
+                    push(
+                        &label,
+                        "skip_serializing",
+                        "lossy",
+                        "Omitted from output; comes back as its default value".to_string(),
+                    );
+                } else {
+                    push(
+                        &label,
+                        "skip_serializing",
+                        "broken",
+                        "Omitted from output but required on input; deserialization fails"
+                            .to_string(),
+                    );
+                }
+            } else if field.skip_deserializing {
+                push(
+                    &label,
+                    "skip_deserializing",
+                    "lossy",
+                    "Written to output but ignored on input; comes back as its default value"
+                        .to_string(),
+                );
+            }
+            if let Some(predicate) = &field.skip_serializing_if {
+                if !field.skip_serializing && !has_default && !field.is_optional {
+                    push(
+                        &label,
+                        "skip_serializing_if",
+                        "broken",
+                        format!(
+                            "Omitted when {} holds, but has no default so deserialization fails",
+                            predicate
+                        ),
+                    );
+                } else if let Some(default) = field
+                    .default_value
+                    .as_ref()
+                    .filter(|d| d.as_str() != "Default::default()")
+                {
+                    push(
+                        &label,
+                        "default",
+                        "ambiguous",
+                        format!(
+                            "Omitted when {} holds but restored from {}; the two may disagree",
+                            predicate, default
+                        ),
+                    );
+                }
+            }
+            if field.flatten && container.deny_unknown_fields {
+                push(
+                    &label,
+                    "flatten",
+                    "broken",
+                    "flatten is not supported together with deny_unknown_fields".to_string(),
+                );
+            }
+            if format == "json" && matches!(field.ty.as_str(), "f32" | "f64") {
+                push(
+                    &label,
+                    "f32/f64",
+                    "lossy",
+                    "NaN and infinite values serialize as null and fail to deserialize"
+                        .to_string(),
+                );
+            }
+            if shape != TypeShape::Named || field.flatten {
+                continue;
+            }
+            let serialized = field
+                .rename
+                .clone()
+                .unwrap_or_else(|| {
+                    apply_rename_rule(container.rename_all.as_deref(), &field.name, false)
+                });
+            let deserialized = field
+                .rename_deserialize
+                .clone()
+                .unwrap_or_else(|| {
+                    apply_rename_rule(
+                        container.rename_all_deserialize.as_deref(),
+                        &field.name,
+                        false,
+                    )
+                });
+            if !field.skip_serializing && !field.skip_deserializing
+                && serialized != deserialized && !field.aliases.contains(&serialized)
+            {
+                push(
+                    &label,
+                    "rename",
+                    "broken",
+                    format!(
+                        "Serialized as `{}` but deserialized from `{}`", serialized,
+                        deserialized
+                    ),
+                );
+            }
+            if !field.skip_serializing {
+                if let Some(other) = serialized_names
+                    .insert(serialized.clone(), label.clone())
+                {
+                    push(
+                        &label,
+                        "rename_all",
+                        "ambiguous",
+                        format!("Serializes to the same key `{}` as {}", serialized, other),
+                    );
+                }
+            }
+            if !field.skip_deserializing {
+                for name in std::iter::once(&deserialized).chain(field.aliases.iter()) {
+                    match accepted_names.get(name) {
+                        Some(other) if other != &label => {
+                            push(
+                                &label,
+                                "alias",
+                                "ambiguous",
+                                format!("Accepts key `{}` already claimed by {}", name, other),
+                            );
+                        }
+                        _ => {
+                            accepted_names.insert(name.clone(), label.clone());
+                        }
+                    }
+                }
+            }
+        }
+        issues
+    }
+    fn check_untagged_variants(&self, analysis: &StructAnalysis) -> Vec<RoundTripIssue> {
+        let variants: Vec<(&VariantAnalysis, VariantSignature)> = analysis
+            .variants
+            .iter()
+            .filter(|v| !v.skipped)
+            .map(|v| (v, variant_signature(v)))
+            .collect();
+        let mut issues = Vec::new();
+        for (index, (later, later_signature)) in variants.iter().enumerate() {
+            let shadowed_by = variants[..index]
+                .iter()
+                .find(|(_, earlier_signature)| {
+                    signature_shadows(
+                        earlier_signature,
+                        later_signature,
+                        analysis.container.deny_unknown_fields,
+                    )
+                });
+            if let Some((earlier, _)) = shadowed_by {
+                issues
+                    .push(RoundTripIssue {
+                        type_name: analysis.name.clone(),
+                        field_name: later.name.clone(),
+                        attribute: "untagged".to_string(),
+                        kind: "ambiguous".to_string(),
+                        description: format!(
+                            "Values of {} also match earlier variant {} and deserialize as {}",
+                            later.name, earlier.name, earlier.name
+                        ),
+                    });
+            }
+        }
+        issues
+    }
+    fn round_trip_candidates<'a>(
+        &self,
+        types: &'a [StructAnalysis],
+    ) -> Vec<&'a StructAnalysis> {
+        let mut seen = HashSet::new();
+        let unique: Vec<&StructAnalysis> = types
+            .iter()
+            .filter(|t| t.has_derive_serde && !t.has_generics)
+            .filter(|t| !t.is_enum || t.variants.iter().any(|v| !v.skipped))
+            .filter(|t| seen.insert(t.name.clone()))
+            .collect();
+        let names: HashSet<&str> = unique.iter().map(|t| t.name.as_str()).collect();
+        let dependencies: HashMap<&str, HashSet<&str>> = unique
+            .iter()
+            .map(|t| {
+                let referenced = t
+                    .fields
+                    .iter()
+                    .chain(t.variants.iter().flat_map(|v| v.fields.iter()))
+                    .flat_map(|f| {
+                        f.ty.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    })
+                    .filter_map(|word| names.get(word).copied())
+                    .collect();
+                (t.name.as_str(), referenced)
+            })
+            .collect();
+        let mut excluded: HashSet<&str> = names
+            .iter()
+            .copied()
+            .filter(|name| reaches(name, name, &dependencies, &mut HashSet::new()))
+            .collect();
+        loop {
+            let newly_excluded: Vec<&str> = dependencies
+                .iter()
+                .filter(|(name, deps)| {
+                    !excluded.contains(*name)
+                        && deps.iter().any(|dep| excluded.contains(dep))
+                })
+                .map(|(name, _)| *name)
+                .collect();
+            if newly_excluded.is_empty() {
+                break;
+            }
+            excluded.extend(newly_excluded);
+        }
+        unique.into_iter().filter(|t| !excluded.contains(t.name.as_str())).collect()
+    }
+    fn generate_round_trip_tests(
+        &self,
+        types: &[StructAnalysis],
+        issues: &[RoundTripIssue],
+        format: &str,
+        cases: u32,
+    ) -> Vec<String> {
+        let candidates = self.round_trip_candidates(types);
+        let known: HashSet<String> = candidates.iter().map(|t| t.name.clone()).collect();
+        let mut tests = Vec::new();
+        for analysis in candidates {
+            let snake = to_snake_case(&analysis.name);
+            let mut code = format!("// Round-trip: {} ({})\n", analysis.name, format);
+            code.push_str(&self.generate_strategy(analysis, &known));
+            code.push_str("\nproptest! {\n");
+            code.push_str(
+                &format!("    #![proptest_config(ProptestConfig::with_cases({}))]\n", cases),
+            );
+            code.push_str("    #[test]\n");
+            if let Some(issue) = issues
+                .iter()
+                .find(|i| {
+                    i.type_name == analysis.name
+                        && (i.kind == "ambiguous" || i.kind == "broken")
+                })
+            {
+                code.push_str(
+                    &format!(
+                        "    #[ignore = {:?}]\n", format!("{} {}: {}", issue.kind, issue
+                        .field_name, issue.description)
+                    ),
+                );
+            }
+            code.push_str(
+                &format!("    fn roundtrip_{}(value in arb_{}()) {{\n", snake, snake),
+            );
+            code.push_str(
+                &format!(
+                    "        let encoded = {}.expect(\"serialize {}\");\n",
+                    encode_expr(format, "value"), analysis.name
+                ),
+            );
+            code.push_str(
+                &format!(
+                    "        let decoded: {} = {}.expect(\"deserialize {}\");\n", analysis
+                    .name, decode_expr(format, "encoded"), analysis.name
+                ),
+            );
+            if analysis.has_partial_eq {
+                code.push_str("        prop_assert_eq!(&value, &decoded);\n");
+            } else {
+                code.push_str(
+                    &format!(
+                        "        prop_assert_eq!(encoded, {}.expect(\"serialize {}\"));\n",
+                        encode_expr(format, "decoded"), analysis.name
+                    ),
+                );
+            }
+            code.push_str("    }\n}\n\n");
+            tests.push(code);
+        }
+        tests
+    }
+    fn generate_strategy(
+        &self,
+        analysis: &StructAnalysis,
+        known: &HashSet<String>,
+    ) -> String {
+        let body = if analysis.is_enum {
+            let arms: Vec<String> = analysis
+                .variants
+                .iter()
+                .filter(|v| !v.skipped)
+                .map(|v| {
+                    self.constructor_strategy(
+                        &format!("{}::{}", analysis.name, v.name),
+                        v.shape,
+                        &v.fields,
+                        known,
+                    )
+                })
+                .collect();
+            if arms.len() == 1 {
+                arms[0].clone()
+            } else {
+                format!("prop_oneof![\n        {},\n    ]", arms.join(",\n        "))
+            }
+        } else {
+            self.constructor_strategy(&analysis.name, analysis.shape, &analysis.fields, known)
+        };
+        format!(
+            "fn arb_{}() -> impl Strategy<Value = {}> {{\n    {}\n}}\n",
+            to_snake_case(&analysis.name), analysis.name, body
+        )
+    }
+    fn constructor_strategy(
+        &self,
+        path: &str,
+        shape: TypeShape,
+        fields: &[FieldAnalysis],
+        known: &HashSet<String>,
+    ) -> String {
+        if fields.is_empty() {
+            return match shape {
+                TypeShape::Named => format!("LazyJust::new(|| {} {{}})", path),
+                TypeShape::Tuple => format!("LazyJust::new(|| {}())", path),
+                TypeShape::Unit => format!("LazyJust::new(|| {})", path),
+            };
+        }
+        let strategies: Vec<String> = fields
+            .iter()
+            .map(|f| self.field_strategy(f, known))
+            .collect();
+        let bindings: Vec<String> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                if shape == TypeShape::Named { f.name.clone() } else { format!("v{}", i) }
+            })
+            .collect();
+        let construct = if shape == TypeShape::Named {
+            format!("{} {{ {} }}", path, bindings.join(", "))
+        } else {
+            format!("{}({})", path, bindings.join(", "))
+        };
+        let (strategy, pattern) = nest_tuples(&strategies, &bindings);
+        format!("{}\n        .prop_map(|{}| {})", strategy, pattern, construct)
+    }
+    fn field_strategy(&self, field: &FieldAnalysis, known: &HashSet<String>) -> String {
+        if field.skip_serializing || field.skip_deserializing {
+            return match &field.default_value {
+                Some(default) if default != "Default::default()" => {
+                    format!("LazyJust::new({})", default.trim_end_matches("()"))
+                }
+                _ => format!("LazyJust::new(<{} as Default>::default)", field.ty),
+            };
+        }
+        syn::parse_str::<Type>(&field.ty)
+            .map(|ty| self.strategy_for(&ty, known))
+            .unwrap_or_else(|_| format!("any::<{}>()", field.ty))
+    }
+    fn strategy_for(&self, ty: &Type, known: &HashSet<String>) -> String {
+        let fallback = || format!("any::<{}>()", compact_type(&quote!(# ty).to_string()));
+        match ty {
+            Type::Path(type_path) if type_path.qself.is_none() => {
+                let Some(segment) = type_path.path.segments.last() else {
+                    return fallback();
+                };
+                let args: Vec<&Type> = match &segment.arguments {
+                    PathArguments::AngleBracketed(generic) => {
+                        generic
+                            .args
+                            .iter()
+                            .filter_map(|arg| match arg {
+                                GenericArgument::Type(inner) => Some(inner),
+                                _ => None,
+                            })
+                            .collect()
+                    }
+                    _ => Vec::new(),
+                };
+                let inner = |index: usize| {
+                    args.get(index).map(|t| self.strategy_for(t, known))
+                };
+                let ident = segment.ident.to_string();
+                match (ident.as_str(), inner(0), inner(1)) {
+                    ("Option", Some(s), _) => format!("proptest::option::of({})", s),
+                    ("Vec", Some(s), _) => {
+                        format!("proptest::collection::vec({}, 0..8)", s)
+                    }
+                    ("VecDeque", Some(s), _) => {
+                        format!("proptest::collection::vec_deque({}, 0..8)", s)
+                    }
+                    ("HashSet", Some(s), _) => {
+                        format!("proptest::collection::hash_set({}, 0..8)", s)
+                    }
+                    ("BTreeSet", Some(s), _) => {
+                        format!("proptest::collection::btree_set({}, 0..8)", s)
+                    }
+                    ("HashMap", Some(k), Some(v)) => {
+                        format!("proptest::collection::hash_map({}, {}, 0..8)", k, v)
+                    }
+                    ("BTreeMap", Some(k), Some(v)) => {
+                        format!("proptest::collection::btree_map({}, {}, 0..8)", k, v)
+                    }
+                    ("Box", Some(s), _) => format!("{}.prop_map(Box::new)", s),
+                    ("Arc", Some(s), _) => format!("{}.prop_map(std::sync::Arc::new)", s),
+                    ("f64", ..) => {
+                        "(-1_000_000i32..1_000_000).prop_map(|v| f64::from(v) / 4.0)"
+                            .to_string()
+                    }
+                    ("f32", ..) => {
+                        "(-100_000i32..100_000).prop_map(|v| v as f32 / 4.0)".to_string()
+                    }
+                    (name, ..) if args.is_empty() && known.contains(name) => {
+                        format!("arb_{}()", to_snake_case(name))
+                    }
+                    _ => fallback(),
+                }
+            }
+            Type::Tuple(tuple) if tuple.elems.is_empty() => "Just(())".to_string(),
+            Type::Tuple(tuple) if tuple.elems.len() <= 12 => {
+                let elems: Vec<String> = tuple
+                    .elems
+                    .iter()
+                    .map(|t| self.strategy_for(t, known))
+                    .collect();
+                if elems.len() == 1 {
+                    format!("({},)", elems[0])
+                } else {
+                    format!("({})", elems.join(", "))
+                }
+            }
+            Type::Array(array) => {
+                let len = match &array.len {
+                    syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(int), .. }) => {
+                        int.base10_parse::<usize>().ok()
+                    }
+                    _ => None,
+                };
+                match len {
+                    Some(n) if (1..=32).contains(&n) => {
+                        format!(
+                            "proptest::array::uniform{}({})", n, self.strategy_for(& array
+                            .elem, known)
+                        )
+                    }
+                    _ => fallback(),
+                }
+            }
+            Type::Paren(paren) => self.strategy_for(&paren.elem, known),
+            _ => fallback(),
         }
     }
     fn generate_suggestions(
@@ -341,8 +1187,23 @@ impl SerdeValidatorTool {
         structs: &[StructAnalysis],
         serialization_issues: &[SerializationIssue],
         deserialization_issues: &[DeserializationIssue],
+        round_trip_issues: &[RoundTripIssue],
     ) -> Vec<String> {
         let mut suggestions = Vec::new();
+        if round_trip_issues.iter().any(|i| i.kind == "broken") {
+            suggestions
+                .push(
+                    "Pair skip_serializing/skip_serializing_if with #[serde(default)] so skipped values can be read back"
+                        .to_string(),
+                );
+        }
+        if round_trip_issues.iter().any(|i| i.kind == "ambiguous") {
+            suggestions
+                .push(
+                    "Reorder or tag untagged enum variants and resolve colliding renames"
+                        .to_string(),
+                );
+        }
         if structs.iter().any(|s| !s.has_serialize && !s.has_derive_serde) {
             suggestions
                 .push(
@@ -413,6 +1274,7 @@ impl SerdeValidatorTool {
                     "  • Deserialization Issues: {}", report.deserialization_issues
                     .len()
                 );
+                println!("  • Round-trip Issues: {}", report.round_trip_issues.len());
                 println!(
                     "  • Test Cases Generated: {}", report.test_cases_generated.len()
                 );
@@ -452,6 +1314,20 @@ impl SerdeValidatorTool {
                         }
                     }
                 }
+                if !report.round_trip_issues.is_empty() {
+                    println!("\n🔁 Round-trip Issues:");
+                    for issue in &report.round_trip_issues {
+                        let kind_label = match issue.kind.as_str() {
+                            "broken" => issue.kind.red(),
+                            "ambiguous" => issue.kind.yellow(),
+                            _ => issue.kind.cyan(),
+                        };
+                        println!(
+                            "  [{}] {}::{} ({}) - {}", kind_label, issue.type_name, issue
+                            .field_name, issue.attribute, issue.description
+                        );
+                    }
+                }
                 if verbose && !report.test_cases_generated.is_empty() {
                     println!("\n🧪 Generated Test Cases:");
                     for test_case in &report.test_cases_generated {
@@ -467,6 +1343,7 @@ impl SerdeValidatorTool {
                 println!("\n✅ Validation complete!");
                 if report.serialization_issues.is_empty()
                     && report.deserialization_issues.is_empty()
+                    && report.round_trip_issues.is_empty()
                 {
                     println!("   All structs are properly configured for Serde!");
                 }
@@ -478,28 +1355,35 @@ impl SerdeValidatorTool {
             }
             OutputFormat::Table => {
                 println!(
-                    "{:<25} {:<20} {:<15} {:<15}", "Struct", "Serialization",
-                    "Deserialization", "Total Issues"
+                    "{:<25} {:<20} {:<15} {:<12} {:<15}", "Struct", "Serialization",
+                    "Deserialization", "Round-trip", "Total Issues"
                 );
-                println!("{}", "─".repeat(80));
+                println!("{}", "─".repeat(92));
                 let mut struct_issues = HashMap::new();
                 for issue in &report.serialization_issues {
                     let entry = struct_issues
                         .entry(&issue.struct_name)
-                        .or_insert((0, 0));
+                        .or_insert((0, 0, 0));
                     entry.0 += 1;
                 }
                 for issue in &report.deserialization_issues {
                     let entry = struct_issues
                         .entry(&issue.struct_name)
-                        .or_insert((0, 0));
+                        .or_insert((0, 0, 0));
                     entry.1 += 1;
                 }
-                for (struct_name, (ser_issues, deser_issues)) in struct_issues {
-                    let total = ser_issues + deser_issues;
+                for issue in &report.round_trip_issues {
+                    let entry = struct_issues
+                        .entry(&issue.type_name)
+                        .or_insert((0, 0, 0));
+                    entry.2 += 1;
+                }
+                for (struct_name, (ser_issues, deser_issues, round_trip)) in struct_issues {
+                    let total = ser_issues + deser_issues + round_trip;
                     println!(
-                        "{:<25} {:<20} {:<15} {:<15}", struct_name, ser_issues
-                        .to_string(), deser_issues.to_string(), total.to_string()
+                        "{:<25} {:<20} {:<15} {:<12} {:<15}", struct_name, ser_issues
+                        .to_string(), deser_issues.to_string(), round_trip.to_string(),
+                        total.to_string()
                     );
                 }
             }
@@ -517,12 +1401,14 @@ impl Tool for SerdeValidatorTool {
         Command::new(self.name())
             .about(self.description())
             .long_about(
-                "Analyze Rust structs for proper Serde serialization/deserialization setup. \
-                        Detects common issues and generates test cases.
+                "Analyze Rust structs and enums for proper Serde serialization/deserialization setup. \
+                        Detects common issues, flags attributes (skip, default, rename_all, untagged) \
+                        that make round-trips lossy or ambiguous, and generates test cases.
 
 EXAMPLES:
     cm tool serde-validator --input src/models.rs
     cm tool serde-validator --workspace --generate-tests
+    cm tool serde-validator --input src/models.rs --round-trip --cases 512
     cm tool serde-validator --input src/api.rs --fix",
             )
             .args(
@@ -531,7 +1417,7 @@ EXAMPLES:
                         .long("input")
                         .short('i')
                         .help("Input Rust file to analyze")
-                        .required(true),
+                        .required_unless_present("workspace"),
                     Arg::new("workspace")
                         .long("workspace")
                         .help("Analyze all Rust files in workspace")
@@ -540,9 +1426,17 @@ EXAMPLES:
                         .long("generate-tests")
                         .help("Generate test cases for validated structs")
                         .action(clap::ArgAction::SetTrue),
-                    Arg::new("output")
-                        .long("output")
-                        .short('o')
+                    Arg::new("round-trip")
+                        .long("round-trip")
+                        .help("Generate proptest round-trip tests (serialize → deserialize → compare)")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("cases")
+                        .long("cases")
+                        .help("Number of proptest cases per round-trip test")
+                        .value_parser(clap::value_parser!(u32))
+                        .default_value("256"),
+                    Arg::new("test-output")
+                        .long("test-output")
                         .help("Output file for generated tests")
                         .default_value("tests/serde_tests.rs"),
                     Arg::new("fix")
@@ -563,7 +1457,9 @@ EXAMPLES:
         let input = matches.get_one::<String>("input");
         let workspace = matches.get_flag("workspace");
         let generate_tests = matches.get_flag("generate-tests");
-        let output_file = matches.get_one::<String>("output").unwrap();
+        let round_trip = matches.get_flag("round-trip");
+        let cases = *matches.get_one::<u32>("cases").unwrap();
+        let output_file = matches.get_one::<String>("test-output").unwrap();
         let fix = matches.get_flag("fix");
         let format = matches.get_one::<String>("format").unwrap();
         let output_format = parse_output_format(matches);
@@ -597,11 +1493,11 @@ EXAMPLES:
             );
         }
         if all_structs.is_empty() {
-            println!("{}", "No structs found to analyze".yellow());
+            println!("{}", "No serde types found to analyze".yellow());
             return Ok(());
         }
         if verbose {
-            println!("\n📋 Found {} struct(s):", all_structs.len());
+            println!("\n📋 Found {} serde type(s):", all_structs.len());
             for struct_analysis in &all_structs {
                 let serde_status = if struct_analysis.has_serialize
                     && struct_analysis.has_deserialize
@@ -615,41 +1511,64 @@ EXAMPLES:
                     "❌ No Serde"
                 };
                 println!(
-                    "  • {} - {} field(s) - {}", struct_analysis.name.green(),
-                    struct_analysis.fields.len(), serde_status
+                    "  • {} {} - {} field(s) - {}", struct_analysis.kind(),
+                    struct_analysis.name.green(), struct_analysis.field_count(),
+                    serde_status
                 );
             }
         }
         let serialization_issues = self.validate_serialization(&all_structs);
         let deserialization_issues = self.validate_deserialization(&all_structs);
-        let test_cases = if generate_tests {
-            self.generate_test_cases(&all_structs)
+        let round_trip_issues = self.detect_round_trip_issues(&all_structs, format);
+        let mut test_cases = if generate_tests {
+            self.generate_test_cases(&all_structs, format)
         } else {
             Vec::new()
         };
+        if round_trip {
+            test_cases
+                .extend(
+                    self
+                        .generate_round_trip_tests(
+                            &all_structs,
+                            &round_trip_issues,
+                            format,
+                            cases,
+                        ),
+                );
+        }
         let suggestions = self
             .generate_suggestions(
                 &all_structs,
                 &serialization_issues,
                 &deserialization_issues,
+                &round_trip_issues,
             );
-        let total_fields = all_structs.iter().map(|s| s.fields.len()).sum();
+        let total_fields = all_structs.iter().map(|s| s.field_count()).sum();
         let report = ValidationReport {
             structs_analyzed: all_structs.len(),
             fields_analyzed: total_fields,
             serialization_issues,
             deserialization_issues,
+            round_trip_issues,
             suggestions,
             test_cases_generated: test_cases.clone(),
             timestamp: chrono::Utc::now().to_rfc3339(),
         };
-        if generate_tests && !test_cases.is_empty() {
-            let test_file_content = self.generate_test_file(&test_cases, format);
+        if (generate_tests || round_trip) && !test_cases.is_empty() {
+            let test_file_content = self
+                .generate_test_file(&test_cases, format, &all_structs, round_trip);
             fs::create_dir_all(
                 Path::new(output_file).parent().unwrap_or(Path::new(".")),
             )?;
             fs::write(output_file, test_file_content)?;
             println!("\n✅ Generated test file: {}", output_file);
+            if round_trip {
+                println!(
+                    "   Add {} and {} to [dev-dependencies] to run it", "proptest"
+                    .cyan(), format_crate(format).cyan()
+                );
+            }
         }
         self.display_report(&report, output_format, verbose);
         if fix
@@ -693,17 +1612,42 @@ impl SerdeValidatorTool {
         }
         Ok(())
     }
-    fn generate_test_file(&self, test_cases: &[String], format: &str) -> String {
+    fn generate_test_file(
+        &self,
+        test_cases: &[String],
+        format: &str,
+        types: &[StructAnalysis],
+        round_trip: bool,
+    ) -> String {
         let mut content = format!(
             "//! Auto-generated Serde validation tests
 //! Generated by CargoMate SerdeValidator
 //! Format: {}
+//! Requires dev-dependencies: {}{}
 
 use serde::{{Deserialize, Serialize}};
-
 ",
-            format
+            format, if round_trip { "proptest, " } else { "" }, format_crate(format)
         );
+        if round_trip {
+            content.push_str("use proptest::prelude::*;\nuse proptest::strategy::LazyJust;\n");
+        }
+        let mut imported = HashSet::new();
+        for analysis in types.iter().filter(|t| t.has_derive_serde && !t.has_generics) {
+            if !imported.insert(analysis.name.clone()) {
+                continue;
+            }
+            let crate_root = if analysis.crate_name.is_empty() {
+                "crate"
+            } else {
+                analysis.crate_name.as_str()
+            };
+            let mut path = vec![crate_root.to_string()];
+            path.extend(analysis.module_path.iter().cloned());
+            path.push(analysis.name.clone());
+            content.push_str(&format!("use {};\n", path.join("::")));
+        }
+        content.push('\n');
         for test_case in test_cases {
             content.push_str(test_case);
         }
@@ -714,4 +1658,386 @@ impl Default for SerdeValidatorTool {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}fn parse_serde_attrs(attrs: &[Attribute]) -> Vec<SerdeAttr> {
+    let mut parsed = Vec::new();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        let _ = attr
+            .parse_nested_meta(|meta| {
+                let key = meta
+                    .path
+                    .get_ident()
+                    .map(|ident| ident.to_string())
+                    .unwrap_or_default();
+                let mut value = None;
+                let mut nested = Vec::new();
+                if meta.input.peek(syn::Token![=]) {
+                    let expr: syn::Expr = meta.value()?.parse()?;
+                    value = Some(
+                        match &expr {
+                            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }) => {
+                                s.value()
+                            }
+                            other => quote!(# other).to_string(),
+                        },
+                    );
+                } else if meta.input.peek(syn::token::Paren) {
+                    meta.parse_nested_meta(|inner| {
+                        let inner_key = inner
+                            .path
+                            .get_ident()
+                            .map(|ident| ident.to_string())
+                            .unwrap_or_default();
+                        if inner.input.peek(syn::Token![=]) {
+                            let lit: syn::LitStr = inner.value()?.parse()?;
+                            nested.push((inner_key, lit.value()));
+                        }
+                        Ok(())
+                    })?;
+                }
+                parsed.push(SerdeAttr { key, value, nested });
+                Ok(())
+            });
+    }
+    parsed
+}
+fn rename_pair(attr: &SerdeAttr) -> (Option<String>, Option<String>) {
+    if let Some(value) = &attr.value {
+        return (Some(value.clone()), Some(value.clone()));
+    }
+    let get = |key: &str| {
+        attr.nested.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+    };
+    (get("serialize"), get("deserialize"))
+}
+fn derived_traits(attrs: &[Attribute]) -> Vec<String> {
+    let mut traits = Vec::new();
+    for attr in attrs {
+        if attr.path().is_ident("derive") {
+            let _ = attr
+                .parse_nested_meta(|meta| {
+                    if let Some(segment) = meta.path.segments.last() {
+                        traits.push(segment.ident.to_string());
+                    }
+                    Ok(())
+                });
+        } else if attr.path().is_ident("cfg_attr") {
+            let nested = attr
+                .parse_args_with(
+                    syn::punctuated::Punctuated::<
+                        syn::Meta,
+                        syn::Token![,],
+                    >::parse_terminated,
+                );
+            for meta in nested.into_iter().flatten().skip(1) {
+                if let syn::Meta::List(list) = meta {
+                    if list.path.is_ident("derive") {
+                        let _ = list
+                            .parse_nested_meta(|meta| {
+                                if let Some(segment) = meta.path.segments.last() {
+                                    traits.push(segment.ident.to_string());
+                                }
+                                Ok(())
+                            });
+                    }
+                }
+            }
+        }
+    }
+    traits
+}
+fn outer_type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(type_path) => {
+            type_path.path.segments.last().map(|s| s.ident.to_string())
+        }
+        Type::Reference(reference) => outer_type_name(&reference.elem),
+        Type::Paren(paren) => outer_type_name(&paren.elem),
+        _ => None,
+    }
+}
+fn compact_type(ty: &str) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let chars: Vec<char> = ty.chars().collect();
+    let mut compact = String::new();
+    for (index, c) in chars.iter().enumerate() {
+        if *c != ' ' {
+            compact.push(*c);
+            continue;
+        }
+        let previous = compact.chars().last();
+        let next = chars.get(index + 1).copied();
+        if previous == Some(',') || (previous.is_some_and(is_word) && next.is_some_and(is_word))
+        {
+            compact.push(' ');
+        }
+    }
+    compact
+}
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_uppercase() && index > 0 && !snake.ends_with('_') {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+fn apply_rename_rule(rule: Option<&str>, name: &str, is_variant: bool) -> String {
+    let Some(rule) = rule else {
+        return name.to_string();
+    };
+    let snake = if is_variant { to_snake_case(name) } else { name.to_string() };
+    let pascal: String = snake
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect();
+    match rule {
+        "lowercase" if is_variant => name.to_ascii_lowercase(),
+        "lowercase" => name.to_string(),
+        "UPPERCASE" => name.to_ascii_uppercase(),
+        "PascalCase" if is_variant => name.to_string(),
+        "PascalCase" => pascal,
+        "camelCase" => {
+            let mut chars = pascal.chars();
+            chars
+                .next()
+                .map(|first| first.to_lowercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        "snake_case" => snake,
+        "SCREAMING_SNAKE_CASE" => snake.to_ascii_uppercase(),
+        "kebab-case" => snake.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => snake.to_ascii_uppercase().replace('_', "-"),
+        _ => name.to_string(),
+    }
+}
+fn type_category(ty: &str) -> Option<&'static str> {
+    let parsed = syn::parse_str::<Type>(ty).ok()?;
+    match &parsed {
+        Type::Array(_) | Type::Slice(_) | Type::Tuple(_) => return Some("sequence"),
+        Type::Reference(reference) if outer_type_name(&reference.elem).as_deref()
+            == Some("str") => return Some("string"),
+        _ => {}
+    }
+    match outer_type_name(&parsed)?.as_str() {
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+        | "u128" | "usize" => Some("integer"),
+        "f32" | "f64" => Some("float"),
+        "String" | "str" | "char" | "PathBuf" => Some("string"),
+        "bool" => Some("bool"),
+        "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => Some("sequence"),
+        "HashMap" | "BTreeMap" => Some("map"),
+        "Option" => Some("option"),
+        _ => None,
+    }
+}
+fn variant_signature(variant: &VariantAnalysis) -> VariantSignature {
+    match variant.shape {
+        TypeShape::Unit => VariantSignature::Unit,
+        TypeShape::Tuple if variant.fields.len() == 1 => {
+            let ty = variant.fields[0].ty.clone();
+            VariantSignature::Newtype(type_category(&ty), ty)
+        }
+        TypeShape::Tuple => VariantSignature::Tuple(variant.fields.len()),
+        TypeShape::Named => {
+            VariantSignature::Struct(
+                variant
+                    .fields
+                    .iter()
+                    .filter(|f| !f.skip_serializing)
+                    .map(|f| f.rename.clone().unwrap_or_else(|| f.name.clone()))
+                    .collect(),
+            )
+        }
+    }
+}
+fn signature_shadows(
+    earlier: &VariantSignature,
+    later: &VariantSignature,
+    deny_unknown_fields: bool,
+) -> bool {
+    use VariantSignature::*;
+    match (earlier, later) {
+        (Unit, Unit) => true,
+        (Newtype(Some("option"), _), Unit) => true,
+        (Newtype(earlier_category, earlier_ty), Newtype(later_category, later_ty)) => {
+            earlier_ty == later_ty
+                || (earlier_category.is_some() && earlier_category == later_category)
+                || (*earlier_category == Some("float") && *later_category == Some("integer"))
+        }
+        (Newtype(Some("sequence"), _), Tuple(_)) => true,
+        (Tuple(earlier_len), Tuple(later_len)) => earlier_len == later_len,
+        (Newtype(Some("map"), _), Struct(_)) => true,
+        (Struct(earlier_fields), Struct(later_fields)) => {
+            if deny_unknown_fields {
+                earlier_fields == later_fields
+            } else {
+                earlier_fields.is_subset(later_fields)
+            }
+        }
+        _ => false,
+    }
+}
+fn reaches<'a>(
+    from: &'a str,
+    target: &str,
+    dependencies: &HashMap<&'a str, HashSet<&'a str>>,
+    visited: &mut HashSet<&'a str>,
+) -> bool {
+    let Some(deps) = dependencies.get(from) else {
+        return false;
+    };
+    for dep in deps {
+        if *dep == target {
+            return true;
+        }
+        if visited.insert(dep) && reaches(dep, target, dependencies, visited) {
+            return true;
+        }
+    }
+    false
+}
+fn nest_tuples(strategies: &[String], bindings: &[String]) -> (String, String) {
+    if strategies.len() == 1 {
+        return (strategies[0].clone(), bindings[0].clone());
+    }
+    if strategies.len() <= 12 {
+        return (
+            format!("({})", strategies.join(", ")),
+            format!("({})", bindings.join(", ")),
+        );
+    }
+    let (grouped_strategies, grouped_bindings): (Vec<String>, Vec<String>) = strategies
+        .chunks(10)
+        .zip(bindings.chunks(10))
+        .map(|(s, b)| nest_tuples(s, b))
+        .unzip();
+    nest_tuples(&grouped_strategies, &grouped_bindings)
+}
+fn format_crate(format: &str) -> &'static str {
+    match format {
+        "toml" => "toml",
+        "yaml" => "serde_yaml",
+        "bincode" => "bincode",
+        _ => "serde_json",
+    }
+}
+fn encode_expr(format: &str, value: &str) -> String {
+    match format {
+        "bincode" => format!("bincode::serialize(&{})", value),
+        other => format!("{}::to_string(&{})", format_crate(other), value),
+    }
+}
+fn decode_expr(format: &str, encoded: &str) -> String {
+    match format {
+        "bincode" => format!("bincode::deserialize(&{})", encoded),
+        other => format!("{}::from_str(&{})", format_crate(other), encoded),
+    }
+}
+fn module_path_for(file_path: &Path) -> Vec<String> {
+    let components: Vec<String> = file_path
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let start = components
+        .iter()
+        .rposition(|c| c == "src")
+        .map(|i| i + 1)
+        .unwrap_or(components.len());
+    let mut path = components[start..].to_vec();
+    if matches!(path.last().map(String::as_str), Some("mod" | "lib" | "main")) {
+        path.pop();
+    }
+    path
+}
+fn crate_name_for(file_path: &Path) -> String {
+    let absolute = fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf());
+    for dir in absolute.ancestors().skip(1) {
+        let Ok(manifest) = fs::read_to_string(dir.join("Cargo.toml")) else {
+            continue;
+        };
+        let name = manifest
+            .parse::<toml::Value>()
+            .ok()
+            .and_then(|v| {
+                v.get("package")?.get("name")?.as_str().map(|s| s.replace('-', "_"))
+            });
+        if let Some(name) = name {
+            return name;
+        }
+    }
+    "crate".to_string()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const SOURCE: &str = r#"
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        pub struct Account {
+            pub user_id: u64,
+            #[serde(rename = "userId")]
+            pub legacy_id: u64,
+            #[serde(skip)]
+            pub cache: Vec<u8>,
+            #[serde(skip_serializing_if = "String::is_empty")]
+            pub note: String,
+        }
+        #[derive(Debug, Serialize, Deserialize)]
+        #[serde(untagged)]
+        pub enum Value {
+            Float(f64),
+            Int(i64),
+            Named { id: u32 },
+            Detailed { id: u32, label: String },
+        }
+        #[derive(Debug)]
+        pub struct Plain {
+            pub x: u8,
+        }
+    "#;
+    fn analyze() -> Vec<StructAnalysis> {
+        SerdeValidatorTool::new()
+            .analyze_source(SOURCE, Path::new("src/models.rs"))
+            .unwrap()
+    }
+    #[test]
+    fn test_detects_lossy_and_ambiguous_attributes() {
+        let types = analyze();
+        assert_eq!(types.len(), 2);
+        let issues = SerdeValidatorTool::new().detect_round_trip_issues(&types, "json");
+        let has = |field: &str, kind: &str| {
+            issues.iter().any(|i| i.field_name == field && i.kind == kind)
+        };
+        assert!(has("cache", "lossy"));
+        assert!(has("note", "broken"));
+        assert!(has("legacy_id", "ambiguous"));
+        assert!(has("Int", "ambiguous"));
+        assert!(has("Detailed", "ambiguous"));
+        assert!(! has("Named", "ambiguous"));
+    }
+    #[test]
+    fn test_generates_proptest_round_trips() {
+        let types = analyze();
+        let tool = SerdeValidatorTool::new();
+        let issues = tool.detect_round_trip_issues(&types, "json");
+        let tests = tool.generate_round_trip_tests(&types, &issues, "json", 64);
+        assert_eq!(tests.len(), 2);
+        assert!(tests[0].contains("fn arb_account() -> impl Strategy<Value = Account>"));
+        assert!(tests[0].contains("LazyJust::new(<Vec<u8> as Default>::default)"));
+        assert!(tests[0].contains("prop_assert_eq!(&value, &decoded)"));
+        assert!(tests[1].contains("prop_oneof!["));
+        assert!(tests[1].contains("#[ignore"));
+        let file = tool.generate_test_file(&tests, "json", &types, true);
+        assert!(file.contains("::models::Account;"));
+    }
+}
@@ -63,6 +63,43 @@ struct TraitSuggestion {
     reason: String,
     confidence: f64,
 }
+#[derive(Debug, Clone)]
+struct TypeInfo {
+    name: String,
+    kind: String,
+    is_public: bool,
+    derives: Vec<String>,
+    fields: Vec<(String, String)>,
+    impl_generics: String,
+    ty_generics: String,
+    where_clause: String,
+    file_path: String,
+    line_number: usize,
+}
+#[derive(Debug, Clone, PartialEq)]
+enum ImplStatus {
+    Derived,
+    Implemented,
+    Auto,
+    Missing,
+    Blocked(String),
+}
+#[derive(Debug, Clone)]
+struct MatrixRow {
+    type_info: TypeInfo,
+    cells: Vec<ImplStatus>,
+    gaps: Vec<String>,
+}
+#[derive(Debug, Clone)]
+struct TraitMatrix {
+    traits: Vec<String>,
+    rows: Vec<MatrixRow>,
+}
+const MATRIX_STD_TRAITS: &[&str] = &[
+    "Debug", "Clone", "Copy", "PartialEq", "Eq", "Hash", "PartialOrd", "Ord", "Default",
+    "Display", "Send", "Sync",
+];
+const GAP_TRAITS: &[&str] = &["Debug", "Clone", "Send", "Sync"];
 impl TraitExplorerTool {
     pub fn new() -> Self {
         Self
@@ -589,65 +626,6 @@ impl TraitExplorerTool {
         dot.push_str("}\n");
         Ok(dot)
     }
-    fn suggest_trait_implementations(
-        &self,
-        traits: &[TraitDefinition],
-        implementations: &[TraitImplementation],
-    ) -> Vec<TraitSuggestion> {
-        let mut suggestions = Vec::new();
-        let common_types = vec![
-            "String", "i32", "i64", "bool", "Vec<T>", "HashMap<K, V>", "Option<T>",
-            "Result<T, E>"
-        ];
-        for trait_def in traits {
-            let implementation_count = implementations
-                .iter()
-                .filter(|impl_info| impl_info.trait_name == trait_def.name)
-                .count();
-            if implementation_count > 5 {
-                continue;
-            }
-            for common_type in &common_types {
-                let already_implemented = implementations
-                    .iter()
-                    .any(|impl_info| {
-                        impl_info.trait_name == trait_def.name
-                            && impl_info.target_type == *common_type
-                    });
-                if !already_implemented {
-                    let confidence = if common_type.contains("String")
-                        && trait_def.name.contains("Display")
-                    {
-                        0.9
-                    } else if common_type.contains("Vec")
-                        && trait_def.name.contains("IntoIterator")
-                    {
-                        0.8
-                    } else {
-                        0.5
-                    };
-                    suggestions
-                        .push(TraitSuggestion {
-                            trait_name: trait_def.name.clone(),
-                            target_type: common_type.to_string(),
-                            reason: format!(
-                                "{} would benefit from {} implementation", common_type,
-                                trait_def.name
-                            ),
-                            confidence,
-                        });
-                }
-            }
-        }
-        suggestions
-            .sort_by(|a, b| {
-                b
-                    .confidence
-                    .partial_cmp(&a.confidence)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            });
-        suggestions
-    }
     fn generate_implementations_report(
         &self,
         _traits: &[TraitDefinition],
@@ -668,13 +646,614 @@ impl TraitExplorerTool {
     }
     fn generate_suggestions_report(
         &self,
-        _suggestions: &[TraitSuggestion],
+        suggestions: &[TraitSuggestion],
+        matrix: &TraitMatrix,
     ) -> Result<String> {
-        Err(
-            ToolError::ExecutionFailed(
-                "Suggestions report feature not yet implemented".to_string(),
-            ),
-        )
+        let mut code = String::new();
+        for row in &matrix.rows {
+            if suggestions.iter().any(|s| s.target_type == row.type_info.name) {
+                code.push_str(&self.generate_trait_stubs(row, &matrix.traits));
+                code.push('\n');
+            }
+        }
+        Ok(code)
+    }
+    fn discover_types(&self, source_path: &str) -> Result<Vec<TypeInfo>> {
+        let mut types = Vec::new();
+        self.analyze_directory_for_types(source_path, &mut types)?;
+        Ok(types)
+    }
+    fn analyze_directory_for_types(
+        &self,
+        dir_path: &str,
+        types: &mut Vec<TypeInfo>,
+    ) -> Result<()> {
+        let entries = fs::read_dir(dir_path)
+            .map_err(|e| ToolError::ExecutionFailed(
+                format!("Failed to read directory {}: {}", dir_path, e),
+            ))?;
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            if path.is_dir() {
+                self.analyze_directory_for_types(&path.to_string_lossy(), types)?;
+            } else if path.extension().map(|ext| ext == "rs").unwrap_or(false) {
+                self.analyze_file_for_types(&path, types)?;
+            }
+        }
+        Ok(())
+    }
+    fn analyze_file_for_types(&self, file_path: &Path, types: &mut Vec<TypeInfo>) -> Result<()> {
+        let content = fs::read_to_string(file_path)?;
+        let ast = parse_file(&content)?;
+        struct TypeVisitor<'a> {
+            types: &'a mut Vec<TypeInfo>,
+            current_file: String,
+        }
+        impl TypeVisitor<'_> {
+            fn push(
+                &mut self,
+                ident: &syn::Ident,
+                kind: &str,
+                vis: &syn::Visibility,
+                attrs: &[syn::Attribute],
+                generics: &syn::Generics,
+                fields: Vec<(String, String)>,
+            ) {
+                let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+                self.types
+                    .push(TypeInfo {
+                        name: ident.to_string(),
+                        kind: kind.to_string(),
+                        is_public: matches!(vis, syn::Visibility::Public(_)),
+                        derives: Self::extract_derives(attrs),
+                        fields,
+                        impl_generics: Self::tidy(quote!(# impl_generics)),
+                        ty_generics: Self::tidy(quote!(# ty_generics)),
+                        where_clause: Self::tidy(quote!(# where_clause)),
+                        file_path: self.current_file.clone(),
+                        line_number: ident.span().start().line,
+                    });
+            }
+            fn tidy(tokens: TokenStream) -> String {
+                tokens
+                    .to_string()
+                    .replace(" < ", "<")
+                    .replace("< ", "<")
+                    .replace(" >", ">")
+                    .replace(" :: ", "::")
+                    .replace(" , ", ", ")
+                    .replace(" : ", ": ")
+            }
+            fn extract_derives(attrs: &[syn::Attribute]) -> Vec<String> {
+                let mut derives = Vec::new();
+                for attr in attrs {
+                    let tokens = if attr.path().is_ident("derive") {
+                        attr.meta.require_list().map(|list| list.tokens.clone()).ok()
+                    } else if attr.path().is_ident("cfg_attr") {
+                        attr.meta.require_list().map(|list| list.tokens.clone()).ok()
+                    } else {
+                        None
+                    };
+                    let Some(tokens) = tokens else { continue };
+                    let text = tokens.to_string();
+                    let list = if attr.path().is_ident("derive") {
+                        Some(text.as_str())
+                    } else {
+                        text.find("derive").map(|i| &text[i + "derive".len()..])
+                    };
+                    if let Some(list) = list {
+                        derives
+                            .extend(
+                                list
+                                    .split(|c: char| !c.is_alphanumeric() && c != '_' && c != ':')
+                                    .filter(|s| !s.is_empty())
+                                    .filter_map(|s| s.rsplit("::").next())
+                                    .map(|s| s.to_string()),
+                            );
+                    }
+                }
+                derives
+            }
+            fn extract_fields(fields: &syn::Fields) -> Vec<(String, String)> {
+                fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| {
+                        let name = field
+                            .ident
+                            .as_ref()
+                            .map(|ident| ident.to_string())
+                            .unwrap_or_else(|| i.to_string());
+                        let ty = &field.ty;
+                        (name, quote!(# ty).to_string())
+                    })
+                    .collect()
+            }
+        }
+        impl<'a> Visit<'_> for TypeVisitor<'a> {
+            fn visit_item_struct(&mut self, node: &syn::ItemStruct) {
+                let fields = Self::extract_fields(&node.fields);
+                self.push(&node.ident, "struct", &node.vis, &node.attrs, &node.generics, fields);
+            }
+            fn visit_item_enum(&mut self, node: &syn::ItemEnum) {
+                let fields = node
+                    .variants
+                    .iter()
+                    .flat_map(|variant| {
+                        Self::extract_fields(&variant.fields)
+                            .into_iter()
+                            .map(move |(name, ty)| (format!("{}.{}", variant.ident, name), ty))
+                    })
+                    .collect();
+                self.push(&node.ident, "enum", &node.vis, &node.attrs, &node.generics, fields);
+            }
+        }
+        let mut visitor = TypeVisitor {
+            types,
+            current_file: file_path.to_string_lossy().to_string(),
+        };
+        syn::visit::visit_file(&mut visitor, &ast);
+        Ok(())
+    }
+    fn type_idents(ty: &str) -> Vec<&str> {
+        ty.split(|c: char| !c.is_alphanumeric() && c != '_').filter(|s| !s.is_empty()).collect()
+    }
+    fn auto_trait_blocker(trait_name: &str, ty: &str) -> Option<String> {
+        if ty.contains("* const") || ty.contains("* mut") {
+            return Some("a raw pointer".to_string());
+        }
+        let idents = Self::type_idents(ty);
+        let blockers: &[(&str, &str)] = if trait_name == "Send" {
+            &[("Rc", "use `Arc`"), ("NonNull", "wrap it in a type with a safety argument")]
+        } else {
+            &[
+                ("Rc", "use `Arc`"),
+                ("NonNull", "wrap it in a type with a safety argument"),
+                ("RefCell", "use `Mutex` or `RwLock`"),
+                ("Cell", "use an atomic or `Mutex`"),
+                ("UnsafeCell", "guard it with a lock"),
+                ("Receiver", "wrap it in a `Mutex`"),
+            ]
+        };
+        blockers
+            .iter()
+            .find(|(marker, _)| idents.contains(marker))
+            .map(|(marker, fix)| format!("`{}` ({})", marker, fix))
+    }
+    fn derive_blocker(trait_name: &str, ty: &str) -> Option<String> {
+        let idents = Self::type_idents(ty);
+        let shared = idents.contains(&"Arc") || idents.contains(&"Rc");
+        match trait_name {
+            "Debug" if idents.contains(&"dyn") && !idents.contains(&"Debug") => {
+                Some("a trait object without a Debug bound".to_string())
+            }
+            "Clone" if idents.contains(&"dyn") && !shared => {
+                Some("an owned trait object".to_string())
+            }
+            "Clone" if !shared => {
+                idents
+                    .iter()
+                    .find(|ident| {
+                        ["Mutex", "RwLock", "File", "TcpStream", "TcpListener", "Receiver", "JoinHandle"]
+                            .contains(ident) || ident.starts_with("Atomic")
+                    })
+                    .map(|ident| format!("`{}`", ident))
+            }
+            _ => None,
+        }
+    }
+    fn build_trait_matrix(
+        &self,
+        types: &[TypeInfo],
+        traits: &[TraitDefinition],
+        implementations: &[TraitImplementation],
+        specific_trait: Option<&String>,
+    ) -> TraitMatrix {
+        let short = |path: &str| path.rsplit("::").next().unwrap_or(path).to_string();
+        let implemented: std::collections::HashSet<(String, String)> = implementations
+            .iter()
+            .map(|i| (short(&i.target_type), short(&i.trait_name)))
+            .collect();
+        let mut columns: Vec<String> = MATRIX_STD_TRAITS.iter().map(|t| t.to_string()).collect();
+        for trait_def in traits {
+            let used = implementations.iter().any(|i| short(&i.trait_name) == trait_def.name);
+            if used && !columns.contains(&trait_def.name) {
+                columns.push(trait_def.name.clone());
+            }
+        }
+        if let Some(name) = specific_trait {
+            columns.retain(|c| c == name);
+        }
+        let mut blocked: HashMap<(String, String), String> = HashMap::new();
+        for auto_trait in ["Send", "Sync"] {
+            loop {
+                let mut changed = false;
+                for ty in types {
+                    let key = (ty.name.clone(), auto_trait.to_string());
+                    if blocked.contains_key(&key)
+                        || implemented.contains(&(ty.name.clone(), auto_trait.to_string()))
+                    {
+                        continue;
+                    }
+                    let reason = ty
+                        .fields
+                        .iter()
+                        .find_map(|(field, field_ty)| {
+                            Self::auto_trait_blocker(auto_trait, field_ty)
+                                .or_else(|| {
+                                    Self::type_idents(field_ty)
+                                        .into_iter()
+                                        .find(|ident| {
+                                            *ident != ty.name
+                                                && blocked
+                                                    .contains_key(
+                                                        &(ident.to_string(), auto_trait.to_string()),
+                                                    )
+                                        })
+                                        .map(|ident| format!("`{}`", ident))
+                                })
+                                .map(|reason| format!("field `{}` holds {}", field, reason))
+                        });
+                    if let Some(reason) = reason {
+                        blocked.insert(key, reason);
+                        changed = true;
+                    }
+                }
+                if !changed {
+                    break;
+                }
+            }
+        }
+        let mut rows: Vec<MatrixRow> = types
+            .iter()
+            .map(|ty| {
+                let cells: Vec<ImplStatus> = columns
+                    .iter()
+                    .map(|column| {
+                        if ty.derives.contains(column) {
+                            ImplStatus::Derived
+                        } else if implemented.contains(&(ty.name.clone(), column.clone())) {
+                            ImplStatus::Implemented
+                        } else if let Some(reason) = blocked.get(&(ty.name.clone(), column.clone())) {
+                            ImplStatus::Blocked(reason.clone())
+                        } else if column == "Send" || column == "Sync" {
+                            ImplStatus::Auto
+                        } else {
+                            ImplStatus::Missing
+                        }
+                    })
+                    .collect();
+                let gaps = if ty.is_public {
+                    columns
+                        .iter()
+                        .zip(&cells)
+                        .filter(|(column, cell)| {
+                            GAP_TRAITS.contains(&column.as_str())
+                                && matches!(cell, ImplStatus::Missing | ImplStatus::Blocked(_))
+                        })
+                        .map(|(column, _)| column.clone())
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                MatrixRow {
+                    type_info: ty.clone(),
+                    cells,
+                    gaps,
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            b.type_info
+                .is_public
+                .cmp(&a.type_info.is_public)
+                .then(a.type_info.name.cmp(&b.type_info.name))
+        });
+        TraitMatrix { traits: columns, rows }
+    }
+    fn status_symbol(status: &ImplStatus) -> &'static str {
+        match status {
+            ImplStatus::Derived => "D",
+            ImplStatus::Implemented => "I",
+            ImplStatus::Auto => "A",
+            ImplStatus::Missing => "-",
+            ImplStatus::Blocked(_) => "✗",
+        }
+    }
+    fn generate_matrix_markdown(&self, matrix: &TraitMatrix) -> String {
+        let mut md = String::from("# Trait Coverage Matrix\n\n");
+        md.push_str(
+            "`D` derived · `I` manual impl · `A` auto trait · `✗` blocked by a field · `-` missing · **bold** = gap on a public type\n\n",
+        );
+        md.push_str(&format!("| Type | {} |\n", matrix.traits.join(" | ")));
+        md.push_str(&format!("|---|{}\n", "---|".repeat(matrix.traits.len())));
+        for row in &matrix.rows {
+            let cells: Vec<String> = matrix
+                .traits
+                .iter()
+                .zip(&row.cells)
+                .map(|(column, cell)| {
+                    if row.gaps.contains(column) {
+                        format!("**{}**", Self::status_symbol(cell))
+                    } else {
+                        Self::status_symbol(cell).to_string()
+                    }
+                })
+                .collect();
+            let visibility = if row.type_info.is_public { "pub " } else { "" };
+            md.push_str(
+                &format!(
+                    "| {}`{}` | {} |\n", visibility, row.type_info.name, cells.join(" | ")
+                ),
+            );
+        }
+        let gaps: Vec<&MatrixRow> = matrix.rows.iter().filter(|r| !r.gaps.is_empty()).collect();
+        if !gaps.is_empty() {
+            md.push_str("\n## Gaps\n\n");
+            for row in gaps {
+                md.push_str(
+                    &format!(
+                        "- `{}` ({}:{}): missing {}\n", row.type_info.name, row.type_info
+                        .file_path, row.type_info.line_number, row.gaps.join(", ")
+                    ),
+                );
+            }
+        }
+        md
+    }
+    fn print_matrix(&self, matrix: &TraitMatrix) {
+        let name_width = matrix
+            .rows
+            .iter()
+            .map(|row| row.type_info.name.len())
+            .max()
+            .unwrap_or(4)
+            .max(4);
+        let header: Vec<String> = matrix.traits.iter().map(|t| format!("{:^9}", t.chars().take(9).collect::<String>())).collect();
+        println!("  {:<width$} {}", "Type".bold(), header.join("").bold(), width = name_width);
+        println!("  {}", "─".repeat(name_width + 1 + 9 * matrix.traits.len()));
+        for row in &matrix.rows {
+            let cells: Vec<String> = matrix
+                .traits
+                .iter()
+                .zip(&row.cells)
+                .map(|(column, cell)| {
+                    let symbol = format!("{:^9}", Self::status_symbol(cell));
+                    if row.gaps.contains(column) {
+                        symbol.red().bold().to_string()
+                    } else {
+                        match cell {
+                            ImplStatus::Derived | ImplStatus::Implemented => symbol.green().to_string(),
+                            ImplStatus::Auto => symbol.dimmed().to_string(),
+                            _ => symbol.yellow().to_string(),
+                        }
+                    }
+                })
+                .collect();
+            let name = format!("{:<width$}", row.type_info.name, width = name_width);
+            let name = if row.type_info.is_public { name.bold().to_string() } else { name };
+            println!("  {} {}", name, cells.join(""));
+        }
+    }
+    fn generate_trait_stubs(&self, row: &MatrixRow, traits: &[String]) -> String {
+        let ty = &row.type_info;
+        let target = format!("{}{}", ty.name, ty.ty_generics);
+        let where_clause = if ty.where_clause.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", ty.where_clause)
+        };
+        let mut derives = Vec::new();
+        let mut code = String::new();
+        for gap in &row.gaps {
+            let cell = traits.iter().position(|t| t == gap).map(|i| &row.cells[i]);
+            match gap.as_str() {
+                "Send" | "Sync" => {
+                    if let Some(ImplStatus::Blocked(reason)) = cell {
+                        code.push_str(&format!("// not {}: {}\n", gap, reason));
+                    }
+                }
+                _ => {
+                    let blocker = ty
+                        .fields
+                        .iter()
+                        .find_map(|(field, field_ty)| {
+                            Self::derive_blocker(gap, field_ty)
+                                .map(|reason| (field.clone(), reason))
+                        });
+                    match blocker {
+                        None => derives.push(gap.clone()),
+                        Some((field, reason)) if gap == "Debug" => {
+                            code.push_str(
+                                &format!(
+                                    "// field `{}` holds {}, so Debug is written by hand\n", field,
+                                    reason
+                                ),
+                            );
+                            code.push_str(
+                                &format!(
+                                    "impl{} std::fmt::Debug for {}{} {{\n", ty
+                                    .impl_generics, target, where_clause
+                                ),
+                            );
+                            code.push_str(
+                                "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n",
+                            );
+                            code.push_str(&format!("        f.debug_struct(\"{}\")", ty.name));
+                            if ty.kind == "struct" {
+                                for (name, field_ty) in &ty.fields {
+                                    if Self::derive_blocker("Debug", field_ty).is_none()
+                                        && name.parse::<usize>().is_err()
+                                    {
+                                        code.push_str(&format!(".field(\"{0}\", &self.{0})", name));
+                                    }
+                                }
+                            }
+                            code.push_str(".finish_non_exhaustive()\n    }\n}\n");
+                        }
+                        Some((field, reason)) => {
+                            code.push_str(
+                                &format!(
+                                    "// field `{}` holds {}, so Clone is written by hand\n", field,
+                                    reason
+                                ),
+                            );
+                            code.push_str(
+                                &format!(
+                                    "impl{} Clone for {}{} {{\n", ty.impl_generics, target,
+                                    where_clause
+                                ),
+                            );
+                            code.push_str("    fn clone(&self) -> Self {\n");
+                            code.push_str(
+                                &format!("        todo!(\"clone `{}` field by field\")\n", ty.name),
+                            );
+                            code.push_str("    }\n}\n");
+                        }
+                    }
+                }
+            }
+        }
+        let mut header = format!("// {} ({}:{})\n", ty.name, ty.file_path, ty.line_number);
+        if !derives.is_empty() {
+            header.push_str(&format!("#[derive({})]\n", derives.join(", ")));
+        }
+        code.insert_str(0, &header);
+        let auto: Vec<&String> = row.gaps.iter().filter(|g| *g == "Send" || *g == "Sync").collect();
+        if !auto.is_empty() && ty.ty_generics.is_empty() {
+            let bounds = auto.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(" + ");
+            code.push_str("const _: () = {\n");
+            code.push_str(&format!("    fn assert_auto_traits<T: {}>() {{}}\n", bounds));
+            code.push_str(&format!("    fn check() {{\n        assert_auto_traits::<{}>();\n    }}\n", ty.name));
+            code.push_str("};\n");
+        }
+        code
+    }
+    fn suggest_from_matrix(&self, matrix: &TraitMatrix) -> Vec<TraitSuggestion> {
+        let mut suggestions = Vec::new();
+        for row in &matrix.rows {
+            for gap in &row.gaps {
+                let cell = matrix.traits.iter().position(|t| t == gap).map(|i| &row.cells[i]);
+                let (reason, confidence) = match cell {
+                    Some(ImplStatus::Blocked(reason)) => (format!("not {}: {}", gap, reason), 0.5),
+                    _ => (format!("public type without {}", gap), 0.9),
+                };
+                suggestions
+                    .push(TraitSuggestion {
+                        trait_name: gap.clone(),
+                        target_type: row.type_info.name.clone(),
+                        reason,
+                        confidence,
+                    });
+            }
+        }
+        suggestions
+    }
+    fn run_matrix(
+        &self,
+        matches: &ArgMatches,
+        traits: &[TraitDefinition],
+        implementations: &[TraitImplementation],
+    ) -> Result<()> {
+        let input = matches.get_one::<String>("input").unwrap();
+        let specific_trait = matches.get_one::<String>("trait");
+        let format = matches.get_one::<String>("format").unwrap();
+        let output = matches.get_one::<String>("analysis-output").unwrap();
+        let suggest = matches.get_flag("suggest");
+        let dry_run = matches.get_flag("dry-run");
+        let verbose = matches.get_flag("verbose");
+        let output_format = parse_output_format(matches);
+        let types = self.discover_types(input)?;
+        let matrix = self.build_trait_matrix(&types, traits, implementations, specific_trait);
+        let suggestions = if suggest { self.suggest_from_matrix(&matrix) } else { Vec::new() };
+        let stubs = if suggest {
+            self.generate_suggestions_report(&suggestions, &matrix)?
+        } else {
+            String::new()
+        };
+        let gap_rows: Vec<&MatrixRow> = matrix.rows.iter().filter(|r| !r.gaps.is_empty()).collect();
+        let matrix_json = serde_json::json!(
+            { "traits" : matrix.traits, "types" : matrix.rows.iter().map(| row | {
+            let cells : serde_json::Map < String, serde_json::Value > = matrix.traits
+            .iter().zip(& row.cells).map(| (t, c) | (t.clone(), match c {
+            ImplStatus::Derived => "derived".into(), ImplStatus::Implemented => "impl"
+            .into(), ImplStatus::Auto => "auto".into(), ImplStatus::Missing => "missing"
+            .into(), ImplStatus::Blocked(reason) => serde_json::json!({ "blocked" :
+            reason }), })).collect(); serde_json::json!({ "name" : row.type_info.name,
+            "public" : row.type_info.is_public, "file" : row.type_info.file_path, "line" :
+            row.type_info.line_number, "traits" : cells, "gaps" : row.gaps, }) }).collect::<
+            Vec < _ >> () }
+        );
+        let mut content = match format.as_str() {
+            "json" => serde_json::to_string_pretty(&matrix_json).unwrap(),
+            _ => self.generate_matrix_markdown(&matrix),
+        };
+        if suggest && format != "json" {
+            content.push_str("\n## Suggested stubs\n\n```rust\n");
+            content.push_str(&stubs);
+            content.push_str("```\n");
+        }
+        match output_format {
+            OutputFormat::Human => {
+                self.print_matrix(&matrix);
+                println!(
+                    "\n  D derived · I impl · A auto · ✗ blocked · - missing · {} = gap on a public type",
+                    "red".red().bold()
+                );
+                if gap_rows.is_empty() {
+                    println!("  ✅ Every public type implements Debug, Clone, Send and Sync");
+                } else {
+                    println!("\n  ⚠️  {} public types have gaps:", gap_rows.len());
+                    for row in &gap_rows {
+                        println!(
+                            "     • {} missing {}", row.type_info.name.bold(), row.gaps
+                            .join(", ").yellow()
+                        );
+                        if verbose {
+                            for (column, cell) in matrix.traits.iter().zip(&row.cells) {
+                                if let ImplStatus::Blocked(reason) = cell {
+                                    println!("         {}: {}", column, reason.dimmed());
+                                }
+                            }
+                        }
+                    }
+                }
+                if suggest {
+                    println!("\n  💡 {}", "Suggested stubs:".bold());
+                    for line in stubs.lines() {
+                        println!("     {}", line);
+                    }
+                }
+                if !dry_run {
+                    fs::write(output, &content)
+                        .map_err(|e| ToolError::ExecutionFailed(
+                            format!("Failed to write {}: {}", output, e),
+                        ))?;
+                    println!("\n  💾 Matrix written to {}", output.cyan());
+                }
+            }
+            OutputFormat::Json => {
+                let result = serde_json::json!(
+                    { "matrix" : matrix_json, "gaps" : gap_rows.len(), "suggestions" :
+                    suggestions.iter().map(| s | serde_json::json!({ "trait" : s
+                    .trait_name, "type" : s.target_type, "reason" : s.reason, "confidence"
+                    : s.confidence })).collect::< Vec < _ >> (), "stubs" : stubs }
+                );
+                println!("{}", serde_json::to_string_pretty(& result).unwrap());
+            }
+            OutputFormat::Table => {
+                println!("{:<25} {:<8} {:<40}", "Type", "Public", "Gaps");
+                println!("{}", "─".repeat(75));
+                for row in &matrix.rows {
+                    println!(
+                        "{:<25} {:<8} {:<40}", row.type_info.name, if row.type_info
+                        .is_public { "yes" } else { "no" }, row.gaps.join(", ")
+                    );
+                }
+            }
+        }
+        Ok(())
     }
 }
 impl Tool for TraitExplorerTool {
@@ -688,7 +1267,7 @@ impl Tool for TraitExplorerTool {
         Command::new(self.name())
             .about(self.description())
             .long_about(
-                "Discover all trait implementations in your codebase, analyze usage patterns, find missing implementations, and generate comprehensive trait documentation.",
+                "Discover all trait implementations in your codebase, analyze usage patterns, find missing implementations, and generate comprehensive trait documentation. --matrix shows which standard and crate traits each type implements and highlights public types missing Debug, Clone, Send or Sync; --suggest emits derive/impl stubs for those gaps.",
             )
             .args(
                 &[
@@ -715,21 +1294,23 @@ impl Tool for TraitExplorerTool {
                         .action(clap::ArgAction::SetTrue),
                     Arg::new("visualize")
                         .long("visualize")
-                        .short('v')
                         .help("Generate trait relationship visualization")
                         .action(clap::ArgAction::SetTrue),
                     Arg::new("format")
                         .long("format")
                         .help("Output format: markdown, json, mermaid, dot")
                         .default_value("markdown"),
-                    Arg::new("output")
-                        .long("output")
-                        .short('o')
+                    Arg::new("analysis-output")
+                        .long("analysis-output")
                         .help("Output file for analysis")
                         .default_value("trait-analysis.md"),
                     Arg::new("suggest")
                         .long("suggest")
-                        .help("Suggest additional trait implementations")
+                        .help("Generate derive/impl stubs for traits missing on public types")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("matrix")
+                        .long("matrix")
+                        .help("Show a types × traits impl coverage matrix")
                         .action(clap::ArgAction::SetTrue),
                     Arg::new("workspace")
                         .long("workspace")
@@ -747,7 +1328,7 @@ impl Tool for TraitExplorerTool {
         let usage_analysis = matches.get_flag("usage");
         let visualize = matches.get_flag("visualize");
         let format = matches.get_one::<String>("format").unwrap();
-        let output = matches.get_one::<String>("output").unwrap();
+        let output = matches.get_one::<String>("analysis-output").unwrap();
         let suggest = matches.get_flag("suggest");
         let workspace = matches.get_flag("workspace");
         let dry_run = matches.get_flag("dry-run");
@@ -775,6 +1356,9 @@ impl Tool for TraitExplorerTool {
                 "   📋 Found {} trait implementations", trait_implementations.len()
             );
         }
+        if matches.get_flag("matrix") || suggest {
+            return self.run_matrix(matches, &trait_definitions, &trait_implementations);
+        }
         let (filtered_definitions, filtered_implementations) = if let Some(trait_name) = specific_trait {
             let defs: Vec<TraitDefinition> = trait_definitions
                 .into_iter()
@@ -798,14 +1382,6 @@ impl Tool for TraitExplorerTool {
                 &filtered_definitions,
                 &filtered_implementations,
             );
-        let suggestions = if suggest {
-            self.suggest_trait_implementations(
-                &filtered_definitions,
-                &filtered_implementations,
-            )
-        } else {
-            Vec::new()
-        };
         let mut output_content = String::new();
         if implementations_only {
             output_content = self
@@ -822,8 +1398,6 @@ impl Tool for TraitExplorerTool {
                     &filtered_implementations,
                     format,
                 )?;
-        } else if suggest {
-            output_content = self.generate_suggestions_report(&suggestions)?;
         } else {
             output_content = self
                 .generate_trait_documentation(
@@ -847,11 +1421,6 @@ impl Tool for TraitExplorerTool {
                     );
                 } else if visualize {
                     println!("  📊 Generated {} visualization", format);
-                } else if suggest {
-                    println!(
-                        "  💡 Generated {} implementation suggestions", suggestions
-                        .len()
-                    );
                 }
                 if dry_run {
                     println!("   📋 {}", "Analysis preview:".bold());
@@ -881,24 +1450,22 @@ impl Tool for TraitExplorerTool {
                     { "traits_analyzed" : filtered_definitions.len(),
                     "implementations_found" : filtered_implementations.len(),
                     "missing_implementations" : missing_implementations.len(),
-                    "suggestions_count" : suggestions.len(), "analysis_content" :
-                    output_content }
+                    "analysis_content" : output_content }
                 );
                 println!("{}", serde_json::to_string_pretty(& result).unwrap());
             }
             OutputFormat::Table => {
                 println!(
-                    "{:<25} {:<15} {:<12} {:<10} {:<12}", "Analysis Type", "Traits",
-                    "Impls", "Missing", "Suggestions"
+                    "{:<25} {:<15} {:<12} {:<10}", "Analysis Type", "Traits", "Impls",
+                    "Missing"
                 );
                 println!("{}", "─".repeat(80));
                 println!(
-                    "{:<25} {:<15} {:<12} {:<10} {:<12}", if implementations_only {
+                    "{:<25} {:<15} {:<12} {:<10}", if implementations_only {
                     "Implementations" } else if missing_only { "Missing" } else if
-                    visualize { "Visualization" } else if suggest { "Suggestions" } else
-                    { "Full Analysis" }, filtered_definitions.len(),
-                    filtered_implementations.len(), missing_implementations.len(),
-                    suggestions.len()
+                    visualize { "Visualization" } else { "Full Analysis" },
+                    filtered_definitions.len(), filtered_implementations.len(),
+                    missing_implementations.len()
                 );
             }
         }
@@ -910,4 +1477,41 @@ impl Default for TraitExplorerTool {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_matrix_flags_gaps_on_public_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        fs::write(
+                &file,
+                "#[derive(Debug, Clone)]\npub struct Good(u8);\npub struct Shared { cache: std::rc::Rc<String> }\nstruct Hidden;\n",
+            )
+            .unwrap();
+        let tool = TraitExplorerTool::new();
+        let types = tool.discover_types(&dir.path().to_string_lossy()).unwrap();
+        let matrix = tool.build_trait_matrix(&types, &[], &[], None);
+        let gaps = |name: &str| {
+            matrix
+                .rows
+                .iter()
+                .find(|r| r.type_info.name == name)
+                .unwrap()
+                .gaps
+                .clone()
+        };
+        assert!(gaps("Good").is_empty());
+        assert_eq!(gaps("Shared"), vec!["Debug", "Clone", "Send", "Sync"]);
+        assert!(gaps("Hidden").is_empty());
+        let shared = matrix
+            .rows
+            .iter()
+            .find(|r| r.type_info.name == "Shared")
+            .unwrap();
+        let stubs = tool.generate_trait_stubs(shared, &matrix.traits);
+        assert!(stubs.contains("#[derive(Debug, Clone)]"));
+        assert!(stubs.contains("assert_auto_traits::<Shared>()"));
+    }
+}
@@ -0,0 +1,397 @@
+use super::{Tool, Result, ToolError, common_options, parse_output_format, OutputFormat};
+use crate::captain::config::ConfigManager;
+use crate::lockdiff::{self, LockPackage};
+use chrono::{DateTime, Utc};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::time::Duration;
+const API: &str = "https://crates.io/api/v1/crates";
+const GITHUB_API: &str = "https://api.github.com/repos";
+/// Answers are cached for a day; trust signals move slowly
+const CACHE_TTL: Duration = Duration::from_secs(24 * 3600);
+/// crates.io asks crawlers for at most one request per second
+const REQUEST_DELAY: Duration = Duration::from_millis(1000);
+#[derive(Debug, Clone)]
+pub struct TrustScoreTool;
+/// Thresholds from `[trust]` in .cg
+#[derive(Debug, Clone, Serialize)]
+struct Policy {
+    min_score: u32,
+    min_downloads: u64,
+    min_age_days: i64,
+    min_owners: usize,
+    allow: Vec<String>,
+}
+impl Default for Policy {
+    fn default() -> Self {
+        Self { min_score: 50, min_downloads: 10_000, min_age_days: 90, min_owners: 1, allow: Vec::new() }
+    }
+}
+impl Policy {
+    fn load() -> Self {
+        let mut policy = Self::default();
+        let Ok(config) = ConfigManager::new() else {
+            return policy;
+        };
+        let number = |key: &str| config.get(key).and_then(|v| v.trim().parse::<i64>().ok());
+        if let Some(v) = number("trust.min_score") {
+            policy.min_score = v.max(0) as u32;
+        }
+        if let Some(v) = number("trust.min_downloads") {
+            policy.min_downloads = v.max(0) as u64;
+        }
+        if let Some(v) = number("trust.min_age_days") {
+            policy.min_age_days = v;
+        }
+        if let Some(v) = number("trust.min_owners") {
+            policy.min_owners = v.max(0) as usize;
+        }
+        policy.allow = config.get_list("trust.allow");
+        policy
+    }
+    /// Allow-listed crates pass whatever they score
+    fn passes(&self, name: &str, score: u32) -> bool {
+        score >= self.min_score || self.allow.iter().any(|allowed| allowed == name)
+    }
+}
+/// What crates.io and the repository host say about one crate
+#[derive(Debug, Clone, Default, Serialize)]
+struct Signals {
+    downloads: u64,
+    recent_downloads: u64,
+    created_at: Option<DateTime<Utc>>,
+    last_release: Option<DateTime<Utc>>,
+    owners: usize,
+    repository: Option<String>,
+    archived: Option<bool>,
+    versions: usize,
+    yanked_versions: usize,
+    locked_yanked: bool,
+}
+#[derive(Debug, Clone, Serialize)]
+struct TrustReport {
+    name: String,
+    version: String,
+    score: u32,
+    signals: Signals,
+    findings: Vec<String>,
+    passed: bool,
+}
+fn parse_time(value: &Value) -> Option<DateTime<Utc>> {
+    value.as_str().and_then(|s| DateTime::parse_from_rfc3339(s).ok()).map(|t| t.with_timezone(&Utc))
+}
+fn signals_from_api(info: &Value, owners: &Value, locked_version: &str) -> Signals {
+    let versions = info["versions"].as_array().cloned().unwrap_or_default();
+    let yanked = |v: &Value| v["yanked"].as_bool().unwrap_or(false);
+    Signals {
+        downloads: info["crate"]["downloads"].as_u64().unwrap_or(0),
+        recent_downloads: info["crate"]["recent_downloads"].as_u64().unwrap_or(0),
+        created_at: parse_time(&info["crate"]["created_at"]),
+        last_release: versions.iter().filter(|v| !yanked(v)).filter_map(|v| parse_time(&v["created_at"])).max(),
+        owners: owners["users"].as_array().map_or(0, Vec::len),
+        repository: info["crate"]["repository"].as_str().filter(|r| !r.is_empty()).map(str::to_string),
+        archived: None,
+        versions: versions.len(),
+        yanked_versions: versions.iter().filter(|v| yanked(v)).count(),
+        locked_yanked: versions.iter().any(|v| v["num"].as_str() == Some(locked_version) && yanked(v)),
+    }
+}
+/// 0-100: downloads 25, age 15, release recency 20, owners 15, repository 15, yank history 10
+fn score(signals: &Signals, policy: &Policy, now: DateTime<Utc>) -> (u32, Vec<String>) {
+    let mut findings = Vec::new();
+    let days = |t: Option<DateTime<Utc>>| t.map(|t| (now - t).num_days());
+    let mut total = 0.0;
+    total += (25.0 * (signals.downloads.max(1) as f64).log10() / 7.0).min(25.0);
+    if signals.downloads < policy.min_downloads {
+        findings.push(format!("only {} downloads (policy: {})", signals.downloads, policy.min_downloads));
+    }
+    match days(signals.created_at) {
+        Some(age) => {
+            total += 15.0 * (age as f64 / 730.0).min(1.0);
+            if age < policy.min_age_days {
+                findings.push(format!("first published {} day(s) ago (policy: {})", age, policy.min_age_days));
+            }
+        }
+        None => findings.push("publication date unknown".to_string()),
+    }
+    match days(signals.last_release) {
+        Some(since) if since <= 365 => total += 20.0,
+        Some(since) if since <= 730 => total += 10.0,
+        Some(since) => findings.push(format!("no release in {} day(s)", since)),
+        None => {}
+    }
+    total += match signals.owners {
+        0 => 0.0,
+        1 => 5.0,
+        _ => 15.0,
+    };
+    if signals.owners < policy.min_owners {
+        findings.push(format!("{} owner(s) (policy: {})", signals.owners, policy.min_owners));
+    }
+    match (&signals.repository, signals.archived) {
+        (None, _) => findings.push("no repository link".to_string()),
+        (Some(_), Some(true)) => findings.push("repository is archived".to_string()),
+        (Some(_), _) => total += 15.0,
+    }
+    if signals.versions > 0 {
+        let ratio = signals.yanked_versions as f64 / signals.versions as f64;
+        total += 10.0 * (1.0 - 2.0 * ratio).max(0.0);
+    }
+    if signals.locked_yanked {
+        total -= 30.0;
+        findings.push("the locked version is yanked".to_string());
+    }
+    (total.clamp(0.0, 100.0).round() as u32, findings)
+}
+/// `owner/repo` for GitHub repository links
+fn github_slug(repository: &str) -> Option<String> {
+    let rest = repository.split_once("github.com/")?.1;
+    let mut parts = rest.trim_end_matches('/').trim_end_matches(".git").split('/');
+    let (owner, repo) = (parts.next()?, parts.next()?);
+    (!owner.is_empty() && !repo.is_empty()).then(|| format!("{}/{}", owner, repo.trim_end_matches(".git")))
+}
+fn fetch(url: &str, github: bool) -> anyhow::Result<Value> {
+    let body = crate::net::cached(url, CACHE_TTL, || {
+        std::thread::sleep(REQUEST_DELAY);
+        let mut request = crate::outdated::client()?.get(url);
+        if github {
+            if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+                request = request.bearer_auth(token);
+            }
+        }
+        let response = crate::net::send(request)?;
+        if !response.status().is_success() {
+            anyhow::bail!("{} returned HTTP {}", url, response.status());
+        }
+        Ok(response.text()?)
+    })?;
+    Ok(serde_json::from_str(&body)?)
+}
+fn signals_for(name: &str, version: &str) -> anyhow::Result<Signals> {
+    let info = fetch(&format!("{}/{}", API, name), false)?;
+    let owners = fetch(&format!("{}/{}/owners", API, name), false).unwrap_or_default();
+    let mut signals = signals_from_api(&info, &owners, version);
+    if let Some(slug) = signals.repository.as_deref().and_then(github_slug) {
+        signals.archived = fetch(&format!("{}/{}", GITHUB_API, slug), true).ok().and_then(|repo| repo["archived"].as_bool());
+    }
+    Ok(signals)
+}
+impl TrustScoreTool {
+    pub fn new() -> Self {
+        Self
+    }
+    /// Registry crates to score: the named one, those added since `base`, every locked crate,
+    /// or by default the workspace's direct dependencies
+    fn select(&self, matches: &ArgMatches) -> anyhow::Result<Vec<(String, String)>> {
+        let lockfile = lockdiff::lockfile_path()?;
+        let packages = lockdiff::parse_lock(&fs::read_to_string(&lockfile)?)?;
+        let registry: Vec<&LockPackage> = packages.iter().filter(|p| p.source.as_deref().is_some_and(|s| s.starts_with("registry+"))).collect();
+        let pick = |p: &&LockPackage| (p.name.clone(), p.version.clone());
+        if let Some(name) = matches.get_one::<String>("crate") {
+            return Ok(registry.iter().filter(|p| &p.name == name).map(pick).collect());
+        }
+        if matches.get_flag("new") {
+            let base = matches.get_one::<String>("base").cloned().unwrap_or_else(lockdiff::default_base);
+            let old = lockdiff::parse_lock(&lockdiff::lock_at_revision(&base, &lockfile)?)?;
+            let known: BTreeSet<(&str, &str)> = old.iter().map(|p| (p.name.as_str(), p.version.as_str())).collect();
+            return Ok(registry
+                .iter()
+                .filter(|p| !known.contains(&(p.name.as_str(), p.version.as_str())))
+                .map(pick)
+                .collect());
+        }
+        if matches.get_flag("all") {
+            return Ok(registry.iter().map(pick).collect());
+        }
+        // Cargo.lock lists a dependency as "name" or, when several versions are locked, "name version"
+        let direct: BTreeSet<&str> = packages.iter().filter(|p| p.source.is_none()).flat_map(|p| p.dependencies.iter().map(String::as_str)).collect();
+        Ok(registry
+            .iter()
+            .filter(|p| direct.contains(p.name.as_str()) || direct.iter().any(|d| d.starts_with(&format!("{} {}", p.name, p.version))))
+            .map(pick)
+            .collect())
+    }
+    fn display(&self, reports: &[TrustReport], policy: &Policy, format: OutputFormat) -> Result<()> {
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(reports)?),
+            _ => {
+                println!("{}", format!("🛡️  Dependency trust scores (minimum {})", policy.min_score).bold().blue());
+                println!("{}", "═".repeat(50).blue());
+                for report in reports {
+                    let score = match report.score {
+                        s if !report.passed => s.to_string().red(),
+                        s if s < policy.min_score + 15 => s.to_string().yellow(),
+                        s => s.to_string().green(),
+                    };
+                    println!(
+                        "  {:>3}  {} {}  {}",
+                        score,
+                        report.name.bold(),
+                        report.version,
+                        format!("{} downloads, {} owner(s)", report.signals.downloads, report.signals.owners).dimmed()
+                    );
+                    for finding in &report.findings {
+                        println!("       ⚠️  {}", finding);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+impl Tool for TrustScoreTool {
+    fn name(&self) -> &'static str {
+        "trust-score"
+    }
+    fn description(&self) -> &'static str {
+        "Score dependencies on downloads, age, maintenance, owners, repository and yanks"
+    }
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about(self.description())
+            .long_about(
+                "Combine crates.io metadata and repository signals into a 0-100 trust score per dependency. Thresholds come from [trust] in .cg (min_score, min_downloads, min_age_days, min_owners, allow); --strict fails when a scored crate is below min_score",
+            )
+            .args(
+                &[
+                    Arg::new("crate").help("Score only this crate").index(1),
+                    Arg::new("new")
+                        .long("new")
+                        .help("Score only crates added to Cargo.lock since --base")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("base")
+                        .long("base")
+                        .help("Git revision to compare against (default: merge base with the main branch)"),
+                    Arg::new("all")
+                        .long("all")
+                        .help("Score every crate in Cargo.lock, not just direct dependencies")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("min-score")
+                        .long("min-score")
+                        .help("Override trust.min_score")
+                        .value_parser(clap::value_parser!(u32)),
+                    Arg::new("strict")
+                        .long("strict")
+                        .help("Fail when a crate scores below the minimum")
+                        .action(clap::ArgAction::SetTrue),
+                ],
+            )
+            .args(common_options())
+    }
+    fn execute(&self, matches: &ArgMatches) -> Result<()> {
+        let format = parse_output_format(matches);
+        let mut policy = Policy::load();
+        if let Some(min) = matches.get_one::<u32>("min-score") {
+            policy.min_score = *min;
+        }
+        let crates = self.select(matches).map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+        if crates.is_empty() {
+            println!("✅ No registry dependencies to score");
+            return Ok(());
+        }
+        if !matches!(format, OutputFormat::Json) {
+            println!("🔍 {} - Scoring {} crate(s)", "CargoMate TrustScore".bold().blue(), crates.len());
+        }
+        let now = Utc::now();
+        let mut reports = Vec::new();
+        for (name, version) in crates {
+            let signals = match signals_for(&name, &version) {
+                Ok(signals) => signals,
+                Err(e) => {
+                    eprintln!("⚠️  Skipping {}: {}", name, e);
+                    continue;
+                }
+            };
+            let (score, findings) = score(&signals, &policy, now);
+            let passed = policy.passes(&name, score);
+            reports.push(TrustReport { name, version, score, signals, findings, passed });
+        }
+        if reports.is_empty() {
+            return Err(ToolError::ExecutionFailed("No crate could be scored; see the warnings above".to_string()));
+        }
+        reports.sort_by_key(|r| r.score);
+        self.display(&reports, &policy, format)?;
+        let failed: Vec<&str> = reports.iter().filter(|r| !r.passed).map(|r| r.name.as_str()).collect();
+        if matches.get_flag("strict") && !failed.is_empty() {
+            return Err(ToolError::ExecutionFailed(format!(
+                "{} dependenc{} below trust score {}: {}",
+                failed.len(),
+                if failed.len() == 1 { "y" } else { "ies" },
+                policy.min_score,
+                failed.join(", ")
+            )));
+        }
+        Ok(())
+    }
+}
+impl Default for TrustScoreTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    #[test]
+    fn test_scores_established_and_fresh_crates() {
+        let now = DateTime::parse_from_rfc3339("2026-10-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let info = json!({
+            "crate": {"downloads": 250_000_000u64, "recent_downloads": 40_000_000u64, "created_at": "2015-05-15T00:00:00Z", "repository": "https://github.com/serde-rs/serde"},
+            "versions": [
+                {"num": "1.0.210", "yanked": false, "created_at": "2026-08-01T00:00:00Z"},
+                {"num": "1.0.209", "yanked": true, "created_at": "2026-07-01T00:00:00Z"},
+                {"num": "1.0.208", "yanked": false, "created_at": "2026-06-01T00:00:00Z"},
+                {"num": "1.0.207", "yanked": false, "created_at": "2026-05-01T00:00:00Z"}
+            ]
+        });
+        let owners = json!({"users": [{"login": "dtolnay"}, {"login": "oli-obk"}]});
+        let signals = signals_from_api(&info, &owners, "1.0.210");
+        assert_eq!((signals.versions, signals.yanked_versions, signals.owners), (4, 1, 2));
+        let (established, findings) = score(&signals, &Policy::default(), now);
+        assert!(established >= 90, "{}", established);
+        assert!(findings.is_empty());
+        let fresh = json!({
+            "crate": {"downloads": 40, "created_at": "2026-09-25T00:00:00Z", "repository": ""},
+            "versions": [{"num": "0.1.0", "yanked": true, "created_at": "2026-09-25T00:00:00Z"}]
+        });
+        let signals = signals_from_api(&fresh, &json!({"users": [{"login": "someone"}]}), "0.1.0");
+        let (suspicious, findings) = score(&signals, &Policy::default(), now);
+        assert!(suspicious < 20, "{}", suspicious);
+        assert_eq!(findings.len(), 4);
+        assert!(findings.iter().any(|f| f == "the locked version is yanked"));
+        assert_eq!(github_slug("https://github.com/serde-rs/serde.git"), Some("serde-rs/serde".to_string()));
+        assert_eq!(github_slug("https://gitlab.com/a/b"), None);
+    }
+    #[test]
+    fn test_policy_gate_flags_archived_and_stale_crates() {
+        let now = DateTime::parse_from_rfc3339("2026-10-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).ok().map(|t| t.with_timezone(&Utc));
+        let signals = Signals {
+            downloads: 5_000_000,
+            created_at: at("2018-01-01T00:00:00Z"),
+            last_release: at("2023-01-01T00:00:00Z"),
+            owners: 1,
+            repository: Some("https://github.com/old/unmaintained".to_string()),
+            archived: Some(true),
+            versions: 10,
+            ..Signals::default()
+        };
+        let policy = Policy { min_owners: 2, ..Policy::default() };
+        let (abandoned, findings) = score(&signals, &policy, now);
+        assert_eq!(
+            findings,
+            vec!["no release in 1369 day(s)", "1 owner(s) (policy: 2)", "repository is archived"]
+        );
+        let (maintained, _) = score(&Signals { last_release: at("2026-09-01T00:00:00Z"), archived: Some(false), ..signals }, &policy, now);
+        assert_eq!(maintained - abandoned, 35);
+        let policy = Policy { min_score: maintained, allow: vec!["unmaintained".to_string()], ..Policy::default() };
+        assert!(policy.passes("maintained", maintained));
+        assert!(!policy.passes("abandoned", abandoned));
+        assert!(policy.passes("unmaintained", abandoned));
+    }
+}
@@ -3,7 +3,9 @@ use clap::{Arg, ArgMatches, Command};
 use colored::*;
 use std::path::Path;
 use std::process::Command as ProcessCommand;
+use std::collections::HashMap;
 use std::fs;
+use std::time::Instant;
 use regex::Regex;
 #[derive(Debug, Clone)]
 pub struct WasmOptimizeTool;
@@ -28,6 +30,41 @@ struct WasmAnalysis {
     has_names_section: bool,
     optimization_level: String,
 }
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct FunctionSize {
+    name: String,
+    size: u64,
+}
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct FunctionDelta {
+    name: String,
+    before: u64,
+    after: u64,
+}
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct BaselineComparison {
+    baseline_file: String,
+    baseline_size: u64,
+    size_change_percentage: f64,
+    regressions: Vec<FunctionDelta>,
+    passed: bool,
+}
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct SizePipelineReport {
+    wasm_file: String,
+    output_file: String,
+    size_profile: String,
+    passes: Vec<String>,
+    original_size: u64,
+    optimized_size: u64,
+    reduction_percentage: f64,
+    attribution: String,
+    functions: Vec<FunctionDelta>,
+    steps_completed: Vec<String>,
+    build_time: f64,
+    baseline: Option<BaselineComparison>,
+    timestamp: String,
+}
 impl WasmOptimizeTool {
     pub fn new() -> Self {
         Self
@@ -303,6 +340,561 @@ impl WasmOptimizeTool {
         }
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
+    fn read_leb(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+        let mut result = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = *bytes.get(*pos)?;
+            *pos += 1;
+            result |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+            shift += 7;
+            if shift > 63 {
+                return None;
+            }
+        }
+    }
+    fn read_name(bytes: &[u8], pos: &mut usize) -> Option<String> {
+        let len = Self::read_leb(bytes, pos)? as usize;
+        let name = bytes.get(*pos..*pos + len)?;
+        *pos += len;
+        Some(String::from_utf8_lossy(name).to_string())
+    }
+    fn count_imported_functions(section: &[u8]) -> Option<u64> {
+        let mut pos = 0;
+        let mut functions = 0;
+        for _ in 0..Self::read_leb(section, &mut pos)? {
+            Self::read_name(section, &mut pos)?;
+            Self::read_name(section, &mut pos)?;
+            let kind = *section.get(pos)?;
+            pos += 1;
+            match kind {
+                0 => {
+                    Self::read_leb(section, &mut pos)?;
+                    functions += 1;
+                }
+                1 | 2 => {
+                    if kind == 1 {
+                        pos += 1;
+                    }
+                    let flags = Self::read_leb(section, &mut pos)?;
+                    Self::read_leb(section, &mut pos)?;
+                    if flags & 1 != 0 {
+                        Self::read_leb(section, &mut pos)?;
+                    }
+                }
+                3 => pos += 2,
+                4 => {
+                    pos += 1;
+                    Self::read_leb(section, &mut pos)?;
+                }
+                _ => return None,
+            }
+        }
+        Some(functions)
+    }
+    fn function_names(section: &[u8]) -> HashMap<u64, String> {
+        let mut names = HashMap::new();
+        let mut pos = 0;
+        while pos < section.len() {
+            let id = section[pos];
+            pos += 1;
+            let Some(size) = Self::read_leb(section, &mut pos) else {
+                break;
+            };
+            let end = (pos + size as usize).min(section.len());
+            if id == 1 {
+                let body = &section[pos..end];
+                let mut inner = 0;
+                let count = Self::read_leb(body, &mut inner).unwrap_or(0);
+                for _ in 0..count {
+                    let (Some(index), Some(name)) = (
+                        Self::read_leb(body, &mut inner),
+                        Self::read_name(body, &mut inner),
+                    ) else {
+                        break;
+                    };
+                    names.insert(index, name);
+                }
+            }
+            pos = end;
+        }
+        names
+    }
+    fn function_sizes_from_wasm(bytes: &[u8]) -> Option<Vec<FunctionSize>> {
+        if bytes.get(..4)? != b"\0asm" {
+            return None;
+        }
+        let mut pos = 8;
+        let mut imported = 0;
+        let mut bodies = Vec::new();
+        let mut names = HashMap::new();
+        while pos < bytes.len() {
+            let id = bytes[pos];
+            pos += 1;
+            let size = Self::read_leb(bytes, &mut pos)? as usize;
+            let section = bytes.get(pos..pos + size)?;
+            match id {
+                0 => {
+                    let mut inner = 0;
+                    if Self::read_name(section, &mut inner)? == "name" {
+                        names = Self::function_names(&section[inner..]);
+                    }
+                }
+                2 => imported = Self::count_imported_functions(section)?,
+                10 => {
+                    let mut inner = 0;
+                    for _ in 0..Self::read_leb(section, &mut inner)? {
+                        let start = inner;
+                        let body = Self::read_leb(section, &mut inner)?;
+                        inner += body as usize;
+                        bodies.push((inner - start) as u64);
+                    }
+                }
+                _ => {}
+            }
+            pos += size;
+        }
+        let mut sizes: Vec<FunctionSize> = bodies
+            .into_iter()
+            .enumerate()
+            .map(|(i, size)| FunctionSize {
+                name: names
+                    .get(&(imported + i as u64))
+                    .cloned()
+                    .unwrap_or_else(|| format!("code[{}]", i)),
+                size,
+            })
+            .collect();
+        sizes.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        Some(sizes)
+    }
+    fn function_sizes_with_twiggy(&self, file_path: &str) -> Option<Vec<FunctionSize>> {
+        let output = ProcessCommand::new("twiggy")
+            .args(["top", "--format", "json", "-n", "100000", file_path])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let items: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).ok()?;
+        Some(
+            items
+                .iter()
+                .filter_map(|item| {
+                    Some(FunctionSize {
+                        name: item["name"].as_str()?.to_string(),
+                        size: item["shallow_size"].as_u64()?,
+                    })
+                })
+                .collect(),
+        )
+    }
+    fn attribute_sizes(
+        &self,
+        file_path: &str,
+        use_twiggy: bool,
+    ) -> Result<(Vec<FunctionSize>, &'static str)> {
+        if use_twiggy {
+            if let Some(sizes) = self.function_sizes_with_twiggy(file_path) {
+                return Ok((sizes, "twiggy"));
+            }
+        }
+        let bytes = fs::read(file_path)?;
+        let sizes = Self::function_sizes_from_wasm(&bytes)
+            .ok_or_else(|| ToolError::ExecutionFailed(
+                format!("{} is not a valid WebAssembly module", file_path),
+            ))?;
+        Ok((sizes, "code section"))
+    }
+    fn diff_function_sizes(
+        before: &[FunctionSize],
+        after: &[FunctionSize],
+    ) -> Vec<FunctionDelta> {
+        let mut deltas: Vec<FunctionDelta> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for (item, is_after) in before
+            .iter()
+            .map(|f| (f, false))
+            .chain(after.iter().map(|f| (f, true)))
+        {
+            let slot = *index
+                .entry(item.name.clone())
+                .or_insert_with(|| {
+                    deltas
+                        .push(FunctionDelta {
+                            name: item.name.clone(),
+                            before: 0,
+                            after: 0,
+                        });
+                    deltas.len() - 1
+                });
+            if is_after {
+                deltas[slot].after += item.size;
+            } else {
+                deltas[slot].before += item.size;
+            }
+        }
+        deltas
+            .sort_by(|a, b| {
+                b.before.max(b.after).cmp(&a.before.max(a.after)).then_with(|| a.name.cmp(&b.name))
+            });
+        deltas
+    }
+    fn compare_with_baseline(
+        baseline_file: &str,
+        baseline: &SizePipelineReport,
+        report: &SizePipelineReport,
+        max_growth: f64,
+    ) -> BaselineComparison {
+        let size_change_percentage = if baseline.optimized_size > 0 {
+            (report.optimized_size as f64 - baseline.optimized_size as f64)
+                / baseline.optimized_size as f64 * 100.0
+        } else {
+            0.0
+        };
+        let previous: HashMap<&str, u64> = baseline
+            .functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.after))
+            .collect();
+        let regressions: Vec<FunctionDelta> = report
+            .functions
+            .iter()
+            .filter_map(|f| {
+                let before = previous.get(f.name.as_str()).copied().unwrap_or(0);
+                let grown = f.after.saturating_sub(before);
+                let limit = (before as f64 * max_growth / 100.0).max(64.0);
+                (grown as f64 > limit)
+                    .then(|| FunctionDelta {
+                        name: f.name.clone(),
+                        before,
+                        after: f.after,
+                    })
+            })
+            .collect();
+        BaselineComparison {
+            baseline_file: baseline_file.to_string(),
+            baseline_size: baseline.optimized_size,
+            size_change_percentage,
+            passed: size_change_percentage <= max_growth,
+            regressions,
+        }
+    }
+    fn profile_overrides(size_profile: &str) -> Vec<String> {
+        let settings: &[&str] = match size_profile {
+            "size" => {
+                &[
+                    "opt-level=\"z\"",
+                    "lto=true",
+                    "codegen-units=1",
+                    "panic=\"abort\"",
+                    "debug=false",
+                ]
+            }
+            "speed" => &["opt-level=3", "lto=true", "codegen-units=1"],
+            _ => &[],
+        };
+        settings.iter().map(|setting| format!("profile.release.{}", setting)).collect()
+    }
+    fn build_wasm_artifact(&self, size_profile: &str, verbose: bool) -> Result<String> {
+        let mut command = ProcessCommand::new("cargo");
+        command
+            .args([
+                "rustc",
+                "--lib",
+                "--release",
+                "--target",
+                "wasm32-unknown-unknown",
+                "--crate-type",
+                "cdylib",
+                "--message-format=json-render-diagnostics",
+            ]);
+        for setting in Self::profile_overrides(size_profile) {
+            command.arg("--config").arg(setting);
+        }
+        if verbose {
+            println!("🔨 Building with the {} profile for wasm32-unknown-unknown", size_profile);
+        }
+        let output = command
+            .output()
+            .map_err(|e| ToolError::ExecutionFailed(
+                format!("Failed to run cargo: {}", e),
+            ))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let hint = if stderr.contains("wasm32-unknown-unknown") && stderr.contains("target may not be installed") {
+                "\nhint: rustup target add wasm32-unknown-unknown"
+            } else {
+                ""
+            };
+            return Err(
+                ToolError::ExecutionFailed(format!("cargo build failed: {}{}", stderr, hint)),
+            );
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter(|message| message["reason"] == "compiler-artifact")
+            .flat_map(|message| {
+                message["filenames"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .filter_map(|file| file.as_str().map(str::to_string))
+            .rfind(|file| file.ends_with(".wasm"))
+            .ok_or_else(|| ToolError::ExecutionFailed(
+                "cargo did not produce a .wasm artifact".to_string(),
+            ))
+    }
+    fn default_passes(optimization: &str) -> Vec<String> {
+        let level = match optimization {
+            "none" => return Vec::new(),
+            "basic" => "O",
+            "aggressive" => "O3",
+            "size" => "Oz",
+            "maximum" => "O4",
+            _ => "O2",
+        };
+        vec![level.to_string()]
+    }
+    fn wasm_opt_args(passes: &[String]) -> Vec<String> {
+        passes
+            .iter()
+            .map(|pass| {
+                let pass = pass.trim_start_matches('-');
+                if pass.starts_with('O') && pass.len() <= 2 {
+                    format!("-{}", pass)
+                } else {
+                    format!("--{}", pass)
+                }
+            })
+            .collect()
+    }
+    fn run_wasm_opt_passes(
+        &self,
+        input_file: &str,
+        output_file: &str,
+        passes: &[String],
+        keep_names: bool,
+    ) -> Result<()> {
+        let mut command = ProcessCommand::new("wasm-opt");
+        command.args(Self::wasm_opt_args(passes)).arg(input_file).arg("-o").arg(output_file);
+        if keep_names {
+            command.arg("--debuginfo");
+        }
+        let output = command
+            .output()
+            .map_err(|e| ToolError::ExecutionFailed(
+                format!("Failed to run wasm-opt: {}", e),
+            ))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(
+                ToolError::ExecutionFailed(format!("wasm-opt failed: {}", stderr)),
+            );
+        }
+        Ok(())
+    }
+    fn run_size_pipeline(&self, matches: &ArgMatches) -> Result<()> {
+        let started = Instant::now();
+        let size_profile = matches.get_one::<String>("size-profile").unwrap();
+        let output_file = matches.get_one::<String>("wasm-output").unwrap();
+        let baseline_file = matches.get_one::<String>("baseline").unwrap();
+        let max_growth = *matches.get_one::<f64>("max-growth").unwrap();
+        let top = *matches.get_one::<usize>("top").unwrap();
+        let verbose = matches.get_flag("verbose");
+        let output_format = parse_output_format(matches);
+        let mut passes: Vec<String> = match matches.get_one::<String>("passes") {
+            Some(list) => {
+                list.split(',')
+                    .map(|pass| pass.trim().to_string())
+                    .filter(|pass| !pass.is_empty())
+                    .collect()
+            }
+            None => {
+                Self::default_passes(matches.get_one::<String>("optimization").unwrap())
+            }
+        };
+        let mut steps_completed = Vec::new();
+        let wasm_file = match matches.get_one::<String>("input") {
+            Some(input) => {
+                if !Path::new(input).exists() {
+                    return Err(
+                        ToolError::InvalidArguments(
+                            format!("Input file {} not found", input),
+                        ),
+                    );
+                }
+                input.clone()
+            }
+            None => {
+                let built = self.build_wasm_artifact(size_profile, verbose)?;
+                steps_completed.push(format!("Built {} with the {} profile", built, size_profile));
+                built
+            }
+        };
+        let build_time = started.elapsed().as_secs_f64();
+        let use_twiggy = self.is_tool_available("twiggy");
+        let original_size = fs::metadata(&wasm_file)?.len();
+        let (before, attribution) = self.attribute_sizes(&wasm_file, use_twiggy)?;
+        let mut after = before.clone();
+        if passes.is_empty() {
+            fs::copy(&wasm_file, output_file)?;
+        } else if self.is_tool_available("wasm-opt") {
+            self.run_wasm_opt_passes(&wasm_file, output_file, &passes, false)?;
+            steps_completed
+                .push(format!("wasm-opt {}", Self::wasm_opt_args(&passes).join(" ")));
+            let named = format!("{}.names.wasm", output_file.trim_end_matches(".wasm"));
+            if self.run_wasm_opt_passes(&wasm_file, &named, &passes, true).is_ok() {
+                after = self.attribute_sizes(&named, use_twiggy)?.0;
+                let _ = fs::remove_file(&named);
+            }
+        } else {
+            println!(
+                "⚠️  {} not found - skipping optimization passes", "wasm-opt".yellow()
+            );
+            fs::copy(&wasm_file, output_file)?;
+            passes.clear();
+        }
+        steps_completed.push(format!("Attributed sizes with {}", attribution));
+        let optimized_size = fs::metadata(output_file)?.len();
+        let mut report = SizePipelineReport {
+            wasm_file: wasm_file.clone(),
+            output_file: output_file.clone(),
+            size_profile: size_profile.clone(),
+            passes,
+            original_size,
+            optimized_size,
+            reduction_percentage: if original_size > 0 {
+                (original_size as f64 - optimized_size as f64) / original_size as f64
+                    * 100.0
+            } else {
+                0.0
+            },
+            attribution: attribution.to_string(),
+            functions: Self::diff_function_sizes(&before, &after),
+            steps_completed,
+            build_time,
+            baseline: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        if let Ok(content) = fs::read_to_string(baseline_file) {
+            let baseline: SizePipelineReport = serde_json::from_str(&content)?;
+            report.baseline = Some(
+                Self::compare_with_baseline(baseline_file, &baseline, &report, max_growth),
+            );
+        }
+        self.display_pipeline_report(&report, output_format, top, verbose);
+        if matches.get_flag("store-baseline") {
+            let mut stored = report.clone();
+            stored.baseline = None;
+            fs::write(baseline_file, serde_json::to_string_pretty(&stored)?)?;
+            println!("✅ Size baseline stored in {}", baseline_file);
+        }
+        match &report.baseline {
+            Some(comparison) if !comparison.passed => {
+                Err(
+                    ToolError::ExecutionFailed(
+                        format!(
+                            "WASM size grew {:.1}% over baseline (limit {:.1}%)", comparison
+                            .size_change_percentage, max_growth
+                        ),
+                    ),
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+    fn display_pipeline_report(
+        &self,
+        report: &SizePipelineReport,
+        output_format: OutputFormat,
+        top: usize,
+        verbose: bool,
+    ) {
+        let kb = |bytes: u64| bytes as f64 / 1024.0;
+        match output_format {
+            OutputFormat::Json => {
+                println!(
+                    "{}", serde_json::to_string_pretty(report).unwrap_or_else(| _ | "{}"
+                    .to_string())
+                );
+            }
+            OutputFormat::Table => {
+                println!(
+                    "{:<50} {:>10} {:>10} {:>10}", "Function", "Before", "After", "Delta"
+                );
+                println!("{}", "─".repeat(83));
+                for function in report.functions.iter().take(top) {
+                    println!(
+                        "{:<50} {:>10} {:>10} {:>+10}", function.name, function.before,
+                        function.after, function.after as i64 - function.before as i64
+                    );
+                }
+                println!("{}", "─".repeat(83));
+                println!(
+                    "{:<50} {:>10} {:>10} {:>+10}", "TOTAL (file)", report.original_size,
+                    report.optimized_size, report.optimized_size as i64 - report
+                    .original_size as i64
+                );
+            }
+            OutputFormat::Human => {
+                println!("\n{}", "🚀 WASM Size Pipeline".bold().blue());
+                println!("{}", "═".repeat(50).blue());
+                for step in &report.steps_completed {
+                    println!("  • {}", step.cyan());
+                }
+                println!(
+                    "\n📊 Size: {:.2} KB → {:.2} KB ({:.1}% smaller)", kb(report
+                    .original_size), kb(report.optimized_size), report
+                    .reduction_percentage
+                );
+                println!(
+                    "\n🔬 Largest functions (attribution: {}):", report.attribution
+                );
+                for function in report.functions.iter().take(top) {
+                    let delta = function.after as i64 - function.before as i64;
+                    let delta = if delta < 0 {
+                        format!("{:+}", delta).green()
+                    } else if delta > 0 {
+                        format!("{:+}", delta).red()
+                    } else {
+                        "±0".normal()
+                    };
+                    println!(
+                        "  {:>8} → {:>8}  {:>8}  {}", function.before, function.after,
+                        delta, function.name
+                    );
+                }
+                if verbose && report.functions.len() > top {
+                    println!("  ... {} more", report.functions.len() - top);
+                }
+                if let Some(baseline) = &report.baseline {
+                    let status = if baseline.passed {
+                        "✅ within budget".green()
+                    } else {
+                        "❌ over budget".red()
+                    };
+                    println!(
+                        "\n📏 Baseline {}: {:.2} KB → {:.2} KB ({:+.1}%) {}", baseline
+                        .baseline_file, kb(baseline.baseline_size), kb(report
+                        .optimized_size), baseline.size_change_percentage, status
+                    );
+                    for regression in &baseline.regressions {
+                        println!(
+                            "  ⚠️  {} grew {} → {} bytes", regression.name.yellow(),
+                            regression.before, regression.after
+                        );
+                    }
+                }
+                println!("\n⏱️  Build Time: {:.2}s", report.build_time);
+            }
+        }
+    }
     fn display_report(
         &self,
         report: &OptimizationReport,
@@ -386,7 +978,9 @@ EXAMPLES:
     cm tool wasm-optimize --release --aggressive
     cm tool wasm-optimize --target web --size-optimized --lib-type cdylib
     cm tool wasm-optimize --analyze-only --verbose --lib-type minimal
-    cm tool wasm-optimize --lib-type both --target nodejs",
+    cm tool wasm-optimize --lib-type both --target nodejs
+    cm tool wasm-optimize --pipeline --passes Oz,strip-debug --store-baseline
+    cm tool wasm-optimize --pipeline --input pkg/app_bg.wasm --max-growth 2.5",
             )
             .args(
                 &[
@@ -439,11 +1033,46 @@ EXAMPLES:
                         .help("Library type to use for WASM compilation")
                         .default_value("cdylib")
                         .value_parser(["cdylib", "rlib", "both", "minimal"]),
+                    Arg::new("pipeline")
+                        .long("pipeline")
+                        .help(
+                            "Build, run wasm-opt and report per-function sizes against a baseline",
+                        )
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("passes")
+                        .long("passes")
+                        .help("Comma-separated wasm-opt passes (e.g. Oz,strip-debug,vacuum)"),
+                    Arg::new("size-profile")
+                        .long("size-profile")
+                        .help("Release profile overrides used for the pipeline build")
+                        .default_value("size")
+                        .value_parser(["size", "speed", "none"]),
+                    Arg::new("baseline")
+                        .long("baseline")
+                        .help("Size baseline file used for regression checks")
+                        .default_value("wasm-size-baseline.json"),
+                    Arg::new("store-baseline")
+                        .long("store-baseline")
+                        .help("Store this run as the new size baseline")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("max-growth")
+                        .long("max-growth")
+                        .help("Allowed size growth over the baseline (%)")
+                        .value_parser(clap::value_parser!(f64))
+                        .default_value("1.0"),
+                    Arg::new("top")
+                        .long("top")
+                        .help("Number of functions to show in the size report")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("15"),
                 ],
             )
             .args(&common_options())
     }
     fn execute(&self, matches: &ArgMatches) -> Result<()> {
+        if matches.get_flag("pipeline") {
+            return self.run_size_pipeline(matches);
+        }
         let release = matches.get_flag("release");
         let target = matches.get_one::<String>("target").unwrap();
         let optimization = matches.get_one::<String>("optimization").unwrap();
@@ -653,4 +1282,49 @@ impl Default for WasmOptimizeTool {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_function_sizes_and_baseline() {
+        let module: Vec<u8> = vec![
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x02, 0x07, 0x01, 0x01, 0x65, 0x01, 0x66, 0x00, 0x00, 0x03, 0x03, 0x02, 0x00, 0x00,
+            0x0a, 0x09, 0x02, 0x02, 0x00, 0x0b, 0x04, 0x00, 0x01, 0x01, 0x0b, 0x00, 0x0f, 0x04,
+            0x6e, 0x61, 0x6d, 0x65, 0x01, 0x08, 0x02, 0x01, 0x01, 0x61, 0x02, 0x02, 0x62, 0x62,
+        ];
+        let sizes = WasmOptimizeTool::function_sizes_from_wasm(&module).unwrap();
+        let named: Vec<(&str, u64)> = sizes.iter().map(|f| (f.name.as_str(), f.size)).collect();
+        assert_eq!(named, vec![("bb", 5), ("a", 3)]);
+        let after = vec![FunctionSize {
+            name: "bb".to_string(),
+            size: 200,
+        }];
+        let deltas = WasmOptimizeTool::diff_function_sizes(&sizes, &after);
+        assert_eq!(deltas[0].name, "bb");
+        assert_eq!((deltas[1].before, deltas[1].after), (3, 0));
+        let report = |size: u64, functions: Vec<FunctionDelta>| SizePipelineReport {
+            wasm_file: String::new(),
+            output_file: String::new(),
+            size_profile: "size".to_string(),
+            passes: Vec::new(),
+            original_size: size,
+            optimized_size: size,
+            reduction_percentage: 0.0,
+            attribution: "code section".to_string(),
+            functions,
+            steps_completed: Vec::new(),
+            build_time: 0.0,
+            baseline: None,
+            timestamp: String::new(),
+        };
+        let baseline = report(1000, WasmOptimizeTool::diff_function_sizes(&sizes, &sizes));
+        let current = report(1100, deltas);
+        let comparison =
+            WasmOptimizeTool::compare_with_baseline("baseline.json", &baseline, &current, 1.0);
+        assert!(!comparison.passed);
+        assert_eq!(comparison.regressions.len(), 1);
+        assert_eq!(comparison.regressions[0].name, "bb");
+    }
+}
@@ -2,13 +2,15 @@ use anyhow::{Context, Result};
 use cargo_metadata::{DependencyKind, Metadata, MetadataCommand, Package, PackageId};
 use colored::*;
 use petgraph::graph::{DiGraph, NodeIndex};
-use petgraph::visit::Dfs;
+use petgraph::visit::EdgeRef;
 use petgraph::Direction;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
 use crate::captain::license;
+use crate::map_render::{GraphEdge, GraphExport, GraphNode, MapFormat};
+use crate::display::format_size;
 #[derive(Debug, Clone)]
 pub struct DependencyNode {
     pub name: String,
@@ -17,6 +19,7 @@ pub struct DependencyNode {
     pub features: Vec<String>,
     pub size_bytes: Option<u64>,
     pub license: Option<String>,
+    pub proc_macro: bool,
     pub is_dev: bool,
     pub is_build: bool,
     pub depth: usize,
@@ -51,6 +54,10 @@ impl TreasureMap {
                 features: package.features.keys().cloned().collect(),
                 size_bytes: self.estimate_package_size(package),
                 license: package.license.clone(),
+                proc_macro: package
+                    .targets
+                    .iter()
+                    .any(|t| t.kind.iter().any(|k| k == "proc-macro")),
                 is_dev: false,
                 is_build: false,
                 depth: 0,
@@ -305,13 +312,7 @@ impl TreasureMap {
         dot.push_str("    node [shape=box];\n\n");
         for idx in self.graph.node_indices() {
             let node = &self.graph[idx];
-            let color = match self.get_node_color(node) {
-                NodeColor::Green => "green",
-                NodeColor::Yellow => "yellow",
-                NodeColor::Red => "red",
-                NodeColor::Blue => "blue",
-                NodeColor::Gray => "gray",
-            };
+            let color = self.get_node_color(node).name();
             dot.push_str(
                 &format!(
                     "    \"{}\" [label=\"{}\\nv{}\", color=\"{}\"];\n", node.name, node
@@ -339,6 +340,64 @@ impl TreasureMap {
         println!("✅ Dependency graph exported to {}", path.display());
         Ok(())
     }
+    pub fn graph_export(&self) -> GraphExport {
+        let nodes = self
+            .graph
+            .node_indices()
+            .map(|idx| {
+                let node = &self.graph[idx];
+                GraphNode {
+                    id: idx.index(),
+                    name: node.name.clone(),
+                    version: node.version.clone(),
+                    source: node.source.clone(),
+                    license: node.license.clone(),
+                    depth: node.depth,
+                    dev: node.is_dev,
+                    build: node.is_build,
+                    size_bytes: node.size_bytes,
+                    color: self.get_node_color(node).name(),
+                }
+            })
+            .collect();
+        let edges = self
+            .graph
+            .edge_indices()
+            .filter_map(|edge| {
+                let (from, to) = self.graph.edge_endpoints(edge)?;
+                let kind = match self.graph[edge] {
+                    DependencyKind::Development => "dev",
+                    DependencyKind::Build => "build",
+                    _ => "normal",
+                };
+                Some(GraphEdge {
+                    from: from.index(),
+                    to: to.index(),
+                    kind,
+                })
+            })
+            .collect();
+        GraphExport {
+            root: self.root_package.as_ref().map(|p| p.name.clone()),
+            nodes,
+            edges,
+        }
+    }
+    pub fn export(&self, path: &PathBuf, format: MapFormat) -> Result<()> {
+        let graph = self.graph_export();
+        let content = match format {
+            MapFormat::Dot => return self.export_dot(path),
+            MapFormat::Json => graph.to_json()?,
+            MapFormat::Svg => graph.to_svg(),
+            MapFormat::Mermaid if path.extension().is_some_and(|e| e == "md") => {
+                format!("```mermaid\n{}```\n", graph.to_mermaid())
+            }
+            MapFormat::Mermaid => graph.to_mermaid(),
+        };
+        fs::write(path, content)?;
+        println!("✅ Dependency graph exported to {}", path.display());
+        Ok(())
+    }
     pub fn find_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
         let from_idx = self
             .graph
@@ -369,6 +428,309 @@ impl TreasureMap {
             _ => Vec::new(),
         }
     }
+    fn license_class(&self, node: NodeIndex) -> LicenseClass {
+        self.graph[node]
+            .license
+            .as_deref()
+            .map(|license| LicenseExpr::parse(license).class())
+            .unwrap_or(LicenseClass::Unknown)
+    }
+    /// Strictest license class in the non-dev subtree of every node, with the crate it comes from
+    pub fn inherited_licenses(&self) -> HashMap<NodeIndex, (LicenseClass, NodeIndex)> {
+        let mut memo = HashMap::new();
+        for idx in self.graph.node_indices() {
+            self.inherit_license(idx, &mut memo, &mut HashSet::new());
+        }
+        memo
+    }
+    fn inherit_license(
+        &self,
+        node: NodeIndex,
+        memo: &mut HashMap<NodeIndex, (LicenseClass, NodeIndex)>,
+        visiting: &mut HashSet<NodeIndex>,
+    ) -> (LicenseClass, NodeIndex) {
+        if let Some(result) = memo.get(&node) {
+            return *result;
+        }
+        let mut strictest = (self.license_class(node), node);
+        if !visiting.insert(node) {
+            return strictest;
+        }
+        for edge in self.graph.edges_directed(node, Direction::Outgoing) {
+            if *edge.weight() == DependencyKind::Development {
+                continue;
+            }
+            let child = self.inherit_license(edge.target(), memo, visiting);
+            if child.0 > strictest.0 {
+                strictest = child;
+            }
+        }
+        visiting.remove(&node);
+        memo.insert(node, strictest);
+        strictest
+    }
+    /// Shortest chain from `from` to `to` that only follows edges accepted by `follow`
+    fn path_where(
+        &self,
+        from: NodeIndex,
+        to: NodeIndex,
+        follow: impl Fn(&DependencyKind, NodeIndex) -> bool,
+    ) -> Vec<String> {
+        let mut parents: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        let mut seen = HashSet::from([from]);
+        while let Some(node) = queue.pop_front() {
+            if node == to {
+                break;
+            }
+            for edge in self.graph.edges_directed(node, Direction::Outgoing) {
+                if follow(edge.weight(), edge.target()) && seen.insert(edge.target()) {
+                    parents.insert(edge.target(), node);
+                    queue.push_back(edge.target());
+                }
+            }
+        }
+        let mut path = vec![self.graph[to].name.clone()];
+        let mut current = to;
+        while let Some(&parent) = parents.get(&current) {
+            path.push(self.graph[parent].name.clone());
+            current = parent;
+        }
+        path.reverse();
+        path
+    }
+    fn license_label(&self, node: NodeIndex) -> String {
+        self.graph[node].license.clone().unwrap_or_else(|| "no license".to_string())
+    }
+    pub fn show_licenses(&self, krate: Option<&str>) -> Result<()> {
+        let inherited = self.inherited_licenses();
+        let Some(name) = krate else {
+            println!("{}", "🗺️  Treasure Map - Licenses".blue().bold());
+            println!();
+            if let Some(&root_idx) = self.root_package.as_ref().and_then(|root| self.node_map.get(&root.id)) {
+                self.print_license_tree(root_idx, "", true, &inherited, &mut HashSet::new());
+            }
+            return Ok(());
+        };
+        let node = self
+            .graph
+            .node_indices()
+            .find(|&idx| self.graph[idx].name == name)
+            .with_context(|| format!("'{}' is not in the dependency graph", name))?;
+        let (class, source) = inherited[&node];
+        let dep = &self.graph[node];
+        println!("📜 {} v{}", dep.name.cyan(), dep.version);
+        println!("   License:   {} ({})", self.license_label(node), self.license_class(node).label());
+        println!("   Strictest: {}", class.paint(&format!("{} ({})", self.license_label(source), class.label())));
+        if source != node {
+            let path = self.path_where(node, source, |kind, _| *kind != DependencyKind::Development);
+            println!("   Via:       {}", path.join(" → "));
+        }
+        Ok(())
+    }
+    fn print_license_tree(
+        &self,
+        node: NodeIndex,
+        prefix: &str,
+        is_last: bool,
+        inherited: &HashMap<NodeIndex, (LicenseClass, NodeIndex)>,
+        visited: &mut HashSet<NodeIndex>,
+    ) {
+        let dep = &self.graph[node];
+        let own = self.license_class(node);
+        let mut line = format!("{} v{}  {}", dep.name, dep.version, own.paint(&self.license_label(node)));
+        let (class, source) = inherited[&node];
+        if class > own {
+            line.push_str(&format!(
+                "  ⇢ {}",
+                class.paint(&format!("{} via {}", self.license_label(source), self.graph[source].name))
+            ));
+        }
+        if visited.contains(&node) {
+            line.push_str(&" [seen above]".dimmed().to_string());
+        }
+        println!("{}{}{}", prefix, if is_last { "└── " } else { "├── " }, line);
+        if !visited.insert(node) {
+            return;
+        }
+        let mut children: Vec<NodeIndex> = self
+            .graph
+            .edges_directed(node, Direction::Outgoing)
+            .filter(|edge| *edge.weight() != DependencyKind::Development)
+            .map(|edge| edge.target())
+            .collect();
+        children.sort_by_key(|&idx| &self.graph[idx].name);
+        children.dedup();
+        for (i, child) in children.iter().enumerate() {
+            let new_prefix = format!("{}{}", prefix, if is_last { "    " } else { "│   " });
+            self.print_license_tree(*child, &new_prefix, i == children.len() - 1, inherited, visited);
+        }
+    }
+    /// Crates with a denied license that end up linked into a workspace binary: normal
+    /// dependencies only, not descending into proc-macros since they never ship
+    pub fn denied_licenses(&self, deny: &[String]) -> Vec<LicenseViolation> {
+        let mut violations = Vec::new();
+        for package in self.metadata.workspace_packages() {
+            if !package.targets.iter().any(|t| t.kind.iter().any(|k| k == "bin")) {
+                continue;
+            }
+            let Some(&start) = self.node_map.get(&package.id) else {
+                continue;
+            };
+            let linked = |kind: &DependencyKind, target: NodeIndex| {
+                *kind == DependencyKind::Normal && !self.graph[target].proc_macro
+            };
+            let mut stack = vec![start];
+            let mut seen = HashSet::from([start]);
+            while let Some(node) = stack.pop() {
+                let denied = self.graph[node]
+                    .license
+                    .as_deref()
+                    .is_some_and(|license| LicenseExpr::parse(license).denied(deny));
+                if denied {
+                    violations.push(LicenseViolation {
+                        binary: package.name.clone(),
+                        krate: self.graph[node].name.clone(),
+                        license: self.license_label(node),
+                        path: self.path_where(start, node, linked),
+                    });
+                }
+                for edge in self.graph.edges_directed(node, Direction::Outgoing) {
+                    if linked(edge.weight(), edge.target()) && seen.insert(edge.target()) {
+                        stack.push(edge.target());
+                    }
+                }
+            }
+        }
+        violations
+    }
+}
+#[derive(Debug)]
+pub struct LicenseViolation {
+    pub binary: String,
+    pub krate: String,
+    pub license: String,
+    pub path: Vec<String>,
+}
+/// Ordered from least to most restrictive; a missing or unrecognised license ranks
+/// above permissive ones so it is never hidden behind them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LicenseClass {
+    Permissive,
+    Unknown,
+    WeakCopyleft,
+    Copyleft,
+    NetworkCopyleft,
+}
+impl LicenseClass {
+    pub fn of(id: &str) -> Self {
+        let id = id.to_ascii_uppercase();
+        let starts = |prefixes: &[&str]| prefixes.iter().any(|p| id.starts_with(p));
+        if starts(&["AGPL", "SSPL", "OSL"]) {
+            LicenseClass::NetworkCopyleft
+        } else if starts(&["LGPL", "MPL", "EPL", "CDDL"]) {
+            LicenseClass::WeakCopyleft
+        } else if starts(&["GPL", "EUPL"]) {
+            LicenseClass::Copyleft
+        } else if starts(&[
+            "MIT", "APACHE", "BSD", "0BSD", "ISC", "ZLIB", "UNLICENSE", "CC0", "BSL-1.0", "UNICODE", "WTFPL",
+        ]) {
+            LicenseClass::Permissive
+        } else {
+            LicenseClass::Unknown
+        }
+    }
+    pub fn label(&self) -> &'static str {
+        match self {
+            LicenseClass::Permissive => "permissive",
+            LicenseClass::Unknown => "unknown",
+            LicenseClass::WeakCopyleft => "weak copyleft",
+            LicenseClass::Copyleft => "copyleft",
+            LicenseClass::NetworkCopyleft => "network copyleft",
+        }
+    }
+    fn paint(&self, text: &str) -> ColoredString {
+        match self {
+            LicenseClass::Permissive => text.green(),
+            LicenseClass::Unknown => text.dimmed(),
+            LicenseClass::WeakCopyleft => text.yellow(),
+            LicenseClass::Copyleft => text.red(),
+            LicenseClass::NetworkCopyleft => text.red().bold(),
+        }
+    }
+}
+/// An SPDX expression; `OR` lets the user pick the friendliest option, `AND` imposes all
+#[derive(Debug, Clone, PartialEq)]
+pub enum LicenseExpr {
+    Id(String),
+    All(Vec<LicenseExpr>),
+    Any(Vec<LicenseExpr>),
+}
+impl LicenseExpr {
+    /// Also accepts the legacy `MIT/Apache-2.0` form; `WITH` exceptions stay on the id
+    pub fn parse(expression: &str) -> Self {
+        let spaced = expression.replace('(', " ( ").replace(')', " ) ").replace('/', " OR ");
+        let tokens: Vec<&str> = spaced.split_whitespace().collect();
+        Self::parse_any(&tokens, &mut 0)
+    }
+    fn parse_any(tokens: &[&str], pos: &mut usize) -> Self {
+        let mut options = vec![Self::parse_all(tokens, pos)];
+        while tokens.get(*pos) == Some(&"OR") {
+            *pos += 1;
+            options.push(Self::parse_all(tokens, pos));
+        }
+        if options.len() == 1 { options.remove(0) } else { LicenseExpr::Any(options) }
+    }
+    fn parse_all(tokens: &[&str], pos: &mut usize) -> Self {
+        let mut terms = vec![Self::parse_term(tokens, pos)];
+        while tokens.get(*pos) == Some(&"AND") {
+            *pos += 1;
+            terms.push(Self::parse_term(tokens, pos));
+        }
+        if terms.len() == 1 { terms.remove(0) } else { LicenseExpr::All(terms) }
+    }
+    fn parse_term(tokens: &[&str], pos: &mut usize) -> Self {
+        let Some(token) = tokens.get(*pos) else {
+            return LicenseExpr::Id(String::new());
+        };
+        *pos += 1;
+        if *token == "(" {
+            let inner = Self::parse_any(tokens, pos);
+            if tokens.get(*pos) == Some(&")") {
+                *pos += 1;
+            }
+            return inner;
+        }
+        let mut id = token.to_string();
+        if tokens.get(*pos) == Some(&"WITH") {
+            if let Some(exception) = tokens.get(*pos + 1) {
+                id = format!("{} WITH {}", id, exception);
+            }
+            *pos += 2;
+        }
+        LicenseExpr::Id(id)
+    }
+    pub fn class(&self) -> LicenseClass {
+        match self {
+            LicenseExpr::Id(id) => LicenseClass::of(id),
+            LicenseExpr::All(terms) => terms.iter().map(Self::class).max().unwrap_or(LicenseClass::Unknown),
+            LicenseExpr::Any(options) => options.iter().map(Self::class).min().unwrap_or(LicenseClass::Unknown),
+        }
+    }
+    /// `AGPL-3.0` denies `AGPL-3.0-only` and `AGPL-3.0-or-later`; a trailing `*` matches any suffix
+    pub fn denied(&self, deny: &[String]) -> bool {
+        match self {
+            LicenseExpr::Id(id) => {
+                let id = id.split(" WITH ").next().unwrap_or(id).to_ascii_uppercase();
+                deny.iter().map(|d| d.to_ascii_uppercase()).any(|d| match d.strip_suffix('*') {
+                    Some(prefix) => id.starts_with(prefix),
+                    None => id == d || id.strip_prefix(&d).is_some_and(|rest| rest.starts_with('-') || rest == "+"),
+                })
+            }
+            LicenseExpr::All(terms) => terms.iter().any(|term| term.denied(deny)),
+            LicenseExpr::Any(options) => options.iter().all(|option| option.denied(deny)),
+        }
+    }
 }
 #[derive(Debug)]
 pub struct DependencyAnalysis {
@@ -455,15 +817,16 @@ enum NodeColor {
     Blue,
     Gray,
 }
-fn format_size(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
-    let mut size = bytes as f64;
-    let mut unit_idx = 0;
-    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_idx += 1;
-    }
-    format!("{:.2} {}", size, UNITS[unit_idx])
+impl NodeColor {
+    fn name(&self) -> &'static str {
+        match self {
+            NodeColor::Green => "green",
+            NodeColor::Yellow => "yellow",
+            NodeColor::Red => "red",
+            NodeColor::Blue => "blue",
+            NodeColor::Gray => "gray",
+        }
+    }
 }
 pub fn check_bosun_quotas(command: &str) -> Result<bool> {
     println!("🔨 Bosun checking quotas for command '{}' - tally ho!", command.cyan());
@@ -493,4 +856,37 @@ pub fn check_bosun_quotas(command: &str) -> Result<bool> {
             Ok(false)
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_license_expressions() {
+        assert_eq!(
+            LicenseExpr::parse("MIT OR Apache-2.0").class(),
+            LicenseClass::Permissive
+        );
+        assert_eq!(
+            LicenseExpr::parse("MIT/GPL-3.0").class(),
+            LicenseClass::Permissive
+        );
+        assert_eq!(
+            LicenseExpr::parse("(MIT OR Apache-2.0) AND MPL-2.0").class(),
+            LicenseClass::WeakCopyleft
+        );
+        assert_eq!(
+            LicenseExpr::parse("Apache-2.0 WITH LLVM-exception").class(),
+            LicenseClass::Permissive
+        );
+        assert_eq!(
+            LicenseExpr::parse("LicenseRef-Proprietary").class(),
+            LicenseClass::Unknown
+        );
+        let deny = vec!["AGPL-3.0".to_string()];
+        assert!(LicenseExpr::parse("AGPL-3.0-or-later").denied(&deny));
+        assert!(LicenseExpr::parse("MIT AND AGPL-3.0-only").denied(&deny));
+        assert!(!LicenseExpr::parse("MIT OR AGPL-3.0-only").denied(&deny));
+        assert!(!LicenseExpr::parse("AGPL-3.01").denied(&deny));
+        assert!(LicenseExpr::parse("GPL-2.0+").denied(&["gpl-*".to_string()]));
+    }
+}
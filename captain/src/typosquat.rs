@@ -0,0 +1,254 @@
+use crate::captain::config::ConfigManager;
+use crate::git::git;
+use crate::outdated::manifest_dependencies;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::time::Duration;
+use toml_edit::DocumentMut;
+/// Frequently downloaded crates that squatters imitate
+const POPULAR: &[&str] = &[
+    "actix-web", "ahash", "anyhow", "arrayvec", "async-trait", "atty", "aws-config", "aws-sdk-s3",
+    "axum", "base64", "bincode", "bitflags", "byteorder", "bytes", "cc", "cfg-if", "chrono", "clap",
+    "colored", "console", "criterion", "crossbeam", "crossterm", "csv", "ctrlc", "dashmap", "diesel",
+    "dirs", "dotenv", "either", "env_logger", "fastrand", "flate2", "futures", "getrandom", "glob",
+    "h2", "hashbrown", "hex", "http", "humantime", "hyper", "indexmap", "indicatif", "itertools",
+    "itoa", "lazy_static", "libc", "log", "md5", "memchr", "mime", "mio", "nom", "num", "num_cpus",
+    "once_cell", "openssl", "parking_lot", "pin-project", "proc-macro2", "prost", "quote", "rand",
+    "rayon", "regex", "reqwest", "ring", "rusqlite", "rustls", "ryu", "semver", "serde",
+    "serde_derive", "serde_json", "serde_yaml", "sha2", "slab", "smallvec", "socket2", "sqlx",
+    "strsim", "structopt", "syn", "tempfile", "thiserror", "time", "tokio", "toml", "tonic",
+    "tower", "tracing", "tracing-subscriber", "unicode-width", "url", "uuid", "walkdir", "warp",
+    "winapi", "windows-sys", "zstd",
+];
+/// Suffixes that make a lookalike of a popular crate's name
+const SUFFIXES: &[&str] = &["rs", "rust", "lib", "2", "core-rs", "utils-rs"];
+const SECTION: &str = "typosquat";
+/// Subcommands that resolve dependencies from the manifests
+const RESOLVING: &[&str] = &["build", "b", "check", "c", "run", "r", "test", "t", "bench", "fetch", "update", "doc", "clippy"];
+#[derive(Debug, Clone)]
+pub struct Policy {
+    pub enabled: bool,
+    pub max_age_days: i64,
+    pub min_downloads: u64,
+    pub approved: Vec<String>,
+    pub rejected: Vec<String>,
+}
+impl Policy {
+    pub fn from_config(config: Option<&ConfigManager>) -> Self {
+        let number = |key: &str| {
+            config.and_then(|c| c.get(&format!("{}.{}", SECTION, key))).and_then(|v| v.trim().parse::<i64>().ok())
+        };
+        let list = |key: &str| config.map(|c| c.get_list(&format!("{}.{}", SECTION, key))).unwrap_or_default();
+        Policy {
+            enabled: config.map(|c| c.get_bool("typosquat.enabled", true)).unwrap_or(true),
+            max_age_days: number("max_age_days").unwrap_or(30),
+            min_downloads: number("min_downloads").map_or(1000, |n| n.max(0) as u64),
+            approved: list("approved"),
+            rejected: list("rejected"),
+        }
+    }
+}
+/// crates.io treats `-` and `_` as the same character
+fn normalize(name: &str) -> String {
+    name.to_lowercase().replace('-', "_")
+}
+/// Optimal string alignment distance, so a swapped pair of letters counts once
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+/// The popular crate `name` imitates, if any
+pub fn lookalike(name: &str) -> Option<&'static str> {
+    let name = normalize(name);
+    if POPULAR.iter().any(|p| normalize(p) == name) {
+        return None;
+    }
+    let limit = if name.len() <= 8 { 1 } else { 2 };
+    POPULAR.iter().copied().find(|popular| {
+        let popular = normalize(popular);
+        let affixed = SUFFIXES.iter().any(|s| {
+            let s = normalize(s);
+            name == format!("{}{}", popular, s) || name == format!("{}_{}", popular, s)
+        }) || name == format!("rust_{}", popular)
+            || name == format!("rs_{}", popular);
+        affixed || (popular.len() >= 5 && edit_distance(&name, &popular) <= limit)
+    })
+}
+/// Why `name` needs a second look, from its spelling and its crates.io record
+fn reasons(name: &str, info: Option<&Value>, policy: &Policy, now: DateTime<Utc>) -> Vec<String> {
+    let mut out = Vec::new();
+    if let Some(popular) = lookalike(name) {
+        out.push(format!("name is close to the popular crate '{}'", popular));
+    }
+    if let Some(info) = info {
+        let created = info["crate"]["created_at"]
+            .as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| (now - t.with_timezone(&Utc)).num_days());
+        let downloads = info["crate"]["downloads"].as_u64().unwrap_or(0);
+        if let Some(age) = created.filter(|age| *age <= policy.max_age_days) {
+            if downloads < policy.min_downloads {
+                out.push(format!("first published {} day(s) ago with only {} downloads", age, downloads));
+            }
+        }
+    }
+    out
+}
+fn crate_info(name: &str) -> Option<Value> {
+    let url = format!("https://crates.io/api/v1/crates/{}", name);
+    let body = crate::net::cached(&url, Duration::from_secs(24 * 3600), || {
+        let response = crate::net::send(crate::outdated::client()?.get(&url))?;
+        if !response.status().is_success() {
+            anyhow::bail!("{} returned HTTP {}", url, response.status());
+        }
+        Ok(response.text()?)
+    })
+    .ok()?;
+    serde_json::from_str(&body).ok()
+}
+/// Registry crates named on a `cargo add` command line
+pub fn added_crates(args: &[&str]) -> Vec<String> {
+    const VALUE_FLAGS: &[&str] = &[
+        "-F", "--features", "--rename", "--registry", "-p", "--package", "--manifest-path", "--target",
+        "--config", "-Z", "--branch", "--tag", "--rev", "--color", "--lockfile-path",
+    ];
+    let mut rest = args.iter().skip_while(|a| **a != "add").skip(1);
+    let mut names = Vec::new();
+    while let Some(arg) = rest.next() {
+        match *arg {
+            "--path" | "--git" => return Vec::new(),
+            flag if VALUE_FLAGS.contains(&flag) => {
+                rest.next();
+            }
+            flag if flag.starts_with('-') => {}
+            spec => names.push(spec.split('@').next().unwrap_or(spec).to_string()),
+        }
+    }
+    names
+}
+fn registry_names(manifest: &str) -> BTreeSet<String> {
+    manifest
+        .parse::<DocumentMut>()
+        .map(|doc| manifest_dependencies(&doc).into_iter().map(|d| d.name).collect())
+        .unwrap_or_default()
+}
+/// Registry dependencies present in the working tree's manifests but not at HEAD
+pub fn manifest_additions() -> Vec<String> {
+    let Some(toplevel) = git(&["rev-parse", "--show-toplevel"]) else {
+        return Vec::new();
+    };
+    let toplevel = std::path::PathBuf::from(toplevel.trim());
+    let changed = git(&["-C", &toplevel.to_string_lossy(), "diff", "--name-only", "HEAD", "--", "*Cargo.toml"])
+        .unwrap_or_default();
+    let mut added = BTreeSet::new();
+    for path in changed.lines().filter(|l| !l.is_empty()) {
+        let Ok(current) = std::fs::read_to_string(toplevel.join(path)) else {
+            continue;
+        };
+        let before = registry_names(&git(&["-C", &toplevel.to_string_lossy(), "show", &format!("HEAD:{}", path)]).unwrap_or_default());
+        added.extend(registry_names(&current).into_iter().filter(|name| !before.contains(name)));
+    }
+    added.into_iter().collect()
+}
+fn record(key: &str, name: &str) -> Result<()> {
+    let mut config = ConfigManager::new()?;
+    let key = format!("{}.{}", SECTION, key);
+    let mut list = config.get_list(&key);
+    if !list.iter().any(|n| n == name) {
+        list.push(name.to_string());
+        config.set_string(&key, &list.join(", "), true)?;
+    }
+    Ok(())
+}
+/// Checks new dependencies before a wrapped cargo command; false blocks the command
+pub fn enforce(args: &[&str]) -> Result<bool> {
+    let config = ConfigManager::new().ok();
+    let policy = Policy::from_config(config.as_ref());
+    if !policy.enabled {
+        return Ok(true);
+    }
+    let subcommand = args.iter().find(|a| **a != "cargo" && !a.starts_with('+')).copied();
+    let names = match subcommand {
+        Some("add") => added_crates(args),
+        Some(sub) if RESOLVING.contains(&sub) => manifest_additions(),
+        _ => return Ok(true),
+    };
+    let now = Utc::now();
+    for name in names.iter().filter(|n| !policy.approved.iter().any(|a| normalize(a) == normalize(n))) {
+        if policy.rejected.iter().any(|r| normalize(r) == normalize(name)) {
+            println!("🛑 {} '{}' was rejected as a suspicious dependency", "Blocked:".red().bold(), name);
+            println!("💡 Remove it, or clear the decision with 'cm config unset typosquat.rejected --local'");
+            return Ok(false);
+        }
+        let found = reasons(name, crate_info(name).as_ref(), &policy, now);
+        if found.is_empty() {
+            continue;
+        }
+        println!("\n{} {}", "⚠️  POSSIBLE TYPOSQUAT:".red().bold(), name.yellow().bold());
+        for reason in &found {
+            println!("   • {}", reason);
+        }
+        println!("   🔗 https://crates.io/crates/{}", name);
+        let interactive = !crate::ci::is_ci() && atty::is(atty::Stream::Stdin);
+        let accepted = crate::ci::confirm(&format!("Use '{}' anyway?", name))?;
+        if interactive {
+            record(if accepted { "approved" } else { "rejected" }, name)?;
+        }
+        if !accepted {
+            println!("🛑 {} - '{}' not confirmed", "Command blocked".red().bold(), name);
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    #[test]
+    fn test_flags_lookalikes_and_fresh_crates() {
+        assert_eq!(edit_distance("serde", "sedre"), 1);
+        assert_eq!(lookalike("serde-json"), None);
+        assert_eq!(lookalike("serde_jsno"), Some("serde_json"));
+        assert_eq!(lookalike("tokio-rs"), Some("tokio"));
+        assert_eq!(lookalike("reqwests"), Some("reqwest"));
+        assert_eq!(lookalike("regexp"), Some("regex"));
+        assert_eq!(lookalike("petgraph"), None);
+        assert_eq!(
+            added_crates(&["cargo", "add", "serde@1", "-F", "derive", "tokoi", "--dev"]),
+            vec!["serde", "tokoi"]
+        );
+        assert!(added_crates(&["cargo", "add", "mylib", "--path", "../mylib"]).is_empty());
+        let now = DateTime::parse_from_rfc3339("2026-10-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let policy = Policy::from_config(None);
+        let fresh = json!({"crate": {"created_at": "2026-09-28T00:00:00Z", "downloads": 12}});
+        assert_eq!(
+            reasons("tokoi", Some(&fresh), &policy, now),
+            vec![
+                "name is close to the popular crate 'tokio'".to_string(),
+                "first published 3 day(s) ago with only 12 downloads".to_string(),
+            ]
+        );
+        let established = json!({"crate": {"created_at": "2019-01-01T00:00:00Z", "downloads": 12}});
+        assert!(reasons("petgraph", Some(&established), &policy, now).is_empty());
+    }
+}
@@ -0,0 +1,555 @@
+use crate::anchor::{AnchorManager, AnchorSummary};
+use crate::checklist::{self, ChecklistItem};
+use crate::history::{self, HistoryEntry};
+use crate::parser::ParsedError;
+use crate::tide::TideCharts;
+use anyhow::{Context, Result};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    },
+    ExecutableCommand,
+};
+use ratatui::{
+    backend::CrosstermBackend, layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Sparkline},
+    Frame, Terminal,
+};
+use std::io::{self, Stdout};
+use std::path::PathBuf;
+use std::process::Command;
+const RECENT_BUILDS: usize = 50;
+const SPARKLINE_BUILDS: usize = 120;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Builds,
+    Errors,
+    Checklist,
+    Anchors,
+    Journeys,
+}
+impl Pane {
+    const ALL: [Pane; 5] = [
+        Pane::Builds,
+        Pane::Errors,
+        Pane::Checklist,
+        Pane::Anchors,
+        Pane::Journeys,
+    ];
+    fn index(self) -> usize {
+        Pane::ALL.iter().position(|p| *p == self).unwrap_or(0)
+    }
+    fn title(self) -> &'static str {
+        match self {
+            Pane::Builds => "🔨 Recent Builds",
+            Pane::Errors => "🔴 Errors & Warnings",
+            Pane::Checklist => "📋 Checklist",
+            Pane::Anchors => "⚓ Anchors",
+            Pane::Journeys => "🗺️  Journeys",
+        }
+    }
+    fn help(self) -> &'static str {
+        match self {
+            Pane::Builds => "Enter re-run selected",
+            Pane::Errors => "Enter open in $EDITOR",
+            Pane::Checklist => "Enter toggle | a add | c clear done",
+            Pane::Anchors => "",
+            Pane::Journeys => "Enter play journey",
+        }
+    }
+}
+#[derive(Debug, Clone)]
+struct Diagnostic {
+    error: ParsedError,
+    is_error: bool,
+}
+#[derive(Debug, Clone)]
+enum Suspend {
+    Cm(Vec<String>),
+    Edit { file: String, line: usize },
+}
+#[derive(Default)]
+struct UiData {
+    builds: Vec<HistoryEntry>,
+    diagnostics: Vec<Diagnostic>,
+    checklist: Vec<ChecklistItem>,
+    checklist_file: Option<PathBuf>,
+    anchors: Vec<AnchorSummary>,
+    journeys: Vec<String>,
+    durations: Vec<u64>,
+}
+impl UiData {
+    fn load() -> Self {
+        let mut builds = history::load_visible_history();
+        builds.reverse();
+        builds.truncate(RECENT_BUILDS);
+        let diagnostics = builds
+            .first()
+            .map(|latest| {
+                let errors = latest.errors.iter().map(|line| (line, true));
+                let warnings = latest.warnings.iter().map(|line| (line, false));
+                errors
+                    .chain(warnings)
+                    .filter_map(|(line, is_error)| {
+                        ParsedError::parse_stored(line)
+                            .map(|error| Diagnostic { error, is_error })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let checklist_file = checklist::items_file().ok();
+        let checklist = checklist_file
+            .as_deref()
+            .and_then(|file| checklist::load_items(file).ok())
+            .unwrap_or_default();
+        let durations = TideCharts::new()
+            .map(|tide| {
+                let builds = tide.builds();
+                builds[builds.len().saturating_sub(SPARKLINE_BUILDS)..]
+                    .iter()
+                    .map(|b| (b.duration_seconds * 10.0).round() as u64)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            builds,
+            diagnostics,
+            checklist,
+            checklist_file,
+            anchors: AnchorManager::new().and_then(|m| m.list()).unwrap_or_default(),
+            journeys: crate::journey::list_journeys().unwrap_or_default(),
+            durations,
+        }
+    }
+    fn len(&self, pane: Pane) -> usize {
+        match pane {
+            Pane::Builds => self.builds.len(),
+            Pane::Errors => self.diagnostics.len(),
+            Pane::Checklist => self.checklist.len(),
+            Pane::Anchors => self.anchors.len(),
+            Pane::Journeys => self.journeys.len(),
+        }
+    }
+    /// Applies `change` to the checklist on disk under its lock and shows the result, so items
+    /// a build or `cm checklist` added meanwhile survive
+    fn update_checklist<R>(&mut self, change: impl FnOnce(&mut Vec<ChecklistItem>) -> R) -> Result<R> {
+        let file = self.checklist_file.as_deref().context("No checklist file available")?;
+        let (result, items) = checklist::update_items(file, |items| (change(items), items.clone()))?;
+        self.checklist = items;
+        Ok(result)
+    }
+}
+struct App {
+    data: UiData,
+    focus: Pane,
+    selected: [ListState; 5],
+    input: Option<String>,
+    status: String,
+}
+impl App {
+    fn new() -> Self {
+        let mut app = Self {
+            data: UiData::load(),
+            focus: Pane::Builds,
+            selected: Default::default(),
+            input: None,
+            status: String::new(),
+        };
+        app.clamp_selection();
+        app
+    }
+    fn reload(&mut self) {
+        self.data = UiData::load();
+        self.clamp_selection();
+    }
+    fn clamp_selection(&mut self) {
+        for pane in Pane::ALL {
+            let len = self.data.len(pane);
+            let state = &mut self.selected[pane.index()];
+            let selected = match state.selected() {
+                _ if len == 0 => None,
+                Some(i) => Some(i.min(len - 1)),
+                None => Some(0),
+            };
+            state.select(selected);
+        }
+    }
+    fn selected(&self, pane: Pane) -> Option<usize> {
+        self.selected[pane.index()].selected()
+    }
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.data.len(self.focus);
+        if len == 0 {
+            return;
+        }
+        let current = self.selected(self.focus).unwrap_or(0) as isize;
+        let next = (current + delta).rem_euclid(len as isize) as usize;
+        self.selected[self.focus.index()].select(Some(next));
+    }
+    fn cycle_focus(&mut self, forward: bool) {
+        let len = Pane::ALL.len();
+        let index = self.focus.index();
+        let next = if forward { (index + 1) % len } else { (index + len - 1) % len };
+        self.focus = Pane::ALL[next];
+    }
+    fn last_command(&self) -> Option<Vec<String>> {
+        self.data.builds.first().map(|build| command_args(&build.command))
+    }
+    fn activate(&mut self) -> Result<Option<Suspend>> {
+        let Some(index) = self.selected(self.focus) else {
+            return Ok(None);
+        };
+        match self.focus {
+            Pane::Builds => {
+                Ok(Some(Suspend::Cm(command_args(&self.data.builds[index].command))))
+            }
+            Pane::Errors => {
+                let error = &self.data.diagnostics[index].error;
+                Ok(
+                    Some(Suspend::Edit {
+                        file: error.file.clone(),
+                        line: error.line,
+                    }),
+                )
+            }
+            Pane::Checklist => {
+                let id = self.data.checklist[index].id;
+                let done = self.data.update_checklist(|items| {
+                    let item = items.iter_mut().find(|item| item.id == id)?;
+                    item.done = !item.done;
+                    Some(item.done)
+                })?;
+                self.clamp_selection();
+                self.status = match done {
+                    Some(done) => format!("Item #{} marked {}", id, if done { "done" } else { "open" }),
+                    None => format!("Item #{} was removed elsewhere", id),
+                };
+                Ok(None)
+            }
+            Pane::Anchors => Ok(None),
+            Pane::Journeys => {
+                let name = self.data.journeys[index].clone();
+                Ok(Some(Suspend::Cm(vec!["journey".into(), "play".into(), name])))
+            }
+        }
+    }
+    fn handle_input_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(input) = self.input.as_mut() else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => self.input = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Enter => {
+                let text = input.trim().to_string();
+                self.input = None;
+                if !text.is_empty() {
+                    let id = self.data.update_checklist(|items| checklist::add_item(items, &text))?;
+                    self.clamp_selection();
+                    self.status = format!("Added item #{}", id);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    fn handle_key(&mut self, key: KeyEvent) -> Result<Option<Option<Suspend>>> {
+        if self.input.is_some() {
+            self.handle_input_key(key)?;
+            return Ok(Some(None));
+        }
+        let suspend = match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+            KeyCode::Tab | KeyCode::Right => {
+                self.cycle_focus(true);
+                None
+            }
+            KeyCode::BackTab | KeyCode::Left => {
+                self.cycle_focus(false);
+                None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.move_selection(1);
+                None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.move_selection(-1);
+                None
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => self.activate()?,
+            KeyCode::Char('r') => {
+                match self.last_command() {
+                    Some(args) => Some(Suspend::Cm(args)),
+                    None => {
+                        self.status = "No previous command to re-run".to_string();
+                        None
+                    }
+                }
+            }
+            KeyCode::Char('a') if self.focus == Pane::Checklist => {
+                self.input = Some(String::new());
+                None
+            }
+            KeyCode::Char('c') if self.focus == Pane::Checklist => {
+                self.data.update_checklist(|items| items.retain(|item| !item.done))?;
+                self.clamp_selection();
+                self.status = "Removed completed items".to_string();
+                None
+            }
+            KeyCode::Char('g') => {
+                self.reload();
+                self.status = "Reloaded".to_string();
+                None
+            }
+            _ => None,
+        };
+        Ok(Some(suspend))
+    }
+    fn items(&self, pane: Pane) -> Vec<ListItem<'static>> {
+        match pane {
+            Pane::Builds => {
+                self.data
+                    .builds
+                    .iter()
+                    .map(|build| {
+                        let color = if build.error_count == 0 { Color::Green } else { Color::Red };
+                        ListItem::new(
+                                format!(
+                                    "{} {} ({}e/{}w)", build.timestamp.format("%m-%d %H:%M"),
+                                    build.command, build.error_count, build.warning_count
+                                ),
+                            )
+                            .style(Style::default().fg(color))
+                    })
+                    .collect()
+            }
+            Pane::Errors => {
+                self.data
+                    .diagnostics
+                    .iter()
+                    .map(|d| {
+                        let color = if d.is_error { Color::Red } else { Color::Yellow };
+                        ListItem::new(
+                                format!(
+                                    "{}:{} [{}] {}", d.error.file, d.error.line, d.error.code, d
+                                    .error.message
+                                ),
+                            )
+                            .style(Style::default().fg(color))
+                    })
+                    .collect()
+            }
+            Pane::Checklist => {
+                self.data
+                    .checklist
+                    .iter()
+                    .map(|item| {
+                        let mark = if item.done { "☑" } else { "☐" };
+                        ListItem::new(format!("{} {}. {}", mark, item.id, item.text))
+                    })
+                    .collect()
+            }
+            Pane::Anchors => {
+                self.data
+                    .anchors
+                    .iter()
+                    .map(|anchor| {
+                        ListItem::new(
+                            format!(
+                                "{} - {} ({} files)", anchor.name, anchor.timestamp
+                                .format("%Y-%m-%d %H:%M"), anchor.files_count
+                            ),
+                        )
+                    })
+                    .collect()
+            }
+            Pane::Journeys => {
+                self.data.journeys.iter().map(|name| ListItem::new(name.clone())).collect()
+            }
+        }
+    }
+    fn render_pane(&mut self, frame: &mut Frame, area: Rect, pane: Pane) {
+        let focused = self.focus == pane;
+        let border = if focused { Color::Cyan } else { Color::DarkGray };
+        let list = List::new(self.items(pane))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(border))
+                    .title(pane.title()),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.selected[pane.index()]);
+    }
+    fn ui(&mut self, frame: &mut Frame) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(10), Constraint::Length(5), Constraint::Length(3)])
+            .split(frame.size());
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(rows[0]);
+        let left = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(columns[0]);
+        let right = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(40),
+                Constraint::Percentage(30),
+                Constraint::Percentage(30),
+            ])
+            .split(columns[1]);
+        self.render_pane(frame, left[0], Pane::Builds);
+        self.render_pane(frame, left[1], Pane::Errors);
+        self.render_pane(frame, right[0], Pane::Checklist);
+        self.render_pane(frame, right[1], Pane::Anchors);
+        self.render_pane(frame, right[2], Pane::Journeys);
+        let sparkline = Sparkline::default()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("🌊 Tide - build time per build"),
+            )
+            .data(&self.data.durations)
+            .style(Style::default().fg(Color::Cyan));
+        frame.render_widget(sparkline, rows[1]);
+        let footer = match &self.input {
+            Some(input) => format!("New checklist item: {}▏ (Enter save, Esc cancel)", input),
+            None => {
+                let mut help = vec![
+                    "Tab switch pane", "↑/↓ select", "r re-run last", "g reload", "q quit",
+                ];
+                if !self.focus.help().is_empty() {
+                    help.insert(2, self.focus.help());
+                }
+                let help = help.join(" | ");
+                if self.status.is_empty() { help } else { format!("{}  —  {}", self.status, help) }
+            }
+        };
+        let footer = Paragraph::new(footer)
+            .style(Style::default().fg(Color::DarkGray))
+            .block(Block::default().borders(Borders::ALL));
+        frame.render_widget(footer, rows[2]);
+    }
+}
+fn command_args(command: &str) -> Vec<String> {
+    shell_words::split(command)
+        .unwrap_or_else(|_| command.split_whitespace().map(String::from).collect())
+}
+fn enter(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+    enable_raw_mode()?;
+    terminal.backend_mut().execute(EnterAlternateScreen)?;
+    terminal.clear()?;
+    Ok(())
+}
+fn leave(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+    disable_raw_mode()?;
+    terminal.backend_mut().execute(LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+fn run_suspended(action: &Suspend) -> Result<()> {
+    let status = match action {
+        Suspend::Cm(args) => {
+            println!("▶ cm {}", args.join(" "));
+            Command::new(std::env::current_exe()?).args(args).status()?
+        }
+        Suspend::Edit { file, line } => {
+            let editor = std::env::var("VISUAL")
+                .or_else(|_| std::env::var("EDITOR"))
+                .unwrap_or_else(|_| "vi".to_string());
+            let mut parts = command_args(&editor);
+            if parts.is_empty() {
+                parts.push("vi".to_string());
+            }
+            let root = crate::project::current_project()
+                .and_then(|p| p.manifest.parent().map(PathBuf::from));
+            let mut command = Command::new(&parts[0]);
+            command.args(&parts[1..]).arg(format!("+{}", line)).arg(file);
+            if let Some(root) = root {
+                command.current_dir(root);
+            }
+            command.status().with_context(|| format!("Failed to launch editor '{}'", editor))?
+        }
+    };
+    if matches!(action, Suspend::Cm(_)) {
+        println!();
+        println!("Exited with {} - press Enter to return to cm ui", status);
+        let mut line = String::new();
+        io::stdin().read_line(&mut line)?;
+    }
+    Ok(())
+}
+fn event_loop(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) -> Result<()> {
+    loop {
+        terminal.draw(|f| app.ui(f))?;
+        if !event::poll(std::time::Duration::from_millis(200))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        let suspend = match app.handle_key(key) {
+            Ok(Some(suspend)) => suspend,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                app.status = format!("Error: {}", e);
+                None
+            }
+        };
+        if let Some(action) = suspend {
+            leave(terminal)?;
+            let result = run_suspended(&action);
+            enter(terminal)?;
+            app.reload();
+            if let Err(e) = result {
+                app.status = format!("Error: {}", e);
+            }
+        }
+    }
+}
+pub fn run() -> Result<()> {
+    if crate::ci::is_ci() || !atty::is(atty::Stream::Stdout) {
+        anyhow::bail!("cm ui needs an interactive terminal");
+    }
+    let mut app = App::new();
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    enter(&mut terminal)?;
+    let res = event_loop(&mut terminal, &mut app);
+    leave(&mut terminal)?;
+    res
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_pane_navigation_and_command_parsing() {
+        let mut app = App {
+            data: UiData::default(),
+            focus: Pane::Journeys,
+            selected: Default::default(),
+            input: None,
+            status: String::new(),
+        };
+        app.data.journeys = vec!["setup".to_string(), "release".to_string()];
+        app.clamp_selection();
+        app.move_selection(-1);
+        match app.activate().unwrap() {
+            Some(Suspend::Cm(args)) => assert_eq!(args, ["journey", "play", "release"]),
+            other => panic!("unexpected action: {:?}", other),
+        }
+        app.cycle_focus(true);
+        assert_eq!(app.focus, Pane::Builds);
+        assert_eq!(command_args("build --features \"a b\""), ["build", "--features", "a b"]);
+        assert!(ParsedError::parse_stored("[E0308] src/lib.rs:12 - mismatched types")
+            .is_some_and(|e| e.file == "src/lib.rs" && e.line == 12));
+    }
+}
@@ -0,0 +1,276 @@
+use crate::audit::{self, Change};
+use crate::captain::config::ConfigManager;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::Subcommand;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+const STACK_FILE: &str = "stack.json";
+const DEFAULT_DEPTH: usize = 20;
+#[derive(Subcommand, Debug)]
+pub enum UndoAction {
+    /// Show undoable operations, most recent first
+    List,
+}
+/// How to reverse one file change
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Inverse {
+    /// Put back the saved copy (edited or deleted files)
+    Restore { path: PathBuf, backup: String, after: String },
+    /// Delete a file the operation created
+    Remove { path: PathBuf, after: String },
+}
+impl Inverse {
+    fn path(&self) -> &Path {
+        match self {
+            Inverse::Restore { path, .. } | Inverse::Remove { path, .. } => path,
+        }
+    }
+    fn after(&self) -> &str {
+        match self {
+            Inverse::Restore { after, .. } | Inverse::Remove { after, .. } => after,
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub action: String,
+    pub details: String,
+    pub cwd: String,
+    pub inverses: Vec<Inverse>,
+}
+fn undo_dir() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join("undo"))
+}
+fn load_stack(root: &Path) -> Vec<Operation> {
+    crate::storage::read_json_file(&root.join(STACK_FILE)).ok().flatten().unwrap_or_default()
+}
+fn save_stack(root: &Path, stack: &[Operation]) -> Result<()> {
+    crate::storage::write_json_atomic(&root.join(STACK_FILE), &stack)
+}
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().map(|d| d.join(path)).unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+/// Saves files before a mutating operation; `commit` pushes the inverse onto the undo stack
+/// and writes the matching audit entry
+pub struct Recorder {
+    root: PathBuf,
+    operation: Operation,
+    before: Vec<(PathBuf, String, Option<String>)>,
+}
+impl Recorder {
+    pub fn begin(action: &str, details: &str) -> Result<Self> {
+        Ok(Self::begin_in(&undo_dir()?, action, details))
+    }
+    pub fn capture<I: IntoIterator<Item = PathBuf>>(action: &str, details: &str, paths: I) -> Result<Self> {
+        let mut recorder = Self::begin(action, details)?;
+        for path in paths {
+            recorder.track(&path)?;
+        }
+        Ok(recorder)
+    }
+    fn begin_in(root: &Path, action: &str, details: &str) -> Self {
+        let timestamp = Utc::now();
+        Recorder {
+            root: root.to_path_buf(),
+            operation: Operation {
+                id: format!("{}-{}", timestamp.format("%Y%m%d-%H%M%S%3f"), std::process::id()),
+                timestamp,
+                action: action.to_string(),
+                details: details.to_string(),
+                cwd: std::env::current_dir().map(|d| d.display().to_string()).unwrap_or_default(),
+                inverses: Vec::new(),
+            },
+            before: Vec::new(),
+        }
+    }
+    /// Save `path` as it is now, before the operation touches it
+    pub fn track(&mut self, path: &Path) -> Result<()> {
+        let path = absolute(path);
+        let backup = if path.is_file() {
+            let files = self.root.join(&self.operation.id);
+            fs::create_dir_all(&files)?;
+            let name = self.before.len().to_string();
+            fs::copy(&path, files.join(&name)).with_context(|| format!("Cannot save {} for undo", path.display()))?;
+            Some(name)
+        } else {
+            None
+        };
+        let print = audit::fingerprint(&path);
+        self.before.push((path, print, backup));
+        Ok(())
+    }
+    fn finish(mut self) -> Result<Vec<Change>> {
+        let mut changes = Vec::new();
+        for (path, before, backup) in self.before.drain(..) {
+            let after = audit::fingerprint(&path);
+            if after == before {
+                if let Some(name) = backup {
+                    let _ = fs::remove_file(self.root.join(&self.operation.id).join(name));
+                }
+                continue;
+            }
+            changes.push(Change { path: path.display().to_string(), before, after: after.clone() });
+            self.operation.inverses.push(match backup {
+                Some(backup) => Inverse::Restore { path, backup, after },
+                None => Inverse::Remove { path, after },
+            });
+        }
+        if self.operation.inverses.is_empty() {
+            let _ = fs::remove_dir_all(self.root.join(&self.operation.id));
+            return Ok(changes);
+        }
+        let depth = ConfigManager::new()
+            .ok()
+            .and_then(|c| c.get("undo.depth"))
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .unwrap_or(DEFAULT_DEPTH)
+            .max(1);
+        let mut stack = load_stack(&self.root);
+        stack.push(self.operation.clone());
+        while stack.len() > depth {
+            let dropped = stack.remove(0);
+            let _ = fs::remove_dir_all(self.root.join(dropped.id));
+        }
+        save_stack(&self.root, &stack)?;
+        Ok(changes)
+    }
+    /// Finish the operation; failures warn instead of failing the operation itself
+    pub fn commit(self) {
+        let action = self.operation.action.clone();
+        let details = self.operation.details.clone();
+        match self.finish() {
+            Ok(changes) => audit::log(&action, &details, changes),
+            Err(e) => log::warn!("Could not record '{}' for undo: {}", action, e),
+        }
+    }
+}
+/// Paths changed again since the operation, which undo would overwrite
+fn drifted(operation: &Operation) -> Vec<&Path> {
+    operation
+        .inverses
+        .iter()
+        .filter(|i| audit::fingerprint(i.path()) != i.after())
+        .map(|i| i.path())
+        .collect()
+}
+fn revert(root: &Path, operation: &Operation) -> Result<Vec<Change>> {
+    let mut changes = Vec::new();
+    for inverse in operation.inverses.iter().rev() {
+        let before = audit::fingerprint(inverse.path());
+        match inverse {
+            Inverse::Restore { path, backup, .. } => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(root.join(&operation.id).join(backup), path)
+                    .with_context(|| format!("Cannot restore {}", path.display()))?;
+            }
+            Inverse::Remove { path, .. } => {
+                if path.exists() {
+                    fs::remove_file(path).with_context(|| format!("Cannot remove {}", path.display()))?;
+                }
+            }
+        }
+        changes.push(Change {
+            path: inverse.path().display().to_string(),
+            before,
+            after: audit::fingerprint(inverse.path()),
+        });
+    }
+    Ok(changes)
+}
+fn undo_last(root: &Path, force: bool) -> Result<Option<(Operation, Vec<Change>)>> {
+    let mut stack = load_stack(root);
+    let Some(operation) = stack.pop() else {
+        return Ok(None);
+    };
+    let drifted = drifted(&operation);
+    if !drifted.is_empty() && !force {
+        let list: Vec<String> = drifted.iter().map(|p| format!("   {}", p.display())).collect();
+        anyhow::bail!(
+            "Files changed since '{}' ran; undo would discard those edits (use --force):\n{}",
+            operation.action,
+            list.join("\n")
+        );
+    }
+    let changes = revert(root, &operation)?;
+    save_stack(root, &stack)?;
+    let _ = fs::remove_dir_all(root.join(&operation.id));
+    Ok(Some((operation, changes)))
+}
+pub fn handle_undo(action: Option<UndoAction>, force: bool) -> Result<()> {
+    let root = undo_dir()?;
+    match action {
+        Some(UndoAction::List) => {
+            let stack = load_stack(&root);
+            if stack.is_empty() {
+                println!("↩️  Nothing to undo");
+                return Ok(());
+            }
+            println!("↩️  {}", "Undo stack (most recent first)".bold().blue());
+            for (i, operation) in stack.iter().rev().enumerate() {
+                println!(
+                    "{:>3}. {} {} {} ({} file(s))",
+                    i + 1,
+                    operation.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string().dimmed(),
+                    operation.action.cyan().bold(),
+                    operation.details,
+                    operation.inverses.len()
+                );
+            }
+        }
+        None => match undo_last(&root, force)? {
+            None => println!("↩️  Nothing to undo"),
+            Some((operation, changes)) => {
+                audit::log("undo", &format!("{} ({})", operation.action, operation.details), changes);
+                println!("↩️  Undid {} {}", operation.action.cyan().bold(), operation.details);
+                for inverse in &operation.inverses {
+                    let verb = if matches!(inverse, Inverse::Remove { .. }) { "removed" } else { "restored" };
+                    println!("   {} {}", verb, inverse.path().display());
+                }
+            }
+        },
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_undo_restores_edits_and_removes_created_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let root = base.join("undo");
+        let edited = base.join("Cargo.toml");
+        let created = base.join("generated.rs");
+        fs::write(&edited, "[package]\nname = \"a\"\n").unwrap();
+        let mut recorder = Recorder::begin_in(&root, "optimize", "Balanced");
+        recorder.track(&edited).unwrap();
+        recorder.track(&created).unwrap();
+        fs::write(&edited, "[package]\nname = \"b\"\n").unwrap();
+        fs::write(&created, "fn main() {}").unwrap();
+        recorder.finish().unwrap();
+        let stack = load_stack(&root);
+        assert_eq!(stack.len(), 1);
+        assert!(matches!(stack[0].inverses[0], Inverse::Restore { .. }));
+        assert!(matches!(stack[0].inverses[1], Inverse::Remove { .. }));
+        fs::write(&edited, "[package]\nname = \"c\"\n").unwrap();
+        assert!(undo_last(&root, false).is_err());
+        assert_eq!(load_stack(&root).len(), 1);
+        undo_last(&root, true).unwrap().unwrap();
+        assert_eq!(fs::read_to_string(&edited).unwrap(), "[package]\nname = \"a\"\n");
+        assert!(!created.exists());
+        assert!(load_stack(&root).is_empty());
+        assert!(undo_last(&root, false).unwrap().is_none());
+    }
+}
@@ -1,7 +1,256 @@
-use anyhow::Result;
-pub fn handle_user_command() -> Result<()> {
-    println!("👤 User Information:");
-    println!("   This is a placeholder for user command functionality");
-    println!("   User management features coming soon!");
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+const DEFAULT_API: &str = "https://cargo.do/api";
+#[derive(Subcommand, Debug)]
+pub enum UserAction {
+    Add {
+        name: String,
+        #[arg(long)]
+        license_key: Option<String>,
+        #[arg(long)]
+        api: Option<String>,
+        #[arg(long)]
+        email: Option<String>,
+    },
+    Switch { name: String },
+    List,
+    Remove { name: String },
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserProfile {
+    pub name: String,
+    pub license_key: Option<String>,
+    pub api_endpoint: Option<String>,
+    pub email: Option<String>,
+    pub created_at: String,
+}
+/// Profiles in `~/.shipwreck/users/<name>.json`, and the `active` file naming the selected one
+struct ProfileStore {
+    dir: PathBuf,
+}
+impl ProfileStore {
+    fn home() -> Result<Self> {
+        Ok(Self {
+            dir: dirs::home_dir().context("Could not find home directory")?.join(".shipwreck").join("users"),
+        })
+    }
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        if name.is_empty()
+            || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            anyhow::bail!("Profile names may only contain letters, digits, '-' and '_'");
+        }
+        Ok(self.dir.join(format!("{}.json", name)))
+    }
+    fn load(&self, name: &str) -> Result<UserProfile> {
+        let content = fs::read_to_string(self.path(name)?)
+            .with_context(|| format!("Profile '{}' does not exist", name))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+    fn save(&self, profile: &UserProfile) -> Result<()> {
+        crate::storage::write_json_atomic(&self.path(&profile.name)?, profile)
+    }
+    fn list(&self) -> Result<Vec<UserProfile>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut profiles: Vec<UserProfile> = fs::read_dir(&self.dir)?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|e| crate::storage::read_json_file(&e.path()).ok().flatten())
+            .collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(profiles)
+    }
+    fn active_name(&self) -> Option<String> {
+        let name = fs::read_to_string(self.dir.join("active")).ok()?;
+        let name = name.trim();
+        (!name.is_empty()).then(|| name.to_string())
+    }
+    fn set_active(&self, name: &str) -> Result<()> {
+        crate::storage::write_atomic(&self.dir.join("active"), name)
+    }
+    /// Removing the active profile falls back to the default identity
+    fn remove(&self, name: &str) -> Result<()> {
+        fs::remove_file(self.path(name)?).with_context(|| format!("Profile '{}' does not exist", name))?;
+        if self.active_name().as_deref() == Some(name) {
+            fs::remove_file(self.dir.join("active"))?;
+        }
+        Ok(())
+    }
+}
+pub fn load_profile(name: &str) -> Result<UserProfile> {
+    ProfileStore::home()?.load(name)
+}
+pub fn list_profiles() -> Result<Vec<UserProfile>> {
+    ProfileStore::home()?.list()
+}
+/// `CARGO_MATE_PROFILE`, else the profile last switched to
+pub fn active_profile_name() -> Option<String> {
+    if let Ok(name) = std::env::var("CARGO_MATE_PROFILE") {
+        return Some(name);
+    }
+    ProfileStore::home().ok()?.active_name()
+}
+/// `None`, meaning the default identity, when no profile is selected or it no longer exists
+pub fn active_profile() -> Option<UserProfile> {
+    load_profile(&active_profile_name()?).ok()
+}
+pub fn api_base_url() -> String {
+    std::env::var("CARGO_MATE_API")
+        .ok()
+        .or_else(|| active_profile().and_then(|p| p.api_endpoint))
+        .unwrap_or_else(|| DEFAULT_API.to_string())
+}
+/// Each profile keeps its license in the secrets vault; profiles written before the
+/// vault existed still carry it in plain text
+fn license_secret_name(profile: Option<&str>) -> String {
+    match profile {
+        Some(name) => format!("{}.{}", crate::secrets::LICENSE, name),
+        None => crate::secrets::LICENSE.to_string(),
+    }
+}
+pub fn license_key() -> Option<String> {
+    let profile = active_profile();
+    crate::secrets::lookup(&license_secret_name(profile.as_ref().map(|p| p.name.as_str())))
+        .or_else(|| profile.and_then(|p| p.license_key))
+}
+pub fn record_license_key(key: &str) -> Result<()> {
+    let profile = active_profile();
+    crate::secrets::set(&license_secret_name(profile.as_ref().map(|p| p.name.as_str())), key)?;
+    if let Some(mut profile) = profile {
+        profile.license_key = None;
+        ProfileStore::home()?.save(&profile)?;
+        println!("👤 License stored for profile {}", profile.name.cyan());
+    }
     Ok(())
-}
\ No newline at end of file
+}
+fn mask_key(key: &str) -> String {
+    let visible: String = key.chars().rev().take(4).collect::<Vec<_>>().into_iter().rev().collect();
+    format!("****{}", visible)
+}
+pub fn active_profile_summary() -> String {
+    match active_profile() {
+        Some(profile) => {
+            format!(
+                "{} (api: {}, license: {})", profile.name, profile.api_endpoint
+                .as_deref().unwrap_or(DEFAULT_API), license_key().as_deref()
+                .map(mask_key).unwrap_or_else(|| "none".to_string())
+            )
+        }
+        None => "default (no profile selected)".to_string(),
+    }
+}
+fn show_profiles() -> Result<()> {
+    let active = active_profile_name();
+    let profiles = list_profiles()?;
+    println!("👤 {}", "User Profiles".bold().blue());
+    if profiles.is_empty() {
+        println!("   No profiles yet. Add one with: cm user add <name> --license-key <key>");
+        return Ok(());
+    }
+    for profile in profiles {
+        let marker = if active.as_deref() == Some(profile.name.as_str()) {
+            "●".green()
+        } else {
+            "○".dimmed()
+        };
+        println!(
+            "   {} {} {}{}", marker, profile.name.cyan(), profile.api_endpoint.as_deref()
+            .unwrap_or(DEFAULT_API).dimmed(), profile.email.map(| e | format!(" <{}>", e))
+            .unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+pub fn handle_user_command(action: Option<UserAction>) -> Result<()> {
+    match action {
+        None => {
+            println!("👤 User Information:");
+            println!("   Active profile: {}", active_profile_summary().cyan());
+            println!("   API endpoint: {}", api_base_url());
+            if let Some(usage) = crate::wtf_usage::summary_line() {
+                println!("   AI usage this month: {}", usage);
+            }
+        }
+        Some(UserAction::List) => show_profiles()?,
+        Some(UserAction::Add { name, license_key, api, email }) => {
+            let store = ProfileStore::home()?;
+            if store.path(&name)?.exists() {
+                anyhow::bail!("Profile '{}' already exists", name);
+            }
+            if let Some(key) = &license_key {
+                crate::secrets::set(&license_secret_name(Some(&name)), key)?;
+            }
+            store
+                .save(
+                    &UserProfile {
+                        name: name.clone(),
+                        license_key: None,
+                        api_endpoint: api,
+                        email,
+                        created_at: chrono::Utc::now().to_rfc3339(),
+                    },
+                )?;
+            println!("✅ Added profile {}", name.cyan());
+            if active_profile_name().is_none() {
+                store.set_active(&name)?;
+                println!("👤 {} is now the active profile", name.cyan());
+            } else {
+                println!("💡 Switch to it with: cm user switch {}", name);
+            }
+        }
+        Some(UserAction::Switch { name }) => {
+            let store = ProfileStore::home()?;
+            let profile = store.load(&name)?;
+            store.set_active(&profile.name)?;
+            println!("🔄 Switched to profile {}", profile.name.cyan());
+            println!("   {}", active_profile_summary());
+        }
+        Some(UserAction::Remove { name }) => {
+            ProfileStore::home()?.remove(&name)?;
+            crate::secrets::remove(&license_secret_name(Some(&name)))?;
+            println!("🗑️  Removed profile {}", name.cyan());
+        }
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn profile(name: &str, api: Option<&str>) -> UserProfile {
+        UserProfile {
+            name: name.to_string(),
+            license_key: None,
+            api_endpoint: api.map(str::to_string),
+            email: None,
+            created_at: "2026-10-01T00:00:00Z".to_string(),
+        }
+    }
+    #[test]
+    fn test_create_switch_delete_and_fall_back_to_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ProfileStore { dir: dir.path().join("users") };
+        assert!(store.list().unwrap().is_empty() && store.active_name().is_none());
+        store.save(&profile("work", Some("https://cm.example.com/api"))).unwrap();
+        store.save(&profile("home", None)).unwrap();
+        assert!(store.path("../escape").is_err());
+        let names: Vec<String> = store.list().unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, ["home", "work"]);
+        store.set_active("work").unwrap();
+        assert_eq!(store.active_name().as_deref(), Some("work"));
+        assert_eq!(store.load("work").unwrap().api_endpoint.as_deref(), Some("https://cm.example.com/api"));
+        store.set_active("home").unwrap();
+        store.remove("work").unwrap();
+        assert_eq!(store.active_name().as_deref(), Some("home"));
+        assert!(store.load("work").is_err() && store.remove("work").is_err());
+        store.remove("home").unwrap();
+        assert!(store.active_name().is_none());
+        store.set_active("gone").unwrap();
+        assert!(store.active_name().and_then(|name| store.load(&name).ok()).is_none());
+    }
+}
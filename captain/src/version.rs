@@ -1,5 +1,12 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::captain::config::ConfigManager;
 #[derive(Debug, Clone)]
 pub struct VersionConfig {
     pub auto_increment: bool,
@@ -33,65 +40,413 @@ impl Default for VersionConfig {
         }
     }
 }
+impl IncrementPolicy {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "minor" => IncrementPolicy::Minor,
+            "major" => IncrementPolicy::Major,
+            other => {
+                match other.strip_prefix("custom:") {
+                    Some(command) => IncrementPolicy::Custom(command.to_string()),
+                    None => IncrementPolicy::Patch,
+                }
+            }
+        }
+    }
+}
+impl std::fmt::Display for IncrementPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IncrementPolicy::Patch => write!(f, "patch"),
+            IncrementPolicy::Minor => write!(f, "minor"),
+            IncrementPolicy::Major => write!(f, "major"),
+            IncrementPolicy::Custom(command) => write!(f, "custom:{}", command),
+        }
+    }
+}
+impl VersionFormat {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "date" => VersionFormat::Date,
+            "build" | "build_number" => VersionFormat::BuildNumber,
+            "semantic" | "semver" | "" => VersionFormat::Semantic,
+            other => VersionFormat::Custom(other.to_string()),
+        }
+    }
+}
+impl std::fmt::Display for VersionFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VersionFormat::Semantic => write!(f, "semantic"),
+            VersionFormat::Date => write!(f, "date"),
+            VersionFormat::BuildNumber => write!(f, "build_number"),
+            VersionFormat::Custom(template) => write!(f, "{}", template),
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionChange {
+    pub version: String,
+    pub previous: Option<String>,
+    pub reason: String,
+    pub timestamp: DateTime<Utc>,
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionInfo {
+    pub current_version: String,
+    pub display_version: String,
+    pub project_root: PathBuf,
+    pub cargo_version: Option<String>,
+    pub version_file: PathBuf,
+    pub initialized: bool,
+    pub auto_increment: bool,
+    pub increment_policy: String,
+    pub version_format: String,
+}
+impl VersionInfo {
+    pub fn display(&self) {
+        println!("{}", "=== Version Info ===".blue().bold());
+        println!("🔢 Current version: {}", self.display_version.cyan().bold());
+        println!("📂 Project: {}", self.project_root.display());
+        if let Some(ref cargo) = self.cargo_version {
+            let marker = if *cargo == self.current_version { "✅" } else { "⚠️ " };
+            println!("📦 Cargo.toml: {} {}", cargo, marker);
+        }
+        println!(
+            "📁 Version file: {}{}", self.version_file.display(), if self.initialized {
+            "" } else { " (not initialized, run 'cm version init')" }
+        );
+        println!(
+            "🔄 Auto-increment: {}", if self.auto_increment { "enabled".green() } else {
+            "disabled".yellow() }
+        );
+        println!("📈 Increment policy: {}", self.increment_policy);
+        println!("🏷️  Format: {}", self.version_format);
+    }
+}
 pub struct VersionManager {
     pub config: VersionConfig,
     project_root: PathBuf,
 }
 impl VersionManager {
-    pub fn new(_project_root: Option<PathBuf>) -> Result<Self> {
-        unimplemented!()
+    pub fn new(project_root: Option<PathBuf>) -> Result<Self> {
+        let project_root = match project_root {
+            Some(root) => root,
+            None => find_project_root()?,
+        };
+        let settings = ConfigManager::new()?;
+        let mut config = VersionConfig::default();
+        config.auto_increment = settings
+            .get_bool("version.auto_increment", config.auto_increment);
+        if let Some(policy) = settings.get("version.increment_policy") {
+            config.increment_policy = IncrementPolicy::parse(&policy);
+        }
+        if let Some(format) = settings.get("version.format") {
+            config.version_format = VersionFormat::parse(&format);
+        }
+        if let Some(file) = settings.get("version.file") {
+            config.version_file = file;
+        }
+        let mut manager = Self { config, project_root };
+        if let Some(version) = manager.read_version_file() {
+            manager.config.current_version = version;
+        } else if let Some(version) = manager.cargo_version() {
+            manager.config.current_version = version;
+        }
+        Ok(manager)
     }
-    pub fn init(&mut self, _initial_version: Option<String>) -> Result<()> {
-        unimplemented!()
+    pub fn project_root(&self) -> &Path {
+        &self.project_root
+    }
+    fn version_file(&self) -> PathBuf {
+        self.project_root.join(&self.config.version_file)
+    }
+    pub fn is_initialized(&self) -> bool {
+        self.version_file().exists()
+    }
+    fn read_version_file(&self) -> Option<String> {
+        let content = fs::read_to_string(self.version_file()).ok()?;
+        let version = content.trim();
+        (!version.is_empty()).then(|| version.to_string())
+    }
+    fn cargo_version(&self) -> Option<String> {
+        let content = fs::read_to_string(self.project_root.join("Cargo.toml")).ok()?;
+        let manifest: toml::Value = content.parse().ok()?;
+        manifest.get("package")?.get("version")?.as_str().map(str::to_string)
+    }
+    pub fn init(&mut self, initial_version: Option<String>) -> Result<String> {
+        let version = initial_version
+            .or_else(|| self.cargo_version())
+            .unwrap_or_else(|| self.config.current_version.clone());
+        self.validate(&version)?;
+        self.write_version(&version, "init")?;
+        Ok(version)
     }
     pub fn current_version(&self) -> &str {
-        unimplemented!()
+        &self.config.current_version
     }
     pub fn increment(&mut self) -> Result<String> {
-        unimplemented!()
+        let next = match self.config.increment_policy.clone() {
+            IncrementPolicy::Patch => self.increment_patch()?,
+            IncrementPolicy::Minor => self.increment_minor()?,
+            IncrementPolicy::Major => self.increment_major()?,
+            IncrementPolicy::Custom(command) => self.execute_custom_increment(&command)?,
+        };
+        self.write_version(&next, &format!("increment ({})", self.config.increment_policy))?;
+        Ok(next)
     }
     pub fn auto_increment(&mut self) -> Result<Option<String>> {
-        unimplemented!()
+        if !self.config.auto_increment || !self.is_initialized() {
+            return Ok(None);
+        }
+        self.increment().map(Some)
+    }
+    pub fn set_version(&mut self, version: &str) -> Result<()> {
+        self.validate(version)?;
+        self.write_version(version, "set")
     }
-    pub fn set_version(&mut self, _version: &str) -> Result<()> {
-        unimplemented!()
+    pub fn info(&self) -> VersionInfo {
+        VersionInfo {
+            current_version: self.config.current_version.clone(),
+            display_version: self.get_display_version(),
+            project_root: self.project_root().to_path_buf(),
+            cargo_version: self.cargo_version(),
+            version_file: self.version_file(),
+            initialized: self.is_initialized(),
+            auto_increment: self.config.auto_increment,
+            increment_policy: self.config.increment_policy.to_string(),
+            version_format: self.config.version_format.to_string(),
+        }
     }
     pub fn show_info(&self) {
-        unimplemented!()
+        self.info().display();
+    }
+    pub fn history(&self) -> Result<Vec<VersionChange>> {
+        let Some(path) = self.history_path() else {
+            return Ok(Vec::new());
+        };
+        Ok(crate::storage::read_json_file(&path)?.unwrap_or_default())
     }
     pub fn show_history(&self) -> Result<()> {
-        unimplemented!()
+        let history = self.history()?;
+        if history.is_empty() {
+            println!("No version changes recorded yet");
+            return Ok(());
+        }
+        println!("{}", "=== Version History ===".blue().bold());
+        for change in history.iter().rev() {
+            println!(
+                "  {} {} {}{}", change.timestamp.format("%Y-%m-%d %H:%M").to_string()
+                .dimmed(), change.version.cyan(), change.reason, change.previous
+                .as_ref().map(| p | format!(" (from {})", p)).unwrap_or_default()
+            );
+        }
+        Ok(())
     }
-    pub fn update_cargo_toml(&self) -> Result<()> {
-        unimplemented!()
+    pub fn update_cargo_toml(&self) -> Result<bool> {
+        let path = self.project_root.join("Cargo.toml");
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let updated = replace_package_version(&content, &self.config.current_version)
+            .context("Cargo.toml has no [package] version to update")?;
+        if updated == content {
+            return Ok(false);
+        }
+        crate::storage::write_atomic(&path, updated)?;
+        Ok(true)
     }
     pub fn get_display_version(&self) -> String {
-        unimplemented!()
+        match self.config.version_format {
+            VersionFormat::BuildNumber => format!("build {}", self.config.current_version),
+            _ => format!("v{}", self.config.current_version),
+        }
     }
     pub fn save_config(&self) -> Result<()> {
-        unimplemented!()
+        let mut settings = ConfigManager::new()?;
+        settings
+            .set(
+                "version.auto_increment",
+                &self.config.auto_increment.to_string(),
+                true,
+            )?;
+        settings
+            .set(
+                "version.increment_policy",
+                &self.config.increment_policy.to_string(),
+                true,
+            )?;
+        settings.set("version.format", &self.config.version_format.to_string(), true)
+    }
+    fn validate(&self, version: &str) -> Result<()> {
+        match self.config.version_format {
+            VersionFormat::Semantic => {
+                semver::Version::parse(version)
+                    .with_context(|| format!("'{}' is not a valid semantic version", version))?;
+            }
+            VersionFormat::BuildNumber => {
+                version
+                    .parse::<u64>()
+                    .with_context(|| format!("'{}' is not a build number", version))?;
+            }
+            VersionFormat::Date | VersionFormat::Custom(_) => {
+                if version.trim().is_empty() || version.contains(char::is_whitespace) {
+                    anyhow::bail!("'{}' is not a valid version", version);
+                }
+            }
+        }
+        Ok(())
+    }
+    fn write_version(&mut self, version: &str, reason: &str) -> Result<()> {
+        let previous = self.read_version_file();
+        crate::storage::write_atomic(&self.version_file(), format!("{}\n", version))?;
+        self.config.current_version = version.to_string();
+        self.append_history(version, previous, reason)
+    }
+    /// Records an event for a release (e.g. a yank) without changing the current version
+    pub fn record(&self, version: &str, reason: &str) -> Result<()> {
+        self.append_history(version, None, reason)
+    }
+    fn append_history(&self, version: &str, previous: Option<String>, reason: &str) -> Result<()> {
+        if let Some(path) = self.history_path() {
+            crate::storage::update_json(&path, |history: &mut Vec<VersionChange>| {
+                history
+                    .push(VersionChange {
+                        version: version.to_string(),
+                        previous,
+                        reason: reason.to_string(),
+                        timestamp: Utc::now(),
+                    });
+            })?;
+        }
+        Ok(())
+    }
+    fn history_path(&self) -> Option<PathBuf> {
+        let digest = format!(
+            "{:x}", Sha256::digest(self.project_root.to_string_lossy().as_bytes())
+        );
+        Some(
+            dirs::home_dir()?
+                .join(".shipwreck")
+                .join("versions")
+                .join(format!("{}.json", &digest[..16])),
+        )
+    }
+    fn bump(&self, part: usize) -> Result<String> {
+        match self.config.version_format {
+            VersionFormat::BuildNumber => {
+                let build: u64 = self.config.current_version.parse().unwrap_or(0);
+                return Ok((build + 1).to_string());
+            }
+            VersionFormat::Date => {
+                let today = Utc::now().format("%Y.%-m.%-d").to_string();
+                let current = &self.config.current_version;
+                let next = match current.strip_prefix(&format!("{}.", today)) {
+                    Some(n) => n.parse::<u32>().map(|n| n + 1).unwrap_or(1),
+                    None if *current == today => 1,
+                    None => return Ok(today),
+                };
+                return Ok(format!("{}.{}", today, next));
+            }
+            VersionFormat::Semantic | VersionFormat::Custom(_) => {}
+        }
+        let mut version = semver::Version::parse(&self.config.current_version)
+            .with_context(|| {
+                format!(
+                    "Current version '{}' is not semantic", self.config.current_version
+                )
+            })?;
+        match part {
+            0 => {
+                version.major += 1;
+                version.minor = 0;
+                version.patch = 0;
+            }
+            1 => {
+                version.minor += 1;
+                version.patch = 0;
+            }
+            _ => version.patch += 1,
+        }
+        version.pre = semver::Prerelease::EMPTY;
+        Ok(version.to_string())
     }
     fn increment_patch(&self) -> Result<String> {
-        unimplemented!()
+        self.bump(2)
     }
     fn increment_minor(&self) -> Result<String> {
-        unimplemented!()
+        self.bump(1)
     }
     fn increment_major(&self) -> Result<String> {
-        unimplemented!()
+        self.bump(0)
     }
-    fn execute_custom_increment(&self, _command: &str) -> Result<String> {
-        unimplemented!()
+    fn execute_custom_increment(&self, command: &str) -> Result<String> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .current_dir(&self.project_root)
+            .env("CURRENT_VERSION", &self.config.current_version)
+            .output()
+            .with_context(|| format!("Failed to run increment command '{}'", command))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Increment command failed: {}", String::from_utf8_lossy(& output.stderr)
+                .trim()
+            );
+        }
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if version.is_empty() {
+            anyhow::bail!("Increment command '{}' printed no version", command);
+        }
+        Ok(version)
     }
 }
-pub fn pre_operation_hook(_project_root: Option<PathBuf>) -> Result<()> {
-    unimplemented!()
+fn find_project_root() -> Result<PathBuf> {
+    let cwd = std::env::current_dir()?;
+    Ok(
+        cwd
+            .ancestors()
+            .find(|dir| dir.join("Cargo.toml").exists())
+            .map(Path::to_path_buf)
+            .unwrap_or(cwd),
+    )
+}
+fn replace_package_version(content: &str, version: &str) -> Option<String> {
+    let mut in_package = false;
+    let mut replaced = false;
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_package = trimmed == "[package]";
+        }
+        let is_version = trimmed
+            .strip_prefix("version")
+            .is_some_and(|rest| rest.trim_start().starts_with('='));
+        if in_package && !replaced && is_version && !trimmed.contains("workspace") {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            lines.push(format!("{}version = \"{}\"", indent, version));
+            replaced = true;
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    if !replaced {
+        return None;
+    }
+    let mut updated = lines.join("\n");
+    if content.ends_with('\n') {
+        updated.push('\n');
+    }
+    Some(updated)
+}
+pub fn pre_operation_hook(project_root: Option<PathBuf>) -> Result<Option<String>> {
+    VersionManager::new(project_root)?.auto_increment()
 }
 pub fn post_operation_hook(
     _project_root: Option<PathBuf>,
     _success: bool,
 ) -> Result<()> {
-    unimplemented!()
+    Ok(())
 }
 pub fn check_sea_legs(_command: &str) -> Result<bool> {
     unimplemented!()
@@ -117,4 +472,27 @@ pub fn get_protection_key() -> String {
 }
 pub fn is_using_fallback_key() -> bool {
     get_protection_key() == FALLBACK_KEY
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_bump_and_cargo_rewrite() {
+        let mut manager = VersionManager {
+            config: VersionConfig::default(),
+            project_root: PathBuf::from("."),
+        };
+        manager.config.current_version = "1.4.2-beta.1".to_string();
+        assert_eq!(manager.increment_patch().unwrap(), "1.4.3");
+        assert_eq!(manager.increment_minor().unwrap(), "1.5.0");
+        assert_eq!(manager.increment_major().unwrap(), "2.0.0");
+        manager.config.version_format = VersionFormat::BuildNumber;
+        manager.config.current_version = "41".to_string();
+        assert_eq!(manager.increment_patch().unwrap(), "42");
+        let manifest = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = { version = \"1\" }\n";
+        let updated = replace_package_version(manifest, "0.2.0").unwrap();
+        assert!(updated.contains("version = \"0.2.0\""));
+        assert!(updated.contains("serde = { version = \"1\" }"));
+        assert!(replace_package_version("[workspace]\nmembers = []\n", "1.0.0").is_none());
+    }
+}
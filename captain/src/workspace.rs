@@ -0,0 +1,309 @@
+use crate::history::HistoryEntry;
+use crate::map_render::{GraphEdge, GraphExport, GraphNode, MapFormat};
+use crate::parser::ParsedError;
+use anyhow::{Context, Result};
+use cargo_metadata::DependencyKind;
+use chrono::{DateTime, Utc};
+use colored::*;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+const BUILD_COMMANDS: [&str; 5] = ["build", "check", "test", "clippy", "run"];
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub name: String,
+    pub version: String,
+    /// Directory relative to the workspace root, empty for a root package
+    pub dir: PathBuf,
+    pub deps: Vec<(String, &'static str)>,
+}
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceStats {
+    pub files: usize,
+    pub loc: usize,
+    pub tests: usize,
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildStatus {
+    pub command: String,
+    pub ok: bool,
+    pub errors: usize,
+    pub when: DateTime<Utc>,
+}
+fn kind_name(kind: DependencyKind) -> &'static str {
+    match kind {
+        DependencyKind::Development => "dev",
+        DependencyKind::Build => "build",
+        _ => "normal",
+    }
+}
+pub fn load_members() -> Result<(PathBuf, Vec<Member>)> {
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .no_deps()
+        .exec()
+        .context("Failed to read cargo metadata - run this inside a cargo project")?;
+    let root = PathBuf::from(metadata.workspace_root.as_std_path());
+    let names: BTreeSet<String> = metadata.packages.iter().map(|p| p.name.clone()).collect();
+    let mut members: Vec<Member> = metadata
+        .packages
+        .iter()
+        .map(|package| {
+            let dir = package
+                .manifest_path
+                .as_std_path()
+                .parent()
+                .and_then(|dir| dir.strip_prefix(&root).ok())
+                .map(PathBuf::from)
+                .unwrap_or_default();
+            let mut deps: Vec<(String, &'static str)> = package
+                .dependencies
+                .iter()
+                .filter(|dep| dep.path.is_some() && names.contains(&dep.name))
+                .map(|dep| (dep.name.clone(), kind_name(dep.kind)))
+                .collect();
+            deps.sort();
+            deps.dedup();
+            Member { name: package.name.clone(), version: package.version.to_string(), dir, deps }
+        })
+        .collect();
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok((root, members))
+}
+pub fn count_source(text: &str) -> SourceStats {
+    let mut stats = SourceStats { files: 1, ..SourceStats::default() };
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        stats.loc += 1;
+        if line.starts_with("#[") && (line.contains("test]") || line.contains("::test(")) {
+            stats.tests += 1;
+        }
+    }
+    stats
+}
+/// Rust sources under a member, leaving out `target/` and nested members
+fn source_stats(root: &Path, member: &Member, nested: &[PathBuf]) -> SourceStats {
+    let dir = root.join(&member.dir);
+    WalkDir::new(&dir)
+        .into_iter()
+        .filter_entry(|entry| {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy();
+            path == dir || !(name == "target" || name.starts_with('.') || nested.iter().any(|n| path == root.join(n)))
+        })
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rs"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .map(|text| count_source(&text))
+        .fold(SourceStats::default(), |total, file| SourceStats {
+            files: total.files + file.files,
+            loc: total.loc + file.loc,
+            tests: total.tests + file.tests,
+        })
+}
+fn selected_packages(args: &[&str]) -> Vec<String> {
+    args.windows(2)
+        .filter(|pair| pair[0] == "-p" || pair[0] == "--package")
+        .map(|pair| pair[1].to_string())
+        .chain(args.iter().filter_map(|arg| arg.strip_prefix("--package=").map(String::from)))
+        .collect()
+}
+/// The newest build-like history entry that covered `member`; errors count against it
+/// only when they point into its directory (and not into a nested member's)
+pub fn build_status(history: &[HistoryEntry], member: &Member, members: &[Member]) -> Option<BuildStatus> {
+    let entry = history.iter().rev().find(|entry| {
+        let args: Vec<&str> = entry.command.split_whitespace().collect();
+        let Some(subcommand) = args.iter().find(|arg| **arg != "cargo" && !arg.starts_with('+')) else {
+            return false;
+        };
+        if !BUILD_COMMANDS.contains(subcommand) {
+            return false;
+        }
+        let selected = selected_packages(&args);
+        selected.is_empty() || selected.contains(&member.name)
+    })?;
+    let owns = |file: &str| {
+        let file = Path::new(file);
+        file.starts_with(&member.dir)
+            && !members
+                .iter()
+                .any(|other| other.dir != member.dir && other.dir.starts_with(&member.dir) && file.starts_with(&other.dir))
+    };
+    let errors = entry
+        .errors
+        .iter()
+        .filter_map(|line| ParsedError::parse_stored(line))
+        .filter(|error| owns(&error.file))
+        .count();
+    Some(BuildStatus { command: entry.command.clone(), ok: errors == 0, errors, when: entry.timestamp })
+}
+pub fn show_overview() -> Result<()> {
+    let (root, members) = load_members()?;
+    let history = crate::history::load_visible_history();
+    println!("{}", format!("🏗️  Workspace {} ({} crates)", root.display(), members.len()).bold().blue());
+    println!("{}", "═".repeat(90).blue());
+    let width = members.iter().map(|m| m.name.len()).max().unwrap_or(5).max(5);
+    println!(
+        "{}", format!("{:<width$}  {:<9} {:>7} {:>6}  {:<14}  {}", "CRATE", "VERSION", "LOC", "TESTS",
+        "LAST BUILD", "DEPENDS ON", width = width).bold()
+    );
+    for member in &members {
+        let nested: Vec<PathBuf> = members
+            .iter()
+            .filter(|other| other.dir != member.dir && other.dir.starts_with(&member.dir))
+            .map(|other| other.dir.clone())
+            .collect();
+        let stats = source_stats(&root, member, &nested);
+        let status = match build_status(&history, member, &members) {
+            Some(status) if status.ok => format!("{:<14}", format!("✅ {}", status.when.format("%m-%d %H:%M"))).green(),
+            Some(status) => format!("{:<14}", format!("❌ {} errors", status.errors)).red(),
+            None => format!("{:<14}", "—").dimmed(),
+        };
+        let deps: Vec<String> = member
+            .deps
+            .iter()
+            .map(|(name, kind)| if *kind == "normal" { name.clone() } else { format!("{} ({})", name, kind) })
+            .collect();
+        println!(
+            "{}  {:<9} {:>7} {:>6}  {}  {}", format!("{:<width$}", member.name, width = width).cyan(),
+            member.version, stats.loc, stats.tests, status, deps.join(", ").dimmed()
+        );
+    }
+    println!();
+    println!("Internal crate graph: {}", "cm ws graph".yellow());
+    Ok(())
+}
+pub fn graph_export(members: &[Member]) -> GraphExport {
+    let id = |name: &str| members.iter().position(|m| m.name == name);
+    let used: BTreeSet<&str> = members.iter().flat_map(|m| m.deps.iter().map(|(d, _)| d.as_str())).collect();
+    GraphExport {
+        root: None,
+        nodes: members
+            .iter()
+            .enumerate()
+            .map(|(index, member)| GraphNode {
+                id: index,
+                name: member.name.clone(),
+                version: member.version.clone(),
+                source: Some(member.dir.display().to_string()),
+                license: None,
+                depth: 0,
+                dev: false,
+                build: false,
+                size_bytes: None,
+                color: if used.contains(member.name.as_str()) { "blue" } else { "green" },
+            })
+            .collect(),
+        edges: members
+            .iter()
+            .enumerate()
+            .flat_map(|(from, member)| {
+                member.deps.iter().filter_map(move |(dep, kind)| Some(GraphEdge { from, to: id(dep)?, kind }))
+            })
+            .collect(),
+    }
+}
+fn to_dot(graph: &GraphExport) -> String {
+    let mut out = String::from("digraph workspace {\n    rankdir=LR;\n    node [shape=box];\n");
+    for node in &graph.nodes {
+        out.push_str(&format!("    n{} [label=\"{} {}\"];\n", node.id, node.name, node.version));
+    }
+    for edge in &graph.edges {
+        let style = if edge.kind == "normal" { "" } else { " [style=dashed]" };
+        out.push_str(&format!("    n{} -> n{}{};\n", edge.from, edge.to, style));
+    }
+    out.push_str("}\n");
+    out
+}
+fn print_tree(members: &[Member], name: &str, prefix: &str, seen: &mut Vec<String>) {
+    let Some(member) = members.iter().find(|m| m.name == name) else {
+        return;
+    };
+    for (index, (dep, kind)) in member.deps.iter().enumerate() {
+        let last = index + 1 == member.deps.len();
+        let branch = if last { "└── " } else { "├── " };
+        let label = if *kind == "normal" { dep.cyan().to_string() } else { format!("{} ({})", dep.cyan(), kind.dimmed()) };
+        if seen.contains(dep) {
+            println!("{}{}{} {}", prefix, branch, label, "(cycle)".red());
+            continue;
+        }
+        println!("{}{}{}", prefix, branch, label);
+        seen.push(dep.clone());
+        let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        print_tree(members, dep, &child_prefix, seen);
+        seen.pop();
+    }
+}
+pub fn show_graph(format: Option<MapFormat>, output: Option<PathBuf>) -> Result<()> {
+    let (_, members) = load_members()?;
+    let format = format.or_else(|| output.as_deref().map(MapFormat::from_path));
+    let Some(format) = format else {
+        println!("{}", "🗺️  Internal crate graph".bold().blue());
+        let depended_on: BTreeSet<&str> = members.iter().flat_map(|m| m.deps.iter().map(|(d, _)| d.as_str())).collect();
+        for top in members.iter().filter(|m| !depended_on.contains(m.name.as_str())) {
+            println!("{} {}", top.name.green().bold(), top.version.dimmed());
+            print_tree(&members, &top.name, "", &mut vec![top.name.clone()]);
+        }
+        return Ok(());
+    };
+    let graph = graph_export(&members);
+    let rendered = match format {
+        MapFormat::Dot => to_dot(&graph),
+        MapFormat::Mermaid => graph.to_mermaid(),
+        MapFormat::Json => graph.to_json()?,
+        MapFormat::Svg => graph.to_svg(),
+    };
+    match output {
+        Some(path) => {
+            fs::write(&path, rendered)?;
+            println!("🗺️  Workspace graph written to {}", path.display().to_string().cyan());
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn member(name: &str, dir: &str, deps: &[(&str, &'static str)]) -> Member {
+        Member {
+            name: name.to_string(),
+            version: "0.1.0".to_string(),
+            dir: PathBuf::from(dir),
+            deps: deps.iter().map(|(d, k)| (d.to_string(), *k)).collect(),
+        }
+    }
+    fn entry(command: &str, errors: &[&str]) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: Utc::now(),
+            command: command.to_string(),
+            error_count: errors.len(),
+            warning_count: 0,
+            errors: errors.iter().map(|e| e.to_string()).collect(),
+            warnings: Vec::new(),
+        }
+    }
+    #[test]
+    fn counts_sources_attributes_builds_and_graphs_members() {
+        let stats = count_source("// header\nfn a() {}\n\n#[test]\nfn t() {}\n#[tokio::test]\nasync fn u() {}\n");
+        assert_eq!((stats.loc, stats.tests), (5, 2));
+        let members = vec![
+            member("app", "", &[("core", "normal"), ("testkit", "dev")]),
+            member("core", "crates/core", &[]),
+            member("testkit", "crates/testkit", &[("core", "normal")]),
+        ];
+        let history = vec![
+            entry("build", &["[E0308] crates/core/src/lib.rs:3 - mismatched types"]),
+            entry("test -p testkit", &[]),
+            entry("history", &[]),
+        ];
+        assert!(!build_status(&history, &members[1], &members).unwrap().ok);
+        assert!(build_status(&history, &members[0], &members).unwrap().ok);
+        assert_eq!(build_status(&history, &members[2], &members).unwrap().command, "test -p testkit");
+        let graph = graph_export(&members);
+        assert_eq!(graph.edges.len(), 3);
+        assert!(to_dot(&graph).contains("n0 -> n2 [style=dashed]"));
+    }
+}
@@ -0,0 +1,297 @@
+use crate::captain::wtf::WtfAction;
+use crate::fingerprint;
+use crate::parser::ParsedError;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+const SESSIONS_DIR: &str = "wtf_history/sessions";
+/// Older turns are dropped from a resumed prompt once it grows past this
+const MAX_CONTEXT_CHARS: usize = 24_000;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Turn {
+    pub role: String,
+    pub content: String,
+    pub at: DateTime<Utc>,
+}
+/// One conversation with CargoMate AI, linked to the error fingerprints it was about
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub title: String,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+    #[serde(default)]
+    pub fingerprints: Vec<String>,
+    pub turns: Vec<Turn>,
+}
+impl Session {
+    pub fn new(id: String, question: &str, fingerprints: Vec<String>) -> Self {
+        let title: String = question.lines().find(|l| !l.trim().is_empty()).unwrap_or("untitled").chars().take(72).collect();
+        Self {
+            id,
+            title: title.trim().to_string(),
+            created: Utc::now(),
+            updated: Utc::now(),
+            fingerprints,
+            turns: Vec::new(),
+        }
+    }
+    pub fn push(&mut self, role: &str, content: &str) {
+        self.turns.push(Turn { role: role.to_string(), content: content.trim().to_string(), at: Utc::now() });
+        self.updated = Utc::now();
+    }
+    /// The follow-up question preceded by as much of the conversation as fits, newest turns kept
+    pub fn context_prompt(&self, question: &str) -> String {
+        let mut kept = Vec::new();
+        let mut size = 0;
+        for turn in self.turns.iter().rev() {
+            let block = format!("{}: {}\n\n", if turn.role == "user" { "User" } else { "Assistant" }, turn.content);
+            if size + block.len() > MAX_CONTEXT_CHARS && !kept.is_empty() {
+                break;
+            }
+            size += block.len();
+            kept.push(block);
+        }
+        kept.reverse();
+        format!(
+            "This continues an earlier conversation about this project.\n\nPrevious conversation:\n\n{}Follow-up question: {}",
+            kept.concat(),
+            question
+        )
+    }
+    pub fn to_markdown(&self) -> String {
+        let created: DateTime<Local> = self.created.into();
+        let mut out = format!("# {}\n\n_Session {} · started {}_\n\n", self.title, self.id, created.format("%Y-%m-%d %H:%M"));
+        if !self.fingerprints.is_empty() {
+            out.push_str(&format!("Linked errors: {}\n\n", self.fingerprints.iter().map(|f| format!("`{}`", &f[..f.len().min(12)])).collect::<Vec<_>>().join(", ")));
+        }
+        for turn in &self.turns {
+            let heading = if turn.role == "user" { "🧑 Question" } else { "🤖 CargoMate AI" };
+            out.push_str(&format!("## {}\n\n{}\n\n", heading, turn.content));
+        }
+        out
+    }
+}
+fn sessions_dir() -> Result<PathBuf> {
+    let dir = crate::project::data_dir()?.join(SESSIONS_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+fn new_id(dir: &Path) -> String {
+    let base = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut id = base.clone();
+    let mut n = 2;
+    while dir.join(format!("{}.json", id)).exists() {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+pub fn save(session: &Session) -> Result<()> {
+    crate::storage::write_json_atomic(&sessions_dir()?.join(format!("{}.json", session.id)), session)
+}
+/// Newest first
+pub fn list() -> Result<Vec<Session>> {
+    let mut sessions: Vec<Session> = fs::read_dir(sessions_dir()?)?
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "json"))
+        .filter_map(|entry| crate::storage::read_json_file(&entry.path()).ok().flatten())
+        .collect();
+    sessions.sort_by_key(|s| Reverse(s.updated));
+    Ok(sessions)
+}
+/// A full id, a unique prefix of one, or `last`
+pub fn load(wanted: &str) -> Result<Session> {
+    let sessions = list()?;
+    if wanted == "last" {
+        return sessions.into_iter().next().context("No wtf sessions yet");
+    }
+    let mut matches: Vec<Session> = sessions.into_iter().filter(|s| s.id.starts_with(wanted)).collect();
+    if let Some(pos) = matches.iter().position(|s| s.id == wanted) {
+        return Ok(matches.swap_remove(pos));
+    }
+    match matches.len() {
+        0 => bail!("No wtf session matches '{}' (see 'cm wtf sessions')", wanted),
+        1 => Ok(matches.remove(0)),
+        n => bail!("'{}' matches {} sessions - use a longer id", wanted, n),
+    }
+}
+/// Fingerprints of the stored error lines in `text`
+pub fn fingerprints_in(text: &str) -> Vec<String> {
+    let mut fingerprints: Vec<String> = text
+        .lines()
+        .filter(|line| ParsedError::parse_stored(line.trim()).is_some())
+        .map(|line| fingerprint::of_stored(line.trim()))
+        .collect();
+    fingerprints.dedup();
+    fingerprints
+}
+/// The question sent to captain and the errors it is about, for actions worth recording
+fn recordable(action: &WtfAction) -> Option<(String, Vec<String>)> {
+    match action {
+        WtfAction::Ask { input, file } | WtfAction::Direct { input, file } => {
+            let question = if *file { fs::read_to_string(input).ok()? } else { input.clone() };
+            let fingerprints = fingerprints_in(&question);
+            Some((question, fingerprints))
+        }
+        WtfAction::Er { count } => {
+            let errors = crate::project::read_text("errors/latest.txt").ok().flatten().unwrap_or_default();
+            let errors: Vec<&str> = errors.lines().filter(|l| !l.trim().is_empty()).take(*count).collect();
+            let fingerprints = fingerprints_in(&errors.join("\n"));
+            Some((format!("Explain and fix these errors:\n{}", errors.join("\n")), fingerprints))
+        }
+        _ => None,
+    }
+}
+/// Runs captain with its answer streamed to the terminal and recorded in `session`
+fn ask_recorded(captain: &str, args: &[String], question: &str, mut session: Session) -> Result<()> {
+    let usage_report = crate::wtf_usage::report_path();
+    let mut command = Command::new(captain);
+    crate::net::NetworkSettings::load().apply_env(&mut command);
+    let mut child = command
+        .args(args)
+        .env(crate::wtf_usage::USAGE_FILE_ENV, &usage_report)
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut answer = String::new();
+    let mut stdout = std::io::stdout();
+    for line in BufReader::new(child.stdout.take().unwrap()).lines() {
+        let line = line?;
+        writeln!(stdout, "{}", line)?;
+        answer.push_str(&crate::hooks::strip_ansi(&line));
+        answer.push('\n');
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        eprintln!("⚠️  Captain exited with status: {}", status);
+    }
+    if answer.trim().is_empty() {
+        crate::wtf_usage::record_from(&usage_report, None, None);
+        return Ok(());
+    }
+    crate::wtf_usage::record_from(&usage_report, Some((question, &answer)), Some(&session.id));
+    session.push("user", question);
+    session.push("assistant", &answer);
+    save(&session)?;
+    println!("{}", format!("💬 Session {} - continue with 'cm wtf resume {}'", session.id, session.id).dimmed());
+    Ok(())
+}
+/// Records asks and error explanations as sessions; false when the action isn't one of them
+pub fn forward_recorded(captain: &str, action: &WtfAction, args: &[String]) -> Result<bool> {
+    let Some((question, fingerprints)) = recordable(action) else {
+        return Ok(false);
+    };
+    let earlier: Vec<Session> = list()?
+        .into_iter()
+        .filter(|s| s.fingerprints.iter().any(|f| fingerprints.contains(f)))
+        .collect();
+    if let Some(previous) = earlier.first() {
+        println!(
+            "{}",
+            format!("💡 Session {} already discussed these errors ('{}') - 'cm wtf resume {}' keeps its context", previous.id, previous.title, previous.id).dimmed()
+        );
+    }
+    let session = Session::new(new_id(&sessions_dir()?), &question, fingerprints);
+    ask_recorded(captain, args, &question, session)?;
+    Ok(true)
+}
+fn handle_sessions(error: Option<String>) -> Result<()> {
+    let mut sessions = list()?;
+    if let Some(error) = &error {
+        let wanted = fingerprint::of_stored(error.trim());
+        sessions.retain(|s| s.fingerprints.iter().any(|f| *f == wanted || f.starts_with(error.trim())));
+    }
+    if sessions.is_empty() {
+        println!("No wtf sessions{}. Ask with 'cm wtf ask \"...\"'", if error.is_some() { " about that error" } else { "" });
+        return Ok(());
+    }
+    println!("{}", "💬 WTF sessions".bold().blue());
+    for session in sessions {
+        let updated: DateTime<Local> = session.updated.into();
+        let linked = if session.fingerprints.is_empty() {
+            String::new()
+        } else {
+            format!(" · {} error(s)", session.fingerprints.len())
+        };
+        println!(
+            "  {}  {}  {}{}",
+            session.id.cyan(),
+            updated.format("%Y-%m-%d %H:%M").to_string().dimmed(),
+            session.title,
+            format!(" · {} turn(s){}", session.turns.len(), linked).dimmed()
+        );
+    }
+    Ok(())
+}
+fn handle_export(id: &str, output: Option<PathBuf>) -> Result<()> {
+    let session = load(id)?;
+    let markdown = session.to_markdown();
+    match output {
+        Some(path) => {
+            fs::write(&path, markdown)?;
+            println!("📝 Session {} exported to {}", session.id.cyan(), path.display());
+        }
+        None => print!("{}", markdown),
+    }
+    Ok(())
+}
+fn handle_resume(id: &str, question: &[String]) -> Result<()> {
+    let session = load(id)?;
+    let question = if question.is_empty() {
+        print!("❓ Follow-up for '{}': ", session.title);
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        line.trim().to_string()
+    } else {
+        question.join(" ")
+    };
+    if question.is_empty() {
+        bail!("No follow-up question given");
+    }
+    let captain = crate::captain::captain_status::get_captain_path()
+        .filter(|_| crate::captain::captain_status::is_captain_available())
+        .context("Resuming a session needs the captain binary - run 'cm install'")?;
+    let prompt = std::env::temp_dir().join(format!("cargo-mate-wtf-{}.txt", session.id));
+    fs::write(&prompt, session.context_prompt(&question))?;
+    println!("↩️  Resuming session {} ({} earlier turn(s))", session.id.cyan(), session.turns.len());
+    let args = vec!["ask".to_string(), prompt.to_string_lossy().to_string(), "--file".to_string()];
+    let result = ask_recorded(&captain, &args, &question, session);
+    let _ = fs::remove_file(&prompt);
+    result
+}
+/// Session commands never reach captain; true when `action` was one of them
+pub fn handle_local(action: &WtfAction) -> Result<bool> {
+    match action {
+        WtfAction::Sessions { error } => handle_sessions(error.clone())?,
+        WtfAction::Export { id, output } => handle_export(id, output.clone())?,
+        WtfAction::Resume { id, question } => handle_resume(id, question)?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_session_prompt_and_export() {
+        let mut session = Session::new("20260101-120000".to_string(), "Why does E0382 happen?\nmore", vec!["abc".to_string()]);
+        assert_eq!(session.title, "Why does E0382 happen?");
+        session.push("user", "Why does E0382 happen?");
+        session.push("assistant", "The value was moved.");
+        let prompt = session.context_prompt("How do I clone it?");
+        assert!(prompt.contains("User: Why does E0382 happen?\n\nAssistant: The value was moved."));
+        assert!(prompt.ends_with("Follow-up question: How do I clone it?"));
+        session.push("assistant", &"x".repeat(MAX_CONTEXT_CHARS));
+        assert!(!session.context_prompt("?").contains("The value was moved."));
+        let markdown = session.to_markdown();
+        assert!(markdown.starts_with("# Why does E0382 happen?"));
+        assert!(markdown.contains("## 🤖 CargoMate AI\n\nThe value was moved."));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::captain::config::ConfigManager;
+use crate::captain::wtf::WtfAction;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Datelike, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+/// Captain appends one JSON object per API request to this file, e.g.
+/// `{"prompt_tokens": 812, "completion_tokens": 301, "model": "..."}`
+pub const USAGE_FILE_ENV: &str = "CM_WTF_USAGE_FILE";
+const USAGE_LOG: &str = "wtf_history/usage.jsonl";
+const DEFAULT_WARN_PERCENT: u64 = 80;
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Usage {
+    pub at: DateTime<Utc>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Counted from the text because captain did not report the API's numbers
+    #[serde(default)]
+    pub estimated: bool,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub session: Option<String>,
+}
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonthlyUsage {
+    pub requests: usize,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub estimated_requests: usize,
+}
+impl MonthlyUsage {
+    pub fn total(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+/// Actions that reach the AI and therefore count against the budget
+pub fn is_ai_action(action: &WtfAction) -> bool {
+    matches!(
+        action,
+        WtfAction::Ask { .. }
+            | WtfAction::Direct { .. }
+            | WtfAction::Er { .. }
+            | WtfAction::Checklist { .. }
+            | WtfAction::Interactive
+            | WtfAction::Resume { .. }
+    )
+}
+/// Roughly four characters per token, the usual rule of thumb for English and code
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+fn log_path() -> Result<PathBuf> {
+    Ok(crate::project::shipwreck_dir()?.join(USAGE_LOG))
+}
+/// Fresh path handed to captain through `CM_WTF_USAGE_FILE`
+pub fn report_path() -> PathBuf {
+    std::env::temp_dir().join(format!("cargo-mate-wtf-usage-{}-{}.jsonl", std::process::id(), Utc::now().timestamp_millis()))
+}
+/// Reads captain's per-request usage lines, accepting both flat and OpenAI-style `{"usage": {...}}` objects
+pub fn parse_report(content: &str) -> Vec<Usage> {
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .map(|json| {
+            let usage = if json.get("usage").is_some() { &json["usage"] } else { &json };
+            Usage {
+                at: Utc::now(),
+                prompt_tokens: usage["prompt_tokens"].as_u64().or(usage["input_tokens"].as_u64()).unwrap_or(0),
+                completion_tokens: usage["completion_tokens"].as_u64().or(usage["output_tokens"].as_u64()).unwrap_or(0),
+                estimated: false,
+                model: json["model"].as_str().map(str::to_string),
+                session: None,
+            }
+        })
+        .filter(|usage| usage.prompt_tokens + usage.completion_tokens > 0)
+        .collect()
+}
+/// Logs what captain reported in `report`, falling back to an estimate from `exchange`
+/// (question, answer) when it reported nothing and the text is known
+pub fn record_from(report: &Path, exchange: Option<(&str, &str)>, session: Option<&str>) {
+    let mut usages = fs::read_to_string(report).map(|content| parse_report(&content)).unwrap_or_default();
+    let _ = fs::remove_file(report);
+    if usages.is_empty() {
+        if let Some((question, answer)) = exchange {
+            usages.push(Usage {
+                at: Utc::now(),
+                prompt_tokens: estimate_tokens(question),
+                completion_tokens: estimate_tokens(answer),
+                estimated: true,
+                ..Default::default()
+            });
+        }
+    }
+    for mut usage in usages {
+        usage.session = session.map(str::to_string);
+        if let Err(e) = append(&usage) {
+            log::warn!("Could not record AI usage: {}", e);
+        }
+    }
+}
+fn append(usage: &Usage) -> Result<()> {
+    let path = log_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(usage)?)?;
+    Ok(())
+}
+pub fn monthly(usages: &[Usage], now: DateTime<Local>) -> MonthlyUsage {
+    let mut month = MonthlyUsage::default();
+    for usage in usages {
+        let at: DateTime<Local> = usage.at.into();
+        if at.year() == now.year() && at.month() == now.month() {
+            month.requests += 1;
+            month.prompt_tokens += usage.prompt_tokens;
+            month.completion_tokens += usage.completion_tokens;
+            month.estimated_requests += usage.estimated as usize;
+        }
+    }
+    month
+}
+pub fn this_month() -> MonthlyUsage {
+    let usages: Vec<Usage> = log_path()
+        .and_then(|path| Ok(fs::read_to_string(path)?))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    monthly(&usages, Local::now())
+}
+/// `wtf.monthly_token_budget` and `wtf.budget_warn_percent` from config
+fn budget() -> Option<(u64, u64)> {
+    let config = ConfigManager::new().ok()?;
+    let limit = config.get("wtf.monthly_token_budget")?.trim().replace('_', "").parse().ok().filter(|l| *l > 0)?;
+    let warn = config
+        .get("wtf.budget_warn_percent")
+        .and_then(|w| w.trim().parse().ok())
+        .unwrap_or(DEFAULT_WARN_PERCENT);
+    Some((limit, warn))
+}
+/// Warns past the warning threshold and refuses once the monthly budget is used up
+pub fn check_budget() -> Result<()> {
+    let Some((limit, warn)) = budget() else {
+        return Ok(());
+    };
+    let used = this_month().total();
+    if used >= limit {
+        bail!(
+            "Monthly AI token budget reached: {} of {} tokens used - raise wtf.monthly_token_budget or wait for next month",
+            used,
+            limit
+        );
+    }
+    if used * 100 >= limit * warn {
+        eprintln!("⚠️  {}% of this month's AI token budget used ({} of {} tokens)", used * 100 / limit, used, limit);
+    }
+    Ok(())
+}
+/// For `cm user`; None before the first AI request this month
+pub fn summary_line() -> Option<String> {
+    let month = this_month();
+    if month.requests == 0 {
+        return None;
+    }
+    let mut line = format!(
+        "{} tokens in {} request(s) ({} prompt / {} completion)",
+        month.total(),
+        month.requests,
+        month.prompt_tokens,
+        month.completion_tokens
+    );
+    if let Some((limit, _)) = budget() {
+        line.push_str(&format!(", {}% of the {} token budget", month.total() * 100 / limit, limit));
+    }
+    if month.estimated_requests > 0 {
+        line.push_str(&format!(", {} estimated", month.estimated_requests));
+    }
+    Some(line)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    #[test]
+    fn test_usage_report_and_month() {
+        let report = "{\"prompt_tokens\": 100, \"completion_tokens\": 40, \"model\": \"m\"}\n{\"usage\": {\"input_tokens\": 7, \"output_tokens\": 3}}\nnot json\n";
+        let usages = parse_report(report);
+        assert_eq!(usages.len(), 2);
+        assert_eq!((usages[1].prompt_tokens, usages[1].completion_tokens), (7, 3));
+        assert_eq!(usages[0].model.as_deref(), Some("m"));
+        assert_eq!(estimate_tokens("abcdefghi"), 3);
+        let now = Local.with_ymd_and_hms(2026, 3, 15, 12, 0, 0).unwrap();
+        let old = Usage { at: Local.with_ymd_and_hms(2026, 2, 27, 12, 0, 0).unwrap().into(), prompt_tokens: 999, ..Default::default() };
+        let current = Usage { at: now.into(), prompt_tokens: 10, completion_tokens: 5, estimated: true, ..Default::default() };
+        let month = monthly(&[old, current], now);
+        assert_eq!(month, MonthlyUsage { requests: 1, prompt_tokens: 10, completion_tokens: 5, estimated_requests: 1 });
+    }
+}
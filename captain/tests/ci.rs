@@ -0,0 +1,29 @@
+use std::process::Command;
+fn cm(home: &std::path::Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_cm"))
+        .args(args)
+        .current_dir(home)
+        .env("HOME", home)
+        .env_remove("CM_CI")
+        .env_remove("CM_CI_FILTERED")
+        .env("CLICOLOR_FORCE", "1")
+        .output()
+        .unwrap();
+    format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr))
+}
+fn decorated(c: char) -> bool {
+    c == '\x1b' || matches!(c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0xFE0F)
+}
+#[test]
+fn test_ci_output_has_no_emoji_or_color() {
+    let home = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(home.path().join(".shipwreck")).unwrap();
+    cm(home.path(), &["checklist", "add", "fix the flaky test"]);
+    let interactive = cm(home.path(), &["checklist", "list"]);
+    assert!(interactive.chars().any(decorated), "{}", interactive);
+    for args in [&["--ci", "checklist", "list"][..], &["checklist", "show", "--ci"], &["--ci", "checklist", "clear"]] {
+        let output = cm(home.path(), args);
+        assert!(output.contains("fix the flaky test") || args.contains(&"clear"), "{}", output);
+        assert!(!output.chars().any(decorated), "cm {}: {:?}", args.join(" "), output);
+    }
+}